@@ -0,0 +1,79 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { describe, expect, it } from 'vitest';
+import {
+  parseSinceTime,
+  parseStartTime,
+  readApiConnection,
+  stateDirCandidates,
+} from './client.js';
+
+describe('stateDirCandidates', () => {
+  it('follows Electron userData locations', () => {
+    expect(stateDirCandidates('linux', {}, '/home/me')).toEqual([
+      '/home/me/.config/Parallel Code',
+      '/home/me/.config/parallel-code-dev',
+    ]);
+    expect(stateDirCandidates('linux', { XDG_CONFIG_HOME: '/xdg' }, '/home/me')[0]).toBe(
+      '/xdg/Parallel Code',
+    );
+    expect(stateDirCandidates('darwin', {}, '/Users/me')[0]).toBe(
+      '/Users/me/Library/Application Support/Parallel Code',
+    );
+  });
+
+  it('honours an explicit state dir', () => {
+    expect(stateDirCandidates('linux', { PARALLEL_CODE_STATE_DIR: '/tmp/pc' }, '/h')).toEqual([
+      '/tmp/pc',
+    ]);
+  });
+});
+
+describe('readApiConnection', () => {
+  it('needs both a port and a token', () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'pc-cli-'));
+    const writeSettings = (settings: object) =>
+      fs.writeFileSync(path.join(dir, 'settings.json'), JSON.stringify(settings));
+    try {
+      writeSettings({ automation_api_port: 7788 });
+      expect(readApiConnection(dir)).toBeNull();
+      fs.writeFileSync(path.join(dir, 'automation-token'), 'secret\n');
+      expect(readApiConnection(dir)).toEqual({
+        baseUrl: 'http://127.0.0.1:7788/v1',
+        token: 'secret',
+      });
+      writeSettings({ automation_api_port: null });
+      expect(readApiConnection(dir)).toBeNull();
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
+
+describe('parseStartTime', () => {
+  const now = new Date(2026, 0, 10, 22, 30);
+
+  it('takes HH:MM as the next time the clock shows it', () => {
+    expect(parseStartTime('23:15', now)).toBe(new Date(2026, 0, 10, 23, 15).getTime());
+    expect(parseStartTime('02:00', now)).toBe(new Date(2026, 0, 11, 2, 0).getTime());
+  });
+
+  it('accepts dates and rejects junk', () => {
+    expect(parseStartTime('2026-01-12T08:00:00Z', now)).toBe(Date.UTC(2026, 0, 12, 8));
+    expect(() => parseStartTime('25:00', now)).toThrow('Invalid time');
+    expect(() => parseStartTime('tomorrow', now)).toThrow('Invalid time');
+  });
+});
+
+describe('parseSinceTime', () => {
+  it('takes durations as time ago', () => {
+    expect(parseSinceTime('90m', 10_000_000)).toBe(10_000_000 - 90 * 60_000);
+    expect(parseSinceTime('2d', 200_000_000)).toBe(200_000_000 - 2 * 86_400_000);
+  });
+
+  it('accepts dates and rejects junk', () => {
+    expect(parseSinceTime('2026-01-12T08:00:00Z')).toBe(Date.UTC(2026, 0, 12, 8));
+    expect(() => parseSinceTime('2w')).toThrow('Invalid time');
+  });
+});
@@ -0,0 +1,108 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+
+/** Folder names Electron gives the app's userData: packaged, then dev builds. */
+const APP_DIR_NAMES = ['Parallel Code', 'parallel-code-dev'];
+
+/**
+ * Where the app may keep its state, most likely first. `PARALLEL_CODE_STATE_DIR`
+ * overrides the lookup.
+ */
+export function stateDirCandidates(
+  platform: NodeJS.Platform = process.platform,
+  env: NodeJS.ProcessEnv = process.env,
+  home: string = os.homedir(),
+): string[] {
+  if (env.PARALLEL_CODE_STATE_DIR) return [env.PARALLEL_CODE_STATE_DIR];
+  const base =
+    platform === 'darwin'
+      ? path.join(home, 'Library', 'Application Support')
+      : (env.XDG_CONFIG_HOME ?? path.join(home, '.config'));
+  return APP_DIR_NAMES.map((name) => path.join(base, name));
+}
+
+export function findStateDir(): string | null {
+  return stateDirCandidates().find((dir) => fs.existsSync(path.join(dir, 'state.json'))) ?? null;
+}
+
+export interface ApiConnection {
+  baseUrl: string;
+  token: string;
+}
+
+/** The running app's automation API, or null when it isn't enabled. */
+export function readApiConnection(stateDir: string): ApiConnection | null {
+  try {
+    const settings = JSON.parse(fs.readFileSync(path.join(stateDir, 'settings.json'), 'utf8')) as {
+      automation_api_port?: unknown;
+    };
+    const port = settings.automation_api_port;
+    if (typeof port !== 'number') return null;
+    const token = fs.readFileSync(path.join(stateDir, 'automation-token'), 'utf8').trim();
+    return token ? { baseUrl: `http://127.0.0.1:${port}/v1`, token } : null;
+  } catch {
+    return null;
+  }
+}
+
+export class ApiError extends Error {
+  constructor(
+    readonly status: number,
+    message: string,
+  ) {
+    super(message);
+  }
+}
+
+export async function apiRequest<T>(
+  conn: ApiConnection,
+  method: 'GET' | 'POST',
+  endpoint: string,
+  body?: Record<string, unknown>,
+): Promise<T> {
+  const res = await fetch(`${conn.baseUrl}${endpoint}`, {
+    method,
+    headers: {
+      Authorization: `Bearer ${conn.token}`,
+      ...(body ? { 'Content-Type': 'application/json' } : {}),
+    },
+    body: body ? JSON.stringify(body) : undefined,
+  });
+  const payload = (await res.json().catch(() => null)) as unknown;
+  if (!res.ok) {
+    const message = (payload as { error?: string } | null)?.error ?? res.statusText;
+    throw new ApiError(res.status, message);
+  }
+  return payload as T;
+}
+
+/**
+ * A start time from the command line: `HH:MM` for its next occurrence in
+ * local time, or anything `Date.parse` understands. Milliseconds since epoch.
+ */
+export function parseStartTime(value: string, now = new Date()): number {
+  const clock = value.match(/^(\d{1,2}):(\d{2})$/);
+  if (clock) {
+    const [hours, minutes] = [Number(clock[1]), Number(clock[2])];
+    if (hours > 23 || minutes > 59) throw new Error(`Invalid time: ${value}`);
+    const at = new Date(now);
+    at.setHours(hours, minutes, 0, 0);
+    if (at.getTime() <= now.getTime()) at.setDate(at.getDate() + 1);
+    return at.getTime();
+  }
+  const time = Date.parse(value);
+  if (!Number.isFinite(time)) throw new Error(`Invalid time: ${value}`);
+  return time;
+}
+
+const UNIT_MS: Record<string, number> = { m: 60_000, h: 3_600_000, d: 86_400_000 };
+
+/** A past time from the command line: `30m`, `2h` or `7d` ago, or a date. */
+export function parseSinceTime(value: string, now = Date.now()): number {
+  const ago = value.match(/^(\d+)([mhd])$/);
+  if (ago) return now - Number(ago[1]) * UNIT_MS[ago[2]];
+  const time = Date.parse(value);
+  if (!Number.isFinite(time)) throw new Error(`Invalid time: ${value}`);
+  return time;
+}
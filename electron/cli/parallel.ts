@@ -0,0 +1,1050 @@
+#!/usr/bin/env node
+// electron/cli/parallel.ts — `parallel`, a terminal front end for Parallel Code.
+
+import fs from 'fs';
+import path from 'path';
+import { parseArgs } from 'util';
+import type { AutomationTaskStatus } from '../remote/automation-protocol.js';
+import type { AutomationCommand } from '../remote/automation-commands.js';
+import type { ScheduledRun } from '../ipc/scheduler.js';
+import type { SessionSummary } from '../ipc/pty.js';
+import type { TrashEntry } from '../ipc/trash.js';
+import type { BlameLine, ConflictState, TaskCommit, TaskSnapshot } from '../ipc/git.js';
+import type { MergeQueueState } from '../ipc/merge-queue.js';
+import type { HistoryEvent, TranscriptMatch } from '../ipc/storage.js';
+import type { ActivityMetrics } from '../ipc/metrics.js';
+import type { TaskTime } from '../ipc/task-time.js';
+import type { DiskUsage } from '../ipc/disk-usage.js';
+import type { WorkspaceGcReport } from '../ipc/workspace-gc.js';
+import type { ResourceUsage } from '../ipc/resource-usage.js';
+import { backupFileName, exportAppData, importAppData } from '../ipc/app-data.js';
+import {
+  apiRequest,
+  findStateDir,
+  parseSinceTime,
+  parseStartTime,
+  readApiConnection,
+  stateDirCandidates,
+  type ApiConnection,
+} from './client.js';
+
+const USAGE = `Usage: parallel <command> [options]
+
+Commands:
+  projects                            List projects
+  project info <project>              Show a project's default branch, remote and repo name
+  project gc <project> [--yes]        List worktrees and branches no task owns; --yes removes
+        [--force]                     them, --force also dirty worktrees and unmerged branches
+  project tasks <project>             List the project's tasks
+  project prompt <project> <text>     Send a prompt to every agent of the project's tasks
+  project stop <project>              Stop every agent and terminal of the project's tasks
+  task list                           List tasks
+  task query [<text>]                 Find tasks by name or notes text, and filters
+        [--status <s>] [--label <l>] [--agent <agent-id>]
+        [--sort updated_at|created_at|name] [--limit <n>] [--offset <n>]
+  task status <task-id>               Show a task and its agents
+  task update <task-id>               Change a task's name, lifecycle or labels
+        [--name <name>] [--lifecycle <stage>] [--labels <a,b>]
+  task create <name> --project <p>    Create a task and start its agent
+        [--agent <agent-id>] [--prompt <text>] [--scope <dir>]
+  task prompt <task-id> <text>        Send a prompt to the task's agent
+  task spawn <task-id> [--resume]     Restart the task's exited agent
+  task delete <task-id>...            Close tasks and remove their worktrees
+        [--delete-branches] [--force]
+  task archive <task-id>...           Archive tasks, keeping their branches
+  task rebase <task-id>...            Rebase tasks onto their main branch
+        [--keep-conflicts]
+  task conflicts <task-id>            List the files a stopped rebase or merge conflicts on
+  task continue <task-id>             Carry on once the conflicts are resolved
+  task abort-merge <task-id>          Give up on a stopped rebase or merge
+  task summary <task-id>              Show what the task's agents did and where it stands
+  task notes <task-id>                Print the task's markdown notes
+  task note <task-id> [<text>]        Replace the notes, read from stdin without <text>
+        [--append]
+  task time <task-id>                 Show time spent on the task and what it cost
+  task time --project <name-or-id>    Show time spent on each of a project's tasks
+  task start <task-id>                Start the task's timer, counting until paused
+  task pause <task-id>                Pause the task's timer, agents or not
+  task log <task-id> [--limit <n>]    Show the commits on the task branch, newest first
+  task blame <task-id> <file>         Blame a file; * marks lines the task wrote
+  task depend <task-id> [<dep-id>...] Set the tasks that must merge first
+  task widen <task-id> <dir>...       Check out more directories in a sparse task
+  task snapshot <task-id> [<label>]   Record the task's worktree to restore later
+  task snapshots <task-id>            List the task's snapshots, newest first
+  task rollback <task-id> <snap-id>   Restore the task's worktree from a snapshot
+  task comment <task-id> <file:line[-end]> <text>
+                                      Comment on lines of the task's diff
+  task comments <task-id>             List the task's review comments
+  task resolve <task-id> <comment-id> Mark a review comment resolved [--reopen]
+  task address <task-id>              Send unresolved comments to the task's agent
+  task trash                          List deleted tasks that can be restored
+  task restore <entry-id>             Restore a deleted task from the trash
+  task undo                           Restore the most recently deleted task
+  run queue <task-id> <prompt>        Queue a headless agent run in the task
+        [--agent <agent-id>] [--at <HH:MM|date>]
+  run list                            List queued and finished runs
+  run cancel <run-id>                 Cancel a queued or running run
+  merge queue <task-id>... [--squash] Land tasks on main one after another
+  merge list                          Show the merge queue
+  merge remove <entry-id>             Take a task out of the merge queue
+  merge resume <project>              Carry on with a paused merge queue
+  secret set <name> [--inject]        Store a secret read from stdin in the OS keychain
+  secret list                         List stored secrets, without their values
+  secret delete <name>                Remove a secret
+  history [--task <id>] [--type <t>]  Show recorded task events, newest first
+        [--since <30m|2h|7d|date>] [--limit <n>]
+  search <text> [--task <id>]         Search the output of finished agent sessions
+  metrics [--since <7d|date>]         Show tasks per day, agent time and token spend
+        [--project <name-or-id>]
+  disk [--refresh]                    Show how much space task worktrees take
+  sessions [--task <id>]              List running and recently exited sessions
+        [--project <name-or-id>]
+  resources                           Show CPU, memory and scrollback of sessions
+  commands                            List every API command with its parameters
+  invoke <command> [<json-args>]      Run an API command by id [--task <id>]
+  data export [<dir>]                 Back up all app data into one archive
+  data import <file>                  Replace all app data with a backup
+
+Options:
+  --json    Print raw JSON
+
+Everything but listing needs the app running with automation_api_port set.
+Without it, \`projects\` and \`task list\` read the saved state instead.
+\`data\` works on the files directly; quit the app before importing.`;
+
+interface ProjectSummary {
+  id: string;
+  name: string;
+  path: string;
+}
+
+interface TaskSummary {
+  task_id: string;
+  phase: string;
+  files_touched: string[];
+  commands_run: string[];
+  last_error: string | null;
+  last_activity_at: number | null;
+  commit_count: number | null;
+}
+
+interface ProjectInfo {
+  default_branch: string | null;
+  remote_url: string | null;
+  repo_name: string;
+  default_base_branch: string | null;
+}
+
+/** Projects and tasks from the app's saved state, for when it isn't running. */
+function readSavedState(stateDir: string): {
+  projects: ProjectSummary[];
+  tasks: Array<{ id: string; name: string; projectId: string; branchName: string }>;
+} {
+  const raw = JSON.parse(fs.readFileSync(path.join(stateDir, 'state.json'), 'utf8')) as {
+    projects?: ProjectSummary[];
+    taskOrder?: string[];
+    tasks?: Record<string, { id: string; name: string; projectId: string; branchName: string }>;
+  };
+  const tasks = raw.tasks ?? {};
+  return {
+    projects: raw.projects ?? [],
+    tasks: (raw.taskOrder ?? []).flatMap((id) => (tasks[id] ? [tasks[id]] : [])),
+  };
+}
+
+function printTasks(tasks: AutomationTaskStatus[]): void {
+  for (const t of tasks) {
+    const agents = t.agents.map((a) => (a.asking ? 'asking' : a.status)).join(',') || '-';
+    console.log(`${t.id}\t${t.activity}\t${agents}\t${t.branch_name}\t${t.name}`);
+  }
+}
+
+interface BulkTaskResult {
+  taskId: string;
+  ok: boolean;
+  error?: string;
+  branchRisk?: { branch: string; tip: string; unmerged: number; unpushed: number | null };
+}
+
+function printBulkResults(results: BulkTaskResult[]): void {
+  for (const r of results) {
+    console.log(r.ok ? `${r.taskId}\tok` : `${r.taskId}\tfailed\t${r.error ?? ''}`);
+  }
+}
+
+function printGcReport(report: WorkspaceGcReport): void {
+  for (const w of report.worktrees) {
+    const kind = w.registered ? 'worktree' : 'leftover';
+    const changes = w.dirty ? '\tuncommitted changes' : '';
+    console.log(`${kind}\t${formatBytes(w.bytes)}\t${w.path}\t${w.branch ?? ''}${changes}`);
+  }
+  for (const w of report.prunable) console.log(`prunable\t-\t${w.path}\t${w.reason}`);
+  for (const b of report.branches) {
+    const unmerged = b.unmerged > 0 ? `${b.unmerged} unmerged commits` : 'merged';
+    console.log(`branch\t${b.tip.slice(0, 12)}\t${b.branch}\t${unmerged}`);
+  }
+  for (const k of report.kept_worktrees) console.log(`kept\t-\t${k.path}\t${k.reason}`);
+  for (const k of report.kept_branches) console.log(`kept\t-\t${k.branch}\t${k.reason}`);
+  for (const e of report.errors) console.error(`failed: ${e}`);
+  if (report.worktrees.length + report.prunable.length + report.branches.length === 0) {
+    console.log('Nothing to clean up');
+  } else if (report.dry_run) {
+    console.log(`Would free ${formatBytes(report.reclaimed_bytes)}; --yes removes all of this`);
+    if (report.branches.some((b) => b.unmerged > 0)) {
+      console.log('Branches with unmerged commits also need --force');
+    }
+  } else {
+    console.log(`Freed ${formatBytes(report.reclaimed_bytes)}`);
+  }
+}
+
+function print(value: unknown, json: boolean, human: () => void): void {
+  if (json) console.log(JSON.stringify(value, null, 2));
+  else human();
+}
+
+function requireConnection(conn: ApiConnection | null): ApiConnection {
+  if (!conn) {
+    throw new Error(
+      'Parallel Code is not reachable. Start the app and set automation_api_port in its settings.',
+    );
+  }
+  return conn;
+}
+
+async function resolveProjectId(conn: ApiConnection, ref: string): Promise<string> {
+  const projects = await apiRequest<ProjectSummary[]>(conn, 'GET', '/projects');
+  const match =
+    projects.find((p) => p.id === ref) ??
+    projects.find((p) => p.name.toLowerCase() === ref.toLowerCase()) ??
+    projects.find((p) => path.resolve(p.path) === path.resolve(ref));
+  if (!match) throw new Error(`No project matches "${ref}"`);
+  return match.id;
+}
+
+function formatBytes(bytes: number): string {
+  const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+}
+
+function formatDuration(ms: number): string {
+  const minutes = Math.round(ms / 60_000);
+  if (minutes < 60) return `${minutes}m`;
+  const hours = Math.floor(minutes / 60);
+  return hours < 24 ? `${hours}h ${minutes % 60}m` : `${Math.floor(hours / 24)}d ${hours % 24}h`;
+}
+
+function printTaskTime(t: TaskTime, name = t.task_id): void {
+  const cost = t.cost_usd === null ? '' : `\t$${t.cost_usd.toFixed(2)}`;
+  const times = `${formatDuration(t.active_ms)} active\t${formatDuration(t.agent_ms)} agents`;
+  console.log(`${name}\t${times}\t${t.total_tokens.toLocaleString()} tokens${cost}\t${t.timer}`);
+}
+
+function printMetrics(m: ActivityMetrics): void {
+  for (const d of m.days) console.log(`${d.day}\t${d.created} created\t${d.merged} merged`);
+  for (const a of m.agents) {
+    const cost = a.cost_usd === null ? '' : `\t$${a.cost_usd.toFixed(2)}`;
+    const tokens = `${a.total_tokens.toLocaleString()} tokens`;
+    const agent = `${a.agent_def_id ?? 'unknown'}\t${a.sessions} sessions`;
+    console.log(`${agent}\t${formatDuration(a.active_ms)}\t${tokens}${cost}`);
+  }
+  const lifetime =
+    m.avg_task_lifetime_ms === null ? '' : `, ${formatDuration(m.avg_task_lifetime_ms)} on average`;
+  console.log(`${m.tasks_created} tasks created, ${m.tasks_merged} merged${lifetime}`);
+  const cost = m.cost_usd === null ? '' : ` ($${m.cost_usd.toFixed(2)})`;
+  console.log(`${m.total_tokens.toLocaleString()} tokens${cost}`);
+}
+
+interface ReviewComment {
+  id: string;
+  path: string;
+  startLine: number;
+  endLine: number;
+  body: string;
+  resolvedAt?: number;
+}
+
+function printReviewComments(comments: ReviewComment[]): void {
+  for (const c of comments) {
+    const lines = c.startLine === c.endLine ? `${c.startLine}` : `${c.startLine}-${c.endLine}`;
+    const state = c.resolvedAt === undefined ? 'open' : 'resolved';
+    console.log(`${c.id}\t${state}\t${c.path}:${lines}\t${c.body.split('\n')[0]}`);
+  }
+}
+
+/** `src/app.ts:12` or `src/app.ts:12-20` as a comment anchor. */
+function parseCommentAnchor(anchor: string | undefined) {
+  const match = anchor?.match(/^(.+):(\d+)(?:-(\d+))?$/);
+  if (!match) throw new Error('Expected <file:line[-end]>');
+  const startLine = Number(match[2]);
+  return { path: match[1], startLine, endLine: match[3] ? Number(match[3]) : startLine };
+}
+
+interface TaskDiskUsage {
+  taskId: string;
+  name: string;
+  bytes: number;
+  stale: boolean;
+}
+
+function printRuns(runs: ScheduledRun[]): void {
+  for (const r of runs) {
+    const at = r.start_at ? new Date(r.start_at).toLocaleString() : '-';
+    console.log(`${r.id}\t${r.status}\t${r.task_id}\t${r.agent_id}\t${at}\t${r.prompt}`);
+  }
+}
+
+function printMergeQueue(state: MergeQueueState): void {
+  for (const e of state.entries) {
+    const report = e.report ? `\t${e.report.stage}: ${e.report.message}` : '';
+    console.log(`${e.id}\t${e.status}\t${e.task_id}\t${e.branch_name}${report}`);
+  }
+  for (const projectRoot of state.paused) console.log(`paused\t${projectRoot}`);
+}
+
+function printSnapshots(snapshots: TaskSnapshot[]): void {
+  for (const s of snapshots) {
+    console.log(`${s.id}\t${new Date(s.created_at).toLocaleString()}\t${s.label}`);
+  }
+}
+
+async function runCommand(
+  conn: ApiConnection,
+  sub: string | undefined,
+  rest: string[],
+  values: { agent?: string; at?: string },
+  json: boolean,
+): Promise<void> {
+  switch (sub) {
+    case 'queue': {
+      const [taskId, ...words] = rest;
+      if (!taskId) throw new Error('Missing <task-id>');
+      const prompt = words.join(' ').trim();
+      if (!prompt) throw new Error('Missing <prompt>');
+      const run = await apiRequest<ScheduledRun>(
+        conn,
+        'POST',
+        `/tasks/${encodeURIComponent(taskId)}/runs`,
+        {
+          prompt,
+          agentId: values.agent,
+          startAt: values.at ? parseStartTime(values.at) : undefined,
+        },
+      );
+      return print(run, json, () => printRuns([run]));
+    }
+    case 'list': {
+      const runs = await apiRequest<ScheduledRun[]>(conn, 'GET', '/runs');
+      return print(runs, json, () => printRuns(runs));
+    }
+    case 'cancel': {
+      if (!rest[0]) throw new Error('Missing <run-id>');
+      const runs = await apiRequest<ScheduledRun[]>(conn, 'POST', '/runs/cancel', {
+        runId: rest[0],
+      });
+      return print(runs, json, () => printRuns(runs.filter((r) => r.id === rest[0])));
+    }
+    default:
+      throw new Error(`Unknown run command: ${sub ?? ''}\n\n${USAGE}`);
+  }
+}
+
+async function mergeCommand(
+  conn: ApiConnection,
+  sub: string | undefined,
+  rest: string[],
+  values: { squash?: boolean },
+  json: boolean,
+): Promise<void> {
+  switch (sub) {
+    case 'queue': {
+      if (rest.length === 0) throw new Error('Missing <task-id>');
+      const results = await apiRequest<BulkTaskResult[]>(conn, 'POST', '/merge-queue', {
+        taskIds: rest,
+        squash: values.squash,
+      });
+      return print(results, json, () => printBulkResults(results));
+    }
+    case 'list': {
+      const state = await apiRequest<MergeQueueState>(conn, 'GET', '/merge-queue');
+      return print(state, json, () => printMergeQueue(state));
+    }
+    case 'remove': {
+      if (!rest[0]) throw new Error('Missing <entry-id>');
+      const state = await apiRequest<MergeQueueState>(conn, 'POST', '/merge-queue/remove', {
+        entryId: rest[0],
+      });
+      return print(state, json, () => printMergeQueue(state));
+    }
+    case 'resume': {
+      if (!rest[0]) throw new Error('Missing <project>');
+      const projectId = await resolveProjectId(conn, rest[0]);
+      const state = await apiRequest<MergeQueueState>(conn, 'POST', '/merge-queue/resume', {
+        projectId,
+      });
+      return print(state, json, () => printMergeQueue(state));
+    }
+    default:
+      throw new Error(`Unknown merge command: ${sub ?? ''}\n\n${USAGE}`);
+  }
+}
+
+interface SecretInfo {
+  name: string;
+  inject: boolean;
+  updated_at: number;
+}
+
+function printSecrets(secrets: SecretInfo[]): void {
+  for (const s of secrets) {
+    const at = new Date(s.updated_at).toLocaleString();
+    console.log(`${s.name}\t${s.inject ? 'injected' : '-'}\t${at}`);
+  }
+}
+
+async function secretCommand(
+  conn: ApiConnection,
+  sub: string | undefined,
+  rest: string[],
+  values: { inject?: boolean },
+  json: boolean,
+): Promise<void> {
+  switch (sub) {
+    case 'set': {
+      if (!rest[0]) throw new Error('Missing <name>');
+      // From stdin, so the value stays out of shell history and process lists
+      const value = fs.readFileSync(0, 'utf8').replace(/\r?\n$/, '');
+      const secret = await apiRequest<SecretInfo>(conn, 'POST', '/secrets', {
+        name: rest[0],
+        value,
+        inject: values.inject,
+      });
+      return print(secret, json, () => printSecrets([secret]));
+    }
+    case 'list': {
+      const secrets = await apiRequest<SecretInfo[]>(conn, 'GET', '/secrets');
+      return print(secrets, json, () => printSecrets(secrets));
+    }
+    case 'delete': {
+      if (!rest[0]) throw new Error('Missing <name>');
+      const secrets = await apiRequest<SecretInfo[]>(conn, 'POST', '/secrets/delete', {
+        name: rest[0],
+      });
+      return print(secrets, json, () => printSecrets(secrets));
+    }
+    default:
+      throw new Error(`Unknown secret command: ${sub ?? ''}\n\n${USAGE}`);
+  }
+}
+
+async function main(argv: string[]): Promise<void> {
+  const { values, positionals } = parseArgs({
+    args: argv,
+    allowPositionals: true,
+    options: {
+      json: { type: 'boolean', default: false },
+      project: { type: 'string', short: 'p' },
+      agent: { type: 'string', short: 'a' },
+      prompt: { type: 'string' },
+      scope: { type: 'string' },
+      resume: { type: 'boolean', default: false },
+      'delete-branches': { type: 'boolean', default: false },
+      force: { type: 'boolean', default: false },
+      yes: { type: 'boolean', default: false },
+      'keep-conflicts': { type: 'boolean', default: false },
+      reopen: { type: 'boolean', default: false },
+      append: { type: 'boolean', default: false },
+      squash: { type: 'boolean', default: false },
+      inject: { type: 'boolean' },
+      at: { type: 'string' },
+      task: { type: 'string' },
+      type: { type: 'string', multiple: true },
+      since: { type: 'string' },
+      limit: { type: 'string' },
+      offset: { type: 'string' },
+      status: { type: 'string' },
+      label: { type: 'string' },
+      sort: { type: 'string' },
+      name: { type: 'string' },
+      lifecycle: { type: 'string' },
+      labels: { type: 'string' },
+      refresh: { type: 'boolean', default: false },
+      help: { type: 'boolean', short: 'h', default: false },
+    },
+  });
+  const [command, sub, ...rest] = positionals;
+  if (values.help || !command) {
+    console.log(USAGE);
+    return;
+  }
+
+  const stateDir = findStateDir();
+  const conn = stateDir ? readApiConnection(stateDir) : null;
+  const json = values.json;
+  const encodeTask = (id: string | undefined) => {
+    if (!id) throw new Error('Missing <task-id>');
+    return `/tasks/${encodeURIComponent(id)}`;
+  };
+
+  if (command === 'projects') {
+    const projects = conn
+      ? await apiRequest<ProjectSummary[]>(conn, 'GET', '/projects').catch(() => null)
+      : null;
+    const list = projects ?? (stateDir ? readSavedState(stateDir).projects : []);
+    print(list, json, () => list.forEach((p) => console.log(`${p.id}\t${p.name}\t${p.path}`)));
+    return;
+  }
+  if (command === 'project') {
+    if (!['info', 'gc', 'tasks', 'prompt', 'stop'].includes(sub ?? '')) {
+      throw new Error(`Unknown project command: ${sub ?? ''}\n\n${USAGE}`);
+    }
+    if (!rest[0]) throw new Error('Missing <project>');
+    const api = requireConnection(conn);
+    if (sub === 'tasks') {
+      const tasks = await apiRequest<AutomationTaskStatus[]>(api, 'POST', '/projects/tasks', {
+        projectId: await resolveProjectId(api, rest[0]),
+      });
+      return print(tasks, json, () => printTasks(tasks));
+    }
+    if (sub === 'prompt' || sub === 'stop') {
+      const text = rest.slice(1).join(' ');
+      if (sub === 'prompt' && !text) throw new Error('Missing prompt <text>');
+      const result = await apiRequest<{ agent_ids: string[] }>(api, 'POST', `/projects/${sub}`, {
+        projectId: await resolveProjectId(api, rest[0]),
+        ...(sub === 'prompt' ? { text } : {}),
+      });
+      return print(result, json, () => result.agent_ids.forEach((id) => console.log(id)));
+    }
+    if (sub === 'gc') {
+      const projectId = await resolveProjectId(api, rest[0]);
+      let report = await apiRequest<WorkspaceGcReport>(api, 'POST', '/projects/gc', {
+        projectId,
+        dryRun: true,
+      });
+      if (values.yes) {
+        // What was just listed, branches at the tips listed: anything newer is left alone
+        report = await apiRequest<WorkspaceGcReport>(api, 'POST', '/projects/gc', {
+          projectId,
+          dryRun: false,
+          worktrees: report.worktrees.map((w) => w.path),
+          branches: Object.fromEntries(report.branches.map((b) => [b.branch, b.tip])),
+          force: values.force,
+        });
+      }
+      if (report.errors.length > 0) process.exitCode = 1;
+      return print(report, json, () => printGcReport(report));
+    }
+    const info = await apiRequest<ProjectInfo>(api, 'POST', '/projects/info', {
+      projectId: await resolveProjectId(api, rest[0]),
+    });
+    print(info, json, () => {
+      console.log(`Repository:      ${info.repo_name}`);
+      console.log(`Remote:          ${info.remote_url ?? '(none)'}`);
+      console.log(`Default branch:  ${info.default_branch ?? '(unknown)'}`);
+      console.log(`New tasks from:  ${info.default_base_branch ?? '(app setting)'}`);
+    });
+    return;
+  }
+  if (command === 'run') return runCommand(requireConnection(conn), sub, rest, values, json);
+  if (command === 'merge') return mergeCommand(requireConnection(conn), sub, rest, values, json);
+  if (command === 'secret') return secretCommand(requireConnection(conn), sub, rest, values, json);
+  if (command === 'disk') {
+    const usage = await apiRequest<DiskUsage & { tasks: TaskDiskUsage[] }>(
+      requireConnection(conn),
+      'POST',
+      '/disk-usage',
+      { refresh: values.refresh },
+    );
+    return print(usage, json, () => {
+      for (const t of usage.tasks) {
+        console.log(`${formatBytes(t.bytes)}\t${t.stale ? 'stale' : '-'}\t${t.taskId}\t${t.name}`);
+      }
+      console.log(`${formatBytes(usage.total_bytes)}\ttotal`);
+    });
+  }
+  if (command === 'sessions') {
+    const api = requireConnection(conn);
+    const sessions = await apiRequest<SessionSummary[]>(api, 'POST', '/sessions', {
+      taskId: values.task,
+      projectId: values.project ? await resolveProjectId(api, values.project) : undefined,
+    });
+    return print(sessions, json, () =>
+      sessions.forEach((s) => {
+        const kind = s.is_shell ? 'shell' : (s.agent_def_id ?? 'agent');
+        const exit = s.status === 'running' ? `pid ${s.pid}` : `exit ${s.exit_code ?? s.signal}`;
+        const last = new Date(s.last_output_at).toLocaleString();
+        console.log(`${s.status}\t${exit}\t${kind}\t${last}\t${s.task_id}\t${s.agent_id}`);
+      }),
+    );
+  }
+  if (command === 'commands') {
+    const commands = await apiRequest<AutomationCommand[]>(
+      requireConnection(conn),
+      'GET',
+      '/commands',
+    );
+    return print(commands, json, () =>
+      commands.forEach((c) => {
+        const params = Object.entries(c.params).map(([k, p]) => (p.required ? k : `${k}?`));
+        const target = c.requires_task ? ' <task>' : '';
+        console.log(`${c.id}${target}\t${c.title}\t${params.join(' ')}`);
+      }),
+    );
+  }
+  if (command === 'invoke') {
+    if (!sub) throw new Error('Missing <command>');
+    let args: unknown = {};
+    try {
+      if (rest[0]) args = JSON.parse(rest[0]);
+    } catch {
+      throw new Error('<json-args> must be a JSON object');
+    }
+    const result = await apiRequest<unknown>(requireConnection(conn), 'POST', '/commands/invoke', {
+      command: sub,
+      taskId: values.task,
+      args,
+    });
+    // Results have no fixed shape to print for humans
+    console.log(JSON.stringify(result, null, 2));
+    return;
+  }
+  if (command === 'resources') {
+    const usage = await apiRequest<ResourceUsage>(requireConnection(conn), 'GET', '/resources');
+    return print(usage, json, () => {
+      for (const s of usage.sessions) {
+        const cpu = s.cpu_percent === null ? '-' : `${s.cpu_percent}%`;
+        const rss = s.rss_bytes === null ? '-' : formatBytes(s.rss_bytes);
+        const kind = s.is_shell ? 'shell' : 'agent';
+        const scrollback = formatBytes(s.scrollback.capacity_bytes);
+        console.log(`${cpu}\t${rss}\t${scrollback}\t${kind}\t${s.task_id}\t${s.agent_id}`);
+      }
+      const max = usage.max_agent_sessions > 0 ? ` of ${usage.max_agent_sessions}` : '';
+      console.log(`${usage.agent_sessions}${max} agents running`);
+      const budget = formatBytes(usage.scrollback_budget_bytes);
+      console.log(`${formatBytes(usage.scrollback_bytes)} of ${budget} scrollback`);
+    });
+  }
+  if (command === 'data') {
+    if (sub === 'export') {
+      if (!stateDir) throw new Error('No Parallel Code data found');
+      const outPath = path.resolve(rest[0] ?? '.', backupFileName());
+      const file = await exportAppData(stateDir, outPath);
+      return print({ path: file }, json, () => console.log(file));
+    }
+    if (sub === 'import') {
+      if (!rest[0]) throw new Error('Missing <file>');
+      // The app would write its in-memory state back over the import
+      const running = conn
+        ? await apiRequest(conn, 'GET', '/projects').then(
+            () => true,
+            () => false,
+          )
+        : false;
+      if (running) throw new Error('Quit Parallel Code before importing, or use Settings → Data');
+      const target = stateDir ?? stateDirCandidates()[0];
+      const result = await importAppData(target, path.resolve(rest[0]));
+      return print(result, json, () =>
+        console.log(
+          result.backup_path
+            ? `Imported. Previous data saved to ${result.backup_path}`
+            : 'Imported.',
+        ),
+      );
+    }
+    throw new Error(`Unknown data command: ${sub ?? ''}\n\n${USAGE}`);
+  }
+  if (command === 'history') {
+    const limit = values.limit ? Number(values.limit) : undefined;
+    if (limit !== undefined && !Number.isInteger(limit)) {
+      throw new Error('--limit must be a number');
+    }
+    const events = await apiRequest<HistoryEvent[]>(requireConnection(conn), 'POST', '/history', {
+      taskId: values.task,
+      types: values.type,
+      since: values.since ? parseSinceTime(values.since) : undefined,
+      limit,
+    });
+    return print(events, json, () =>
+      events.forEach((e) => {
+        const at = new Date(e.ts).toLocaleString();
+        const data = e.data ? JSON.stringify(e.data) : '';
+        console.log(`${at}\t${e.type}\t${e.task_name ?? e.task_id}\t${data}`);
+      }),
+    );
+  }
+  if (command === 'metrics') {
+    const api = requireConnection(conn);
+    const metrics = await apiRequest<ActivityMetrics>(api, 'POST', '/metrics', {
+      since: values.since ? parseSinceTime(values.since) : undefined,
+      projectId: values.project ? await resolveProjectId(api, values.project) : undefined,
+    });
+    return print(metrics, json, () => printMetrics(metrics));
+  }
+  if (command === 'search') {
+    const query = [sub, ...rest].filter(Boolean).join(' ').trim();
+    if (!query) throw new Error('Missing search <text>');
+    const matches = await apiRequest<TranscriptMatch[]>(
+      requireConnection(conn),
+      'POST',
+      '/search',
+      { query, taskId: values.task },
+    );
+    return print(matches, json, () =>
+      matches.forEach((m) => console.log(`${m.task_name ?? m.task_id}:${m.line_no}\t${m.text}`)),
+    );
+  }
+  if (command !== 'task') throw new Error(`Unknown command: ${command}\n\n${USAGE}`);
+
+  switch (sub) {
+    case 'list': {
+      const live = conn
+        ? await apiRequest<AutomationTaskStatus[]>(conn, 'GET', '/tasks').catch(() => null)
+        : null;
+      if (live) return print(live, json, () => printTasks(live));
+      if (!stateDir) throw new Error('No Parallel Code state found');
+      const saved = readSavedState(stateDir).tasks;
+      return print(saved, json, () =>
+        saved.forEach((t) => console.log(`${t.id}\t${t.branchName}\t${t.name}`)),
+      );
+    }
+    case 'update': {
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'POST',
+        encodeTask(rest[0]),
+        {
+          name: values.name,
+          lifecycle: values.lifecycle,
+          labels: values.labels?.split(',').filter(Boolean),
+        },
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'query': {
+      const result = await apiRequest<{ total: number; tasks: AutomationTaskStatus[] }>(
+        requireConnection(conn),
+        'POST',
+        '/tasks/query',
+        {
+          text: rest.join(' ') || undefined,
+          status: values.status,
+          label: values.label,
+          agent: values.agent,
+          sort: values.sort,
+          limit: values.limit ? Number(values.limit) : undefined,
+          offset: values.offset ? Number(values.offset) : undefined,
+        },
+      );
+      return print(result, json, () => {
+        printTasks(result.tasks);
+        if (result.tasks.length < result.total) {
+          console.log(`(${result.tasks.length} of ${result.total} matching tasks)`);
+        }
+      });
+    }
+    case 'status': {
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'GET',
+        encodeTask(rest[0]),
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'create': {
+      const api = requireConnection(conn);
+      const name = rest.join(' ').trim();
+      if (!name) throw new Error('Missing task <name>');
+      if (!values.project) throw new Error('--project is required');
+      const task = await apiRequest<AutomationTaskStatus>(api, 'POST', '/tasks', {
+        projectId: await resolveProjectId(api, values.project),
+        name,
+        agentId: values.agent,
+        prompt: values.prompt,
+        scope: values.scope,
+      });
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'prompt': {
+      const [taskId, ...words] = rest;
+      const text = words.join(' ');
+      if (!text) throw new Error('Missing prompt <text>');
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/prompt`,
+        { text },
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'spawn': {
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(rest[0])}/agent`,
+        { resume: values.resume },
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'depend': {
+      const [taskId, ...dependsOn] = rest;
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/dependencies`,
+        { dependsOn },
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'widen': {
+      const [taskId, ...paths] = rest;
+      if (paths.length === 0) throw new Error('Missing <dir>');
+      const result = await apiRequest<{ paths: string[] }>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/sparse-checkout`,
+        { paths },
+      );
+      return print(result, json, () => result.paths.forEach((p) => console.log(p)));
+    }
+    case 'conflicts':
+    case 'continue':
+    case 'abort-merge': {
+      const endpoint = {
+        conflicts: 'conflicts',
+        continue: 'conflicts/continue',
+        'abort-merge': 'conflicts/abort',
+      }[sub];
+      const state = await apiRequest<ConflictState>(
+        requireConnection(conn),
+        sub === 'conflicts' ? 'GET' : 'POST',
+        `${encodeTask(rest[0])}/${endpoint}`,
+      );
+      return print(state, json, () => {
+        if (!state.operation) console.log('No merge or rebase in progress');
+        for (const f of state.files) {
+          const detail = f.binary ? 'binary' : `${f.regions.length} conflicts`;
+          console.log(`${state.operation}\t${f.path}\t${detail}`);
+        }
+      });
+    }
+    case 'summary': {
+      const s = await apiRequest<TaskSummary>(
+        requireConnection(conn),
+        'GET',
+        `${encodeTask(rest[0])}/summary`,
+      );
+      return print(s, json, () => {
+        const at = s.last_activity_at ? new Date(s.last_activity_at).toLocaleString() : '-';
+        console.log(`Phase:          ${s.phase}`);
+        console.log(`Last activity:  ${at}`);
+        console.log(`Commits:        ${s.commit_count ?? '-'}`);
+        console.log(`Files touched:  ${s.files_touched.length}`);
+        s.files_touched.forEach((f) => console.log(`  ${f}`));
+        console.log(`Commands run:   ${s.commands_run.length}`);
+        s.commands_run.forEach((c) => console.log(`  $ ${c}`));
+        if (s.last_error) console.log(`Last error:     ${s.last_error}`);
+      });
+    }
+    case 'time': {
+      if (values.project) {
+        const api = requireConnection(conn);
+        const projectId = await resolveProjectId(api, values.project);
+        const time = await apiRequest<{ tasks: TaskTime[]; active_ms: number }>(
+          api,
+          'POST',
+          '/projects/time',
+          { projectId },
+        );
+        return print(time, json, () => {
+          time.tasks.forEach((t) => printTaskTime(t));
+          console.log(`${formatDuration(time.active_ms)} active in total`);
+        });
+      }
+      const time = await apiRequest<TaskTime>(
+        requireConnection(conn),
+        'GET',
+        `${encodeTask(rest[0])}/time`,
+      );
+      return print(time, json, () => printTaskTime(time));
+    }
+    case 'start':
+    case 'pause': {
+      const time = await apiRequest<TaskTime>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(rest[0])}/time/${sub}`,
+      );
+      return print(time, json, () => printTaskTime(time));
+    }
+    case 'notes':
+    case 'note': {
+      const [taskId, ...text] = rest;
+      const body =
+        sub === 'note'
+          ? {
+              notes: text.length > 0 ? text.join(' ') : fs.readFileSync(0, 'utf8'),
+              append: values.append,
+            }
+          : undefined;
+      const result = await apiRequest<{ notes: string }>(
+        requireConnection(conn),
+        sub === 'note' ? 'POST' : 'GET',
+        `${encodeTask(taskId)}/notes`,
+        body,
+      );
+      return print(result, json, () => console.log(result.notes));
+    }
+    case 'log': {
+      const commits = await apiRequest<TaskCommit[]>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(rest[0])}/log`,
+        { limit: values.limit ? Number(values.limit) : undefined },
+      );
+      return print(commits, json, () =>
+        commits.forEach((c) => {
+          const at = new Date(c.date).toLocaleString();
+          const stats = `+${c.insertions} -${c.deletions}`;
+          console.log(`${c.sha.slice(0, 10)}\t${at}\t${c.author_name}\t${stats}\t${c.subject}`);
+        }),
+      );
+    }
+    case 'blame': {
+      const [taskId, file] = rest;
+      if (!file) throw new Error('Missing <file>');
+      const lines = await apiRequest<BlameLine[]>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/blame`,
+        { path: file },
+      );
+      return print(lines, json, () =>
+        lines.forEach((l) => {
+          const mark = l.on_branch ? '*' : ' ';
+          console.log(`${mark} ${l.sha.slice(0, 8)} ${l.author}\t${l.line}\t${l.content}`);
+        }),
+      );
+    }
+    case 'snapshot': {
+      const [taskId, ...label] = rest;
+      const snapshot = await apiRequest<TaskSnapshot>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/snapshots`,
+        { label: label.join(' ') },
+      );
+      return print(snapshot, json, () => printSnapshots([snapshot]));
+    }
+    case 'snapshots': {
+      const snapshots = await apiRequest<TaskSnapshot[]>(
+        requireConnection(conn),
+        'GET',
+        `${encodeTask(rest[0])}/snapshots`,
+      );
+      return print(snapshots, json, () => printSnapshots(snapshots));
+    }
+    case 'rollback': {
+      const [taskId, snapshotId] = rest;
+      if (!snapshotId) throw new Error('Missing <snap-id>');
+      const result = await apiRequest<{ restored: TaskSnapshot; backup: TaskSnapshot }>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/snapshots/restore`,
+        { snapshotId },
+      );
+      return print(result, json, () => {
+        console.log(`Restored ${result.restored.id} (${result.restored.label})`);
+        console.log(`Previous state saved as ${result.backup.id}`);
+      });
+    }
+    case 'comment': {
+      const [taskId, anchor, ...text] = rest;
+      const comment = await apiRequest<ReviewComment>(
+        requireConnection(conn),
+        'POST',
+        `${encodeTask(taskId)}/comments`,
+        { ...parseCommentAnchor(anchor), body: text.join(' ') },
+      );
+      return print(comment, json, () => printReviewComments([comment]));
+    }
+    case 'comments':
+    case 'resolve':
+    case 'address': {
+      const [taskId, commentId] = rest;
+      if (sub === 'resolve' && !commentId) throw new Error('Missing <comment-id>');
+      const endpoint = {
+        comments: 'comments',
+        resolve: 'comments/resolve',
+        address: 'comments/send',
+      }[sub];
+      const comments = await apiRequest<ReviewComment[]>(
+        requireConnection(conn),
+        sub === 'comments' ? 'GET' : 'POST',
+        `${encodeTask(taskId)}/${endpoint}`,
+        sub === 'resolve' ? { commentId, resolved: !values.reopen } : undefined,
+      );
+      return print(comments, json, () => printReviewComments(comments));
+    }
+    case 'trash': {
+      const entries = await apiRequest<TrashEntry[]>(requireConnection(conn), 'GET', '/trash');
+      return print(entries, json, () =>
+        entries.forEach((e) => {
+          const name = typeof e.metadata.name === 'string' ? e.metadata.name : '';
+          const at = new Date(e.deleted_at).toLocaleString();
+          console.log(`${e.id}\t${at}\t${e.branch_name}\t${name}`);
+        }),
+      );
+    }
+    case 'restore': {
+      if (!rest[0]) throw new Error('Missing <entry-id>');
+      const task = await apiRequest<AutomationTaskStatus>(
+        requireConnection(conn),
+        'POST',
+        '/trash/restore',
+        { entryId: rest[0] },
+      );
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'undo': {
+      const task = await apiRequest<AutomationTaskStatus>(requireConnection(conn), 'POST', '/undo');
+      return print(task, json, () => printTasks([task]));
+    }
+    case 'delete':
+    case 'archive':
+    case 'rebase': {
+      if (rest.length === 0) throw new Error('Missing <task-id>');
+      const body: Record<string, unknown> = { taskIds: rest };
+      if (sub === 'delete') {
+        body.deleteBranches = values['delete-branches'];
+        body.force = values.force;
+      }
+      if (sub === 'rebase') body.keepConflicts = values['keep-conflicts'];
+      const results = await apiRequest<BulkTaskResult[]>(
+        requireConnection(conn),
+        'POST',
+        `/tasks/${sub}`,
+        body,
+      );
+      if (results.some((r) => !r.ok)) process.exitCode = 1;
+      return print(results, json, () => {
+        printBulkResults(results);
+        if (results.some((r) => r.branchRisk)) {
+          console.error('Branches with unmerged commits were kept; --force deletes them anyway');
+        }
+      });
+    }
+    default:
+      throw new Error(`Unknown task command: ${sub ?? ''}\n\n${USAGE}`);
+  }
+}
+
+main(process.argv.slice(2)).catch((err: unknown) => {
+  console.error(`parallel: ${err instanceof Error ? err.message : String(err)}`);
+  process.exit(1);
+});
@@ -0,0 +1,43 @@
+import { emptyMetrics, parseTokenCount } from '../usage.js';
+import type { AgentAdapter } from './types.js';
+
+// Printed after every reply; tokens are per reply, cost per reply and for the session:
+//   Tokens: 12k sent, 2.3k cache hit, 345 received. Cost: $0.01 message, $0.05 session.
+const NUM = '([\\d.,]+[kKmM]?)';
+const USAGE_RE = new RegExp(
+  `Tokens: ${NUM} sent(?:, ${NUM} cache write)?(?:, ${NUM} cache hit)?, ${NUM} received\\.` +
+    '(?:\\s*Cost: \\$[\\d.,]+ message, \\$([\\d.,]+) session)?',
+);
+
+export const aider: AgentAdapter = {
+  id: 'aider',
+  name: 'Aider',
+  description: 'AI pair programming in your terminal (aider.chat)',
+  command: 'aider',
+  args: [],
+  resume_args: ['--restore-chat-history'],
+  skip_permissions_args: ['--yes-always'],
+  headless_args: ['--message'],
+
+  parseUsageLine(line, current) {
+    const m = line.match(USAGE_RE);
+    if (!m) return null;
+    const next = { ...(current ?? emptyMetrics()) };
+    next.input_tokens += parseTokenCount(m[1]);
+    next.cache_write_tokens += m[2] ? parseTokenCount(m[2]) : 0;
+    next.cache_read_tokens += m[3] ? parseTokenCount(m[3]) : 0;
+    next.output_tokens += parseTokenCount(m[4]);
+    next.total_tokens =
+      next.input_tokens + next.output_tokens + next.cache_read_tokens + next.cache_write_tokens;
+    if (m[5]) next.cost_usd = parseFloat(m[5].replace(/,/g, ''));
+    return next;
+  },
+
+  // "Run shell command? (Y)es/(N)o/(D)on't ask again [Yes]:" — question and answers on one line
+  approval: {
+    question: /\?\s*\(Y\)es\/\(N\)o/,
+    options: /\(Y\)es\/\(N\)o/,
+    approve: 'y\r',
+    deny: 'n\r',
+  },
+};
@@ -0,0 +1,99 @@
+import os from 'os';
+import path from 'path';
+import { emptyMetrics, parseTokenCount } from '../usage.js';
+import { recentFiles } from './session-files.js';
+import type { AgentAdapter } from './types.js';
+
+// `/cost` summary:
+//   Total cost:            $0.0520
+//   Usage by model:
+//       claude-sonnet:  22 input, 1.1k output, 34.5k cache read, 8.9k cache write
+const COST_RE = /Total cost:\s*\$([\d.,]+)/;
+const NUM = '([\\d.,]+[kKmM]?)';
+const USAGE_RE = new RegExp(
+  `${NUM} input, ${NUM} output(?:, ${NUM} cache read)?(?:, ${NUM} cache write)?`,
+);
+
+// Tool calls and their results:
+//   ⏺ Update(src/app.ts)
+//   ⏺ Bash(npm test)
+//     ⎿  Error: Exit code 1
+// and the line left when a reply finishes: "✻ Worked for 1m 12s"
+const TOOL_RE = /^\s*[⏺●]\s*(\w+)\((.+)\)\s*$/;
+const EDIT_TOOLS = new Set(['Edit', 'MultiEdit', 'Update', 'Write', 'NotebookEdit']);
+const TOOL_ERROR_RE = /^\s*⎿\s+Error:\s*(.+)$/;
+const API_ERROR_RE = /^\s*[⏺●]?\s*(API Error:.+)$/;
+const TURN_DONE_RE = /^\s*[✻✽✶✳✢·*]\s+\w+ for (?:\d+h\s*)?(?:\d+m\s*)?\d+s\b/;
+
+/** Where Claude Code keeps a directory's conversations: `projects/<cwd with - for / and .>/`. */
+function projectDir(cwd: string): string {
+  const configDir = process.env.CLAUDE_CONFIG_DIR || path.join(os.homedir(), '.claude');
+  return path.join(configDir, 'projects', path.resolve(cwd).replace(/[^a-zA-Z0-9-]/g, '-'));
+}
+
+export const claudeCode: AgentAdapter = {
+  id: 'claude-code',
+  name: 'Claude Code',
+  description: "Anthropic's Claude Code CLI agent",
+  command: 'claude',
+  args: [],
+  resume_args: ['--continue'],
+  skip_permissions_args: ['--dangerously-skip-permissions'],
+  headless_args: ['-p'],
+  profiles: [
+    {
+      id: 'accept-edits',
+      name: 'Auto-approve edits',
+      args: ['--permission-mode', 'acceptEdits'],
+      env: {},
+    },
+    { id: 'plan', name: 'Plan mode', args: ['--permission-mode', 'plan'], env: {} },
+    { id: 'opus', name: 'Opus', args: ['--model', 'opus'], env: {} },
+  ],
+
+  parseUsageLine(line, current) {
+    const cost = line.match(COST_RE);
+    if (cost) {
+      // A new summary block starts — per-model usage lines follow it
+      return { ...emptyMetrics(), cost_usd: parseFloat(cost[1].replace(/,/g, '')) };
+    }
+    const usage = line.match(USAGE_RE);
+    if (!usage || !current) return null;
+    const next = { ...current };
+    next.input_tokens += parseTokenCount(usage[1]);
+    next.output_tokens += parseTokenCount(usage[2]);
+    next.cache_read_tokens += usage[3] ? parseTokenCount(usage[3]) : 0;
+    next.cache_write_tokens += usage[4] ? parseTokenCount(usage[4]) : 0;
+    next.total_tokens =
+      next.input_tokens + next.output_tokens + next.cache_read_tokens + next.cache_write_tokens;
+    return next;
+  },
+
+  parseEventLine(line) {
+    const tool = line.match(TOOL_RE);
+    if (tool) {
+      if (EDIT_TOOLS.has(tool[1])) return { kind: 'editing', detail: tool[2] };
+      if (tool[1] === 'Bash') return { kind: 'command', detail: tool[2] };
+      return null;
+    }
+    const error = line.match(TOOL_ERROR_RE) ?? line.match(API_ERROR_RE);
+    if (error) return { kind: 'error', detail: error[1].trim() };
+    return TURN_DONE_RE.test(line) ? { kind: 'turn_complete' } : null;
+  },
+
+  // "Do you want to proceed?" / "Do you want to make this edit to foo.ts?"
+  // followed by "❯ 1. Yes" … "3. No, and tell Claude what to do differently (esc)"
+  approval: {
+    question: /Do you want to .+\?/i,
+    options: /^\W*1\.\s*Yes\b/i,
+    approve: '1',
+    deny: '\x1b',
+  },
+
+  // Each conversation is `<session uuid>.jsonl`
+  findSessionId(cwd, since) {
+    const [newest] = recentFiles(projectDir(cwd), '.jsonl', since);
+    return newest ? path.basename(newest, '.jsonl') : null;
+  },
+  resumeSessionArgs: (id) => ['--resume', id],
+};
@@ -0,0 +1,113 @@
+import os from 'os';
+import path from 'path';
+import { emptyMetrics, parseTokenCount } from '../usage.js';
+import { readFirstLine, recentFiles } from './session-files.js';
+import type { AgentAdapter } from './types.js';
+
+// Exit summary:
+//   Token usage: total=12,345 input=10,000 (+ 2,000 cached) output=2,345
+const USAGE_RE =
+  /Token usage:\s*total=([\d,]+)\s+input=([\d,]+)(?:\s*\(\+\s*([\d,]+)\s+cached\))?\s+output=([\d,]+)/;
+
+// Transcript cells:
+//   • Edited src/app.ts (+3 -1)
+//   • Ran npm test
+//   ■ stream error: …
+// and the separator printed when a turn ends: "─ Worked for 1m 05s ──────"
+const EDIT_RE = /^\s*•\s*(?:Edited|Added|Deleted)\s+(.+?)(?:\s+\(\+\d+ -\d+\))?\s*$/;
+const RAN_RE = /^\s*•\s*Ran\s+(.+?)\s*$/;
+const ERROR_RE = /^\s*■\s*(.+)$/;
+const TURN_DONE_RE = /^\s*─\s*Worked for\b/;
+
+const ROLLOUT_ID_RE = /([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})\.jsonl$/;
+/** Days of rollout directories searched, so a long-running session is still found. */
+const MAX_SEARCH_DAYS = 7;
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+/** The directory a rollout file's session ran in, from its `session_meta` first line. */
+function rolloutCwd(file: string): string | null {
+  try {
+    const meta = JSON.parse(readFirstLine(file)) as { cwd?: unknown; payload?: { cwd?: unknown } };
+    const cwd = meta.payload?.cwd ?? meta.cwd;
+    return typeof cwd === 'string' ? cwd : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Rollouts are `sessions/YYYY/MM/DD/rollout-<time>-<session uuid>.jsonl`,
+ * shared by every directory, so each candidate's recorded cwd is checked.
+ */
+function findRollout(cwd: string, since: number): string | null {
+  const root = path.join(process.env.CODEX_HOME || path.join(os.homedir(), '.codex'), 'sessions');
+  const target = path.resolve(cwd);
+  const now = Date.now();
+  for (let day = 0; day < MAX_SEARCH_DAYS; day++) {
+    const date = new Date(now - day * DAY_MS);
+    if (date.getTime() < since - DAY_MS) break;
+    const dir = path.join(
+      root,
+      String(date.getFullYear()),
+      String(date.getMonth() + 1).padStart(2, '0'),
+      String(date.getDate()).padStart(2, '0'),
+    );
+    for (const file of recentFiles(dir, '.jsonl', since)) {
+      const fileCwd = rolloutCwd(file);
+      if (fileCwd && path.resolve(fileCwd) === target) return file;
+    }
+  }
+  return null;
+}
+
+export const codex: AgentAdapter = {
+  id: 'codex',
+  name: 'Codex CLI',
+  description: "OpenAI's Codex CLI agent",
+  command: 'codex',
+  args: [],
+  resume_args: ['resume', '--last'],
+  skip_permissions_args: ['--full-auto'],
+  headless_args: ['exec'],
+  profiles: [
+    { id: 'full-auto', name: 'Full auto', args: ['--full-auto'], env: {} },
+    { id: 'read-only', name: 'Read-only', args: ['--sandbox', 'read-only'], env: {} },
+  ],
+
+  parseUsageLine(line) {
+    const m = line.match(USAGE_RE);
+    if (!m) return null;
+    return {
+      ...emptyMetrics(),
+      total_tokens: parseTokenCount(m[1]),
+      input_tokens: parseTokenCount(m[2]),
+      cache_read_tokens: m[3] ? parseTokenCount(m[3]) : 0,
+      output_tokens: parseTokenCount(m[4]),
+    };
+  },
+
+  parseEventLine(line) {
+    const edit = line.match(EDIT_RE);
+    if (edit) return { kind: 'editing', detail: edit[1] };
+    const ran = line.match(RAN_RE);
+    if (ran) return { kind: 'command', detail: ran[1] };
+    const error = line.match(ERROR_RE);
+    if (error) return { kind: 'error', detail: error[1].trim() };
+    return TURN_DONE_RE.test(line) ? { kind: 'turn_complete' } : null;
+  },
+
+  // "Allow command?" / "Would you like to run the following command?"
+  // followed by "Yes, proceed (y)" … "No, and tell Codex what to do differently (esc)"
+  approval: {
+    question: /(?:Allow command\?|Would you like to (?:run|make|apply) .+\?)/i,
+    options: /\bYes\b.*\(y\)/i,
+    approve: 'y',
+    deny: '\x1b',
+  },
+
+  findSessionId(cwd, since) {
+    const file = findRollout(cwd, since);
+    return file?.match(ROLLOUT_ID_RE)?.[1] ?? null;
+  },
+  resumeSessionArgs: (id) => ['resume', id],
+};
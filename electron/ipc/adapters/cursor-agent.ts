@@ -0,0 +1,12 @@
+import type { AgentAdapter } from './types.js';
+
+export const cursorAgent: AgentAdapter = {
+  id: 'cursor-agent',
+  name: 'Cursor Agent',
+  description: "Cursor's CLI agent",
+  command: 'cursor-agent',
+  args: [],
+  resume_args: ['resume'],
+  skip_permissions_args: ['--force'],
+  headless_args: ['-p'],
+};
@@ -0,0 +1,12 @@
+import type { AgentAdapter } from './types.js';
+
+export const gemini: AgentAdapter = {
+  id: 'gemini',
+  name: 'Gemini CLI',
+  description: "Google's Gemini CLI agent",
+  command: 'gemini',
+  args: [],
+  resume_args: ['--resume', 'latest'],
+  skip_permissions_args: ['--yolo'],
+  headless_args: ['-p'],
+};
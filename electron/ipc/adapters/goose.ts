@@ -0,0 +1,13 @@
+import type { AgentAdapter } from './types.js';
+
+export const goose: AgentAdapter = {
+  id: 'goose',
+  name: 'Goose',
+  description: "Block's open source AI agent (block.github.io/goose)",
+  command: 'goose',
+  args: ['session'],
+  resume_args: ['session', '--resume'],
+  // Goose takes its approval mode from GOOSE_MODE rather than a flag
+  skip_permissions_args: [],
+  headless_args: ['run', '-t'],
+};
@@ -0,0 +1,49 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('../persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import {
+  AGENT_ADAPTERS,
+  adapterForCommand,
+  headlessInvocation,
+  resolveAgentAdapter,
+} from './index.js';
+
+describe('AGENT_ADAPTERS', () => {
+  it('has unique ids and commands', () => {
+    expect(new Set(AGENT_ADAPTERS.map((a) => a.id)).size).toBe(AGENT_ADAPTERS.length);
+    expect(new Set(AGENT_ADAPTERS.map((a) => a.command)).size).toBe(AGENT_ADAPTERS.length);
+  });
+
+  it('has unique profile ids per agent', () => {
+    for (const adapter of AGENT_ADAPTERS) {
+      const ids = (adapter.profiles ?? []).map((p) => p.id);
+      expect(new Set(ids).size).toBe(ids.length);
+    }
+  });
+});
+
+describe('resolveAgentAdapter', () => {
+  it('prefers the parser id, then the agent definition, then the command', () => {
+    expect(resolveAgentAdapter('codex', 'claude-code', 'claude')?.id).toBe('codex');
+    expect(resolveAgentAdapter(undefined, 'goose', 'goose')?.id).toBe('goose');
+    expect(resolveAgentAdapter(undefined, 'my-agent', '/opt/bin/aider')?.id).toBe('aider');
+    expect(resolveAgentAdapter(undefined, 'my-agent', 'my-agent')).toBeNull();
+  });
+
+  it('matches commands by binary name', () => {
+    expect(adapterForCommand('/usr/local/bin/Claude')?.id).toBe('claude-code');
+  });
+});
+
+describe('headlessInvocation', () => {
+  it('passes the prompt after the adapter headless args', () => {
+    const goose = adapterForCommand('goose');
+    expect(goose && headlessInvocation(goose, 'fix the build')).toEqual({
+      command: 'goose',
+      args: ['run', '-t', 'fix the build'],
+    });
+  });
+});
@@ -0,0 +1,61 @@
+import path from 'path';
+import { aider } from './aider.js';
+import { claudeCode } from './claude-code.js';
+import { codex } from './codex.js';
+import { cursorAgent } from './cursor-agent.js';
+import { gemini } from './gemini.js';
+import { goose } from './goose.js';
+import { opencode } from './opencode.js';
+import type { AgentAdapter } from './types.js';
+
+export type {
+  AgentAdapter,
+  AgentEvent,
+  AgentEventKind,
+  AgentProfile,
+  ApprovalPatterns,
+  EventLineParser,
+  UsageLineParser,
+} from './types.js';
+
+/** Built-in agents, in the order they're offered. Add new agents here. */
+export const AGENT_ADAPTERS: readonly AgentAdapter[] = [
+  claudeCode,
+  codex,
+  gemini,
+  opencode,
+  aider,
+  goose,
+  cursorAgent,
+];
+
+export function getAgentAdapter(id: string | undefined): AgentAdapter | null {
+  return AGENT_ADAPTERS.find((a) => a.id === id) ?? null;
+}
+
+/** The built-in adapter whose binary `command` runs, for custom agents that wrap one. */
+export function adapterForCommand(command: string): AgentAdapter | null {
+  const bin = path.basename(command).toLowerCase();
+  return AGENT_ADAPTERS.find((a) => a.command === bin) ?? null;
+}
+
+/**
+ * The adapter a session's output should be read with: an explicit parser id
+ * first, then the agent definition, then whatever the command looks like.
+ */
+export function resolveAgentAdapter(
+  parserId: string | undefined,
+  agentDefId: string | undefined,
+  command: string,
+): AgentAdapter | null {
+  return getAgentAdapter(parserId) ?? getAgentAdapter(agentDefId) ?? adapterForCommand(command);
+}
+
+/** Command and args for a one-shot run of `prompt`, or null when the agent has no such mode. */
+export function headlessInvocation(
+  adapter: AgentAdapter,
+  prompt: string,
+): { command: string; args: string[] } | null {
+  if (!adapter.headless_args) return null;
+  return { command: adapter.command, args: [...adapter.headless_args, prompt] };
+}
@@ -0,0 +1,12 @@
+import type { AgentAdapter } from './types.js';
+
+export const opencode: AgentAdapter = {
+  id: 'opencode',
+  name: 'OpenCode',
+  description: 'Open source AI coding agent (opencode.ai)',
+  command: 'opencode',
+  args: [],
+  resume_args: ['--continue'],
+  skip_permissions_args: [],
+  headless_args: ['run'],
+};
@@ -0,0 +1,38 @@
+import fs from 'fs';
+import path from 'path';
+
+/** Files in `dir` ending in `ext` and modified at or after `since`, newest first. */
+export function recentFiles(dir: string, ext: string, since: number): string[] {
+  let names: string[];
+  try {
+    names = fs.readdirSync(dir);
+  } catch {
+    return [];
+  }
+  const files: { file: string; mtime: number }[] = [];
+  for (const name of names) {
+    if (!name.endsWith(ext)) continue;
+    const file = path.join(dir, name);
+    try {
+      const mtime = fs.statSync(file).mtimeMs;
+      if (mtime >= since) files.push({ file, mtime });
+    } catch {
+      // Removed while listing
+    }
+  }
+  return files.sort((a, b) => b.mtime - a.mtime).map((f) => f.file);
+}
+
+/** The first line of a file, read without loading the rest. */
+export function readFirstLine(file: string, maxBytes = 64 * 1024): string {
+  const fd = fs.openSync(file, 'r');
+  try {
+    const buf = Buffer.alloc(maxBytes);
+    const n = fs.readSync(fd, buf, 0, maxBytes, 0);
+    const text = buf.subarray(0, n).toString('utf8');
+    const end = text.indexOf('\n');
+    return end === -1 ? text : text.slice(0, end);
+  } finally {
+    fs.closeSync(fd);
+  }
+}
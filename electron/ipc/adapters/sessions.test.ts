@@ -0,0 +1,59 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+
+vi.mock('../persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { claudeCode } from './claude-code.js';
+import { codex } from './codex.js';
+
+const UUID = '0199a213-81c0-7800-8aa1-bbab2a035a53';
+let tmp: string;
+
+beforeEach(() => {
+  tmp = fs.mkdtempSync(path.join(os.tmpdir(), 'agent-sessions-'));
+});
+
+afterEach(() => {
+  vi.unstubAllEnvs();
+  fs.rmSync(tmp, { recursive: true, force: true });
+});
+
+describe('claude-code sessions', () => {
+  it('finds the newest conversation saved for the worktree', () => {
+    vi.stubEnv('CLAUDE_CONFIG_DIR', tmp);
+    const dir = path.join(tmp, 'projects', '-work-my-app-wt');
+    fs.mkdirSync(dir, { recursive: true });
+    fs.writeFileSync(path.join(dir, `${UUID}.jsonl`), '{}\n');
+
+    expect(claudeCode.findSessionId?.('/work/my.app/wt', 0)).toBe(UUID);
+    expect(claudeCode.findSessionId?.('/work/other', 0)).toBeNull();
+    expect(claudeCode.findSessionId?.('/work/my.app/wt', Date.now() + 60_000)).toBeNull();
+    expect(claudeCode.resumeSessionArgs?.(UUID)).toEqual(['--resume', UUID]);
+  });
+});
+
+describe('codex sessions', () => {
+  it('matches rollouts by their recorded cwd', () => {
+    vi.stubEnv('CODEX_HOME', tmp);
+    const now = new Date();
+    const dir = path.join(
+      tmp,
+      'sessions',
+      String(now.getFullYear()),
+      String(now.getMonth() + 1).padStart(2, '0'),
+      String(now.getDate()).padStart(2, '0'),
+    );
+    fs.mkdirSync(dir, { recursive: true });
+    const meta = { type: 'session_meta', payload: { id: UUID, cwd: '/work/wt' } };
+    const file = path.join(dir, `rollout-2025-01-01T00-00-00-${UUID}.jsonl`);
+    fs.writeFileSync(file, JSON.stringify(meta) + '\n');
+
+    expect(codex.findSessionId?.('/work/wt', 0)).toBe(UUID);
+    expect(codex.findSessionId?.('/work/elsewhere', 0)).toBeNull();
+    expect(codex.resumeSessionArgs?.(UUID)).toEqual(['resume', UUID]);
+  });
+});
@@ -0,0 +1,72 @@
+import type { UsageMetrics } from '../usage.js';
+
+/**
+ * Reads one line of visible output. Returns the session's new cumulative
+ * usage when the line is part of a usage summary, otherwise null. `current`
+ * is the last value returned, for summaries that span several lines.
+ */
+export type UsageLineParser = (line: string, current: UsageMetrics | null) => UsageMetrics | null;
+
+export type AgentEventKind = 'editing' | 'command' | 'turn_complete' | 'error';
+
+/** Something an agent reported doing, read from its output. */
+export interface AgentEvent {
+  kind: AgentEventKind;
+  /** The file being edited, the command being run, or the error message. */
+  detail?: string;
+}
+
+/** Reads one line of visible output. Returns the event the line reports, otherwise null. */
+export type EventLineParser = (line: string) => AgentEvent | null;
+
+export interface ApprovalPatterns {
+  /** The question line of a pending permission prompt. */
+  question: RegExp;
+  /**
+   * A line at or after the question listing the answers, so finished
+   * dialogs in scrollback don't match.
+   */
+  options: RegExp;
+  /** Keys that answer yes. */
+  approve: string;
+  /** Keys that answer no. */
+  deny: string;
+}
+
+/** A named bundle of extra arguments and environment for launching an agent. */
+export interface AgentProfile {
+  id: string;
+  name: string;
+  /** Appended after the agent's own arguments. */
+  args: string[];
+  env: Record<string, string>;
+}
+
+/** Everything Parallel Code needs to know to run one kind of agent CLI. */
+export interface AgentAdapter {
+  id: string;
+  name: string;
+  description: string;
+  /** Binary, resolved via PATH. */
+  command: string;
+  /** Arguments for an interactive session. */
+  args: string[];
+  /** Used instead of `args` to continue the previous session in the worktree. */
+  resume_args: string[];
+  /** Appended to `args` to skip permission prompts. */
+  skip_permissions_args: string[];
+  /** Arguments for a one-shot run; the prompt is passed after them. Null when unsupported. */
+  headless_args: string[] | null;
+  /** Built-in launch profiles; projects can add their own. */
+  profiles?: AgentProfile[];
+  parseUsageLine?: UsageLineParser;
+  parseEventLine?: EventLineParser;
+  approval?: ApprovalPatterns;
+  /**
+   * The id of the conversation a session started in `cwd` at or after
+   * `since` (epoch ms) saved, so exactly that one can be resumed later.
+   */
+  findSessionId?: (cwd: string, since: number) => string | null;
+  /** Used instead of `args` to resume conversation `id`. */
+  resumeSessionArgs?: (id: string) => string[];
+}
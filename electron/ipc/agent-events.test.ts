@@ -0,0 +1,72 @@
+import { describe, expect, it } from 'vitest';
+import { createAgentEventParser, parseGenericEventLine } from './agent-events.js';
+import { getAgentAdapter } from './adapters/index.js';
+
+function parserFor(id: string) {
+  const parseLine = getAgentAdapter(id)?.parseEventLine;
+  if (!parseLine) throw new Error(`${id} has no event parser`);
+  return createAgentEventParser(parseLine);
+}
+
+describe('createAgentEventParser', () => {
+  it('reads lines split across chunks and through ANSI styling', () => {
+    const parser = parserFor('claude-code');
+    expect(parser.feed('\x1b[1m⏺ Update\x1b[22m(src/app')).toEqual([]);
+    expect(parser.feed('.ts)\r\n')).toEqual([{ kind: 'editing', detail: 'src/app.ts' }]);
+  });
+
+  it('drops an event repeated by a redraw', () => {
+    const parser = parserFor('claude-code');
+    const line = '⏺ Bash(npm test)\n';
+    expect(parser.feed(line + line)).toEqual([{ kind: 'command', detail: 'npm test' }]);
+    expect(parser.feed('✻ Worked for 1m 12s\n' + line)).toEqual([
+      { kind: 'turn_complete' },
+      { kind: 'command', detail: 'npm test' },
+    ]);
+  });
+});
+
+describe('claude-code events', () => {
+  it('recognizes tool errors and ignores other tools', () => {
+    const parser = parserFor('claude-code');
+    const output = [
+      '⏺ Read(README.md)',
+      '  ⎿  Error: File does not exist.',
+      '⏺ API Error: 529 Overloaded',
+      '',
+    ].join('\n');
+    expect(parser.feed(output)).toEqual([
+      { kind: 'error', detail: 'File does not exist.' },
+      { kind: 'error', detail: 'API Error: 529 Overloaded' },
+    ]);
+  });
+});
+
+describe('codex events', () => {
+  it('recognizes edits, commands, errors and the end of a turn', () => {
+    const parser = parserFor('codex');
+    const output = [
+      '• Ran git status --short',
+      '• Edited src/app.ts (+3 -1)',
+      '■ stream disconnected before completion',
+      '─ Worked for 1m 05s ──────────',
+      '',
+    ].join('\n');
+    expect(parser.feed(output)).toEqual([
+      { kind: 'command', detail: 'git status --short' },
+      { kind: 'editing', detail: 'src/app.ts' },
+      { kind: 'error', detail: 'stream disconnected before completion' },
+      { kind: 'turn_complete' },
+    ]);
+  });
+});
+
+describe('parseGenericEventLine', () => {
+  it('only recognizes errors', () => {
+    expect(parseGenericEventLine('Error: no such file')).toEqual({
+      kind: 'error',
+      detail: 'no such file',
+    });
+    expect(parseGenericEventLine('Running tests...')).toBeNull();
+  });
+});
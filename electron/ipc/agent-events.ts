@@ -0,0 +1,41 @@
+import { stripAnsiKeepRows } from './ansi.js';
+import type { AgentEvent, EventLineParser } from './adapters/types.js';
+
+/** Longest partial line kept while waiting for the rest of it. */
+const LINE_BUF_CAP = 4 * 1024;
+
+// `Error: …` and `error: …` as most CLIs print them
+const GENERIC_ERROR_RE = /^\s*(?:error|fatal):\s*(.+)$/i;
+
+/** For agents without their own parser: errors are about the only thing CLIs print alike. */
+export const parseGenericEventLine: EventLineParser = (line) => {
+  const m = line.match(GENERIC_ERROR_RE);
+  return m ? { kind: 'error', detail: m[1].trim() } : null;
+};
+
+/**
+ * Incremental parser for one session's output. Feed raw PTY text; returns the
+ * events `parseLine` recognized in the lines it completed. TUIs redraw the
+ * same lines over and over, so an event equal to the previous one is dropped.
+ */
+export function createAgentEventParser(parseLine: EventLineParser) {
+  let pending = '';
+  let last: AgentEvent | null = null;
+
+  return {
+    feed(text: string): AgentEvent[] {
+      pending += stripAnsiKeepRows(text);
+      const lines = pending.split(/[\r\n]/);
+      // Keep the trailing partial line for the next chunk
+      pending = (lines.pop() ?? '').slice(-LINE_BUF_CAP);
+      const events: AgentEvent[] = [];
+      for (const line of lines) {
+        const event = parseLine(line);
+        if (!event || (event.kind === last?.kind && event.detail === last.detail)) continue;
+        last = event;
+        events.push(event);
+      }
+      return events;
+    },
+  };
+}
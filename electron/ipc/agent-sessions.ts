@@ -0,0 +1,96 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { getAgentAdapter, type AgentAdapter } from './adapters/index.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('agent-sessions');
+
+/** The agent conversation a task's agent last had, for resuming it. */
+export interface AgentSessionRecord {
+  adapter_id: string;
+  session_id: string;
+  updated_at: number;
+}
+
+/** taskId -> agentDefId -> record */
+type SessionStore = Record<string, Record<string, AgentSessionRecord>>;
+
+let cache: SessionStore | null = null;
+
+function storePath(): string {
+  return path.join(getStateDir(), 'agent-sessions.json');
+}
+
+function load(): SessionStore {
+  if (cache) return cache;
+  try {
+    cache = JSON.parse(fs.readFileSync(storePath(), 'utf8')) as SessionStore;
+  } catch {
+    cache = {};
+  }
+  return cache;
+}
+
+function save(): void {
+  try {
+    const file = storePath();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    const tmp = file + '.tmp';
+    fs.writeFileSync(tmp, JSON.stringify(cache ?? {}), 'utf8');
+    fs.renameSync(tmp, file);
+  } catch (err) {
+    log.warn('Failed to save agent sessions', err);
+  }
+}
+
+export function recordAgentSession(
+  taskId: string,
+  agentDefId: string,
+  adapterId: string,
+  sessionId: string,
+): void {
+  const all = load();
+  const current = all[taskId]?.[agentDefId];
+  if (current?.adapter_id === adapterId && current.session_id === sessionId) return;
+  all[taskId] = {
+    ...all[taskId],
+    [agentDefId]: { adapter_id: adapterId, session_id: sessionId, updated_at: Date.now() },
+  };
+  save();
+}
+
+/** Look up and remember the conversation a finished session saved. */
+export function captureAgentSession(
+  taskId: string,
+  agentDefId: string,
+  adapter: AgentAdapter,
+  cwd: string,
+  startedAt: number,
+): void {
+  if (!adapter.findSessionId) return;
+  try {
+    const sessionId = adapter.findSessionId(cwd, startedAt);
+    if (sessionId) recordAgentSession(taskId, agentDefId, adapter.id, sessionId);
+  } catch (err) {
+    log.warn(`Could not find the ${adapter.id} session for task ${taskId}`, err);
+  }
+}
+
+/**
+ * Arguments that resume the conversation a task's agent last had, or null
+ * when none was recorded and the agent's generic resume flags should be used.
+ */
+export function getAgentResumeArgs(taskId: string, agentDefId: string): string[] | null {
+  const record = load()[taskId]?.[agentDefId];
+  if (!record) return null;
+  const adapter = getAgentAdapter(record.adapter_id);
+  return adapter?.resumeSessionArgs ? adapter.resumeSessionArgs(record.session_id) : null;
+}
+
+export function deleteAgentSessions(taskId: string): void {
+  const all = load();
+  if (!(taskId in all)) return;
+  delete all[taskId];
+  save();
+}
@@ -1,5 +1,7 @@
 import { execFile } from 'child_process';
 import { promisify } from 'util';
+import type { RestartPolicy } from './pty.js';
+import { AGENT_ADAPTERS, type AgentAdapter, type AgentProfile } from './adapters/index.js';
 
 const execFileAsync = promisify(execFile);
 
@@ -10,48 +12,29 @@ interface AgentDef {
   args: string[];
   resume_args: string[];
   skip_permissions_args: string[];
+  headless_args: string[] | null;
+  profiles: AgentProfile[];
   description: string;
+  restart_policy?: RestartPolicy;
+  /** Adapter whose usage summaries the session's output is scanned for. */
+  usage_parser?: string;
   available?: boolean;
 }
 
-const DEFAULT_AGENTS: AgentDef[] = [
-  {
-    id: 'claude-code',
-    name: 'Claude Code',
-    command: 'claude',
-    args: [],
-    resume_args: ['--continue'],
-    skip_permissions_args: ['--dangerously-skip-permissions'],
-    description: "Anthropic's Claude Code CLI agent",
-  },
-  {
-    id: 'codex',
-    name: 'Codex CLI',
-    command: 'codex',
-    args: [],
-    resume_args: ['resume', '--last'],
-    skip_permissions_args: ['--full-auto'],
-    description: "OpenAI's Codex CLI agent",
-  },
-  {
-    id: 'gemini',
-    name: 'Gemini CLI',
-    command: 'gemini',
-    args: [],
-    resume_args: ['--resume', 'latest'],
-    skip_permissions_args: ['--yolo'],
-    description: "Google's Gemini CLI agent",
-  },
-  {
-    id: 'opencode',
-    name: 'OpenCode',
-    command: 'opencode',
-    args: [],
-    resume_args: [],
-    skip_permissions_args: [],
-    description: 'Open source AI coding agent (opencode.ai)',
-  },
-];
+function toAgentDef(adapter: AgentAdapter): AgentDef {
+  return {
+    id: adapter.id,
+    name: adapter.name,
+    command: adapter.command,
+    args: adapter.args,
+    resume_args: adapter.resume_args,
+    skip_permissions_args: adapter.skip_permissions_args,
+    headless_args: adapter.headless_args,
+    profiles: adapter.profiles ?? [],
+    description: adapter.description,
+    ...(adapter.parseUsageLine ? { usage_parser: adapter.id } : {}),
+  };
+}
 
 async function isCommandAvailable(command: string): Promise<boolean> {
   try {
@@ -74,7 +57,7 @@ export async function listAgents(): Promise<AgentDef[]> {
   }
 
   cachedAgents = await Promise.all(
-    DEFAULT_AGENTS.map(async (agent) => ({
+    AGENT_ADAPTERS.map(toAgentDef).map(async (agent) => ({
       ...agent,
       available: await isCommandAvailable(agent.command),
     })),
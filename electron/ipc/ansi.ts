@@ -0,0 +1,28 @@
+/** ANSI escape handling shared by backend output parsers. */
+
+// CSI/ESC sequences and OSC strings (terminated by BEL or ST).
+const ANSI_RE =
+  // eslint-disable-next-line no-control-regex
+  /[\u001b\u009b][[()#;?]*(?:[0-9]{1,4}(?:;[0-9]{0,4})*)?[0-9A-ORZcf-nq-uy=><~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)?/g;
+
+// Cursor moves to another row (CUU/CUD/CNL/CPL/CUP/VPA/HVP).
+// eslint-disable-next-line no-control-regex
+const ROW_MOVE_RE = /\x1b\[[0-9;]*[ABEFHdf]/g;
+
+// Cursor forward, which TUI renderers use in place of runs of spaces.
+// eslint-disable-next-line no-control-regex
+const CURSOR_FORWARD_RE = /\x1b\[[0-9]*C/g;
+
+/** Remove all ANSI escape sequences. */
+export function stripAnsi(text: string): string {
+  return text.replace(ANSI_RE, '');
+}
+
+/**
+ * Remove ANSI escape sequences while keeping visually separate rows apart.
+ * TUI apps position text with cursor moves instead of newlines, so
+ * line-oriented parsers see row moves as `\n` and forward moves as a space.
+ */
+export function stripAnsiKeepRows(text: string): string {
+  return stripAnsi(text.replace(ROW_MOVE_RE, '\n').replace(CURSOR_FORWARD_RE, ' '));
+}
@@ -0,0 +1,82 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { execFileSync } from 'child_process';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { backupFileName, exportAppData, importAppData, parseAppDataManifest } from './app-data.js';
+
+describe('backupFileName', () => {
+  it('stamps the local date and time', () => {
+    expect(backupFileName(new Date(2026, 0, 31, 14, 5, 12))).toBe(
+      'parallel-code-backup-2026-01-31-140512.tar.gz',
+    );
+  });
+});
+
+describe('parseAppDataManifest', () => {
+  it('accepts a manifest listing known entries', () => {
+    expect(
+      parseAppDataManifest({ version: 1, exported_at: 5, entries: ['state.json', 'task-events'] }),
+    ).toEqual({ version: 1, exported_at: 5, entries: ['state.json', 'task-events'] });
+  });
+
+  it('rejects archives that are not backups', () => {
+    expect(() => parseAppDataManifest(null)).toThrow('Not a Parallel Code backup');
+    expect(() => parseAppDataManifest({ version: 1 })).toThrow('Not a Parallel Code backup');
+  });
+
+  it('rejects backups from a newer format', () => {
+    expect(() => parseAppDataManifest({ version: 99, entries: [] })).toThrow('newer version');
+  });
+
+  it('rejects entries outside the app data', () => {
+    expect(() => parseAppDataManifest({ version: 1, entries: ['../.ssh/id_rsa'] })).toThrow(
+      'unexpected file',
+    );
+  });
+});
+
+describe('exportAppData / importAppData', () => {
+  let dir: string;
+
+  beforeEach(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'app-data-'));
+  });
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('restores exported data and backs up what it replaces', async () => {
+    const source = path.join(dir, 'source');
+    const target = path.join(dir, 'target');
+    fs.mkdirSync(path.join(source, 'task-events'), { recursive: true });
+    fs.writeFileSync(path.join(source, 'state.json'), '{"tasks":{}}');
+    fs.writeFileSync(path.join(source, 'task-events', 't1.jsonl'), '{}\n');
+    fs.writeFileSync(path.join(source, 'automation-token'), 'secret');
+    fs.mkdirSync(target);
+    fs.writeFileSync(path.join(target, 'state.json'), '{"old":true}');
+    fs.writeFileSync(path.join(target, 'usage.json'), '{}');
+
+    const archive = await exportAppData(source, path.join(dir, backupFileName()));
+    const stopped: string[] = [];
+    const result = await importAppData(target, archive, () => stopped.push('agents'));
+
+    expect(stopped).toEqual(['agents']);
+    expect(fs.readFileSync(path.join(target, 'state.json'), 'utf8')).toBe('{"tasks":{}}');
+    expect(fs.existsSync(path.join(target, 'task-events', 't1.jsonl'))).toBe(true);
+    expect(fs.existsSync(path.join(target, 'usage.json'))).toBe(false);
+    expect(fs.existsSync(path.join(target, 'automation-token'))).toBe(false);
+    expect(result.backup_path).toMatch(/backups[/\\]parallel-code-backup-.*\.tar\.gz$/);
+  });
+
+  it('leaves the data alone when the archive is not a backup', async () => {
+    fs.writeFileSync(path.join(dir, 'state.json'), '{"old":true}');
+    const bogus = path.join(dir, 'bogus.tar.gz');
+    fs.writeFileSync(path.join(dir, 'notes.txt'), 'hi');
+    execFileSync('tar', ['-czf', bogus, '-C', dir, 'notes.txt']);
+
+    await expect(importAppData(dir, bogus)).rejects.toThrow('Not a Parallel Code backup');
+    expect(fs.readFileSync(path.join(dir, 'state.json'), 'utf8')).toBe('{"old":true}');
+  });
+});
@@ -0,0 +1,126 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+
+const exec = promisify(execFile);
+
+const ARCHIVE_VERSION = 1;
+
+/**
+ * What a backup holds, relative to the state dir. Logs, caches and
+ * machine-local files (the automation token, reserved ports, the shell
+ * environment snapshot) stay behind.
+ */
+const DATA_ENTRIES = [
+  'state.json',
+  'settings.json',
+  'recent-projects.json',
+  'agent-sessions.json',
+  'usage.json',
+  'scheduled-runs.json',
+  'trash.json',
+  'history.db',
+  'history.db-wal',
+  'task-events',
+  'recordings',
+];
+
+export interface AppDataManifest {
+  version: number;
+  exported_at: number;
+  entries: string[];
+}
+
+/** `parallel-code-backup-2026-01-31-140512.tar.gz`, in local time. */
+export function backupFileName(now = new Date()): string {
+  const pad = (n: number) => String(n).padStart(2, '0');
+  const date = `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}`;
+  const time = `${pad(now.getHours())}${pad(now.getMinutes())}${pad(now.getSeconds())}`;
+  return `parallel-code-backup-${date}-${time}.tar.gz`;
+}
+
+/** Check a backup's manifest; throws when it's from a newer app or lists unknown files. */
+export function parseAppDataManifest(raw: unknown): AppDataManifest {
+  const m = raw as Partial<AppDataManifest> | null;
+  if (!m || typeof m.version !== 'number' || !Array.isArray(m.entries)) {
+    throw new Error('Not a Parallel Code backup');
+  }
+  if (m.version > ARCHIVE_VERSION) {
+    throw new Error(`Backup is from a newer version of the app (format ${m.version})`);
+  }
+  const unknown = m.entries.find((e) => typeof e !== 'string' || !DATA_ENTRIES.includes(e));
+  if (unknown !== undefined) throw new Error(`Backup holds an unexpected file: ${String(unknown)}`);
+  return { version: m.version, exported_at: m.exported_at ?? 0, entries: m.entries };
+}
+
+/** Copy a file or folder, leaving out symlinks so a backup can't point outside it. */
+function copyEntry(source: string, target: string): void {
+  fs.cpSync(source, target, {
+    recursive: true,
+    filter: (src) => !fs.lstatSync(src).isSymbolicLink(),
+  });
+}
+
+function withStagingDir<T>(fn: (dir: string) => Promise<T>): Promise<T> {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'parallel-code-data-'));
+  return fn(dir).finally(() => fs.rmSync(dir, { recursive: true, force: true }));
+}
+
+/** Pack the app's data in `stateDir` into a `.tar.gz` at `outPath`. */
+export function exportAppData(stateDir: string, outPath: string): Promise<string> {
+  if (fs.existsSync(outPath)) throw new Error(`File already exists: ${outPath}`);
+  return withStagingDir(async (staging) => {
+    const entries = DATA_ENTRIES.filter((e) => fs.existsSync(path.join(stateDir, e)));
+    // Copied first so the archive is one consistent snapshot, not files caught mid-write
+    for (const entry of entries) copyEntry(path.join(stateDir, entry), path.join(staging, entry));
+    const manifest: AppDataManifest = {
+      version: ARCHIVE_VERSION,
+      exported_at: Date.now(),
+      entries,
+    };
+    fs.writeFileSync(path.join(staging, 'manifest.json'), JSON.stringify(manifest, null, 2));
+    fs.mkdirSync(path.dirname(outPath), { recursive: true });
+    await exec('tar', ['-czf', outPath, '-C', staging, 'manifest.json', ...entries]);
+    return outPath;
+  });
+}
+
+/**
+ * Replace the app's data in `stateDir` with a backup's. The current data,
+ * if any, is backed up under `backups/` first. The app must restart
+ * afterwards, since it holds the old state in memory and would write it back.
+ */
+export function importAppData(
+  stateDir: string,
+  archivePath: string,
+  /** Runs once the backup checks out, before any file is touched: stop what writes them. */
+  beforeReplace: () => void = () => {},
+): Promise<{ backup_path: string | null }> {
+  if (!fs.existsSync(archivePath)) throw new Error(`File not found: ${archivePath}`);
+  return withStagingDir(async (staging) => {
+    await exec('tar', ['-xzf', archivePath, '-C', staging]);
+    let raw: unknown;
+    try {
+      raw = JSON.parse(fs.readFileSync(path.join(staging, 'manifest.json'), 'utf8'));
+    } catch {
+      throw new Error('Not a Parallel Code backup');
+    }
+    const manifest = parseAppDataManifest(raw);
+    beforeReplace();
+
+    let backupPath: string | null = null;
+    if (DATA_ENTRIES.some((e) => fs.existsSync(path.join(stateDir, e)))) {
+      backupPath = await exportAppData(stateDir, path.join(stateDir, 'backups', backupFileName()));
+    }
+    for (const entry of [...DATA_ENTRIES, 'history.db-shm']) {
+      fs.rmSync(path.join(stateDir, entry), { recursive: true, force: true });
+    }
+    for (const entry of manifest.entries) {
+      const source = path.join(staging, entry);
+      if (fs.existsSync(source)) copyEntry(source, path.join(stateDir, entry));
+    }
+    return { backup_path: backupPath };
+  });
+}
@@ -0,0 +1,66 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { approvalAgentKind, approvalKeys, detectApprovalPrompt } from './approvals.js';
+
+const CLAUDE_PROMPT = [
+  '╭──────────────────────────────────────────╮',
+  '│ Bash command                             │',
+  '│   npm test                               │',
+  '│ Do you want to proceed?                  │',
+  '│ ❯ 1. Yes                                 │',
+  "│   2. Yes, and don't ask again for npm    │",
+  '│   3. No, and tell Claude what to do (esc)│',
+  '╰──────────────────────────────────────────╯',
+].join('\r\n');
+
+describe('approvalAgentKind', () => {
+  it('prefers the adapter and falls back to the command name', () => {
+    expect(approvalAgentKind('codex', 'claude')).toBe('codex');
+    expect(approvalAgentKind(undefined, '/usr/local/bin/claude')).toBe('claude-code');
+    expect(approvalAgentKind(undefined, 'aider')).toBe('aider');
+    expect(approvalAgentKind('gemini', 'gemini')).toBeNull();
+  });
+});
+
+describe('detectApprovalPrompt', () => {
+  it('finds a pending Claude Code prompt with its context', () => {
+    const prompt = detectApprovalPrompt('claude-code', `working…\r\n${CLAUDE_PROMPT}`);
+    expect(prompt).toBe('working…\nBash command\nnpm test\nDo you want to proceed?');
+  });
+
+  it('sees through colors and cursor moves', () => {
+    const drawn = '\x1b[1mDo you want to proceed?\x1b[0m\x1b[2;1H\x1b[36m❯ 1. Yes\x1b[0m';
+    expect(detectApprovalPrompt('claude-code', drawn)).toBe('Do you want to proceed?');
+  });
+
+  it('ignores questions without options and answered ones', () => {
+    expect(detectApprovalPrompt('claude-code', 'Do you want to know a secret?')).toBeNull();
+    const answered = `${CLAUDE_PROMPT}\r\nRunning…\r\n❯ `;
+    expect(detectApprovalPrompt('claude-code', answered)).toBeNull();
+  });
+
+  it('finds a pending Codex prompt', () => {
+    const output = '$ rm -rf build\r\nAllow command?\r\n  Yes, proceed (y)\r\n  No (esc)';
+    expect(detectApprovalPrompt('codex', output)).toBe('$ rm -rf build\nAllow command?');
+  });
+
+  it('finds an Aider prompt with its answers on the question line', () => {
+    const output = 'npm test\r\nRun shell command? (Y)es/(N)o/(D)on\'t ask again [Yes]: ';
+    expect(detectApprovalPrompt('aider', output)).toBe(
+      "npm test\nRun shell command? (Y)es/(N)o/(D)on't ask again [Yes]:",
+    );
+  });
+});
+
+describe('approvalKeys', () => {
+  it('answers each agent with its own keys', () => {
+    expect(approvalKeys('claude-code', true)).toBe('1');
+    expect(approvalKeys('codex', true)).toBe('y');
+    expect(approvalKeys('codex', false)).toBe('\x1b');
+    expect(approvalKeys('aider', false)).toBe('n\r');
+  });
+});
@@ -0,0 +1,67 @@
+import { stripAnsiKeepRows } from './ansi.js';
+import { adapterForCommand, getAgentAdapter, type ApprovalPatterns } from './adapters/index.js';
+
+/** Visible characters of recent output searched for a prompt. */
+const SCAN_CHARS = 3000;
+/** Lines above the question kept as context (the command or file being asked about). */
+const CONTEXT_LINES = 8;
+
+/** Id of the agent adapter whose prompt patterns a session is matched against. */
+export type ApprovalAgentKind = string;
+
+function patternsFor(kind: ApprovalAgentKind): ApprovalPatterns | null {
+  return getAgentAdapter(kind)?.approval ?? null;
+}
+
+/** Which agent's prompts a session shows, from its adapter or command name. */
+export function approvalAgentKind(
+  adapterId: string | undefined,
+  command: string,
+): ApprovalAgentKind | null {
+  const adapter = getAgentAdapter(adapterId) ?? adapterForCommand(command);
+  return adapter?.approval ? adapter.id : null;
+}
+
+/**
+ * The pending permission prompt at the end of `output`, as plain text, or
+ * null when the agent isn't waiting on one. A prompt only counts while its
+ * options are the last thing drawn; once answered, new output follows.
+ */
+export function detectApprovalPrompt(kind: ApprovalAgentKind, output: string): string | null {
+  const matcher = patternsFor(kind);
+  if (!matcher) return null;
+  const lines = stripAnsiKeepRows(output.slice(-SCAN_CHARS * 4))
+    .slice(-SCAN_CHARS)
+    .split(/\r?\n/)
+    .map((l) => l.trimEnd())
+    .filter((l) => l.trim().length > 0);
+
+  let question = -1;
+  for (let i = lines.length - 1; i >= 0; i--) {
+    if (matcher.question.test(lines[i])) {
+      question = i;
+      break;
+    }
+  }
+  if (question === -1) return null;
+  // Some agents list the answers on the question line itself
+  const after = lines.slice(question);
+  if (!after.some((l) => matcher.options.test(l))) return null;
+  // An agent prompt drawn after the dialog means it was answered
+  const last = lines[lines.length - 1];
+  if (/^\s*[>❯›]\s*$/.test(last)) return null;
+
+  return lines
+    .slice(Math.max(0, question - CONTEXT_LINES), question + 1)
+    // Drop the dialog's box drawing
+    .map((l) => l.replace(/^[\s│╭╮╰╯─]+|[\s│╭╮╰╯─]+$/g, ''))
+    .filter(Boolean)
+    .join('\n');
+}
+
+/** Keys that answer a pending prompt. */
+export function approvalKeys(kind: ApprovalAgentKind, approve: boolean): string {
+  const matcher = patternsFor(kind);
+  if (!matcher) return '';
+  return approve ? matcher.approve : matcher.deny;
+}
@@ -0,0 +1,66 @@
+import { describe, expect, it } from 'vitest';
+import { isValidBranchName, slug, uniqueBranchName } from './branch-names.js';
+
+describe('slug', () => {
+  it('transliterates accented and ligature letters', () => {
+    expect(slug('Café Übersicht')).toBe('cafe-ubersicht');
+    expect(slug('Straße Ærø Łódź')).toBe('strasse-aero-lodz');
+  });
+
+  it('collapses runs of anything else into one dash', () => {
+    expect(slug('  Fix -- login!!  (again) ')).toBe('fix-login-again');
+    expect(slug('修复 login 问题')).toBe('login');
+    expect(slug('你好 世界')).toBe('');
+  });
+
+  it('cuts long names at a word boundary', () => {
+    expect(slug('refactor the session token refresh flow', 24)).toBe('refactor-the-session');
+    expect(slug('a'.repeat(40), 24)).toBe('a'.repeat(24));
+  });
+});
+
+describe('isValidBranchName', () => {
+  it('accepts ordinary task branches', () => {
+    expect(isValidBranchName('task/fix-login')).toBe(true);
+    expect(isValidBranchName('feature/team/v1.2')).toBe(true);
+  });
+
+  it('rejects what git check-ref-format rejects', () => {
+    for (const name of [
+      '',
+      '@',
+      '-x',
+      'task/a b',
+      'task/a..b',
+      'task/a~1',
+      'task/a@{1}',
+      'task//a',
+      'task/',
+      'task/a.',
+      'task/.hidden',
+      'task/a.lock',
+      'task/a:b',
+    ]) {
+      expect(isValidBranchName(name), name).toBe(false);
+    }
+  });
+});
+
+describe('uniqueBranchName', () => {
+  it('keeps a free name as is', async () => {
+    await expect(uniqueBranchName('task/a', () => Promise.resolve(false))).resolves.toBe('task/a');
+  });
+
+  it('adds the first free numeric suffix', async () => {
+    const taken = new Set(['task/a', 'task/a-2']);
+    await expect(uniqueBranchName('task/a', (b) => Promise.resolve(taken.has(b)))).resolves.toBe(
+      'task/a-3',
+    );
+  });
+
+  it('refuses names git would reject', async () => {
+    await expect(uniqueBranchName('task/a..b', () => Promise.resolve(false))).rejects.toThrow(
+      'Invalid branch name',
+    );
+  });
+});
@@ -0,0 +1,67 @@
+const MAX_SLUG_LEN = 72;
+const MAX_SUFFIX = 99;
+
+/** Letters NFKD doesn't split into a base letter plus accents. */
+const TRANSLITERATIONS: Record<string, string> = {
+  ß: 'ss',
+  æ: 'ae',
+  œ: 'oe',
+  ø: 'o',
+  đ: 'd',
+  ð: 'd',
+  ł: 'l',
+  þ: 'th',
+  ı: 'i',
+};
+
+/**
+ * Turn a task name into a branch-name segment: accents dropped, anything
+ * that isn't a letter or digit collapsed to a single `-`, cut at a word
+ * boundary when longer than `maxLen`. Scripts with no Latin spelling
+ * (e.g. Chinese) come out empty; callers pick a fallback.
+ */
+export function slug(name: string, maxLen = MAX_SLUG_LEN): string {
+  const latin = name
+    .toLowerCase()
+    .normalize('NFKD')
+    .replace(/\p{M}/gu, '')
+    .replace(/./gu, (c) => TRANSLITERATIONS[c] ?? c);
+  const dashed = latin.replace(/[^a-z0-9]+/g, '-').replace(/^-+|-+$/g, '');
+  if (dashed.length <= maxLen) return dashed;
+  const cut = dashed.slice(0, maxLen + 1);
+  const boundary = cut.lastIndexOf('-');
+  // Only back up to a word boundary when it doesn't throw most of the name away
+  const end = boundary >= maxLen / 2 ? boundary : maxLen;
+  return dashed.slice(0, end).replace(/-+$/, '');
+}
+
+/** Whether git accepts `name` as a branch, by the rules of `git check-ref-format --branch`. */
+export function isValidBranchName(name: string): boolean {
+  if (!name || name === '@' || name.startsWith('-')) return false;
+  // Control characters, space and the characters git reserves for revision syntax
+  // eslint-disable-next-line no-control-regex
+  if (/[\x00-\x20\x7f~^:?*[\\]/.test(name)) return false;
+  if (name.includes('..') || name.includes('@{') || name.includes('//')) return false;
+  if (name.startsWith('/') || name.endsWith('/') || name.endsWith('.')) return false;
+  return name
+    .split('/')
+    .every((part) => part.length > 0 && !part.startsWith('.') && !part.endsWith('.lock'));
+}
+
+/**
+ * `branchName`, or the first of `branchName-2`, `branchName-3`, … that
+ * `isTaken` says is free, so two tasks with the same name get their own
+ * branches instead of sharing one.
+ */
+export async function uniqueBranchName(
+  branchName: string,
+  isTaken: (candidate: string) => Promise<boolean>,
+): Promise<string> {
+  if (!isValidBranchName(branchName)) throw new Error(`Invalid branch name: ${branchName}`);
+  if (!(await isTaken(branchName))) return branchName;
+  for (let n = 2; n <= MAX_SUFFIX; n++) {
+    const candidate = `${branchName}-${n}`;
+    if (!(await isTaken(candidate))) return candidate;
+  }
+  throw new Error(`Too many branches named ${branchName}`);
+}
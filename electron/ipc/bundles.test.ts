@@ -0,0 +1,63 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { parseTranscripts, readBundleManifest } from './bundles.js';
+
+describe('readBundleManifest', () => {
+  let dir: string;
+
+  beforeEach(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'pctask-'));
+  });
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  function writeManifest(value: unknown): void {
+    fs.writeFileSync(path.join(dir, 'manifest.json'), JSON.stringify(value));
+  }
+
+  it('reads a valid manifest and defaults missing metadata', () => {
+    writeManifest({ version: 1, branch_name: 'task/demo', exported_at: 5 });
+    expect(readBundleManifest(dir)).toEqual({
+      version: 1,
+      branch_name: 'task/demo',
+      exported_at: 5,
+      metadata: {},
+    });
+  });
+
+  it('rejects unsupported versions and unsafe branch names', () => {
+    writeManifest({ version: 2, branch_name: 'task/demo' });
+    expect(() => readBundleManifest(dir)).toThrow('Unsupported task bundle version');
+    writeManifest({ version: 1, branch_name: '--upload-pack=evil' });
+    expect(() => readBundleManifest(dir)).toThrow('must not start with "-"');
+    for (const branch_name of ['task/../main', 'task/a b', 'task/x.lock', 'refs/heads/']) {
+      writeManifest({ version: 1, branch_name });
+      expect(() => readBundleManifest(dir)).toThrow('not a valid branch name');
+    }
+  });
+});
+
+describe('parseTranscripts', () => {
+  it('reads one transcript per line, skipping damaged ones', () => {
+    const text = [
+      JSON.stringify({ agent_id: 'a1', lines: ['hello', 'world'] }),
+      '{"agent_id": "a2", "lines": [',
+      JSON.stringify({ agent_id: 'a3' }),
+      JSON.stringify({ agent_id: 'a4', lines: ['ok', 5] }),
+      '',
+    ].join('\n');
+    expect(parseTranscripts(text)).toEqual([
+      { agent_id: 'a1', lines: ['hello', 'world'] },
+      { agent_id: 'a4', lines: ['ok'] },
+    ]);
+  });
+});
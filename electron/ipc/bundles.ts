@@ -0,0 +1,200 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { randomUUID } from 'crypto';
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { branchExists, createWorktree, withRepoLock } from './git.js';
+import { AppError } from './errors.js';
+import { getTaskTranscripts, storeTranscript, type AgentTranscript } from './storage.js';
+import { validateBranchName } from './validate.js';
+import {
+  appendTaskEvents,
+  getTaskEvents,
+  parseTaskEvents,
+  recordTaskEvent,
+} from './task-events.js';
+
+const exec = promisify(execFile);
+
+const BUNDLE_VERSION = 1;
+const BUNDLE_EXT = '.pctask';
+const MAX_BUFFER = 50 * 1024 * 1024;
+
+/**
+ * A task bundle is a directory holding:
+ *   manifest.json      — version, branch name, renderer-supplied task metadata
+ *   branch.bundle      — `git bundle` of the task branch
+ *   uncommitted.patch  — binary diff of uncommitted changes, untracked files included (optional)
+ *   events.jsonl       — the task's event log (optional)
+ *   transcripts.jsonl  — its agents' stored transcripts, one per line (optional)
+ */
+export interface TaskBundleManifest {
+  version: number;
+  branch_name: string;
+  exported_at: number;
+  metadata: Record<string, unknown>;
+}
+
+function bundleDirName(branchName: string): string {
+  return branchName.replace(/[^\w.-]+/g, '-') + BUNDLE_EXT;
+}
+
+/**
+ * Everything in the worktree that isn't committed, as a binary patch. The
+ * files go through a throwaway copy of the index, so untracked ones are
+ * included without the worktree's own index changing.
+ */
+async function uncommittedPatch(worktreePath: string): Promise<string> {
+  const { stdout: indexPath } = await exec('git', ['rev-parse', '--git-path', 'index'], {
+    cwd: worktreePath,
+  });
+  const tmpDir = await fs.promises.mkdtemp(path.join(os.tmpdir(), 'parallel-code-export-'));
+  try {
+    const tmpIndex = path.join(tmpDir, 'index');
+    const realIndex = path.resolve(worktreePath, indexPath.trim());
+    await fs.promises.copyFile(realIndex, tmpIndex).catch(() => {});
+    const env = { ...process.env, GIT_INDEX_FILE: tmpIndex };
+    await exec('git', ['add', '-A'], { cwd: worktreePath, env });
+    const { stdout } = await exec('git', ['diff', '--cached', '--binary', 'HEAD'], {
+      cwd: worktreePath,
+      env,
+      maxBuffer: MAX_BUFFER,
+    });
+    return stdout;
+  } finally {
+    await fs.promises.rm(tmpDir, { recursive: true, force: true });
+  }
+}
+
+export async function exportTask(
+  taskId: string,
+  worktreePath: string,
+  branchName: string,
+  metadata: Record<string, unknown>,
+  outDir: string,
+): Promise<string> {
+  const bundleDir = path.join(outDir, bundleDirName(branchName));
+  if (fs.existsSync(bundleDir)) throw new Error(`Bundle already exists: ${bundleDir}`);
+  fs.mkdirSync(bundleDir, { recursive: true });
+  try {
+    await exec('git', ['bundle', 'create', path.join(bundleDir, 'branch.bundle'), branchName], {
+      cwd: worktreePath,
+    });
+    const patch = await uncommittedPatch(worktreePath);
+    if (patch.trim()) fs.writeFileSync(path.join(bundleDir, 'uncommitted.patch'), patch, 'utf8');
+
+    const events = getTaskEvents(taskId);
+    if (events.length > 0) {
+      const lines = events.map((e) => JSON.stringify(e)).join('\n') + '\n';
+      fs.writeFileSync(path.join(bundleDir, 'events.jsonl'), lines, 'utf8');
+    }
+
+    const transcripts = getTaskTranscripts(taskId);
+    if (transcripts.length > 0) {
+      const lines = transcripts.map((t) => JSON.stringify(t)).join('\n') + '\n';
+      fs.writeFileSync(path.join(bundleDir, 'transcripts.jsonl'), lines, 'utf8');
+    }
+
+    const manifest: TaskBundleManifest = {
+      version: BUNDLE_VERSION,
+      branch_name: branchName,
+      exported_at: Date.now(),
+      metadata,
+    };
+    fs.writeFileSync(path.join(bundleDir, 'manifest.json'), JSON.stringify(manifest, null, 2));
+  } catch (err) {
+    fs.rmSync(bundleDir, { recursive: true, force: true });
+    throw err;
+  }
+  recordTaskEvent(taskId, 'exported', { bundle: bundleDir });
+  return bundleDir;
+}
+
+export function readBundleManifest(bundleDir: string): TaskBundleManifest {
+  const raw: unknown = JSON.parse(fs.readFileSync(path.join(bundleDir, 'manifest.json'), 'utf8'));
+  const m = raw as Partial<TaskBundleManifest>;
+  if (!raw || typeof raw !== 'object' || typeof m.branch_name !== 'string' || !m.branch_name) {
+    throw new Error('Invalid task bundle: missing branch name');
+  }
+  if (m.version !== BUNDLE_VERSION) {
+    throw new Error(`Unsupported task bundle version: ${m.version}`);
+  }
+  validateBranchName(m.branch_name, 'Task bundle branch name');
+  return {
+    version: m.version,
+    branch_name: m.branch_name,
+    exported_at: typeof m.exported_at === 'number' ? m.exported_at : 0,
+    metadata: m.metadata && typeof m.metadata === 'object' ? m.metadata : {},
+  };
+}
+
+/** The transcripts in a bundle's `transcripts.jsonl`, skipping lines that aren't one. */
+export function parseTranscripts(text: string): AgentTranscript[] {
+  const out: AgentTranscript[] = [];
+  for (const line of text.split('\n')) {
+    if (!line.trim()) continue;
+    try {
+      const t = JSON.parse(line) as Partial<AgentTranscript>;
+      if (typeof t.agent_id !== 'string' || !Array.isArray(t.lines)) continue;
+      out.push({ agent_id: t.agent_id, lines: t.lines.filter((l) => typeof l === 'string') });
+    } catch {
+      // A damaged line loses that transcript only
+    }
+  }
+  return out;
+}
+
+export async function importTask(
+  projectRoot: string,
+  bundleDir: string,
+  symlinkDirs: string[],
+): Promise<{
+  id: string;
+  branch_name: string;
+  worktree_path: string;
+  metadata: Record<string, unknown>;
+}> {
+  const manifest = readBundleManifest(bundleDir);
+  const branchName = manifest.branch_name;
+  if (await branchExists(projectRoot, branchName)) {
+    throw new AppError('branch_exists', `Branch ${branchName} already exists in this repository`, {
+      branch: branchName,
+    });
+  }
+
+  const bundleFile = path.join(bundleDir, 'branch.bundle');
+  await exec('git', ['bundle', 'verify', bundleFile], { cwd: projectRoot });
+  await withRepoLock(projectRoot, () =>
+    exec('git', ['fetch', bundleFile, `refs/heads/${branchName}:refs/heads/${branchName}`], {
+      cwd: projectRoot,
+    }),
+  );
+  const worktree = await createWorktree(projectRoot, branchName, symlinkDirs);
+
+  const patchFile = path.join(bundleDir, 'uncommitted.patch');
+  if (fs.existsSync(patchFile)) {
+    await exec('git', ['apply', '--binary', patchFile], { cwd: worktree.path });
+  }
+
+  const id = randomUUID();
+  const eventsFile = path.join(bundleDir, 'events.jsonl');
+  if (fs.existsSync(eventsFile)) {
+    // Carry the exported history over under the new task id
+    appendTaskEvents(id, parseTaskEvents(fs.readFileSync(eventsFile, 'utf8')));
+  }
+  const transcriptsFile = path.join(bundleDir, 'transcripts.jsonl');
+  if (fs.existsSync(transcriptsFile)) {
+    for (const t of parseTranscripts(fs.readFileSync(transcriptsFile, 'utf8'))) {
+      storeTranscript(id, t.agent_id, t.lines);
+    }
+  }
+  recordTaskEvent(id, 'imported', { bundle: bundleDir, branch_name: branchName });
+
+  return {
+    id,
+    branch_name: worktree.branch,
+    worktree_path: worktree.path,
+    metadata: manifest.metadata,
+  };
+}
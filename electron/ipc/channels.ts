@@ -2,36 +2,162 @@ export enum IPC {
   // Agent/PTY
   SpawnAgent = 'spawn_agent',
   WriteToAgent = 'write_to_agent',
+  SendToAgent = 'send_to_agent',
+  SendToTaskAgents = 'send_to_task_agents',
+  SendToProjectAgents = 'send_to_project_agents',
   ResizeAgent = 'resize_agent',
   PauseAgent = 'pause_agent',
   ResumeAgent = 'resume_agent',
   KillAgent = 'kill_agent',
   CountRunningAgents = 'count_running_agents',
   KillAllAgents = 'kill_all_agents',
+  KillProjectAgents = 'kill_project_agents',
+  ListOrphanedSessions = 'list_orphaned_sessions',
+  ResolveOrphanedSessions = 'resolve_orphaned_sessions',
+  AgentRestarted = 'agent_restarted',
   ListAgents = 'list_agents',
   ListClaudeCommands = 'list_claude_commands',
+  GetTaskUsage = 'get_task_usage',
+  GetTaskTimes = 'get_task_times',
+  SetTaskTimer = 'set_task_timer',
+  SearchSessionBuffer = 'search_session_buffer',
+  GetSessionInfo = 'get_session_info',
+  ListSessions = 'list_sessions',
+  SessionInfoChanged = 'session_info_changed',
+  AgentAwaitingApproval = 'agent_awaiting_approval',
+  RespondToAgent = 'respond_to_agent',
+  AgentIdle = 'agent_idle',
+  AgentHung = 'agent_hung',
+  AgentEvent = 'agent_event',
+  GetAgentEvents = 'get_agent_events',
+  GetAgentResumeArgs = 'get_agent_resume_args',
+  GetHandoffContext = 'get_handoff_context',
+
+  // Scheduled runs
+  EnqueueAgentRun = 'enqueue_agent_run',
+  ListScheduledRuns = 'list_scheduled_runs',
+  CancelScheduledRun = 'cancel_scheduled_run',
+  ScheduledRunChanged = 'scheduled_run_changed',
+
+  // Merge queue
+  EnqueueMerge = 'enqueue_merge',
+  ListMergeQueue = 'list_merge_queue',
+  RemoveFromMergeQueue = 'remove_from_merge_queue',
+  ResumeMergeQueue = 'resume_merge_queue',
+  MergeQueueChanged = 'merge_queue_changed',
+
+  // Secrets
+  ListSecrets = 'list_secrets',
+  SetSecret = 'set_secret',
+  HasSecret = 'has_secret',
+  SetSecretInjected = 'set_secret_injected',
+  DeleteSecret = 'delete_secret',
+
+  // App data
+  ExportAppData = 'export_app_data',
+  ImportAppData = 'import_app_data',
 
   // Task
   CreateTask = 'create_task',
+  CreateTaskFromBranch = 'create_task_from_branch',
   DeleteTask = 'delete_task',
+  CheckBranchDeletion = 'check_branch_deletion',
+  CreateRace = 'create_race',
+  ArchiveTask = 'archive_task',
+  RestoreTask = 'restore_task',
+  RenameTask = 'rename_task',
+  RecordTaskEvent = 'record_task_event',
+  GetTaskEvents = 'get_task_events',
+  QueryHistory = 'query_history',
+  SearchTranscripts = 'search_transcripts',
+  GetMetrics = 'get_metrics',
+  ExportTask = 'export_task',
+  ImportTask = 'import_task',
+  ListRecordings = 'list_recordings',
+  ExportRecording = 'export_recording',
+  ListTrash = 'list_trash',
+  RestoreDeletedTask = 'restore_deleted_task',
+  GetDiskUsage = 'get_disk_usage',
+  GcWorkspace = 'gc_workspace',
+  GetResourceUsage = 'get_resource_usage',
+  ShareNodeModules = 'share_node_modules',
+  GetPnpmStoreDir = 'get_pnpm_store_dir',
 
   // Git
   GetChangedFiles = 'get_changed_files',
   GetChangedFilesFromBranch = 'get_changed_files_from_branch',
   GetFileDiff = 'get_file_diff',
   GetFileDiffFromBranch = 'get_file_diff_from_branch',
+  GetStructuredFileDiff = 'get_structured_file_diff',
+  StartDiff = 'start_diff',
+  ReadDiffChunks = 'read_diff_chunks',
+  LoadMoreDiff = 'load_more_diff',
+  CancelDiff = 'cancel_diff',
+  DiffChunk = 'diff_chunk',
+  RevertFiles = 'revert_files',
+  RevertHunks = 'revert_hunks',
+  DiffTaskBranches = 'diff_task_branches',
+  GetFileDiffBetweenBranches = 'get_file_diff_between_branches',
+  CherryPick = 'cherry_pick',
+  StashTask = 'stash_task',
+  StashPopTask = 'stash_pop_task',
+  ListStashes = 'list_stashes',
+  CreateSnapshot = 'create_snapshot',
+  ListSnapshots = 'list_snapshots',
+  RestoreSnapshot = 'restore_snapshot',
+  FetchProject = 'fetch_project',
+  UpdateBaseBranch = 'update_base_branch',
+  WidenSparseCheckout = 'widen_sparse_checkout',
+  GitProgress = 'git_progress',
+  CancelOperation = 'cancel_operation',
+  SetWatchedTasks = 'set_watched_tasks',
+  RefreshTaskGitStatus = 'refresh_task_git_status',
+  TaskGitStatusChanged = 'task_git_status_changed',
+  WatchWorktree = 'watch_worktree',
+  UnwatchWorktree = 'unwatch_worktree',
+  WorktreeFilesChanged = 'worktree_files_changed',
+
+  // Logs
+  GetRecentLogs = 'get_recent_logs',
+
+  // Notifications
+  ListNotifications = 'list_notifications',
+  MarkNotificationsRead = 'mark_notifications_read',
+  ClearNotifications = 'clear_notifications',
+  Notify = 'notify',
+  NotificationAdded = 'notification_added',
   GetGitignoredDirs = 'get_gitignored_dirs',
   GetWorktreeStatus = 'get_worktree_status',
   CheckMergeStatus = 'check_merge_status',
   MergeTask = 'merge_task',
   GetBranchLog = 'get_branch_log',
+  GetBranchTracking = 'get_branch_tracking',
+  GetTaskLog = 'get_task_log',
+  GetBlame = 'get_blame',
   PushTask = 'push_task',
   RebaseTask = 'rebase_task',
+  GetConflicts = 'get_conflicts',
+  ResolveConflict = 'resolve_conflict',
+  ContinueMerge = 'continue_merge',
+  AbortMerge = 'abort_merge',
+  FindMergedBranches = 'find_merged_branches',
+  GetBranchTip = 'get_branch_tip',
   GetMainBranch = 'get_main_branch',
   GetCurrentBranch = 'get_current_branch',
   CommitAll = 'commit_all',
   DiscardUncommitted = 'discard_uncommitted',
 
+  // Projects
+  ListRecentProjects = 'list_recent_projects',
+  OpenProject = 'open_project',
+  GetProjectInfo = 'get_project_info',
+  ReadProjectConfig = 'read_project_config',
+
+  // Settings
+  GetSettings = 'get_settings',
+  UpdateSettings = 'update_settings',
+  SettingsChanged = 'settings_changed',
+
   // Persistence
   SaveAppState = 'save_app_state',
   LoadAppState = 'load_app_state',
@@ -63,8 +189,28 @@ export enum IPC {
   // Shell
   ShellReveal = '__shell_reveal',
   ShellOpenFile = '__shell_open_file',
-  SaveClipboardImage = 'save_clipboard_image',
-  ShellOpenInEditor = '__shell_open_in_editor',
+  PasteImageToSession = 'paste_image_to_session',
+  ReadClipboardImage = 'read_clipboard_image',
+  AttachFilesToSession = 'attach_files_to_session',
+  OpenInEditor = 'open_in_editor',
+
+  // Checks
+  RunTaskTests = 'run_task_tests',
+  CancelTaskTests = 'cancel_task_tests',
+  TaskTestOutput = 'task_test_output',
+  RunTaskChecks = 'run_task_checks',
+  GetCheckResults = 'get_check_results',
+  CheckStatusChanged = 'check_status_changed',
+
+  // Ports
+  GetTaskPorts = 'get_task_ports',
+
+  // Remotes (GitHub, GitLab, Bitbucket)
+  FetchIssue = 'fetch_issue',
+  CreatePullRequest = 'create_pull_request',
+  SetWatchedPrTasks = 'set_watched_pr_tasks',
+  RefreshTaskPrStatus = 'refresh_task_pr_status',
+  TaskPrStatusChanged = 'task_pr_status_changed',
 
   // Arena
   SaveArenaData = 'save_arena_data',
@@ -78,6 +224,16 @@ export enum IPC {
   StopRemoteServer = 'stop_remote_server',
   GetRemoteStatus = 'get_remote_status',
 
+  // Automation API
+  AutomationRequest = 'automation_request',
+  AutomationResponse = 'automation_response',
+  GetAutomationApiInfo = 'get_automation_api_info',
+  RegenerateAutomationToken = 'regenerate_automation_token',
+
+  // Deep links
+  DeepLinkReceived = 'deep_link_received',
+  TakePendingDeepLinks = 'take_pending_deep_links',
+
   // Plan
   PlanContent = 'plan_content',
 }
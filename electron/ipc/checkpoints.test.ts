@@ -0,0 +1,23 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { checkpointMessage } from './checkpoints.js';
+
+describe('checkpointMessage', () => {
+  it('names the agent and lists the changed files', () => {
+    expect(checkpointMessage('claude-code', ['src/a.ts'])).toBe(
+      'Checkpoint after claude-code turn (1 file)\n\n- src/a.ts\n',
+    );
+  });
+
+  it('lists at most 20 files', () => {
+    const files = Array.from({ length: 25 }, (_, i) => `f${i}.ts`);
+    const lines = checkpointMessage('codex', files).trimEnd().split('\n');
+    expect(lines[0]).toBe('Checkpoint after codex turn (25 files)');
+    expect(lines).toHaveLength(2 + 21);
+    expect(lines.at(-1)).toBe('- … and 5 more');
+  });
+});
@@ -0,0 +1,51 @@
+import { commitCheckpoint } from './git.js';
+import { getSettings } from './settings.js';
+import { recordTaskEvent } from './task-events.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('checkpoints');
+
+/** Files named in a checkpoint's message body; the commit itself has all of them. */
+const MAX_LISTED_FILES = 20;
+
+export type CheckpointTrigger = 'quiet' | 'run_finished';
+
+/** Commit message for a checkpoint of `files` after a turn of `agent`. */
+export function checkpointMessage(agent: string, files: string[]): string {
+  const noun = files.length === 1 ? 'file' : 'files';
+  const listed = files.slice(0, MAX_LISTED_FILES).map((f) => `- ${f}`);
+  const unlisted = files.length - listed.length;
+  if (unlisted > 0) listed.push(`- … and ${unlisted} more`);
+  return `Checkpoint after ${agent} turn (${files.length} ${noun})\n\n${listed.join('\n')}\n`;
+}
+
+const inFlight = new Set<string>();
+
+/**
+ * Commit the task's worktree after one of its agents finished a turn, when
+ * `auto_checkpoint` is on. Recorded as a `checkpoint` task event; failures are
+ * logged, never thrown, since the agent's work is still in the worktree.
+ */
+export async function checkpointAfterTurn(
+  taskId: string,
+  worktreePath: string,
+  agent: string,
+  trigger: CheckpointTrigger,
+): Promise<void> {
+  if (!getSettings().auto_checkpoint || inFlight.has(worktreePath)) return;
+  inFlight.add(worktreePath);
+  try {
+    const result = await commitCheckpoint(worktreePath, (files) => checkpointMessage(agent, files));
+    if (!result) return;
+    recordTaskEvent(taskId, 'checkpoint', {
+      commit: result.commit,
+      files: result.files.length,
+      agent,
+      trigger,
+    });
+  } catch (err) {
+    log.warn(`Checkpoint of ${worktreePath} failed`, err);
+  } finally {
+    inFlight.delete(worktreePath);
+  }
+}
@@ -0,0 +1,95 @@
+import { describe, expect, it, vi } from 'vitest';
+
+const head = vi.hoisted(() => ({ sha: 'aaa111' }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./git.js', () => ({
+  getHeadCommit: async () => head.sha,
+}));
+
+import { assertChecksPassed, parseTestCounts, runTaskChecks, runTaskTests } from './checks.js';
+
+describe('parseTestCounts', () => {
+  it('sums cargo results across test binaries', () => {
+    const output = [
+      'test result: ok. 12 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out',
+      'test result: FAILED. 3 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out',
+    ].join('\n');
+    expect(parseTestCounts(output)).toEqual({ passed: 15, failed: 2 });
+  });
+
+  it('reads the vitest and jest summary lines', () => {
+    const vitest = [
+      ' Test Files  1 failed | 4 passed (5)',
+      '      Tests  \x1b[31m3 failed\x1b[39m | 40 passed (43)',
+    ].join('\n');
+    expect(parseTestCounts(vitest)).toEqual({ passed: 40, failed: 3 });
+    expect(parseTestCounts('Tests:       1 failed, 12 passed, 13 total')).toEqual({
+      passed: 12,
+      failed: 1,
+    });
+  });
+
+  it('reads pytest and mocha summaries', () => {
+    expect(parseTestCounts('==== 2 failed, 10 passed in 1.23s ====')).toEqual({
+      passed: 10,
+      failed: 2,
+    });
+    expect(parseTestCounts('  7 passing (20ms)\n')).toEqual({ passed: 7, failed: 0 });
+  });
+
+  it('returns null for unrecognized output', () => {
+    expect(parseTestCounts('Build succeeded')).toBeNull();
+  });
+});
+
+describe('runTaskTests', () => {
+  it('streams output and reports the exit code and counts', async () => {
+    const chunks: string[] = [];
+    const result = await runTaskTests(
+      { taskId: 'task-1', worktreePath: process.cwd(), command: 'echo "5 passing"; exit 1' },
+      (chunk) => chunks.push(chunk),
+    );
+    expect(chunks.join('')).toContain('5 passing');
+    expect(result).toMatchObject({ exit_code: 1, passed: 5, failed: 0 });
+  });
+});
+
+describe('runTaskChecks', () => {
+  const worktreePath = process.cwd();
+  const noop = () => {};
+
+  it('stops at the first failing check and skips the rest', async () => {
+    const statuses: string[] = [];
+    const run = await runTaskChecks(
+      { taskId: 'task-2', worktreePath, commands: ['true', 'false', 'echo never'] },
+      (_id, index, result) => statuses.push(`${index}:${result.status}`),
+      noop,
+    );
+    expect(run.results.map((r) => r.status)).toEqual(['passed', 'failed', 'skipped']);
+    expect(statuses).toEqual(['0:running', '0:passed', '1:running', '1:failed', '2:skipped']);
+    const commands = run.results.map((r) => r.command);
+    await expect(assertChecksPassed('task-2', worktreePath, commands)).rejects.toThrow(
+      'Pre-merge check failed: false',
+    );
+  });
+
+  it('gates merges on the checks having passed at the current HEAD', async () => {
+    await expect(assertChecksPassed('task-3', worktreePath, ['true'])).rejects.toThrow(
+      'Run the pre-merge checks',
+    );
+    await runTaskChecks({ taskId: 'task-3', worktreePath, commands: ['true'] }, noop, noop);
+    await expect(assertChecksPassed('task-3', worktreePath, ['true'])).resolves.toBeUndefined();
+    await expect(assertChecksPassed('task-3', worktreePath, ['true', 'true'])).rejects.toThrow(
+      'changed since they last ran',
+    );
+
+    head.sha = 'bbb222';
+    await expect(assertChecksPassed('task-3', worktreePath, ['true'])).rejects.toThrow(
+      'new commits',
+    );
+  });
+});
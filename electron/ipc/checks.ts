@@ -0,0 +1,262 @@
+import { spawn, type ChildProcess } from 'child_process';
+import { stripAnsi } from './ansi.js';
+import { AppError } from './errors.js';
+import { getHeadCommit } from './git.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('checks');
+
+/** Output kept on the result for display; the full stream goes to the renderer live. */
+const OUTPUT_TAIL_CHARS = 16 * 1024;
+
+export interface TestCounts {
+  passed: number;
+  failed: number;
+}
+
+export interface TestRunResult {
+  task_id: string;
+  command: string;
+  exit_code: number | null;
+  /** Signal name when the run was cancelled or killed. */
+  signal: string | null;
+  /** Parsed from the runner's summary; null when the output wasn't recognized. */
+  passed: number | null;
+  failed: number | null;
+  started_at: number;
+  finished_at: number;
+  output_tail: string;
+}
+
+const running = new Map<string, ChildProcess>();
+
+function lastCount(text: string, re: RegExp): number | null {
+  let value: number | null = null;
+  for (const m of text.matchAll(re)) value = parseInt(m[1], 10);
+  return value;
+}
+
+/**
+ * Pass/fail counts from common runner summaries: cargo (summed across test
+ * binaries), vitest, jest, pytest and mocha. Returns null when none match.
+ */
+export function parseTestCounts(output: string): TestCounts | null {
+  const text = stripAnsi(output);
+
+  // cargo prints one "test result:" line per test binary
+  const cargo = [...text.matchAll(/test result: \w+\. (\d+) passed; (\d+) failed/g)];
+  if (cargo.length > 0) {
+    return cargo.reduce(
+      (acc, m) => ({
+        passed: acc.passed + parseInt(m[1], 10),
+        failed: acc.failed + parseInt(m[2], 10),
+      }),
+      { passed: 0, failed: 0 },
+    );
+  }
+
+  // vitest "Tests  3 failed | 12 passed (15)", jest "Tests: 1 failed, 12 passed, 13 total"
+  const summary = text.match(/^\s*Tests:?\s+(.*)$/m)?.[1] ?? text;
+  const passed = lastCount(summary, /(\d+) (?:passed|passing)\b/g);
+  const failed = lastCount(summary, /(\d+) (?:failed|failing)\b/g);
+  if (passed === null && failed === null) return null;
+  return { passed: passed ?? 0, failed: failed ?? 0 };
+}
+
+interface CommandOutcome {
+  exit_code: number | null;
+  signal: string | null;
+  output: string;
+}
+
+/** Run `command` through the user's shell. One command runs per task at a time. */
+function runCommand(
+  taskId: string,
+  worktreePath: string,
+  command: string,
+  onOutput: (chunk: string) => void,
+): Promise<CommandOutcome> {
+  if (running.has(taskId)) throw new Error('A check is already running for this task');
+
+  let output = '';
+  const child = spawn(command, {
+    cwd: worktreePath,
+    shell: true,
+    // Own process group so cancelling also stops the runner's children
+    detached: true,
+    stdio: ['ignore', 'pipe', 'pipe'],
+  });
+  running.set(taskId, child);
+
+  const onData = (data: Buffer) => {
+    const chunk = data.toString('utf8');
+    output = (output + chunk).slice(-OUTPUT_TAIL_CHARS * 4);
+    onOutput(chunk);
+  };
+  child.stdout?.on('data', onData);
+  child.stderr?.on('data', onData);
+
+  return new Promise<CommandOutcome>((resolve, reject) => {
+    child.on('error', (err) => {
+      running.delete(taskId);
+      reject(new Error(`Failed to run "${command}": ${err.message}`));
+    });
+    child.on('close', (code, signal) => {
+      running.delete(taskId);
+      resolve({ exit_code: code, signal, output });
+    });
+  });
+}
+
+/**
+ * Run the project's test command in a task worktree. Output chunks are passed
+ * to `onOutput` as they arrive.
+ */
+export async function runTaskTests(
+  opts: { taskId: string; worktreePath: string; command: string },
+  onOutput: (chunk: string) => void,
+): Promise<TestRunResult> {
+  const command = opts.command.trim();
+  if (!command) throw new Error('No test command configured for this project');
+
+  const startedAt = Date.now();
+  const outcome = await runCommand(opts.taskId, opts.worktreePath, command, onOutput);
+  const counts = parseTestCounts(outcome.output);
+  return {
+    task_id: opts.taskId,
+    command,
+    exit_code: outcome.exit_code,
+    signal: outcome.signal,
+    passed: counts?.passed ?? null,
+    failed: counts?.failed ?? null,
+    started_at: startedAt,
+    finished_at: Date.now(),
+    output_tail: outcome.output.slice(-OUTPUT_TAIL_CHARS),
+  };
+}
+
+// --- Pre-merge check pipeline ---
+
+export type CheckStatus = 'pending' | 'running' | 'passed' | 'failed' | 'skipped';
+
+export interface CheckResult {
+  command: string;
+  status: CheckStatus;
+  exit_code: number | null;
+  started_at: number | null;
+  finished_at: number | null;
+  output_tail: string;
+}
+
+export interface TaskCheckResults {
+  task_id: string;
+  /** Commit the checks ran against; a later commit invalidates them. */
+  head: string;
+  finished: boolean;
+  results: CheckResult[];
+}
+
+const checkResults = new Map<string, TaskCheckResults>();
+
+export function getCheckResults(taskId: string): TaskCheckResults | null {
+  return checkResults.get(taskId) ?? null;
+}
+
+/**
+ * Run `commands` in order in the task worktree, stopping at the first failure
+ * (later checks are marked skipped). `onStatus` gets every status change.
+ */
+export async function runTaskChecks(
+  opts: { taskId: string; worktreePath: string; commands: string[] },
+  onStatus: (taskId: string, index: number, result: CheckResult) => void,
+  onOutput: (chunk: string) => void,
+): Promise<TaskCheckResults> {
+  if (running.has(opts.taskId)) throw new Error('A check is already running for this task');
+  const run: TaskCheckResults = {
+    task_id: opts.taskId,
+    head: await getHeadCommit(opts.worktreePath),
+    finished: false,
+    results: opts.commands.map((command) => ({
+      command,
+      status: 'pending',
+      exit_code: null,
+      started_at: null,
+      finished_at: null,
+      output_tail: '',
+    })),
+  };
+  checkResults.set(opts.taskId, run);
+
+  let failed = false;
+  for (const [index, result] of run.results.entries()) {
+    if (failed) {
+      result.status = 'skipped';
+      onStatus(opts.taskId, index, result);
+      continue;
+    }
+    result.status = 'running';
+    result.started_at = Date.now();
+    onStatus(opts.taskId, index, result);
+    try {
+      const outcome = await runCommand(opts.taskId, opts.worktreePath, result.command, onOutput);
+      result.exit_code = outcome.exit_code;
+      result.output_tail = outcome.output.slice(-OUTPUT_TAIL_CHARS);
+      result.status = outcome.exit_code === 0 ? 'passed' : 'failed';
+    } catch (err) {
+      result.output_tail = err instanceof Error ? err.message : String(err);
+      result.status = 'failed';
+    }
+    result.finished_at = Date.now();
+    failed = result.status === 'failed';
+    onStatus(opts.taskId, index, result);
+  }
+  run.finished = true;
+  return run;
+}
+
+/**
+ * Refuse to merge unless `commands` all passed, in the task worktree, against
+ * its current HEAD.
+ */
+export async function assertChecksPassed(
+  taskId: string,
+  worktreePath: string,
+  commands: string[],
+): Promise<void> {
+  const run = checkResults.get(taskId);
+  const fail = (message: string): never => {
+    throw new AppError('checks_failed', message, { path: worktreePath });
+  };
+  if (!run || !run.finished) return fail('Run the pre-merge checks before merging');
+  const ran = run.results.map((r) => r.command);
+  if (ran.length !== commands.length || ran.some((c, i) => c !== commands[i])) {
+    return fail('Pre-merge checks changed since they last ran; run them again');
+  }
+  const failing = run.results.find((r) => r.status !== 'passed');
+  if (failing) return fail(`Pre-merge check failed: ${failing.command}`);
+  if ((await getHeadCommit(worktreePath)) !== run.head) {
+    return fail('The task has new commits since the checks ran; run them again');
+  }
+}
+
+function killTree(child: ChildProcess): void {
+  try {
+    if (child.pid !== undefined) process.kill(-child.pid);
+    else child.kill();
+  } catch (err) {
+    log.warn('Failed to stop test run:', err);
+  }
+}
+
+/** Stop a task's running test or check command. Returns false when none was running. */
+export function cancelTaskTests(taskId: string): boolean {
+  const child = running.get(taskId);
+  if (!child) return false;
+  killTree(child);
+  return true;
+}
+
+export function stopAllTaskTests(): void {
+  for (const child of running.values()) killTree(child);
+  running.clear();
+}
@@ -0,0 +1,39 @@
+import { describe, expect, it } from 'vitest';
+import { parseConflictRegions } from './conflicts.js';
+
+describe('parseConflictRegions', () => {
+  it('splits each region into ours and theirs', () => {
+    const content = [
+      'intro',
+      '<<<<<<< HEAD',
+      'const a = 1;',
+      '=======',
+      'const a = 2;',
+      'const b = 3;',
+      '>>>>>>> abc123 (Change a)',
+      'outro',
+      '',
+    ].join('\n');
+    expect(parseConflictRegions(content)).toEqual([
+      {
+        start_line: 2,
+        end_line: 7,
+        ours: 'const a = 1;',
+        base: null,
+        theirs: 'const a = 2;\nconst b = 3;',
+      },
+    ]);
+  });
+
+  it('keeps the base of diff3-style regions, even when empty', () => {
+    const content = '<<<<<<< ours\nx\n||||||| base\n=======\ny\n>>>>>>> theirs\n';
+    expect(parseConflictRegions(content)).toEqual([
+      { start_line: 1, end_line: 6, ours: 'x', base: '', theirs: 'y' },
+    ]);
+  });
+
+  it('ignores marker-like lines outside regions', () => {
+    const content = 'Title\n=======\n\n<<<<<<<<< not a marker\n';
+    expect(parseConflictRegions(content)).toEqual([]);
+  });
+});
@@ -0,0 +1,60 @@
+/** Conflict markers git leaves in a file it couldn't merge. */
+
+export interface ConflictRegion {
+  /** 1-based line of the `<<<<<<<` marker. */
+  start_line: number;
+  /** 1-based line of the `>>>>>>>` marker. */
+  end_line: number;
+  ours: string;
+  /** The common ancestor's lines, when the file was written with `merge.conflictStyle=diff3`. */
+  base: string | null;
+  theirs: string;
+}
+
+type Section = 'ours' | 'base' | 'theirs';
+
+function isMarker(line: string, char: string): boolean {
+  if (!line.startsWith(char.repeat(7))) return false;
+  return line.length === 7 || line[7] === ' ' || line[7] === '\r';
+}
+
+/**
+ * Conflict regions in a file's working-tree content. Marker lines must be
+ * exactly seven characters followed by a space or the end of the line, and
+ * only count inside a region, so an ordinary `=======` underline is left alone.
+ */
+export function parseConflictRegions(content: string): ConflictRegion[] {
+  const regions: ConflictRegion[] = [];
+  let start = 0;
+  let section: Section | null = null;
+  let parts: Record<Section, string[]> = { ours: [], base: [], theirs: [] };
+  let hasBase = false;
+
+  for (const [i, line] of content.split('\n').entries()) {
+    if (section === null) {
+      if (isMarker(line, '<')) {
+        start = i + 1;
+        section = 'ours';
+        parts = { ours: [], base: [], theirs: [] };
+        hasBase = false;
+      }
+    } else if (section === 'ours' && isMarker(line, '|')) {
+      section = 'base';
+      hasBase = true;
+    } else if (section !== 'theirs' && isMarker(line, '=')) {
+      section = 'theirs';
+    } else if (section === 'theirs' && isMarker(line, '>')) {
+      regions.push({
+        start_line: start,
+        end_line: i + 1,
+        ours: parts.ours.join('\n'),
+        base: hasBase ? parts.base.join('\n') : null,
+        theirs: parts.theirs.join('\n'),
+      });
+      section = null;
+    } else {
+      parts[section].push(line);
+    }
+  }
+  return regions;
+}
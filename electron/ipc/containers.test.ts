@@ -0,0 +1,123 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import {
+  containerName,
+  containerRunArgs,
+  detectContainer,
+  parseJsonc,
+  wrapInContainer,
+  type ContainerSession,
+} from './containers.js';
+
+const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'containers-'));
+
+function worktree(name: string, files: Record<string, string>): string {
+  const root = path.join(dir, name);
+  for (const [rel, content] of Object.entries(files)) {
+    fs.mkdirSync(path.dirname(path.join(root, rel)), { recursive: true });
+    fs.writeFileSync(path.join(root, rel), content);
+  }
+  return root;
+}
+
+afterAll(() => {
+  fs.rmSync(dir, { recursive: true, force: true });
+});
+
+describe('parseJsonc', () => {
+  it('ignores comments and trailing commas but not slashes in strings', () => {
+    const text = '{\n  // the image\n  "image": "node:20", /* pinned */\n  "url": "a//b",\n}';
+    expect(parseJsonc(text)).toEqual({ image: 'node:20', url: 'a//b' });
+  });
+});
+
+describe('detectContainer', () => {
+  it('uses the image of a devcontainer config', () => {
+    const root = worktree('image', {
+      '.devcontainer.json': '{ "image": "node:20", "runArgs": ["--init"] }',
+    });
+    expect(detectContainer(root)).toEqual({ image: 'node:20', runArgs: ['--init'] });
+  });
+
+  it('keeps only runArgs that stay inside the container', () => {
+    const runArgs = [
+      '--init',
+      '--privileged',
+      '-v',
+      '/:/host',
+      '--memory=2g',
+      '--cap-add',
+      'SYS_ADMIN',
+      '--network=host',
+      '--shm-size',
+      '1g',
+      '-p',
+      '3000:3000',
+      '--cpus',
+    ];
+    const root = worktree('run-args', {
+      '.devcontainer.json': JSON.stringify({ image: 'node:20', runArgs }),
+    });
+    expect(detectContainer(root)?.runArgs).toEqual(['--init', '--memory=2g', '--shm-size', '1g']);
+  });
+
+  it('builds the Dockerfile a devcontainer config points at, relative to it', () => {
+    const root = worktree('build', {
+      '.devcontainer/devcontainer.json': '{"build":{"dockerfile":"Dockerfile","context":".."}}',
+      '.devcontainer/Dockerfile': 'FROM node:20\n',
+    });
+    const config = detectContainer(root);
+    expect(config?.dockerfile).toBe(path.join(root, '.devcontainer', 'Dockerfile'));
+    expect(config?.context).toBe(root);
+    expect(config?.image).toMatch(/^parallel-code-[0-9a-f]{12}$/);
+  });
+
+  it('falls back to a top-level Dockerfile, and to nothing', () => {
+    const root = worktree('dockerfile', { Dockerfile: 'FROM alpine\n' });
+    expect(detectContainer(root)?.context).toBe(root);
+    expect(detectContainer(worktree('plain', { 'README.md': '' }))).toBeNull();
+  });
+});
+
+describe('containerRunArgs', () => {
+  const session: ContainerSession = {
+    runtime: 'docker',
+    name: containerName('agent 1', 42),
+    worktreeRoot: '/nonexistent/wt',
+    cwd: '/nonexistent/wt/packages/api',
+    env: { PORT: '20000' },
+    ports: { task_id: 't', base: 20000, count: 10 },
+  };
+
+  it('mounts the worktree at its own path and publishes the task ports', () => {
+    const args = containerRunArgs({ image: 'node:20', runArgs: [] }, session, 'claude', ['-c']);
+    expect(session.name).toBe('parallel-agent-1-42');
+    expect(args.join(' ')).toContain('-v /nonexistent/wt:/nonexistent/wt');
+    expect(args.join(' ')).toContain('-w /nonexistent/wt/packages/api');
+    expect(args.join(' ')).toContain('-e PORT');
+    expect(args).not.toContain('PORT=20000');
+    expect(args.join(' ')).toContain('-p 127.0.0.1:20000-20009:20000-20009');
+    expect(args.slice(-3)).toEqual(['node:20', 'claude', '-c']);
+  });
+
+  it('starts sh for plain terminals', () => {
+    const args = containerRunArgs({ image: 'node:20', runArgs: [] }, session, null, []);
+    expect(args.slice(-2)).toEqual(['node:20', 'sh']);
+  });
+
+  it('builds a Dockerfile image before running it', () => {
+    const config = { image: 'img', dockerfile: '/wt/Dockerfile', context: '/wt', runArgs: [] };
+    const wrapped = wrapInContainer(config, session, 'claude', []);
+    expect(wrapped.command).toBe('/bin/sh');
+    const [build, run] = wrapped.args[1].split(' && exec ');
+    expect(build).toBe('docker build -t img -f /wt/Dockerfile /wt');
+    expect(run).toMatch(/^docker run --rm -it /);
+  });
+});
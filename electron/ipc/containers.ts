@@ -0,0 +1,217 @@
+import crypto from 'crypto';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { execFile } from 'child_process';
+import { gitCommonDir } from './sandbox.js';
+import { shellQuote } from './ssh.js';
+import type { TaskPorts } from './ports.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('containers');
+
+/** How a repo says its sessions should run: an image, or a Dockerfile to build one from. */
+export interface ContainerConfig {
+  /** Image to run; for a Dockerfile, the tag it is built as. */
+  image: string;
+  /** Dockerfile to build `image` from; absent for prebuilt images. */
+  dockerfile?: string;
+  /** Build context of `dockerfile`. */
+  context?: string;
+  /** Extra `run` arguments: what `allowedRunArgs` keeps of devcontainer.json's `runArgs`. */
+  runArgs: string[];
+}
+
+/**
+ * `run` flags a repo may add, and whether each takes a value. They only tune
+ * the container; ones that reach the host (mounts, devices, capabilities,
+ * host namespaces, published ports) are left out.
+ */
+const ALLOWED_RUN_ARGS: Record<string, boolean> = {
+  '--init': false,
+  '--read-only': false,
+  '--cpus': true,
+  '--memory': true,
+  '-m': true,
+  '--memory-swap': true,
+  '--shm-size': true,
+  '--pids-limit': true,
+  '--ulimit': true,
+  '--cap-drop': true,
+  '--env': true,
+  '-e': true,
+  '--label': true,
+  '-l': true,
+  '--hostname': true,
+  '--add-host': true,
+  '--dns': true,
+  '--tmpfs': true,
+  '--platform': true,
+};
+
+const DEVCONTAINER_FILES = [path.join('.devcontainer', 'devcontainer.json'), '.devcontainer.json'];
+
+/** devcontainer.json is JSON with comments and trailing commas. */
+export function parseJsonc(text: string): unknown {
+  const stripped = text
+    .replace(/("(?:\\.|[^"\\])*")|\/\/[^\n]*|\/\*[\s\S]*?\*\//g, (_m, str?: string) => str ?? '')
+    .replace(/,(\s*[}\]])/g, '$1');
+  return JSON.parse(stripped);
+}
+
+/** Images built from a Dockerfile are shared by every worktree with the same one. */
+function builtImageTag(dockerfile: string): string {
+  const hash = crypto.createHash('sha256').update(fs.readFileSync(dockerfile)).digest('hex');
+  return `parallel-code-${hash.slice(0, 12)}`;
+}
+
+/** The allowed flags among a devcontainer's `runArgs`, with their values; the rest are dropped. */
+export function allowedRunArgs(runArgs: string[]): string[] {
+  const kept: string[] = [];
+  const dropped: string[] = [];
+  for (let i = 0; i < runArgs.length; i++) {
+    const arg = runArgs[i];
+    const inline = arg.includes('=');
+    const takesValue = ALLOWED_RUN_ARGS[arg.split('=', 1)[0]];
+    // A separate value goes with its flag, kept or not
+    const next = runArgs[i + 1];
+    const separate = !inline && takesValue !== false && next !== undefined && !next.startsWith('-');
+    const value = separate ? [runArgs[++i]] : [];
+    // Without its value, a flag would take the image name as one
+    const ok = takesValue !== undefined && (!takesValue || inline || separate);
+    (ok ? kept : dropped).push(arg, ...value);
+  }
+  if (dropped.length > 0) log.warn(`Ignoring devcontainer runArgs: ${dropped.join(' ')}`);
+  return kept;
+}
+
+function fromDevcontainer(file: string): ContainerConfig | null {
+  const raw = parseJsonc(fs.readFileSync(file, 'utf8'));
+  if (!raw || typeof raw !== 'object') return null;
+  const config = raw as Record<string, unknown>;
+  const dir = path.dirname(file);
+  const runArgs = allowedRunArgs(
+    Array.isArray(config.runArgs)
+      ? config.runArgs.filter((a): a is string => typeof a === 'string')
+      : [],
+  );
+  if (typeof config.image === 'string' && config.image) return { image: config.image, runArgs };
+
+  const build = (config.build ?? {}) as Record<string, unknown>;
+  const name = build.dockerfile ?? config.dockerFile;
+  // Docker Compose setups are not supported
+  if (typeof name !== 'string') return null;
+  const dockerfile = path.resolve(dir, name);
+  const context = path.resolve(dir, typeof build.context === 'string' ? build.context : '.');
+  return { image: builtImageTag(dockerfile), dockerfile, context, runArgs };
+}
+
+/**
+ * The container a worktree's sessions should run in, from its devcontainer
+ * config or else a top-level `Dockerfile`; null when it has neither.
+ */
+export function detectContainer(worktreeRoot: string): ContainerConfig | null {
+  for (const name of DEVCONTAINER_FILES) {
+    const file = path.join(worktreeRoot, name);
+    if (!fs.existsSync(file)) continue;
+    try {
+      return fromDevcontainer(file);
+    } catch (err) {
+      log.warn(`Could not read ${file}`, err);
+      return null;
+    }
+  }
+  const dockerfile = path.join(worktreeRoot, 'Dockerfile');
+  if (!fs.existsSync(dockerfile)) return null;
+  return { image: builtImageTag(dockerfile), dockerfile, context: worktreeRoot, runArgs: [] };
+}
+
+/** The worktree `cwd` is in: the nearest directory with a `.git`. */
+export function findWorktreeRoot(cwd: string): string {
+  for (let dir = cwd; ; dir = path.dirname(dir)) {
+    if (fs.existsSync(path.join(dir, '.git'))) return dir;
+    if (path.dirname(dir) === dir) return cwd;
+  }
+}
+
+export interface ContainerSession {
+  runtime: string;
+  /** Container name, so it can be removed if the runtime leaves it behind. */
+  name: string;
+  worktreeRoot: string;
+  cwd: string;
+  /** Passed in by name only: the runtime must be spawned with these values in its env. */
+  env: Record<string, string>;
+  ports: TaskPorts;
+}
+
+/**
+ * `run` arguments for a session: the worktree (and the repo's git dir, which
+ * its `.git` file points into) mounted at their host paths, so paths mean the
+ * same inside and out, and the task's ports published on the loopback
+ * interface only, like the servers it runs on the host. A null `command`
+ * starts `sh`, since the image's shells are unknown.
+ */
+export function containerRunArgs(
+  config: ContainerConfig,
+  session: ContainerSession,
+  command: string | null,
+  args: string[],
+): string[] {
+  const mounts = [session.worktreeRoot];
+  const commonDir = gitCommonDir(session.worktreeRoot);
+  if (commonDir && !commonDir.startsWith(session.worktreeRoot + path.sep)) mounts.push(commonDir);
+  const lastPort = session.ports.base + session.ports.count - 1;
+  // Files the session writes into the worktree should stay the user's
+  const user = process.platform === 'linux' ? os.userInfo() : null;
+  return [
+    'run',
+    '--rm',
+    '-it',
+    '--init',
+    '--name',
+    session.name,
+    ...mounts.flatMap((m) => ['-v', `${m}:${m}`]),
+    '-w',
+    session.cwd,
+    // Values come from the runtime's own environment, keeping secrets out of its argv
+    ...Object.keys(session.env).flatMap((k) => ['-e', k]),
+    '-p',
+    `127.0.0.1:${session.ports.base}-${lastPort}:${session.ports.base}-${lastPort}`,
+    ...(user ? ['--user', `${user.uid}:${user.gid}`] : []),
+    ...config.runArgs,
+    config.image,
+    ...(command === null ? ['sh'] : [command, ...args]),
+  ];
+}
+
+/**
+ * The command running a session in its container. A Dockerfile image is
+ * built first, in the session's own terminal so its output shows; the
+ * runtime's cache makes that quick after the first time.
+ */
+export function wrapInContainer(
+  config: ContainerConfig,
+  session: ContainerSession,
+  command: string | null,
+  args: string[],
+): { command: string; args: string[] } {
+  const run = [session.runtime, ...containerRunArgs(config, session, command, args)];
+  if (!config.dockerfile) return { command: session.runtime, args: run.slice(1) };
+  const build = [session.runtime, 'build', '-t', config.image, '-f', config.dockerfile];
+  build.push(config.context ?? session.worktreeRoot);
+  const script = `${build.map(shellQuote).join(' ')} && exec ${run.map(shellQuote).join(' ')}`;
+  return { command: '/bin/sh', args: ['-c', script] };
+}
+
+/** A container name unique to one spawn of an agent. */
+export function containerName(agentId: string, startedAt: number): string {
+  return `parallel-${agentId.replace(/[^A-Za-z0-9_.-]/g, '-')}-${startedAt}`;
+}
+
+/** Remove a session's container if the runtime didn't when its terminal closed. */
+export function removeContainer(runtime: string, name: string): void {
+  execFile(runtime, ['rm', '-f', name], (err) => {
+    if (err) log.debug(`Could not remove container ${name}`, err);
+  });
+}
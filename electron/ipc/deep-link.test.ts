@@ -0,0 +1,49 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { findDeepLinkArg, parseDeepLink } from './deep-link.js';
+
+describe('parseDeepLink', () => {
+  it('parses new task links', () => {
+    const url =
+      'parallel-code://task/new?name=Fix%20login&agent=claude-code' +
+      '&prompt=Fix+the+redirect&project=web';
+    expect(parseDeepLink(url)).toEqual({
+      action: 'new_task',
+      name: 'Fix login',
+      prompt: 'Fix the redirect',
+      agent: 'claude-code',
+      project: 'web',
+    });
+    expect(parseDeepLink('parallel-code://task/new/')).toEqual({
+      action: 'new_task',
+      name: undefined,
+      prompt: undefined,
+      agent: undefined,
+      project: undefined,
+    });
+  });
+
+  it('caps field lengths', () => {
+    const link = parseDeepLink(`parallel-code://task/new?name=${'x'.repeat(500)}`);
+    expect(link?.name).toHaveLength(200);
+  });
+
+  it('rejects other schemes and actions', () => {
+    expect(parseDeepLink('https://task/new?name=x')).toBeNull();
+    expect(parseDeepLink('parallel-code://task/delete?id=1')).toBeNull();
+    expect(parseDeepLink('not a url')).toBeNull();
+  });
+});
+
+describe('findDeepLinkArg', () => {
+  it('finds the link among launch args', () => {
+    expect(findDeepLinkArg(['/opt/app', '--no-sandbox', 'parallel-code://task/new'])).toBe(
+      'parallel-code://task/new',
+    );
+    expect(findDeepLinkArg(['/opt/app'])).toBeNull();
+  });
+});
@@ -0,0 +1,87 @@
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('deep-link');
+
+export const DEEP_LINK_SCHEME = 'parallel-code';
+
+const MAX_FIELD_CHARS = 200;
+const MAX_PROMPT_CHARS = 16 * 1024;
+
+/** `parallel-code://task/new?name=…&agent=…&prompt=…&project=…` */
+export interface NewTaskLink {
+  action: 'new_task';
+  name?: string;
+  prompt?: string;
+  /** Agent id, e.g. `claude-code`. */
+  agent?: string;
+  /** Project id, name or path. */
+  project?: string;
+}
+
+export type DeepLinkAction = NewTaskLink;
+
+/** Parse a `parallel-code://` URL. Returns null for other schemes and unknown actions. */
+export function parseDeepLink(raw: string): DeepLinkAction | null {
+  let url: URL;
+  try {
+    url = new URL(raw);
+  } catch {
+    return null;
+  }
+  if (url.protocol !== `${DEEP_LINK_SCHEME}:`) return null;
+
+  const route = `${url.host}${url.pathname}`.replace(/\/+$/, '');
+  if (route !== 'task/new') return null;
+
+  const param = (key: string, max = MAX_FIELD_CHARS) => {
+    const value = url.searchParams.get(key)?.trim();
+    return value ? value.slice(0, max) : undefined;
+  };
+  return {
+    action: 'new_task',
+    name: param('name'),
+    prompt: param('prompt', MAX_PROMPT_CHARS),
+    agent: param('agent'),
+    project: param('project'),
+  };
+}
+
+/** The deep link among command-line args (Linux passes it to the launched or second instance). */
+export function findDeepLinkArg(argv: string[]): string | null {
+  return argv.find((arg) => arg.startsWith(`${DEEP_LINK_SCHEME}://`)) ?? null;
+}
+
+let win: BrowserWindow | null = null;
+/** False until the renderer has loaded and collected queued links. */
+let rendererReady = false;
+const queued: DeepLinkAction[] = [];
+
+export function initDeepLinks(window: BrowserWindow): void {
+  win = window;
+  rendererReady = false;
+  // A reload drops the renderer's listener; queue until it asks again
+  window.webContents.on('did-start-loading', () => {
+    rendererReady = false;
+  });
+}
+
+export function handleDeepLink(url: string): void {
+  const action = parseDeepLink(url);
+  if (!action) {
+    log.warn(`Ignoring unsupported link: ${url.slice(0, MAX_FIELD_CHARS)}`);
+    return;
+  }
+  if (rendererReady && win && !win.isDestroyed()) {
+    win.webContents.send(IPC.DeepLinkReceived, action);
+  } else {
+    queued.push(action);
+  }
+}
+
+/** Links that arrived before the renderer was listening. Later links are pushed. */
+export function takePendingDeepLinks(): DeepLinkAction[] {
+  rendererReady = true;
+  return queued.splice(0);
+}
@@ -0,0 +1,90 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { createDiffChunker, type DiffChunkLimits, type DiffFilePart } from './diff-stream.js';
+
+const DIFF = [
+  'diff --git a/src/a.ts b/src/a.ts',
+  'index 1111111..2222222 100644',
+  '--- a/src/a.ts',
+  '+++ b/src/a.ts',
+  '@@ -1,2 +1,3 @@',
+  ' keep',
+  '-old',
+  '+new',
+  '+more',
+  '@@ -10 +11 @@',
+  '-x',
+  '\\ No newline at end of file',
+  '+y',
+  'diff --git "a/docs/\\303\\251t\\303\\251 notes.md" "b/docs/\\303\\251t\\303\\251 notes.md"',
+  'deleted file mode 100644',
+  '--- "a/docs/\\303\\251t\\303\\251 notes.md"',
+  '+++ /dev/null',
+  '@@ -1 +0,0 @@',
+  '-gone',
+  'diff --git a/logo.png b/logo.png',
+  'new file mode 100644',
+  'Binary files /dev/null and b/logo.png differ',
+  '',
+].join('\n');
+
+function run(limits: DiffChunkLimits, input: string[]): DiffFilePart[][] {
+  const chunks: DiffFilePart[][] = [];
+  const chunker = createDiffChunker(limits, (files) => chunks.push(files));
+  for (const data of input) chunker.push(data);
+  chunker.end();
+  return chunks;
+}
+
+const roomy = { fileBytes: 1000, totalBytes: 1000, chunkBytes: 1000 };
+
+describe('createDiffChunker', () => {
+  it('parses files as they arrive, split anywhere', () => {
+    const input = DIFF.match(/[\s\S]{1,7}/g) ?? [];
+    const [files] = run(roomy, input);
+    expect(files.map((f) => [f.path, f.binary, f.additions, f.deletions, f.more])).toEqual([
+      ['src/a.ts', false, 3, 2, null],
+      ['docs/été notes.md', false, 0, 1, null],
+      ['logo.png', true, 0, 0, null],
+    ]);
+    expect(files[0].hunks.map((h) => [h.id, h.old_start, h.new_lines, h.lines.length])).toEqual([
+      [0, 1, 3, 4],
+      [1, 10, 1, 2],
+    ]);
+  });
+
+  it('continues a hunk cut by a chunk under the same id', () => {
+    const chunks = run({ ...roomy, chunkBytes: 8 }, [DIFF]);
+    const parts = chunks.flat().filter((f) => f.path === 'src/a.ts');
+    expect(parts.length).toBeGreaterThan(1);
+    expect(parts.filter((p) => p.done)).toHaveLength(1);
+    const lines = parts.flatMap((p) => p.hunks.filter((h) => h.id === 0).flatMap((h) => h.lines));
+    expect(lines.map((l) => l.text)).toEqual(['keep', 'old', 'new', 'more']);
+  });
+
+  it('leaves the rest of a file out at its cap and pages on from there', () => {
+    const [files] = run({ ...roomy, fileBytes: 10 }, [DIFF]);
+    // " keep", "-old" fit; "+new" would pass 10
+    expect(files[0].hunks[0].lines.map((l) => l.text)).toEqual(['keep', 'old']);
+    expect(files[0].more).toBe(2);
+    expect(files[0].additions).toBe(3);
+
+    const [page] = run({ ...roomy, skipLines: 2 }, [DIFF]);
+    expect(page[0].hunks.map((h) => [h.id, h.lines.map((l) => l.text)])).toEqual([
+      [0, ['new', 'more']],
+      [1, ['x', 'y']],
+    ]);
+  });
+
+  it('sends files past the stream cap without hunks', () => {
+    // Just enough for src/a.ts
+    const [files] = run({ ...roomy, totalBytes: 22 }, [DIFF]);
+    expect(files[0].more).toBeNull();
+    expect(files[1].hunks).toEqual([]);
+    expect(files[1].more).toBe(0);
+  });
+});
@@ -0,0 +1,341 @@
+import { HUNK_HEADER_RE, type DiffHunk, type DiffLine } from './diff.js';
+import { getTaskDiffBase, streamGit } from './git.js';
+
+/**
+ * A task's diff handed out piece by piece, for changes too big to send in
+ * one response (regenerated lockfiles, vendored code). `startDiff` returns a
+ * handle at once and sends the diff as `DiffChunk`s while git produces it.
+ * Each file gets a share of text before the rest is left out; the part that
+ * ends it says where, and `loadMoreDiff` pages on from there.
+ */
+
+export interface DiffFilePart {
+  path: string;
+  binary: boolean;
+  /** A hunk the previous part cut off goes on here under the same id. */
+  hunks: DiffHunk[];
+  /** The file's last part, which carries its totals. */
+  done: boolean;
+  additions: number;
+  deletions: number;
+  /** With `done`: the diff line the cap stopped at, to load more from; null when all was sent. */
+  more: number | null;
+}
+
+export interface DiffChunk {
+  handle: string;
+  /** 0, 1, 2… in the order they were sent. */
+  seq: number;
+  files: DiffFilePart[];
+  /** The stream's last chunk; `error` says why when git failed. */
+  done: boolean;
+  error: string | null;
+}
+
+export interface DiffPage {
+  path: string;
+  hunks: DiffHunk[];
+  more: number | null;
+}
+
+export interface DiffChunkLimits {
+  /** Text sent per file before the rest is left out. */
+  fileBytes: number;
+  /** Text sent in the whole stream; files after it come without hunks. */
+  totalBytes: number;
+  /** Text per chunk. */
+  chunkBytes: number;
+  /** Leave out each file's first this many diff lines. */
+  skipLines?: number;
+}
+
+const STREAM_LIMITS: DiffChunkLimits = {
+  fileBytes: 256 * 1024,
+  totalBytes: 4 * 1024 * 1024,
+  chunkBytes: 128 * 1024,
+};
+const PAGE_BYTES = 1024 * 1024;
+/** Finished streams are kept for `loadMoreDiff` and polling; the oldest go first. */
+const MAX_STREAMS = 8;
+
+const LINE_TYPES: Record<string, DiffLine['type']> = { '+': 'add', '-': 'del', ' ': 'context' };
+const ESCAPES: Record<string, number> = { a: 7, b: 8, t: 9, n: 10, v: 11, f: 12, r: 13 };
+
+/** A path as git prints it, undoing the C-style quoting of unusual names. */
+function unquote(s: string): string {
+  if (!s.startsWith('"') || !s.endsWith('"')) return s;
+  const bytes: number[] = [];
+  for (let i = 1; i < s.length - 1; i++) {
+    const c = s[i];
+    if (c !== '\\') {
+      bytes.push(...Buffer.from(c));
+      continue;
+    }
+    const next = s[++i];
+    const octal = /^[0-7]{3}/.exec(s.slice(i, i + 3));
+    if (octal) {
+      bytes.push(parseInt(octal[0], 8));
+      i += 2;
+    } else {
+      bytes.push(ESCAPES[next] ?? next.charCodeAt(0));
+    }
+  }
+  return Buffer.from(bytes).toString('utf8');
+}
+
+/** The path in `diff --git a/<p> b/<p>`; without renames both sides name the same file. */
+function pathFromHeader(line: string): string {
+  const rest = line.slice('diff --git '.length);
+  const quoted = /^"(?:[^"\\]|\\.)*"/.exec(rest);
+  if (quoted) return unquote(quoted[0]).slice(2);
+  return rest.slice(2, 2 + (rest.length - 5) / 2);
+}
+
+interface OpenFile {
+  part: DiffFilePart;
+  /** The hunk being read; its lines go into `part` as `sent`. */
+  hunk: Omit<DiffHunk, 'lines'> | null;
+  sent: DiffHunk | null;
+  hunks: number;
+  lines: number;
+  bytes: number;
+  additions: number;
+  deletions: number;
+  more: number | null;
+}
+
+/**
+ * Parse `git diff --no-renames` output as it arrives into file parts of at
+ * most `chunkBytes` of text, handed to `emit` in order. A line is kept while
+ * its file and the stream are under their caps; a file's first line gets past
+ * the file cap, so one huge line can't stall paging.
+ */
+export function createDiffChunker(
+  limits: DiffChunkLimits,
+  emit: (files: DiffFilePart[], done: boolean) => void,
+): { push: (data: string) => void; end: () => void; truncated: () => boolean } {
+  const skip = limits.skipLines ?? 0;
+  let rest = '';
+  let file: OpenFile | null = null;
+  let ready: DiffFilePart[] = [];
+  let pending = 0;
+  let total = 0;
+
+  const newPart = (path: string, binary: boolean): DiffFilePart => ({
+    path,
+    binary,
+    hunks: [],
+    done: false,
+    additions: 0,
+    deletions: 0,
+    more: null,
+  });
+
+  function flush(done: boolean): void {
+    if (file && file.part.hunks.length > 0) {
+      ready.push(file.part);
+      file.part = newPart(file.part.path, file.part.binary);
+      file.sent = null;
+    }
+    emit(ready, done);
+    ready = [];
+    pending = 0;
+  }
+
+  function closeFile(): void {
+    if (!file) return;
+    const { part, additions, deletions, more } = file;
+    ready.push(Object.assign(part, { done: true, additions, deletions, more }));
+    pending += part.path.length;
+    file = null;
+    if (pending >= limits.chunkBytes) flush(false);
+  }
+
+  function line(text: string): void {
+    if (text.startsWith('diff --git ')) {
+      closeFile();
+      file = {
+        part: newPart(pathFromHeader(text), false),
+        hunk: null,
+        sent: null,
+        hunks: 0,
+        lines: 0,
+        bytes: 0,
+        additions: 0,
+        deletions: 0,
+        more: null,
+      };
+      return;
+    }
+    if (!file) return;
+    const header = HUNK_HEADER_RE.exec(text);
+    if (header) {
+      file.hunk = {
+        id: file.hunks++,
+        header: text,
+        old_start: parseInt(header[1], 10),
+        old_lines: header[2] === undefined ? 1 : parseInt(header[2], 10),
+        new_start: parseInt(header[3], 10),
+        new_lines: header[4] === undefined ? 1 : parseInt(header[4], 10),
+      };
+      file.sent = null;
+      return;
+    }
+    if (!file.hunk) {
+      // File header: ---/+++ name the file even where the diff --git line is ambiguous; git
+      // ends names with spaces in a tab
+      const name = /^(?:---|\+\+\+) (.*?)\t?$/.exec(text)?.[1];
+      if (name !== undefined && name !== '/dev/null') file.part.path = unquote(name).slice(2);
+      if (text.startsWith('Binary files ')) file.part.binary = true;
+      return;
+    }
+    const type = LINE_TYPES[text[0]];
+    // "\ No newline at end of file" carries no content
+    if (type === undefined) return;
+    if (type === 'add') file.additions++;
+    if (type === 'del') file.deletions++;
+    const index = file.lines++;
+    if (index < skip || file.more !== null) return;
+    const bytes = text.length;
+    const fits =
+      (file.bytes === 0 || file.bytes + bytes <= limits.fileBytes) &&
+      (total === 0 || total + bytes <= limits.totalBytes);
+    if (!fits) {
+      file.more = index;
+      return;
+    }
+    if (!file.sent) {
+      file.sent = { ...file.hunk, lines: [] };
+      file.part.hunks.push(file.sent);
+    }
+    file.sent.lines.push({ type, text: text.slice(1) });
+    file.bytes += bytes;
+    total += bytes;
+    pending += bytes;
+    if (pending >= limits.chunkBytes) flush(false);
+  }
+
+  return {
+    push(data) {
+      const lines = (rest + data).split('\n');
+      rest = lines.pop() ?? '';
+      for (const l of lines) line(l);
+    },
+    end() {
+      if (rest) line(rest);
+      rest = '';
+      closeFile();
+      flush(true);
+    },
+    truncated: () => file?.more != null,
+  };
+}
+
+interface DiffStream {
+  worktreePath: string;
+  base: string;
+  controller: AbortController;
+  chunks: DiffChunk[];
+}
+
+const streams = new Map<string, DiffStream>();
+
+function diffArgs(base: string): string[] {
+  return [
+    'diff',
+    '--no-color',
+    '--no-ext-diff',
+    '--no-renames',
+    '--src-prefix=a/',
+    '--dst-prefix=b/',
+    base,
+  ];
+}
+
+function getStream(handle: string): DiffStream {
+  const stream = streams.get(handle);
+  if (!stream) throw new Error('Diff not found; it was cancelled or has expired');
+  return stream;
+}
+
+/**
+ * Start streaming everything the task changed since it forked, committed or
+ * not (tracked files, as `getTaskDiff`). Resolves with the commit it's
+ * compared against; chunks go to `onChunk` and are kept for `readDiffChunks`.
+ */
+export async function startDiff(
+  worktreePath: string,
+  handle: string,
+  onChunk: (chunk: DiffChunk) => void,
+): Promise<{ handle: string; base: string }> {
+  if (streams.has(handle)) throw new Error('Diff handle is already in use');
+  const base = await getTaskDiffBase(worktreePath);
+  const stream: DiffStream = { worktreePath, base, controller: new AbortController(), chunks: [] };
+  streams.set(handle, stream);
+  for (const [old] of streams) {
+    if (streams.size <= MAX_STREAMS) break;
+    cancelDiff(old);
+  }
+  const { signal } = stream.controller;
+  const send = (files: DiffFilePart[], done: boolean, error: string | null = null) => {
+    const chunk: DiffChunk = { handle, seq: stream.chunks.length, files, done, error };
+    stream.chunks.push(chunk);
+    onChunk(chunk);
+  };
+  const chunker = createDiffChunker(STREAM_LIMITS, send);
+  void streamGit(worktreePath, diffArgs(base), chunker.push, signal).then(
+    () => {
+      if (!signal.aborted) chunker.end();
+    },
+    (err: unknown) => send([], true, err instanceof Error ? err.message : String(err)),
+  );
+  return { handle, base };
+}
+
+/** Chunks the stream has sent after `after` (a `seq`), for callers that poll. */
+export function readDiffChunks(handle: string, after = -1): DiffChunk[] {
+  return getStream(handle).chunks.filter((c) => c.seq > after);
+}
+
+/**
+ * The next page of a file the stream cut off, from its `more` line. Read
+ * again from the worktree, so later edits to the file show up.
+ */
+export async function loadMoreDiff(
+  handle: string,
+  filePath: string,
+  from: number,
+): Promise<DiffPage> {
+  const { worktreePath, base } = getStream(handle);
+  const page: DiffPage = { path: filePath, hunks: [], more: null };
+  const controller = new AbortController();
+  const chunker = createDiffChunker(
+    { fileBytes: PAGE_BYTES, totalBytes: Infinity, chunkBytes: Infinity, skipLines: from },
+    (files) => {
+      const part = files.find((f) => f.done);
+      if (part) Object.assign(page, { hunks: part.hunks, more: part.more });
+    },
+  );
+  const args = [...diffArgs(base), '--', `:(literal)${filePath}`];
+  await streamGit(
+    worktreePath,
+    args,
+    (data) => {
+      chunker.push(data);
+      // The rest of the file isn't needed for this page
+      if (chunker.truncated()) controller.abort();
+    },
+    controller.signal,
+  );
+  chunker.end();
+  return page;
+}
+
+/** Stop a stream and forget it. Returns false for an unknown handle. */
+export function cancelDiff(handle: string): boolean {
+  const stream = streams.get(handle);
+  if (!stream) return false;
+  stream.controller.abort();
+  streams.delete(handle);
+  return true;
+}
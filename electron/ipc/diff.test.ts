@@ -0,0 +1,59 @@
+import { describe, expect, it } from 'vitest';
+import { parseUnifiedDiff, selectHunks } from './diff.js';
+
+describe('parseUnifiedDiff', () => {
+  it('splits hunks and counts additions and deletions', () => {
+    const diff = [
+      'diff --git a/src/a.ts b/src/a.ts',
+      'index 1111111..2222222 100644',
+      '--- a/src/a.ts',
+      '+++ b/src/a.ts',
+      '@@ -1,3 +1,3 @@',
+      ' keep',
+      '-old',
+      '+new',
+      ' keep',
+      '@@ -10 +10,2 @@ function f() {',
+      '-- not a header',
+      '+++ also content',
+      '+x',
+      '\\ No newline at end of file',
+      '',
+    ].join('\n');
+
+    const result = parseUnifiedDiff('src/a.ts', diff);
+
+    expect(result).toMatchObject({ binary: false, additions: 3, deletions: 2 });
+    expect(result.hunks).toHaveLength(2);
+    expect(result.hunks[0]).toMatchObject({ id: 0, old_start: 1, old_lines: 3, new_lines: 3 });
+    expect(result.hunks[1]).toMatchObject({ id: 1, old_start: 10, old_lines: 1, new_lines: 2 });
+    expect(result.hunks[1].lines[0]).toEqual({ type: 'del', text: '- not a header' });
+  });
+
+  it('flags binary files', () => {
+    const diff = 'diff --git a/img.png b/img.png\nBinary files a/img.png and b/img.png differ\n';
+    expect(parseUnifiedDiff('img.png', diff)).toMatchObject({ binary: true, hunks: [] });
+  });
+});
+
+describe('selectHunks', () => {
+  const diff = [
+    '--- a/f',
+    '+++ b/f',
+    '@@ -1 +1 @@',
+    '-a',
+    '+A',
+    '@@ -5 +5 @@',
+    '-b',
+    '+B',
+    '',
+  ].join('\n');
+
+  it('keeps the file header and only the chosen hunks', () => {
+    expect(selectHunks(diff, [1])).toBe('--- a/f\n+++ b/f\n@@ -5 +5 @@\n-b\n+B\n');
+  });
+
+  it('returns an empty patch when no hunk matches', () => {
+    expect(selectHunks(diff, [7])).toBe('');
+  });
+});
@@ -0,0 +1,89 @@
+/** Structured view of a single-file unified diff (`git diff` output). */
+
+export interface DiffLine {
+  type: 'context' | 'add' | 'del';
+  text: string;
+}
+
+export interface DiffHunk {
+  /** Position of the hunk within the file diff, stable for a given diff. */
+  id: number;
+  header: string;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: DiffLine[];
+}
+
+export interface StructuredFileDiff {
+  path: string;
+  binary: boolean;
+  additions: number;
+  deletions: number;
+  hunks: DiffHunk[];
+}
+
+export const HUNK_HEADER_RE = /^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@/;
+
+export function parseUnifiedDiff(filePath: string, diff: string): StructuredFileDiff {
+  const result: StructuredFileDiff = {
+    path: filePath,
+    binary: false,
+    additions: 0,
+    deletions: 0,
+    hunks: [],
+  };
+  let hunk: DiffHunk | null = null;
+
+  for (const line of diff.split('\n')) {
+    const header = line.match(HUNK_HEADER_RE);
+    if (header) {
+      hunk = {
+        id: result.hunks.length,
+        header: line,
+        old_start: parseInt(header[1], 10),
+        old_lines: header[2] === undefined ? 1 : parseInt(header[2], 10),
+        new_start: parseInt(header[3], 10),
+        new_lines: header[4] === undefined ? 1 : parseInt(header[4], 10),
+        lines: [],
+      };
+      result.hunks.push(hunk);
+      continue;
+    }
+    if (!hunk) {
+      // File header section (diff --git, index, ---/+++)
+      if (line.startsWith('Binary files ') || line === 'GIT binary patch') result.binary = true;
+      continue;
+    }
+    if (line.startsWith('+')) {
+      hunk.lines.push({ type: 'add', text: line.slice(1) });
+      result.additions++;
+    } else if (line.startsWith('-')) {
+      hunk.lines.push({ type: 'del', text: line.slice(1) });
+      result.deletions++;
+    } else if (line.startsWith(' ')) {
+      hunk.lines.push({ type: 'context', text: line.slice(1) });
+    }
+    // "\ No newline at end of file" and trailing empty lines carry no content
+  }
+  return result;
+}
+
+/**
+ * Build a patch containing only the hunks with the given ids, keeping the
+ * file header. Returns an empty string when no id matches.
+ */
+export function selectHunks(diff: string, hunkIds: number[]): string {
+  const wanted = new Set(hunkIds);
+  const header: string[] = [];
+  const selected: string[] = [];
+  let current = -1;
+  for (const line of diff.split('\n')) {
+    if (HUNK_HEADER_RE.test(line)) current++;
+    if (current === -1) header.push(line);
+    else if (wanted.has(current)) selected.push(line);
+  }
+  if (selected.length === 0) return '';
+  return [...header, ...selected].join('\n').replace(/\n*$/, '\n');
+}
@@ -0,0 +1,63 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./settings.js', () => ({
+  getWorktreeRoot: (repoRoot: string) => path.join(repoRoot, '.worktrees'),
+}));
+
+import { getDiskUsage } from './disk-usage.js';
+
+const repo = fs.mkdtempSync(path.join(os.tmpdir(), 'disk-usage-'));
+const root = path.join(repo, '.worktrees');
+
+function write(rel: string, bytes: number): void {
+  const file = path.join(root, rel);
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, Buffer.alloc(bytes, 1));
+}
+
+afterAll(() => {
+  fs.rmSync(repo, { recursive: true, force: true });
+});
+
+describe('getDiskUsage', () => {
+  it('charges files to the worktree they are in, largest first', async () => {
+    write('task/small/.git', 10);
+    write('task/small/a.txt', 1_000);
+    write('task/big/.git', 10);
+    write('task/big/node_modules/pkg/index.js', 200_000);
+    write('leftover/file.bin', 50_000);
+    fs.symlinkSync(path.join(root, 'task/big/node_modules'), path.join(root, 'task/small/nm'));
+
+    const usage = await getDiskUsage([repo]);
+    const [project] = usage.projects;
+    expect(project.worktrees.map((w) => w.branch_name)).toEqual(['task/big', 'task/small']);
+    const [big, small] = project.worktrees;
+    expect(big.path).toBe(path.join(root, 'task/big'));
+    expect(big.bytes).toBeGreaterThanOrEqual(200_000);
+    // The symlink into the other worktree isn't followed
+    expect(small.bytes).toBeLessThan(big.bytes);
+    expect(project.total_bytes).toBeGreaterThanOrEqual(big.bytes + small.bytes + 50_000);
+    expect(usage.total_bytes).toBe(project.total_bytes);
+  });
+
+  it('serves cached sizes until asked to refresh', async () => {
+    const before = await getDiskUsage([repo]);
+    write('task/small/more.bin', 500_000);
+
+    expect((await getDiskUsage([repo])).total_bytes).toBe(before.total_bytes);
+    expect((await getDiskUsage([repo], true)).total_bytes).toBeGreaterThan(before.total_bytes);
+  });
+
+  it('reports nothing for projects without worktrees', async () => {
+    const usage = await getDiskUsage([path.join(repo, 'missing')]);
+    expect(usage.total_bytes).toBe(0);
+    expect(usage.projects[0].worktrees).toEqual([]);
+  });
+});
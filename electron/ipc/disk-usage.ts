@@ -0,0 +1,146 @@
+import fs from 'fs';
+import path from 'path';
+import { getWorktreeRoot } from './settings.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('disk-usage');
+
+/** Sizes are reused for this long unless a refresh is asked for. */
+const CACHE_TTL_MS = 10 * 60_000;
+
+export interface WorktreeDiskUsage {
+  /** Path relative to the worktree root, which is the task's branch name. */
+  branch_name: string;
+  path: string;
+  bytes: number;
+}
+
+export interface ProjectDiskUsage {
+  project_root: string;
+  worktree_root: string;
+  /** Everything under the worktree root, including leftovers that belong to no worktree. */
+  total_bytes: number;
+  /** Largest first. */
+  worktrees: WorktreeDiskUsage[];
+  computed_at: number;
+}
+
+export interface DiskUsage {
+  total_bytes: number;
+  projects: ProjectDiskUsage[];
+}
+
+const cache = new Map<string, ProjectDiskUsage>();
+const inFlight = new Map<string, Promise<ProjectDiskUsage>>();
+
+/** Space a file takes on disk; the size where the filesystem reports no blocks. */
+function diskBytes(st: fs.Stats): number {
+  return st.blocks ? st.blocks * 512 : st.size;
+}
+
+/**
+ * Walk `root`, charging each file to the worktree it sits in (a folder with
+ * a `.git` entry). Symlinks aren't followed, so directories shared into
+ * worktrees via `symlinkDirs` count once in the project, not per task, and
+ * hard-linked files (e.g. from pnpm's store) count once.
+ */
+async function measureWorktrees(
+  root: string,
+): Promise<{ total: number; worktrees: Map<string, number> }> {
+  const worktrees = new Map<string, number>();
+  const seenInodes = new Set<string>();
+  let total = 0;
+
+  async function walk(dir: string, owner: string | null): Promise<void> {
+    let entries: fs.Dirent[];
+    try {
+      entries = await fs.promises.readdir(dir, { withFileTypes: true });
+    } catch (err) {
+      log.debug(`Skipping unreadable ${dir}`, err);
+      return;
+    }
+    if (owner === null && entries.some((e) => e.name === '.git')) {
+      owner = dir;
+      worktrees.set(owner, 0);
+    }
+    const files = entries.filter((e) => !e.isDirectory() && !e.isSymbolicLink());
+    const stats = await Promise.all(
+      files.map((e) => fs.promises.lstat(path.join(dir, e.name)).catch(() => null)),
+    );
+    let bytes = 0;
+    for (const st of stats) {
+      if (!st) continue;
+      if (st.nlink > 1) {
+        const key = `${st.dev}:${st.ino}`;
+        if (seenInodes.has(key)) continue;
+        seenInodes.add(key);
+      }
+      bytes += diskBytes(st);
+    }
+    total += bytes;
+    if (owner !== null) worktrees.set(owner, (worktrees.get(owner) ?? 0) + bytes);
+    // One directory at a time keeps a walk through node_modules from flooding the fs pool
+    for (const e of entries) {
+      if (e.isDirectory()) await walk(path.join(dir, e.name), owner);
+    }
+  }
+
+  await walk(root, null);
+  return { total, worktrees };
+}
+
+/** Space everything under `dir` takes, counted as for worktrees. */
+export async function measureDir(dir: string): Promise<number> {
+  return (await measureWorktrees(dir)).total;
+}
+
+/** Drop a project's cached sizes, e.g. after removing worktrees behind the app's back. */
+export function forgetDiskUsage(projectRoot: string): void {
+  cache.delete(projectRoot);
+}
+
+async function measureProject(projectRoot: string): Promise<ProjectDiskUsage> {
+  const worktreeRoot = getWorktreeRoot(projectRoot);
+  const { total, worktrees } = fs.existsSync(worktreeRoot)
+    ? await measureWorktrees(worktreeRoot)
+    : { total: 0, worktrees: new Map<string, number>() };
+  return {
+    project_root: projectRoot,
+    worktree_root: worktreeRoot,
+    total_bytes: total,
+    worktrees: [...worktrees]
+      .map(([dir, bytes]) => ({
+        branch_name: path.relative(worktreeRoot, dir).split(path.sep).join('/'),
+        path: dir,
+        bytes,
+      }))
+      .sort((a, b) => b.bytes - a.bytes),
+    computed_at: Date.now(),
+  };
+}
+
+function projectUsage(projectRoot: string, refresh: boolean): Promise<ProjectDiskUsage> {
+  const cached = cache.get(projectRoot);
+  if (!refresh && cached && Date.now() - cached.computed_at < CACHE_TTL_MS) {
+    return Promise.resolve(cached);
+  }
+  const running = inFlight.get(projectRoot);
+  if (running) return running;
+  const measuring = measureProject(projectRoot)
+    .then((usage) => {
+      cache.set(projectRoot, usage);
+      return usage;
+    })
+    .finally(() => inFlight.delete(projectRoot));
+  inFlight.set(projectRoot, measuring);
+  return measuring;
+}
+
+/**
+ * How much space each project's worktrees take. Results are cached for ten
+ * minutes; `refresh` measures again, e.g. after tasks were cleaned up.
+ */
+export async function getDiskUsage(projectRoots: string[], refresh = false): Promise<DiskUsage> {
+  const projects = await Promise.all(projectRoots.map((root) => projectUsage(root, refresh)));
+  return { total_bytes: projects.reduce((sum, p) => sum + p.total_bytes, 0), projects };
+}
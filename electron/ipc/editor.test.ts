@@ -0,0 +1,47 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { EDITOR_PRESETS, buildEditorCommand, resolveEditorTemplate } from './editor.js';
+
+describe('resolveEditorTemplate', () => {
+  it('uses presets by id', () => {
+    expect(resolveEditorTemplate('zed')).toBe(EDITOR_PRESETS.zed);
+  });
+
+  it('turns a plain command into worktree and file templates', () => {
+    expect(resolveEditorTemplate(' subl ')).toEqual({
+      folder: 'subl {worktree}',
+      file: 'subl {worktree} {file}',
+    });
+  });
+});
+
+describe('buildEditorCommand', () => {
+  it('substitutes placeholders per argument', () => {
+    expect(
+      buildEditorCommand(EDITOR_PRESETS.vscode.file, {
+        worktree: '/My Repo/.worktrees/task',
+        file: '/My Repo/.worktrees/task/src/a b.ts',
+        line: 42,
+      }),
+    ).toEqual({
+      command: 'code',
+      args: ['/My Repo/.worktrees/task', '--goto', '/My Repo/.worktrees/task/src/a b.ts:42'],
+    });
+  });
+
+  it('defaults the line to 1', () => {
+    expect(
+      buildEditorCommand('idea --line {line} {file}', { worktree: '/wt', file: '/wt/x' }),
+    ).toEqual({ command: 'idea', args: ['--line', '1', '/wt/x'] });
+  });
+
+  it('rejects shell metacharacters in the command', () => {
+    expect(() => buildEditorCommand('code;rm {worktree}', { worktree: '/wt' })).toThrow(
+      'shell metacharacters',
+    );
+  });
+});
@@ -0,0 +1,94 @@
+import { spawn } from 'child_process';
+import path from 'path';
+import { getSettings } from './settings.js';
+
+/**
+ * Command templates for launching an editor. Templates are split on
+ * whitespace and `{worktree}`, `{file}` and `{line}` are substituted per
+ * argument, so paths containing spaces stay a single argument.
+ */
+export interface EditorTemplate {
+  /** Opens the worktree folder. */
+  folder: string;
+  /** Opens the worktree with `{file}` at `{line}`. */
+  file: string;
+}
+
+export const EDITOR_PRESETS: Record<string, EditorTemplate> = {
+  vscode: { folder: 'code {worktree}', file: 'code {worktree} --goto {file}:{line}' },
+  cursor: { folder: 'cursor {worktree}', file: 'cursor {worktree} --goto {file}:{line}' },
+  zed: { folder: 'zed {worktree}', file: 'zed {worktree} {file}:{line}' },
+  jetbrains: { folder: 'idea {worktree}', file: 'idea {worktree} --line {line} {file}' },
+};
+
+const UNSAFE_COMMAND_RE = /[;&|`$(){}[\]<>\\'"*?!#~]/;
+
+/**
+ * Templates for `editor`: a user-configured template, a preset id, or a plain
+ * command such as `subl` (which gets the worktree, then the file, appended).
+ */
+export function resolveEditorTemplate(editor: string): EditorTemplate {
+  const name = editor.trim();
+  if (!name) throw new Error('editor must be a non-empty string');
+  const template = getSettings().editor_templates[name] ?? EDITOR_PRESETS[name];
+  if (template) return template;
+  if (name.includes('{')) return { folder: name, file: name };
+  return { folder: `${name} {worktree}`, file: `${name} {worktree} {file}` };
+}
+
+export function buildEditorCommand(
+  template: string,
+  vars: { worktree: string; file?: string; line?: number },
+): { command: string; args: string[] } {
+  const [command, ...rest] = template.trim().split(/\s+/);
+  if (!command) throw new Error('Editor command template is empty');
+  if (UNSAFE_COMMAND_RE.test(command)) {
+    throw new Error('Editor command must not contain shell metacharacters');
+  }
+  const values: Record<string, string> = {
+    worktree: vars.worktree,
+    file: vars.file ?? '',
+    line: String(vars.line ?? 1),
+  };
+  const args = rest.map((arg) =>
+    arg.replace(/\{(worktree|file|line)\}/g, (_m, key: string) => values[key]),
+  );
+  return { command, args };
+}
+
+export function openInEditor(opts: {
+  editor: string;
+  worktreePath: string;
+  filePath?: string;
+  line?: number;
+}): Promise<void> {
+  const template = resolveEditorTemplate(opts.editor);
+  const { command, args } = opts.filePath
+    ? buildEditorCommand(template.file, {
+        worktree: opts.worktreePath,
+        file: path.join(opts.worktreePath, opts.filePath),
+        line: opts.line,
+      })
+    : buildEditorCommand(template.folder, { worktree: opts.worktreePath });
+
+  return new Promise<void>((resolve, reject) => {
+    let settled = false;
+    const child = spawn(command, args, {
+      detached: true,
+      stdio: 'ignore',
+    });
+    child.on('error', (err) => {
+      if (!settled) {
+        settled = true;
+        reject(new Error(`Failed to launch "${command}": ${err.message}`));
+      }
+    });
+    child.on('spawn', () => {
+      if (!settled) {
+        settled = true;
+        child.unref();
+        resolve();
+      }
+    });
+  });
+}
@@ -0,0 +1,36 @@
+import { describe, expect, it } from 'vitest';
+import { AppError, decodeFromIpc, encodeForIpc, isAppError } from './errors.js';
+
+describe('AppError IPC encoding', () => {
+  it('round-trips code, message and context through an Electron-style message', () => {
+    const err = new AppError('branch_checked_out_elsewhere', 'Branch task/x is checked out', {
+      branch: 'task/x',
+      path: '/repo/.worktrees/task/x',
+    });
+    const encoded = encodeForIpc(err) as Error;
+    // Electron wraps handler errors like this before rejecting in the renderer
+    const message = `Error invoking remote method 'create_task': Error: ${encoded.message}`;
+    expect(decodeFromIpc(message)).toEqual({
+      code: 'branch_checked_out_elsewhere',
+      message: 'Branch task/x is checked out',
+      context: { branch: 'task/x', path: '/repo/.worktrees/task/x' },
+    });
+  });
+
+  it('passes other errors through and ignores plain messages', () => {
+    const plain = new Error('boom');
+    expect(encodeForIpc(plain)).toBe(plain);
+    expect(decodeFromIpc('Error: boom')).toBeNull();
+    expect(decodeFromIpc('__app_error__:{"code":"made_up","message":"x"}')).toBeNull();
+  });
+});
+
+describe('isAppError', () => {
+  it('optionally matches a specific code', () => {
+    const err = new AppError('not_a_git_repo', 'Not a git repository: /tmp');
+    expect(isAppError(err)).toBe(true);
+    expect(isAppError(err, 'not_a_git_repo')).toBe(true);
+    expect(isAppError(err, 'merge_conflict')).toBe(false);
+    expect(isAppError(new Error('x'))).toBe(false);
+  });
+});
@@ -0,0 +1,94 @@
+// Typed backend errors. Electron only forwards an error's message to the
+// renderer, so AppErrors cross IPC encoded in it (see encodeForIpc) and are
+// decoded again by the renderer's invoke wrapper. Kept free of Node imports
+// so the renderer can share it.
+
+export const ERROR_CODES = [
+  'worktree_exists',
+  'branch_exists',
+  'branch_checked_out_elsewhere',
+  'not_a_git_repo',
+  'agent_not_installed',
+  'uncommitted_changes',
+  'merge_conflict',
+  'checks_failed',
+  'operation_cancelled',
+  'resource_limit_exceeded',
+  'unmerged_commits',
+] as const;
+
+export type ErrorCode = (typeof ERROR_CODES)[number];
+
+export interface ErrorContext {
+  path?: string;
+  branch?: string;
+  command?: string;
+  /** Raw stderr of the failing git/agent command, when there is one. */
+  stderr?: string;
+  files?: string[];
+  /** For `unmerged_commits`: the branch tip, and how many commits deleting it would lose. */
+  commit?: string;
+  unmerged?: number;
+  unpushed?: number | null;
+}
+
+export interface SerializedAppError {
+  code: ErrorCode;
+  message: string;
+  context: ErrorContext;
+}
+
+export class AppError extends Error {
+  constructor(
+    readonly code: ErrorCode,
+    message: string,
+    readonly context: ErrorContext = {},
+  ) {
+    super(message);
+    this.name = 'AppError';
+  }
+}
+
+export function isAppError(err: unknown, code?: ErrorCode): err is AppError {
+  return err instanceof AppError && (code === undefined || err.code === code);
+}
+
+const IPC_PREFIX = '__app_error__:';
+
+/** Error to rethrow from an IPC handler so the AppError survives the trip. */
+export function encodeForIpc(err: unknown): unknown {
+  if (!(err instanceof AppError)) return err;
+  const payload: SerializedAppError = {
+    code: err.code,
+    message: err.message,
+    context: err.context,
+  };
+  return new Error(IPC_PREFIX + JSON.stringify(payload));
+}
+
+function isErrorCode(v: unknown): v is ErrorCode {
+  return typeof v === 'string' && (ERROR_CODES as readonly string[]).includes(v);
+}
+
+/**
+ * Recover an AppError from a rejected IPC call. Electron prefixes the message
+ * with "Error invoking remote method '…': Error: ", so search rather than match.
+ */
+export function decodeFromIpc(message: string): SerializedAppError | null {
+  const idx = message.indexOf(IPC_PREFIX);
+  if (idx === -1) return null;
+  try {
+    const raw = JSON.parse(message.slice(idx + IPC_PREFIX.length)) as Partial<SerializedAppError>;
+    if (!isErrorCode(raw.code) || typeof raw.message !== 'string') return null;
+    const context = raw.context && typeof raw.context === 'object' ? raw.context : {};
+    return { code: raw.code, message: raw.message, context };
+  } catch {
+    return null;
+  }
+}
+
+/** Stderr of a failed execFile call, for error context. */
+export function stderrOf(err: unknown): string | undefined {
+  const stderr = (err as { stderr?: unknown } | null)?.stderr;
+  return typeof stderr === 'string' && stderr.trim() ? stderr.trim() : undefined;
+}
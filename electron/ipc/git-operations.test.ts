@@ -0,0 +1,60 @@
+import { describe, expect, it } from 'vitest';
+import {
+  cancelGitOperation,
+  currentGitOperation,
+  parseGitProgress,
+  runGitOperation,
+} from './git-operations.js';
+
+describe('parseGitProgress', () => {
+  it('reads phases with a known total', () => {
+    expect(parseGitProgress('Receiving objects:  45% (450/1000), 1.20 MiB | 1.00 MiB/s')).toEqual({
+      phase: 'Receiving objects',
+      percent: 45,
+      current: 450,
+      total: 1000,
+    });
+    expect(parseGitProgress('Updating files: 100% (300/300), done.')?.percent).toBe(100);
+  });
+
+  it('reads remote phases and counters without a total', () => {
+    expect(parseGitProgress('remote: Enumerating objects: 1234, done.')).toEqual({
+      phase: 'Enumerating objects',
+      percent: null,
+      current: 1234,
+      total: null,
+    });
+  });
+
+  it('ignores lines that are not progress', () => {
+    expect(parseGitProgress('From github.com:owner/repo')).toBeNull();
+    expect(parseGitProgress(' * [new branch]      main -> origin/main')).toBeNull();
+    expect(parseGitProgress("Preparing worktree (new branch 'task/a')")).toBeNull();
+  });
+});
+
+describe('runGitOperation', () => {
+  it('exposes a signal that cancelGitOperation aborts', async () => {
+    let aborted = false;
+    const lines: string[] = [];
+    await runGitOperation('op-1', (line) => lines.push(line), async () => {
+      const op = currentGitOperation();
+      op?.onLine('Updating files:  10% (1/10)');
+      expect(cancelGitOperation('op-1')).toBe(true);
+      aborted = op?.signal.aborted ?? false;
+    });
+
+    expect(aborted).toBe(true);
+    expect(lines).toEqual(['Updating files:  10% (1/10)']);
+    expect(cancelGitOperation('op-1')).toBe(false);
+    expect(currentGitOperation()).toBeUndefined();
+  });
+
+  it('refuses an id that is already running', async () => {
+    await runGitOperation('op-2', () => {}, async () => {
+      await expect(runGitOperation('op-2', () => {}, async () => {})).rejects.toThrow(
+        'already running',
+      );
+    });
+  });
+});
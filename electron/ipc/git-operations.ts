@@ -0,0 +1,79 @@
+import { AsyncLocalStorage } from 'async_hooks';
+import { AppError } from './errors.js';
+
+/** A parsed git `--progress` line, e.g. `Receiving objects:  45% (450/1000), 1.20 MiB`. */
+export interface GitProgress {
+  phase: string;
+  percent: number | null;
+  current: number | null;
+  total: number | null;
+}
+
+interface GitOperation {
+  id: string;
+  controller: AbortController;
+  onLine: (line: string) => void;
+}
+
+const operations = new Map<string, GitOperation>();
+const current = new AsyncLocalStorage<GitOperation>();
+
+/** Parse one progress line from git's stderr; null for anything else (hints, errors). */
+export function parseGitProgress(line: string): GitProgress | null {
+  const text = line.replace(/^remote:\s*/, '').trim();
+  const withPercent = /^([A-Za-z][A-Za-z ]*):\s+(\d+)%\s+\((\d+)\/(\d+)\)/.exec(text);
+  if (withPercent) {
+    return {
+      phase: withPercent[1],
+      percent: Number(withPercent[2]),
+      current: Number(withPercent[3]),
+      total: Number(withPercent[4]),
+    };
+  }
+  // Phases with no known total only count up: "Counting objects: 1234, done."
+  const counting = /^([A-Za-z][A-Za-z ]*):\s+(\d+)(?:,|$)/.exec(text);
+  if (counting) {
+    return { phase: counting[1], percent: null, current: Number(counting[2]), total: null };
+  }
+  return null;
+}
+
+export function operationCancelledError(): AppError {
+  return new AppError('operation_cancelled', 'Cancelled');
+}
+
+/**
+ * Run `fn` as git operation `opId`: git commands it starts with progress
+ * (fetch, worktree checkout) report their lines to `onLine`, and
+ * `cancelGitOperation(opId)` kills them.
+ */
+export async function runGitOperation<T>(
+  opId: string,
+  onLine: (line: string) => void,
+  fn: () => Promise<T>,
+): Promise<T> {
+  if (operations.has(opId)) throw new Error(`Operation ${opId} is already running`);
+  const op: GitOperation = { id: opId, controller: new AbortController(), onLine };
+  operations.set(opId, op);
+  try {
+    return await current.run(op, fn);
+  } finally {
+    operations.delete(opId);
+  }
+}
+
+/** Stop a running operation. Returns false when it already finished. */
+export function cancelGitOperation(opId: string): boolean {
+  const op = operations.get(opId);
+  if (!op) return false;
+  op.controller.abort();
+  return true;
+}
+
+/** The operation the caller runs in, if any: where to report progress and when to stop. */
+export function currentGitOperation():
+  | { signal: AbortSignal; onLine: (line: string) => void }
+  | undefined {
+  const op = current.getStore();
+  return op && { signal: op.controller.signal, onLine: op.onLine };
+}
@@ -0,0 +1,91 @@
+import { afterEach, describe, expect, it, vi } from 'vitest';
+import type { BrowserWindow } from 'electron';
+
+const getWorktreeStatusMock = vi.hoisted(() => vi.fn());
+const getAheadBehindMock = vi.hoisted(() => vi.fn());
+
+vi.mock('./git.js', () => ({
+  getWorktreeStatus: getWorktreeStatusMock,
+  getAheadBehind: getAheadBehindMock,
+}));
+
+import {
+  computeAllTasksInterval,
+  refreshTaskGitStatus,
+  setWatchedTasks,
+  startGitStatusPoller,
+  stopGitStatusPoller,
+} from './git-status-poller.js';
+
+function fakeWindow() {
+  const send = vi.fn();
+  const win = { isDestroyed: () => false, webContents: { send } } as unknown as BrowserWindow;
+  return { win, send };
+}
+
+const flush = () => new Promise((resolve) => setTimeout(resolve, 0));
+
+afterEach(() => {
+  stopGitStatusPoller();
+  getWorktreeStatusMock.mockReset();
+  getAheadBehindMock.mockReset();
+});
+
+describe('git status poller', () => {
+  it('sends status for newly watched tasks and skips unchanged results', async () => {
+    const { win, send } = fakeWindow();
+    getWorktreeStatusMock.mockResolvedValue({
+      has_committed_changes: true,
+      has_uncommitted_changes: false,
+    });
+    getAheadBehindMock.mockResolvedValue({ ahead: 2, behind: 1 });
+
+    startGitStatusPoller(win);
+    setWatchedTasks([{ taskId: 't1', worktreePath: '/wt/t1' }], 't1');
+    await flush();
+    expect(send).toHaveBeenCalledTimes(1);
+    expect(send.mock.calls[0][1]).toEqual({
+      task_id: 't1',
+      has_committed_changes: true,
+      has_uncommitted_changes: false,
+      ahead: 2,
+      behind: 1,
+    });
+
+    refreshTaskGitStatus('t1');
+    await flush();
+    expect(send).toHaveBeenCalledTimes(1);
+
+    getAheadBehindMock.mockResolvedValue({ ahead: 3, behind: 1 });
+    refreshTaskGitStatus('t1');
+    await flush();
+    expect(send).toHaveBeenCalledTimes(2);
+  });
+
+  it('resends everything after a reset', async () => {
+    const { win, send } = fakeWindow();
+    getWorktreeStatusMock.mockResolvedValue({
+      has_committed_changes: false,
+      has_uncommitted_changes: true,
+    });
+    getAheadBehindMock.mockResolvedValue({ ahead: 0, behind: 0 });
+
+    startGitStatusPoller(win);
+    const tasks = [{ taskId: 't1', worktreePath: '/wt/t1' }];
+    setWatchedTasks(tasks, null);
+    await flush();
+    setWatchedTasks(tasks, null);
+    await flush();
+    expect(send).toHaveBeenCalledTimes(1);
+
+    setWatchedTasks(tasks, null, true);
+    await flush();
+    expect(send).toHaveBeenCalledTimes(2);
+  });
+
+  it('scales the background interval with the task count', () => {
+    expect(computeAllTasksInterval(1)).toBe(30_000);
+    expect(computeAllTasksInterval(5)).toBe(40_000);
+    expect(computeAllTasksInterval(100)).toBe(120_000);
+  });
+});
@@ -0,0 +1,131 @@
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { getAheadBehind, getWorktreeStatus } from './git.js';
+
+export interface TaskGitStatus {
+  task_id: string;
+  has_committed_changes: boolean;
+  has_uncommitted_changes: boolean;
+  ahead: number;
+  behind: number;
+}
+
+interface WatchedTask {
+  taskId: string;
+  worktreePath: string;
+  /** Sub-directory the task works in; changes elsewhere don't count. */
+  scope?: string;
+}
+
+const ACTIVE_INTERVAL_MS = 5_000;
+const BATCH_SIZE = 4;
+
+let win: BrowserWindow | null = null;
+let watched: WatchedTask[] = [];
+let activeTaskId: string | null = null;
+const lastStatus = new Map<string, string>();
+let activeTimer: ReturnType<typeof setInterval> | null = null;
+let allTimer: ReturnType<typeof setInterval> | null = null;
+let allInterval = 0;
+let isPollingAll = false;
+
+/** Inactive tasks: 30s base + 5s per task beyond 3, capped at 2 minutes. */
+export function computeAllTasksInterval(taskCount: number): number {
+  return Math.min(120_000, 30_000 + Math.max(0, taskCount - 3) * 5_000);
+}
+
+async function pollTask(task: WatchedTask): Promise<void> {
+  let status: TaskGitStatus;
+  try {
+    const [worktree, counts] = await Promise.all([
+      getWorktreeStatus(task.worktreePath, task.scope),
+      getAheadBehind(task.worktreePath),
+    ]);
+    status = { task_id: task.taskId, ...worktree, ...counts };
+  } catch {
+    // Worktree may not exist yet or was removed — ignore
+    return;
+  }
+  // The task may have been unwatched while git was running
+  if (!watched.some((t) => t.taskId === task.taskId)) return;
+  const key = JSON.stringify(status);
+  if (lastStatus.get(task.taskId) === key) return;
+  lastStatus.set(task.taskId, key);
+  if (win && !win.isDestroyed()) win.webContents.send(IPC.TaskGitStatusChanged, status);
+}
+
+function pollActive(): void {
+  const task = watched.find((t) => t.taskId === activeTaskId);
+  if (task) void pollTask(task);
+}
+
+/** Poll every inactive task, limiting concurrency to avoid spawning too many git processes. */
+async function pollAll(): Promise<void> {
+  if (isPollingAll) return;
+  isPollingAll = true;
+  try {
+    const toPoll = watched.filter((t) => t.taskId !== activeTaskId);
+    for (let i = 0; i < toPoll.length; i += BATCH_SIZE) {
+      await Promise.allSettled(toPoll.slice(i, i + BATCH_SIZE).map(pollTask));
+    }
+  } finally {
+    isPollingAll = false;
+  }
+}
+
+export function startGitStatusPoller(window: BrowserWindow): void {
+  win = window;
+  if (activeTimer) return;
+  activeTimer = setInterval(pollActive, ACTIVE_INTERVAL_MS);
+}
+
+/**
+ * Replace the set of watched tasks. Newly watched tasks and a newly active
+ * task are polled right away; unchanged status is never re-sent. `reset`
+ * forgets what was sent, for a renderer that has just (re)loaded.
+ */
+export function setWatchedTasks(tasks: WatchedTask[], active: string | null, reset = false): void {
+  if (reset) {
+    watched = [];
+    lastStatus.clear();
+  }
+  const previous = new Set(watched.map((t) => t.taskId));
+  const activeChanged = active !== activeTaskId;
+  watched = tasks;
+  activeTaskId = active;
+
+  const current = new Set(tasks.map((t) => t.taskId));
+  for (const taskId of lastStatus.keys()) {
+    if (!current.has(taskId)) lastStatus.delete(taskId);
+  }
+
+  const interval = computeAllTasksInterval(tasks.length);
+  if (win && interval !== allInterval) {
+    if (allTimer) clearInterval(allTimer);
+    allTimer = setInterval(() => void pollAll(), interval);
+    allInterval = interval;
+  }
+
+  for (const task of tasks) {
+    if (!previous.has(task.taskId) || (activeChanged && task.taskId === active)) {
+      void pollTask(task);
+    }
+  }
+}
+
+/** Poll one task now, e.g. after its agent exits. */
+export function refreshTaskGitStatus(taskId: string): void {
+  const task = watched.find((t) => t.taskId === taskId);
+  if (task) void pollTask(task);
+}
+
+export function stopGitStatusPoller(): void {
+  if (activeTimer) clearInterval(activeTimer);
+  if (allTimer) clearInterval(allTimer);
+  activeTimer = null;
+  allTimer = null;
+  allInterval = 0;
+  watched = [];
+  activeTaskId = null;
+  lastStatus.clear();
+}
@@ -1,7 +1,14 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
 import { beforeEach, describe, expect, it, vi } from 'vitest';
 
 const execFileMock = vi.hoisted(() => vi.fn());
 
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
 vi.mock('child_process', () => ({
   execFile: execFileMock,
   default: {
@@ -9,7 +16,31 @@ vi.mock('child_process', () => ({
   },
 }));
 
-import { createWorktree } from './git.js';
+import {
+  assertBranchDeletable,
+  cherryPickCommits,
+  commitAll,
+  commitCheckpoint,
+  continueMerge,
+  createWorktree,
+  discardUncommitted,
+  getBranchTracking,
+  getWorktreeStatus,
+  invalidateWorktreeStatus,
+  isTransientGitError,
+  listSnapshots,
+  parseStashList,
+  parseTaskLog,
+  parseBlame,
+  propagateGitSetup,
+  pushTask,
+  reflogHasOwnCommits,
+  removeWorktree,
+  repoNameFromUrl,
+  restoreSnapshot,
+  revertFiles,
+  widenSparseCheckout,
+} from './git.js';
 
 function gitError(message: string): Error & { stderr: string; code: number } {
   const error = new Error(`Command failed: git\n${message}`) as Error & {
@@ -78,4 +109,645 @@ describe('createWorktree', () => {
     );
     expect(execFileMock).toHaveBeenCalledTimes(1);
   });
+
+  it('rejects sparse paths outside the worktree before running git', async () => {
+    await expect(
+      createWorktree('/repo', 'task/demo', [], false, undefined, ['../x']),
+    ).rejects.toThrow('Invalid sparse-checkout path: ../x');
+    await expect(
+      createWorktree('/repo', 'task/demo', [], false, undefined, ['/etc']),
+    ).rejects.toThrow('Invalid sparse-checkout path: /etc');
+    expect(execFileMock).not.toHaveBeenCalled();
+  });
+});
+
+describe('widenSparseCheckout', () => {
+  beforeEach(() => {
+    execFileMock.mockReset();
+  });
+
+  function respond(stdout: string) {
+    execFileMock.mockImplementationOnce(
+      (_cmd: string, _args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        cb(null, { stdout, stderr: '' });
+      },
+    );
+  }
+
+  it('adds directories and returns the full list', async () => {
+    respond('true\n');
+    // The repo's lock key
+    respond('/repo/.git\n');
+    respond('');
+    respond('packages/web\npackages/api\n');
+
+    await expect(widenSparseCheckout('/wt', ['packages/api'])).resolves.toEqual([
+      'packages/web',
+      'packages/api',
+    ]);
+    expect(execFileMock).toHaveBeenNthCalledWith(
+      3,
+      'git',
+      ['sparse-checkout', 'add', '--', 'packages/api'],
+      { cwd: '/wt' },
+      expect.any(Function),
+    );
+  });
+
+  it('refuses worktrees that check out everything', async () => {
+    execFileMock.mockImplementationOnce(
+      (_cmd: string, _args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        cb(gitError(''), '', '');
+      },
+    );
+
+    await expect(widenSparseCheckout('/wt', ['packages/api'])).rejects.toThrow(
+      'The worktree is not a sparse checkout',
+    );
+    expect(execFileMock).toHaveBeenCalledTimes(1);
+  });
+});
+
+describe('getWorktreeStatus', () => {
+  beforeEach(() => {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        cb(null, { stdout: args[0] === 'status' ? ' M a.ts\n' : '', stderr: '' });
+      },
+    );
+  });
+
+  const statusCalls = () => execFileMock.mock.calls.filter((c) => c[1][0] === 'status').length;
+
+  it('answers from the cache until the worktree is invalidated', async () => {
+    const expected = { has_committed_changes: false, has_uncommitted_changes: true };
+    await expect(getWorktreeStatus('/wt-cached')).resolves.toEqual(expected);
+    await expect(getWorktreeStatus('/wt-cached')).resolves.toEqual(expected);
+    expect(statusCalls()).toBe(1);
+
+    invalidateWorktreeStatus('/wt-cached');
+    await getWorktreeStatus('/wt-cached');
+    expect(statusCalls()).toBe(2);
+  });
+
+  it('caches each scope separately and skips the cache when asked for a fresh status', async () => {
+    await getWorktreeStatus('/wt-scoped');
+    await getWorktreeStatus('/wt-scoped', 'packages/web');
+    expect(statusCalls()).toBe(2);
+    await getWorktreeStatus('/wt-scoped', undefined, true);
+    expect(statusCalls()).toBe(3);
+  });
+});
+
+describe('commitCheckpoint', () => {
+  function respondTo(outputs: Record<string, string>) {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        cb(null, { stdout: outputs[args.slice(0, 2).join(' ')] ?? '', stderr: '' });
+      },
+    );
+  }
+  const gitCalls = () => execFileMock.mock.calls.map((c) => c[1].join(' '));
+
+  it('commits every change without running hooks', async () => {
+    respondTo({
+      'rev-parse --git-dir': '/repo/.git/worktrees/fix\n/repo/.git\n',
+      'status --porcelain': ' M src/a.ts\n?? notes.md\n',
+      'rev-parse HEAD': 'abc123\n',
+    });
+
+    await expect(
+      commitCheckpoint('/repo/.worktrees/task/fix', (files) => `checkpoint ${files.join(',')}`),
+    ).resolves.toEqual({ commit: 'abc123', files: ['src/a.ts', 'notes.md'] });
+    expect(gitCalls()).toContain('add -A');
+    expect(gitCalls()).toContain('commit --no-verify -q -m checkpoint src/a.ts,notes.md');
+  });
+
+  it("leaves the project's own checkout and clean worktrees alone", async () => {
+    respondTo({ 'rev-parse --git-dir': '.git\n.git\n', 'status --porcelain': ' M a.ts\n' });
+    await expect(commitCheckpoint('/repo', () => 'checkpoint')).resolves.toBeNull();
+
+    respondTo({ 'rev-parse --git-dir': '/repo/.git/worktrees/fix\n/repo/.git\n' });
+    const clean = commitCheckpoint('/repo/.worktrees/task/fix', () => 'checkpoint');
+    await expect(clean).resolves.toBeNull();
+    expect(gitCalls()).not.toContain('add -A');
+  });
+});
+
+describe('snapshots', () => {
+  const ref = (id: string) => `refs/parallel-code/snapshots/t1/${id}`;
+  const refs = [
+    `${ref('aaaaaaaaaaaa')}\0aaaaaaaaaaaa1\x001700000000\0First`,
+    `${ref('bbbbbbbbbbbb')}\0bbbbbbbbbbbb2\x001700000100\0Before refactor`,
+  ].join('\n');
+
+  beforeEach(() => {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        const stdout: Record<string, string> = {
+          'for-each-ref': `${refs}\n`,
+          'write-tree': 'tree1\n',
+          'commit-tree': 'cccccccccccc3\n',
+        };
+        cb(null, { stdout: stdout[args[0]] ?? '.git\n', stderr: '' });
+      },
+    );
+  });
+  const gitCalls = () => execFileMock.mock.calls.map((c) => c[1].join(' '));
+
+  it("lists a task's snapshots newest first", async () => {
+    const snapshots = await listSnapshots('/repo', 't1');
+    expect(snapshots.map((s) => [s.id, s.label, s.created_at])).toEqual([
+      ['bbbbbbbbbbbb', 'Before refactor', 1700000100000],
+      ['aaaaaaaaaaaa', 'First', 1700000000000],
+    ]);
+    await expect(listSnapshots('/repo', '../heads')).rejects.toThrow('Invalid task id');
+  });
+
+  it('snapshots the current state before restoring, leaving the files uncommitted', async () => {
+    const result = await restoreSnapshot('/repo/.worktrees/task/fix', 't1', 'aaaaaaaaaaaa');
+    expect(result.restored.commit).toBe('aaaaaaaaaaaa1');
+    expect(result.backup).toMatchObject({ id: 'cccccccccccc', label: 'Before restoring "First"' });
+
+    const calls = gitCalls();
+    const backup = calls.indexOf(`update-ref ${ref('cccccccccccc')} cccccccccccc3`);
+    const reset = calls.indexOf('reset --hard -q aaaaaaaaaaaa1');
+    expect(backup).toBeGreaterThan(-1);
+    expect(reset).toBeGreaterThan(backup);
+    expect(calls.slice(reset + 1)).toEqual(['clean -fdq', 'reset -q aaaaaaaaaaaa1^']);
+  });
+
+  it('refuses unknown snapshots without touching the worktree', async () => {
+    await expect(restoreSnapshot('/repo', 't1', 'nope')).rejects.toThrow('Snapshot not found');
+    expect(gitCalls().some((c) => c.startsWith('reset'))).toBe(false);
+  });
+});
+
+describe('continueMerge', () => {
+  function inRebase(unmerged: string[]) {
+    const gitDir = fs.mkdtempSync(path.join(os.tmpdir(), 'parallel-code-test-'));
+    fs.mkdirSync(path.join(gitDir, 'rebase-merge'));
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        const stdout: Record<string, string> = {
+          'rev-parse': `${gitDir}\n`,
+          diff: unmerged.join('\n'),
+        };
+        cb(null, { stdout: stdout[args[0]] ?? '', stderr: '' });
+      },
+    );
+    return gitDir;
+  }
+
+  it('continues the rebase without opening an editor', async () => {
+    const gitDir = inRebase([]);
+    await continueMerge('/repo/.worktrees/task/fix');
+    const call = execFileMock.mock.calls.find((c) => c[1][0] === 'rebase');
+    expect(call?.[1]).toEqual(['rebase', '--continue']);
+    expect(call?.[2].env.GIT_EDITOR).toBe('true');
+    fs.rmSync(gitDir, { recursive: true });
+  });
+
+  it('refuses while files are still conflicted', async () => {
+    const gitDir = inRebase(['src/a.ts']);
+    await expect(continueMerge('/repo/.worktrees/task/fix')).rejects.toMatchObject({
+      code: 'merge_conflict',
+      context: { files: ['src/a.ts'] },
+    });
+    expect(execFileMock.mock.calls.some((c) => c[1][0] === 'rebase')).toBe(false);
+    fs.rmSync(gitDir, { recursive: true });
+  });
+});
+
+describe('propagateGitSetup', () => {
+  function inRepo(config: Record<string, string>) {
+    const root = fs.mkdtempSync(path.join(os.tmpdir(), 'parallel-code-test-'));
+    const worktree = path.join(root, '.worktrees', 'task');
+    fs.mkdirSync(path.join(root, '.husky', '_'), { recursive: true });
+    fs.mkdirSync(worktree, { recursive: true });
+    fs.writeFileSync(path.join(root, '.husky', '_', 'pre-commit'), '#!/bin/sh\n');
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        if (args[0] === 'rev-parse') return cb(null, { stdout: '.husky/_\n', stderr: '' });
+        if (args[1] === '--get-all') {
+          const value = config[args[2]];
+          return value === undefined
+            ? cb(Object.assign(new Error('exit 1'), { code: 1 }))
+            : cb(null, { stdout: `${value}\n`, stderr: '' });
+        }
+        cb(null, { stdout: '', stderr: '' });
+      },
+    );
+    return { root, worktree };
+  }
+
+  it('copies a relative hooks directory the worktree lacks', async () => {
+    const { root, worktree } = inRepo({});
+    await propagateGitSetup(root, worktree, { hooks: 'copy', configKeys: [] });
+    expect(fs.existsSync(path.join(worktree, '.husky', '_', 'pre-commit'))).toBe(true);
+    fs.rmSync(root, { recursive: true });
+  });
+
+  it('points core.hooksPath at the main checkout and copies config values', async () => {
+    const { root, worktree } = inRepo({ 'user.email': 'dev@example.com' });
+    await propagateGitSetup(root, worktree, {
+      hooks: 'hooks_path',
+      configKeys: ['user.email', 'commit.gpgsign'],
+    });
+    const added = execFileMock.mock.calls
+      .map((c) => c[1] as string[])
+      .filter((args) => args[2] === '--add');
+    expect(added).toEqual([
+      ['config', '--worktree', '--add', 'core.hooksPath', path.join(root, '.husky', '_')],
+      ['config', '--worktree', '--add', 'user.email', 'dev@example.com'],
+    ]);
+    expect(fs.existsSync(path.join(worktree, '.husky'))).toBe(false);
+    fs.rmSync(root, { recursive: true });
+  });
+});
+
+describe('pushTask', () => {
+  function withUpstream(upstream: string | null) {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        if (args[0] === 'rev-parse' && upstream === null) return cb(gitError('no upstream'));
+        cb(null, { stdout: args[0] === 'rev-parse' ? `${upstream}\n` : '', stderr: '' });
+      },
+    );
+  }
+  const pushArgs = () => execFileMock.mock.calls.find((c) => c[1][0] === 'push')?.[1];
+  const configArgs = () =>
+    execFileMock.mock.calls.filter((c) => c[1][0] === 'config').map((c) => c[1]);
+
+  it('sets the upstream of a branch pushed for the first time', async () => {
+    withUpstream(null);
+    await pushTask('/repo', 'task/fix');
+    expect(pushArgs()).toEqual(['push', 'origin', '--', 'task/fix']);
+    expect(configArgs()).toEqual([
+      ['config', 'branch.task/fix.remote', 'origin'],
+      ['config', 'branch.task/fix.merge', 'refs/heads/task/fix'],
+    ]);
+  });
+
+  it('keeps a branch tracking its base', async () => {
+    withUpstream('origin/main');
+    await pushTask('/repo', 'task/fix');
+    expect(pushArgs()).toEqual(['push', 'origin', '--', 'task/fix']);
+    expect(configArgs()).toEqual([]);
+  });
+});
+
+describe('repo lock', () => {
+  beforeEach(() => {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        const stdout = args[1] === '--git-common-dir' ? '/repo/.git\n' : '';
+        // Slow enough for another operation to start meanwhile if nothing stops it
+        setTimeout(() => cb(null, { stdout, stderr: '' }), args[0] === 'add' ? 20 : 0);
+      },
+    );
+  });
+  const gitCommands = () =>
+    execFileMock.mock.calls.map((c) => (c[1] as string[])[0]).filter((a) => a !== 'rev-parse');
+
+  it('runs writes to worktrees of the same repo one at a time', async () => {
+    await Promise.all([commitAll('/repo/wt1', 'wip'), discardUncommitted('/repo/wt2')]);
+    expect(gitCommands()).toEqual(['add', 'commit', 'checkout', 'clean']);
+  });
+
+  it('lets reads run alongside a write', async () => {
+    const write = commitAll('/repo/wt1', 'wip');
+    await getBranchTracking('/repo/wt2');
+    expect(gitCommands()).not.toContain('commit');
+    await write;
+  });
+
+  it('pushes without waiting for writes', async () => {
+    const write = commitAll('/repo/wt1', 'wip');
+    const push = pushTask('/repo', 'task/fix');
+    await vi.waitFor(() => expect(gitCommands()).toContain('push'));
+    expect(gitCommands()).not.toContain('commit');
+    await Promise.all([write, push]);
+  });
+});
+
+describe('assertBranchDeletable', () => {
+  function withBranch(
+    counts: { unmerged: number; unpushed: number },
+    remotes = 'origin\n',
+    missingRef?: string,
+  ) {
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        if (args[0] === 'rev-parse' && missingRef && args.includes(missingRef)) {
+          return cb(gitError('fatal: Needed a single revision\n'), '', '');
+        }
+        let stdout = '';
+        if (args[0] === 'rev-parse') stdout = 'abc123def4567890\n';
+        if (args[0] === 'symbolic-ref') stdout = 'refs/remotes/origin/main\n';
+        if (args[0] === 'remote') stdout = remotes;
+        if (args[0] === 'rev-list') {
+          stdout = `${args.includes('--remotes') ? counts.unpushed : counts.unmerged}\n`;
+        }
+        cb(null, { stdout, stderr: '' });
+      },
+    );
+  }
+
+  it('lets a branch whose commits are all in main go', async () => {
+    withBranch({ unmerged: 0, unpushed: 0 });
+    await expect(assertBranchDeletable('/repo-risk', 'task/done')).resolves.toBeUndefined();
+  });
+
+  it('refuses with the tip and what would be lost', async () => {
+    withBranch({ unmerged: 3, unpushed: 2 });
+    const err = await assertBranchDeletable('/repo-risk', 'task/wip').catch((e: unknown) => e);
+    expect(err).toMatchObject({
+      code: 'unmerged_commits',
+      context: { branch: 'task/wip', commit: 'abc123def4567890', unmerged: 3, unpushed: 2 },
+    });
+    expect((err as Error).message).toBe(
+      'Branch task/wip has 3 commits not merged into the main branch, 2 of them not pushed ' +
+        '(tip abc123def456)',
+    );
+  });
+
+  it("doesn't count pushes without a remote", async () => {
+    withBranch({ unmerged: 1, unpushed: 1 }, '');
+    await expect(assertBranchDeletable('/repo-risk', 'task/wip')).rejects.toMatchObject({
+      context: { unpushed: null },
+    });
+  });
+
+  const countedAgainst = () =>
+    execFileMock.mock.calls
+      .map((call) => call[1] as string[])
+      .filter((args) => args[0] === 'rev-list')
+      .map((args) => args[args.indexOf('--not') + 1]);
+
+  it("counts commits against the task's base branch", async () => {
+    withBranch({ unmerged: 2, unpushed: 0 });
+    const err = await assertBranchDeletable('/repo-risk', 'task/wip', 'develop').catch(
+      (e: unknown) => e,
+    );
+    expect((err as Error).message).toBe(
+      'Branch task/wip has 2 commits not merged into develop, all of them pushed ' +
+        '(tip abc123def456)',
+    );
+    expect(countedAgainst()).toEqual(['develop', 'develop']);
+  });
+
+  it('falls back to the main branch once the base branch is gone', async () => {
+    withBranch({ unmerged: 1, unpushed: 1 }, 'origin\n', 'develop');
+    const err = await assertBranchDeletable('/repo-risk', 'task/wip', 'develop').catch(
+      (e: unknown) => e,
+    );
+    expect((err as Error).message).toContain('not merged into the main branch');
+    expect(countedAgainst()).toEqual(['main', 'main']);
+  });
+});
+
+describe('cherryPickCommits', () => {
+  beforeEach(() => {
+    execFileMock.mockReset();
+  });
+
+  it('stops at the first conflict and reports the conflicting files', async () => {
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        if (args[0] === 'rev-parse') return cb(null, { stdout: '.git\n', stderr: '' });
+        if (args[0] === 'diff') return cb(null, { stdout: 'src/a.ts\nsrc/b.ts\n', stderr: '' });
+        if (args[0] === 'cherry-pick' && args[2] === 'bbbbbbb') {
+          return cb(gitError('error: could not apply bbbbbbb\n'), '', '');
+        }
+        cb(null, { stdout: '', stderr: '' });
+      },
+    );
+
+    await expect(cherryPickCommits('/wt', ['aaaaaaa', 'bbbbbbb', 'ccccccc'])).resolves.toEqual({
+      applied: ['aaaaaaa'],
+      conflict: { commit: 'bbbbbbb', conflicting_files: ['src/a.ts', 'src/b.ts'] },
+    });
+    expect(execFileMock).toHaveBeenCalledWith(
+      'git',
+      ['cherry-pick', '--abort'],
+      { cwd: '/wt' },
+      expect.any(Function),
+    );
+    expect(execFileMock).not.toHaveBeenCalledWith(
+      'git',
+      ['cherry-pick', '-x', 'ccccccc'],
+      expect.anything(),
+      expect.any(Function),
+    );
+  });
+
+  it('refuses to pick into a dirty worktree', async () => {
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        if (args[0] === 'status') return cb(null, { stdout: ' M file.ts\n', stderr: '' });
+        cb(null, { stdout: '.git\n', stderr: '' });
+      },
+    );
+
+    await expect(cherryPickCommits('/wt', ['aaaaaaa'])).rejects.toThrow('uncommitted changes');
+  });
+});
+
+describe('parseStashList', () => {
+  it('keeps only stashes made on the given branch', () => {
+    const output = [
+      'stash@{0}\u00001700000100\u0000On task/other: unrelated',
+      'stash@{1}\u00001700000050\u0000On task/demo: before rebase',
+      'stash@{2}\u00001700000000\u0000WIP on task/demo: abc123 msg',
+      'stash@{3}\u00001690000000\u0000On task/demo-2: similar name',
+      '',
+    ].join('\n');
+    expect(parseStashList(output, 'task/demo')).toEqual([
+      { index: 1, message: 'before rebase', created_at: 1700000050000 },
+      { index: 2, message: 'abc123 msg', created_at: 1700000000000 },
+    ]);
+  });
+});
+
+describe('parseTaskLog', () => {
+  it('sums each commit\'s numstat, counting binary files without lines', () => {
+    const stdout = [
+      '\x1eabc123\x1fAda\x1fada@example.com\x1f1700000000\x1fAdd login',
+      '',
+      '10\t2\tsrc/login.ts',
+      '-\t-\tassets/logo.png',
+      '\x1edef456\x1fAda\x1fada@example.com\x1f1699990000\x1fEmpty commit',
+      '',
+    ].join('\n');
+    expect(parseTaskLog(stdout)).toEqual([
+      {
+        sha: 'abc123',
+        author_name: 'Ada',
+        author_email: 'ada@example.com',
+        date: 1700000000000,
+        subject: 'Add login',
+        files_changed: 2,
+        insertions: 10,
+        deletions: 2,
+      },
+      expect.objectContaining({ sha: 'def456', files_changed: 0, subject: 'Empty commit' }),
+    ]);
+  });
+});
+
+describe('repoNameFromUrl', () => {
+  it('takes the last path segment without .git', () => {
+    expect(repoNameFromUrl('https://github.com/owner/repo.git')).toBe('repo');
+    expect(repoNameFromUrl('git@github.com:owner/repo')).toBe('repo');
+    expect(repoNameFromUrl('git@host:repo.git')).toBe('repo');
+    expect(repoNameFromUrl('ssh://git@host:2222/srv/git/repo/')).toBe('repo');
+    expect(repoNameFromUrl('C:\\src\\repo')).toBe('repo');
+  });
+
+  it('returns null when there is no name', () => {
+    expect(repoNameFromUrl('')).toBeNull();
+    expect(repoNameFromUrl('.git')).toBeNull();
+  });
+});
+
+describe('parseBlame', () => {
+  it('repeats commit details on later lines and flags the branch\'s own lines', () => {
+    const base = 'a'.repeat(40);
+    const task = 'b'.repeat(40);
+    const stdout = [
+      `${base} 1 1 1`,
+      'author Ada',
+      'author-time 1700000000',
+      'summary Initial commit',
+      'filename src/a.ts',
+      '\tconst a = 1;',
+      `${task} 2 2 1`,
+      'author Agent',
+      'author-time 1700000100',
+      'summary Add b',
+      'filename src/a.ts',
+      '\tconst b = 2;',
+      `${base} 2 3 1`,
+      'filename src/a.ts',
+      '\tconst c = 3;',
+      `${'0'.repeat(40)} 4 4 1`,
+      'author Not Committed Yet',
+      'author-time 1700000200',
+      'summary Version of src/a.ts from src/a.ts',
+      'filename src/a.ts',
+      '\tconst d = 4;',
+      '',
+    ].join('\n');
+    const lines = parseBlame(stdout, new Set([task]));
+    expect(lines.map((l) => [l.line, l.author, l.on_branch])).toEqual([
+      [1, 'Ada', false],
+      [2, 'Agent', true],
+      [3, 'Ada', false],
+      [4, 'Not Committed Yet', true],
+    ]);
+    expect(lines[2]).toMatchObject({ content: 'const c = 3;', date: 1700000000000 });
+  });
+});
+
+describe('reflogHasOwnCommits', () => {
+  it('counts commits and cherry-picks, not creation or rebases', () => {
+    expect(reflogHasOwnCommits(['commit: fix login', 'branch: Created from HEAD'])).toBe(true);
+    expect(reflogHasOwnCommits(['cherry-pick: add test', 'branch: Created from HEAD'])).toBe(
+      true,
+    );
+    const rebased = ['rebase (finish): refs/heads/task/x onto abc', 'branch: Created from HEAD'];
+    expect(reflogHasOwnCommits(rebased)).toBe(false);
+    expect(reflogHasOwnCommits([''])).toBe(false);
+  });
+});
+
+describe('transient git failures', () => {
+  beforeEach(() => {
+    execFileMock.mockReset();
+  });
+
+  it('recognizes lock contention as transient', () => {
+    expect(
+      isTransientGitError(
+        gitError("fatal: Unable to create '/repo/.git/index.lock': File exists.\n"),
+      ),
+    ).toBe(true);
+    expect(isTransientGitError(gitError("error: cannot lock ref 'refs/heads/main'\n"))).toBe(true);
+    expect(isTransientGitError(gitError("fatal: invalid reference: 'nope'\n"))).toBe(false);
+  });
+
+  it('retries a command that hit index.lock', async () => {
+    execFileMock
+      .mockImplementationOnce(
+        (_cmd: string, _args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+          cb(gitError("fatal: Unable to create '/repo/.git/index.lock': File exists.\n"), '', '');
+        },
+      )
+      .mockImplementationOnce(
+        (_cmd: string, _args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+          cb(null, { stdout: '', stderr: '' });
+        },
+      );
+
+    await expect(createWorktree('/repo', 'task/demo', [])).resolves.toEqual({
+      path: '/repo/.worktrees/task/demo',
+      branch: 'task/demo',
+    });
+    expect(execFileMock).toHaveBeenCalledTimes(2);
+    expect(execFileMock).toHaveBeenNthCalledWith(
+      2,
+      'git',
+      ['worktree', 'add', '-b', 'task/demo', '/repo/.worktrees/task/demo'],
+      { cwd: '/repo' },
+      expect.any(Function),
+    );
+  });
+});
+
+describe('remote projects', () => {
+  const repo = '/nonexistent/ssh/box/srv/repo';
+
+  /** Remote command lines run, failing those that match `failing`. */
+  function runRemote(failing: RegExp): string[] {
+    const commands: string[] = [];
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (cmd: string, args: string[], _opts: unknown, cb: (...cbArgs: unknown[]) => void) => {
+        expect(cmd).toBe('ssh');
+        const command = args[args.length - 1];
+        commands.push(command);
+        if (failing.test(command)) cb(gitError('fatal: failed'), '', '');
+        else cb(null, { stdout: '', stderr: '' });
+      },
+    );
+    return commands;
+  }
+
+  it('removes a worktree and its branch over ssh', async () => {
+    const commands = runRemote(/worktree remove/);
+    await removeWorktree(repo, 'task/x', true);
+    expect(commands).toEqual([
+      'cd /srv/repo && exec git worktree remove --force /srv/repo/.worktrees/task/x',
+      'cd /srv/repo && exec rm -rf -- /srv/repo/.worktrees/task/x',
+      'cd /srv/repo && exec git worktree prune',
+      'cd /srv/repo && exec git branch -D -- task/x',
+    ]);
+  });
+
+  it('deletes files a revert removes over ssh', async () => {
+    const commands = runRemote(/cat-file/);
+    await revertFiles(`${repo}/.worktrees/task/x`, ['new.ts']);
+    expect(commands).toContain(
+      'cd /srv/repo/.worktrees/task/x && exec rm -rf -- /srv/repo/.worktrees/task/x/new.ts',
+    );
+  });
 });
@@ -1,9 +1,67 @@
-import { execFile } from 'child_process';
+import { execFile, spawn, type ExecFileOptions } from 'child_process';
+import { AsyncLocalStorage } from 'async_hooks';
 import { promisify } from 'util';
 import fs from 'fs';
+import os from 'os';
 import path from 'path';
+import {
+  getSettings,
+  getWorktreePath,
+  onSettingsChanged,
+  type WorktreeHooksMode,
+} from './settings.js';
+import { parseUnifiedDiff, selectHunks, type StructuredFileDiff } from './diff.js';
+import { parseConflictRegions, type ConflictRegion } from './conflicts.js';
+import { createLogger } from './log.js';
+import { AppError, isAppError, stderrOf } from './errors.js';
+import { materializeMcpConfig } from './mcp.js';
+import { currentGitOperation, operationCancelledError } from './git-operations.js';
+import { remoteCommand, remoteTargetFor } from './ssh.js';
+
+const execFileAsync = promisify(execFile);
+const log = createLogger('git');
+
+// --- Transient failure retry ---
+
+const TRANSIENT_RETRIES = 4;
+const TRANSIENT_BACKOFF_MS = 100;
 
-const exec = promisify(execFile);
+/**
+ * Lock contention from a concurrent git process (another task's agent, an
+ * editor's git integration) — worth retrying rather than surfacing.
+ */
+export function isTransientGitError(error: unknown): boolean {
+  const text = errorToText(error);
+  return (
+    text.includes('index.lock') ||
+    text.includes('another git process seems to be running') ||
+    text.includes('cannot lock ref') ||
+    /unable to create '[^']*\.lock'/.test(text)
+  );
+}
+
+/** execFile, retried with exponential backoff on transient git failures. */
+async function exec(
+  file: string,
+  args: string[],
+  options: ExecFileOptions = {},
+): Promise<{ stdout: string; stderr: string }> {
+  // Remote projects' commands run on their machine, started from the local home directory
+  const remote = remoteCommand(file, args, options.cwd, options.env);
+  const remoteOptions = { ...options, cwd: os.homedir(), env: undefined };
+  for (let attempt = 0; ; attempt++) {
+    try {
+      return remote
+        ? await execFileAsync(remote.file, remote.args, remoteOptions)
+        : await execFileAsync(file, args, options);
+    } catch (error) {
+      if (attempt >= TRANSIENT_RETRIES || !isTransientGitError(error)) throw error;
+      const delay = TRANSIENT_BACKOFF_MS * 2 ** attempt;
+      log.info(`git ${args[0]} hit a lock, retrying in ${delay}ms`, error);
+      await new Promise((resolve) => setTimeout(resolve, delay));
+    }
+  }
+}
 
 // --- TTL Caches ---
 
@@ -20,12 +78,114 @@ const MAX_BUFFER = 10 * 1024 * 1024; // 10MB
 
 function invalidateMergeBaseCache(): void {
   mergeBaseCache.clear();
+  // Ahead/behind counts are taken against the base branch
+  statusCache.clear();
 }
 
+// A configured base branch changes what detectMainBranch returns
+onSettingsChanged(() => {
+  mainBranchCache.clear();
+  invalidateMergeBaseCache();
+});
+
 function cacheKey(p: string): string {
   return p.replace(/\/+$/, '');
 }
 
+// --- Worktree status cache ---
+
+interface WorktreeStatus {
+  has_committed_changes: boolean;
+  has_uncommitted_changes: boolean;
+}
+
+interface StatusCacheEntry {
+  fingerprint: string;
+  expiresAt: number;
+  /** Keyed by task scope ('' for the whole worktree). */
+  status: Map<string, WorktreeStatus>;
+  counts: { ahead: number; behind: number } | null;
+}
+
+/** Edits in a worktree nobody watches go unnoticed, so its status is re-checked this often. */
+const STATUS_TTL = 15_000;
+/** A watched worktree's edits invalidate it, and git's own writes change the fingerprint. */
+const WATCHED_STATUS_TTL = 10 * 60_000;
+
+const statusCache = new Map<string, StatusCacheEntry>();
+const watchedWorktrees = new Set<string>();
+
+/** Forget a worktree's cached status, e.g. because its files changed. */
+export function invalidateWorktreeStatus(worktreePath: string): void {
+  statusCache.delete(cacheKey(worktreePath));
+}
+
+/** Whether the worktree watcher reports edits here, which lets the status live longer. */
+export function setWorktreeStatusWatched(worktreePath: string, watched: boolean): void {
+  const key = cacheKey(worktreePath);
+  if (watched) watchedWorktrees.add(key);
+  else watchedWorktrees.delete(key);
+  statusCache.delete(key);
+}
+
+async function readGitFile(file: string): Promise<string | null> {
+  return fs.promises.readFile(file, 'utf8').then(
+    (s) => s.trim(),
+    () => null,
+  );
+}
+
+/**
+ * Modification times of what commits, checkouts, stashes and branch updates
+ * write, in the worktree's git dir and the repo's shared one. Reading them is
+ * far cheaper than `git status`, and any change means the cache is stale.
+ */
+async function statusFingerprint(worktreePath: string): Promise<string> {
+  const dotGit = path.join(worktreePath, '.git');
+  const link = await readGitFile(dotGit);
+  const gitDir = link?.startsWith('gitdir:')
+    ? path.resolve(worktreePath, link.slice('gitdir:'.length).trim())
+    : dotGit;
+  const common = await readGitFile(path.join(gitDir, 'commondir'));
+  const commonDir = common ? path.resolve(gitDir, common) : gitDir;
+  const files = [
+    path.join(gitDir, 'HEAD'),
+    path.join(gitDir, 'index'),
+    path.join(gitDir, 'logs', 'HEAD'),
+    path.join(commonDir, 'packed-refs'),
+    path.join(commonDir, 'refs', 'heads'),
+    path.join(commonDir, 'logs', 'HEAD'),
+  ];
+  const times = await Promise.all(
+    files.map((f) =>
+      fs.promises.stat(f).then(
+        (s) => s.mtimeMs,
+        () => 0,
+      ),
+    ),
+  );
+  return times.join(':');
+}
+
+/** The worktree's cache entry, replaced by an empty one when it is stale. */
+async function statusCacheEntry(worktreePath: string): Promise<StatusCacheEntry> {
+  const key = cacheKey(worktreePath);
+  const fingerprint = await statusFingerprint(worktreePath);
+  const cached = statusCache.get(key);
+  if (cached && cached.fingerprint === fingerprint && cached.expiresAt > Date.now()) {
+    return cached;
+  }
+  const ttl = watchedWorktrees.has(key) ? WATCHED_STATUS_TTL : STATUS_TTL;
+  const entry = { fingerprint, expiresAt: Date.now() + ttl, status: new Map(), counts: null };
+  statusCache.set(key, entry);
+  return entry;
+}
+
+/** Whether the entry is still current, so a result computed for it may be stored. */
+function isCurrentStatusEntry(worktreePath: string, entry: StatusCacheEntry): boolean {
+  return statusCache.get(cacheKey(worktreePath)) === entry;
+}
+
 function errorToText(error: unknown): string {
   if (typeof error === 'string') return error;
   if (!error || typeof error !== 'object') return '';
@@ -38,13 +198,56 @@ function isBranchAlreadyExistsError(error: unknown): boolean {
   return errorToText(error).includes('already exists');
 }
 
+/** Map a failed `git worktree add` to an AppError when the cause is recognizable. */
+function worktreeAddError(error: unknown, worktreePath: string, branch: string): unknown {
+  const text = errorToText(error);
+  const context = { path: worktreePath, branch, stderr: stderrOf(error) };
+  if (text.includes('is already checked out at') || text.includes('is already used by worktree')) {
+    return new AppError(
+      'branch_checked_out_elsewhere',
+      `Branch ${branch} is already checked out in another worktree`,
+      context,
+    );
+  }
+  if (text.includes('already exists')) {
+    return new AppError(
+      'worktree_exists',
+      `Worktree path already exists: ${worktreePath}`,
+      context,
+    );
+  }
+  return error;
+}
+
 // --- Worktree lock serialization ---
+//
+// Everything that writes to a repository (refs, the index, worktree metadata,
+// config) runs under its repo's lock, one operation at a time, so concurrent
+// task creation, deletion and merges can't trip over each other's index.lock
+// or half-registered worktrees. Read-only queries (status, diffs, logs) don't
+// take it and run alongside.
 
 const worktreeLocks = new Map<string, Promise<void>>();
+// Keys held by the current async call chain, so nested locked calls
+// (e.g. mergeTask → removeWorktree) don't wait on themselves.
+const heldLocks = new AsyncLocalStorage<Set<string>>();
+
+/** The key detectRepoLockKey yields for a main checkout, without spawning git. */
+function lockKeyForRepoRoot(repoRoot: string): string {
+  const gitDir = path.join(repoRoot, '.git');
+  try {
+    return fs.realpathSync(gitDir);
+  } catch {
+    return gitDir;
+  }
+}
 
 function withWorktreeLock<T>(key: string, fn: () => Promise<T>): Promise<T> {
+  const held = heldLocks.getStore();
+  if (held?.has(key)) return fn();
+  const run = () => heldLocks.run(new Set([...(held ?? []), key]), fn);
   const prev = worktreeLocks.get(key) ?? Promise.resolve();
-  const next = prev.then(fn, fn);
+  const next = prev.then(run, run);
   const voidNext = next.then(
     () => {},
     () => {},
@@ -58,6 +261,12 @@ function withWorktreeLock<T>(key: string, fn: () => Promise<T>): Promise<T> {
   return next;
 }
 
+/** Run `fn` under the lock of the repo `p` (a main checkout or any of its worktrees) is in. */
+export async function withRepoLock<T>(p: string, fn: () => Promise<T>): Promise<T> {
+  const key = await detectRepoLockKey(p).catch(() => p);
+  return withWorktreeLock(key, fn);
+}
+
 // --- Symlink candidates ---
 
 const SYMLINK_CANDIDATES = [
@@ -91,6 +300,17 @@ async function detectMainBranch(repoRoot: string): Promise<string> {
 }
 
 async function detectMainBranchUncached(repoRoot: string): Promise<string> {
+  // Explicitly configured base branch wins when it exists in this repo
+  const configured = getSettings().base_branch;
+  if (configured) {
+    try {
+      await exec('git', ['rev-parse', '--verify', '--', configured], { cwd: repoRoot });
+      return configured;
+    } catch {
+      /* not in this repo — auto-detect */
+    }
+  }
+
   // Try remote HEAD reference first
   try {
     const { stdout } = await exec('git', ['symbolic-ref', 'refs/remotes/origin/HEAD'], {
@@ -162,13 +382,13 @@ async function detectMergeBase(repoRoot: string, head?: string): Promise<string>
   return result;
 }
 
-async function pinHead(worktreePath: string): Promise<string> {
-  try {
-    const { stdout } = await exec('git', ['rev-parse', 'HEAD'], { cwd: worktreePath });
-    return stdout.trim();
-  } catch {
-    return 'HEAD';
-  }
+export async function getHeadCommit(worktreePath: string): Promise<string> {
+  const { stdout } = await exec('git', ['rev-parse', 'HEAD'], { cwd: worktreePath });
+  return stdout.trim();
+}
+
+function pinHead(worktreePath: string): Promise<string> {
+  return getHeadCommit(worktreePath).catch(() => 'HEAD');
 }
 
 async function detectRepoLockKey(p: string): Promise<string> {
@@ -284,7 +504,7 @@ function shallowSymlinkDir(source: string, target: string, exclude: Set<string>)
   try {
     entries = fs.readdirSync(source, { withFileTypes: true });
   } catch (err) {
-    console.warn(`Failed to read directory ${source} for shallow-symlink:`, err);
+    log.warn(`Failed to read directory ${source} for shallow-symlink:`, err);
     return;
   }
   for (const entry of entries) {
@@ -295,55 +515,182 @@ function shallowSymlinkDir(source: string, target: string, exclude: Set<string>)
       if (!fs.existsSync(dst)) {
         fs.symlinkSync(src, dst);
       }
-    } catch {
-      /* ignore */
+    } catch (err) {
+      log.debug(`Failed to symlink ${src} into worktree`, err);
     }
   }
 }
 
+/** Whether the checked-out tree stores files in Git LFS. */
+function usesLfs(worktreePath: string): boolean {
+  try {
+    const attributes = fs.readFileSync(path.join(worktreePath, '.gitattributes'), 'utf8');
+    return attributes.includes('filter=lfs');
+  } catch {
+    return false;
+  }
+}
+
 // --- Public functions (used by tasks.ts and register.ts) ---
 
+export interface CreatedWorktree {
+  path: string;
+  branch: string;
+  /** Set when the worktree was created but its submodules failed to check out. */
+  submodule_error?: string;
+  /** Set when the worktree was created but its Git LFS files couldn't be downloaded. */
+  lfs_error?: string;
+  /** The branch's upstream, e.g. `origin/main`, when it has one. */
+  upstream?: string;
+}
+
 export async function createWorktree(
   repoRoot: string,
   branchName: string,
   symlinkDirs: string[],
   forceClean = false,
-): Promise<{ path: string; branch: string }> {
-  const worktreePath = `${repoRoot}/.worktrees/${branchName}`;
+  baseRef?: string,
+  /** Directories to check out (cone mode); everything when empty. */
+  sparsePaths: string[] = [],
+): Promise<CreatedWorktree> {
+  checkSparsePaths(sparsePaths);
+  const lockKey = lockKeyForRepoRoot(repoRoot);
+  const worktreePath = getWorktreePath(repoRoot, branchName);
+  // Inside a git operation the files are checked out separately so progress can be reported,
+  // and a sparse worktree needs its patterns set before any file is written
+  const deferCheckout = currentGitOperation() !== undefined || sparsePaths.length > 0;
+  // Leaves LFS files as pointers instead of downloading them during checkout
+  const lfsEnv = getSettings().lfs_fetch
+    ? undefined
+    : { ...process.env, GIT_LFS_SKIP_SMUDGE: '1' };
+
+  // Only registering the worktree and writing the repo's shared .git (submodules, LFS) take
+  // the repo lock; checkouts and the rest run alongside other tasks being created
+  const createdBranch = await withWorktreeLock(lockKey, async () => {
+    if (forceClean) {
+      // Clean up stale worktree/branch from a previous session that wasn't properly removed
+      if (fs.existsSync(worktreePath)) {
+        try {
+          await exec('git', ['worktree', 'remove', '--force', worktreePath], { cwd: repoRoot });
+        } catch {
+          fs.rmSync(worktreePath, { recursive: true, force: true });
+        }
+        await exec('git', ['worktree', 'prune'], { cwd: repoRoot }).catch((e) =>
+          log.warn('git worktree prune failed:', e),
+        );
+      }
+
+      // Delete stale branch ref if it still exists
+      try {
+        await exec('git', ['branch', '-D', branchName], { cwd: repoRoot });
+      } catch {
+        // Branch doesn't exist — fine
+      }
+    }
+
+    const noCheckout = deferCheckout ? ['--no-checkout'] : [];
+    const addOptions = lfsEnv ? { cwd: repoRoot, env: lfsEnv } : { cwd: repoRoot };
+
+    // Try -b first (new branch), fall back to existing branch when it already exists.
+    try {
+      const args = ['worktree', 'add', ...noCheckout, '-b', branchName, worktreePath];
+      if (baseRef) args.push(baseRef);
+      await exec('git', args, addOptions);
+      return true;
+    } catch (error) {
+      if (!isBranchAlreadyExistsError(error)) throw error;
+      try {
+        await exec('git', ['worktree', 'add', ...noCheckout, worktreePath, branchName], addOptions);
+      } catch (fallbackError) {
+        throw worktreeAddError(fallbackError, worktreePath, branchName);
+      }
+      return false;
+    }
+  });
 
-  if (forceClean) {
-    // Clean up stale worktree/branch from a previous session that wasn't properly removed
-    if (fs.existsSync(worktreePath)) {
+  // Leave nothing half checked out behind
+  const discardWorktree = () =>
+    withWorktreeLock(lockKey, async () => {
       try {
         await exec('git', ['worktree', 'remove', '--force', worktreePath], { cwd: repoRoot });
       } catch {
         fs.rmSync(worktreePath, { recursive: true, force: true });
+        await exec('git', ['worktree', 'prune'], { cwd: repoRoot }).catch((e) =>
+          log.warn('git worktree prune failed:', e),
+        );
       }
-      await exec('git', ['worktree', 'prune'], { cwd: repoRoot }).catch((e) =>
-        console.warn('git worktree prune failed:', e),
-      );
-    }
+      if (createdBranch) {
+        await exec('git', ['branch', '-D', '--', branchName], { cwd: repoRoot }).catch((e) =>
+          log.warn(`Failed to delete branch ${branchName} after a failed checkout:`, e),
+        );
+      }
+    });
 
-    // Delete stale branch ref if it still exists
+  if (deferCheckout) {
     try {
-      await exec('git', ['branch', '-D', branchName], { cwd: repoRoot });
-    } catch {
-      // Branch doesn't exist — fine
+      if (sparsePaths.length > 0) {
+        await exec('git', ['sparse-checkout', 'set', '--cone', '--', ...sparsePaths], {
+          cwd: worktreePath,
+        });
+      }
+      const checkout = ['checkout', '--progress', '--force'];
+      await runWithProgress(worktreePath, checkout, () => {}, lfsEnv);
+    } catch (error) {
+      await discardWorktree();
+      throw error;
     }
   }
 
-  // Try -b first (new branch), fall back to existing branch when it already exists.
-  try {
-    await exec('git', ['worktree', 'add', '-b', branchName, worktreePath], { cwd: repoRoot });
-  } catch (error) {
-    if (!isBranchAlreadyExistsError(error)) throw error;
-    await exec('git', ['worktree', 'add', worktreePath, branchName], { cwd: repoRoot });
-  }
+  const { submoduleError, lfsError } = await withWorktreeLock(lockKey, async () => {
+    // A failed submodule update leaves a usable worktree, so it's reported rather than thrown
+    let submoduleError: string | undefined;
+    if (getSettings().init_submodules && fs.existsSync(path.join(worktreePath, '.gitmodules'))) {
+      try {
+        await runWithProgress(worktreePath, [
+          'submodule',
+          'update',
+          '--init',
+          '--recursive',
+          '--progress',
+        ]);
+      } catch (error) {
+        if (isAppError(error, 'operation_cancelled')) {
+          await discardWorktree();
+          throw error;
+        }
+        log.warn(`Submodule update failed in ${worktreePath}:`, error);
+        submoduleError = error instanceof Error ? error.message : String(error);
+      }
+    }
+
+    // Git LFS may not be set up for this user, leaving pointer files agents would overwrite
+    let lfsError: string | undefined;
+    if (getSettings().lfs_fetch && usesLfs(worktreePath)) {
+      try {
+        await exec('git', ['lfs', 'install', '--local'], { cwd: worktreePath });
+        await runWithProgress(worktreePath, ['lfs', 'pull']);
+      } catch (error) {
+        if (isAppError(error, 'operation_cancelled')) {
+          await discardWorktree();
+          throw error;
+        }
+        log.warn(`Git LFS pull failed in ${worktreePath}:`, error);
+        const message = error instanceof Error ? error.message : String(error);
+        lfsError = errorToText(error).includes('is not a git command')
+          ? 'Git LFS is not installed, so LFS files are left as pointers'
+          : message;
+      }
+    }
+
+    return { submoduleError, lfsError };
+  });
 
   // Symlink selected directories
   for (const name of symlinkDirs) {
     // Reject names that could escape the worktree directory
-    if (name.includes('/') || name.includes('\\') || name.includes('..') || name === '.') continue;
+    if (name.includes('/') || name.includes('\\') || name.includes('..') || name === '.') {
+      continue;
+    }
     const source = path.join(repoRoot, name);
     const target = path.join(worktreePath, name);
     try {
@@ -356,165 +703,613 @@ export async function createWorktree(
       } else {
         fs.symlinkSync(source, target);
       }
-    } catch {
-      /* ignore */
+    } catch (err) {
+      log.warn(`Failed to symlink ${name} into worktree ${worktreePath}`, err);
     }
   }
 
-  return { path: worktreePath, branch: branchName };
-}
-
-export async function removeWorktree(
-  repoRoot: string,
-  branchName: string,
-  deleteBranch: boolean,
-): Promise<void> {
-  const worktreePath = `${repoRoot}/.worktrees/${branchName}`;
+  const { worktree_hooks: hooks, worktree_git_config: configKeys } = getSettings();
+  if (hooks !== 'off' || configKeys.length > 0) {
+    // Worktree config is switched on in the shared .git/config
+    await withWorktreeLock(lockKey, () =>
+      propagateGitSetup(repoRoot, worktreePath, { hooks, configKeys }),
+    );
+  }
 
-  if (!fs.existsSync(repoRoot)) return;
+  await materializeMcpConfig(repoRoot, worktreePath);
 
-  if (fs.existsSync(worktreePath)) {
+  const result: CreatedWorktree = { path: worktreePath, branch: branchName };
+  if (submoduleError !== undefined) result.submodule_error = submoduleError;
+  if (lfsError !== undefined) result.lfs_error = lfsError;
+  if (createdBranch && getSettings().track_base_upstream) {
     try {
-      await exec('git', ['worktree', 'remove', '--force', worktreePath], { cwd: repoRoot });
-    } catch {
-      // Fallback: direct directory removal
-      fs.rmSync(worktreePath, { recursive: true, force: true });
+      const upstream = await withWorktreeLock(lockKey, () =>
+        trackBaseUpstream(worktreePath, branchName, baseRef),
+      );
+      if (upstream) result.upstream = upstream;
+    } catch (err) {
+      log.warn(`Failed to set the upstream of ${branchName}:`, err);
     }
   }
+  return result;
+}
 
-  // Prune stale worktree entries
-  try {
-    await exec('git', ['worktree', 'prune'], { cwd: repoRoot });
-  } catch {
-    /* ignore */
-  }
+/** The directory git runs `cwd`'s hooks from, following `core.hooksPath`. */
+async function hooksDir(cwd: string): Promise<string> {
+  const { stdout } = await exec('git', ['rev-parse', '--git-path', 'hooks'], { cwd });
+  return path.resolve(cwd, stdout.trim());
+}
 
-  if (deleteBranch) {
-    try {
-      await exec('git', ['branch', '-D', '--', branchName], { cwd: repoRoot });
-    } catch (e: unknown) {
-      const msg = String(e);
-      if (!msg.toLowerCase().includes('not found')) throw e;
+/** Set `key` for this worktree only, turning on per-worktree config as `sparse-checkout` does. */
+function setWorktreeConfig(worktreePath: string, key: string, values: string[]) {
+  // The extension is switched on in the repo's shared config
+  return withRepoLock(worktreePath, async () => {
+    await exec('git', ['config', 'extensions.worktreeConfig', 'true'], { cwd: worktreePath });
+    // Exits 5 when the key wasn't set
+    const unset = ['config', '--worktree', '--unset-all', key];
+    await exec('git', unset, { cwd: worktreePath }).catch(() => {});
+    for (const value of values) {
+      await exec('git', ['config', '--worktree', '--add', key, value], { cwd: worktreePath });
     }
-  }
+  });
 }
 
-// --- IPC command functions ---
-
-export async function getGitIgnoredDirs(projectRoot: string): Promise<string[]> {
-  const results: string[] = [];
-  for (const name of SYMLINK_CANDIDATES) {
-    const dirPath = path.join(projectRoot, name);
+/**
+ * Make the main checkout's hooks run in a new worktree, and set the given
+ * config keys on it to their values in the main checkout. Failures are
+ * logged: the worktree is usable without them.
+ */
+export async function propagateGitSetup(
+  repoRoot: string,
+  worktreePath: string,
+  options: { hooks: WorktreeHooksMode; configKeys: string[] },
+): Promise<void> {
+  if (options.hooks !== 'off') {
     try {
-      fs.statSync(dirPath); // throws if entry doesn't exist
-    } catch {
-      continue;
+      const mainHooks = await hooksDir(repoRoot);
+      const worktreeHooks = await hooksDir(worktreePath);
+      // Hooks in the shared .git/hooks or at an absolute core.hooksPath already apply
+      if (mainHooks !== worktreeHooks && fs.existsSync(mainHooks)) {
+        if (options.hooks === 'hooks_path') {
+          await setWorktreeConfig(worktreePath, 'core.hooksPath', [mainHooks]);
+        } else if (!fs.existsSync(worktreeHooks)) {
+          fs.cpSync(mainHooks, worktreeHooks, { recursive: true });
+        }
+      }
+    } catch (err) {
+      log.warn(`Failed to set up git hooks in ${worktreePath}:`, err);
     }
+  }
+
+  for (const key of options.configKeys) {
     try {
-      await exec('git', ['check-ignore', '-q', name], { cwd: projectRoot });
-      results.push(name);
-    } catch {
-      /* not ignored */
+      const { stdout } = await exec('git', ['config', '--get-all', key], { cwd: repoRoot });
+      await setWorktreeConfig(worktreePath, key, stdout.split('\n').filter(Boolean));
+    } catch (err) {
+      // `git config --get-all` exits 1 for keys the main checkout doesn't set
+      if ((err as { code?: unknown }).code !== 1) {
+        log.warn(`Failed to copy git config ${key} into ${worktreePath}:`, err);
+      }
     }
   }
-  return results;
-}
-
-export async function getMainBranch(projectRoot: string): Promise<string> {
-  return detectMainBranch(projectRoot);
 }
 
-export async function getCurrentBranch(projectRoot: string): Promise<string> {
-  return getCurrentBranchName(projectRoot);
+async function upstreamOf(cwd: string, branchName: string): Promise<string | null> {
+  try {
+    const { stdout } = await exec(
+      'git',
+      ['rev-parse', '--abbrev-ref', '--symbolic-full-name', `${branchName}@{upstream}`],
+      { cwd },
+    );
+    return stdout.trim() || null;
+  } catch {
+    // No upstream configured
+    return null;
+  }
 }
 
-export async function getChangedFiles(worktreePath: string): Promise<
-  Array<{
-    path: string;
-    lines_added: number;
-    lines_removed: number;
-    status: string;
-    committed: boolean;
-  }>
-> {
-  // Pin HEAD first so merge-base and diff use the same immutable commit
-  const headHash = await pinHead(worktreePath);
-  const base = await detectMergeBase(worktreePath, headHash).catch(() => headHash);
-
-  // git diff --raw --numstat <base> <head> — committed changes only (immutable)
-  let diffStr = '';
+/**
+ * Point a new branch's upstream at `origin/<base>` and push it to its own
+ * name. A branch git already set up tracking for, e.g. one created from
+ * `origin/<branch>`, keeps it. Null when origin has no such base branch.
+ */
+async function trackBaseUpstream(
+  worktreePath: string,
+  branchName: string,
+  baseRef?: string,
+): Promise<string | null> {
+  const existing = await upstreamOf(worktreePath, branchName);
+  if (existing) return existing;
+  const base = (baseRef ?? (await detectMainBranch(worktreePath))).replace(/^origin\//, '');
   try {
-    const { stdout } = await exec('git', ['diff', '--raw', '--numstat', base, headHash], {
+    await exec('git', ['rev-parse', '--verify', '--quiet', `refs/remotes/origin/${base}`], {
       cwd: worktreePath,
-      maxBuffer: MAX_BUFFER,
     });
-    diffStr = stdout;
   } catch {
-    /* empty */
+    return null;
   }
+  await exec('git', ['config', `branch.${branchName}.remote`, 'origin'], { cwd: worktreePath });
+  await exec('git', ['config', `branch.${branchName}.merge`, `refs/heads/${base}`], {
+    cwd: worktreePath,
+  });
+  await setWorktreeConfig(worktreePath, 'push.default', ['current']);
+  return `origin/${base}`;
+}
 
-  const { statusMap: committedStatusMap, numstatMap: committedNumstatMap } =
-    parseDiffRawNumstat(diffStr);
+export interface BranchTracking {
+  upstream: string;
+  ahead: number;
+  behind: number;
+}
 
-  // git status --porcelain for uncommitted/untracked paths
-  let statusStr = '';
+/** The worktree branch's upstream and how far HEAD is ahead of and behind it. */
+export async function getBranchTracking(worktreePath: string): Promise<BranchTracking | null> {
+  const upstream = await upstreamOf(worktreePath, 'HEAD');
+  if (!upstream) return null;
+  const { stdout } = await exec(
+    'git',
+    ['rev-list', '--left-right', '--count', '@{upstream}...HEAD'],
+    { cwd: worktreePath },
+  );
+  const [behind, ahead] = stdout.trim().split(/\s+/).map((n) => parseInt(n, 10) || 0);
+  return { upstream, ahead: ahead ?? 0, behind: behind ?? 0 };
+}
+
+/** Sparse-checkout directories must stay inside the worktree. */
+function checkSparsePaths(paths: string[]): void {
+  for (const p of paths) {
+    const parts = p.split(/[\\/]/);
+    if (!p || p.startsWith('-') || path.isAbsolute(p) || parts.includes('..')) {
+      throw new Error(`Invalid sparse-checkout path: ${p}`);
+    }
+  }
+}
+
+async function isSparseCheckout(worktreePath: string): Promise<boolean> {
   try {
-    const { stdout } = await exec('git', ['status', '--porcelain'], {
+    const { stdout } = await exec('git', ['config', '--bool', 'core.sparseCheckout'], {
       cwd: worktreePath,
-      maxBuffer: MAX_BUFFER,
     });
-    statusStr = stdout;
+    return stdout.trim() === 'true';
   } catch {
-    /* empty */
+    // Unset: exits 1
+    return false;
   }
+}
 
-  const uncommittedPaths = new Map<string, string>(); // path -> status letter
-  const untrackedPaths = new Set<string>();
-  for (const line of statusStr.split('\n')) {
-    if (line.length < 3) continue;
-    const p = normalizeStatusPath(line.slice(3));
-    if (!p) continue;
-    if (line.startsWith('??')) {
-      untrackedPaths.add(p);
-      uncommittedPaths.set(p, '?');
-    } else {
-      // Prefer working tree status, fall back to index status
-      const wtStatus = line[1];
-      const indexStatus = line[0];
-      uncommittedPaths.set(p, wtStatus !== ' ' ? wtStatus : indexStatus);
-    }
+/**
+ * Check out more directories in a sparse worktree, e.g. when an agent needs
+ * files outside the ones its task started with. Returns the full list.
+ */
+export async function widenSparseCheckout(
+  worktreePath: string,
+  paths: string[],
+): Promise<string[]> {
+  checkSparsePaths(paths);
+  if (!(await isSparseCheckout(worktreePath))) {
+    throw new Error('The worktree is not a sparse checkout');
   }
+  if (paths.length > 0) {
+    await withRepoLock(worktreePath, () =>
+      exec('git', ['sparse-checkout', 'add', '--', ...paths], { cwd: worktreePath }),
+    );
+    invalidateWorktreeStatus(worktreePath);
+  }
+  const { stdout } = await exec('git', ['sparse-checkout', 'list'], { cwd: worktreePath });
+  return stdout.split('\n').filter((l) => l.length > 0);
+}
 
-  const files: Array<{
-    path: string;
-    lines_added: number;
-    lines_removed: number;
-    status: string;
-    committed: boolean;
-  }> = [];
-  const seen = new Set<string>();
-
-  // Committed files from diff base..HEAD
-  for (const [p, [added, removed]] of committedNumstatMap) {
-    const status = committedStatusMap.get(p) ?? 'M';
-    // If also in uncommitted paths, mark as uncommitted (has local changes on top)
-    const committed = !uncommittedPaths.has(p);
-    seen.add(p);
-    files.push({ path: p, lines_added: added, lines_removed: removed, status, committed });
+/**
+ * Delete `target`, a file or directory under `cwd`. A remote project's
+ * stand-in paths aren't on this machine, so there it runs `rm` over ssh.
+ */
+async function removePath(target: string, cwd: string): Promise<void> {
+  if (remoteTargetFor(cwd)) {
+    await exec('rm', ['-rf', '--', target], { cwd });
+  } else {
+    await fs.promises.rm(target, { recursive: true, force: true });
   }
+}
 
-  // Uncommitted-only files (in status but not in committed diff)
-  // Use git diff --numstat HEAD for tracked files to get actual changed line counts
-  const uncommittedNumstat = new Map<string, [number, number]>();
-  const hasTrackedUncommitted = [...uncommittedPaths.keys()].some(
-    (p) => !seen.has(p) && !untrackedPaths.has(p),
-  );
-  if (hasTrackedUncommitted) {
-    try {
-      const { stdout } = await exec('git', ['diff', '--numstat', 'HEAD'], {
-        cwd: worktreePath,
-        maxBuffer: MAX_BUFFER,
+export async function removeWorktree(
+  repoRoot: string,
+  branchName: string,
+  deleteBranch: boolean,
+): Promise<void> {
+  return withWorktreeLock(lockKeyForRepoRoot(repoRoot), async () => {
+    const worktreePath = getWorktreePath(repoRoot, branchName);
+    // Can't look for a remote project's paths locally; git and rm there tell
+    const remote = remoteTargetFor(repoRoot) !== undefined;
+
+    if (!remote && !fs.existsSync(repoRoot)) return;
+
+    if (remote || fs.existsSync(worktreePath)) {
+      try {
+        await exec('git', ['worktree', 'remove', '--force', worktreePath], { cwd: repoRoot });
+      } catch (err) {
+        // Fallback: direct directory removal
+        log.info(`git worktree remove failed for ${worktreePath}, deleting directly`, err);
+        await removePath(worktreePath, repoRoot);
+      }
+    }
+    invalidateWorktreeStatus(worktreePath);
+
+    // Prune stale worktree entries
+    try {
+      await exec('git', ['worktree', 'prune'], { cwd: repoRoot });
+    } catch (err) {
+      log.debug('git worktree prune failed', err);
+    }
+
+    if (deleteBranch) {
+      try {
+        await exec('git', ['branch', '-D', '--', branchName], { cwd: repoRoot });
+      } catch (e: unknown) {
+        const msg = String(e);
+        if (!msg.toLowerCase().includes('not found')) throw e;
+      }
+    }
+  });
+}
+
+async function refExists(repoRoot: string, ref: string): Promise<boolean> {
+  try {
+    await exec('git', ['rev-parse', '--verify', '--quiet', ref], { cwd: repoRoot });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+export function branchExists(repoRoot: string, branchName: string): Promise<boolean> {
+  return refExists(repoRoot, `refs/heads/${branchName}`);
+}
+
+/** The commit a branch points at, or null when it doesn't exist. */
+export async function getBranchTip(repoRoot: string, branchName: string): Promise<string | null> {
+  try {
+    const { stdout } = await exec(
+      'git',
+      ['rev-parse', '--verify', '--quiet', `refs/heads/${branchName}^{commit}`],
+      { cwd: repoRoot },
+    );
+    return stdout.trim() || null;
+  } catch {
+    return null;
+  }
+}
+
+export interface BranchDeletionRisk {
+  branch: string;
+  /** The commit the branch points at, to recreate it from. */
+  tip: string;
+  /** Commits on the branch that the main branch doesn't have. */
+  unmerged: number;
+  /** Of those, the ones not on any remote branch either; null without a remote. */
+  unpushed: number | null;
+}
+
+async function countCommits(repoRoot: string, args: string[]): Promise<number> {
+  const { stdout } = await exec('git', ['rev-list', '--count', ...args], { cwd: repoRoot });
+  return Number(stdout.trim()) || 0;
+}
+
+/**
+ * The branch a task's commits count as merged into: `baseBranch` while it
+ * exists, otherwise the main branch.
+ */
+async function deletionBase(repoRoot: string, baseBranch?: string): Promise<string> {
+  if (baseBranch && (await refExists(repoRoot, baseBranch))) return baseBranch;
+  return detectMainBranch(repoRoot);
+}
+
+/**
+ * What deleting `branchName` would lose: null when the branch is gone or
+ * every commit on it is in `baseBranch`, or the main branch without one.
+ */
+export async function getBranchDeletionRisk(
+  repoRoot: string,
+  branchName: string,
+  baseBranch?: string,
+): Promise<BranchDeletionRisk | null> {
+  const tip = await getBranchTip(repoRoot, branchName);
+  if (!tip) return null;
+  const mainBranch = await deletionBase(repoRoot, baseBranch);
+  // No main branch yet (empty repo): every commit on the branch is unmerged
+  const notMain = (await refExists(repoRoot, mainBranch)) ? [mainBranch] : [];
+  const unmerged = await countCommits(repoRoot, [tip, '--not', ...notMain, '--']);
+  if (unmerged === 0) return null;
+  const { stdout: remotes } = await exec('git', ['remote'], { cwd: repoRoot });
+  const unpushed = remotes.trim()
+    ? await countCommits(repoRoot, [tip, '--not', ...notMain, '--remotes', '--'])
+    : null;
+  return { branch: branchName, tip, unmerged, unpushed };
+}
+
+/**
+ * Throw `unmerged_commits` when deleting `branchName` would lose commits
+ * that `baseBranch` (or the main branch) doesn't have, so the caller has to
+ * confirm first.
+ */
+export async function assertBranchDeletable(
+  repoRoot: string,
+  branchName: string,
+  baseBranch?: string,
+): Promise<void> {
+  const base = await deletionBase(repoRoot, baseBranch);
+  const risk = await getBranchDeletionRisk(repoRoot, branchName, base);
+  if (!risk) return;
+  const commits = (n: number) => `${n} commit${n === 1 ? '' : 's'}`;
+  const pushed =
+    risk.unpushed === null
+      ? ''
+      : risk.unpushed === 0
+        ? ', all of them pushed'
+        : risk.unpushed === risk.unmerged
+          ? ', none of them pushed'
+          : `, ${risk.unpushed} of them not pushed`;
+  throw new AppError(
+    'unmerged_commits',
+    `Branch ${branchName} has ${commits(risk.unmerged)} not merged into ` +
+      `${base === baseBranch ? base : 'the main branch'}${pushed} (tip ${risk.tip.slice(0, 12)})`,
+    { branch: branchName, commit: risk.tip, unmerged: risk.unmerged, unpushed: risk.unpushed },
+  );
+}
+
+/** Create `branchName` at `sha`, e.g. to bring back a deleted branch. */
+export async function createBranchAt(
+  repoRoot: string,
+  branchName: string,
+  sha: string,
+): Promise<void> {
+  try {
+    await withWorktreeLock(lockKeyForRepoRoot(repoRoot), () =>
+      exec('git', ['branch', '--', branchName, sha], { cwd: repoRoot }),
+    );
+  } catch (err) {
+    const reason = stderrOf(err) ?? String(err);
+    throw new Error(`Couldn't recreate ${branchName} at ${sha.slice(0, 7)}: ${reason}`);
+  }
+}
+
+export function remoteBranchExists(
+  repoRoot: string,
+  remote: string,
+  branchName: string,
+): Promise<boolean> {
+  return refExists(repoRoot, `refs/remotes/${remote}/${branchName}`);
+}
+
+/**
+ * Rename a task branch and move its worktree to the matching path. The branch
+ * rename is rolled back if the worktree cannot be moved.
+ */
+export async function renameWorktreeBranch(
+  repoRoot: string,
+  oldBranch: string,
+  newBranch: string,
+): Promise<{ path: string; branch: string }> {
+  return withWorktreeLock(lockKeyForRepoRoot(repoRoot), async () => {
+    const oldPath = getWorktreePath(repoRoot, oldBranch);
+    const newPath = getWorktreePath(repoRoot, newBranch);
+    if (await branchExists(repoRoot, newBranch)) {
+      throw new AppError('branch_exists', `Branch ${newBranch} already exists`, {
+        branch: newBranch,
+      });
+    }
+    if (fs.existsSync(newPath)) {
+      throw new AppError('worktree_exists', `Worktree path already exists: ${newPath}`, {
+        path: newPath,
       });
+    }
+
+    await exec('git', ['branch', '-m', '--', oldBranch, newBranch], { cwd: repoRoot });
+    if (fs.existsSync(oldPath)) {
+      try {
+        fs.mkdirSync(path.dirname(newPath), { recursive: true });
+        await exec('git', ['worktree', 'move', oldPath, newPath], { cwd: repoRoot });
+      } catch (err) {
+        await exec('git', ['branch', '-m', '--', newBranch, oldBranch], { cwd: repoRoot }).catch(
+          (e) => log.warn('Failed to roll back branch rename:', e),
+        );
+        throw err;
+      }
+    }
+    invalidateMergeBaseCache();
+    return { path: newPath, branch: newBranch };
+  });
+}
+
+// --- IPC command functions ---
+
+export async function getGitIgnoredDirs(projectRoot: string): Promise<string[]> {
+  const results: string[] = [];
+  for (const name of SYMLINK_CANDIDATES) {
+    const dirPath = path.join(projectRoot, name);
+    try {
+      fs.statSync(dirPath); // throws if entry doesn't exist
+    } catch {
+      continue;
+    }
+    try {
+      await exec('git', ['check-ignore', '-q', name], { cwd: projectRoot });
+      results.push(name);
+    } catch {
+      /* not ignored */
+    }
+  }
+  return results;
+}
+
+export async function getMainBranch(projectRoot: string): Promise<string> {
+  return detectMainBranch(projectRoot);
+}
+
+export interface ProjectInfo {
+  /** The branch origin's HEAD points at, or `main`/`master`; null when none can be found. */
+  default_branch: string | null;
+  remote_url: string | null;
+  /** Taken from the remote URL, or the directory name when there is no remote. */
+  repo_name: string;
+}
+
+/** `owner/repo.git`, `git@host:owner/repo` and the like, down to `repo`. */
+export function repoNameFromUrl(url: string): string | null {
+  const last = url
+    .trim()
+    .replace(/[/\\]+$/, '')
+    .split(/[/:\\]/)
+    .pop();
+  const name = last?.replace(/\.git$/, '');
+  return name || null;
+}
+
+/**
+ * The repository's default branch, ignoring the `base_branch` setting: origin's
+ * HEAD when it's known locally, else what `git remote show` reports, else a
+ * local `main` or `master`.
+ */
+async function detectDefaultBranch(repoRoot: string): Promise<string | null> {
+  try {
+    const { stdout } = await exec('git', ['symbolic-ref', '--short', 'refs/remotes/origin/HEAD'], {
+      cwd: repoRoot,
+    });
+    const branch = stdout.trim().replace(/^origin\//, '');
+    if (branch) return branch;
+  } catch {
+    /* origin/HEAD not set — ask the remote */
+  }
+
+  try {
+    const { stdout } = await exec('git', ['remote', 'show', 'origin'], {
+      cwd: repoRoot,
+      timeout: 10_000,
+      env: { ...process.env, GIT_TERMINAL_PROMPT: '0' },
+    });
+    const branch = /^\s*HEAD branch:\s*(\S+)/m.exec(stdout)?.[1];
+    if (branch && branch !== '(unknown)') return branch;
+  } catch {
+    /* no origin, or it can't be reached */
+  }
+
+  for (const name of ['main', 'master']) {
+    if (await branchExists(repoRoot, name)) return name;
+  }
+  return null;
+}
+
+export async function getProjectInfo(projectRoot: string): Promise<ProjectInfo> {
+  const remoteUrl = await exec('git', ['remote', 'get-url', 'origin'], { cwd: projectRoot })
+    .then(({ stdout }) => stdout.trim() || null)
+    .catch(() => null);
+  return {
+    default_branch: await detectDefaultBranch(projectRoot),
+    remote_url: remoteUrl,
+    repo_name: (remoteUrl && repoNameFromUrl(remoteUrl)) || path.basename(projectRoot),
+  };
+}
+
+export async function getCurrentBranch(projectRoot: string): Promise<string> {
+  return getCurrentBranchName(projectRoot);
+}
+
+export async function getRemoteUrl(repoRoot: string, remote = 'origin'): Promise<string> {
+  const { stdout } = await exec('git', ['remote', 'get-url', '--', remote], { cwd: repoRoot });
+  return stdout.trim();
+}
+
+/** Limits a git command to a task's scope, if it has one. */
+function scopePathspec(scope?: string): string[] {
+  return scope ? ['--', scope] : [];
+}
+
+/** Changes since the merge base; with a `scope`, only those under that sub-directory. */
+export async function getChangedFiles(
+  worktreePath: string,
+  scope?: string,
+): Promise<
+  Array<{
+    path: string;
+    lines_added: number;
+    lines_removed: number;
+    status: string;
+    committed: boolean;
+  }>
+> {
+  // Pin HEAD first so merge-base and diff use the same immutable commit
+  const headHash = await pinHead(worktreePath);
+  const base = await detectMergeBase(worktreePath, headHash).catch(() => headHash);
+
+  // git diff --raw --numstat <base> <head> — committed changes only (immutable)
+  let diffStr = '';
+  try {
+    const args = ['diff', '--raw', '--numstat', base, headHash, ...scopePathspec(scope)];
+    const { stdout } = await exec('git', args, { cwd: worktreePath, maxBuffer: MAX_BUFFER });
+    diffStr = stdout;
+  } catch {
+    /* empty */
+  }
+
+  const { statusMap: committedStatusMap, numstatMap: committedNumstatMap } =
+    parseDiffRawNumstat(diffStr);
+
+  // git status --porcelain for uncommitted/untracked paths
+  let statusStr = '';
+  try {
+    const { stdout } = await exec('git', ['status', '--porcelain', ...scopePathspec(scope)], {
+      cwd: worktreePath,
+      maxBuffer: MAX_BUFFER,
+    });
+    statusStr = stdout;
+  } catch {
+    /* empty */
+  }
+
+  const uncommittedPaths = new Map<string, string>(); // path -> status letter
+  const untrackedPaths = new Set<string>();
+  for (const line of statusStr.split('\n')) {
+    if (line.length < 3) continue;
+    const p = normalizeStatusPath(line.slice(3));
+    if (!p) continue;
+    if (line.startsWith('??')) {
+      untrackedPaths.add(p);
+      uncommittedPaths.set(p, '?');
+    } else {
+      // Prefer working tree status, fall back to index status
+      const wtStatus = line[1];
+      const indexStatus = line[0];
+      uncommittedPaths.set(p, wtStatus !== ' ' ? wtStatus : indexStatus);
+    }
+  }
+
+  const files: Array<{
+    path: string;
+    lines_added: number;
+    lines_removed: number;
+    status: string;
+    committed: boolean;
+  }> = [];
+  const seen = new Set<string>();
+
+  // Committed files from diff base..HEAD
+  for (const [p, [added, removed]] of committedNumstatMap) {
+    const status = committedStatusMap.get(p) ?? 'M';
+    // If also in uncommitted paths, mark as uncommitted (has local changes on top)
+    const committed = !uncommittedPaths.has(p);
+    seen.add(p);
+    files.push({ path: p, lines_added: added, lines_removed: removed, status, committed });
+  }
+
+  // Uncommitted-only files (in status but not in committed diff)
+  // Use git diff --numstat HEAD for tracked files to get actual changed line counts
+  const uncommittedNumstat = new Map<string, [number, number]>();
+  const hasTrackedUncommitted = [...uncommittedPaths.keys()].some(
+    (p) => !seen.has(p) && !untrackedPaths.has(p),
+  );
+  if (hasTrackedUncommitted) {
+    try {
+      const args = ['diff', '--numstat', 'HEAD', ...scopePathspec(scope)];
+      const { stdout } = await exec('git', args, { cwd: worktreePath, maxBuffer: MAX_BUFFER });
       for (const line of stdout.split('\n')) {
         const parts = line.split('\t');
         if (parts.length >= 3) {
@@ -686,21 +1481,40 @@ export async function getFileDiff(worktreePath: string, filePath: string): Promi
   return { diff, oldContent, newContent };
 }
 
+/**
+ * Whether the worktree has commits and uncommitted changes of its own. Served
+ * from the status cache unless `fresh`, for decisions that could lose work.
+ */
 export async function getWorktreeStatus(
   worktreePath: string,
-): Promise<{ has_committed_changes: boolean; has_uncommitted_changes: boolean }> {
-  const { stdout: statusOut } = await exec('git', ['status', '--porcelain'], {
-    cwd: worktreePath,
-    maxBuffer: MAX_BUFFER,
-  });
+  scope?: string,
+  fresh = false,
+): Promise<WorktreeStatus> {
+  if (fresh) invalidateWorktreeStatus(worktreePath);
+  const entry = await statusCacheEntry(worktreePath);
+  const cached = entry.status.get(scope ?? '');
+  if (cached) return cached;
+  const status = await computeWorktreeStatus(worktreePath, scope);
+  if (isCurrentStatusEntry(worktreePath, entry)) entry.status.set(scope ?? '', status);
+  return status;
+}
+
+async function computeWorktreeStatus(
+  worktreePath: string,
+  scope?: string,
+): Promise<WorktreeStatus> {
+  const { stdout: statusOut } = await exec(
+    'git',
+    ['status', '--porcelain', ...scopePathspec(scope)],
+    { cwd: worktreePath, maxBuffer: MAX_BUFFER },
+  );
   const hasUncommittedChanges = statusOut.trim().length > 0;
 
   const mainBranch = await detectMainBranch(worktreePath).catch(() => 'HEAD');
   let hasCommittedChanges = false;
   try {
-    const { stdout: logOut } = await exec('git', ['log', `${mainBranch}..HEAD`, '--oneline'], {
-      cwd: worktreePath,
-    });
+    const args = ['log', `${mainBranch}..HEAD`, '--oneline', ...scopePathspec(scope)];
+    const { stdout: logOut } = await exec('git', args, { cwd: worktreePath });
     hasCommittedChanges = logOut.trim().length > 0;
   } catch {
     /* ignore */
@@ -712,34 +1526,119 @@ export async function getWorktreeStatus(
   };
 }
 
-/** Stage all changes and commit in a worktree. */
-export async function commitAll(worktreePath: string, message: string): Promise<void> {
-  await exec('git', ['add', '-A'], { cwd: worktreePath });
-  await exec('git', ['commit', '-m', message], { cwd: worktreePath });
-}
-
-/** Discard all uncommitted changes in a worktree (keeps committed work). */
-export async function discardUncommitted(worktreePath: string): Promise<void> {
-  await exec('git', ['checkout', '.'], { cwd: worktreePath });
-  await exec('git', ['clean', '-fd'], { cwd: worktreePath });
+/** Commits the worktree's HEAD is ahead of / behind the base branch. */
+export async function getAheadBehind(
+  worktreePath: string,
+): Promise<{ ahead: number; behind: number }> {
+  const entry = await statusCacheEntry(worktreePath);
+  if (entry.counts) return entry.counts;
+  const counts = await computeAheadBehind(worktreePath);
+  if (isCurrentStatusEntry(worktreePath, entry)) entry.counts = counts;
+  return counts;
 }
 
-export async function checkMergeStatus(
+async function computeAheadBehind(
   worktreePath: string,
-): Promise<{ main_ahead_count: number; conflicting_files: string[] }> {
-  const mainBranch = await detectMainBranch(worktreePath);
-
-  let mainAheadCount = 0;
+): Promise<{ ahead: number; behind: number }> {
+  const mainBranch = await detectMainBranch(worktreePath).catch(() => 'HEAD');
   try {
-    const { stdout } = await exec('git', ['rev-list', '--count', `HEAD..${mainBranch}`], {
-      cwd: worktreePath,
-    });
-    mainAheadCount = parseInt(stdout.trim(), 10) || 0;
+    const { stdout } = await exec(
+      'git',
+      ['rev-list', '--left-right', '--count', `${mainBranch}...HEAD`],
+      { cwd: worktreePath },
+    );
+    const [behind, ahead] = stdout.trim().split(/\s+/).map((n) => parseInt(n, 10) || 0);
+    return { ahead: ahead ?? 0, behind: behind ?? 0 };
+  } catch {
+    return { ahead: 0, behind: 0 };
+  }
+}
+
+/** Stage all changes and commit in a worktree. */
+export function commitAll(worktreePath: string, message: string): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    await exec('git', ['add', '-A'], { cwd: worktreePath });
+    await exec('git', ['commit', '-m', message], { cwd: worktreePath });
+    invalidateWorktreeStatus(worktreePath);
+  });
+}
+
+/**
+ * Commit everything in a task worktree as a checkpoint, with `message` built
+ * from the changed paths. Hooks are skipped so a slow or failing pre-commit
+ * can't get in the agent's way. Null when there was nothing to commit, or
+ * `worktreePath` is not a linked worktree.
+ */
+export async function commitCheckpoint(
+  worktreePath: string,
+  message: (files: string[]) => string,
+): Promise<{ commit: string; files: string[] } | null> {
+  return withRepoLock(worktreePath, async () => {
+    // The project's own checkout (a direct-mode task) isn't on a task branch
+    const { stdout: dirs } = await exec('git', ['rev-parse', '--git-dir', '--git-common-dir'], {
+      cwd: worktreePath,
+    });
+    const [gitDir, commonDir] = dirs.trim().split('\n');
+    if (path.resolve(worktreePath, gitDir) === path.resolve(worktreePath, commonDir)) return null;
+
+    const { stdout: statusOut } = await exec('git', ['status', '--porcelain'], {
+      cwd: worktreePath,
+      maxBuffer: MAX_BUFFER,
+    });
+    const files = statusOut
+      .split('\n')
+      .map((line) => normalizeStatusPath(line.slice(3)))
+      .filter((f) => f.length > 0);
+    if (files.length === 0) return null;
+    await exec('git', ['add', '-A'], { cwd: worktreePath });
+    await exec('git', ['commit', '--no-verify', '-q', '-m', message(files)], {
+      cwd: worktreePath,
+    });
+    invalidateWorktreeStatus(worktreePath);
+    return { commit: await getHeadCommit(worktreePath), files };
+  });
+}
+
+/** Discard all uncommitted changes in a worktree (keeps committed work). */
+export function discardUncommitted(worktreePath: string): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    await exec('git', ['checkout', '.'], { cwd: worktreePath });
+    await exec('git', ['clean', '-fd'], { cwd: worktreePath });
+    invalidateWorktreeStatus(worktreePath);
+  });
+}
+
+/**
+ * How far main moved ahead and which files would conflict merging it. A
+ * task with a `scope` is only checked for conflicts once main touches that
+ * sub-directory, since its own changes are meant to stay inside it.
+ */
+export async function checkMergeStatus(
+  worktreePath: string,
+  scope?: string,
+): Promise<{ main_ahead_count: number; conflicting_files: string[] }> {
+  const mainBranch = await detectMainBranch(worktreePath);
+
+  let mainAheadCount = 0;
+  try {
+    const { stdout } = await exec('git', ['rev-list', '--count', `HEAD..${mainBranch}`], {
+      cwd: worktreePath,
+    });
+    mainAheadCount = parseInt(stdout.trim(), 10) || 0;
   } catch {
     /* ignore */
   }
 
   if (mainAheadCount === 0) return { main_ahead_count: 0, conflicting_files: [] };
+  if (scope) {
+    const args = ['rev-list', '--count', `HEAD..${mainBranch}`, ...scopePathspec(scope)];
+    const touching = await exec('git', args, { cwd: worktreePath })
+      .then(({ stdout }) => parseInt(stdout.trim(), 10) || 0)
+      .catch(() => mainAheadCount);
+    if (touching === 0) {
+      return { main_ahead_count: mainAheadCount, conflicting_files: [] };
+    }
+  }
 
   const conflictingFiles: string[] = [];
   try {
@@ -756,6 +1655,15 @@ export async function checkMergeStatus(
   return { main_ahead_count: mainAheadCount, conflicting_files: conflictingFiles };
 }
 
+function mergeError(message: string, cause: unknown, branch: string, conflicts: string[]): Error {
+  if (conflicts.length === 0) return new Error(message);
+  return new AppError('merge_conflict', message, {
+    branch,
+    files: conflicts,
+    stderr: stderrOf(cause),
+  });
+}
+
 export async function mergeTask(
   projectRoot: string,
   branchName: string,
@@ -763,9 +1671,7 @@ export async function mergeTask(
   message: string | null,
   cleanup: boolean,
 ): Promise<{ main_branch: string; lines_added: number; lines_removed: number }> {
-  const lockKey = await detectRepoLockKey(projectRoot).catch(() => projectRoot);
-
-  return withWorktreeLock(lockKey, async () => {
+  return withRepoLock(projectRoot, async () => {
     const mainBranch = await detectMainBranch(projectRoot);
     const { linesAdded, linesRemoved } = await computeBranchDiffStats(
       projectRoot,
@@ -778,8 +1684,10 @@ export async function mergeTask(
       cwd: projectRoot,
     });
     if (statusOut.trim())
-      throw new Error(
+      throw new AppError(
+        'uncommitted_changes',
         'Project root has uncommitted changes. Please commit or stash them before merging.',
+        { path: projectRoot },
       );
 
     const originalBranch = await getCurrentBranchName(projectRoot).catch(() => null);
@@ -792,7 +1700,7 @@ export async function mergeTask(
         try {
           await exec('git', ['checkout', originalBranch], { cwd: projectRoot });
         } catch (e) {
-          console.warn(`Failed to restore branch '${originalBranch}':`, e);
+          log.warn(`Failed to restore branch '${originalBranch}':`, e);
         }
       }
     };
@@ -801,18 +1709,19 @@ export async function mergeTask(
       try {
         await exec('git', ['merge', '--squash', '--', branchName], { cwd: projectRoot });
       } catch (e) {
+        const conflicts = await listUnmergedFiles(projectRoot);
         await exec('git', ['reset', '--hard', 'HEAD'], { cwd: projectRoot }).catch((recoverErr) =>
-          console.warn('git reset --hard failed during squash recovery:', recoverErr),
+          log.warn('git reset --hard failed during squash recovery:', recoverErr),
         );
         await restoreBranch();
-        throw new Error(`Squash merge failed: ${e}`);
+        throw mergeError(`Squash merge failed: ${e}`, e, branchName, conflicts);
       }
       const msg = message ?? 'Squash merge';
       try {
         await exec('git', ['commit', '-m', msg], { cwd: projectRoot });
       } catch (e) {
         await exec('git', ['reset', '--hard', 'HEAD'], { cwd: projectRoot }).catch((recoverErr) =>
-          console.warn('git reset --hard failed during commit recovery:', recoverErr),
+          log.warn('git reset --hard failed during commit recovery:', recoverErr),
         );
         await restoreBranch();
         throw new Error(`Commit failed: ${e}`);
@@ -821,11 +1730,12 @@ export async function mergeTask(
       try {
         await exec('git', ['merge', '--', branchName], { cwd: projectRoot });
       } catch (e) {
+        const conflicts = await listUnmergedFiles(projectRoot);
         await exec('git', ['merge', '--abort'], { cwd: projectRoot }).catch((recoverErr) =>
-          console.warn('git merge --abort failed:', recoverErr),
+          log.warn('git merge --abort failed:', recoverErr),
         );
         await restoreBranch();
-        throw new Error(`Merge failed: ${e}`);
+        throw mergeError(`Merge failed: ${e}`, e, branchName, conflicts);
       }
     }
 
@@ -841,6 +1751,266 @@ export async function mergeTask(
   });
 }
 
+export interface CherryPickResult {
+  applied: string[];
+  /** The commit that stopped the pick, with the files it conflicted on. */
+  conflict: { commit: string; conflicting_files: string[] } | null;
+}
+
+async function listUnmergedFiles(worktreePath: string): Promise<string[]> {
+  try {
+    const { stdout } = await exec('git', ['diff', '--name-only', '--diff-filter=U'], {
+      cwd: worktreePath,
+    });
+    return stdout.split('\n').filter(Boolean);
+  } catch {
+    return [];
+  }
+}
+
+/**
+ * Cherry-pick commits into a worktree, in order. Stops at the first conflict,
+ * aborts that pick (earlier picks stay applied) and reports it.
+ */
+export async function cherryPickCommits(
+  worktreePath: string,
+  commits: string[],
+): Promise<CherryPickResult> {
+  return withRepoLock(worktreePath, async () => {
+    const { stdout: statusOut } = await exec('git', ['status', '--porcelain'], {
+      cwd: worktreePath,
+    });
+    if (statusOut.trim()) {
+      throw new AppError(
+        'uncommitted_changes',
+        'Target worktree has uncommitted changes. Commit or stash them first.',
+        { path: worktreePath },
+      );
+    }
+
+    const applied: string[] = [];
+    for (const commit of commits) {
+      try {
+        await exec('git', ['cherry-pick', '-x', commit], { cwd: worktreePath });
+        invalidateWorktreeStatus(worktreePath);
+        applied.push(commit);
+      } catch (e) {
+        const files = await listUnmergedFiles(worktreePath);
+        await exec('git', ['cherry-pick', '--abort'], { cwd: worktreePath }).catch((recoverErr) =>
+          log.warn('git cherry-pick --abort failed:', recoverErr),
+        );
+        if (files.length === 0) throw new Error(`Cherry-pick of ${commit} failed: ${e}`);
+        return { applied, conflict: { commit, conflicting_files: files } };
+      }
+    }
+    return { applied, conflict: null };
+  });
+}
+
+export interface StashEntry {
+  /** Position in the repo-wide stash list, e.g. 2 for `stash@{2}`. */
+  index: number;
+  message: string;
+  created_at: number;
+}
+
+/**
+ * Stashes are shared by every worktree of a repo, so a task's stashes are the
+ * entries git recorded as made "On <branch>" (or "WIP on <branch>" when no
+ * message was given).
+ */
+export function parseStashList(output: string, branchName: string): StashEntry[] {
+  const prefixes = [`On ${branchName}: `, `WIP on ${branchName}: `];
+  const entries: StashEntry[] = [];
+  for (const line of output.split('\n')) {
+    const [ref, ts, subject] = line.split('\0');
+    const m = ref?.match(/^stash@\{(\d+)\}$/);
+    const prefix = prefixes.find((p) => subject?.startsWith(p));
+    if (!m || !prefix) continue;
+    entries.push({
+      index: parseInt(m[1], 10),
+      message: subject.slice(prefix.length),
+      created_at: parseInt(ts, 10) * 1000,
+    });
+  }
+  return entries;
+}
+
+export async function listStashes(
+  worktreePath: string,
+  branchName: string,
+): Promise<StashEntry[]> {
+  const { stdout } = await exec('git', ['stash', 'list', '--format=%gd%x00%ct%x00%gs'], {
+    cwd: worktreePath,
+    maxBuffer: MAX_BUFFER,
+  });
+  return parseStashList(stdout, branchName);
+}
+
+/** Stash all uncommitted changes, untracked files included. */
+export async function stashTask(worktreePath: string, message: string): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    const { stdout: statusOut } = await exec('git', ['status', '--porcelain'], {
+      cwd: worktreePath,
+    });
+    if (!statusOut.trim()) throw new Error('No uncommitted changes to stash');
+    await exec('git', ['stash', 'push', '--include-untracked', '-m', message || 'WIP'], {
+      cwd: worktreePath,
+    });
+    invalidateWorktreeStatus(worktreePath);
+  });
+}
+
+/**
+ * Pop one of the task's stashes (the most recent when `index` is omitted).
+ * On conflict git keeps the stash entry, so nothing is lost.
+ */
+export async function stashPopTask(
+  worktreePath: string,
+  branchName: string,
+  index?: number,
+): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    const stashes = await listStashes(worktreePath, branchName);
+    const entry = index === undefined ? stashes[0] : stashes.find((s) => s.index === index);
+    if (!entry) throw new Error('No stash found for this task');
+    try {
+      await exec('git', ['stash', 'pop', `stash@{${entry.index}}`], { cwd: worktreePath });
+    } catch (e) {
+      throw new Error(`Stash pop failed (the stash was kept): ${e}`);
+    } finally {
+      invalidateWorktreeStatus(worktreePath);
+    }
+  });
+}
+
+// --- Snapshots ---
+
+export interface TaskSnapshot {
+  id: string;
+  label: string;
+  /** Commit holding the files; its parent is where the branch was. */
+  commit: string;
+  created_at: number;
+}
+
+const SNAPSHOT_REFS = 'refs/parallel-code/snapshots';
+
+function snapshotRefPrefix(taskId: string): string {
+  // Task ids are UUIDs; anything else could reach other refs
+  if (!/^[\w-]+$/.test(taskId)) throw new Error(`Invalid task id: ${taskId}`);
+  return `${SNAPSHOT_REFS}/${taskId}/`;
+}
+
+/** A task's snapshots, newest first. */
+export async function listSnapshots(cwd: string, taskId: string): Promise<TaskSnapshot[]> {
+  const prefix = snapshotRefPrefix(taskId);
+  const format = '%(refname)%00%(objectname)%00%(creatordate:unix)%00%(subject)';
+  const { stdout } = await exec('git', ['for-each-ref', `--format=${format}`, prefix], {
+    cwd,
+    maxBuffer: MAX_BUFFER,
+  });
+  const snapshots: TaskSnapshot[] = [];
+  for (const line of stdout.split('\n')) {
+    const [ref, commit, ts, label] = line.split('\0');
+    if (!ref?.startsWith(prefix) || !commit) continue;
+    const createdAt = parseInt(ts, 10) * 1000;
+    snapshots.push({ id: ref.slice(prefix.length), label, commit, created_at: createdAt });
+  }
+  return snapshots.sort((a, b) => b.created_at - a.created_at);
+}
+
+/**
+ * Commit the worktree as it is, untracked files included, without touching
+ * it or the branch: the files go through a throwaway index into a commit on
+ * top of HEAD, which a ref of its own keeps from being garbage collected.
+ */
+async function snapshotWorktree(
+  worktreePath: string,
+  taskId: string,
+  label: string,
+): Promise<TaskSnapshot> {
+  const { stdout: indexPath } = await exec('git', ['rev-parse', '--git-path', 'index'], {
+    cwd: worktreePath,
+  });
+  const tmpDir = await fs.promises.mkdtemp(path.join(os.tmpdir(), 'parallel-code-snapshot-'));
+  try {
+    const tmpIndex = path.join(tmpDir, 'index');
+    const realIndex = path.resolve(worktreePath, indexPath.trim());
+    // Starting from the real index keeps `git add` from re-hashing every file
+    await fs.promises.copyFile(realIndex, tmpIndex).catch(() => {});
+    const env = { ...process.env, GIT_INDEX_FILE: tmpIndex };
+    await exec('git', ['add', '-A'], { cwd: worktreePath, env });
+    const { stdout: tree } = await exec('git', ['write-tree'], { cwd: worktreePath, env });
+    const head = await getHeadCommit(worktreePath);
+    const { stdout } = await exec('git', ['commit-tree', tree.trim(), '-p', head, '-m', label], {
+      cwd: worktreePath,
+    });
+    const commit = stdout.trim();
+    const id = commit.slice(0, 12);
+    await exec('git', ['update-ref', `${snapshotRefPrefix(taskId)}${id}`, commit], {
+      cwd: worktreePath,
+    });
+    return { id, label, commit, created_at: Date.now() };
+  } finally {
+    await fs.promises.rm(tmpDir, { recursive: true, force: true });
+  }
+}
+
+/** Record the task's worktree, uncommitted and untracked files included, to restore later. */
+export async function createSnapshot(
+  worktreePath: string,
+  taskId: string,
+  label: string,
+): Promise<TaskSnapshot> {
+  snapshotRefPrefix(taskId);
+  return withRepoLock(worktreePath, () =>
+    snapshotWorktree(worktreePath, taskId, label.trim() || 'Snapshot'),
+  );
+}
+
+/**
+ * Roll the worktree back to a snapshot: the branch returns to the commit it
+ * was on, and the snapshot's files come back as uncommitted changes. What's
+ * there now is snapshotted first, so the restore can itself be undone.
+ */
+export async function restoreSnapshot(
+  worktreePath: string,
+  taskId: string,
+  snapshotId: string,
+): Promise<{ restored: TaskSnapshot; backup: TaskSnapshot }> {
+  return withRepoLock(worktreePath, async () => {
+    const snapshots = await listSnapshots(worktreePath, taskId);
+    const snapshot = snapshots.find((s) => s.id === snapshotId);
+    if (!snapshot) throw new Error('Snapshot not found');
+    const backup = await snapshotWorktree(
+      worktreePath,
+      taskId,
+      `Before restoring "${snapshot.label}"`,
+    );
+    try {
+      // The snapshot exactly, then back to its parent with the files left in place
+      await exec('git', ['reset', '--hard', '-q', snapshot.commit], { cwd: worktreePath });
+      await exec('git', ['clean', '-fdq'], { cwd: worktreePath });
+      await exec('git', ['reset', '-q', `${snapshot.commit}^`], { cwd: worktreePath });
+    } finally {
+      invalidateWorktreeStatus(worktreePath);
+    }
+    return { restored: snapshot, backup };
+  });
+}
+
+/** Drop a task's snapshots, e.g. once it can no longer be restored from the trash. */
+export function deleteSnapshots(repoRoot: string, taskId: string): Promise<void> {
+  return withRepoLock(repoRoot, async () => {
+    for (const snapshot of await listSnapshots(repoRoot, taskId)) {
+      await exec('git', ['update-ref', '-d', `${snapshotRefPrefix(taskId)}${snapshot.id}`], {
+        cwd: repoRoot,
+      });
+    }
+  });
+}
+
 export async function getBranchLog(worktreePath: string): Promise<string> {
   const mainBranch = await detectMainBranch(worktreePath).catch(() => 'HEAD');
   try {
@@ -858,6 +2028,188 @@ export async function getBranchLog(worktreePath: string): Promise<string> {
   }
 }
 
+export interface TaskCommit {
+  sha: string;
+  author_name: string;
+  author_email: string;
+  /** Author date, epoch ms. */
+  date: number;
+  subject: string;
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+}
+
+const LOG_FORMAT = '%x1e%H%x1f%an%x1f%ae%x1f%at%x1f%s';
+
+/** Parse `git log --numstat` output written with LOG_FORMAT. */
+export function parseTaskLog(stdout: string): TaskCommit[] {
+  const commits: TaskCommit[] = [];
+  for (const record of stdout.split('\x1e').slice(1)) {
+    const [header, ...stats] = record.split('\n');
+    const [sha, author_name, author_email, at, subject] = header.split('\x1f');
+    const commit: TaskCommit = {
+      sha,
+      author_name,
+      author_email,
+      date: Number(at) * 1000,
+      subject: subject ?? '',
+      files_changed: 0,
+      insertions: 0,
+      deletions: 0,
+    };
+    for (const line of stats) {
+      const [added, removed] = line.split('\t');
+      if (removed === undefined) continue;
+      commit.files_changed++;
+      // Binary files show `-`
+      commit.insertions += parseInt(added, 10) || 0;
+      commit.deletions += parseInt(removed, 10) || 0;
+    }
+    commits.push(commit);
+  }
+  return commits;
+}
+
+/** The commits on the task branch that main doesn't have, newest first, a page at a time. */
+export async function getTaskLog(
+  worktreePath: string,
+  limit = 50,
+  offset = 0,
+): Promise<TaskCommit[]> {
+  const mainBranch = await detectMainBranch(worktreePath).catch(() => 'HEAD');
+  const { stdout } = await exec(
+    'git',
+    [
+      'log',
+      `--format=${LOG_FORMAT}`,
+      '--numstat',
+      `--max-count=${limit}`,
+      `--skip=${offset}`,
+      `${mainBranch}..HEAD`,
+      '--',
+    ],
+    { cwd: worktreePath, maxBuffer: MAX_BUFFER },
+  );
+  return parseTaskLog(stdout);
+}
+
+export interface BlameLine {
+  /** 1-based line in the working-tree file. */
+  line: number;
+  content: string;
+  /** The commit that last touched the line; all zeros for uncommitted changes. */
+  sha: string;
+  author: string;
+  /** Author date, epoch ms. */
+  date: number;
+  summary: string;
+  /** Written on the task branch, committed or not, rather than inherited from main. */
+  on_branch: boolean;
+}
+
+/** Parse `git blame --porcelain`. Commit details only follow a commit's first line. */
+export function parseBlame(stdout: string, branchCommits: Set<string>): BlameLine[] {
+  const commits = new Map<string, { author: string; date: number; summary: string }>();
+  const lines: BlameLine[] = [];
+  let current: { sha: string; line: number } | null = null;
+  for (const raw of stdout.split('\n')) {
+    if (raw.startsWith('\t')) {
+      if (!current) continue;
+      const info = commits.get(current.sha) ?? { author: '', date: 0, summary: '' };
+      lines.push({
+        line: current.line,
+        content: raw.slice(1),
+        sha: current.sha,
+        ...info,
+        on_branch: /^0+$/.test(current.sha) || branchCommits.has(current.sha),
+      });
+      current = null;
+      continue;
+    }
+    const header = raw.match(/^([0-9a-f]{40,64}) \d+ (\d+)/);
+    if (header) {
+      current = { sha: header[1], line: Number(header[2]) };
+      if (!commits.has(current.sha)) commits.set(current.sha, { author: '', date: 0, summary: '' });
+      continue;
+    }
+    const info = current && commits.get(current.sha);
+    if (!info) continue;
+    const space = raw.indexOf(' ');
+    const key = space < 0 ? raw : raw.slice(0, space);
+    const value = space < 0 ? '' : raw.slice(space + 1);
+    if (key === 'author') info.author = value;
+    else if (key === 'author-time') info.date = Number(value) * 1000;
+    else if (key === 'summary') info.summary = value;
+  }
+  return lines;
+}
+
+/**
+ * Who last touched each line of a worktree file, with uncommitted edits
+ * included, and whether the line came from the task branch or from main.
+ */
+export async function getBlame(worktreePath: string, filePath: string): Promise<BlameLine[]> {
+  const mainBranch = await detectMainBranch(worktreePath).catch(() => 'HEAD');
+  const [{ stdout: blame }, { stdout: revs }] = await Promise.all([
+    exec('git', ['blame', '--porcelain', '--', filePath], {
+      cwd: worktreePath,
+      maxBuffer: MAX_BUFFER,
+    }),
+    exec('git', ['rev-list', `${mainBranch}..HEAD`], { cwd: worktreePath, maxBuffer: MAX_BUFFER }),
+  ]);
+  return parseBlame(blame, new Set(revs.split('\n').filter(Boolean)));
+}
+
+/** Everything a task changed since it forked, committed or not, as one patch of tracked files. */
+export async function getTaskDiff(worktreePath: string): Promise<string> {
+  const base = await getTaskDiffBase(worktreePath);
+  const { stdout } = await exec('git', ['diff', base], {
+    cwd: worktreePath,
+    maxBuffer: MAX_BUFFER,
+  });
+  return stdout;
+}
+
+/** The commit `getTaskDiff` compares the worktree against: where the task forked. */
+export async function getTaskDiffBase(worktreePath: string): Promise<string> {
+  const headHash = await pinHead(worktreePath);
+  return detectMergeBase(worktreePath, headHash).catch(() => headHash);
+}
+
+/**
+ * Run git for output too large to buffer, handing stdout to `onData` as it
+ * arrives. Aborting `signal` stops git and resolves.
+ */
+export function streamGit(
+  cwd: string,
+  args: string[],
+  onData: (chunk: string) => void,
+  signal: AbortSignal,
+): Promise<void> {
+  if (signal.aborted) return Promise.resolve();
+  const remote = remoteCommand('git', args, cwd);
+  return new Promise((resolve, reject) => {
+    const child = remote
+      ? spawn(remote.file, remote.args, { cwd: os.homedir(), signal })
+      : spawn('git', args, { cwd, signal });
+    let stderr = '';
+    child.stdout.setEncoding('utf8');
+    child.stdout.on('data', (chunk: string) => {
+      if (!signal.aborted) onData(chunk);
+    });
+    child.stderr.setEncoding('utf8');
+    child.stderr.on('data', (chunk: string) => {
+      stderr = (stderr + chunk).slice(-8192);
+    });
+    child.on('error', (err) => (signal.aborted ? resolve() : reject(err)));
+    child.on('close', (code) => {
+      if (signal.aborted || code === 0) resolve();
+      else reject(new Error(`git ${args[0]} failed: ${stderr.trim()}`));
+    });
+  });
+}
+
 export async function getChangedFilesFromBranch(
   projectRoot: string,
   branchName: string,
@@ -871,14 +2223,27 @@ export async function getChangedFilesFromBranch(
   }>
 > {
   const mainBranch = await detectMainBranch(projectRoot);
+  return getChangedFilesInRange(projectRoot, `${mainBranch}...${branchName}`);
+}
 
+async function getChangedFilesInRange(
+  projectRoot: string,
+  range: string,
+): Promise<
+  Array<{
+    path: string;
+    lines_added: number;
+    lines_removed: number;
+    status: string;
+    committed: boolean;
+  }>
+> {
   let diffStr = '';
   try {
-    const { stdout } = await exec(
-      'git',
-      ['diff', '--raw', '--numstat', `${mainBranch}...${branchName}`],
-      { cwd: projectRoot, maxBuffer: MAX_BUFFER },
-    );
+    const { stdout } = await exec('git', ['diff', '--raw', '--numstat', range], {
+      cwd: projectRoot,
+      maxBuffer: MAX_BUFFER,
+    });
     diffStr = stdout;
   } catch {
     return [];
@@ -909,6 +2274,127 @@ export async function getChangedFilesFromBranch(
   return files;
 }
 
+/**
+ * Files changed by `branchB` relative to `branchA`, three-dot style: what B did
+ * since the two branches forked. Used to compare racing tasks.
+ */
+export function diffTaskBranches(projectRoot: string, branchA: string, branchB: string) {
+  return getChangedFilesInRange(projectRoot, `${branchA}...${branchB}`);
+}
+
+export async function getFileDiffBetweenBranches(
+  projectRoot: string,
+  branchA: string,
+  branchB: string,
+  filePath: string,
+): Promise<StructuredFileDiff> {
+  const { stdout } = await exec(
+    'git',
+    ['diff', '--no-color', '--no-ext-diff', `${branchA}...${branchB}`, '--', filePath],
+    { cwd: projectRoot, maxBuffer: MAX_BUFFER },
+  );
+  return parseUnifiedDiff(filePath, stdout);
+}
+
+async function isTracked(worktreePath: string, filePath: string): Promise<boolean> {
+  try {
+    await exec('git', ['ls-files', '--error-unmatch', '--', filePath], { cwd: worktreePath });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Raw unified diff of one file in a worktree against the branch's merge
+ * base, including uncommitted changes. Untracked files diff against empty.
+ */
+async function getWorktreeFileDiffText(
+  worktreePath: string,
+  filePath: string,
+): Promise<{ base: string; diff: string }> {
+  const headHash = await pinHead(worktreePath);
+  const base = await detectMergeBase(worktreePath, headHash).catch(() => headHash);
+  const { stdout } = await exec(
+    'git',
+    ['diff', '--no-color', '--no-ext-diff', base, '--', filePath],
+    { cwd: worktreePath, maxBuffer: MAX_BUFFER },
+  );
+  if (stdout || !fs.existsSync(path.join(worktreePath, filePath))) return { base, diff: stdout };
+  if (await isTracked(worktreePath, filePath)) return { base, diff: '' };
+
+  try {
+    await exec('git', ['diff', '--no-color', '--no-index', '--', '/dev/null', filePath], {
+      cwd: worktreePath,
+      maxBuffer: MAX_BUFFER,
+    });
+    return { base, diff: '' };
+  } catch (err) {
+    // --no-index exits 1 when the files differ, with the diff on stdout
+    const out = (err as { stdout?: string }).stdout;
+    if (typeof out !== 'string') throw err;
+    return { base, diff: out };
+  }
+}
+
+/** Hunk-level diff of one worktree file against the merge base. */
+export async function getStructuredFileDiff(
+  worktreePath: string,
+  filePath: string,
+): Promise<StructuredFileDiff> {
+  const { diff } = await getWorktreeFileDiffText(worktreePath, filePath);
+  return parseUnifiedDiff(filePath, diff);
+}
+
+/**
+ * Restore files in a worktree to their merge-base content. Files that did not
+ * exist at the merge base are deleted. Only the working tree is touched; the
+ * index and commits are left alone so the revert shows up as a change.
+ */
+export async function revertFiles(worktreePath: string, filePaths: string[]): Promise<void> {
+  const headHash = await pinHead(worktreePath);
+  const base = await detectMergeBase(worktreePath, headHash).catch(() => headHash);
+  await withRepoLock(worktreePath, async () => {
+    for (const filePath of filePaths) {
+      try {
+        await exec('git', ['cat-file', '-e', `${base}:${filePath}`], { cwd: worktreePath });
+      } catch {
+        // Added by the task — reverting means removing it
+        await removePath(path.join(worktreePath, filePath), worktreePath);
+        continue;
+      }
+      await exec('git', ['restore', `--source=${base}`, '--worktree', '--', filePath], {
+        cwd: worktreePath,
+      });
+    }
+  });
+  invalidateWorktreeStatus(worktreePath);
+}
+
+/** Undo selected hunks (ids from getStructuredFileDiff) of one worktree file. */
+export async function revertHunks(
+  worktreePath: string,
+  filePath: string,
+  hunkIds: number[],
+): Promise<void> {
+  const { diff } = await getWorktreeFileDiffText(worktreePath, filePath);
+  const patch = selectHunks(diff, hunkIds);
+  if (!patch) throw new Error('No matching hunks to revert');
+  const tmpDir = await fs.promises.mkdtemp(path.join(os.tmpdir(), 'parallel-code-revert-'));
+  const patchFile = path.join(tmpDir, 'revert.patch');
+  try {
+    await fs.promises.writeFile(patchFile, patch, 'utf8');
+    await withRepoLock(worktreePath, () =>
+      exec('git', ['apply', '-R', '--recount', '--whitespace=nowarn', patchFile], {
+        cwd: worktreePath,
+      }),
+    );
+    invalidateWorktreeStatus(worktreePath);
+  } finally {
+    await fs.promises.rm(tmpDir, { recursive: true, force: true });
+  }
+}
+
 export async function getFileDiffFromBranch(
   projectRoot: string,
   branchName: string,
@@ -964,23 +2450,313 @@ export async function getFileDiffFromBranch(
   return { diff, oldContent, newContent };
 }
 
-export async function pushTask(projectRoot: string, branchName: string): Promise<void> {
-  await exec('git', ['push', '-u', 'origin', '--', branchName], { cwd: projectRoot });
+/**
+ * Run a git command that takes `--progress`, reporting each stderr progress
+ * line to `onProgress` and to the current git operation, which can kill it.
+ */
+function runWithProgress(
+  cwd: string,
+  args: string[],
+  onProgress: (line: string) => void = () => {},
+  env?: NodeJS.ProcessEnv,
+): Promise<void> {
+  const op = currentGitOperation();
+  if (op?.signal.aborted) return Promise.reject(operationCancelledError());
+  const remote = remoteCommand('git', args, cwd, env);
+  return new Promise((resolve, reject) => {
+    const child = remote
+      ? spawn(remote.file, remote.args, { cwd: os.homedir(), signal: op?.signal })
+      : spawn('git', args, { cwd, env, signal: op?.signal });
+    let stderr = '';
+    child.stderr.setEncoding('utf8');
+    child.stderr.on('data', (chunk: string) => {
+      stderr = (stderr + chunk).slice(-8192);
+      // Progress lines are redrawn with \r; report each one
+      for (const line of chunk.split(/[\r\n]+/)) {
+        if (!line.trim()) continue;
+        onProgress(line.trim());
+        op?.onLine(line.trim());
+      }
+    });
+    child.on('error', (err) => reject(op?.signal.aborted ? operationCancelledError() : err));
+    child.on('close', (code) => {
+      if (op?.signal.aborted) reject(operationCancelledError());
+      else if (code === 0) resolve();
+      else reject(new Error(`git ${args[0]} failed: ${stderr.trim()}`));
+    });
+  });
+}
+
+function fetchWithProgress(
+  cwd: string,
+  args: string[],
+  onProgress: (line: string) => void,
+): Promise<void> {
+  return runWithProgress(cwd, ['fetch', '--progress', ...args], onProgress);
+}
+
+export async function fetchProject(
+  projectRoot: string,
+  remote: string,
+  onProgress: (line: string) => void = () => {},
+): Promise<void> {
+  // Not under the repo lock: that would hold up every task's commits and merges for as long
+  // as the transfer takes. git locks each remote-tracking ref it updates itself
+  await fetchWithProgress(projectRoot, ['--prune', '--', remote], onProgress);
+  invalidateMergeBaseCache();
+}
+
+/**
+ * Fetch `remote` and fast-forward the base branch to its remote counterpart.
+ * Never creates a merge commit: diverged base branches are reported as errors.
+ */
+export async function updateBaseBranch(
+  projectRoot: string,
+  remote: string,
+  onProgress: (line: string) => void = () => {},
+): Promise<{ main_branch: string; updated: boolean }> {
+  return withRepoLock(projectRoot, async () => {
+    const mainBranch = await detectMainBranch(projectRoot);
+    const revParse = async (ref: string) =>
+      (await exec('git', ['rev-parse', ref], { cwd: projectRoot })).stdout.trim();
+    const before = await revParse(`refs/heads/${mainBranch}`);
+
+    const checkedOut = (await getCurrentBranchName(projectRoot).catch(() => null)) === mainBranch;
+    if (checkedOut) {
+      // fetch refuses to update the checked-out branch; merge it ourselves
+      await fetchWithProgress(projectRoot, ['--', remote, mainBranch], onProgress);
+      try {
+        await exec('git', ['merge', '--ff-only', `${remote}/${mainBranch}`], { cwd: projectRoot });
+      } catch (e) {
+        throw new Error(`Cannot fast-forward ${mainBranch}: ${e}`);
+      }
+    } else {
+      // refspec without "+" only ever fast-forwards
+      await fetchWithProgress(
+        projectRoot,
+        ['--', remote, `refs/heads/${mainBranch}:refs/heads/${mainBranch}`],
+        onProgress,
+      );
+    }
+
+    invalidateMergeBaseCache();
+    const after = await revParse(`refs/heads/${mainBranch}`);
+    return { main_branch: mainBranch, updated: before !== after };
+  });
 }
 
-export async function rebaseTask(worktreePath: string): Promise<void> {
-  const lockKey = await detectRepoLockKey(worktreePath).catch(() => worktreePath);
+/**
+ * Whether a branch's reflog shows work of its own (commits, cherry-picks),
+ * as opposed to only being created or moved along with its base. Subjects
+ * are `git reflog --format=%gs` output.
+ */
+export function reflogHasOwnCommits(subjects: string[]): boolean {
+  return subjects.some((s) => /^(commit|cherry-pick)\b/.test(s));
+}
 
-  return withWorktreeLock(lockKey, async () => {
+/**
+ * Which of `branchNames` were merged into the main branch: the tip is
+ * reachable from main and the branch had commits of its own, so a fresh
+ * or merely rebased branch doesn't count. Squash merges aren't seen here;
+ * their pull requests are.
+ */
+export async function findMergedBranches(
+  projectRoot: string,
+  branchNames: string[],
+): Promise<string[]> {
+  const mainBranch = await detectMainBranch(projectRoot);
+  const merged: string[] = [];
+  for (const branch of branchNames) {
+    if (branch === mainBranch) continue;
+    try {
+      await exec('git', ['merge-base', '--is-ancestor', `refs/heads/${branch}`, mainBranch], {
+        cwd: projectRoot,
+      });
+      const { stdout } = await exec(
+        'git',
+        ['reflog', 'show', '--format=%gs', `refs/heads/${branch}`],
+        { cwd: projectRoot },
+      );
+      if (reflogHasOwnCommits(stdout.split('\n'))) merged.push(branch);
+    } catch {
+      // Not an ancestor, or the branch is gone
+    }
+  }
+  return merged;
+}
+
+export async function pushTask(projectRoot: string, branchName: string): Promise<void> {
+  // A branch tracking its base keeps that upstream
+  const upstream = await upstreamOf(projectRoot, branchName);
+  // The transfer runs outside the repo lock, as fetchProject's does; only the branch's
+  // config is written under it
+  await exec('git', ['push', 'origin', '--', branchName], { cwd: projectRoot });
+  if (upstream) return;
+  await withWorktreeLock(lockKeyForRepoRoot(projectRoot), async () => {
+    await exec('git', ['config', `branch.${branchName}.remote`, 'origin'], { cwd: projectRoot });
+    await exec('git', ['config', `branch.${branchName}.merge`, `refs/heads/${branchName}`], {
+      cwd: projectRoot,
+    });
+  });
+}
+
+/**
+ * Rebase the worktree's branch onto main. A conflict aborts the rebase,
+ * unless `keepConflicts` leaves it stopped for `getConflicts` and friends.
+ */
+export async function rebaseTask(worktreePath: string, keepConflicts = false): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
     const mainBranch = await detectMainBranch(worktreePath);
     try {
       await exec('git', ['rebase', mainBranch], { cwd: worktreePath });
     } catch (e) {
-      await exec('git', ['rebase', '--abort'], { cwd: worktreePath }).catch((recoverErr) =>
-        console.warn('git rebase --abort failed:', recoverErr),
-      );
-      throw new Error(`Rebase failed: ${e}`);
+      const conflicts = await listUnmergedFiles(worktreePath);
+      invalidateWorktreeStatus(worktreePath);
+      if (!keepConflicts || conflicts.length === 0) {
+        await exec('git', ['rebase', '--abort'], { cwd: worktreePath }).catch((recoverErr) =>
+          log.warn('git rebase --abort failed:', recoverErr),
+        );
+      }
+      if (conflicts.length === 0) throw new Error(`Rebase failed: ${e}`);
+      throw new AppError('merge_conflict', `Rebase failed: ${e}`, {
+        path: worktreePath,
+        files: conflicts,
+        stderr: stderrOf(e),
+      });
     }
     invalidateMergeBaseCache();
   });
 }
+
+// --- Conflict resolution ---
+
+export type ConflictOperation = 'merge' | 'rebase' | 'cherry-pick';
+
+export interface ConflictFile {
+  path: string;
+  /** The common ancestor's version; null when the file didn't exist there. */
+  base: string | null;
+  /** The checked-out side: main while rebasing, the task's branch while merging. */
+  ours: string | null;
+  /** The side being applied: the task's commit while rebasing. */
+  theirs: string | null;
+  /** The working-tree file, conflict markers included; null when it was deleted. */
+  working: string | null;
+  regions: ConflictRegion[];
+  /** Binary files have no content or regions; resolve them by writing a whole version. */
+  binary: boolean;
+}
+
+export interface ConflictState {
+  operation: ConflictOperation | null;
+  files: ConflictFile[];
+}
+
+/** The merge, rebase or cherry-pick the worktree stopped in, if any. */
+async function conflictOperation(worktreePath: string): Promise<ConflictOperation | null> {
+  const { stdout } = await exec('git', ['rev-parse', '--absolute-git-dir'], { cwd: worktreePath });
+  const gitDir = stdout.trim();
+  const has = (name: string) => fs.existsSync(path.join(gitDir, name));
+  if (has('rebase-merge') || has('rebase-apply')) return 'rebase';
+  if (has('MERGE_HEAD')) return 'merge';
+  if (has('CHERRY_PICK_HEAD')) return 'cherry-pick';
+  return null;
+}
+
+/** A conflicted path relative to the worktree, refusing anything that would leave it. */
+function conflictFilePath(worktreePath: string, filePath: string): string {
+  const resolved = path.resolve(worktreePath, filePath);
+  const relative = path.relative(worktreePath, resolved);
+  if (!relative || relative.startsWith('..') || path.isAbsolute(relative)) {
+    throw new Error(`Invalid path: ${filePath}`);
+  }
+  return resolved;
+}
+
+async function conflictFile(worktreePath: string, filePath: string): Promise<ConflictFile> {
+  const stage = (n: number) =>
+    exec('git', ['show', `:${n}:${filePath}`], { cwd: worktreePath, maxBuffer: MAX_BUFFER })
+      .then(({ stdout }) => stdout)
+      .catch(() => null);
+  const [base, ours, theirs] = await Promise.all([stage(1), stage(2), stage(3)]);
+  const working = await fs.promises
+    .readFile(conflictFilePath(worktreePath, filePath), 'utf8')
+    .catch(() => null);
+  const binary = [base, ours, theirs, working].some((v) => v?.includes('\0'));
+  if (binary) {
+    const none = { base: null, ours: null, theirs: null, working: null };
+    return { path: filePath, ...none, regions: [], binary };
+  }
+  const regions = working === null ? [] : parseConflictRegions(working);
+  return { path: filePath, base, ours, theirs, working, regions, binary };
+}
+
+/**
+ * The files a stopped merge, rebase or cherry-pick conflicted on, with the
+ * three versions of each and the conflict regions left in the working tree.
+ */
+export async function getConflicts(worktreePath: string): Promise<ConflictState> {
+  const operation = await conflictOperation(worktreePath);
+  if (!operation) return { operation, files: [] };
+  const files = await listUnmergedFiles(worktreePath);
+  return { operation, files: await Promise.all(files.map((f) => conflictFile(worktreePath, f))) };
+}
+
+/** Write the resolved content of a conflicted file and mark it resolved. */
+export async function resolveConflict(
+  worktreePath: string,
+  filePath: string,
+  content: string,
+): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    if (!(await listUnmergedFiles(worktreePath)).includes(filePath)) {
+      throw new Error(`${filePath} has no conflict to resolve`);
+    }
+    await fs.promises.writeFile(conflictFilePath(worktreePath, filePath), content, 'utf8');
+    await exec('git', ['add', '--', filePath], { cwd: worktreePath });
+    invalidateWorktreeStatus(worktreePath);
+  });
+}
+
+/**
+ * Finish the stopped operation once every conflict is resolved. A rebase
+ * can stop again at a later commit; the returned state has its conflicts.
+ */
+export async function continueMerge(worktreePath: string): Promise<ConflictState> {
+  return withRepoLock(worktreePath, async () => {
+    const operation = await conflictOperation(worktreePath);
+    if (!operation) throw new Error('No merge, rebase or cherry-pick is in progress');
+    const unresolved = await listUnmergedFiles(worktreePath);
+    if (unresolved.length > 0) {
+      throw new AppError('merge_conflict', 'Resolve every conflict before continuing', {
+        path: worktreePath,
+        files: unresolved,
+      });
+    }
+    // Keep the prepared commit messages instead of opening an editor
+    const env = { ...process.env, GIT_EDITOR: 'true' };
+    const args = operation === 'merge' ? ['commit', '--no-edit'] : [operation, '--continue'];
+    try {
+      await exec('git', args, { cwd: worktreePath, env });
+    } catch (e) {
+      if ((await listUnmergedFiles(worktreePath)).length === 0) throw e;
+    } finally {
+      invalidateWorktreeStatus(worktreePath);
+      invalidateMergeBaseCache();
+    }
+    return getConflicts(worktreePath);
+  });
+}
+
+/** Give up on the stopped operation, putting the worktree back as it was before it. */
+export async function abortMerge(worktreePath: string): Promise<void> {
+  return withRepoLock(worktreePath, async () => {
+    const operation = await conflictOperation(worktreePath);
+    if (!operation) return;
+    try {
+      await exec('git', [operation, '--abort'], { cwd: worktreePath });
+    } finally {
+      invalidateWorktreeStatus(worktreePath);
+    }
+  });
+}
@@ -0,0 +1,40 @@
+import { describe, expect, it } from 'vitest';
+import { parseIssueRef, summarizeChecks } from './github.js';
+
+describe('parseIssueRef', () => {
+  it('accepts numbers, short refs and issue URLs', () => {
+    expect(parseIssueRef('42')).toEqual({ number: 42 });
+    expect(parseIssueRef(' #42 ')).toEqual({ number: 42 });
+    expect(parseIssueRef('acme/widgets#7')).toEqual({ repo: 'acme/widgets', number: 7 });
+    expect(parseIssueRef('https://github.com/acme/widgets/issues/7#issuecomment-1')).toEqual({
+      repo: 'acme/widgets',
+      number: 7,
+    });
+  });
+
+  it('rejects anything else', () => {
+    expect(parseIssueRef('https://github.com/acme/widgets/pull/7')).toBeNull();
+    expect(parseIssueRef('seven')).toBeNull();
+    expect(parseIssueRef('--repo=evil')).toBeNull();
+  });
+});
+
+describe('summarizeChecks', () => {
+  it('fails on any failure and is pending while anything runs', () => {
+    expect(summarizeChecks([])).toBe('none');
+    expect(
+      summarizeChecks([
+        { status: 'COMPLETED', conclusion: 'SUCCESS' },
+        { status: 'COMPLETED', conclusion: 'SKIPPED' },
+        { state: 'SUCCESS' },
+      ]),
+    ).toBe('passing');
+    expect(
+      summarizeChecks([{ status: 'IN_PROGRESS', conclusion: '' }, { state: 'SUCCESS' }]),
+    ).toBe('pending');
+    expect(summarizeChecks([{ state: 'PENDING' }])).toBe('pending');
+    expect(
+      summarizeChecks([{ status: 'IN_PROGRESS' }, { status: 'COMPLETED', conclusion: 'FAILURE' }]),
+    ).toBe('failing');
+  });
+});
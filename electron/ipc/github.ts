@@ -0,0 +1,142 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import type { PullRequestStatus, RemoteIssue, CreatedPullRequest } from './remotes.js';
+
+const exec = promisify(execFile);
+
+/** Run the GitHub CLI, turning its failures into readable errors. */
+async function gh(args: string[], cwd: string): Promise<string> {
+  try {
+    const { stdout } = await exec('gh', args, { cwd });
+    return stdout;
+  } catch (err) {
+    if ((err as NodeJS.ErrnoException).code === 'ENOENT') {
+      throw new Error('GitHub CLI (gh) is not installed or not on PATH');
+    }
+    const stderr = (err as { stderr?: string }).stderr?.trim();
+    throw new Error(stderr || `gh ${args.slice(0, 2).join(' ')} failed`);
+  }
+}
+
+export interface IssueRef {
+  /** `owner/repo`; absent means the repo `gh` infers from the project's remotes. */
+  repo?: string;
+  number: number;
+}
+
+/** Accepts `123`, `#123`, `owner/repo#123` or an issue URL. Returns null otherwise. */
+export function parseIssueRef(ref: string): IssueRef | null {
+  const text = ref.trim();
+  const bare = text.match(/^#?(\d+)$/);
+  if (bare) return { number: parseInt(bare[1], 10) };
+  const short = text.match(/^([\w.-]+\/[\w.-]+)#(\d+)$/);
+  if (short) return { repo: short[1], number: parseInt(short[2], 10) };
+  const url = text.match(/^https?:\/\/(?:www\.)?github\.com\/([\w.-]+\/[\w.-]+)\/issues\/(\d+)/);
+  if (url) return { repo: url[1], number: parseInt(url[2], 10) };
+  return null;
+}
+
+/** Fetch an issue with the GitHub CLI, authenticated as the user's `gh` login. */
+export async function fetchIssue(projectRoot: string, ref: string): Promise<RemoteIssue> {
+  const parsed = parseIssueRef(ref);
+  if (!parsed) throw new Error(`Not a GitHub issue reference: ${ref}`);
+  const args = ['issue', 'view', String(parsed.number)];
+  if (parsed.repo) args.push('--repo', parsed.repo);
+  args.push('--json', 'number,title,body,url,state,labels');
+
+  const raw = JSON.parse(await gh(args, projectRoot)) as Omit<RemoteIssue, 'labels'> & {
+    labels?: Array<{ name: string }>;
+  };
+  return {
+    number: raw.number,
+    title: raw.title,
+    body: raw.body ?? '',
+    url: raw.url,
+    state: raw.state,
+    labels: (raw.labels ?? []).map((l) => l.name),
+  };
+}
+
+// --- Pull requests ---
+
+/** One entry of `statusCheckRollup`: a check run (status/conclusion) or a commit status (state). */
+interface RollupEntry {
+  status?: string;
+  conclusion?: string;
+  state?: string;
+}
+
+const FAILED_CONCLUSIONS = new Set([
+  'FAILURE',
+  'ERROR',
+  'CANCELLED',
+  'TIMED_OUT',
+  'ACTION_REQUIRED',
+  'STARTUP_FAILURE',
+]);
+
+/** Overall CI status: any failure fails; otherwise anything unfinished is pending. */
+export function summarizeChecks(rollup: RollupEntry[]): PullRequestStatus['ci'] {
+  if (rollup.length === 0) return 'none';
+  let pending = false;
+  for (const entry of rollup) {
+    const result = entry.conclusion || entry.state || '';
+    if (FAILED_CONCLUSIONS.has(result)) return 'failing';
+    const finished = entry.status ? entry.status === 'COMPLETED' : result !== 'PENDING';
+    if (!finished || result === 'EXPECTED') pending = true;
+  }
+  return pending ? 'pending' : 'passing';
+}
+
+/** The PR for `branchName`, or null when the branch has none. */
+export async function fetchPrStatus(
+  cwd: string,
+  branchName: string,
+): Promise<PullRequestStatus | null> {
+  let stdout: string;
+  try {
+    stdout = await gh(
+      ['pr', 'view', branchName, '--json', 'number,url,state,statusCheckRollup'],
+      cwd,
+    );
+  } catch (err) {
+    if (err instanceof Error && /no pull requests found/i.test(err.message)) return null;
+    throw err;
+  }
+  const raw = JSON.parse(stdout) as Omit<PullRequestStatus, 'ci'> & {
+    statusCheckRollup?: RollupEntry[];
+  };
+  return {
+    number: raw.number,
+    url: raw.url,
+    state: raw.state,
+    ci: summarizeChecks(raw.statusCheckRollup ?? []),
+  };
+}
+
+/** Open a PR for an already pushed branch. */
+export async function createPullRequest(
+  cwd: string,
+  opts: { branch: string; base: string; title: string; body: string },
+): Promise<CreatedPullRequest> {
+  const stdout = await gh(
+    [
+      'pr',
+      'create',
+      '--head',
+      opts.branch,
+      '--base',
+      opts.base,
+      '--title',
+      opts.title,
+      '--body',
+      opts.body,
+    ],
+    cwd,
+  );
+  // gh prints the new PR's URL last
+  const url = stdout.trim().split('\n').pop() ?? '';
+  const number = url.match(/\/pull\/(\d+)/)?.[1];
+  if (!number) throw new Error(`Unexpected gh pr create output: ${stdout.trim()}`);
+  return { number: parseInt(number, 10), url };
+}
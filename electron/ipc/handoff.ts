@@ -0,0 +1,35 @@
+import { getBranchLog, getTaskDiff } from './git.js';
+import { getAgentScrollback } from './pty.js';
+import { transcriptTail } from './scrollback-search.js';
+
+const MAX_DIFF_CHARS = 30_000;
+const TRANSCRIPT_LINES = 60;
+
+/** What one agent leaves the next when a task is handed off. */
+export interface HandoffContext {
+  /** The task's changes since it forked, committed or not. */
+  diff: string;
+  diff_truncated: boolean;
+  /** `- <hash> <subject>` per commit on the task branch. */
+  commits: string;
+  /** The end of the agent's terminal output, as plain text. */
+  transcript: string;
+}
+
+export async function getHandoffContext(
+  agentId: string,
+  worktreePath: string,
+): Promise<HandoffContext> {
+  const [diff, commits] = await Promise.all([
+    getTaskDiff(worktreePath).catch(() => ''),
+    getBranchLog(worktreePath),
+  ]);
+  const scrollback = getAgentScrollback(agentId);
+  const output = scrollback ? Buffer.from(scrollback, 'base64').toString('utf8') : '';
+  return {
+    diff: diff.slice(0, MAX_DIFF_CHARS),
+    diff_truncated: diff.length > MAX_DIFF_CHARS,
+    commits,
+    transcript: transcriptTail(output, TRANSCRIPT_LINES),
+  };
+}
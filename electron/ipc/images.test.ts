@@ -0,0 +1,101 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterEach, beforeEach, describe, it, expect } from 'vitest';
+import {
+  attachmentPath,
+  clipboardImageFrom,
+  extensionForImageMime,
+  imageReference,
+  WORKTREE_ATTACHMENT_DIR,
+} from './images.js';
+
+describe('extensionForImageMime', () => {
+  it('maps supported image types', () => {
+    expect(extensionForImageMime('image/png')).toBe('png');
+    expect(extensionForImageMime(' IMAGE/JPEG ')).toBe('jpg');
+    expect(extensionForImageMime('image/tiff')).toBeNull();
+  });
+});
+
+describe('clipboardImageFrom', () => {
+  it('returns the image as PNG with its size', () => {
+    const png = Buffer.from('png-bytes');
+    const image = {
+      isEmpty: () => false,
+      toPNG: () => png,
+      getSize: () => ({ width: 640, height: 480 }),
+    };
+    expect(clipboardImageFrom(image)).toEqual({
+      base64_data: png.toString('base64'),
+      mime_type: 'image/png',
+      width: 640,
+      height: 480,
+      size: png.length,
+    });
+  });
+
+  it('returns null when the clipboard has no image', () => {
+    const image = {
+      isEmpty: () => true,
+      toPNG: () => Buffer.alloc(0),
+      getSize: () => ({ width: 0, height: 0 }),
+    };
+    expect(clipboardImageFrom(image)).toBeNull();
+  });
+});
+
+describe('attachmentPath', () => {
+  let root: string;
+  let worktree: string;
+
+  beforeEach(() => {
+    root = fs.mkdtempSync(path.join(os.tmpdir(), 'attach-'));
+    worktree = path.join(root, 'worktree');
+    fs.mkdirSync(path.join(worktree, '.git'), { recursive: true });
+  });
+
+  afterEach(() => {
+    fs.rmSync(root, { recursive: true, force: true });
+  });
+
+  it('copies outside files into the ignored attachments dir', () => {
+    const source = path.join(root, 'spec.pdf');
+    fs.writeFileSync(source, 'spec');
+    const attached = attachmentPath(worktree, source);
+    expect(path.dirname(attached)).toBe(path.join(worktree, WORKTREE_ATTACHMENT_DIR));
+    expect(attached.endsWith('-spec.pdf')).toBe(true);
+    expect(fs.readFileSync(attached, 'utf8')).toBe('spec');
+    expect(fs.readFileSync(path.join(worktree, '.parallel-code', '.gitignore'), 'utf8')).toBe(
+      '*\n',
+    );
+  });
+
+  it('references files in the worktree and directories where they are', () => {
+    const inside = path.join(worktree, 'notes.md');
+    fs.writeFileSync(inside, 'notes');
+    expect(attachmentPath(worktree, inside)).toBe(inside);
+    expect(attachmentPath(worktree, root)).toBe(root);
+  });
+});
+
+describe('imageReference', () => {
+  const cwd = '/repo/.worktrees/task';
+  const file = `${cwd}/.parallel-code/images/paste-1.png`;
+
+  it('uses @ mentions for Claude Code and Gemini', () => {
+    expect(imageReference('claude', file, cwd)).toBe('@.parallel-code/images/paste-1.png ');
+    expect(imageReference('/usr/local/bin/gemini', file, cwd)).toBe(
+      '@.parallel-code/images/paste-1.png ',
+    );
+  });
+
+  it('uses the bare path for other agents and shells', () => {
+    expect(imageReference('codex', file, cwd)).toBe('.parallel-code/images/paste-1.png ');
+    expect(imageReference('', file, cwd)).toBe('.parallel-code/images/paste-1.png ');
+  });
+
+  it('keeps absolute paths outside the worktree and quotes spaces', () => {
+    expect(imageReference('codex', '/tmp/my images/a.png', cwd)).toBe('"/tmp/my images/a.png" ');
+  });
+});
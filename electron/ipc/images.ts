@@ -0,0 +1,164 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { randomUUID } from 'crypto';
+import type { NativeImage } from 'electron';
+import { getAgentSessionInfo, writeToAgent } from './pty.js';
+
+export const MAX_PASTED_IMAGE_BYTES = 15 * 1024 * 1024;
+export const MAX_ATTACHMENT_BYTES = 100 * 1024 * 1024;
+
+/** Pasted images live here inside the worktree, so sandboxed agents can read them. */
+export const WORKTREE_IMAGE_DIR = path.join('.parallel-code', 'images');
+/** Files dropped onto a session from outside its worktree are copied here. */
+export const WORKTREE_ATTACHMENT_DIR = path.join('.parallel-code', 'attachments');
+
+export function extensionForImageMime(mimeType: string): string | null {
+  switch (mimeType.trim().toLowerCase()) {
+    case 'image/png':
+      return 'png';
+    case 'image/jpeg':
+      return 'jpg';
+    case 'image/webp':
+      return 'webp';
+    case 'image/gif':
+      return 'gif';
+    default:
+      return null;
+  }
+}
+
+/** An image read from the system clipboard, as PNG. */
+export interface ClipboardImage {
+  base64_data: string;
+  mime_type: 'image/png';
+  width: number;
+  height: number;
+  /** Bytes of PNG data. */
+  size: number;
+}
+
+/**
+ * The clipboard image Electron read natively, or null when the clipboard
+ * holds none. Used where the renderer's clipboard API can't see images,
+ * e.g. screenshots on Linux or files copied in the macOS Finder.
+ */
+export function clipboardImageFrom(
+  image: Pick<NativeImage, 'isEmpty' | 'toPNG' | 'getSize'>,
+): ClipboardImage | null {
+  if (image.isEmpty()) return null;
+  const png = image.toPNG();
+  if (!png.length) return null;
+  if (png.length > MAX_PASTED_IMAGE_BYTES) {
+    throw new Error(`Clipboard image too large (${png.length} bytes)`);
+  }
+  const { width, height } = image.getSize();
+  return {
+    base64_data: png.toString('base64'),
+    mime_type: 'image/png',
+    width,
+    height,
+    size: png.length,
+  };
+}
+
+function decodeImage(base64Data: string, mimeType: string): { bytes: Buffer; ext: string } {
+  if (!mimeType || !base64Data) throw new Error('Invalid clipboard image payload');
+  const ext = extensionForImageMime(mimeType);
+  if (!ext) throw new Error(`Unsupported clipboard image type: ${mimeType}`);
+  const bytes = Buffer.from(base64Data, 'base64');
+  if (!bytes.length) throw new Error('Clipboard image is empty');
+  if (bytes.length > MAX_PASTED_IMAGE_BYTES) {
+    throw new Error(`Clipboard image too large (${bytes.length} bytes)`);
+  }
+  return { bytes, ext };
+}
+
+/**
+ * `worktreeDir` inside `cwd` when it's a worktree (`.parallel-code` ignores
+ * itself so it never shows up in diffs), else `tmpName` in the temp dir.
+ */
+function sessionFileDir(cwd: string, worktreeDir: string, tmpName: string): string {
+  const inWorktree = fs.existsSync(path.join(cwd, '.git'));
+  const dir = inWorktree
+    ? path.join(cwd, worktreeDir)
+    : path.join(os.tmpdir(), 'parallel-code', tmpName);
+  fs.mkdirSync(dir, { recursive: true });
+  if (inWorktree) {
+    const ignore = path.join(cwd, '.parallel-code', '.gitignore');
+    if (!fs.existsSync(ignore)) fs.writeFileSync(ignore, '*\n', 'utf8');
+  }
+  return dir;
+}
+
+function writeImage(cwd: string, bytes: Buffer, ext: string): string {
+  const dir = sessionFileDir(cwd, WORKTREE_IMAGE_DIR, 'clipboard-images');
+  const filePath = path.join(dir, `paste-${Date.now()}-${randomUUID().slice(0, 8)}.${ext}`);
+  fs.writeFileSync(filePath, bytes);
+  return filePath;
+}
+
+function isInside(dir: string, filePath: string): boolean {
+  const rel = path.relative(dir, filePath);
+  return rel !== '' && !rel.startsWith('..') && !path.isAbsolute(rel);
+}
+
+/**
+ * Where the agent should read a dropped path from. Anything already in the
+ * worktree, and directories, are referenced where they are; other files are
+ * copied into `.parallel-code/attachments` so sandboxed agents can read them.
+ */
+export function attachmentPath(cwd: string, source: string): string {
+  const stat = fs.statSync(source);
+  if (stat.isDirectory() || isInside(cwd, source)) return source;
+  if (!stat.isFile()) throw new Error(`Not a file: ${source}`);
+  if (stat.size > MAX_ATTACHMENT_BYTES) {
+    throw new Error(`${path.basename(source)} is too large to attach (${stat.size} bytes)`);
+  }
+  const dir = sessionFileDir(cwd, WORKTREE_ATTACHMENT_DIR, 'attachments');
+  const target = path.join(dir, `${Date.now()}-${path.basename(source)}`);
+  fs.copyFileSync(source, target);
+  return target;
+}
+
+/**
+ * How to reference a file in a prompt for the agent running `command`.
+ * Claude Code and Gemini CLI attach `@path` mentions; Codex and shells take
+ * the path as-is.
+ */
+export function imageReference(command: string, filePath: string, cwd: string): string {
+  const rel = path.relative(cwd, filePath);
+  const shown = rel && !rel.startsWith('..') && !path.isAbsolute(rel) ? rel : filePath;
+  const quoted = /\s/.test(shown) ? `"${shown}"` : shown;
+  const bin = path.basename(command).toLowerCase();
+  if (bin === 'claude' || bin === 'gemini') return `@${quoted} `;
+  return `${quoted} `;
+}
+
+/** Attach dropped files to an agent session and type their references into the PTY. */
+export function attachFilesToSession(agentId: string, paths: string[]): string {
+  const session = getAgentSessionInfo(agentId);
+  if (!session) throw new Error('Terminal session not found');
+  if (paths.length === 0) throw new Error('No files to attach');
+  const command = session.isShell ? '' : session.command;
+  const text = paths
+    .map((p) => imageReference(command, attachmentPath(session.cwd, p), session.cwd))
+    .join('');
+  writeToAgent(agentId, text);
+  return text;
+}
+
+/** Save a pasted image for an agent session and type its reference into the PTY. */
+export function pasteImageToSession(opts: {
+  agentId: string;
+  base64Data: string;
+  mimeType: string;
+}): string {
+  const session = getAgentSessionInfo(opts.agentId);
+  if (!session) throw new Error('Terminal session not found');
+  const { bytes, ext } = decodeImage(opts.base64Data, opts.mimeType);
+  const filePath = writeImage(session.cwd, bytes, ext);
+  const text = imageReference(session.isShell ? '' : session.command, filePath, session.cwd);
+  writeToAgent(opts.agentId, text);
+  return text;
+}
@@ -0,0 +1,49 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+const stateDir = vi.hoisted(() => ({ path: '' }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => stateDir.path,
+}));
+
+import { createLogger, getRecentLogs, initFileLogging } from './log.js';
+
+stateDir.path = fs.mkdtempSync(path.join(os.tmpdir(), 'log-test-'));
+
+afterAll(() => {
+  fs.rmSync(stateDir.path, { recursive: true, force: true });
+});
+
+describe('logger', () => {
+  it('filters recent entries by level and limit', () => {
+    vi.spyOn(console, 'warn').mockImplementation(() => {});
+    vi.spyOn(console, 'error').mockImplementation(() => {});
+    const log = createLogger('git');
+    log.debug('probing');
+    log.info('fetched');
+    log.warn('prune failed', new Error('boom'));
+    log.error('merge failed');
+
+    const warnings = getRecentLogs('warn', 10);
+    expect(warnings.map((e) => [e.scope, e.level, e.message])).toEqual([
+      ['git', 'warn', 'prune failed'],
+      ['git', 'error', 'merge failed'],
+    ]);
+    expect(warnings[0].error).toContain('boom');
+    expect(getRecentLogs('debug', 1).map((e) => e.message)).toEqual(['merge failed']);
+  });
+
+  it('writes entries as JSON lines to the state dir', () => {
+    initFileLogging();
+    createLogger('pty').info('spawned agent');
+    const lines = fs
+      .readFileSync(path.join(stateDir.path, 'logs', 'app.log'), 'utf8')
+      .trim()
+      .split('\n')
+      .map((l) => JSON.parse(l) as { scope: string; message: string });
+    expect(lines.at(-1)).toMatchObject({ scope: 'pty', message: 'spawned agent' });
+  });
+});
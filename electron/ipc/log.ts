@@ -0,0 +1,100 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+
+export const LOG_LEVELS = ['debug', 'info', 'warn', 'error'] as const;
+export type LogLevel = (typeof LOG_LEVELS)[number];
+
+export interface LogEntry {
+  ts: number;
+  level: LogLevel;
+  /** Subsystem that logged, e.g. "git" or "pty". */
+  scope: string;
+  message: string;
+  error?: string;
+}
+
+const MAX_MEMORY_ENTRIES = 2000;
+const MAX_FILE_BYTES = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES = 3;
+
+const recent: LogEntry[] = [];
+let logPath: string | null = null;
+
+export function isLogLevel(v: unknown): v is LogLevel {
+  return typeof v === 'string' && (LOG_LEVELS as readonly string[]).includes(v);
+}
+
+/** Start writing entries to `logs/app.log` in the state dir. Called once at startup. */
+export function initFileLogging(): void {
+  logPath = path.join(getStateDir(), 'logs', 'app.log');
+}
+
+function describeError(err: unknown): string {
+  if (err instanceof Error) return err.stack ?? err.message;
+  return String(err);
+}
+
+/** app.log → app.1.log → … → app.N.log, dropping the oldest. */
+function rotate(file: string): void {
+  const rotated = (n: number) => file.replace(/\.log$/, `.${n}.log`);
+  fs.rmSync(rotated(MAX_ROTATED_FILES), { force: true });
+  for (let n = MAX_ROTATED_FILES - 1; n >= 1; n--) {
+    if (fs.existsSync(rotated(n))) fs.renameSync(rotated(n), rotated(n + 1));
+  }
+  fs.renameSync(file, rotated(1));
+}
+
+function writeToFile(entry: LogEntry): void {
+  if (!logPath) return;
+  try {
+    fs.mkdirSync(path.dirname(logPath), { recursive: true });
+    try {
+      if (fs.statSync(logPath).size >= MAX_FILE_BYTES) rotate(logPath);
+    } catch {
+      // No log file yet
+    }
+    fs.appendFileSync(logPath, JSON.stringify(entry) + '\n', 'utf8');
+  } catch (err) {
+    // Keep logging in memory; a broken log dir must not break the app
+    logPath = null;
+    console.warn('File logging disabled:', err);
+  }
+}
+
+function write(level: LogLevel, scope: string, message: string, err?: unknown): void {
+  const entry: LogEntry = { ts: Date.now(), level, scope, message };
+  if (err !== undefined) entry.error = describeError(err);
+  recent.push(entry);
+  if (recent.length > MAX_MEMORY_ENTRIES) recent.splice(0, recent.length - MAX_MEMORY_ENTRIES);
+  writeToFile(entry);
+  if (level === 'warn' || level === 'error') {
+    const line = `[${scope}] ${message}`;
+    if (level === 'warn') console.warn(line, ...(err === undefined ? [] : [err]));
+    else console.error(line, ...(err === undefined ? [] : [err]));
+  }
+}
+
+export interface Logger {
+  debug: (message: string, err?: unknown) => void;
+  info: (message: string, err?: unknown) => void;
+  warn: (message: string, err?: unknown) => void;
+  error: (message: string, err?: unknown) => void;
+}
+
+/** Logger for one subsystem. Entries are kept in memory and, once enabled, on disk. */
+export function createLogger(scope: string): Logger {
+  return {
+    debug: (message, err) => write('debug', scope, message, err),
+    info: (message, err) => write('info', scope, message, err),
+    warn: (message, err) => write('warn', scope, message, err),
+    error: (message, err) => write('error', scope, message, err),
+  };
+}
+
+/** The latest `limit` entries at `level` or above from this session, oldest first. */
+export function getRecentLogs(level: LogLevel = 'info', limit = 200): LogEntry[] {
+  const min = LOG_LEVELS.indexOf(level);
+  const matching = recent.filter((e) => LOG_LEVELS.indexOf(e.level) >= min);
+  return matching.slice(Math.max(0, matching.length - limit));
+}
@@ -0,0 +1,55 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { mergeMcpServers, serversForProject } from './mcp.js';
+
+const server = (roots: string[] = []) => ({
+  command: 'npx',
+  args: ['-y', 'server'],
+  env: {},
+  project_roots: roots,
+});
+
+describe('serversForProject', () => {
+  it('keeps unscoped servers and those scoped to the project', () => {
+    const servers = {
+      everywhere: server(),
+      here: server(['/work/app/']),
+      elsewhere: server(['/work/other']),
+    };
+    expect(Object.keys(serversForProject(servers, '/work/app'))).toEqual(['everywhere', 'here']);
+  });
+
+  it('writes env only when set', () => {
+    const servers = { a: server(), b: { ...server(), env: { TOKEN: 'x' } } };
+    const result = serversForProject(servers, '/work/app');
+    expect(result.a).toEqual({ command: 'npx', args: ['-y', 'server'] });
+    expect(result.b.env).toEqual({ TOKEN: 'x' });
+  });
+});
+
+describe('mergeMcpServers', () => {
+  const entry = { command: 'npx', args: [] };
+
+  it('adds servers while keeping other settings', () => {
+    expect(mergeMcpServers({ theme: 'dark' }, { fs: entry })).toEqual({
+      theme: 'dark',
+      mcpServers: { fs: entry },
+    });
+  });
+
+  it('lets servers already in the file win', () => {
+    const existing = { mcpServers: { fs: { command: 'custom' } } };
+    expect(mergeMcpServers(existing, { fs: entry, git: entry }).mcpServers).toEqual({
+      fs: { command: 'custom' },
+      git: entry,
+    });
+  });
+
+  it('replaces a config that is not an object', () => {
+    expect(mergeMcpServers(null, { fs: entry })).toEqual({ mcpServers: { fs: entry } });
+  });
+});
@@ -0,0 +1,132 @@
+import fs from 'fs';
+import path from 'path';
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { getSettings, type McpServerConfig } from './settings.js';
+import { createLogger } from './log.js';
+
+const exec = promisify(execFile);
+const log = createLogger('mcp');
+
+/** Project-level MCP config files, by the agent that reads them. */
+export const MCP_CONFIG_FILES = {
+  'claude-code': '.mcp.json',
+  gemini: path.join('.gemini', 'settings.json'),
+} as const;
+
+interface McpServerEntry {
+  command: string;
+  args: string[];
+  env?: Record<string, string>;
+}
+
+/** Servers from settings that apply to `projectRoot`. */
+export function serversForProject(
+  servers: Record<string, McpServerConfig>,
+  projectRoot: string,
+): Record<string, McpServerEntry> {
+  const root = path.resolve(projectRoot);
+  const result: Record<string, McpServerEntry> = {};
+  for (const [name, server] of Object.entries(servers)) {
+    const scoped = server.project_roots.length > 0;
+    if (scoped && !server.project_roots.some((p) => path.resolve(p) === root)) continue;
+    result[name] = {
+      command: server.command,
+      args: server.args,
+      ...(Object.keys(server.env).length > 0 ? { env: server.env } : {}),
+    };
+  }
+  return result;
+}
+
+/**
+ * Add `servers` to the `mcpServers` object of a parsed config. Servers the
+ * file already defines win, since they came with the repo. Other keys are kept.
+ */
+export function mergeMcpServers(
+  existing: unknown,
+  servers: Record<string, McpServerEntry>,
+): Record<string, unknown> {
+  const config =
+    existing && typeof existing === 'object' && !Array.isArray(existing)
+      ? { ...(existing as Record<string, unknown>) }
+      : {};
+  const current = config.mcpServers;
+  const defined =
+    current && typeof current === 'object' && !Array.isArray(current)
+      ? (current as Record<string, unknown>)
+      : {};
+  config.mcpServers = { ...servers, ...defined };
+  return config;
+}
+
+function isSymlink(p: string): boolean {
+  try {
+    return fs.lstatSync(p).isSymbolicLink();
+  } catch {
+    return false;
+  }
+}
+
+async function isTracked(worktreePath: string, file: string): Promise<boolean> {
+  try {
+    await exec('git', ['ls-files', '--error-unmatch', file], { cwd: worktreePath });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/** Keep generated files out of `git status` without touching the repo's .gitignore. */
+async function excludeFromGit(worktreePath: string, files: string[]): Promise<void> {
+  const { stdout } = await exec('git', ['rev-parse', '--git-path', 'info/exclude'], {
+    cwd: worktreePath,
+  });
+  const excludePath = path.resolve(worktreePath, stdout.trim());
+  const current = fs.existsSync(excludePath) ? fs.readFileSync(excludePath, 'utf8') : '';
+  const lines = new Set(current.split('\n'));
+  const missing = files.map((f) => `/${f.split(path.sep).join('/')}`).filter((f) => !lines.has(f));
+  if (missing.length === 0) return;
+  fs.mkdirSync(path.dirname(excludePath), { recursive: true });
+  const prefix = current && !current.endsWith('\n') ? '\n' : '';
+  fs.appendFileSync(excludePath, `${prefix}${missing.join('\n')}\n`);
+}
+
+/**
+ * Write the configured MCP servers into a new worktree's Claude Code and
+ * Gemini CLI config files, so every task's agents get the same tools.
+ * Config files the repo tracks are left alone. Codex only reads MCP servers
+ * from its global config, so it has nothing to write here.
+ */
+export async function materializeMcpConfig(
+  projectRoot: string,
+  worktreePath: string,
+): Promise<void> {
+  const servers = serversForProject(getSettings().mcp_servers, projectRoot);
+  if (Object.keys(servers).length === 0) return;
+
+  const written: string[] = [];
+  for (const file of Object.values(MCP_CONFIG_FILES)) {
+    const target = path.join(worktreePath, file);
+    try {
+      if (await isTracked(worktreePath, file)) {
+        log.info(`Leaving tracked ${file} in ${worktreePath} as it is`);
+        continue;
+      }
+      // A symlinked config dir belongs to the main checkout; don't write through it
+      if (isSymlink(path.dirname(target)) || isSymlink(target)) continue;
+      let existing: unknown = {};
+      if (fs.existsSync(target)) existing = JSON.parse(fs.readFileSync(target, 'utf8'));
+      fs.mkdirSync(path.dirname(target), { recursive: true });
+      fs.writeFileSync(target, JSON.stringify(mergeMcpServers(existing, servers), null, 2) + '\n');
+      written.push(file);
+    } catch (err) {
+      log.warn(`Failed to write ${file} into ${worktreePath}`, err);
+    }
+  }
+  if (written.length > 0) {
+    await excludeFromGit(worktreePath, written).catch((err) =>
+      log.warn(`Failed to exclude MCP config from git in ${worktreePath}`, err),
+    );
+  }
+}
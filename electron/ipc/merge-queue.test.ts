@@ -0,0 +1,156 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+
+const git = vi.hoisted(() => ({
+  calls: [] as string[],
+  rebaseTask: vi.fn(),
+  mergeTask: vi.fn(),
+}));
+const checks = vi.hoisted(() => ({ runTaskChecks: vi.fn() }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+vi.mock('./git.js', () => git);
+vi.mock('./checks.js', () => checks);
+vi.mock('./task-events.js', () => ({ recordTaskEvent: vi.fn() }));
+vi.mock('./webhooks.js', () => ({ emitWebhookEvent: vi.fn() }));
+vi.mock('./notifications.js', () => ({ notify: vi.fn() }));
+
+import { AppError } from './errors.js';
+import {
+  enqueueMerge,
+  failureReport,
+  listMergeQueue,
+  resumeMergeQueue,
+  type MergeQueueEntry,
+} from './merge-queue.js';
+
+function enqueue(
+  projectRoot: string,
+  taskId: string,
+  checkCommands: string[] = [],
+  dependsOn: string[] = [],
+) {
+  return enqueueMerge({
+    taskId,
+    projectRoot,
+    branchName: `task/${taskId}`,
+    worktreePath: `${projectRoot}/.worktrees/task/${taskId}`,
+    checks: checkCommands,
+    dependsOn,
+  });
+}
+
+const statusOf = (entry: MergeQueueEntry) =>
+  listMergeQueue().entries.find((e) => e.id === entry.id)?.status;
+
+describe('merge queue', () => {
+  beforeEach(() => {
+    git.calls = [];
+    git.rebaseTask.mockReset().mockImplementation(async (worktreePath: string) => {
+      git.calls.push(`rebase ${worktreePath.split('/').pop()}`);
+    });
+    git.mergeTask.mockReset().mockImplementation(async (_root: string, branch: string) => {
+      git.calls.push(`merge ${branch}`);
+      return { main_branch: 'main', lines_added: 1, lines_removed: 0 };
+    });
+    checks.runTaskChecks.mockReset().mockImplementation(async (opts: { taskId: string }) => {
+      git.calls.push(`checks ${opts.taskId}`);
+      return { task_id: opts.taskId, head: 'abc', finished: true, results: [] };
+    });
+  });
+
+  it('rebases, checks and merges each task in turn', async () => {
+    const a = enqueue('/repo-a', 'a', ['npm test']);
+    const b = enqueue('/repo-a', 'b');
+    expect(() => enqueue('/repo-a', 'b')).toThrow('already in the merge queue');
+    await vi.waitFor(() => expect(statusOf(b)).toBe('merged'));
+
+    expect(statusOf(a)).toBe('merged');
+    expect(git.calls).toEqual(['rebase a', 'checks a', 'merge task/a', 'rebase b', 'merge task/b']);
+  });
+
+  it('pauses the project at a conflict until resumed', async () => {
+    git.rebaseTask.mockRejectedValueOnce(
+      new AppError('merge_conflict', 'Rebase failed', { files: ['src/a.ts'] }),
+    );
+    const a = enqueue('/repo-b', 'c');
+    const b = enqueue('/repo-b', 'd');
+    await vi.waitFor(() => expect(statusOf(a)).toBe('failed'));
+
+    const failed = listMergeQueue().entries.find((e) => e.id === a.id);
+    expect(failed?.report).toMatchObject({ stage: 'rebase', conflicting_files: ['src/a.ts'] });
+    expect(listMergeQueue().paused).toContain('/repo-b');
+    expect(statusOf(b)).toBe('queued');
+    expect(git.mergeTask).not.toHaveBeenCalled();
+
+    resumeMergeQueue('/repo-b');
+    await vi.waitFor(() => expect(statusOf(b)).toBe('merged'));
+    expect(listMergeQueue().paused).not.toContain('/repo-b');
+  });
+
+  it('holds an entry until its dependencies merged', async () => {
+    git.rebaseTask.mockRejectedValueOnce(new Error('not yet'));
+    const f = enqueue('/repo-d', 'f', [], ['g']);
+    const g = enqueue('/repo-d', 'g');
+    await vi.waitFor(() => expect(statusOf(g)).toBe('failed'));
+    expect(statusOf(f)).toBe('queued');
+
+    const retry = enqueue('/repo-d', 'g');
+    resumeMergeQueue('/repo-d');
+    await vi.waitFor(() => expect(statusOf(f)).toBe('merged'));
+    expect(statusOf(retry)).toBe('merged');
+    expect(git.calls).toEqual(['rebase g', 'merge task/g', 'rebase f', 'merge task/f']);
+  });
+
+  it("fails an entry on resume when a dependency didn't merge", async () => {
+    git.rebaseTask.mockRejectedValueOnce(new Error('conflict'));
+    const h = enqueue('/repo-e', 'h');
+    const i = enqueue('/repo-e', 'i', [], ['h']);
+    await vi.waitFor(() => expect(statusOf(h)).toBe('failed'));
+
+    resumeMergeQueue('/repo-e');
+    await vi.waitFor(() => expect(statusOf(i)).toBe('failed'));
+    const failed = listMergeQueue().entries.find((e) => e.id === i.id);
+    expect(failed?.report).toMatchObject({ stage: 'dependencies' });
+    expect(failed?.report?.message).toContain('task/h');
+    expect(git.mergeTask).not.toHaveBeenCalled();
+    expect(listMergeQueue().paused).toContain('/repo-e');
+  });
+
+  it('stops at a failing check without merging', async () => {
+    const failing = {
+      command: 'npm test',
+      status: 'failed',
+      exit_code: 1,
+      started_at: 0,
+      finished_at: 1,
+      output_tail: '1 failed',
+    };
+    checks.runTaskChecks.mockResolvedValueOnce({
+      task_id: 'e',
+      head: 'abc',
+      finished: true,
+      results: [failing],
+    });
+    const entry = enqueue('/repo-c', 'e', ['npm test']);
+    await vi.waitFor(() => expect(statusOf(entry)).toBe('failed'));
+
+    const failed = listMergeQueue().entries.find((e) => e.id === entry.id);
+    expect(failed?.report).toMatchObject({ stage: 'checks', check: failing });
+    expect(git.mergeTask).not.toHaveBeenCalled();
+  });
+});
+
+describe('failureReport', () => {
+  it('keeps the conflicting files of merge conflicts only', () => {
+    const conflict = new AppError('merge_conflict', 'Merge failed', { files: ['a.ts'] });
+    expect(failureReport('merge', conflict).conflicting_files).toEqual(['a.ts']);
+    expect(failureReport('merge', new Error('dirty'))).toEqual({
+      stage: 'merge',
+      message: 'dirty',
+      conflicting_files: [],
+      check: null,
+    });
+  });
+});
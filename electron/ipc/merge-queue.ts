@@ -0,0 +1,320 @@
+import { randomUUID } from 'crypto';
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { mergeTask, rebaseTask } from './git.js';
+import { runTaskChecks, type CheckResult } from './checks.js';
+import { recordTaskEvent } from './task-events.js';
+import { emitWebhookEvent } from './webhooks.js';
+import { notify } from './notifications.js';
+import { AppError } from './errors.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('merge-queue');
+
+/** Finished entries kept in the list; older ones are dropped. */
+const MAX_FINISHED_ENTRIES = 100;
+
+export type MergeQueueStatus =
+  | 'queued'
+  | 'rebasing'
+  | 'checking'
+  | 'merging'
+  | 'merged'
+  | 'failed'
+  | 'cancelled';
+
+export type MergeQueueStage = 'dependencies' | 'rebase' | 'checks' | 'merge';
+
+/** Why an entry failed; the project's queue stays paused until resumed. */
+export interface MergeQueueReport {
+  stage: MergeQueueStage;
+  message: string;
+  /** Files that conflicted rebasing or merging. */
+  conflicting_files: string[];
+  /** The check that failed, with the end of its output. */
+  check: CheckResult | null;
+}
+
+/** A task branch waiting to be rebased, checked and merged into its project's main branch. */
+export interface MergeQueueEntry {
+  id: string;
+  task_id: string;
+  project_root: string;
+  branch_name: string;
+  worktree_path: string;
+  squash: boolean;
+  message: string | null;
+  /** Commands that must pass on the rebased branch before it merges. */
+  checks: string[];
+  /** Tasks that must merge through the queue first; the entry waits for them. */
+  depends_on: string[];
+  status: MergeQueueStatus;
+  enqueued_at: number;
+  started_at: number | null;
+  finished_at: number | null;
+  report: MergeQueueReport | null;
+  lines_added: number | null;
+  lines_removed: number | null;
+}
+
+export interface MergeQueueState {
+  entries: MergeQueueEntry[];
+  /** Project roots whose queue stopped at a failure. */
+  paused: string[];
+}
+
+export interface EnqueueMergeArgs {
+  taskId: string;
+  projectRoot: string;
+  branchName: string;
+  worktreePath: string;
+  squash?: boolean;
+  message?: string | null;
+  checks?: string[];
+  dependsOn?: string[];
+}
+
+let win: BrowserWindow | null = null;
+let entries: MergeQueueEntry[] = [];
+const paused = new Set<string>();
+/** Projects whose queue is being worked through right now. */
+const draining = new Set<string>();
+
+function isPending(entry: MergeQueueEntry): boolean {
+  return entry.status !== 'merged' && entry.status !== 'failed' && entry.status !== 'cancelled';
+}
+
+function send(channel: IPC, payload: unknown): void {
+  if (win && !win.isDestroyed()) win.webContents.send(channel, payload);
+}
+
+function changed(entry: MergeQueueEntry): void {
+  send(IPC.MergeQueueChanged, entry);
+}
+
+function setStatus(entry: MergeQueueEntry, status: MergeQueueStatus): void {
+  entry.status = status;
+  changed(entry);
+}
+
+/** The report for an error thrown by a stage, with the conflicting files when git had any. */
+export function failureReport(stage: MergeQueueStage, err: unknown): MergeQueueReport {
+  const conflicted = err instanceof AppError && err.code === 'merge_conflict';
+  return {
+    stage,
+    message: err instanceof Error ? err.message : String(err),
+    conflicting_files: (conflicted && err.context.files) || [],
+    check: null,
+  };
+}
+
+function fail(entry: MergeQueueEntry, report: MergeQueueReport): void {
+  entry.status = 'failed';
+  entry.report = report;
+  entry.finished_at = Date.now();
+  paused.add(entry.project_root);
+  changed(entry);
+  recordTaskEvent(entry.task_id, 'error', {
+    action: 'merge_queue',
+    stage: report.stage,
+    message: report.message,
+  });
+  const detail =
+    report.conflicting_files.length > 0 ? report.conflicting_files.join(', ') : report.message;
+  notify('merge_conflict', 'Merge queue paused', `${entry.branch_name}: ${detail}`, entry.task_id);
+}
+
+/**
+ * Whether an entry can land: 'ready' once every dependency merged through the
+ * queue, 'waiting' while one is still pending, else the branch of one that
+ * won't merge (its entry failed or was cancelled and nothing re-queued it).
+ */
+function dependencyState(entry: MergeQueueEntry): 'ready' | 'waiting' | { blocker: string } {
+  let state: 'ready' | 'waiting' = 'ready';
+  for (const taskId of entry.depends_on) {
+    const own = entries.filter((e) => e.task_id === taskId);
+    if (own.some((e) => e.status === 'merged')) continue;
+    const last = own[own.length - 1];
+    if (last === undefined || !isPending(last)) return { blocker: last?.branch_name ?? taskId };
+    state = 'waiting';
+  }
+  return state;
+}
+
+/** Rebase the entry's branch onto main as it is now, run its checks, then merge it. */
+async function land(entry: MergeQueueEntry): Promise<void> {
+  entry.started_at = Date.now();
+  setStatus(entry, 'rebasing');
+  try {
+    await rebaseTask(entry.worktree_path);
+  } catch (err) {
+    return fail(entry, failureReport('rebase', err));
+  }
+
+  if (entry.checks.length > 0) {
+    setStatus(entry, 'checking');
+    const taskId = entry.task_id;
+    try {
+      const run = await runTaskChecks(
+        { taskId, worktreePath: entry.worktree_path, commands: entry.checks },
+        (id, index, result) => send(IPC.CheckStatusChanged, { taskId: id, index, result }),
+        (chunk) => send(IPC.TaskTestOutput, { taskId, chunk }),
+      );
+      const failed = run.results.find((r) => r.status === 'failed');
+      if (failed) {
+        emitWebhookEvent('checks_failed', taskId, {
+          command: failed.command,
+          exit_code: failed.exit_code,
+          head: run.head,
+        });
+        return fail(entry, {
+          stage: 'checks',
+          message: `Check failed: ${failed.command}`,
+          conflicting_files: [],
+          check: failed,
+        });
+      }
+    } catch (err) {
+      return fail(entry, failureReport('checks', err));
+    }
+  }
+
+  setStatus(entry, 'merging');
+  try {
+    const result = await mergeTask(
+      entry.project_root,
+      entry.branch_name,
+      entry.squash,
+      entry.message,
+      false,
+    );
+    entry.lines_added = result.lines_added;
+    entry.lines_removed = result.lines_removed;
+    entry.finished_at = Date.now();
+    setStatus(entry, 'merged');
+    recordTaskEvent(entry.task_id, 'merged', {
+      ...result,
+      squash: entry.squash,
+      merge_queue: true,
+    });
+  } catch (err) {
+    fail(entry, failureReport('merge', err));
+  }
+}
+
+/** The next entry of a project to land; ones whose dependency can't merge fail on the way. */
+function nextEntry(projectRoot: string): MergeQueueEntry | undefined {
+  for (const entry of entries) {
+    if (entry.project_root !== projectRoot || entry.status !== 'queued') continue;
+    const state = dependencyState(entry);
+    if (state === 'ready') return entry;
+    if (state === 'waiting') continue;
+    fail(entry, {
+      stage: 'dependencies',
+      message: `Depends on ${state.blocker}, which didn't merge`,
+      conflicting_files: [],
+      check: null,
+    });
+    return undefined;
+  }
+  return undefined;
+}
+
+/**
+ * Land a project's queued entries one at a time, until it's empty or a
+ * failure pauses it. Entries wait behind their dependencies, which may be
+ * queued after them or in another project's queue.
+ */
+async function drain(projectRoot: string): Promise<void> {
+  if (draining.has(projectRoot)) return;
+  draining.add(projectRoot);
+  try {
+    while (!paused.has(projectRoot)) {
+      const next = nextEntry(projectRoot);
+      if (!next) break;
+      await land(next);
+      if (next.status !== 'merged') continue;
+      // Dependents waiting in other projects' queues can go now
+      for (const e of entries) {
+        if (e.status === 'queued' && e.project_root !== projectRoot) {
+          if (e.depends_on.includes(next.task_id)) void drain(e.project_root);
+        }
+      }
+    }
+  } catch (err) {
+    log.error(`Merge queue of ${projectRoot} stopped`, err);
+  } finally {
+    draining.delete(projectRoot);
+    prune();
+  }
+}
+
+function prune(): void {
+  // Kept while a pending entry still waits on how they ended
+  const needed = new Set(entries.filter(isPending).flatMap((e) => e.depends_on));
+  const finished = entries.filter((e) => !isPending(e) && !needed.has(e.task_id));
+  if (finished.length <= MAX_FINISHED_ENTRIES) return;
+  const drop = new Set(finished.slice(0, finished.length - MAX_FINISHED_ENTRIES));
+  entries = entries.filter((e) => !drop.has(e));
+}
+
+/** Add a task to the end of its project's merge queue. */
+export function enqueueMerge(args: EnqueueMergeArgs): MergeQueueEntry {
+  if (entries.some((e) => e.task_id === args.taskId && isPending(e))) {
+    throw new Error('Task is already in the merge queue');
+  }
+  const entry: MergeQueueEntry = {
+    id: randomUUID(),
+    task_id: args.taskId,
+    project_root: args.projectRoot,
+    branch_name: args.branchName,
+    worktree_path: args.worktreePath,
+    squash: args.squash ?? false,
+    message: args.message ?? null,
+    checks: args.checks ?? [],
+    depends_on: args.dependsOn ?? [],
+    status: 'queued',
+    enqueued_at: Date.now(),
+    started_at: null,
+    finished_at: null,
+    report: null,
+    lines_added: null,
+    lines_removed: null,
+  };
+  entries.push(entry);
+  changed(entry);
+  void drain(entry.project_root);
+  return entry;
+}
+
+export function listMergeQueue(): MergeQueueState {
+  return { entries, paused: [...paused] };
+}
+
+/** Take a queued entry out of the queue; one already being landed can't be stopped. */
+export function removeFromMergeQueue(entryId: string): void {
+  const entry = entries.find((e) => e.id === entryId);
+  if (!entry) throw new Error('Merge queue entry not found');
+  if (!isPending(entry)) return;
+  if (entry.status !== 'queued') throw new Error('Entry is already being merged');
+  entry.status = 'cancelled';
+  entry.finished_at = Date.now();
+  changed(entry);
+}
+
+/** Drop the queued entries of a task, e.g. when it's deleted. */
+export function removeTaskFromMergeQueue(taskId: string): void {
+  for (const entry of entries) {
+    if (entry.task_id === taskId && entry.status === 'queued') removeFromMergeQueue(entry.id);
+  }
+}
+
+/** Carry on with a project's queue after a failure was dealt with. */
+export function resumeMergeQueue(projectRoot: string): void {
+  paused.delete(projectRoot);
+  void drain(projectRoot);
+}
+
+export function startMergeQueue(window: BrowserWindow): void {
+  win = window;
+}
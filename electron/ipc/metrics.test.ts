@@ -0,0 +1,90 @@
+import { describe, expect, it } from 'vitest';
+import { aggregateMetrics, emptyMetricsRows, metricsRange, type MetricsRows } from './metrics.js';
+
+const HOUR = 60 * 60_000;
+const at = (day: number, hour: number) => new Date(2026, 9, day, hour).getTime();
+const range = { since: at(12, 0), until: at(15, 0) };
+const tokens = { input_tokens: 100, output_tokens: 50, total_tokens: 150 };
+
+describe('aggregateMetrics', () => {
+  it('counts tasks created and merged per day', () => {
+    const rows: MetricsRows = {
+      ...emptyMetricsRows(),
+      events: [
+        { task_id: 't1', ts: at(12, 9), type: 'created' },
+        { task_id: 't2', ts: at(12, 10), type: 'created' },
+        { task_id: 't1', ts: at(14, 9), type: 'merged' },
+        { task_id: 't1', ts: at(14, 11), type: 'merged' },
+      ],
+    };
+    const metrics = aggregateMetrics(rows, range, new Set());
+    expect(metrics.days).toEqual([
+      { day: '2026-10-12', created: 2, merged: 0 },
+      { day: '2026-10-13', created: 0, merged: 0 },
+      { day: '2026-10-14', created: 0, merged: 1 },
+    ]);
+    expect(metrics.tasks_created).toBe(2);
+    expect(metrics.tasks_merged).toBe(1);
+  });
+
+  it('sums agent time within the range and tokens per agent', () => {
+    const rows: MetricsRows = {
+      ...emptyMetricsRows(),
+      sessions: [
+        // Started before the range: only the part inside counts
+        { agent_id: 'a1', agent_def_id: 'claude', started_at: at(11, 23), ended_at: at(12, 1) },
+        { agent_id: 'a2', agent_def_id: 'codex', started_at: at(13, 9), ended_at: at(13, 13) },
+        // Cut off by a crash: no known end
+        { agent_id: 'a3', agent_def_id: 'codex', started_at: at(13, 20), ended_at: null },
+        { agent_id: 'a4', agent_def_id: 'claude', started_at: at(14, 20), ended_at: null },
+      ],
+      usage: [
+        { agent_def_id: 'codex', ...tokens, cost_usd: 0.5 },
+        { agent_def_id: 'codex', ...tokens, cost_usd: null },
+      ],
+    };
+    const metrics = aggregateMetrics(rows, range, new Set(['a4']), at(14, 22));
+    expect(metrics.agents.map((a) => [a.agent_def_id, a.sessions, a.active_ms])).toEqual([
+      ['codex', 2, 4 * HOUR],
+      ['claude', 2, 3 * HOUR],
+    ]);
+    expect(metrics.agents[0].total_tokens).toBe(300);
+    expect(metrics.total_tokens).toBe(300);
+    expect(metrics.cost_usd).toBe(0.5);
+  });
+
+  it('averages the lifetime of tasks that ended', () => {
+    const rows: MetricsRows = {
+      ...emptyMetricsRows(),
+      ended: [
+        { created_at: at(12, 9), ended_at: at(12, 11) },
+        { created_at: at(12, 9), ended_at: at(12, 13) },
+        { created_at: null, ended_at: at(13, 9) },
+      ],
+    };
+    expect(aggregateMetrics(rows, range, new Set()).avg_task_lifetime_ms).toBe(3 * HOUR);
+    expect(aggregateMetrics(emptyMetricsRows(), range, new Set()).avg_task_lifetime_ms).toBeNull();
+  });
+
+  it('lists long ranges from the first day with activity', () => {
+    const rows: MetricsRows = {
+      ...emptyMetricsRows(),
+      events: [{ task_id: 't1', ts: at(14, 9), type: 'created' }],
+    };
+    const metrics = aggregateMetrics(rows, { since: 0, until: at(15, 12) }, new Set());
+    expect(metrics.days.map((d) => d.day)).toEqual(['2026-10-14', '2026-10-15']);
+  });
+});
+
+describe('metricsRange', () => {
+  it('defaults to the last 30 days', () => {
+    const now = at(15, 0);
+    expect(metricsRange({}, now)).toEqual({ since: now - 30 * 24 * HOUR, until: now });
+    expect(metricsRange({ since: 5, projectId: 'p1' }, now)).toEqual({
+      since: 5,
+      until: now,
+      projectId: 'p1',
+    });
+    expect(() => metricsRange({ since: now + 1 }, now)).toThrow('since must be before until');
+  });
+});
@@ -0,0 +1,180 @@
+/**
+ * How the app is used over a time range, aggregated from the history
+ * database for a dashboard: tasks started and merged per day, agent time
+ * and token spend per agent, and how long tasks live.
+ */
+
+const DAY_MS = 24 * 60 * 60_000;
+/** Longer ranges list days from the first one with activity instead of from `since`. */
+const MAX_EMPTY_DAYS = 366;
+export const DEFAULT_METRICS_DAYS = 30;
+
+export interface MetricsRange {
+  since: number;
+  until: number;
+  projectId?: string;
+}
+
+export interface TokenTotals {
+  input_tokens: number;
+  output_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+export interface DayActivity {
+  /** Local date, `YYYY-MM-DD`. */
+  day: string;
+  created: number;
+  merged: number;
+}
+
+export interface AgentActivity extends TokenTotals {
+  agent_def_id: string | null;
+  sessions: number;
+  /** Time its sessions ran within the range. */
+  active_ms: number;
+}
+
+export interface ActivityMetrics extends TokenTotals {
+  since: number;
+  until: number;
+  tasks_created: number;
+  tasks_merged: number;
+  /** From creation to being merged, archived or deleted, over tasks that ended in the range. */
+  avg_task_lifetime_ms: number | null;
+  /** Every day of the range, oldest first. */
+  days: DayActivity[];
+  /** Most active first. */
+  agents: AgentActivity[];
+}
+
+/** What the history database holds for a range, before aggregating. */
+export interface MetricsRows {
+  /** `created` and `merged` events in the range. */
+  events: Array<{ task_id: string; ts: number; type: string }>;
+  /** Tasks that were first merged, archived or deleted in the range. */
+  ended: Array<{ created_at: number | null; ended_at: number }>;
+  /** Sessions that ran during the range. */
+  sessions: Array<{
+    agent_id: string;
+    agent_def_id: string | null;
+    started_at: number;
+    ended_at: number | null;
+  }>;
+  /** Each session's latest usage, where it was last updated in the range. */
+  usage: Array<TokenTotals & { agent_def_id: string | null }>;
+}
+
+export function emptyMetricsRows(): MetricsRows {
+  return { events: [], ended: [], sessions: [], usage: [] };
+}
+
+/** The range `since`/`until` ask for; the last 30 days by default. */
+export function metricsRange(
+  query: { since?: number; until?: number; projectId?: string },
+  now = Date.now(),
+): MetricsRange {
+  const until = query.until ?? now;
+  const since = query.since ?? until - DEFAULT_METRICS_DAYS * DAY_MS;
+  if (since > until) throw new Error('since must be before until');
+  return query.projectId ? { since, until, projectId: query.projectId } : { since, until };
+}
+
+function dayKey(ts: number): string {
+  const d = new Date(ts);
+  const pad = (n: number) => String(n).padStart(2, '0');
+  return `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}`;
+}
+
+function addTokens(to: TokenTotals, from: TokenTotals): void {
+  to.input_tokens += from.input_tokens;
+  to.output_tokens += from.output_tokens;
+  to.total_tokens += from.total_tokens;
+  if (from.cost_usd !== null) to.cost_usd = (to.cost_usd ?? 0) + from.cost_usd;
+}
+
+function noTokens(): TokenTotals {
+  return { input_tokens: 0, output_tokens: 0, total_tokens: 0, cost_usd: null };
+}
+
+/**
+ * Aggregate `rows` over `range`. Sessions still open count up to now when
+ * their agent is running; others were cut off by a crash and have no known
+ * end, so only their count is kept.
+ */
+export function aggregateMetrics(
+  rows: MetricsRows,
+  range: MetricsRange,
+  runningAgentIds: ReadonlySet<string>,
+  now = Date.now(),
+): ActivityMetrics {
+  const days = new Map<string, DayActivity>();
+  const first = rows.events.reduce((min, e) => Math.min(min, e.ts), range.until);
+  const start = range.until - range.since > MAX_EMPTY_DAYS * DAY_MS ? first : range.since;
+  const d = new Date(start);
+  for (d.setHours(0, 0, 0, 0); d.getTime() < range.until; d.setDate(d.getDate() + 1)) {
+    const day = dayKey(d.getTime());
+    days.set(day, { day, created: 0, merged: 0 });
+  }
+
+  const created = new Set<string>();
+  const merged = new Set<string>();
+  const counted = new Set<string>();
+  for (const e of rows.events) {
+    if (e.type !== 'created' && e.type !== 'merged') continue;
+    const day = dayKey(e.ts);
+    // A task merged twice in a day (after a revert) is one merged task
+    if (counted.has(`${day}/${e.type}/${e.task_id}`)) continue;
+    counted.add(`${day}/${e.type}/${e.task_id}`);
+    const bucket = days.get(day) ?? { day, created: 0, merged: 0 };
+    days.set(day, bucket);
+    bucket[e.type]++;
+    (e.type === 'created' ? created : merged).add(e.task_id);
+  }
+
+  const lifetimes = rows.ended
+    .filter((t): t is { created_at: number; ended_at: number } => t.created_at !== null)
+    .map((t) => t.ended_at - t.created_at)
+    .filter((ms) => ms >= 0);
+
+  const agents = new Map<string | null, AgentActivity>();
+  const agentFor = (id: string | null): AgentActivity => {
+    let agent = agents.get(id);
+    if (!agent) {
+      agent = { agent_def_id: id, sessions: 0, active_ms: 0, ...noTokens() };
+      agents.set(id, agent);
+    }
+    return agent;
+  };
+  for (const s of rows.sessions) {
+    const agent = agentFor(s.agent_def_id);
+    agent.sessions++;
+    const end = s.ended_at ?? (runningAgentIds.has(s.agent_id) ? now : null);
+    if (end === null) continue;
+    const from = Math.max(s.started_at, range.since);
+    const to = Math.min(end, range.until);
+    if (to > from) agent.active_ms += to - from;
+  }
+  const totals = noTokens();
+  for (const u of rows.usage) {
+    addTokens(agentFor(u.agent_def_id), u);
+    addTokens(totals, u);
+  }
+
+  return {
+    since: range.since,
+    until: range.until,
+    tasks_created: created.size,
+    tasks_merged: merged.size,
+    avg_task_lifetime_ms:
+      lifetimes.length > 0
+        ? Math.round(lifetimes.reduce((a, b) => a + b, 0) / lifetimes.length)
+        : null,
+    ...totals,
+    days: [...days.values()].sort((a, b) => a.day.localeCompare(b.day)),
+    agents: [...agents.values()].sort(
+      (a, b) => b.active_ms - a.active_ms || b.total_tokens - a.total_tokens,
+    ),
+  };
+}
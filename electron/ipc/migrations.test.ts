@@ -0,0 +1,31 @@
+import { describe, expect, it } from 'vitest';
+import { migrateState, STATE_SCHEMA_VERSION, stateSchemaVersion } from './migrations.js';
+
+describe('migrateState', () => {
+  it('moves a single projectRoot into projects and assigns its tasks', () => {
+    const { state, from, to } = migrateState({
+      projectRoot: '/home/me/app',
+      taskOrder: ['t1'],
+      tasks: { t1: { id: 't1', name: 'Fix' } },
+    });
+    expect(from).toBe(0);
+    expect(to).toBe(STATE_SCHEMA_VERSION);
+    expect(state.projectRoot).toBeUndefined();
+    const [project] = state.projects as Array<{ id: string; name: string; path: string }>;
+    expect(project).toMatchObject({ name: 'app', path: '/home/me/app' });
+    expect(state.lastProjectId).toBe(project.id);
+    expect((state.tasks as Record<string, { projectId: string }>).t1.projectId).toBe(project.id);
+    expect(stateSchemaVersion(state)).toBe(STATE_SCHEMA_VERSION);
+  });
+
+  it('leaves current and newer state alone', () => {
+    const current = { schemaVersion: STATE_SCHEMA_VERSION, projects: [] };
+    expect(migrateState(current).state).toBe(current);
+    const newer = { schemaVersion: STATE_SCHEMA_VERSION + 1 };
+    expect(migrateState(newer)).toEqual({
+      state: newer,
+      from: STATE_SCHEMA_VERSION + 1,
+      to: STATE_SCHEMA_VERSION + 1,
+    });
+  });
+});
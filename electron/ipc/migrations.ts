@@ -0,0 +1,62 @@
+import { randomUUID } from 'crypto';
+
+type StateDoc = Record<string, unknown>;
+type Migration = (state: StateDoc) => StateDoc;
+
+/** State from before projects, when the app had a single `projectRoot`. */
+function projectsFromProjectRoot(state: StateDoc): StateDoc {
+  const { projectRoot, ...rest } = state;
+  const projects = Array.isArray(rest.projects) ? rest.projects : [];
+  if (typeof projectRoot !== 'string' || !projectRoot || projects.length > 0) return rest;
+
+  const id = randomUUID();
+  const segments = projectRoot.split('/');
+  const name = segments[segments.length - 1] || projectRoot;
+  const tasks: StateDoc = {};
+  if (rest.tasks && typeof rest.tasks === 'object') {
+    for (const [taskId, task] of Object.entries(rest.tasks as StateDoc)) {
+      tasks[taskId] =
+        task && typeof task === 'object' && !(task as StateDoc).projectId
+          ? { ...(task as StateDoc), projectId: id }
+          : task;
+    }
+  }
+  // Colors are assigned by the renderer to projects that lack one
+  return { ...rest, projects: [{ id, name, path: projectRoot }], lastProjectId: id, tasks };
+}
+
+/**
+ * `STATE_MIGRATIONS[n]` upgrades a version-`n` `state.json` to `n + 1`.
+ * Only ever append; a released migration must keep working on old files.
+ */
+const STATE_MIGRATIONS: Migration[] = [projectsFromProjectRoot];
+
+export const STATE_SCHEMA_VERSION = STATE_MIGRATIONS.length;
+
+/** The layout version a state file was written with; files from before versioning are 0. */
+export function stateSchemaVersion(state: StateDoc): number {
+  const v = state.schemaVersion;
+  return typeof v === 'number' && Number.isInteger(v) && v >= 0 ? v : 0;
+}
+
+export interface StateMigration {
+  state: StateDoc;
+  from: number;
+  to: number;
+}
+
+/**
+ * Bring a parsed state file up to `STATE_SCHEMA_VERSION`. State written by
+ * a newer app is returned as is, since its layout is unknown here.
+ */
+export function migrateState(state: StateDoc): StateMigration {
+  const from = stateSchemaVersion(state);
+  if (from >= STATE_SCHEMA_VERSION) return { state, from, to: from };
+  let migrated = state;
+  for (let v = from; v < STATE_SCHEMA_VERSION; v++) migrated = STATE_MIGRATIONS[v](migrated);
+  return {
+    state: { ...migrated, schemaVersion: STATE_SCHEMA_VERSION },
+    from,
+    to: STATE_SCHEMA_VERSION,
+  };
+}
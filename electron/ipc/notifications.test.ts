@@ -0,0 +1,83 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import type { BrowserWindow } from 'electron';
+
+const showMock = vi.hoisted(() => vi.fn());
+
+vi.mock('electron', () => ({
+  Notification: class {
+    static isSupported() {
+      return true;
+    }
+    on = vi.fn();
+    show = showMock;
+  },
+}));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import {
+  clearNotifications,
+  initNotifications,
+  listNotifications,
+  markNotificationsRead,
+  notify,
+} from './notifications.js';
+
+function fakeWindow(focused: boolean) {
+  const send = vi.fn();
+  const win = {
+    isDestroyed: () => false,
+    isFocused: () => focused,
+    webContents: { send },
+  } as unknown as BrowserWindow;
+  return { win, send };
+}
+
+beforeEach(() => {
+  clearNotifications();
+  showMock.mockReset();
+});
+
+describe('notify', () => {
+  it('adds to the inbox, pushes to the renderer and shows a desktop notification', () => {
+    const { win, send } = fakeWindow(false);
+    initNotifications(win);
+    const n = notify('agent_error', 'Agent exited with an error', 'Exit code 1', 'task-1');
+    expect(n).toMatchObject({ kind: 'agent_error', task_id: 'task-1', read: false });
+    expect(listNotifications()).toEqual([n]);
+    expect(send).toHaveBeenCalledWith('notification_added', n);
+    expect(showMock).toHaveBeenCalledTimes(1);
+  });
+
+  it('skips the desktop notification while the window is focused', () => {
+    initNotifications(fakeWindow(true).win);
+    notify('agent_finished', 'Agent finished', 'Ready for review', 'task-1');
+    expect(showMock).not.toHaveBeenCalled();
+    expect(listNotifications()).toHaveLength(1);
+  });
+
+  it('drops repeats of an unread notification', () => {
+    initNotifications(fakeWindow(true).win);
+    notify('merge_conflict', 'Merge conflicts detected', 'wt: a.ts');
+    expect(notify('merge_conflict', 'Merge conflicts detected', 'wt: a.ts')).toBeNull();
+    markNotificationsRead();
+    expect(notify('merge_conflict', 'Merge conflicts detected', 'wt: a.ts')).not.toBeNull();
+    expect(listNotifications()).toHaveLength(2);
+  });
+});
+
+describe('markNotificationsRead', () => {
+  it('marks only the given ids', () => {
+    initNotifications(fakeWindow(true).win);
+    notify('agent_waiting', 'Agent is waiting for input', 'A', 'task-a');
+    notify('agent_waiting', 'Agent is waiting for input', 'B', 'task-b');
+    const [newest, oldest] = listNotifications();
+    markNotificationsRead([oldest.id]);
+    expect(listNotifications().map((n) => [n.task_id, n.read])).toEqual([
+      [newest.task_id, false],
+      ['task-a', true],
+    ]);
+  });
+});
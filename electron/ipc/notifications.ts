@@ -0,0 +1,103 @@
+import { randomUUID } from 'crypto';
+import { Notification, type BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { getSettings, type NotificationKind } from './settings.js';
+
+export interface AppNotification {
+  id: string;
+  kind: NotificationKind;
+  title: string;
+  body: string;
+  task_id: string | null;
+  created_at: number;
+  read: boolean;
+}
+
+const MAX_NOTIFICATIONS = 200;
+/** Git operations slower than this get a "completed" notification. */
+export const SLOW_GIT_OPERATION_MS = 10_000;
+
+let win: BrowserWindow | null = null;
+// Newest first
+let inbox: AppNotification[] = [];
+
+export function initNotifications(window: BrowserWindow): void {
+  win = window;
+}
+
+/**
+ * Record a notification in the inbox and tell the renderer. A desktop
+ * notification is shown too when the kind is enabled in settings and the
+ * window isn't focused. Repeats of an unread notification are dropped, so
+ * pollers can call this freely.
+ */
+export function notify(
+  kind: NotificationKind,
+  title: string,
+  body: string,
+  taskId?: string,
+): AppNotification | null {
+  const taskKey = taskId ?? null;
+  const duplicate = inbox.some(
+    (n) => !n.read && n.kind === kind && n.task_id === taskKey && n.body === body,
+  );
+  if (duplicate) return null;
+
+  const notification: AppNotification = {
+    id: randomUUID(),
+    kind,
+    title,
+    body,
+    task_id: taskKey,
+    created_at: Date.now(),
+    read: false,
+  };
+  inbox = [notification, ...inbox].slice(0, MAX_NOTIFICATIONS);
+
+  if (win && !win.isDestroyed()) {
+    win.webContents.send(IPC.NotificationAdded, notification);
+    const focused = win.isFocused();
+    if (!focused && getSettings().desktop_notifications.includes(kind)) {
+      showDesktopNotification(notification);
+    }
+  }
+  return notification;
+}
+
+function showDesktopNotification(notification: AppNotification): void {
+  if (!Notification.isSupported()) return;
+  const desktop = new Notification({ title: notification.title, body: notification.body });
+  desktop.on('click', () => {
+    if (!win || win.isDestroyed()) return;
+    if (win.isMinimized()) win.restore();
+    win.focus();
+  });
+  desktop.show();
+}
+
+export function listNotifications(): AppNotification[] {
+  return inbox;
+}
+
+/** Mark the given notifications read, or all of them when `ids` is omitted. */
+export function markNotificationsRead(ids?: string[]): void {
+  const wanted = ids ? new Set(ids) : null;
+  inbox = inbox.map((n) => (!n.read && (!wanted || wanted.has(n.id)) ? { ...n, read: true } : n));
+}
+
+export function clearNotifications(): void {
+  inbox = [];
+}
+
+/** Run a git operation, notifying on completion if it took a while. */
+export async function withSlowOperationNotice<T>(label: string, op: () => Promise<T>): Promise<T> {
+  const started = Date.now();
+  try {
+    return await op();
+  } finally {
+    const elapsed = Date.now() - started;
+    if (elapsed >= SLOW_GIT_OPERATION_MS) {
+      notify('git_operation_done', `${label} finished`, `Took ${Math.round(elapsed / 1000)}s`);
+    }
+  }
+}
@@ -0,0 +1,49 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { matchOrphans, parseStartTimes, parseTrackedSessions } from './orphans.js';
+
+const session = {
+  agent_id: 'a1',
+  task_id: 't1',
+  pid: 4242,
+  command: 'claude',
+  is_shell: false,
+  started_at: Date.parse('Wed Oct 14 10:00:00 2026'),
+};
+
+describe('parseStartTimes', () => {
+  it('reads start times by pid', () => {
+    const output = ' 4242 Wed Oct 14 10:00:01 2026\n  77 Tue Oct 13 09:00:00 2026\n';
+    const times = parseStartTimes(output);
+    expect(times.get(4242)).toBe(Date.parse('Wed Oct 14 10:00:01 2026'));
+    expect(times.size).toBe(2);
+  });
+
+  it('skips lines it cannot read', () => {
+    expect(parseStartTimes('\nPID STARTED\n 12 not-a-date\n').size).toBe(0);
+  });
+});
+
+describe('matchOrphans', () => {
+  it('keeps sessions whose process started when they did', () => {
+    const times = new Map([[4242, session.started_at + 1_000]]);
+    expect(matchOrphans([session], times)).toEqual([session]);
+  });
+
+  it('drops sessions whose pid is gone or was reused', () => {
+    expect(matchOrphans([session], new Map())).toEqual([]);
+    const reused = new Map([[4242, session.started_at + 60 * 60_000]]);
+    expect(matchOrphans([session], reused)).toEqual([]);
+  });
+});
+
+describe('parseTrackedSessions', () => {
+  it('drops malformed entries', () => {
+    expect(parseTrackedSessions([session, { ...session, pid: 'x' }, null])).toEqual([session]);
+    expect(parseTrackedSessions({})).toEqual([]);
+  });
+});
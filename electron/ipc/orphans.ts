@@ -0,0 +1,166 @@
+import { execFile } from 'child_process';
+import fs from 'fs';
+import path from 'path';
+import { promisify } from 'util';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const exec = promisify(execFile);
+const log = createLogger('orphans');
+
+/**
+ * The sessions the app has running, kept on disk so that after a force-quit
+ * the next launch can find agent processes that outlived it. A clean quit
+ * kills every session and empties the file.
+ */
+export interface TrackedSession {
+  agent_id: string;
+  task_id: string;
+  pid: number;
+  command: string;
+  is_shell: boolean;
+  started_at: number;
+}
+
+/** A session from the previous run whose process is still alive. */
+export type OrphanedSession = TrackedSession;
+
+/** How far `ps`'s start time may be from `started_at`; it has one-second resolution. */
+const START_TOLERANCE_MS = 5_000;
+
+const tracked = new Map<string, TrackedSession>();
+let previous: TrackedSession[] = [];
+
+function getTrackedPath(): string {
+  return path.join(getStateDir(), 'running-sessions.json');
+}
+
+function saveTracked(): void {
+  try {
+    const file = getTrackedPath();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file + '.tmp', JSON.stringify([...tracked.values(), ...previous]), 'utf8');
+    fs.renameSync(file + '.tmp', file);
+  } catch (err) {
+    log.warn('Failed to save running sessions', err);
+  }
+}
+
+/** Sessions read back from disk, dropping malformed entries. */
+export function parseTrackedSessions(raw: unknown): TrackedSession[] {
+  if (!Array.isArray(raw)) return [];
+  return raw.filter(
+    (s: unknown): s is TrackedSession =>
+      typeof s === 'object' &&
+      s !== null &&
+      typeof (s as TrackedSession).agent_id === 'string' &&
+      typeof (s as TrackedSession).task_id === 'string' &&
+      Number.isInteger((s as TrackedSession).pid) &&
+      (s as TrackedSession).pid > 0 &&
+      typeof (s as TrackedSession).command === 'string' &&
+      typeof (s as TrackedSession).is_shell === 'boolean' &&
+      typeof (s as TrackedSession).started_at === 'number',
+  );
+}
+
+/** Read what the previous run left running. Call once at startup, before any spawn. */
+export function loadPreviousSessions(): void {
+  try {
+    previous = parseTrackedSessions(JSON.parse(fs.readFileSync(getTrackedPath(), 'utf8')));
+  } catch {
+    previous = [];
+  }
+}
+
+export function trackSession(session: TrackedSession): void {
+  tracked.set(session.agent_id, session);
+  saveTracked();
+}
+
+export function untrackSession(agentId: string): void {
+  if (tracked.delete(agentId)) saveTracked();
+}
+
+/** Forget this run's sessions once they've all been killed on quit. */
+export function clearTrackedSessions(): void {
+  tracked.clear();
+  saveTracked();
+}
+
+/** Parse `ps -o pid=,lstart=` output (in the C locale) into start times by pid. */
+export function parseStartTimes(output: string): Map<number, number> {
+  const times = new Map<number, number>();
+  for (const line of output.split('\n')) {
+    const match = /^\s*(\d+)\s+(.+?)\s*$/.exec(line);
+    if (!match) continue;
+    const time = Date.parse(match[2]);
+    if (!Number.isNaN(time)) times.set(Number(match[1]), time);
+  }
+  return times;
+}
+
+/**
+ * The sessions whose pid is running a process that started when the
+ * session did. A pid reused by an unrelated process started at another
+ * time, so it is never mistaken for an orphan.
+ */
+export function matchOrphans(
+  sessions: TrackedSession[],
+  startTimes: Map<number, number>,
+): OrphanedSession[] {
+  return sessions.filter((s) => {
+    const started = startTimes.get(s.pid);
+    return started !== undefined && Math.abs(started - s.started_at) <= START_TOLERANCE_MS;
+  });
+}
+
+/**
+ * Sessions from the previous run that are still running. Stale entries are
+ * dropped as a side effect.
+ */
+export async function listOrphanedSessions(): Promise<OrphanedSession[]> {
+  if (previous.length === 0) {
+    previous = [];
+    return [];
+  }
+  const pids = previous.map((s) => s.pid).join(',');
+  let output = '';
+  try {
+    // ps exits non-zero when none of the pids exist
+    ({ stdout: output } = await exec('ps', ['-o', 'pid=,lstart=', '-p', pids], {
+      env: { ...process.env, LC_ALL: 'C' },
+    }));
+  } catch (err) {
+    const stdout = (err as { stdout?: unknown }).stdout;
+    output = typeof stdout === 'string' ? stdout : '';
+  }
+  const orphans = matchOrphans(previous, parseStartTimes(output));
+  if (orphans.length !== previous.length) {
+    previous = orphans;
+    saveTracked();
+  }
+  return orphans;
+}
+
+/**
+ * Kill the previous run's surviving sessions, or leave them running and
+ * stop asking about them. The app can't reattach to their terminals either way.
+ */
+export async function resolveOrphanedSessions(kill: boolean): Promise<void> {
+  const orphans = kill ? await listOrphanedSessions() : [];
+  for (const s of orphans) {
+    try {
+      // node-pty starts each session in its own process group
+      process.kill(-s.pid, 'SIGTERM');
+    } catch {
+      try {
+        process.kill(s.pid, 'SIGTERM');
+      } catch (err) {
+        log.warn(`Failed to kill orphaned session ${s.agent_id} (pid ${s.pid})`, err);
+      }
+    }
+  }
+  if (orphans.length > 0) log.info(`Killed ${orphans.length} orphaned sessions`);
+  previous = [];
+  saveTracked();
+}
@@ -0,0 +1,39 @@
+import { describe, it, expect } from 'vitest';
+import { createOscTracker, parseOsc7Cwd } from './osc.js';
+
+describe('parseOsc7Cwd', () => {
+  it('decodes file URLs', () => {
+    expect(parseOsc7Cwd('file://host/Users/me/my%20repo')).toBe('/Users/me/my repo');
+    expect(parseOsc7Cwd('file:///tmp')).toBe('/tmp');
+  });
+
+  it('rejects other values', () => {
+    expect(parseOsc7Cwd('/tmp')).toBeNull();
+    expect(parseOsc7Cwd('https://example.com/x')).toBeNull();
+  });
+});
+
+describe('createOscTracker', () => {
+  it('reports titles set with OSC 0 and 2, with either terminator', () => {
+    const osc = createOscTracker();
+    expect(osc.feed('\x1b]0;vim\x07text')).toEqual({ title: 'vim' });
+    expect(osc.feed('\x1b]2;claude\x1b\\')).toEqual({ title: 'claude' });
+    expect(osc.feed('plain output')).toBeNull();
+  });
+
+  it('keeps the last value of each kind in a chunk', () => {
+    const osc = createOscTracker();
+    expect(osc.feed('\x1b]2;a\x07\x1b]7;file://h/tmp\x07\x1b]2;b\x07')).toEqual({
+      title: 'b',
+      cwd: '/tmp',
+    });
+  });
+
+  it('completes sequences split across reads', () => {
+    const osc = createOscTracker();
+    expect(osc.feed('out\x1b]7;file://h/ho')).toBeNull();
+    expect(osc.feed('me/me\x07')).toEqual({ cwd: '/home/me' });
+    expect(osc.feed('\x1b')).toBeNull();
+    expect(osc.feed(']0;t\x07')).toEqual({ title: 't' });
+  });
+});
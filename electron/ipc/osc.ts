@@ -0,0 +1,62 @@
+/** Longest unterminated OSC sequence kept while waiting for the rest of it. */
+const MAX_PENDING_CHARS = 4096;
+
+// OSC <code> ; <text> terminated by BEL or ST
+// eslint-disable-next-line no-control-regex
+const OSC_SEQUENCE = /\x1b\](\d+);([^\x07\x1b]*)(?:\x07|\x1b\\)/g;
+
+export interface OscUpdate {
+  /** From OSC 0 or 2. */
+  title?: string;
+  /** From OSC 7, as a local path. */
+  cwd?: string;
+}
+
+/** The local path in an OSC 7 `file://host/path` URL, or null when it isn't one. */
+export function parseOsc7Cwd(value: string): string | null {
+  let url: URL;
+  try {
+    url = new URL(value);
+  } catch {
+    return null;
+  }
+  if (url.protocol !== 'file:' || !url.pathname) return null;
+  try {
+    return decodeURIComponent(url.pathname);
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Tracks title (OSC 0/2) and working directory (OSC 7) reports in PTY
+ * output. Sequences split across reads are held until they complete.
+ */
+export function createOscTracker(): { feed(data: string): OscUpdate | null } {
+  let pending = '';
+  return {
+    feed(data: string): OscUpdate | null {
+      const text = pending + data;
+      pending = '';
+      let update: OscUpdate | null = null;
+      let consumed = 0;
+      for (const match of text.matchAll(OSC_SEQUENCE)) {
+        consumed = (match.index ?? 0) + match[0].length;
+        const [, code, value] = match;
+        if (code === '0' || code === '2') {
+          update = { ...update, title: value };
+        } else if (code === '7') {
+          const cwd = parseOsc7Cwd(value);
+          if (cwd) update = { ...update, cwd };
+        }
+      }
+      const open = text.lastIndexOf('\x1b]');
+      if (open >= consumed && text.length - open <= MAX_PENDING_CHARS) {
+        pending = text.slice(open);
+      } else if (text.endsWith('\x1b')) {
+        pending = '\x1b';
+      }
+      return update;
+    },
+  };
+}
@@ -1,8 +1,12 @@
 import { app } from 'electron';
 import fs from 'fs';
 import path from 'path';
+import { migrateState, STATE_SCHEMA_VERSION } from './migrations.js';
+import { createLogger } from './log.js';
 
-function getStateDir(): string {
+const log = createLogger('persistence');
+
+export function getStateDir(): string {
   let dir = app.getPath('userData');
   // Use separate dir for dev mode
   if (!app.isPackaged) {
@@ -21,12 +25,16 @@ export function saveAppState(json: string): void {
   const dir = path.dirname(statePath);
   fs.mkdirSync(dir, { recursive: true });
 
-  // Validate JSON before writing
-  JSON.parse(json);
+  // Validate JSON before writing, and stamp the layout it's in
+  const state = JSON.parse(json) as unknown;
+  const content =
+    state && typeof state === 'object' && !Array.isArray(state)
+      ? JSON.stringify({ ...state, schemaVersion: STATE_SCHEMA_VERSION })
+      : json;
 
   // Atomic write: write to temp, then rename
   const tmpPath = statePath + '.tmp';
-  fs.writeFileSync(tmpPath, json, 'utf8');
+  fs.writeFileSync(tmpPath, content, 'utf8');
 
   // Keep one backup (copy so statePath is never missing during the operation)
   if (fs.existsSync(statePath)) {
@@ -41,6 +49,43 @@ export function saveAppState(json: string): void {
   fs.renameSync(tmpPath, statePath);
 }
 
+/**
+ * Upgrade state written by another version of the app. The file as read is
+ * kept as `state.json.v<N>.bak` first: before a migration, and also when
+ * it's from a newer app, whose fields this one would drop on its next save.
+ */
+function upgradeState(content: string): string {
+  let parsed: unknown;
+  try {
+    parsed = JSON.parse(content);
+  } catch {
+    return content;
+  }
+  if (!parsed || typeof parsed !== 'object' || Array.isArray(parsed)) return content;
+  const { state, from, to } = migrateState(parsed as Record<string, unknown>);
+  if (from === STATE_SCHEMA_VERSION) return content;
+
+  const backupPath = `${getStatePath()}.v${from}.bak`;
+  try {
+    if (!fs.existsSync(backupPath)) fs.writeFileSync(backupPath, content, 'utf8');
+  } catch (err) {
+    log.warn(`Failed to back up state before upgrading from version ${from}`, err);
+  }
+  if (from > STATE_SCHEMA_VERSION) {
+    log.warn(`State is from a newer version (${from} > ${STATE_SCHEMA_VERSION})`);
+    return content;
+  }
+  log.info(`Migrated state from version ${from} to ${to}`);
+  const json = JSON.stringify(state);
+  try {
+    saveAppState(json);
+  } catch (err) {
+    // Still usable this session; migrating again next start is harmless
+    log.warn('Failed to save migrated state', err);
+  }
+  return json;
+}
+
 export function loadAppState(): string | null {
   const statePath = getStatePath();
   const bakPath = statePath + '.bak';
@@ -48,7 +93,7 @@ export function loadAppState(): string | null {
   try {
     if (fs.existsSync(statePath)) {
       const content = fs.readFileSync(statePath, 'utf8');
-      if (content.trim()) return content;
+      if (content.trim()) return upgradeState(content);
     }
   } catch {
     // Primary state file unreadable — try backup
@@ -57,7 +102,7 @@ export function loadAppState(): string | null {
   try {
     if (fs.existsSync(bakPath)) {
       const content = fs.readFileSync(bakPath, 'utf8');
-      if (content.trim()) return content;
+      if (content.trim()) return upgradeState(content);
     }
   } catch {
     // Backup also unreadable
@@ -0,0 +1,57 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+const stateDir = vi.hoisted(() => ({ path: '' }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => stateDir.path,
+}));
+
+import {
+  PORTS_PER_TASK,
+  ensureTaskPorts,
+  getTaskPorts,
+  nextFreeBase,
+  portEnv,
+  releaseTaskPorts,
+} from './ports.js';
+
+stateDir.path = fs.mkdtempSync(path.join(os.tmpdir(), 'ports-test-'));
+
+afterAll(() => {
+  fs.rmSync(stateDir.path, { recursive: true, force: true });
+});
+
+describe('nextFreeBase', () => {
+  it('returns the lowest unused block', () => {
+    expect(nextFreeBase([])).toBe(20000);
+    expect(nextFreeBase([20000, 20000 + 2 * PORTS_PER_TASK])).toBe(20000 + PORTS_PER_TASK);
+  });
+});
+
+describe('task port allocation', () => {
+  it('gives each task its own stable block and reuses released ones', () => {
+    const a = ensureTaskPorts('task-a');
+    const b = ensureTaskPorts('task-b');
+    expect(b.base).toBe(a.base + PORTS_PER_TASK);
+    expect(ensureTaskPorts('task-a')).toEqual(a);
+    expect(getTaskPorts('task-b')).toEqual(b);
+
+    releaseTaskPorts('task-a');
+    expect(getTaskPorts('task-a')).toBeNull();
+    expect(ensureTaskPorts('task-c').base).toBe(a.base);
+
+    const saved = JSON.parse(fs.readFileSync(path.join(stateDir.path, 'ports.json'), 'utf8'));
+    expect(saved.map((p: { task_id: string }) => p.task_id).sort()).toEqual(['task-b', 'task-c']);
+  });
+
+  it('exposes the block as env vars', () => {
+    expect(portEnv({ task_id: 't', base: 20010, count: 10 })).toEqual({
+      PORT: '20010',
+      PARALLEL_PORT_BASE: '20010',
+      PARALLEL_PORT_COUNT: '10',
+    });
+  });
+});
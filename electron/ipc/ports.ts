@@ -0,0 +1,95 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('ports');
+
+/** Ports reserved per task, starting at its base port. */
+export const PORTS_PER_TASK = 10;
+const PORT_RANGE_START = 20000;
+const PORT_RANGE_END = 60000;
+
+export interface TaskPorts {
+  task_id: string;
+  /** First port of the block; the task owns `base` to `base + count - 1`. */
+  base: number;
+  count: number;
+}
+
+let allocations: Map<string, TaskPorts> | null = null;
+
+function getPortsPath(): string {
+  return path.join(getStateDir(), 'ports.json');
+}
+
+function loadAllocations(): Map<string, TaskPorts> {
+  if (allocations) return allocations;
+  allocations = new Map();
+  try {
+    const raw = JSON.parse(fs.readFileSync(getPortsPath(), 'utf8')) as TaskPorts[];
+    for (const p of raw) {
+      if (typeof p.task_id === 'string' && Number.isInteger(p.base)) allocations.set(p.task_id, p);
+    }
+  } catch {
+    // Nothing allocated yet
+  }
+  return allocations;
+}
+
+function saveAllocations(all: Map<string, TaskPorts>): void {
+  try {
+    const portsPath = getPortsPath();
+    fs.mkdirSync(path.dirname(portsPath), { recursive: true });
+    const tmpPath = portsPath + '.tmp';
+    fs.writeFileSync(tmpPath, JSON.stringify([...all.values()]), 'utf8');
+    fs.renameSync(tmpPath, portsPath);
+  } catch (err) {
+    log.warn('Failed to save port allocations:', err);
+  }
+}
+
+/** Lowest block start not used by `taken`. */
+export function nextFreeBase(taken: Iterable<number>): number {
+  const used = new Set(taken);
+  for (let base = PORT_RANGE_START; base < PORT_RANGE_END; base += PORTS_PER_TASK) {
+    if (!used.has(base)) return base;
+  }
+  throw new Error('No free port ranges left');
+}
+
+/**
+ * The task's port block, reserving one on first use. Allocations are
+ * persisted so a task keeps its ports across restarts.
+ */
+export function ensureTaskPorts(taskId: string): TaskPorts {
+  const all = loadAllocations();
+  const existing = all.get(taskId);
+  if (existing) return existing;
+  const ports: TaskPorts = {
+    task_id: taskId,
+    base: nextFreeBase([...all.values()].map((p) => p.base)),
+    count: PORTS_PER_TASK,
+  };
+  all.set(taskId, ports);
+  saveAllocations(all);
+  return ports;
+}
+
+export function getTaskPorts(taskId: string): TaskPorts | null {
+  return loadAllocations().get(taskId) ?? null;
+}
+
+export function releaseTaskPorts(taskId: string): void {
+  const all = loadAllocations();
+  if (all.delete(taskId)) saveAllocations(all);
+}
+
+/** Env vars advertising the block to processes spawned for the task. */
+export function portEnv(ports: TaskPorts): Record<string, string> {
+  return {
+    PORT: String(ports.base),
+    PARALLEL_PORT_BASE: String(ports.base),
+    PARALLEL_PORT_COUNT: String(ports.count),
+  };
+}
@@ -0,0 +1,103 @@
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { getRemoteProvider, type PullRequestStatus } from './remotes.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('pr-poller');
+
+export interface TaskPrStatus extends PullRequestStatus {
+  task_id: string;
+}
+
+interface WatchedPrTask {
+  taskId: string;
+  worktreePath: string;
+  branchName: string;
+}
+
+/** PR state changes slowly and each poll is a call to the remote host's API. */
+const PR_POLL_INTERVAL_MS = 60_000;
+
+let win: BrowserWindow | null = null;
+let watched: WatchedPrTask[] = [];
+const lastStatus = new Map<string, string>();
+/** Tasks whose PR was merged or closed; their status won't change again. */
+const settled = new Set<string>();
+let timer: ReturnType<typeof setInterval> | null = null;
+let isPolling = false;
+
+async function pollTask(task: WatchedPrTask): Promise<void> {
+  let pr: PullRequestStatus | null;
+  try {
+    const provider = await getRemoteProvider(task.worktreePath);
+    pr = await provider.fetchPrStatus(task.worktreePath, task.branchName);
+  } catch (err) {
+    log.debug(`PR status for ${task.branchName} unavailable`, err);
+    return;
+  }
+  if (!pr || !watched.some((t) => t.taskId === task.taskId)) return;
+  const status: TaskPrStatus = { task_id: task.taskId, ...pr };
+  if (pr.state !== 'OPEN') settled.add(task.taskId);
+  const key = JSON.stringify(status);
+  if (lastStatus.get(task.taskId) === key) return;
+  lastStatus.set(task.taskId, key);
+  if (win && !win.isDestroyed()) win.webContents.send(IPC.TaskPrStatusChanged, status);
+}
+
+/** Poll one task at a time; provider calls hit the network. */
+async function pollEach(tasks: WatchedPrTask[]): Promise<void> {
+  for (const task of tasks) await pollTask(task);
+}
+
+async function pollAll(): Promise<void> {
+  if (isPolling) return;
+  isPolling = true;
+  try {
+    await pollEach(watched.filter((t) => !settled.has(t.taskId)));
+  } finally {
+    isPolling = false;
+  }
+}
+
+export function startPrPoller(window: BrowserWindow): void {
+  win = window;
+  if (timer) return;
+  timer = setInterval(() => void pollAll(), PR_POLL_INTERVAL_MS);
+}
+
+/** Replace the set of watched tasks. Newly watched tasks are polled right away. */
+export function setWatchedPrTasks(tasks: WatchedPrTask[], reset = false): void {
+  if (reset) {
+    watched = [];
+    lastStatus.clear();
+    settled.clear();
+  }
+  const previous = new Set(watched.map((t) => t.taskId));
+  watched = tasks;
+
+  const current = new Set(tasks.map((t) => t.taskId));
+  for (const taskId of lastStatus.keys()) {
+    if (!current.has(taskId)) lastStatus.delete(taskId);
+  }
+  for (const taskId of settled) {
+    if (!current.has(taskId)) settled.delete(taskId);
+  }
+
+  void pollEach(tasks.filter((t) => !previous.has(t.taskId)));
+}
+
+/** Poll one task now, e.g. right after pushing its branch. */
+export function refreshTaskPrStatus(taskId: string): void {
+  const task = watched.find((t) => t.taskId === taskId);
+  if (!task) return;
+  settled.delete(taskId);
+  void pollTask(task);
+}
+
+export function stopPrPoller(): void {
+  if (timer) clearInterval(timer);
+  timer = null;
+  watched = [];
+  lastStatus.clear();
+  settled.clear();
+}
@@ -0,0 +1,61 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { describe, expect, it } from 'vitest';
+import { PROJECT_CONFIG_FILE, parseProjectConfig, readProjectConfig } from './project-config.js';
+
+describe('parseProjectConfig', () => {
+  it('reads per-agent args, env and profile', () => {
+    const config = parseProjectConfig(
+      [
+        '[agents.claude-code]',
+        'args = ["--permission-mode", "acceptEdits"]',
+        'profile = "fast"',
+        '[agents.claude-code.env]',
+        'ANTHROPIC_MODEL = "opus"',
+        '[agents.codex]',
+      ].join('\n'),
+    );
+    expect(config.agents).toEqual({
+      'claude-code': {
+        args: ['--permission-mode', 'acceptEdits'],
+        env: { ANTHROPIC_MODEL: 'opus' },
+        profile: 'fast',
+      },
+      codex: { args: [], env: {}, profile: null },
+    });
+  });
+
+  it('is empty without an agents table', () => {
+    expect(parseProjectConfig('# nothing yet\n')).toEqual({ agents: {} });
+  });
+
+  it('rejects values of the wrong shape', () => {
+    expect(() => parseProjectConfig('[agents.a]\nargs = "--fast"')).toThrow(
+      'agents.a.args must be an array of strings',
+    );
+    expect(() => parseProjectConfig('[agents.a.env]\n"NOT-A-NAME" = "x"')).toThrow(
+      'invalid variable name NOT-A-NAME',
+    );
+    expect(() => parseProjectConfig('[agents.a]\nmodel = "opus"')).toThrow('unknown key model');
+  });
+});
+
+describe('readProjectConfig', () => {
+  it('hashes the file, so trust in it ends when it changes', () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'project-config-'));
+    try {
+      expect(readProjectConfig(dir)).toBeNull();
+      const file = path.join(dir, PROJECT_CONFIG_FILE);
+      fs.writeFileSync(file, '[agents.a]\nargs = ["--fast"]\n');
+      const first = readProjectConfig(dir);
+      expect(first?.config.agents.a.args).toEqual(['--fast']);
+      expect(first?.hash).toMatch(/^[0-9a-f]{64}$/);
+      expect(readProjectConfig(dir)?.hash).toBe(first?.hash);
+      fs.writeFileSync(file, '[agents.a]\nargs = ["--yolo"]\n');
+      expect(readProjectConfig(dir)?.hash).not.toBe(first?.hash);
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
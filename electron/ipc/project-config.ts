@@ -0,0 +1,79 @@
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { isTomlTable, parseToml, type TomlTable, type TomlValue } from './toml.js';
+
+/** Checked into a repository to configure how its tasks' agents launch. */
+export const PROJECT_CONFIG_FILE = '.parallel-code.toml';
+
+/** Overrides for one agent in one project, merged over the agent's own definition. */
+export interface ProjectAgentConfig {
+  /** Appended to the agent's arguments, before a launch profile's. */
+  args: string[];
+  /** Over the project's environment, under a launch profile's and the task's. */
+  env: Record<string, string>;
+  /** Launch profile for tasks that don't pick one. */
+  profile: string | null;
+}
+
+export interface ProjectConfig {
+  /** By agent id, e.g. `claude-code`. */
+  agents: Record<string, ProjectAgentConfig>;
+}
+
+/** A project's config and the hash of its file, which the user's trust in it is tied to. */
+export interface ProjectConfigFile {
+  config: ProjectConfig;
+  hash: string;
+}
+
+function isStringArray(v: TomlValue | undefined): v is string[] {
+  return Array.isArray(v) && v.every((s) => typeof s === 'string');
+}
+
+function agentConfig(id: string, table: TomlTable): ProjectAgentConfig {
+  const where = `agents.${id}`;
+  const { args = [], env = {}, profile, ...rest } = table;
+  const unknown = Object.keys(rest)[0];
+  if (unknown !== undefined) throw new Error(`${where}: unknown key ${unknown}`);
+  if (!isStringArray(args)) throw new Error(`${where}.args must be an array of strings`);
+  if (!isTomlTable(env) || !Object.values(env).every((v) => typeof v === 'string')) {
+    throw new Error(`${where}.env must be a table of strings`);
+  }
+  const badName = Object.keys(env).find((k) => !/^[A-Za-z_][A-Za-z0-9_]*$/.test(k));
+  if (badName !== undefined) throw new Error(`${where}.env: invalid variable name ${badName}`);
+  if (profile !== undefined && typeof profile !== 'string') {
+    throw new Error(`${where}.profile must be a string`);
+  }
+  return { args, env: env as Record<string, string>, profile: profile || null };
+}
+
+/** Read a config file's content, e.g. `[agents.claude-code]` with `args`, `env` and `profile`. */
+export function parseProjectConfig(content: string): ProjectConfig {
+  const doc = parseToml(content);
+  const agents = doc.agents ?? {};
+  if (!isTomlTable(agents)) throw new Error('agents must be a table');
+  const config: ProjectConfig = { agents: {} };
+  for (const [id, table] of Object.entries(agents)) {
+    if (!isTomlTable(table)) throw new Error(`agents.${id} must be a table`);
+    config.agents[id] = agentConfig(id, table);
+  }
+  return config;
+}
+
+/** The project's config, or null when the repository has none. */
+export function readProjectConfig(projectRoot: string): ProjectConfigFile | null {
+  let content: string;
+  try {
+    content = fs.readFileSync(path.join(projectRoot, PROJECT_CONFIG_FILE), 'utf8');
+  } catch (err) {
+    if ((err as NodeJS.ErrnoException).code === 'ENOENT') return null;
+    throw err;
+  }
+  try {
+    const hash = crypto.createHash('sha256').update(content).digest('hex');
+    return { config: parseProjectConfig(content), hash };
+  } catch (err) {
+    throw new Error(`${PROJECT_CONFIG_FILE}: ${err instanceof Error ? err.message : String(err)}`);
+  }
+}
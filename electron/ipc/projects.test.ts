@@ -0,0 +1,26 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { pushRecentProject, type RecentProject } from './projects.js';
+
+function entry(path: string, lastOpened: number): RecentProject {
+  return { path, name: path.slice(1), last_opened: lastOpened };
+}
+
+describe('pushRecentProject', () => {
+  it('moves a reopened project to the front without duplicating it', () => {
+    const list = [entry('/a', 3), entry('/b', 2), entry('/c', 1)];
+    expect(pushRecentProject(list, entry('/b', 4)).map((p) => p.path)).toEqual(['/b', '/a', '/c']);
+  });
+
+  it('caps the list length', () => {
+    const list = Array.from({ length: 20 }, (_, i) => entry(`/p${i}`, i));
+    const next = pushRecentProject(list, entry('/new', 99));
+    expect(next).toHaveLength(20);
+    expect(next[0].path).toBe('/new');
+    expect(next.some((p) => p.path === '/p19')).toBe(false);
+  });
+});
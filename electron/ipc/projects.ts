@@ -0,0 +1,98 @@
+import fs from 'fs';
+import path from 'path';
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { getStateDir } from './persistence.js';
+import { AppError, stderrOf } from './errors.js';
+import { isSshUri, localPathFor, parseSshUri, remoteCommand, remoteTargetFor } from './ssh.js';
+
+const exec = promisify(execFile);
+
+const MAX_RECENT_PROJECTS = 20;
+
+export interface RecentProject {
+  path: string;
+  name: string;
+  last_opened: number;
+}
+
+function getRecentPath(): string {
+  return path.join(getStateDir(), 'recent-projects.json');
+}
+
+function isRecentProject(v: unknown): v is RecentProject {
+  if (!v || typeof v !== 'object') return false;
+  const r = v as Record<string, unknown>;
+  return (
+    typeof r.path === 'string' && typeof r.name === 'string' && typeof r.last_opened === 'number'
+  );
+}
+
+export function listRecentProjects(): RecentProject[] {
+  try {
+    const raw: unknown = JSON.parse(fs.readFileSync(getRecentPath(), 'utf8'));
+    if (!Array.isArray(raw)) return [];
+    return raw.filter(isRecentProject).sort((a, b) => b.last_opened - a.last_opened);
+  } catch {
+    return [];
+  }
+}
+
+function saveRecentProjects(list: RecentProject[]): void {
+  const recentPath = getRecentPath();
+  fs.mkdirSync(path.dirname(recentPath), { recursive: true });
+  const tmpPath = recentPath + '.tmp';
+  fs.writeFileSync(tmpPath, JSON.stringify(list, null, 2), 'utf8');
+  fs.renameSync(tmpPath, recentPath);
+}
+
+/** Move `entry` to the front of the list, replacing any entry for the same path. */
+export function pushRecentProject(list: RecentProject[], entry: RecentProject): RecentProject[] {
+  return [entry, ...list.filter((p) => p.path !== entry.path)].slice(0, MAX_RECENT_PROJECTS);
+}
+
+/** Resolve the top-level directory of the git repository containing `dir`. */
+async function resolveRepoRoot(dir: string): Promise<string> {
+  const target = isSshUri(dir) ? parseSshUri(dir) : remoteTargetFor(dir);
+  if (target) {
+    const local = localPathFor(target);
+    const remote = remoteCommand('git', ['rev-parse', '--show-toplevel'], local);
+    if (!remote) throw new Error(`Invalid SSH address: ${dir}`);
+    try {
+      const { stdout } = await exec(remote.file, remote.args);
+      return localPathFor({ ...target, path: stdout.trim() });
+    } catch (err) {
+      throw new AppError('not_a_git_repo', `Not a git repository: ${dir}`, {
+        path: dir,
+        stderr: stderrOf(err),
+      });
+    }
+  }
+  if (!fs.existsSync(dir)) throw new Error(`Path does not exist: ${dir}`);
+  try {
+    const { stdout } = await exec('git', ['rev-parse', '--show-toplevel'], { cwd: dir });
+    return stdout.trim();
+  } catch (err) {
+    throw new AppError('not_a_git_repo', `Not a git repository: ${dir}`, {
+      path: dir,
+      stderr: stderrOf(err),
+    });
+  }
+}
+
+/**
+ * Validate that `dir` is inside a git repository and record its root as the
+ * most recently opened project. `dir` may be an `ssh://` address, whose
+ * project gets the stand-in path described in `ssh.ts`.
+ */
+export async function openProject(dir: string): Promise<RecentProject> {
+  const root = await resolveRepoRoot(dir);
+  const previous = listRecentProjects();
+  const entry: RecentProject = {
+    path: root,
+    name: previous.find((p) => p.path === root)?.name ?? (path.basename(root) || root),
+    last_opened: Date.now(),
+  };
+  saveRecentProjects(pushRecentProject(previous, entry));
+  return entry;
+}
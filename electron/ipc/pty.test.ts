@@ -1,5 +1,136 @@
-import { describe, it, expect } from 'vitest';
-import { validateCommand } from './pty.js';
+import os from 'os';
+import type { BrowserWindow } from 'electron';
+import { afterEach, beforeEach, describe, it, expect, vi } from 'vitest';
+
+/** Stands in for node-pty: records writes, and emits output and exits when told to. */
+const fakePty = vi.hoisted(() => {
+  class FakePty {
+    pid = 1;
+    env: Record<string, string> = {};
+    writes: string[] = [];
+    private dataListeners: Array<(data: string) => void> = [];
+    private exitListeners: Array<(e: { exitCode: number; signal?: number }) => void> = [];
+    onData(fn: (data: string) => void) {
+      this.dataListeners.push(fn);
+      return { dispose: () => {} };
+    }
+    onExit(fn: (e: { exitCode: number; signal?: number }) => void) {
+      this.exitListeners.push(fn);
+      return { dispose: () => {} };
+    }
+    write(data: string) {
+      this.writes.push(data);
+    }
+    output(data: string) {
+      for (const fn of this.dataListeners) fn(data);
+    }
+    exit(exitCode = 0) {
+      for (const fn of this.exitListeners) fn({ exitCode });
+    }
+    kill() {
+      // Like a real pty, the exit is reported after kill returns
+      queueMicrotask(() => this.exit(0));
+    }
+    pause() {}
+    resume() {}
+    resize() {}
+  }
+  return { FakePty, spawned: [] as FakePty[] };
+});
+
+vi.mock('node-pty', () => ({
+  spawn: (_file: string, _args: string[], opts: { env: Record<string, string> }) => {
+    const proc = new fakePty.FakePty();
+    proc.env = opts.env;
+    fakePty.spawned.push(proc);
+    return proc;
+  },
+}));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./secrets.js', () => ({
+  secretEnv: () => ({ INJECTED_KEY: 'injected' }),
+  namedSecretEnv: (names: string[]) => Object.fromEntries(names.map((n) => [n, `stored ${n}`])),
+}));
+
+vi.mock('./notifications.js', () => ({
+  notify: vi.fn(),
+}));
+
+vi.mock('./ports.js', () => ({
+  ensureTaskPorts: () => ({ base: 20000, count: 10 }),
+  portEnv: () => ({}),
+}));
+vi.mock('./orphans.js', () => ({ trackSession: vi.fn(), untrackSession: vi.fn() }));
+vi.mock('./scrollback-spill.js', () => ({ createScrollbackSpill: () => null }));
+vi.mock('./storage.js', () => ({ storeTranscript: vi.fn() }));
+vi.mock('./task-events.js', () => ({ recordTaskEvent: vi.fn() }));
+vi.mock('./checkpoints.js', () => ({ checkpointAfterTurn: vi.fn() }));
+
+import {
+  validateCommand,
+  shouldRestart,
+  restartDelayMs,
+  sessionLimitExceeded,
+  scrollbackCapacity,
+  scanBracketedPasteMode,
+  wrapBracketedPaste,
+  chunkForPty,
+  plainShellArgs,
+  shellInitInput,
+  stallState,
+  killAgent,
+  killProjectAgents,
+  listSessions,
+  pasteToAgent,
+  pauseAgent,
+  resumeAgent,
+  sendToAgent,
+  sendToTaskAgents,
+  sendToProjectAgents,
+  spawnAgent,
+  writeToAgent,
+} from './pty.js';
+
+const win = {
+  isDestroyed: () => false,
+  webContents: { send: vi.fn() },
+} as unknown as BrowserWindow;
+
+interface FakeSpawnOptions {
+  taskId?: string;
+  projectId?: string;
+  isShell?: boolean;
+  env?: Record<string, string>;
+  secrets?: string[];
+}
+
+/** Spawn a session on a fake pty and return that pty. */
+function spawnFake(
+  agentId: string,
+  { taskId = 'task-1', projectId, isShell = true, env = {}, secrets }: FakeSpawnOptions = {},
+) {
+  spawnAgent(win, {
+    taskId,
+    projectId,
+    agentId,
+    command: '/bin/sh',
+    args: [],
+    cwd: os.tmpdir(),
+    env,
+    secrets,
+    cols: 80,
+    rows: 24,
+    isShell,
+    onOutput: { __CHANNEL_ID__: agentId },
+  });
+  const proc = fakePty.spawned[fakePty.spawned.length - 1];
+  if (!proc) throw new Error('nothing spawned');
+  return proc;
+}
 
 describe('validateCommand', () => {
   it('does not throw for a command found in PATH', () => {
@@ -33,3 +164,404 @@ describe('validateCommand', () => {
     expect(() => validateCommand('   ')).toThrow(/must not be empty/);
   });
 });
+
+describe('shouldRestart', () => {
+  const onFailure = { policy: 'on-failure' as const, max_retries: 3 };
+
+  it('never restarts without a policy or with policy "never"', () => {
+    expect(shouldRestart(undefined, { exitCode: 1, killed: false }, 0)).toBe(false);
+    expect(
+      shouldRestart({ policy: 'never', max_retries: 5 }, { exitCode: 1, killed: false }, 0),
+    ).toBe(false);
+  });
+
+  it('restarts on abnormal exit with policy "on-failure"', () => {
+    expect(shouldRestart(onFailure, { exitCode: 1, killed: false }, 0)).toBe(true);
+    expect(shouldRestart(onFailure, { exitCode: 0, signal: 9, killed: false }, 0)).toBe(true);
+    expect(shouldRestart(onFailure, { exitCode: 0, killed: false }, 0)).toBe(false);
+  });
+
+  it('restarts clean exits with policy "always"', () => {
+    const always = { policy: 'always' as const, max_retries: 1 };
+    expect(shouldRestart(always, { exitCode: 0, killed: false }, 0)).toBe(true);
+  });
+
+  it('stops after max retries', () => {
+    expect(shouldRestart(onFailure, { exitCode: 1, killed: false }, 3)).toBe(false);
+  });
+
+  it('does not restart agents that were killed intentionally', () => {
+    expect(shouldRestart(onFailure, { exitCode: 1, killed: true }, 0)).toBe(false);
+  });
+});
+
+describe('sessionLimitExceeded', () => {
+  const limits = { max_agent_sessions: 3, max_agents_per_task: 2 };
+
+  it('allows agents within both limits, and any number when they are 0', () => {
+    expect(sessionLimitExceeded(['a', 'b'], 'a', limits)).toBeNull();
+    const unlimited = { max_agent_sessions: 0, max_agents_per_task: 0 };
+    expect(sessionLimitExceeded(['a', 'a', 'a', 'a'], 'a', unlimited)).toBeNull();
+  });
+
+  it('refuses past the total and the per-task limit', () => {
+    expect(sessionLimitExceeded(['a', 'b', 'c'], 'd', limits)).toMatch(/max_agent_sessions is 3/);
+    expect(sessionLimitExceeded(['a', 'a'], 'a', limits)).toMatch(/max_agents_per_task is 2/);
+  });
+});
+
+describe('scrollbackCapacity', () => {
+  const limits = { scrollback_session_kb: 1024, scrollback_budget_mb: 8 };
+
+  it('gives each session the per-session cap while the budget allows it', () => {
+    expect(scrollbackCapacity(1, limits)).toBe(1024 * 1024);
+    expect(scrollbackCapacity(8, limits)).toBe(1024 * 1024);
+  });
+
+  it('splits the budget past that, but never below 64KB', () => {
+    expect(scrollbackCapacity(16, limits)).toBe(512 * 1024);
+    expect(scrollbackCapacity(1000, limits)).toBe(64 * 1024);
+  });
+});
+
+describe('restartDelayMs', () => {
+  it('backs off exponentially and caps at 30s', () => {
+    expect(restartDelayMs(1)).toBe(1_000);
+    expect(restartDelayMs(2)).toBe(2_000);
+    expect(restartDelayMs(3)).toBe(4_000);
+    expect(restartDelayMs(10)).toBe(30_000);
+  });
+});
+
+describe('scanBracketedPasteMode', () => {
+  it('follows the last mode change in the output', () => {
+    expect(scanBracketedPasteMode('\x1b[?2004h', '', false).enabled).toBe(true);
+    expect(scanBracketedPasteMode('\x1b[?2004hok\x1b[?2004l', '', true).enabled).toBe(false);
+    expect(scanBracketedPasteMode('plain output', '', true).enabled).toBe(true);
+  });
+
+  it('sees a sequence split across reads', () => {
+    const first = scanBracketedPasteMode('prompt \x1b[?20', '', false);
+    expect(first.enabled).toBe(false);
+    expect(scanBracketedPasteMode('04h', first.tail, first.enabled).enabled).toBe(true);
+  });
+});
+
+describe('wrapBracketedPaste', () => {
+  it('wraps text in paste markers', () => {
+    expect(wrapBracketedPaste('a\nb')).toBe('\x1b[200~a\nb\x1b[201~');
+  });
+
+  it('strips embedded end markers', () => {
+    expect(wrapBracketedPaste('a\x1b[201~\rrm -rf')).toBe('\x1b[200~a\rrm -rf\x1b[201~');
+  });
+});
+
+describe('chunkForPty', () => {
+  it('splits text into chunks of the given size', () => {
+    expect(chunkForPty('abcdefg', 3)).toEqual(['abc', 'def', 'g']);
+    expect(chunkForPty('', 3)).toEqual([]);
+  });
+
+  it('does not split surrogate pairs', () => {
+    const chunks = chunkForPty('ab\u{1F600}c', 3);
+    expect(chunks).toEqual(['ab', '\u{1F600}c']);
+    expect(chunks.join('')).toBe('ab\u{1F600}c');
+  });
+
+  it('keeps escape sequences and paste markers whole', () => {
+    expect(chunkForPty('ab\x1b[Acd', 3)).toEqual(['ab', '\x1b[A', 'cd']);
+    expect(chunkForPty(wrapBracketedPaste('abcdef'), 4)).toEqual([
+      '\x1b[200~',
+      'abcd',
+      'ef',
+      '\x1b[201~',
+    ]);
+    expect(chunkForPty('x\x1b]0;title\x1b\\y', 4)).toEqual(['x', '\x1b]0;title\x1b\\', 'y']);
+  });
+
+  it('chunks text after an introducer that is never terminated', () => {
+    expect(chunkForPty('a\x1b]abcdefgh', 4)).toEqual(['a\x1b]a', 'bcde', 'fgh']);
+  });
+});
+
+describe('queued writes', () => {
+  const agents: string[] = [];
+  const spawn = (agentId: string) => {
+    agents.push(agentId);
+    return spawnFake(agentId);
+  };
+  const big = 'x'.repeat(1024) + 'y'.repeat(1024) + 'z';
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(async () => {
+    for (const id of agents.splice(0)) killAgent(id);
+    await vi.advanceTimersByTimeAsync(0);
+    vi.useRealTimers();
+  });
+
+  it('writes chunks in order, with later input behind them', async () => {
+    const proc = spawn('order');
+    expect(writeToAgent('order', big)).toBe(true);
+    expect(writeToAgent('order', 'after')).toBe(true);
+    await vi.advanceTimersByTimeAsync(200);
+    expect(proc.writes).toEqual(['x'.repeat(1024), 'y'.repeat(1024), 'z', 'after']);
+  });
+
+  it('sends the next chunk once the last was echoed, or after the ack timeout', async () => {
+    const proc = spawn('ack');
+    const done = pasteToAgent('ack', big);
+    await vi.advanceTimersByTimeAsync(0);
+    expect(proc.writes).toHaveLength(1);
+    // Past the pause between chunks, it waits for output
+    await vi.advanceTimersByTimeAsync(10);
+    expect(proc.writes).toHaveLength(1);
+    proc.output('x');
+    await vi.advanceTimersByTimeAsync(0);
+    expect(proc.writes).toHaveLength(2);
+    // No echo: the chunk goes anyway once the ack timeout passes
+    await vi.advanceTimersByTimeAsync(40);
+    expect(proc.writes).toHaveLength(2);
+    await vi.advanceTimersByTimeAsync(20);
+    expect(proc.writes).toHaveLength(3);
+    await expect(done).resolves.toBe(true);
+  });
+
+  it('holds chunks while output is paused, until resumed or for two seconds', async () => {
+    const proc = spawn('paused');
+    pauseAgent('paused');
+    const first = pasteToAgent('paused', 'a');
+    await vi.advanceTimersByTimeAsync(1000);
+    expect(proc.writes).toEqual([]);
+    resumeAgent('paused');
+    await expect(first).resolves.toBe(true);
+    expect(proc.writes).toEqual(['a']);
+
+    pauseAgent('paused');
+    const second = pasteToAgent('paused', 'b');
+    await vi.advanceTimersByTimeAsync(1999);
+    expect(proc.writes).toEqual(['a']);
+    await vi.advanceTimersByTimeAsync(1);
+    await expect(second).resolves.toBe(true);
+    expect(proc.writes).toEqual(['a', 'b']);
+  });
+
+  it('reports a write cut short by the session exiting', async () => {
+    const proc = spawn('exits');
+    const done = pasteToAgent('exits', big);
+    await vi.advanceTimersByTimeAsync(0);
+    proc.exit(1);
+    await vi.advanceTimersByTimeAsync(100);
+    await expect(done).resolves.toBe(false);
+    expect(proc.writes).toHaveLength(1);
+  });
+});
+
+describe('plainShellArgs', () => {
+  it('adds -l for known shells when login is on', () => {
+    expect(plainShellArgs('/bin/zsh', true)).toEqual(['-l']);
+    expect(plainShellArgs('/opt/homebrew/bin/fish', true)).toEqual(['-l']);
+    expect(plainShellArgs('/bin/zsh', false)).toEqual([]);
+  });
+
+  it('passes nothing to shells without a login flag', () => {
+    expect(plainShellArgs('pwsh', true)).toEqual([]);
+  });
+});
+
+describe('shellInitInput', () => {
+  it('types each command followed by Enter', () => {
+    expect(shellInitInput(['nvm use', 'source .venv/bin/activate'])).toBe(
+      'nvm use\rsource .venv/bin/activate\r',
+    );
+  });
+});
+
+describe('stallState', () => {
+  const MIN = 60_000;
+
+  it('reports idle, then hung, once the thresholds pass', () => {
+    expect(stallState(0, 4 * MIN, 5, 30, false)).toBeNull();
+    expect(stallState(0, 5 * MIN, 5, 30, false)).toBe('idle');
+    expect(stallState(0, 30 * MIN, 5, 30, false)).toBe('hung');
+  });
+
+  it('never calls an agent waiting on a prompt hung and skips disabled checks', () => {
+    expect(stallState(0, 60 * MIN, 5, 30, true)).toBe('idle');
+    expect(stallState(0, 60 * MIN, 0, 30, true)).toBeNull();
+    expect(stallState(0, 60 * MIN, 0, 0, false)).toBeNull();
+  });
+});
+
+describe('sending prompts', () => {
+  const agents: string[] = [];
+  const spawn = (agentId: string, opts: FakeSpawnOptions = {}) => {
+    agents.push(agentId);
+    return spawnFake(agentId, opts);
+  };
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(async () => {
+    for (const id of agents.splice(0)) killAgent(id);
+    await vi.advanceTimersByTimeAsync(0);
+    vi.useRealTimers();
+  });
+
+  it('presses Enter a moment after the prompt', async () => {
+    const proc = spawn('submit');
+    const sent = sendToAgent('submit', 'fix it', true);
+    await vi.advanceTimersByTimeAsync(49);
+    expect(proc.writes).toEqual(['fix it']);
+    await vi.advanceTimersByTimeAsync(1);
+    await expect(sent).resolves.toBe(true);
+    expect(proc.writes).toEqual(['fix it', '\r']);
+  });
+
+  it('leaves Enter out without submit', async () => {
+    const proc = spawn('draft');
+    await expect(sendToAgent('draft', 'fix it', false)).resolves.toBe(true);
+    await vi.advanceTimersByTimeAsync(100);
+    expect(proc.writes).toEqual(['fix it']);
+  });
+
+  it("sends to every agent of the task, not its terminals or other tasks' agents", async () => {
+    const first = spawn('a1', { taskId: 'fan', isShell: false });
+    const second = spawn('a2', { taskId: 'fan', isShell: false });
+    const shell = spawn('s1', { taskId: 'fan' });
+    const other = spawn('b1', { taskId: 'other', isShell: false });
+    const sent = sendToTaskAgents('fan', 'fix it', true);
+    await vi.advanceTimersByTimeAsync(50);
+    await expect(sent).resolves.toEqual(['a1', 'a2']);
+    expect(first.writes).toEqual(['fix it', '\r']);
+    expect(second.writes).toEqual(['fix it', '\r']);
+    expect(shell.writes).toEqual([]);
+    expect(other.writes).toEqual([]);
+  });
+
+  it('leaves out agents that exit before Enter', async () => {
+    const staying = spawn('stays', { taskId: 'partial', isShell: false });
+    const leaving = spawn('leaves', { taskId: 'partial', isShell: false });
+    const sent = sendToTaskAgents('partial', 'fix it', true);
+    await vi.advanceTimersByTimeAsync(10);
+    leaving.exit(0);
+    await vi.advanceTimersByTimeAsync(40);
+    await expect(sent).resolves.toEqual(['stays']);
+    expect(staying.writes).toEqual(['fix it', '\r']);
+    expect(leaving.writes).toEqual(['fix it']);
+  });
+
+  it('does not press Enter in a session respawned while it waited', async () => {
+    const before = spawn('respawned');
+    const sent = sendToAgent('respawned', 'fix it', true);
+    await vi.advanceTimersByTimeAsync(10);
+    const after = spawnFake('respawned');
+    await vi.advanceTimersByTimeAsync(40);
+    await expect(sent).resolves.toBe(false);
+    expect(before.writes).toEqual(['fix it']);
+    expect(after.writes).toEqual([]);
+  });
+
+  it('reports an agent that is not running', async () => {
+    await expect(sendToAgent('missing', 'fix it', true)).resolves.toBe(false);
+    await expect(sendToTaskAgents('no-such-task', 'fix it', true)).resolves.toEqual([]);
+  });
+});
+
+describe('project sessions', () => {
+  const agents: string[] = [];
+  const spawn = (agentId: string, opts: FakeSpawnOptions) => {
+    agents.push(agentId);
+    return spawnFake(agentId, opts);
+  };
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(async () => {
+    for (const id of agents.splice(0)) killAgent(id);
+    await vi.advanceTimersByTimeAsync(0);
+    vi.useRealTimers();
+  });
+
+  it("prompts the agents of every task in the project, not other projects'", async () => {
+    const first = spawn('p1-a', { taskId: 't1', projectId: 'p1', isShell: false });
+    const second = spawn('p1-b', { taskId: 't2', projectId: 'p1', isShell: false });
+    const shell = spawn('p1-s', { taskId: 't1', projectId: 'p1' });
+    const other = spawn('p2-a', { taskId: 't3', projectId: 'p2', isShell: false });
+    const sent = sendToProjectAgents('p1', 'fix it', true);
+    await vi.advanceTimersByTimeAsync(50);
+    await expect(sent).resolves.toEqual(['p1-a', 'p1-b']);
+    expect(first.writes).toEqual(['fix it', '\r']);
+    expect(second.writes).toEqual(['fix it', '\r']);
+    expect(shell.writes).toEqual([]);
+    expect(other.writes).toEqual([]);
+  });
+
+  it("lists and stops one project's sessions while the other's keep running", async () => {
+    spawn('run-a', { taskId: 't1', projectId: 'run-p1', isShell: false });
+    spawn('run-s', { taskId: 't1', projectId: 'run-p1' });
+    spawn('run-b', { taskId: 't2', projectId: 'run-p2', isShell: false });
+    const ids = (projectId: string) =>
+      listSessions({ projectId }).map((s) => `${s.agent_id}:${s.status}`);
+    expect(ids('run-p1')).toEqual(['run-a:running', 'run-s:running']);
+
+    expect(killProjectAgents('run-p1')).toEqual(['run-a', 'run-s']);
+    await vi.advanceTimersByTimeAsync(0);
+    expect(ids('run-p1')).toEqual(['run-s:exited', 'run-a:exited']);
+    expect(ids('run-p2')).toEqual(['run-b:running']);
+    expect(listSessions({ taskId: 't2', projectId: 'run-p1' })).toEqual([]);
+  });
+});
+
+describe('spawn environment', () => {
+  const agents: string[] = [];
+  const spawn = (agentId: string, opts: FakeSpawnOptions) => {
+    agents.push(agentId);
+    return spawnFake(agentId, opts);
+  };
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(async () => {
+    for (const id of agents.splice(0)) killAgent(id);
+    await vi.advanceTimersByTimeAsync(0);
+    vi.useRealTimers();
+  });
+
+  it("gives an agent the task's env and the secrets it lists", () => {
+    const proc = spawn('env-agent', {
+      isShell: false,
+      env: { TASK_VAR: 'task' },
+      secrets: ['API_KEY'],
+    });
+    expect(proc.env).toMatchObject({
+      TASK_VAR: 'task',
+      API_KEY: 'stored API_KEY',
+      INJECTED_KEY: 'injected',
+    });
+  });
+
+  it('gives a terminal the listed secrets but not the injected ones', () => {
+    const proc = spawn('env-shell', { secrets: ['API_KEY'] });
+    expect(proc.env.API_KEY).toBe('stored API_KEY');
+    expect(proc.env.INJECTED_KEY).toBeUndefined();
+  });
+
+  it('lets a task env value win over a secret of the same name', () => {
+    const proc = spawn('env-override', {
+      isShell: false,
+      env: { API_KEY: 'from env', INJECTED_KEY: 'from env' },
+      secrets: ['API_KEY'],
+    });
+    expect(proc.env).toMatchObject({ API_KEY: 'from env', INJECTED_KEY: 'from env' });
+  });
+});
@@ -1,22 +1,240 @@
 import * as pty from 'node-pty';
 import { execFileSync } from 'child_process';
 import fs from 'fs';
+import path from 'path';
 import type { BrowserWindow } from 'electron';
 import { RingBuffer } from '../remote/ring-buffer.js';
+import { IPC } from './channels.js';
+import { createUsageParser, recordSessionUsage } from './usage.js';
+import { resolveAgentAdapter, type AgentEvent } from './adapters/index.js';
+import { createAgentEventParser, parseGenericEventLine } from './agent-events.js';
+import { captureAgentSession } from './agent-sessions.js';
+import { getSettings } from './settings.js';
+import { recordTaskEvent } from './task-events.js';
+import { notify } from './notifications.js';
+import { createLogger } from './log.js';
+import { AppError } from './errors.js';
+import { ensureTaskPorts, portEnv } from './ports.js';
+import { namedSecretEnv, secretEnv } from './secrets.js';
+import { trackSession, untrackSession } from './orphans.js';
+import { startRecording, type SessionRecorder } from './recordings.js';
+import {
+  scrollbackLines,
+  searchScrollback,
+  type ScrollbackSearchResult,
+} from './scrollback-search.js';
+import { storeTranscript } from './storage.js';
+import { checkpointAfterTurn } from './checkpoints.js';
+import { createScrollbackSpill, type ScrollbackSpill } from './scrollback-spill.js';
+import { buildSpawnEnv, getShellEnv, safeEnvOverridesFrom } from './shell-env.js';
+import { createOscTracker } from './osc.js';
+import { wrapInSandbox } from './sandbox.js';
+import { remoteTargetFor, sshArgs } from './ssh.js';
+import {
+  containerName,
+  detectContainer,
+  findWorktreeRoot,
+  removeContainer,
+  wrapInContainer,
+} from './containers.js';
+import {
+  approvalAgentKind,
+  approvalKeys,
+  detectApprovalPrompt,
+  type ApprovalAgentKind,
+} from './approvals.js';
+
+const log = createLogger('pty');
 
 interface PtySession {
   proc: pty.IPty;
   channelId: string;
   taskId: string;
+  /** Project the task belongs to; null for sessions spawned without one. */
+  projectId: string | null;
   agentId: string;
+  agentDefId: string | null;
   isShell: boolean;
+  command: string;
+  cwd: string;
+  /** Epoch ms the process was spawned. */
+  startedAt: number;
   flushTimer: ReturnType<typeof setTimeout> | null;
   subscribers: Set<(encoded: string) => void>;
   scrollback: RingBuffer;
+  /** Where agents' scrollback goes once it no longer fits, for the transcript. */
+  spill: ScrollbackSpill | null;
+  /** Set by killAgent so an intentional kill is never treated as a crash. */
+  killed: boolean;
+  /** Whether the program enabled bracketed paste (DECSET 2004). */
+  bracketedPaste: boolean;
+  /** Chunked pastes in flight; later writes queue behind them to keep input ordered. */
+  writeQueue: Promise<void>;
+  pendingWrites: number;
+  /** Set while the renderer has paused reading output to catch up. */
+  outputPaused: boolean;
+  /** Counts output reads, so a queued write can tell the program echoed its last chunk. */
+  outputSeq: number;
+  /** Queued writes waiting for output, a resume or the session's end. */
+  flowWaiters: Set<() => void>;
+  /** asciicast recorder when `record_sessions` is on. */
+  recorder: SessionRecorder | null;
+  /** Last title the program set (OSC 0/2). */
+  title: string | null;
+  /** Last working directory the shell reported (OSC 7). */
+  currentCwd: string | null;
+  /** Whose permission prompts to look for; null for shells and unknown agents. */
+  approvalKind: ApprovalAgentKind | null;
+  /** Text of the permission prompt the agent is waiting on. */
+  pendingApproval: string | null;
+  approvalTimer: ReturnType<typeof setTimeout> | null;
+  /** Epoch ms of the last output from and input to the program. */
+  lastOutputAt: number;
+  lastInputAt: number;
+  /** Set while the agent has gone quiet for longer than the idle or hung threshold. */
+  stall: SessionStall | null;
+  /** Fires once the agent has been quiet long enough for its turn to count as finished. */
+  checkpointTimer: ReturnType<typeof setTimeout> | null;
+  /** What the agent reported doing, oldest first, for its timeline. */
+  events: AgentEventRecord[];
+}
+
+export interface AgentEventRecord extends AgentEvent {
+  /** Epoch ms of the output the event was read from. */
+  at: number;
+}
+
+/** Events kept per session; older ones fall off the timeline. */
+const MAX_SESSION_EVENTS = 200;
+
+export type SessionStall = 'idle' | 'hung';
+
+export interface SessionInfo {
+  agent_id: string;
+  task_id: string;
+  command: string;
+  is_shell: boolean;
+  title: string | null;
+  /** Reported working directory, or the spawn directory until one is reported. */
+  cwd: string;
+  /** True once the session has moved out of the directory it was started in. */
+  outside_worktree: boolean;
+  last_output_at: number;
+  last_input_at: number;
+  stall: SessionStall | null;
+}
+
+function sessionInfo(s: PtySession): SessionInfo {
+  const cwd = s.currentCwd ?? s.cwd;
+  const rel = path.relative(s.cwd, cwd);
+  return {
+    agent_id: s.agentId,
+    task_id: s.taskId,
+    command: s.command,
+    is_shell: s.isShell,
+    title: s.title,
+    cwd,
+    outside_worktree: rel.startsWith('..') || path.isAbsolute(rel),
+    last_output_at: s.lastOutputAt,
+    last_input_at: s.lastInputAt,
+    stall: s.stall,
+  };
 }
 
 const sessions = new Map<string, PtySession>();
 
+// --- Restart policy ---
+
+export type RestartPolicyKind = 'never' | 'on-failure' | 'always';
+
+export interface RestartPolicy {
+  policy: RestartPolicyKind;
+  max_retries: number;
+}
+
+const RESTART_BACKOFF_BASE_MS = 1_000;
+const RESTART_BACKOFF_MAX_MS = 30_000;
+
+/** Restarts scheduled after a crash, keyed by agentId. Cancelled by kill/respawn. */
+const pendingRestarts = new Map<
+  string,
+  { timer: ReturnType<typeof setTimeout>; cancel: () => void }
+>();
+
+/**
+ * Decide whether an exited agent should be respawned. `attempts` is the
+ * number of restarts already performed for this agent.
+ */
+export function shouldRestart(
+  policy: RestartPolicy | undefined,
+  exit: { exitCode: number; signal?: number; killed: boolean },
+  attempts: number,
+): boolean {
+  if (!policy || policy.policy === 'never' || exit.killed) return false;
+  if (attempts >= policy.max_retries) return false;
+  if (policy.policy === 'always') return true;
+  return exit.exitCode !== 0 || (exit.signal !== undefined && exit.signal !== 0);
+}
+
+/** Exponential backoff between restarts, capped at 30s. */
+export function restartDelayMs(attempt: number): number {
+  const backoff = RESTART_BACKOFF_BASE_MS * 2 ** Math.max(0, attempt - 1);
+  return Math.min(backoff, RESTART_BACKOFF_MAX_MS);
+}
+
+/**
+ * Why starting another agent in `taskId` would break a session limit, or
+ * null when it fits. `running` are the other agents' task ids; 0 is no limit.
+ */
+export function sessionLimitExceeded(
+  running: string[],
+  taskId: string,
+  limits: { max_agent_sessions: number; max_agents_per_task: number },
+): string | null {
+  const { max_agent_sessions: maxTotal, max_agents_per_task: maxPerTask } = limits;
+  if (maxTotal > 0 && running.length >= maxTotal) {
+    return `${running.length} agents are already running (max_agent_sessions is ${maxTotal})`;
+  }
+  const inTask = running.filter((id) => id === taskId).length;
+  if (maxPerTask > 0 && inTask >= maxPerTask) {
+    return `This task already runs ${inTask} agents (max_agents_per_task is ${maxPerTask})`;
+  }
+  return null;
+}
+
+const MIN_SCROLLBACK_BYTES = 64 * 1024;
+
+export interface ScrollbackStats {
+  /** Memory the session's scrollback buffer takes. */
+  capacity_bytes: number;
+  buffered_bytes: number;
+  /** Output pushed out of memory to the on-disk spill so far. */
+  spilled_bytes: number;
+}
+
+/** Each session's scrollback when `count` run: an equal share of the budget, within bounds. */
+export function scrollbackCapacity(
+  count: number,
+  limits: { scrollback_session_kb: number; scrollback_budget_mb: number },
+): number {
+  const share = Math.floor((limits.scrollback_budget_mb * 1024 * 1024) / Math.max(1, count));
+  return Math.max(MIN_SCROLLBACK_BYTES, Math.min(limits.scrollback_session_kb * 1024, share));
+}
+
+/** Resize every session's scrollback to its share after one starts or ends. */
+function rebalanceScrollback(): void {
+  const capacity = scrollbackCapacity(sessions.size, getSettings());
+  for (const s of sessions.values()) s.scrollback.resize(capacity);
+}
+
+function cancelPendingRestart(agentId: string): void {
+  const pending = pendingRestarts.get(agentId);
+  if (!pending) return;
+  clearTimeout(pending.timer);
+  pendingRestarts.delete(agentId);
+  pending.cancel();
+}
+
 // --- PTY event bus for spawn/exit notifications ---
 
 type PtyEventType = 'spawn' | 'exit' | 'list-changed';
@@ -49,6 +267,112 @@ const BATCH_MAX = 64 * 1024;
 const BATCH_INTERVAL = 8; // ms
 const TAIL_CAP = 8 * 1024;
 const MAX_LINES = 50;
+const SUBMIT_DELAY_MS = 50;
+const PASTE_CHUNK_SIZE = 1024;
+const PASTE_CHUNK_DELAY_MS = 5;
+/** How long a chunk waits for the program to echo the previous one before going anyway. */
+const PASTE_ACK_TIMEOUT_MS = 50;
+/** How long a chunk waits for paused output to resume before going anyway. */
+const PAUSED_WRITE_TIMEOUT_MS = 2_000;
+/** Output must settle this long before checking for a permission prompt. */
+const APPROVAL_SETTLE_MS = 200;
+
+const PASTE_START = '\x1b[200~';
+const PASTE_END = '\x1b[201~';
+// eslint-disable-next-line no-control-regex
+const BRACKETED_PASTE_MODE = /\x1b\[\?2004([hl])/g;
+
+/**
+ * Track DECSET/DECRST 2004 in program output. `tail` holds the end of the
+ * previous chunk so a sequence split across reads is still seen.
+ */
+export function scanBracketedPasteMode(
+  data: string,
+  tail: string,
+  enabled: boolean,
+): { enabled: boolean; tail: string } {
+  const text = tail + data;
+  let result = enabled;
+  for (const match of text.matchAll(BRACKETED_PASTE_MODE)) result = match[1] === 'h';
+  return { enabled: result, tail: text.slice(-7) };
+}
+
+/**
+ * Wrap pasted text in bracketed paste markers so newlines don't submit.
+ * An embedded end marker is stripped so the text can't break out early.
+ */
+export function wrapBracketedPaste(text: string): string {
+  return PASTE_START + text.split(PASTE_END).join('') + PASTE_END;
+}
+
+/**
+ * Where the escape sequence at `i` (an ESC) ends: CSI up to its final byte,
+ * OSC, DCS and the like up to BEL or ST, anything else after one character
+ * (two for SS3). One that's never terminated is only its introducer, so
+ * stray text after it still gets chunked.
+ */
+function escapeEnd(text: string, i: number): number {
+  const kind = text[i + 1];
+  if (kind === '[') {
+    for (let j = i + 2; j < text.length; j++) {
+      const c = text.charCodeAt(j);
+      if (c >= 0x40 && c <= 0x7e) return j + 1;
+    }
+  } else if (kind === ']' || kind === 'P' || kind === '_' || kind === '^' || kind === 'X') {
+    for (let j = i + 2; j < text.length; j++) {
+      if (text[j] === '\x07') return j + 1;
+      if (text[j] === '\x1b' && text[j + 1] === '\\') return j + 2;
+    }
+  } else if (kind === 'O') {
+    return Math.min(i + 3, text.length);
+  }
+  return Math.min(i + 2, text.length);
+}
+
+/**
+ * Split text into PTY-sized writes without breaking surrogate pairs or
+ * escape sequences: a program reading half a bracketed paste marker or key
+ * sequence before the pause between chunks takes the rest as typed text.
+ * A sequence longer than `size` goes out whole in a chunk of its own.
+ */
+export function chunkForPty(text: string, size = PASTE_CHUNK_SIZE): string[] {
+  const chunks: string[] = [];
+  let start = 0;
+  let esc = text.indexOf('\x1b');
+  while (start < text.length) {
+    let end = Math.min(start + size, text.length);
+    while (esc !== -1 && esc < end) {
+      const seqEnd = escapeEnd(text, esc);
+      if (seqEnd > end) {
+        end = esc > start ? esc : seqEnd;
+        break;
+      }
+      esc = text.indexOf('\x1b', seqEnd);
+    }
+    const last = text.charCodeAt(end - 1);
+    if (end < text.length && last >= 0xd800 && last <= 0xdbff) end--;
+    chunks.push(text.slice(start, end));
+    start = end;
+  }
+  return chunks;
+}
+
+/** Shells known to take `-l` for a login shell. */
+const LOGIN_FLAG_SHELLS = new Set(['bash', 'zsh', 'sh', 'dash', 'ksh', 'mksh', 'fish', 'nu']);
+
+/** Arguments that start `shell` for a plain terminal. */
+export function plainShellArgs(shell: string, login: boolean): string[] {
+  const name = path.basename(shell).replace(/\.exe$/i, '');
+  return login && LOGIN_FLAG_SHELLS.has(name) ? ['-l'] : [];
+}
+
+/**
+ * Init commands as typed input. The tty buffers it until the shell has
+ * loaded its profile, so the commands see the same environment a user would.
+ */
+export function shellInitInput(commands: string[]): string {
+  return commands.map((c) => `${c}\r`).join('');
+}
 
 /** Verify that a command exists in PATH. Throws a descriptive error if not found. */
 export function validateCommand(command: string): void {
@@ -61,8 +385,10 @@ export function validateCommand(command: string): void {
       fs.accessSync(command, fs.constants.X_OK);
       return;
     } catch {
-      throw new Error(
+      throw new AppError(
+        'agent_not_installed',
         `Command '${command}' not found or not executable. Check that it is installed.`,
+        { command },
       );
     }
   }
@@ -70,29 +396,38 @@ export function validateCommand(command: string): void {
   try {
     execFileSync('which', [command], { encoding: 'utf8', timeout: 3000 });
   } catch {
-    throw new Error(
+    throw new AppError(
+      'agent_not_installed',
       `Command '${command}' not found in PATH. Make sure it is installed and available in your terminal.`,
+      { command },
     );
   }
 }
 
-export function spawnAgent(
-  win: BrowserWindow,
-  args: {
-    taskId: string;
-    agentId: string;
-    command: string;
-    args: string[];
-    cwd: string;
-    env: Record<string, string>;
-    cols: number;
-    rows: number;
-    isShell?: boolean;
-    onOutput: { __CHANNEL_ID__: string };
-  },
-): void {
+export interface SpawnAgentArgs {
+  taskId: string;
+  /** Project the task belongs to, so its sessions can be listed, prompted and stopped together. */
+  projectId?: string;
+  agentId: string;
+  command: string;
+  args: string[];
+  cwd: string;
+  env: Record<string, string>;
+  /** Stored secrets the task lists, for this session whether or not they're injected. */
+  secrets?: string[];
+  cols: number;
+  rows: number;
+  isShell?: boolean;
+  restartPolicy?: RestartPolicy;
+  agentDefId?: string;
+  /** Adapter id whose output formats the session is read with, for custom agents. */
+  usageParser?: string;
+  onOutput: { __CHANNEL_ID__: string };
+}
+
+export function spawnAgent(win: BrowserWindow, args: SpawnAgentArgs, restartAttempt = 0): void {
   const channelId = args.onOutput.__CHANNEL_ID__;
-  const command = args.command || process.env.SHELL || '/bin/sh';
+  const command = args.command || getSettings().shell || process.env.SHELL || '/bin/sh';
   const cwd = args.cwd || process.env.HOME || '/';
 
   // Reject commands with shell metacharacters (node-pty uses execvp, but
@@ -102,75 +437,197 @@ export function spawnAgent(
     throw new Error(`Command contains disallowed characters: ${command}`);
   }
 
-  validateCommand(command);
+  // Remote projects' agents run on their machine, which the local PATH says nothing about
+  const remote = remoteTargetFor(cwd);
+  const settings = getSettings();
+  const worktreeRoot = !remote && settings.container_sessions ? findWorktreeRoot(cwd) : null;
+  const container = worktreeRoot ? detectContainer(worktreeRoot) : null;
+  // Inside a container the command comes from the image
+  if (!remote && !container) validateCommand(command);
+
+  // A respawn replaces its own session, so that one doesn't count
+  if (!args.isShell) {
+    const running = [...sessions.values()]
+      .filter((s) => !s.isShell && s.agentId !== args.agentId)
+      .map((s) => s.taskId);
+    const exceeded = sessionLimitExceeded(running, args.taskId, settings);
+    if (exceeded) throw new AppError('resource_limit_exceeded', exceeded);
+  }
+
+  // An explicit respawn supersedes any crash restart still waiting to fire
+  if (restartAttempt === 0) cancelPendingRestart(args.agentId);
 
   // Kill any existing session with the same agentId to prevent PTY leaks
   const existing = sessions.get(args.agentId);
   if (existing) {
     if (existing.flushTimer) clearTimeout(existing.flushTimer);
     existing.subscribers.clear();
+    existing.killed = true;
     existing.proc.kill();
+    existing.spill?.close();
     sessions.delete(args.agentId);
   }
 
-  const filteredEnv: Record<string, string> = {};
-  for (const [k, v] of Object.entries(process.env)) {
-    if (v !== undefined) filteredEnv[k] = v;
-  }
-
-  // Only allow safe env overrides from renderer. Reject vars that could
-  // alter process loading or execution behavior.
-  const ENV_BLOCK_LIST = new Set([
-    'PATH',
-    'HOME',
-    'USER',
-    'SHELL',
-    'LD_PRELOAD',
-    'LD_LIBRARY_PATH',
-    'DYLD_INSERT_LIBRARIES',
-    'NODE_OPTIONS',
-    'ELECTRON_RUN_AS_NODE',
-  ]);
-  const safeEnvOverrides: Record<string, string> = {};
-  for (const [k, v] of Object.entries(args.env ?? {})) {
-    if (!ENV_BLOCK_LIST.has(k)) safeEnvOverrides[k] = v;
-  }
+  // Agents get the secrets marked for injection, and agents and terminals the ones their
+  // task lists; task and project values win over them. Over ssh the environment travels
+  // in the command line, which `ps` shows here and on the remote machine, so remote
+  // sessions go without secrets
+  const safeEnvOverrides = safeEnvOverridesFrom({
+    ...(args.isShell || remote ? {} : secretEnv()),
+    ...(remote || !args.secrets?.length ? {} : namedSecretEnv(args.secrets)),
+    ...args.env,
+  });
 
-  const spawnEnv: Record<string, string> = {
-    ...filteredEnv,
+  // Overrides arrive from the renderer with task values already layered over project ones
+  const spawnEnv = buildSpawnEnv(process.env, getShellEnv(), {
     TERM: 'xterm-256color',
     COLORTERM: 'truecolor',
+    // Parallel worktrees running dev servers would otherwise fight over ports
+    ...portEnv(ensureTaskPorts(args.taskId)),
     ...safeEnvOverrides,
-  };
+  });
 
   // Clear env vars that prevent nested agent sessions
   delete spawnEnv.CLAUDECODE;
   delete spawnEnv.CLAUDE_CODE_SESSION;
   delete spawnEnv.CLAUDE_CODE_ENTRYPOINT;
 
-  const proc = pty.spawn(command, args.args, {
+  // Plain terminals on the configured shell follow the shell settings
+  const configuredShell = !!args.isShell && !args.command;
+  let spawnCommand = command;
+  let spawnArgs = configuredShell ? plainShellArgs(command, settings.shell_login) : args.args;
+  let containerToRemove: string | null = null;
+  if (remote) {
+    // Only the task's own variables travel; the remote login environment supplies the rest
+    spawnArgs = sshArgs(remote, configuredShell ? null : command, spawnArgs, {
+      tty: true,
+      env: { ...portEnv(ensureTaskPorts(args.taskId)), ...safeEnvOverrides },
+    });
+    spawnCommand = 'ssh';
+  } else if (container && worktreeRoot) {
+    // The host's TERM and ports go in; the rest of its environment stays out
+    const name = containerName(args.agentId, Date.now());
+    const ports = ensureTaskPorts(args.taskId);
+    ({ command: spawnCommand, args: spawnArgs } = wrapInContainer(
+      container,
+      {
+        runtime: settings.container_runtime,
+        name,
+        worktreeRoot,
+        cwd,
+        env: {
+          TERM: 'xterm-256color',
+          COLORTERM: 'truecolor',
+          ...portEnv(ports),
+          ...safeEnvOverrides,
+        },
+        ports,
+      },
+      configuredShell ? null : command,
+      spawnArgs,
+    ));
+    containerToRemove = name;
+  } else if (settings.sandbox_agents && !args.isShell) {
+    ({ command: spawnCommand, args: spawnArgs } = wrapInSandbox(
+      command,
+      spawnArgs,
+      cwd,
+      settings.sandbox_writable_paths,
+    ));
+  }
+
+  const startedAt = Date.now();
+  const proc = pty.spawn(spawnCommand, spawnArgs, {
     name: 'xterm-256color',
     cols: args.cols,
     rows: args.rows,
-    cwd,
+    cwd: remote ? (process.env.HOME ?? '/') : cwd,
     env: spawnEnv,
   });
 
+  const adapter = args.isShell
+    ? null
+    : resolveAgentAdapter(args.usageParser, args.agentDefId, command);
+  // Terminals keep no transcript, so what they push out is simply dropped
+  const spill = args.isShell ? null : createScrollbackSpill(args.agentId);
+
   const session: PtySession = {
     proc,
     channelId,
     taskId: args.taskId,
+    projectId: args.projectId ?? null,
     agentId: args.agentId,
+    agentDefId: args.agentDefId ?? null,
     isShell: args.isShell ?? false,
+    command,
+    cwd,
+    startedAt,
     flushTimer: null,
     subscribers: new Set(),
-    scrollback: new RingBuffer(),
+    scrollback: new RingBuffer(
+      scrollbackCapacity(sessions.size + 1, settings),
+      spill ? (data) => spill.write(data) : undefined,
+    ),
+    spill,
+    killed: false,
+    bracketedPaste: false,
+    writeQueue: Promise.resolve(),
+    pendingWrites: 0,
+    outputPaused: false,
+    outputSeq: 0,
+    flowWaiters: new Set(),
+    recorder: null,
+    title: null,
+    currentCwd: null,
+    approvalKind: adapter ? approvalAgentKind(adapter.id, command) : null,
+    pendingApproval: null,
+    approvalTimer: null,
+    lastOutputAt: startedAt,
+    lastInputAt: startedAt,
+    stall: null,
+    checkpointTimer: null,
+    events: [],
   };
   sessions.set(args.agentId, session);
+  exitedSessions.delete(args.agentId);
+  trackSession({
+    agent_id: args.agentId,
+    task_id: args.taskId,
+    pid: proc.pid,
+    command,
+    is_shell: session.isShell,
+    started_at: startedAt,
+  });
+  rebalanceScrollback();
+  if (!session.isShell) ensureStallMonitor(win);
+  if (configuredShell && settings.shell_init_commands.length > 0) {
+    proc.write(shellInitInput(settings.shell_init_commands));
+  }
+
+  if (settings.record_sessions) {
+    try {
+      session.recorder = startRecording({
+        taskId: args.taskId,
+        agentId: args.agentId,
+        cols: args.cols,
+        rows: args.rows,
+        title: path.basename(command),
+      });
+    } catch (err) {
+      log.warn(`Could not record ${args.agentId}`, err);
+    }
+  }
+
+  const usageParser = adapter?.parseUsageLine ? createUsageParser(adapter.parseUsageLine) : null;
+  const eventParser = session.isShell
+    ? null
+    : createAgentEventParser(adapter?.parseEventLine ?? parseGenericEventLine);
 
   // Batching strategy matching the Rust implementation
   let batch = Buffer.alloc(0);
   let tailBuf = Buffer.alloc(0);
+  let modeTail = '';
+  const osc = createOscTracker();
 
   const send = (msg: unknown) => {
     if (!win.isDestroyed()) {
@@ -195,6 +652,31 @@ export function spawnAgent(
 
   proc.onData((data: string) => {
     const chunk = Buffer.from(data, 'utf8');
+    session.outputSeq++;
+    notifyFlow(session);
+    session.recorder?.output(data);
+    session.lastOutputAt = Date.now();
+    if (session.stall) setStall(win, session, null);
+    if (!session.isShell) scheduleCheckpoint(session, args.agentDefId ?? 'agent');
+
+    const mode = scanBracketedPasteMode(data, modeTail, session.bracketedPaste);
+    session.bracketedPaste = mode.enabled;
+    modeTail = mode.tail;
+
+    const update = osc.feed(data);
+    if (update) {
+      const title = update.title ?? session.title;
+      const currentCwd = update.cwd ?? session.currentCwd;
+      if (title !== session.title || currentCwd !== session.currentCwd) {
+        session.title = title;
+        session.currentCwd = currentCwd;
+        if (!win.isDestroyed()) win.webContents.send(IPC.SessionInfoChanged, sessionInfo(session));
+      }
+    }
+
+    const usage = usageParser?.feed(data);
+    if (usage) recordSessionUsage(args.taskId, args.agentId, args.agentDefId ?? null, usage);
+    for (const event of eventParser?.feed(data) ?? []) recordAgentEvent(win, session, event);
 
     // Maintain tail buffer for exit diagnostics
     tailBuf = Buffer.concat([tailBuf, chunk]);
@@ -204,6 +686,19 @@ export function spawnAgent(
 
     batch = Buffer.concat([batch, chunk]);
 
+    if (session.approvalKind) {
+      if (session.approvalTimer) clearTimeout(session.approvalTimer);
+      session.approvalTimer = setTimeout(() => {
+        session.approvalTimer = null;
+        if (!session.approvalKind) return;
+        setPendingApproval(
+          win,
+          session,
+          detectApprovalPrompt(session.approvalKind, tailBuf.toString('utf8')),
+        );
+      }, APPROVAL_SETTLE_MS);
+    }
+
     // Flush large batches immediately
     if (batch.length >= BATCH_MAX) {
       flush();
@@ -223,6 +718,17 @@ export function spawnAgent(
   });
 
   proc.onExit(({ exitCode, signal }) => {
+    session.recorder?.close();
+    notifyFlow(session);
+    if (containerToRemove) removeContainer(settings.container_runtime, containerToRemove);
+    // Remember which conversation this was so the task can resume exactly it
+    if (adapter && args.agentDefId) {
+      captureAgentSession(args.taskId, args.agentDefId, adapter, cwd, startedAt);
+    }
+    if (session.approvalTimer) clearTimeout(session.approvalTimer);
+    if (session.checkpointTimer) clearTimeout(session.checkpointTimer);
+    setPendingApproval(win, session, null);
+
     // If this session was replaced by a new spawn with the same agentId,
     // skip cleanup — the new session owns the map entry now.
     if (sessions.get(args.agentId) !== session) return;
@@ -238,51 +744,253 @@ export function spawnAgent(
       .filter((l) => l.length > 0)
       .slice(-MAX_LINES);
 
-    send({
-      type: 'Exit',
-      data: {
-        exit_code: exitCode,
-        signal: signal !== undefined ? String(signal) : null,
-        last_output: lines,
-      },
-    });
+    const sendExit = () =>
+      send({
+        type: 'Exit',
+        data: {
+          exit_code: exitCode,
+          signal: signal !== undefined ? String(signal) : null,
+          last_output: lines,
+        },
+      });
 
     emitPtyEvent('exit', args.agentId, { exitCode, signal });
     sessions.delete(args.agentId);
+    untrackSession(args.agentId);
+    rememberExitedSession(session, exitCode, signal ?? null);
+    log.info(`Agent ${args.agentId} exited (code ${exitCode}, signal ${signal ?? 'none'})`);
+    rebalanceScrollback();
+    if (!args.isShell) {
+      const spilled = session.spill?.read() ?? Buffer.alloc(0);
+      const output = Buffer.concat([spilled, session.scrollback.read()]);
+      storeTranscript(args.taskId, args.agentId, scrollbackLines(output.toString('utf8')));
+      session.spill?.close();
+      recordTaskEvent(args.taskId, 'agent_exited', {
+        agent_id: args.agentId,
+        exit_code: exitCode,
+        signal: signal ?? null,
+      });
+    }
+
+    const exit = { exitCode, signal, killed: session.killed };
+    if (!shouldRestart(args.restartPolicy, exit, restartAttempt)) {
+      if (!args.isShell && !session.killed) {
+        if (exitCode === 0) {
+          notify('agent_finished', 'Agent finished', 'Ready for review', args.taskId);
+        } else {
+          notify('agent_error', 'Agent exited with an error', `Exit code ${exitCode}`, args.taskId);
+        }
+      }
+      sendExit();
+      return;
+    }
+
+    // Crash restart: keep the same output channel so the frontend terminal
+    // simply continues with the new process.
+    const attempt = restartAttempt + 1;
+    const delayMs = restartDelayMs(attempt);
+    if (!win.isDestroyed()) {
+      win.webContents.send(IPC.AgentRestarted, {
+        agentId: args.agentId,
+        taskId: args.taskId,
+        attempt,
+        maxRetries: args.restartPolicy?.max_retries ?? 0,
+        exitCode,
+        signal: signal !== undefined ? String(signal) : null,
+        delayMs,
+        lastOutput: lines,
+      });
+    }
+    const timer = setTimeout(() => {
+      pendingRestarts.delete(args.agentId);
+      try {
+        spawnAgent(win, args, attempt);
+      } catch (err) {
+        log.warn(`Failed to restart agent ${args.agentId}:`, err);
+        sendExit();
+      }
+    }, delayMs);
+    pendingRestarts.set(args.agentId, { timer, cancel: sendExit });
   });
 
   emitPtyEvent('spawn', args.agentId);
+  log.info(`Spawned ${args.isShell ? 'shell' : 'agent'} ${args.agentId} for task ${args.taskId}`);
+  if (!args.isShell) {
+    recordTaskEvent(args.taskId, 'agent_spawned', {
+      agent_id: args.agentId,
+      agent_def_id: args.agentDefId ?? null,
+      restart_attempt: restartAttempt,
+    });
+  }
+}
+
+function notifyFlow(session: PtySession): void {
+  if (session.flowWaiters.size === 0) return;
+  const waiters = [...session.flowWaiters];
+  session.flowWaiters.clear();
+  for (const wake of waiters) wake();
+}
+
+/** Resolves on the session's next output, resume or exit, or after `ms`. */
+function nextFlowSignal(session: PtySession, ms: number): Promise<void> {
+  return new Promise((resolve) => {
+    const wake = () => {
+      clearTimeout(timer);
+      session.flowWaiters.delete(wake);
+      resolve();
+    };
+    const timer = setTimeout(wake, ms);
+    session.flowWaiters.add(wake);
+  });
+}
+
+/**
+ * Queue `chunks` behind earlier writes. The tty only buffers a few KB of
+ * input, so before each chunk the program gets to read the previous one:
+ * it has echoed something since, or had `PASTE_ACK_TIMEOUT_MS`. While the
+ * renderer has output paused the program can block writing output and stop
+ * reading input, so chunks also wait (for a while) for it to resume.
+ * Resolves false when the session ended before every chunk was written.
+ */
+function queueWrite(session: PtySession, chunks: string[]): Promise<boolean> {
+  session.pendingWrites++;
+  const alive = () => sessions.get(session.agentId) === session;
+  const done = session.writeQueue.then(async () => {
+    let writtenAt = session.outputSeq;
+    for (let i = 0; i < chunks.length; i++) {
+      if (!alive()) return false;
+      if (i > 0) {
+        await new Promise((r) => setTimeout(r, PASTE_CHUNK_DELAY_MS));
+        if (session.outputSeq === writtenAt) await nextFlowSignal(session, PASTE_ACK_TIMEOUT_MS);
+      }
+      const pausedSince = Date.now();
+      while (alive() && session.outputPaused) {
+        const left = PAUSED_WRITE_TIMEOUT_MS - (Date.now() - pausedSince);
+        if (left <= 0) break;
+        await nextFlowSignal(session, left);
+      }
+      if (!alive()) return false;
+      writtenAt = session.outputSeq;
+      session.proc.write(chunks[i]);
+    }
+    return alive();
+  });
+  session.writeQueue = done.then(
+    () => undefined,
+    () => undefined,
+  );
+  void session.writeQueue.then(() => session.pendingWrites--);
+  return done;
 }
 
 /** Write input to an agent. Returns false when the agent already exited. */
 export function writeToAgent(agentId: string, data: string): boolean {
   const session = sessions.get(agentId);
   if (!session) return false;
-  session.proc.write(data);
+  session.lastInputAt = Date.now();
+  // Large writes are chunked like pastes so the tty doesn't drop any of it
+  if (session.pendingWrites > 0 || data.length > PASTE_CHUNK_SIZE) {
+    void queueWrite(session, chunkForPty(data));
+  } else {
+    session.proc.write(data);
+  }
   return true;
 }
 
+/**
+ * Paste text into an agent: bracketed when the program asked for it (so
+ * multi-line prompts aren't submitted line by line) and written in chunks so
+ * large pastes aren't truncated. Resolves false when the agent exited first.
+ */
+export function pasteToAgent(agentId: string, text: string): Promise<boolean> {
+  const session = sessions.get(agentId);
+  if (!session) return Promise.resolve(false);
+  session.lastInputAt = Date.now();
+  const data = session.bracketedPaste ? wrapBracketedPaste(text) : text;
+  return queueWrite(session, chunkForPty(data));
+}
+
+/**
+ * Paste a prompt into an agent's PTY, optionally pressing Enter afterwards.
+ * Enter is sent separately after a short delay so TUI apps (Claude Code,
+ * Codex) don't treat the \r as part of the pasted block.
+ * Returns false when the agent exited before the prompt was fully written.
+ */
+export async function sendToAgent(
+  agentId: string,
+  text: string,
+  submit: boolean,
+): Promise<boolean> {
+  const session = sessions.get(agentId);
+  if (!session) return false;
+  if (!(await pasteToAgent(agentId, text))) return false;
+  if (!submit) return true;
+  await new Promise((r) => setTimeout(r, SUBMIT_DELAY_MS));
+  // The session may have exited or been respawned while we waited
+  if (sessions.get(agentId) !== session) return false;
+  // Queued so input written meanwhile can't land between the prompt and Enter
+  return queueWrite(session, ['\r']);
+}
+
+/**
+ * Send the same prompt to every non-shell agent of a task.
+ * Returns the IDs of the agents that received it.
+ */
+export async function sendToTaskAgents(
+  taskId: string,
+  text: string,
+  submit: boolean,
+): Promise<string[]> {
+  const targets = [...sessions.values()].filter((s) => s.taskId === taskId && !s.isShell);
+  const results = await Promise.all(
+    targets.map(async (s) => ((await sendToAgent(s.agentId, text, submit)) ? s.agentId : null)),
+  );
+  return results.filter((id): id is string => id !== null);
+}
+
+/**
+ * Send the same prompt to every non-shell agent of a project's tasks.
+ * Returns the IDs of the agents that received it.
+ */
+export async function sendToProjectAgents(
+  projectId: string,
+  text: string,
+  submit: boolean,
+): Promise<string[]> {
+  const targets = [...sessions.values()].filter((s) => s.projectId === projectId && !s.isShell);
+  const results = await Promise.all(
+    targets.map(async (s) => ((await sendToAgent(s.agentId, text, submit)) ? s.agentId : null)),
+  );
+  return results.filter((id): id is string => id !== null);
+}
+
 export function resizeAgent(agentId: string, cols: number, rows: number): void {
   const session = sessions.get(agentId);
   if (!session) throw new Error(`Agent not found: ${agentId}`);
   session.proc.resize(cols, rows);
+  session.recorder?.resize(cols, rows);
 }
 
 export function pauseAgent(agentId: string): void {
   const session = sessions.get(agentId);
   if (!session) throw new Error(`Agent not found: ${agentId}`);
   session.proc.pause();
+  session.outputPaused = true;
 }
 
 export function resumeAgent(agentId: string): void {
   const session = sessions.get(agentId);
   if (!session) throw new Error(`Agent not found: ${agentId}`);
   session.proc.resume();
+  session.outputPaused = false;
+  notifyFlow(session);
 }
 
 export function killAgent(agentId: string): void {
+  cancelPendingRestart(agentId);
   const session = sessions.get(agentId);
   if (session) {
+    session.killed = true;
     if (session.flushTimer) {
       clearTimeout(session.flushTimer);
       session.flushTimer = null;
@@ -295,12 +1003,23 @@ export function killAgent(agentId: string): void {
   }
 }
 
+/** Kill every session of a project's tasks, terminals included. Returns their IDs. */
+export function killProjectAgents(projectId: string): string[] {
+  const agentIds = [...sessions.values()]
+    .filter((s) => s.projectId === projectId)
+    .map((s) => s.agentId);
+  for (const agentId of agentIds) killAgent(agentId);
+  return agentIds;
+}
+
 export function countRunningAgents(): number {
   return sessions.size;
 }
 
 export function killAllAgents(): void {
+  for (const agentId of [...pendingRestarts.keys()]) cancelPendingRestart(agentId);
   for (const [, session] of sessions) {
+    session.killed = true;
     if (session.flushTimer) clearTimeout(session.flushTimer);
     session.subscribers.clear();
     session.proc.kill();
@@ -328,6 +1047,17 @@ export function getAgentScrollback(agentId: string): string | null {
   return sessions.get(agentId)?.scrollback.toBase64() ?? null;
 }
 
+/** Search the output buffered for an agent, including what the terminal has scrolled away. */
+export function searchAgentScrollback(
+  agentId: string,
+  query: string,
+  opts: { regex: boolean; caseSensitive: boolean },
+): ScrollbackSearchResult {
+  const session = sessions.get(agentId);
+  if (!session) throw new Error(`Agent not found: ${agentId}`);
+  return searchScrollback(session.scrollback.read().toString('utf8'), query, opts);
+}
+
 /** Return all active agent IDs. */
 export function getActiveAgentIds(): string[] {
   return Array.from(sessions.keys());
@@ -341,6 +1071,249 @@ export function getAgentMeta(
   return s ? { taskId: s.taskId, agentId: s.agentId, isShell: s.isShell } : null;
 }
 
+function setPendingApproval(win: BrowserWindow, session: PtySession, prompt: string | null): void {
+  if (prompt === session.pendingApproval) return;
+  session.pendingApproval = prompt;
+  if (!win.isDestroyed()) {
+    win.webContents.send(IPC.AgentAwaitingApproval, {
+      agent_id: session.agentId,
+      task_id: session.taskId,
+      prompt,
+    });
+  }
+}
+
+function recordAgentEvent(win: BrowserWindow, session: PtySession, event: AgentEvent): void {
+  const record: AgentEventRecord = { ...event, at: session.lastOutputAt };
+  session.events.push(record);
+  if (session.events.length > MAX_SESSION_EVENTS) session.events.shift();
+  if (!win.isDestroyed()) {
+    win.webContents.send(IPC.AgentEvent, {
+      agent_id: session.agentId,
+      task_id: session.taskId,
+      ...record,
+    });
+  }
+}
+
+// --- Checkpoints ---
+
+/** (Re)start the wait for the agent to go quiet, after which its worktree is checkpointed. */
+function scheduleCheckpoint(session: PtySession, agent: string): void {
+  if (session.checkpointTimer) clearTimeout(session.checkpointTimer);
+  session.checkpointTimer = null;
+  const { auto_checkpoint, checkpoint_quiet_seconds } = getSettings();
+  if (!auto_checkpoint) return;
+  session.checkpointTimer = setTimeout(() => {
+    session.checkpointTimer = null;
+    // An agent waiting on a permission prompt is in the middle of its turn
+    if (sessions.get(session.agentId) !== session || session.pendingApproval) return;
+    void checkpointAfterTurn(session.taskId, session.cwd, agent, 'quiet');
+  }, checkpoint_quiet_seconds * 1000);
+}
+
+// --- Idle and hang detection ---
+
+const STALL_CHECK_INTERVAL_MS = 15_000;
+let stallMonitor: ReturnType<typeof setInterval> | null = null;
+
+/**
+ * Whether an agent quiet since `lastActivityAt` counts as idle or hung at
+ * `now`. Thresholds are in minutes and 0 turns a check off. An agent waiting
+ * on a permission prompt is only ever idle: it's waiting on the user.
+ */
+export function stallState(
+  lastActivityAt: number,
+  now: number,
+  idleAfterMinutes: number,
+  hungAfterMinutes: number,
+  awaitingApproval: boolean,
+): SessionStall | null {
+  const quietMs = now - lastActivityAt;
+  if (hungAfterMinutes > 0 && !awaitingApproval && quietMs >= hungAfterMinutes * 60_000) {
+    return 'hung';
+  }
+  if (idleAfterMinutes > 0 && quietMs >= idleAfterMinutes * 60_000) return 'idle';
+  return null;
+}
+
+function setStall(win: BrowserWindow, session: PtySession, stall: SessionStall | null): void {
+  if (session.stall === stall) return;
+  session.stall = stall;
+  if (win.isDestroyed()) return;
+  if (stall) {
+    win.webContents.send(stall === 'hung' ? IPC.AgentHung : IPC.AgentIdle, {
+      agent_id: session.agentId,
+      task_id: session.taskId,
+      last_activity_at: Math.max(session.lastOutputAt, session.lastInputAt),
+    });
+  }
+  win.webContents.send(IPC.SessionInfoChanged, sessionInfo(session));
+}
+
+function checkStalls(win: BrowserWindow): void {
+  if (sessions.size === 0 && stallMonitor) {
+    clearInterval(stallMonitor);
+    stallMonitor = null;
+    return;
+  }
+  const { idle_after_minutes, hung_after_minutes } = getSettings();
+  const now = Date.now();
+  for (const session of sessions.values()) {
+    if (session.isShell) continue;
+    const lastActivityAt = Math.max(session.lastOutputAt, session.lastInputAt);
+    const stall = stallState(
+      lastActivityAt,
+      now,
+      idle_after_minutes,
+      hung_after_minutes,
+      session.pendingApproval !== null,
+    );
+    setStall(win, session, stall);
+  }
+}
+
+function ensureStallMonitor(win: BrowserWindow): void {
+  if (!stallMonitor) stallMonitor = setInterval(() => checkStalls(win), STALL_CHECK_INTERVAL_MS);
+}
+
+/**
+ * Approve or deny the permission prompt an agent is waiting on, without
+ * focusing its terminal.
+ */
+export function respondToAgent(win: BrowserWindow, agentId: string, approve: boolean): void {
+  const session = sessions.get(agentId);
+  if (!session) throw new Error(`Agent not found: ${agentId}`);
+  if (!session.approvalKind || !session.pendingApproval) {
+    throw new Error('Agent is not waiting for approval');
+  }
+  writeToAgent(agentId, approvalKeys(session.approvalKind, approve));
+  setPendingApproval(win, session, null);
+}
+
+/** Title and working directory a session last reported. */
+export function getSessionInfo(agentId: string): SessionInfo | null {
+  const s = sessions.get(agentId);
+  return s ? sessionInfo(s) : null;
+}
+
+/** The session's timeline: what its agent reported doing, oldest first. */
+export function getAgentEvents(agentId: string): AgentEventRecord[] {
+  return [...(sessions.get(agentId)?.events ?? [])];
+}
+
+/** What a session runs and where. */
+export function getAgentSessionInfo(
+  agentId: string,
+): { command: string; cwd: string; isShell: boolean } | null {
+  const s = sessions.get(agentId);
+  return s ? { command: s.command, cwd: s.cwd, isShell: s.isShell } : null;
+}
+
+/** Process ids of the running sessions, for resource usage. */
+export function listSessionProcesses(): Array<{
+  agent_id: string;
+  task_id: string;
+  is_shell: boolean;
+  pid: number;
+  scrollback: ScrollbackStats;
+}> {
+  return [...sessions.values()].map((s) => ({
+    agent_id: s.agentId,
+    task_id: s.taskId,
+    is_shell: s.isShell,
+    pid: s.proc.pid,
+    scrollback: {
+      capacity_bytes: s.scrollback.capacity,
+      buffered_bytes: s.scrollback.length,
+      spilled_bytes: s.spill?.bytes ?? 0,
+    },
+  }));
+}
+
+export interface SessionSummary {
+  agent_id: string;
+  task_id: string;
+  project_id: string | null;
+  /** The agent definition it runs; null for terminals and custom commands. */
+  agent_def_id: string | null;
+  is_shell: boolean;
+  command: string;
+  /** `restarting` while a crashed agent waits to be spawned again. */
+  status: 'running' | 'restarting' | 'exited';
+  pid: number | null;
+  started_at: number;
+  last_output_at: number;
+  last_input_at: number;
+  exited_at: number | null;
+  exit_code: number | null;
+  signal: string | null;
+  /** Scrollback held in memory, plus what spilled to disk. */
+  buffered_bytes: number;
+  spilled_bytes: number;
+}
+
+/** Exited sessions kept for `listSessions`, so a reloaded window can tell what ended. */
+const MAX_EXITED_SESSIONS = 50;
+const exitedSessions = new Map<string, SessionSummary>();
+
+function sessionSummary(s: PtySession): SessionSummary {
+  return {
+    agent_id: s.agentId,
+    task_id: s.taskId,
+    project_id: s.projectId,
+    agent_def_id: s.agentDefId,
+    is_shell: s.isShell,
+    command: s.command,
+    status: 'running',
+    pid: s.proc.pid,
+    started_at: s.startedAt,
+    last_output_at: s.lastOutputAt,
+    last_input_at: s.lastInputAt,
+    exited_at: null,
+    exit_code: null,
+    signal: null,
+    buffered_bytes: s.scrollback.length,
+    spilled_bytes: s.spill?.bytes ?? 0,
+  };
+}
+
+function rememberExitedSession(s: PtySession, exitCode: number, signal: number | null): void {
+  exitedSessions.delete(s.agentId);
+  exitedSessions.set(s.agentId, {
+    ...sessionSummary(s),
+    status: 'exited',
+    pid: null,
+    exited_at: Date.now(),
+    exit_code: exitCode,
+    signal: signal !== null ? String(signal) : null,
+  });
+  if (exitedSessions.size > MAX_EXITED_SESSIONS) {
+    const [oldest] = exitedSessions.keys();
+    exitedSessions.delete(oldest);
+  }
+}
+
+/**
+ * Every session the main process knows of, running ones first, optionally
+ * only `taskId`'s or `projectId`'s. Includes recently exited sessions with
+ * their exit status.
+ */
+export function listSessions(
+  filter: { taskId?: string; projectId?: string } = {},
+): SessionSummary[] {
+  const { taskId, projectId } = filter;
+  const running = [...sessions.values()].map(sessionSummary);
+  const exited = [...exitedSessions.values()]
+    .map((s) => (pendingRestarts.has(s.agent_id) ? { ...s, status: 'restarting' as const } : s))
+    .reverse();
+  return [...running, ...exited].filter(
+    (s) =>
+      (taskId === undefined || s.task_id === taskId) &&
+      (projectId === undefined || s.project_id === projectId),
+  );
+}
+
 /** Return the current column width of an agent's PTY. */
 export function getAgentCols(agentId: string): number {
   const s = sessions.get(agentId);
@@ -0,0 +1,49 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import {
+  formatAsciicastEvent,
+  formatAsciicastHeader,
+  listRecordings,
+  parseRecordingName,
+  exportRecording,
+} from './recordings.js';
+
+describe('asciicast formatting', () => {
+  it('writes one JSON document per line', () => {
+    const header = formatAsciicastHeader({ version: 2, width: 80, height: 24, timestamp: 1 });
+    expect(header).toBe('{"version":2,"width":80,"height":24,"timestamp":1}\n');
+    expect(formatAsciicastEvent([1.25, 'o', 'hi\r\n'])).toBe('[1.25,"o","hi\\r\\n"]\n');
+  });
+
+  it('rounds timings to microseconds', () => {
+    expect(JSON.parse(formatAsciicastEvent([0.1234567891, 'r', '100x30']))).toEqual([
+      0.123457,
+      'r',
+      '100x30',
+    ]);
+  });
+});
+
+describe('parseRecordingName', () => {
+  it('splits agent id and start time', () => {
+    expect(parseRecordingName('agent-1-a2-1700000000000.cast')).toEqual({
+      agentId: 'agent-1-a2',
+      startedAt: 1700000000000,
+    });
+    expect(parseRecordingName('notes.txt')).toBeNull();
+  });
+});
+
+describe('recording lookup', () => {
+  it('lists nothing for a task without recordings', () => {
+    expect(listRecordings('task-1')).toEqual([]);
+  });
+
+  it('rejects ids that escape the task folder', () => {
+    expect(() => exportRecording('task-1', '../x-1.cast', '/tmp')).toThrow('Invalid recording id');
+  });
+});
@@ -0,0 +1,152 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('recordings');
+
+const RECORDING_EXT = '.cast';
+
+/** Header line of an asciicast v2 file. */
+export interface AsciicastHeader {
+  version: 2;
+  width: number;
+  height: number;
+  /** Unix seconds. */
+  timestamp: number;
+  title?: string;
+  env?: Record<string, string>;
+}
+
+/** `[seconds since start, "o" | "i" | "r", data]` */
+export type AsciicastEvent = [number, 'o' | 'i' | 'r', string];
+
+export interface RecordingInfo {
+  /** File name, unique within the task. */
+  id: string;
+  task_id: string;
+  agent_id: string;
+  started_at: number;
+  size_bytes: number;
+}
+
+export interface SessionRecorder {
+  output(data: string): void;
+  resize(cols: number, rows: number): void;
+  close(): void;
+}
+
+function safeSegment(id: string): string {
+  return id.replace(/[^\w.-]+/g, '-');
+}
+
+function recordingsDir(taskId: string): string {
+  return path.join(getStateDir(), 'recordings', safeSegment(taskId));
+}
+
+export function formatAsciicastHeader(header: AsciicastHeader): string {
+  return JSON.stringify(header) + '\n';
+}
+
+export function formatAsciicastEvent(event: AsciicastEvent): string {
+  return JSON.stringify([Math.round(event[0] * 1e6) / 1e6, event[1], event[2]]) + '\n';
+}
+
+/**
+ * Record a PTY session to `recordings/<task>/<agent>-<started>.cast` in the
+ * state dir. Write failures are logged and stop the recording, never the session.
+ */
+export function startRecording(opts: {
+  taskId: string;
+  agentId: string;
+  cols: number;
+  rows: number;
+  title: string;
+}): SessionRecorder {
+  const startedAt = Date.now();
+  const dir = recordingsDir(opts.taskId);
+  fs.mkdirSync(dir, { recursive: true });
+  const file = path.join(dir, `${safeSegment(opts.agentId)}-${startedAt}${RECORDING_EXT}`);
+  const stream = fs.createWriteStream(file, { flags: 'wx', mode: 0o600 });
+  let open = true;
+  stream.on('error', (err) => {
+    open = false;
+    log.warn(`Recording ${file} stopped`, err);
+  });
+
+  const write = (line: string) => {
+    if (open) stream.write(line);
+  };
+  const elapsed = () => (Date.now() - startedAt) / 1000;
+
+  write(
+    formatAsciicastHeader({
+      version: 2,
+      width: opts.cols,
+      height: opts.rows,
+      timestamp: Math.floor(startedAt / 1000),
+      title: opts.title,
+      env: { TERM: 'xterm-256color' },
+    }),
+  );
+  return {
+    output: (data) => write(formatAsciicastEvent([elapsed(), 'o', data])),
+    resize: (cols, rows) => write(formatAsciicastEvent([elapsed(), 'r', `${cols}x${rows}`])),
+    close: () => {
+      if (!open) return;
+      open = false;
+      stream.end();
+    },
+  };
+}
+
+/** `<agent>-<started>.cast` back into its parts. */
+export function parseRecordingName(name: string): { agentId: string; startedAt: number } | null {
+  const match = /^(.+)-(\d+)\.cast$/.exec(name);
+  return match ? { agentId: match[1], startedAt: Number(match[2]) } : null;
+}
+
+/** A task's recordings, newest first. */
+export function listRecordings(taskId: string): RecordingInfo[] {
+  const dir = recordingsDir(taskId);
+  let names: string[];
+  try {
+    names = fs.readdirSync(dir);
+  } catch {
+    return [];
+  }
+  const recordings: RecordingInfo[] = [];
+  for (const name of names) {
+    const parsed = parseRecordingName(name);
+    if (!parsed) continue;
+    try {
+      recordings.push({
+        id: name,
+        task_id: taskId,
+        agent_id: parsed.agentId,
+        started_at: parsed.startedAt,
+        size_bytes: fs.statSync(path.join(dir, name)).size,
+      });
+    } catch {
+      // Deleted while listing
+    }
+  }
+  return recordings.sort((a, b) => b.started_at - a.started_at);
+}
+
+/** Copy a recording to `outDir`. Returns the written path. */
+export function exportRecording(taskId: string, recordingId: string, outDir: string): string {
+  if (!parseRecordingName(recordingId) || path.basename(recordingId) !== recordingId) {
+    throw new Error('Invalid recording id');
+  }
+  const source = path.join(recordingsDir(taskId), recordingId);
+  if (!fs.existsSync(source)) throw new Error('Recording not found');
+  const dest = path.join(outDir, recordingId);
+  fs.copyFileSync(source, dest, fs.constants.COPYFILE_EXCL);
+  return dest;
+}
+
+/** Remove a task's recordings, e.g. when the task is deleted. */
+export function deleteRecordings(taskId: string): void {
+  fs.rmSync(recordingsDir(taskId), { recursive: true, force: true });
+}
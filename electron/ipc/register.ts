@@ -1,4 +1,4 @@
-import { ipcMain, dialog, shell, app, BrowserWindow } from 'electron';
+import { ipcMain, dialog, shell, app, BrowserWindow, clipboard } from 'electron';
 import { randomUUID } from 'crypto';
 import fs from 'fs';
 import { fileURLToPath } from 'url';
@@ -6,6 +6,16 @@ import { IPC } from './channels.js';
 import {
   spawnAgent,
   writeToAgent,
+  pasteToAgent,
+  searchAgentScrollback,
+  getSessionInfo,
+  listSessions,
+  getAgentEvents,
+  respondToAgent,
+  sendToAgent,
+  sendToTaskAgents,
+  sendToProjectAgents,
+  killProjectAgents,
   resizeAgent,
   pauseAgent,
   resumeAgent,
@@ -13,33 +23,160 @@ import {
   countRunningAgents,
   killAllAgents,
   getAgentMeta,
+  getActiveAgentIds,
 } from './pty.js';
+import { listOrphanedSessions, resolveOrphanedSessions } from './orphans.js';
+import { readProjectConfig } from './project-config.js';
+import { pnpmStoreDir, shareNodeModules } from './shared-caches.js';
 import { ensurePlansDirectory, startPlanWatcher } from './plans.js';
+import {
+  refreshTaskGitStatus,
+  setWatchedTasks,
+  startGitStatusPoller,
+} from './git-status-poller.js';
+import { unwatchWorktree, watchWorktree } from './worktree-watcher.js';
 import { startRemoteServer } from '../remote/server.js';
+import {
+  getAutomationToken,
+  regenerateAutomationToken,
+  resolveAutomationRequest,
+  startAutomationServer,
+  stopAutomationServer,
+} from '../remote/automation-server.js';
 import {
   getGitIgnoredDirs,
   getMainBranch,
+  findMergedBranches,
   getCurrentBranch,
+  getProjectInfo,
   getChangedFiles,
   getChangedFilesFromBranch,
   getFileDiff,
   getFileDiffFromBranch,
+  getStructuredFileDiff,
+  revertFiles,
+  revertHunks,
+  diffTaskBranches,
+  getFileDiffBetweenBranches,
+  cherryPickCommits,
+  stashTask,
+  stashPopTask,
+  listStashes,
+  createSnapshot,
+  listSnapshots,
+  restoreSnapshot,
+  fetchProject,
+  updateBaseBranch,
   getWorktreeStatus,
   commitAll,
   discardUncommitted,
   checkMergeStatus,
   mergeTask,
   getBranchLog,
+  getBranchTracking,
+  getTaskLog,
+  getBlame,
   pushTask,
   rebaseTask,
+  getConflicts,
+  resolveConflict,
+  continueMerge,
+  abortMerge,
   createWorktree,
   removeWorktree,
+  widenSparseCheckout,
+  assertBranchDeletable,
+  getBranchTip,
 } from './git.js';
-import { createTask, deleteTask } from './tasks.js';
+import {
+  createTask,
+  createTaskFromBranch,
+  deleteTask,
+  createRace,
+  archiveTask,
+  restoreTask,
+  renameTask,
+} from './tasks.js';
 import { listAgents } from './agents.js';
 import { listClaudeCommands } from './claude-commands.js';
-import { saveAppState, loadAppState } from './persistence.js';
-import { spawn } from 'child_process';
+import { saveAppState, loadAppState, getStateDir } from './persistence.js';
+import { flushUsage, getTaskUsage } from './usage.js';
+import { getTaskTime, setTaskTimer } from './task-time.js';
+import {
+  getSettings,
+  updateSettings,
+  onSettingsChanged,
+  NOTIFICATION_KINDS,
+} from './settings.js';
+import { listRecentProjects, openProject } from './projects.js';
+import { isSshUri, remoteTargetFor } from './ssh.js';
+import { getTaskEvents, isTaskEventType, recordTaskEvent } from './task-events.js';
+import { exportTask, importTask } from './bundles.js';
+import { deleteRecordings, exportRecording, listRecordings } from './recordings.js';
+import { deleteAgentSessions, getAgentResumeArgs } from './agent-sessions.js';
+import { getHandoffContext } from './handoff.js';
+import { openInEditor } from './editor.js';
+import {
+  assertChecksPassed,
+  cancelTaskTests,
+  getCheckResults,
+  runTaskChecks,
+  runTaskTests,
+} from './checks.js';
+import { ensureTaskPorts, releaseTaskPorts } from './ports.js';
+import { getRemoteProvider } from './remotes.js';
+import { initDeepLinks, takePendingDeepLinks } from './deep-link.js';
+import { emitWebhookEvent, startWebhooks } from './webhooks.js';
+import { attachFilesToSession, clipboardImageFrom, pasteImageToSession } from './images.js';
+import { refreshTaskPrStatus, setWatchedPrTasks, startPrPoller } from './pr-poller.js';
+import {
+  cancelRun,
+  cancelTaskRuns,
+  enqueueRun,
+  listRuns,
+  startScheduler,
+  stopScheduler,
+} from './scheduler.js';
+import {
+  enqueueMerge,
+  listMergeQueue,
+  removeFromMergeQueue,
+  removeTaskFromMergeQueue,
+  resumeMergeQueue,
+  startMergeQueue,
+} from './merge-queue.js';
+import { listTrash, recordDeletedTask, restoreDeletedTask } from './trash.js';
+import {
+  deleteSecret,
+  hasSecret,
+  listSecrets,
+  setSecret,
+  setSecretInjected,
+} from './secrets.js';
+import {
+  checkpointHistoryDatabase,
+  closeHistoryDatabase,
+  getMetrics,
+  indexTasks,
+  queryHistory,
+  searchTranscripts,
+  startHistoryIndex,
+} from './storage.js';
+import { backupFileName, exportAppData, importAppData } from './app-data.js';
+import { getDiskUsage } from './disk-usage.js';
+import { gcWorkspace } from './workspace-gc.js';
+import { cancelDiff, loadMoreDiff, readDiffChunks, startDiff } from './diff-stream.js';
+import { getResourceUsage } from './resource-usage.js';
+import { metricsRange } from './metrics.js';
+import { cancelGitOperation, parseGitProgress, runGitOperation } from './git-operations.js';
+import {
+  clearNotifications,
+  initNotifications,
+  listNotifications,
+  markNotificationsRead,
+  notify,
+  withSlowOperationNotice,
+} from './notifications.js';
 import path from 'path';
 import {
   assertString,
@@ -48,7 +185,22 @@ import {
   assertStringArray,
   assertOptionalString,
   assertOptionalBoolean,
+  validateBranchName,
 } from './validate.js';
+import { createLogger, getRecentLogs, isLogLevel } from './log.js';
+import { AppError, encodeForIpc } from './errors.js';
+
+const log = createLogger('ipc');
+
+/** `ipcMain.handle`, with AppErrors encoded so the renderer can read their code. */
+const handle: typeof ipcMain.handle = (channel, listener) =>
+  ipcMain.handle(channel, async (event, ...args) => {
+    try {
+      return await listener(event, ...args);
+    } catch (err) {
+      throw encodeForIpc(err);
+    }
+  });
 
 /** Reject paths that are non-absolute or attempt directory traversal. */
 function validatePath(p: unknown, label: string): void {
@@ -64,10 +216,11 @@ function validateRelativePath(p: unknown, label: string): void {
   if (p.includes('..')) throw new Error(`${label} must not contain ".."`);
 }
 
-/** Reject branch names that could be misinterpreted as git flags. */
-function validateBranchName(name: unknown, label: string): void {
-  if (typeof name !== 'string' || !name) throw new Error(`${label} must be a non-empty string`);
-  if (name.startsWith('-')) throw new Error(`${label} must not start with "-"`);
+/** Optional sparse-checkout directories, each relative to the worktree. */
+function validateSparsePaths(paths: unknown, label: string): void {
+  if (paths === undefined) return;
+  assertStringArray(paths, label);
+  for (const p of paths) validateRelativePath(p, label);
 }
 
 function validatePort(port: unknown, label: string): void {
@@ -79,37 +232,47 @@ function validatePort(port: unknown, label: string): void {
   }
 }
 
-const MAX_CLIPBOARD_IMAGE_BYTES = 15 * 1024 * 1024;
-
-function extensionForImageMime(mimeType: string): string | null {
-  const normalized = mimeType.trim().toLowerCase();
-  switch (normalized) {
-    case 'image/png':
-      return 'png';
-    case 'image/jpeg':
-      return 'jpg';
-    case 'image/webp':
-      return 'webp';
-    case 'image/gif':
-      return 'gif';
-    default:
-      return null;
-  }
-}
-
 export function registerAllHandlers(win: BrowserWindow): void {
   // --- Remote access state ---
   let remoteServer: ReturnType<typeof startRemoteServer> | null = null;
   const taskNames = new Map<string, string>();
+  startWebhooks((taskId) => taskNames.get(taskId) ?? null);
+  void startHistoryIndex();
+
+  /**
+   * Run `fn` as a cancellable git operation whose progress lines reach the
+   * renderer as `GitProgress` events. Callers may pick the id (`args.opId`)
+   * so they can cancel before the first event arrives.
+   */
+  const gitOperation = <T>(
+    opId: unknown,
+    projectRoot: string,
+    operation: string,
+    fn: () => Promise<T>,
+  ): Promise<T> => {
+    assertOptionalString(opId, 'opId');
+    const id = opId ?? randomUUID();
+    return runGitOperation(
+      id,
+      (line) => {
+        if (win.isDestroyed()) return;
+        const progress = parseGitProgress(line);
+        win.webContents.send(IPC.GitProgress, { opId: id, projectRoot, operation, line, progress });
+      },
+      fn,
+    );
+  };
 
   // --- PTY commands ---
-  ipcMain.handle(IPC.SpawnAgent, (_e, args) => {
+  handle(IPC.SpawnAgent, (_e, args) => {
     if (args.cwd) validatePath(args.cwd, 'cwd');
+    assertOptionalString(args.projectId, 'projectId');
+    if (args.secrets !== undefined) assertStringArray(args.secrets, 'secrets');
     if (!args.isShell && args.cwd) {
       try {
         ensurePlansDirectory(args.cwd);
       } catch (err) {
-        console.warn('Failed to set up plans directory:', err);
+        log.warn('Failed to set up plans directory:', err);
       }
     }
     const result = spawnAgent(win, args);
@@ -117,159 +280,785 @@ export function registerAllHandlers(win: BrowserWindow): void {
       try {
         startPlanWatcher(win, args.taskId, args.cwd);
       } catch (err) {
-        console.warn('Failed to start plan watcher:', err);
+        log.warn('Failed to start plan watcher:', err);
       }
     }
     return result;
   });
-  ipcMain.handle(IPC.WriteToAgent, (_e, args) => {
+  handle(IPC.WriteToAgent, (_e, args) => {
     assertString(args.agentId, 'agentId');
     assertString(args.data, 'data');
+    if (args.paste !== undefined) assertBoolean(args.paste, 'paste');
+    if (args.paste) return pasteToAgent(args.agentId, args.data);
     return writeToAgent(args.agentId, args.data);
   });
-  ipcMain.handle(IPC.ResizeAgent, (_e, args) => {
+  handle(IPC.RespondToAgent, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    assertBoolean(args.approve, 'approve');
+    respondToAgent(win, args.agentId, args.approve);
+  });
+  handle(IPC.GetAgentResumeArgs, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    assertString(args.agentDefId, 'agentDefId');
+    return getAgentResumeArgs(args.taskId, args.agentDefId);
+  });
+  handle(IPC.GetHandoffContext, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    validatePath(args.worktreePath, 'worktreePath');
+    return getHandoffContext(args.agentId, args.worktreePath);
+  });
+  handle(IPC.GetSessionInfo, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    return getSessionInfo(args.agentId);
+  });
+  handle(IPC.ListSessions, (_e, args) => {
+    assertOptionalString(args.taskId, 'taskId');
+    assertOptionalString(args.projectId, 'projectId');
+    return listSessions({ taskId: args.taskId, projectId: args.projectId });
+  });
+  handle(IPC.GetAgentEvents, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    return getAgentEvents(args.agentId);
+  });
+  handle(IPC.SearchSessionBuffer, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    assertString(args.query, 'query');
+    assertBoolean(args.regex, 'regex');
+    if (args.caseSensitive !== undefined) assertBoolean(args.caseSensitive, 'caseSensitive');
+    return searchAgentScrollback(args.agentId, args.query, {
+      regex: args.regex,
+      caseSensitive: args.caseSensitive ?? false,
+    });
+  });
+  handle(IPC.SendToAgent, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    assertString(args.text, 'text');
+    assertBoolean(args.submit, 'submit');
+    return sendToAgent(args.agentId, args.text, args.submit);
+  });
+  handle(IPC.SendToTaskAgents, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    assertString(args.text, 'text');
+    assertBoolean(args.submit, 'submit');
+    return sendToTaskAgents(args.taskId, args.text, args.submit);
+  });
+  handle(IPC.SendToProjectAgents, (_e, args) => {
+    assertString(args.projectId, 'projectId');
+    assertString(args.text, 'text');
+    assertBoolean(args.submit, 'submit');
+    return sendToProjectAgents(args.projectId, args.text, args.submit);
+  });
+  handle(IPC.ResizeAgent, (_e, args) => {
     assertString(args.agentId, 'agentId');
     assertInt(args.cols, 'cols');
     assertInt(args.rows, 'rows');
     return resizeAgent(args.agentId, args.cols, args.rows);
   });
-  ipcMain.handle(IPC.PauseAgent, (_e, args) => {
+  handle(IPC.PauseAgent, (_e, args) => {
     assertString(args.agentId, 'agentId');
     return pauseAgent(args.agentId);
   });
-  ipcMain.handle(IPC.ResumeAgent, (_e, args) => {
+  handle(IPC.ResumeAgent, (_e, args) => {
     assertString(args.agentId, 'agentId');
     return resumeAgent(args.agentId);
   });
-  ipcMain.handle(IPC.KillAgent, (_e, args) => {
+  handle(IPC.KillAgent, (_e, args) => {
     assertString(args.agentId, 'agentId');
     return killAgent(args.agentId);
   });
-  ipcMain.handle(IPC.CountRunningAgents, () => countRunningAgents());
-  ipcMain.handle(IPC.KillAllAgents, () => killAllAgents());
+  handle(IPC.CountRunningAgents, () => countRunningAgents());
+  handle(IPC.KillAllAgents, () => killAllAgents());
+  handle(IPC.KillProjectAgents, (_e, args) => {
+    assertString(args.projectId, 'projectId');
+    return killProjectAgents(args.projectId);
+  });
+  handle(IPC.ListOrphanedSessions, () => listOrphanedSessions());
+  handle(IPC.ResolveOrphanedSessions, (_e, args) => {
+    assertBoolean(args.kill, 'kill');
+    return resolveOrphanedSessions(args.kill);
+  });
 
   // --- Agent commands ---
-  ipcMain.handle(IPC.ListAgents, () => listAgents());
-  ipcMain.handle(IPC.ListClaudeCommands, () => listClaudeCommands());
+  handle(IPC.ListAgents, () => listAgents());
+  handle(IPC.ListClaudeCommands, () => listClaudeCommands());
+  handle(IPC.GetTaskUsage, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return getTaskUsage(args.taskId);
+  });
+  handle(IPC.GetTaskTimes, (_e, args) => {
+    assertStringArray(args.taskIds, 'taskIds');
+    const running = getActiveAgentIds();
+    return args.taskIds.map((taskId: string) => getTaskTime(taskId, running));
+  });
+  handle(IPC.SetTaskTimer, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    assertBoolean(args.running, 'running');
+    return setTaskTimer(args.taskId, args.running, getActiveAgentIds());
+  });
 
   // --- Task commands ---
-  ipcMain.handle(IPC.CreateTask, (_e, args) => {
+  handle(IPC.CreateTask, (_e, args) => {
     assertString(args.name, 'name');
     validatePath(args.projectRoot, 'projectRoot');
     assertStringArray(args.symlinkDirs, 'symlinkDirs');
     assertOptionalString(args.branchPrefix, 'branchPrefix');
-    const result = createTask(args.name, args.projectRoot, args.symlinkDirs, args.branchPrefix);
+    if (args.baseBranch !== undefined) validateBranchName(args.baseBranch, 'baseBranch');
+    validateSparsePaths(args.sparsePaths, 'sparsePaths');
+    if (args.scope !== undefined) validateRelativePath(args.scope, 'scope');
+    const result = gitOperation(args.opId, args.projectRoot, 'create_task', () =>
+      createTask(
+        args.name,
+        args.projectRoot,
+        args.symlinkDirs,
+        args.branchPrefix,
+        args.baseBranch,
+        args.sparsePaths,
+        args.scope,
+      ),
+    );
     result.then((r: { id: string }) => taskNames.set(r.id, args.name)).catch(() => {});
     return result;
   });
-  ipcMain.handle(IPC.DeleteTask, (_e, args) => {
+  handle(IPC.CreateTaskFromBranch, (_e, args) => {
+    assertString(args.name, 'name');
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    assertStringArray(args.symlinkDirs, 'symlinkDirs');
+    validateSparsePaths(args.sparsePaths, 'sparsePaths');
+    if (args.scope !== undefined) validateRelativePath(args.scope, 'scope');
+    const result = gitOperation(args.opId, args.projectRoot, 'create_task', () =>
+      createTaskFromBranch(
+        args.name,
+        args.projectRoot,
+        args.branchName,
+        args.symlinkDirs,
+        args.sparsePaths,
+        args.scope,
+      ),
+    );
+    result.then((r: { id: string }) => taskNames.set(r.id, args.name)).catch(() => {});
+    return result;
+  });
+  handle(IPC.CreateRace, (_e, args) => {
+    assertString(args.name, 'name');
+    validatePath(args.projectRoot, 'projectRoot');
+    assertStringArray(args.agentDefIds, 'agentDefIds');
+    assertStringArray(args.symlinkDirs, 'symlinkDirs');
+    assertOptionalString(args.branchPrefix, 'branchPrefix');
+    validateSparsePaths(args.sparsePaths, 'sparsePaths');
+    const result = gitOperation(args.opId, args.projectRoot, 'create_race', () =>
+      createRace(
+        args.name,
+        args.projectRoot,
+        args.agentDefIds,
+        args.symlinkDirs,
+        args.branchPrefix ?? '',
+        args.sparsePaths,
+      ),
+    );
+    result
+      .then((r) => r.entries.forEach((entry) => taskNames.set(entry.id, args.name)))
+      .catch(() => {});
+    return result;
+  });
+  handle(IPC.DeleteTask, async (_e, args) => {
     assertStringArray(args.agentIds, 'agentIds');
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     assertBoolean(args.deleteBranch, 'deleteBranch');
-    return deleteTask(args.agentIds, args.branchName, args.deleteBranch, args.projectRoot);
+    assertOptionalString(args.taskId, 'taskId');
+    if (args.baseBranch !== undefined) validateBranchName(args.baseBranch, 'baseBranch');
+    if (args.force !== undefined) assertBoolean(args.force, 'force');
+    if (args.metadata !== undefined && (typeof args.metadata !== 'object' || !args.metadata)) {
+      throw new Error('metadata must be an object');
+    }
+    // Before anything is torn down, so a refusal leaves the task as it was
+    if (args.deleteBranch && !args.force) {
+      await assertBranchDeletable(args.projectRoot, args.branchName, args.baseBranch);
+    }
+    const branchTip = args.deleteBranch
+      ? await getBranchTip(args.projectRoot, args.branchName)
+      : null;
+    // Headless runs in the worktree would fail once it's gone
+    if (args.taskId) {
+      cancelTaskRuns(args.taskId);
+      removeTaskFromMergeQueue(args.taskId);
+    }
+    if (args.taskId && args.metadata) {
+      await recordDeletedTask({
+        taskId: args.taskId,
+        projectRoot: args.projectRoot,
+        branchName: args.branchName,
+        branchDeleted: args.deleteBranch,
+        metadata: args.metadata,
+      });
+    }
+    await deleteTask(args.agentIds, args.branchName, args.deleteBranch, args.projectRoot);
+    if (args.taskId) {
+      releaseTaskPorts(args.taskId);
+      deleteRecordings(args.taskId);
+      deleteAgentSessions(args.taskId);
+    }
+    return { branchTip };
+  });
+  handle(IPC.CheckBranchDeletion, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    if (args.baseBranch !== undefined) validateBranchName(args.baseBranch, 'baseBranch');
+    return assertBranchDeletable(args.projectRoot, args.branchName, args.baseBranch);
+  });
+  handle(IPC.ArchiveTask, (_e, args) => {
+    assertStringArray(args.agentIds, 'agentIds');
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    return archiveTask(args.agentIds, args.branchName, args.projectRoot);
+  });
+  handle(IPC.RestoreTask, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    assertStringArray(args.symlinkDirs, 'symlinkDirs');
+    validateSparsePaths(args.sparsePaths, 'sparsePaths');
+    return gitOperation(args.opId, args.projectRoot, 'restore_task', () =>
+      restoreTask(args.projectRoot, args.branchName, args.symlinkDirs, args.sparsePaths),
+    );
+  });
+  handle(IPC.ListTrash, () => listTrash());
+  handle(IPC.RestoreDeletedTask, (_e, args) => {
+    assertString(args.entryId, 'entryId');
+    assertStringArray(args.symlinkDirs, 'symlinkDirs');
+    return restoreDeletedTask(args.entryId, args.symlinkDirs);
+  });
+  handle(IPC.GetDiskUsage, (_e, args) => {
+    assertStringArray(args.projectRoots, 'projectRoots');
+    for (const root of args.projectRoots) validatePath(root, 'projectRoots');
+    assertOptionalBoolean(args.refresh, 'refresh');
+    return getDiskUsage(args.projectRoots, args.refresh);
+  });
+  handle(IPC.GcWorkspace, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    assertStringArray(args.branchNames, 'branchNames');
+    assertStringArray(args.worktreePaths, 'worktreePaths');
+    assertString(args.branchPrefix, 'branchPrefix');
+    assertBoolean(args.dryRun, 'dryRun');
+    const confirmed: unknown = args.confirmedBranches ?? {};
+    if (
+      typeof confirmed !== 'object' ||
+      confirmed === null ||
+      !Object.values(confirmed).every((tip) => typeof tip === 'string')
+    ) {
+      throw new Error('confirmedBranches must map branch names to tips');
+    }
+    const confirmedWorktrees: unknown = args.confirmedWorktrees ?? [];
+    assertStringArray(confirmedWorktrees, 'confirmedWorktrees');
+    assertOptionalBoolean(args.force, 'force');
+    return gcWorkspace(
+      args.projectRoot,
+      {
+        branches: args.branchNames,
+        worktreePaths: args.worktreePaths,
+        branchPrefix: args.branchPrefix,
+      },
+      args.dryRun,
+      {
+        worktrees: confirmedWorktrees,
+        branches: confirmed as Record<string, string>,
+        force: args.force ?? false,
+      },
+    );
+  });
+  handle(IPC.GetResourceUsage, () => getResourceUsage());
+  handle(IPC.ShareNodeModules, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validatePath(args.worktreePath, 'worktreePath');
+    return shareNodeModules(args.projectRoot, args.worktreePath);
+  });
+  handle(IPC.GetPnpmStoreDir, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    return pnpmStoreDir(args.projectRoot);
+  });
+  handle(IPC.RenameTask, (_e, args) => {
+    assertStringArray(args.agentIds, 'agentIds');
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    assertString(args.newName, 'newName');
+    return renameTask(args.agentIds, args.projectRoot, args.branchName, args.newName);
+  });
+  handle(IPC.RecordTaskEvent, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    if (!isTaskEventType(args.type)) throw new Error('type must be a known task event type');
+    if (args.data !== undefined && (typeof args.data !== 'object' || args.data === null)) {
+      throw new Error('data must be an object');
+    }
+    recordTaskEvent(args.taskId, args.type, args.data);
+  });
+  handle(IPC.GetTaskEvents, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return getTaskEvents(args.taskId);
+  });
+  handle(IPC.QueryHistory, (_e, args) => {
+    assertOptionalString(args.taskId, 'taskId');
+    assertOptionalString(args.projectId, 'projectId');
+    if (args.types !== undefined) {
+      assertStringArray(args.types, 'types');
+      if (!args.types.every(isTaskEventType)) throw new Error('types must be task event types');
+    }
+    for (const key of ['since', 'until', 'limit'] as const) {
+      if (args[key] !== undefined && typeof args[key] !== 'number') {
+        throw new Error(`${key} must be a number`);
+      }
+    }
+    return queryHistory({
+      taskId: args.taskId,
+      projectId: args.projectId,
+      types: args.types,
+      since: args.since,
+      until: args.until,
+      limit: args.limit,
+    });
+  });
+  handle(IPC.GetMetrics, (_e, args) => {
+    assertOptionalString(args.projectId, 'projectId');
+    for (const key of ['since', 'until'] as const) {
+      if (args[key] !== undefined && typeof args[key] !== 'number') {
+        throw new Error(`${key} must be a number`);
+      }
+    }
+    const range = metricsRange({ since: args.since, until: args.until, projectId: args.projectId });
+    return getMetrics(range, getActiveAgentIds());
+  });
+  handle(IPC.SearchTranscripts, (_e, args) => {
+    assertString(args.query, 'query');
+    assertOptionalString(args.taskId, 'taskId');
+    if (args.limit !== undefined && typeof args.limit !== 'number') {
+      throw new Error('limit must be a number');
+    }
+    return searchTranscripts(args.query, { taskId: args.taskId, limit: args.limit });
+  });
+  handle(IPC.ExportTask, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    validatePath(args.worktreePath, 'worktreePath');
+    validateBranchName(args.branchName, 'branchName');
+    validatePath(args.outDir, 'outDir');
+    if (!args.metadata || typeof args.metadata !== 'object') {
+      throw new Error('metadata must be an object');
+    }
+    return exportTask(args.taskId, args.worktreePath, args.branchName, args.metadata, args.outDir);
+  });
+  handle(IPC.ImportTask, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validatePath(args.bundlePath, 'bundlePath');
+    assertStringArray(args.symlinkDirs, 'symlinkDirs');
+    return importTask(args.projectRoot, args.bundlePath, args.symlinkDirs);
+  });
+  handle(IPC.ListRecordings, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return listRecordings(args.taskId);
+  });
+  handle(IPC.ExportRecording, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    assertString(args.recordingId, 'recordingId');
+    validatePath(args.outDir, 'outDir');
+    return exportRecording(args.taskId, args.recordingId, args.outDir);
   });
 
   // --- Git commands ---
-  ipcMain.handle(IPC.GetChangedFiles, (_e, args) => {
+  handle(IPC.GetChangedFiles, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
-    return getChangedFiles(args.worktreePath);
+    if (args.scope !== undefined) validateRelativePath(args.scope, 'scope');
+    return getChangedFiles(args.worktreePath, args.scope);
   });
-  ipcMain.handle(IPC.GetChangedFilesFromBranch, (_e, args) => {
+  handle(IPC.GetChangedFilesFromBranch, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     return getChangedFilesFromBranch(args.projectRoot, args.branchName);
   });
-  ipcMain.handle(IPC.GetFileDiff, (_e, args) => {
+  handle(IPC.GetFileDiff, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
     validateRelativePath(args.filePath, 'filePath');
     return getFileDiff(args.worktreePath, args.filePath);
   });
-  ipcMain.handle(IPC.GetFileDiffFromBranch, (_e, args) => {
+  handle(IPC.GetStructuredFileDiff, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    validateRelativePath(args.filePath, 'filePath');
+    return getStructuredFileDiff(args.worktreePath, args.filePath);
+  });
+  handle(IPC.StartDiff, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    // Callers may pick the handle so they can listen before the first chunk arrives
+    assertOptionalString(args.handle, 'handle');
+    return startDiff(args.worktreePath, args.handle ?? randomUUID(), (chunk) => {
+      if (!win.isDestroyed()) win.webContents.send(IPC.DiffChunk, chunk);
+    });
+  });
+  handle(IPC.ReadDiffChunks, (_e, args) => {
+    assertString(args.handle, 'handle');
+    if (args.after !== undefined) assertInt(args.after, 'after');
+    return readDiffChunks(args.handle, args.after);
+  });
+  handle(IPC.LoadMoreDiff, (_e, args) => {
+    assertString(args.handle, 'handle');
+    validateRelativePath(args.filePath, 'filePath');
+    assertInt(args.from, 'from');
+    return loadMoreDiff(args.handle, args.filePath, args.from);
+  });
+  handle(IPC.CancelDiff, (_e, args) => {
+    assertString(args.handle, 'handle');
+    return cancelDiff(args.handle);
+  });
+  handle(IPC.RevertFiles, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertStringArray(args.filePaths, 'filePaths');
+    for (const p of args.filePaths) validateRelativePath(p, 'filePaths[]');
+    return revertFiles(args.worktreePath, args.filePaths);
+  });
+  handle(IPC.RevertHunks, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    validateRelativePath(args.filePath, 'filePath');
+    if (!Array.isArray(args.hunkIds)) throw new Error('hunkIds must be an array');
+    for (const id of args.hunkIds) assertInt(id, 'hunkIds[]');
+    return revertHunks(args.worktreePath, args.filePath, args.hunkIds);
+  });
+  handle(IPC.DiffTaskBranches, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchA, 'branchA');
+    validateBranchName(args.branchB, 'branchB');
+    return diffTaskBranches(args.projectRoot, args.branchA, args.branchB);
+  });
+  handle(IPC.GetFileDiffBetweenBranches, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchA, 'branchA');
+    validateBranchName(args.branchB, 'branchB');
+    validateRelativePath(args.filePath, 'filePath');
+    return getFileDiffBetweenBranches(
+      args.projectRoot,
+      args.branchA,
+      args.branchB,
+      args.filePath,
+    );
+  });
+  handle(IPC.CherryPick, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertStringArray(args.commits, 'commits');
+    if (args.commits.length === 0) throw new Error('commits must not be empty');
+    for (const c of args.commits) {
+      if (!/^[0-9a-f]{4,64}$/i.test(c)) throw new Error(`Invalid commit sha: ${c}`);
+    }
+    return cherryPickCommits(args.worktreePath, args.commits);
+  });
+  handle(IPC.StashTask, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.message, 'message');
+    return stashTask(args.worktreePath, args.message);
+  });
+  handle(IPC.StashPopTask, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    validateBranchName(args.branchName, 'branchName');
+    if (args.index !== undefined) assertInt(args.index, 'index');
+    return stashPopTask(args.worktreePath, args.branchName, args.index);
+  });
+  handle(IPC.ListStashes, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    validateBranchName(args.branchName, 'branchName');
+    return listStashes(args.worktreePath, args.branchName);
+  });
+  handle(IPC.CreateSnapshot, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.taskId, 'taskId');
+    assertOptionalString(args.label, 'label');
+    return createSnapshot(args.worktreePath, args.taskId, args.label ?? '');
+  });
+  handle(IPC.ListSnapshots, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.taskId, 'taskId');
+    return listSnapshots(args.worktreePath, args.taskId);
+  });
+  handle(IPC.RestoreSnapshot, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.taskId, 'taskId');
+    assertString(args.snapshotId, 'snapshotId');
+    return restoreSnapshot(args.worktreePath, args.taskId, args.snapshotId);
+  });
+  handle(IPC.GetFileDiffFromBranch, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     validateRelativePath(args.filePath, 'filePath');
     return getFileDiffFromBranch(args.projectRoot, args.branchName, args.filePath);
   });
-  ipcMain.handle(IPC.GetGitignoredDirs, (_e, args) => {
+  handle(IPC.GetGitignoredDirs, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     return getGitIgnoredDirs(args.projectRoot);
   });
-  ipcMain.handle(IPC.GetWorktreeStatus, (_e, args) => {
+  handle(IPC.GetWorktreeStatus, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
-    return getWorktreeStatus(args.worktreePath);
+    if (args.scope !== undefined) validateRelativePath(args.scope, 'scope');
+    assertOptionalBoolean(args.fresh, 'fresh');
+    return getWorktreeStatus(args.worktreePath, args.scope, args.fresh);
   });
-  ipcMain.handle(IPC.CommitAll, (_e, args) => {
+  handle(IPC.CommitAll, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
     assertString(args.message, 'message');
     return commitAll(args.worktreePath, args.message);
   });
-  ipcMain.handle(IPC.DiscardUncommitted, (_e, args) => {
+  handle(IPC.DiscardUncommitted, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
     return discardUncommitted(args.worktreePath);
   });
-  ipcMain.handle(IPC.CheckMergeStatus, (_e, args) => {
+  handle(IPC.CheckMergeStatus, async (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
-    return checkMergeStatus(args.worktreePath);
+    if (args.scope !== undefined) validateRelativePath(args.scope, 'scope');
+    const status = await checkMergeStatus(args.worktreePath, args.scope);
+    if (status.conflicting_files.length > 0) {
+      notify(
+        'merge_conflict',
+        'Merge conflicts detected',
+        `${path.basename(args.worktreePath)}: ${status.conflicting_files.join(', ')}`,
+      );
+    }
+    return status;
   });
-  ipcMain.handle(IPC.MergeTask, (_e, args) => {
+  handle(IPC.MergeTask, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     assertBoolean(args.squash, 'squash');
     assertOptionalString(args.message, 'message');
     assertOptionalBoolean(args.cleanup, 'cleanup');
-    return mergeTask(args.projectRoot, args.branchName, args.squash, args.message, args.cleanup);
+    if (args.requireChecks !== undefined) {
+      assertString(args.requireChecks.taskId, 'requireChecks.taskId');
+      validatePath(args.requireChecks.worktreePath, 'requireChecks.worktreePath');
+      assertStringArray(args.requireChecks.commands, 'requireChecks.commands');
+    }
+    const merge = async () => {
+      const gate = args.requireChecks;
+      if (gate) await assertChecksPassed(gate.taskId, gate.worktreePath, gate.commands);
+      return mergeTask(args.projectRoot, args.branchName, args.squash, args.message, args.cleanup);
+    };
+    return withSlowOperationNotice(`Merge of ${args.branchName}`, merge).catch((err: unknown) => {
+      if (err instanceof AppError && err.code === 'merge_conflict') {
+        const files = err.context.files?.join(', ') ?? '';
+        notify('merge_conflict', 'Merge failed with conflicts', `${args.branchName}: ${files}`);
+      }
+      throw err;
+    });
   });
-  ipcMain.handle(IPC.GetBranchLog, (_e, args) => {
+  handle(IPC.GetBranchLog, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
     return getBranchLog(args.worktreePath);
   });
-  ipcMain.handle(IPC.PushTask, (_e, args) => {
+  handle(IPC.GetBranchTracking, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    return getBranchTracking(args.worktreePath);
+  });
+  handle(IPC.GetTaskLog, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    if (args.limit !== undefined) assertInt(args.limit, 'limit');
+    if (args.offset !== undefined) assertInt(args.offset, 'offset');
+    if ((args.limit ?? 1) < 1 || (args.offset ?? 0) < 0) throw new Error('Invalid log page');
+    return getTaskLog(args.worktreePath, args.limit, args.offset);
+  });
+  handle(IPC.GetBlame, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    validateRelativePath(args.path, 'path');
+    return getBlame(args.worktreePath, args.path);
+  });
+  handle(IPC.PushTask, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
-    return pushTask(args.projectRoot, args.branchName);
+    return withSlowOperationNotice(`Push of ${args.branchName}`, () =>
+      pushTask(args.projectRoot, args.branchName),
+    );
+  });
+  startGitStatusPoller(win);
+  handle(IPC.SetWatchedTasks, (_e, args) => {
+    if (!Array.isArray(args.tasks)) throw new Error('tasks must be an array');
+    for (const t of args.tasks) {
+      assertString(t?.taskId, 'tasks[].taskId');
+      validatePath(t.worktreePath, 'tasks[].worktreePath');
+      if (t.scope !== undefined) validateRelativePath(t.scope, 'tasks[].scope');
+    }
+    assertOptionalString(args.activeTaskId, 'activeTaskId');
+    if (args.reset !== undefined) assertBoolean(args.reset, 'reset');
+    const tasks = args.tasks.map((t: { taskId: string; worktreePath: string; scope?: string }) => ({
+      taskId: t.taskId,
+      worktreePath: t.worktreePath,
+      scope: t.scope,
+    }));
+    setWatchedTasks(tasks, args.activeTaskId ?? null, args.reset ?? false);
+  });
+  handle(IPC.RefreshTaskGitStatus, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    refreshTaskGitStatus(args.taskId);
+  });
+  handle(IPC.WatchWorktree, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    watchWorktree(win, args.worktreePath);
+  });
+  handle(IPC.UnwatchWorktree, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    unwatchWorktree(args.worktreePath);
+  });
+  handle(IPC.FetchProject, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    assertOptionalString(args.remote, 'remote');
+    const remote = args.remote || 'origin';
+    validateBranchName(remote, 'remote');
+    return withSlowOperationNotice(`Fetch of ${remote}`, () =>
+      gitOperation(args.opId, args.projectRoot, 'fetch', () =>
+        fetchProject(args.projectRoot, remote),
+      ),
+    );
+  });
+  handle(IPC.UpdateBaseBranch, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    assertOptionalString(args.remote, 'remote');
+    const remote = args.remote || 'origin';
+    validateBranchName(remote, 'remote');
+    return withSlowOperationNotice('Base branch update', () =>
+      gitOperation(args.opId, args.projectRoot, 'update_base_branch', () =>
+        updateBaseBranch(args.projectRoot, remote),
+      ),
+    );
+  });
+  handle(IPC.WidenSparseCheckout, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertStringArray(args.paths, 'paths');
+    for (const p of args.paths) validateRelativePath(p, 'paths');
+    return widenSparseCheckout(args.worktreePath, args.paths);
+  });
+  handle(IPC.CancelOperation, (_e, args) => {
+    assertString(args.opId, 'opId');
+    return cancelGitOperation(args.opId);
   });
-  ipcMain.handle(IPC.RebaseTask, (_e, args) => {
+  handle(IPC.RebaseTask, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
-    return rebaseTask(args.worktreePath);
+    assertOptionalBoolean(args.keepConflicts, 'keepConflicts');
+    return rebaseTask(args.worktreePath, args.keepConflicts).catch((err: unknown) => {
+      if (err instanceof AppError && err.code === 'merge_conflict') {
+        const files = err.context.files?.join(', ') ?? '';
+        const name = path.basename(args.worktreePath);
+        notify('merge_conflict', 'Rebase failed with conflicts', `${name}: ${files}`);
+      }
+      throw err;
+    });
+  });
+  handle(IPC.GetConflicts, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    return getConflicts(args.worktreePath);
+  });
+  handle(IPC.ResolveConflict, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.path, 'path');
+    assertString(args.content, 'content');
+    return resolveConflict(args.worktreePath, args.path, args.content);
+  });
+  handle(IPC.ContinueMerge, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    return continueMerge(args.worktreePath);
+  });
+  handle(IPC.AbortMerge, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    return abortMerge(args.worktreePath);
+  });
+
+  // --- Logs ---
+  handle(IPC.GetRecentLogs, (_e, args) => {
+    if (args?.level !== undefined && !isLogLevel(args.level)) {
+      throw new Error(`Invalid log level: ${args.level}`);
+    }
+    if (args?.limit !== undefined) assertInt(args.limit, 'limit');
+    return getRecentLogs(args?.level, args?.limit);
+  });
+
+  // --- Notifications ---
+  initNotifications(win);
+  handle(IPC.ListNotifications, () => listNotifications());
+  handle(IPC.MarkNotificationsRead, (_e, args) => {
+    if (args?.ids !== undefined) assertStringArray(args.ids, 'ids');
+    markNotificationsRead(args?.ids);
   });
-  ipcMain.handle(IPC.GetMainBranch, (_e, args) => {
+  handle(IPC.ClearNotifications, () => clearNotifications());
+  handle(IPC.Notify, (_e, args) => {
+    if (!NOTIFICATION_KINDS.includes(args.kind)) throw new Error(`Invalid kind: ${args.kind}`);
+    assertString(args.title, 'title');
+    assertString(args.body, 'body');
+    assertOptionalString(args.taskId, 'taskId');
+    notify(args.kind, args.title, args.body, args.taskId);
+  });
+  handle(IPC.FindMergedBranches, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    assertStringArray(args.branchNames, 'branchNames');
+    for (const branch of args.branchNames) validateBranchName(branch, 'branchNames');
+    return findMergedBranches(args.projectRoot, args.branchNames);
+  });
+  handle(IPC.GetBranchTip, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    return getBranchTip(args.projectRoot, args.branchName);
+  });
+  handle(IPC.GetMainBranch, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     return getMainBranch(args.projectRoot);
   });
-  ipcMain.handle(IPC.GetCurrentBranch, (_e, args) => {
+  handle(IPC.GetCurrentBranch, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     return getCurrentBranch(args.projectRoot);
   });
+  handle(IPC.GetProjectInfo, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    return getProjectInfo(args.projectRoot);
+  });
+  handle(IPC.ReadProjectConfig, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    return readProjectConfig(args.projectRoot);
+  });
 
   // --- Persistence ---
   // Extract task names from persisted state so the remote server can
   // show them (taskNames is only populated on CreateTask otherwise).
   function syncTaskNamesFromJson(json: string): void {
     try {
-      const state = JSON.parse(json) as { tasks?: Record<string, { id: string; name: string }> };
+      const state = JSON.parse(json) as {
+        tasks?: Record<
+          string,
+          { id: string; name: string; projectId?: string; branchName?: string; createdAt?: number }
+        >;
+      };
       if (state.tasks) {
-        for (const t of Object.values(state.tasks)) {
-          if (t.id && t.name) taskNames.set(t.id, t.name);
-        }
+        const tasks = Object.values(state.tasks).filter((t) => t.id && t.name);
+        for (const t of tasks) taskNames.set(t.id, t.name);
+        indexTasks(tasks);
       }
     } catch (e) {
-      console.warn('Ignoring malformed saved state:', e);
+      log.warn('Ignoring malformed saved state:', e);
     }
   }
-  ipcMain.handle(IPC.SaveAppState, (_e, args) => {
+  handle(IPC.SaveAppState, (_e, args) => {
     assertString(args.json, 'json');
     syncTaskNamesFromJson(args.json);
     return saveAppState(args.json);
   });
-  ipcMain.handle(IPC.LoadAppState, () => {
+  handle(IPC.LoadAppState, () => {
     const json = loadAppState();
     if (json) syncTaskNamesFromJson(json);
     return json;
   });
 
+  // --- Projects ---
+  handle(IPC.ListRecentProjects, () => listRecentProjects());
+  handle(IPC.OpenProject, (_e, args) => {
+    // An ssh:// address is checked by parseSshUri when the project opens
+    if (typeof args.path !== 'string' || !isSshUri(args.path)) validatePath(args.path, 'path');
+    return openProject(args.path);
+  });
+
+  // --- Settings ---
+  handle(IPC.GetSettings, () => getSettings());
+  handle(IPC.UpdateSettings, (_e, args) => updateSettings(args.settings));
+  onSettingsChanged((settings) => {
+    if (!win.isDestroyed()) win.webContents.send(IPC.SettingsChanged, settings);
+  });
+
   // --- Arena persistence ---
-  ipcMain.handle(IPC.SaveArenaData, (_e, args) => {
+  handle(IPC.SaveArenaData, (_e, args) => {
     assertString(args.filename, 'filename');
     assertString(args.json, 'json');
     const filePath = path.join(app.getPath('userData'), args.filename);
@@ -282,7 +1071,7 @@ export function registerAllHandlers(win: BrowserWindow): void {
     fs.renameSync(tmpPath, filePath);
   });
 
-  ipcMain.handle(IPC.LoadArenaData, (_e, args) => {
+  handle(IPC.LoadArenaData, (_e, args) => {
     assertString(args.filename, 'filename');
     const filePath = path.join(app.getPath('userData'), args.filename);
     const basename = path.basename(filePath);
@@ -296,57 +1085,77 @@ export function registerAllHandlers(win: BrowserWindow): void {
     }
   });
 
-  ipcMain.handle(IPC.CreateArenaWorktree, (_e, args) => {
+  handle(IPC.CreateArenaWorktree, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     return createWorktree(args.projectRoot, args.branchName, args.symlinkDirs ?? [], true);
   });
 
-  ipcMain.handle(IPC.RemoveArenaWorktree, (_e, args) => {
+  handle(IPC.RemoveArenaWorktree, (_e, args) => {
     validatePath(args.projectRoot, 'projectRoot');
     validateBranchName(args.branchName, 'branchName');
     return removeWorktree(args.projectRoot, args.branchName, true);
   });
 
-  ipcMain.handle(IPC.CheckPathExists, (_e, args) => {
+  handle(IPC.CheckPathExists, (_e, args) => {
     validatePath(args.path, 'path');
-    return fs.existsSync(args.path);
+    // Remote projects' stand-in paths never exist locally
+    return remoteTargetFor(args.path) !== undefined || fs.existsSync(args.path);
   });
 
   // --- Window management ---
-  ipcMain.handle(IPC.WindowIsFocused, () => win.isFocused());
-  ipcMain.handle(IPC.WindowIsMaximized, () => win.isMaximized());
-  ipcMain.handle(IPC.WindowMinimize, () => win.minimize());
-  ipcMain.handle(IPC.WindowToggleMaximize, () => {
+  handle(IPC.WindowIsFocused, () => win.isFocused());
+  handle(IPC.WindowIsMaximized, () => win.isMaximized());
+  handle(IPC.WindowMinimize, () => win.minimize());
+  handle(IPC.WindowToggleMaximize, () => {
     if (win.isMaximized()) win.unmaximize();
     else win.maximize();
   });
-  ipcMain.handle(IPC.WindowClose, () => win.close());
-  ipcMain.handle(IPC.WindowForceClose, () => win.destroy());
-  ipcMain.handle(IPC.WindowHide, () => win.hide());
-  ipcMain.handle(IPC.WindowMaximize, () => win.maximize());
-  ipcMain.handle(IPC.WindowUnmaximize, () => win.unmaximize());
-  ipcMain.handle(IPC.WindowSetSize, (_e, args) => {
+  handle(IPC.WindowClose, () => win.close());
+  handle(IPC.WindowForceClose, () => win.destroy());
+  handle(IPC.WindowHide, () => win.hide());
+  handle(IPC.WindowMaximize, () => win.maximize());
+  handle(IPC.WindowUnmaximize, () => win.unmaximize());
+  handle(IPC.WindowSetSize, (_e, args) => {
     assertInt(args.width, 'width');
     assertInt(args.height, 'height');
     return win.setSize(args.width, args.height);
   });
-  ipcMain.handle(IPC.WindowSetPosition, (_e, args) => {
+  handle(IPC.WindowSetPosition, (_e, args) => {
     assertInt(args.x, 'x');
     assertInt(args.y, 'y');
     return win.setPosition(args.x, args.y);
   });
-  ipcMain.handle(IPC.WindowGetPosition, () => {
+  handle(IPC.WindowGetPosition, () => {
     const [x, y] = win.getPosition();
     return { x, y };
   });
-  ipcMain.handle(IPC.WindowGetSize, () => {
+  handle(IPC.WindowGetSize, () => {
     const [width, height] = win.getSize();
     return { width, height };
   });
 
+  // --- App data ---
+  handle(IPC.ExportAppData, (_e, args) => {
+    validatePath(args.outDir, 'outDir');
+    flushUsage();
+    checkpointHistoryDatabase();
+    return exportAppData(getStateDir(), path.join(args.outDir, backupFileName()));
+  });
+  handle(IPC.ImportAppData, async (_e, args) => {
+    validatePath(args.archivePath, 'archivePath');
+    await importAppData(getStateDir(), args.archivePath, () => {
+      killAllAgents();
+      stopScheduler();
+      closeHistoryDatabase();
+    });
+    // Restart without saving: the renderer's state is the one just replaced
+    app.relaunch();
+    app.exit(0);
+  });
+
   // --- Dialog ---
-  ipcMain.handle(IPC.DialogConfirm, async (_e, args) => {
+  handle(IPC.DialogConfirm, async (_e, args) => {
     const result = await dialog.showMessageBox(win, {
       type: args.kind === 'warning' ? 'warning' : 'question',
       title: args.title || 'Confirm',
@@ -358,7 +1167,7 @@ export function registerAllHandlers(win: BrowserWindow): void {
     return result.response === 0;
   });
 
-  ipcMain.handle(IPC.DialogOpen, async (_e, args) => {
+  handle(IPC.DialogOpen, async (_e, args) => {
     const properties: Array<'openDirectory' | 'openFile' | 'multiSelections'> = [];
     if (args?.directory) properties.push('openDirectory');
     else properties.push('openFile');
@@ -369,77 +1178,234 @@ export function registerAllHandlers(win: BrowserWindow): void {
   });
 
   // --- Shell/Opener ---
-  ipcMain.handle(IPC.ShellReveal, (_e, args) => {
+  handle(IPC.ShellReveal, (_e, args) => {
     validatePath(args.filePath, 'filePath');
     shell.showItemInFolder(args.filePath);
   });
 
-  ipcMain.handle(IPC.ShellOpenFile, (_e, args) => {
+  handle(IPC.ShellOpenFile, (_e, args) => {
     validatePath(args.worktreePath, 'worktreePath');
     validateRelativePath(args.filePath, 'filePath');
     return shell.openPath(path.join(args.worktreePath, args.filePath));
   });
 
-  ipcMain.handle(IPC.SaveClipboardImage, (_e, args) => {
-    const mimeType = typeof args?.mimeType === 'string' ? args.mimeType : '';
-    const base64Data = typeof args?.base64Data === 'string' ? args.base64Data : '';
-    if (!mimeType || !base64Data) {
-      throw new Error('Invalid clipboard image payload');
-    }
+  handle(IPC.PasteImageToSession, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    assertString(args.base64Data, 'base64Data');
+    assertString(args.mimeType, 'mimeType');
+    return pasteImageToSession({
+      agentId: args.agentId,
+      base64Data: args.base64Data,
+      mimeType: args.mimeType,
+    });
+  });
+  handle(IPC.ReadClipboardImage, () => clipboardImageFrom(clipboard.readImage()));
+  handle(IPC.AttachFilesToSession, (_e, args) => {
+    assertString(args.agentId, 'agentId');
+    assertStringArray(args.paths, 'paths');
+    for (const p of args.paths) validatePath(p, 'paths');
+    return attachFilesToSession(args.agentId, args.paths);
+  });
+  handle(IPC.OpenInEditor, (_e, args) => {
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.editor, 'editor');
+    if (args.filePath !== undefined) validateRelativePath(args.filePath, 'filePath');
+    if (args.line !== undefined) assertInt(args.line, 'line');
+    return openInEditor({
+      editor: args.editor,
+      worktreePath: args.worktreePath,
+      filePath: args.filePath,
+      line: args.line,
+    });
+  });
 
-    const ext = extensionForImageMime(mimeType);
-    if (!ext) throw new Error(`Unsupported clipboard image type: ${mimeType}`);
+  // --- Checks ---
+  handle(IPC.RunTaskTests, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    validatePath(args.worktreePath, 'worktreePath');
+    assertString(args.command, 'command');
+    const taskId = args.taskId;
+    return runTaskTests(
+      { taskId, worktreePath: args.worktreePath, command: args.command },
+      (chunk) => {
+        if (!win.isDestroyed()) win.webContents.send(IPC.TaskTestOutput, { taskId, chunk });
+      },
+    );
+  });
 
-    let bytes: Buffer;
-    try {
-      bytes = Buffer.from(base64Data, 'base64');
-    } catch {
-      throw new Error('Invalid clipboard image encoding');
-    }
-    if (!bytes.length) throw new Error('Clipboard image is empty');
-    if (bytes.length > MAX_CLIPBOARD_IMAGE_BYTES) {
-      throw new Error(`Clipboard image too large (${bytes.length} bytes)`);
+  handle(IPC.RunTaskChecks, async (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    validatePath(args.worktreePath, 'worktreePath');
+    assertStringArray(args.commands, 'commands');
+    const taskId = args.taskId;
+    const run = await runTaskChecks(
+      { taskId, worktreePath: args.worktreePath, commands: args.commands },
+      (id, index, result) => {
+        if (!win.isDestroyed()) {
+          win.webContents.send(IPC.CheckStatusChanged, { taskId: id, index, result });
+        }
+      },
+      (chunk) => {
+        if (!win.isDestroyed()) win.webContents.send(IPC.TaskTestOutput, { taskId, chunk });
+      },
+    );
+    const failed = run.results.find((r) => r.status === 'failed');
+    if (failed) {
+      emitWebhookEvent('checks_failed', taskId, {
+        command: failed.command,
+        exit_code: failed.exit_code,
+        head: run.head,
+      });
     }
+    return run;
+  });
 
-    const dir = path.join(app.getPath('temp'), 'parallel-code', 'clipboard-images');
-    fs.mkdirSync(dir, { recursive: true });
-    const filePath = path.join(dir, `clipboard-${Date.now()}-${randomUUID().slice(0, 8)}.${ext}`);
-    fs.writeFileSync(filePath, bytes);
-    return filePath;
+  handle(IPC.GetCheckResults, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return getCheckResults(args.taskId);
   });
-  ipcMain.handle(IPC.ShellOpenInEditor, (_e, args) => {
+
+  handle(IPC.CancelTaskTests, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return cancelTaskTests(args.taskId);
+  });
+
+  // --- Ports ---
+  handle(IPC.GetTaskPorts, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    return ensureTaskPorts(args.taskId);
+  });
+
+  // --- Remotes ---
+  handle(IPC.FetchIssue, async (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    assertString(args.ref, 'ref');
+    return (await getRemoteProvider(args.projectRoot)).fetchIssue(args.projectRoot, args.ref);
+  });
+  handle(IPC.CreatePullRequest, async (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    assertString(args.title, 'title');
+    assertOptionalString(args.body, 'body');
+    assertOptionalString(args.base, 'base');
+    if (args.base) validateBranchName(args.base, 'base');
+    const provider = await getRemoteProvider(args.projectRoot);
+    return provider.createPullRequest(args.projectRoot, {
+      branch: args.branchName,
+      base: args.base || (await getMainBranch(args.projectRoot)),
+      title: args.title,
+      body: args.body ?? '',
+    });
+  });
+  startPrPoller(win);
+  startScheduler(win);
+  handle(IPC.EnqueueAgentRun, (_e, args) => {
+    assertString(args.taskId, 'taskId');
     validatePath(args.worktreePath, 'worktreePath');
-    if (typeof args.editorCommand !== 'string' || !args.editorCommand.trim()) {
-      throw new Error('editorCommand must be a non-empty string');
+    assertString(args.agentId, 'agentId');
+    assertString(args.prompt, 'prompt');
+    if (!args.prompt.trim()) throw new Error('prompt must not be empty');
+    if (args.startAt !== undefined && args.startAt !== null && typeof args.startAt !== 'number') {
+      throw new Error('startAt must be a timestamp or null');
     }
-    const cmd = args.editorCommand.trim();
-    if (/[;&|`$(){}[\]<>\\'"*?!#~]/.test(cmd)) {
-      throw new Error('editorCommand must not contain shell metacharacters');
+    if (args.env !== undefined) {
+      if (!args.env || typeof args.env !== 'object' || Array.isArray(args.env)) {
+        throw new Error('env must be an object');
+      }
+      for (const v of Object.values(args.env as Record<string, unknown>)) {
+        assertString(v, 'env value');
+      }
     }
-    return new Promise<void>((resolve, reject) => {
-      let settled = false;
-      const child = spawn(cmd, [args.worktreePath], {
-        detached: true,
-        stdio: 'ignore',
-      });
-      child.on('error', (err) => {
-        if (!settled) {
-          settled = true;
-          reject(new Error(`Failed to launch "${cmd}": ${err.message}`));
-        }
-      });
-      child.on('spawn', () => {
-        if (!settled) {
-          settled = true;
-          child.unref();
-          resolve();
-        }
-      });
+    if (args.secrets !== undefined) assertStringArray(args.secrets, 'secrets');
+    return enqueueRun({
+      taskId: args.taskId,
+      worktreePath: args.worktreePath,
+      agentId: args.agentId,
+      prompt: args.prompt,
+      env: args.env as Record<string, string> | undefined,
+      secrets: args.secrets,
+      startAt: args.startAt as number | null | undefined,
     });
   });
+  handle(IPC.ListScheduledRuns, () => listRuns());
+  handle(IPC.CancelScheduledRun, (_e, args) => {
+    assertString(args.runId, 'runId');
+    cancelRun(args.runId);
+  });
+  startMergeQueue(win);
+  handle(IPC.EnqueueMerge, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    validatePath(args.projectRoot, 'projectRoot');
+    validateBranchName(args.branchName, 'branchName');
+    validatePath(args.worktreePath, 'worktreePath');
+    assertOptionalBoolean(args.squash, 'squash');
+    assertOptionalString(args.message, 'message');
+    if (args.checks !== undefined) assertStringArray(args.checks, 'checks');
+    if (args.dependsOn !== undefined) assertStringArray(args.dependsOn, 'dependsOn');
+    return enqueueMerge({
+      taskId: args.taskId,
+      projectRoot: args.projectRoot,
+      branchName: args.branchName,
+      worktreePath: args.worktreePath,
+      squash: args.squash,
+      message: args.message,
+      checks: args.checks,
+      dependsOn: args.dependsOn,
+    });
+  });
+  handle(IPC.ListMergeQueue, () => listMergeQueue());
+  handle(IPC.RemoveFromMergeQueue, (_e, args) => {
+    assertString(args.entryId, 'entryId');
+    removeFromMergeQueue(args.entryId);
+  });
+  handle(IPC.ResumeMergeQueue, (_e, args) => {
+    validatePath(args.projectRoot, 'projectRoot');
+    resumeMergeQueue(args.projectRoot);
+  });
+  handle(IPC.ListSecrets, () => listSecrets());
+  handle(IPC.SetSecret, (_e, args) => {
+    assertString(args.name, 'name');
+    assertString(args.value, 'value');
+    assertOptionalBoolean(args.inject, 'inject');
+    return setSecret(args.name, args.value, args.inject);
+  });
+  handle(IPC.HasSecret, (_e, args) => {
+    assertString(args.name, 'name');
+    return hasSecret(args.name);
+  });
+  handle(IPC.SetSecretInjected, (_e, args) => {
+    assertString(args.name, 'name');
+    assertBoolean(args.inject, 'inject');
+    return setSecretInjected(args.name, args.inject);
+  });
+  handle(IPC.DeleteSecret, (_e, args) => {
+    assertString(args.name, 'name');
+    deleteSecret(args.name);
+  });
+  handle(IPC.SetWatchedPrTasks, (_e, args) => {
+    if (!Array.isArray(args.tasks)) throw new Error('tasks must be an array');
+    for (const t of args.tasks) {
+      assertString(t?.taskId, 'tasks[].taskId');
+      validatePath(t.worktreePath, 'tasks[].worktreePath');
+      validateBranchName(t.branchName, 'tasks[].branchName');
+    }
+    if (args.reset !== undefined) assertBoolean(args.reset, 'reset');
+    const tasks = args.tasks.map(
+      (t: { taskId: string; worktreePath: string; branchName: string }) => ({
+        taskId: t.taskId,
+        worktreePath: t.worktreePath,
+        branchName: t.branchName,
+      }),
+    );
+    setWatchedPrTasks(tasks, args.reset ?? false);
+  });
+  handle(IPC.RefreshTaskPrStatus, (_e, args) => {
+    assertString(args.taskId, 'taskId');
+    refreshTaskPrStatus(args.taskId);
+  });
 
   // --- Remote access ---
-  ipcMain.handle(
+  handle(
     IPC.StartRemoteServer,
     (_e, args: { port?: number; allowExternal?: boolean } = {}) => {
       if (remoteServer)
@@ -481,14 +1447,14 @@ export function registerAllHandlers(win: BrowserWindow): void {
     },
   );
 
-  ipcMain.handle(IPC.StopRemoteServer, async () => {
+  handle(IPC.StopRemoteServer, async () => {
     if (remoteServer) {
       await remoteServer.stop();
       remoteServer = null;
     }
   });
 
-  ipcMain.handle(IPC.GetRemoteStatus, () => {
+  handle(IPC.GetRemoteStatus, () => {
     if (!remoteServer) return { enabled: false, connectedClients: 0 };
     return {
       enabled: true,
@@ -502,6 +1468,31 @@ export function registerAllHandlers(win: BrowserWindow): void {
     };
   });
 
+  // --- Automation API ---
+  const applyAutomationPort = (port: number | null) => {
+    if (port === null) stopAutomationServer();
+    else startAutomationServer(win, port);
+  };
+  applyAutomationPort(getSettings().automation_api_port);
+  onSettingsChanged((settings) => applyAutomationPort(settings.automation_api_port));
+  handle(IPC.AutomationResponse, (_e, args) => {
+    assertString(args.id, 'id');
+    resolveAutomationRequest(args);
+  });
+  handle(IPC.GetAutomationApiInfo, () => {
+    const port = getSettings().automation_api_port;
+    return {
+      enabled: port !== null,
+      url: port === null ? null : `http://127.0.0.1:${port}/v1`,
+      token: getAutomationToken(),
+    };
+  });
+  handle(IPC.RegenerateAutomationToken, () => regenerateAutomationToken());
+
+  // --- Deep links ---
+  initDeepLinks(win);
+  handle(IPC.TakePendingDeepLinks, () => takePendingDeepLinks());
+
   // --- Forward window events to renderer ---
   win.on('focus', () => {
     if (!win.isDestroyed()) win.webContents.send(IPC.WindowFocus);
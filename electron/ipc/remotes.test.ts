@@ -0,0 +1,79 @@
+import { describe, expect, it } from 'vitest';
+import {
+  bitbucketCiStatus,
+  detectHost,
+  gitlabCiStatus,
+  parseBitbucketIssueRef,
+  parseGitLabIssueRef,
+  parseRemoteUrl,
+} from './remotes.js';
+
+describe('parseRemoteUrl', () => {
+  it('parses scp-style, ssh and https remotes', () => {
+    expect(parseRemoteUrl('git@github.com:acme/widgets.git')).toEqual({
+      hostname: 'github.com',
+      path: 'acme/widgets',
+    });
+    expect(parseRemoteUrl('ssh://git@gitlab.example.com:2222/group/sub/app.git')).toEqual({
+      hostname: 'gitlab.example.com',
+      path: 'group/sub/app',
+    });
+    expect(parseRemoteUrl('https://me@bitbucket.org/team/app')).toEqual({
+      hostname: 'bitbucket.org',
+      path: 'team/app',
+    });
+  });
+
+  it('rejects local paths', () => {
+    expect(parseRemoteUrl('/srv/git/app.git')).toBeNull();
+    expect(parseRemoteUrl('file:///srv/git/app.git')).toBeNull();
+  });
+});
+
+describe('detectHost', () => {
+  it('matches hosted and self-hosted instances', () => {
+    expect(detectHost('github.com')).toBe('github');
+    expect(detectHost('github.acme.corp')).toBe('github');
+    expect(detectHost('gitlab.com')).toBe('gitlab');
+    expect(detectHost('gitlab.acme.corp')).toBe('gitlab');
+    expect(detectHost('bitbucket.org')).toBe('bitbucket');
+    expect(detectHost('git.acme.corp')).toBeNull();
+  });
+});
+
+describe('issue refs', () => {
+  it('parses GitLab refs including subgroups', () => {
+    expect(parseGitLabIssueRef('#5')).toEqual({ number: 5 });
+    expect(parseGitLabIssueRef('group/sub/app#5')).toEqual({ repo: 'group/sub/app', number: 5 });
+    expect(parseGitLabIssueRef('https://gitlab.com/group/app/-/issues/5')).toEqual({
+      repo: 'group/app',
+      number: 5,
+    });
+    expect(parseGitLabIssueRef('https://gitlab.com/group/app/-/merge_requests/5')).toBeNull();
+  });
+
+  it('parses Bitbucket refs', () => {
+    expect(parseBitbucketIssueRef('9')).toEqual({ number: 9 });
+    expect(parseBitbucketIssueRef('https://bitbucket.org/team/app/issues/9/title')).toEqual({
+      repo: 'team/app',
+      number: 9,
+    });
+    expect(parseBitbucketIssueRef('team/app#9')).toBeNull();
+  });
+});
+
+describe('CI status', () => {
+  it('summarizes GitLab pipelines', () => {
+    expect(gitlabCiStatus(undefined)).toBe('none');
+    expect(gitlabCiStatus('success')).toBe('passing');
+    expect(gitlabCiStatus('running')).toBe('pending');
+    expect(gitlabCiStatus('failed')).toBe('failing');
+  });
+
+  it('summarizes Bitbucket commit statuses', () => {
+    expect(bitbucketCiStatus([])).toBe('none');
+    expect(bitbucketCiStatus(['SUCCESSFUL', 'INPROGRESS'])).toBe('pending');
+    expect(bitbucketCiStatus(['INPROGRESS', 'FAILED'])).toBe('failing');
+    expect(bitbucketCiStatus(['SUCCESSFUL'])).toBe('passing');
+  });
+});
@@ -0,0 +1,346 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import * as github from './github.js';
+import { getRemoteUrl } from './git.js';
+
+const exec = promisify(execFile);
+
+export type RemoteHost = 'github' | 'gitlab' | 'bitbucket';
+
+export interface RemoteIssue {
+  number: number;
+  title: string;
+  body: string;
+  url: string;
+  state: string;
+  labels: string[];
+}
+
+export type PrState = 'OPEN' | 'MERGED' | 'CLOSED';
+export type CiStatus = 'none' | 'pending' | 'passing' | 'failing';
+
+/** A GitHub PR, GitLab MR or Bitbucket PR, normalized. */
+export interface PullRequestStatus {
+  number: number;
+  url: string;
+  state: PrState;
+  ci: CiStatus;
+}
+
+export interface CreatedPullRequest {
+  number: number;
+  url: string;
+}
+
+export interface CreatePullRequestOpts {
+  branch: string;
+  base: string;
+  title: string;
+  body: string;
+}
+
+/** Issue and pull request operations for the host a project's `origin` points at. */
+export interface RemoteProvider {
+  readonly host: RemoteHost;
+  fetchIssue(cwd: string, ref: string): Promise<RemoteIssue>;
+  /** The PR for `branch`, or null when the branch has none. */
+  fetchPrStatus(cwd: string, branch: string): Promise<PullRequestStatus | null>;
+  createPullRequest(cwd: string, opts: CreatePullRequestOpts): Promise<CreatedPullRequest>;
+}
+
+export interface RemoteRepo {
+  hostname: string;
+  /** `owner/repo`; GitLab paths may contain subgroups. */
+  path: string;
+}
+
+/** Parses `git@host:path.git`, `ssh://git@host[:port]/path` and `https://[user@]host/path`. */
+export function parseRemoteUrl(url: string): RemoteRepo | null {
+  const text = url.trim();
+  const scp = text.match(/^[\w.-]+@([\w.-]+):(?!\/)(.+?)(?:\.git)?\/?$/);
+  if (scp) return { hostname: scp[1].toLowerCase(), path: scp[2] };
+  const full = text.match(
+    /^(?:ssh|git|https?):\/\/(?:[^@/]+@)?([\w.-]+)(?::\d+)?\/(.+?)(?:\.git)?\/?$/,
+  );
+  if (full) return { hostname: full[1].toLowerCase(), path: full[2] };
+  return null;
+}
+
+/** The provider for `hostname`. Self-hosted GitLab and GitHub Enterprise are matched by name. */
+export function detectHost(hostname: string): RemoteHost | null {
+  if (hostname === 'bitbucket.org') return 'bitbucket';
+  if (hostname.includes('gitlab')) return 'gitlab';
+  if (hostname.includes('github')) return 'github';
+  return null;
+}
+
+const githubProvider: RemoteProvider = {
+  host: 'github',
+  fetchIssue: github.fetchIssue,
+  fetchPrStatus: github.fetchPrStatus,
+  createPullRequest: github.createPullRequest,
+};
+
+// --- GitLab ---
+
+/** Run the GitLab CLI, turning its failures into readable errors. */
+async function glab(args: string[], cwd: string): Promise<string> {
+  try {
+    const { stdout } = await exec('glab', args, { cwd });
+    return stdout;
+  } catch (err) {
+    if ((err as NodeJS.ErrnoException).code === 'ENOENT') {
+      throw new Error('GitLab CLI (glab) is not installed or not on PATH');
+    }
+    const stderr = (err as { stderr?: string }).stderr?.trim();
+    throw new Error(stderr || `glab ${args.slice(0, 2).join(' ')} failed`);
+  }
+}
+
+/** Accepts `123`, `#123`, `group/project#123` or an issue URL. Returns null otherwise. */
+export function parseGitLabIssueRef(ref: string): { repo?: string; number: number } | null {
+  const text = ref.trim();
+  const bare = text.match(/^#?(\d+)$/);
+  if (bare) return { number: parseInt(bare[1], 10) };
+  const short = text.match(/^([\w.-]+(?:\/[\w.-]+)+)#(\d+)$/);
+  if (short) return { repo: short[1], number: parseInt(short[2], 10) };
+  const url = text.match(/^https?:\/\/[\w.-]+\/([\w.-]+(?:\/[\w.-]+)+)\/-\/issues\/(\d+)/);
+  if (url) return { repo: url[1], number: parseInt(url[2], 10) };
+  return null;
+}
+
+/** GitLab pipeline status as a CI summary. */
+export function gitlabCiStatus(status: string | undefined): CiStatus {
+  if (!status) return 'none';
+  if (status === 'success' || status === 'skipped') return 'passing';
+  if (status === 'failed' || status === 'canceled') return 'failing';
+  return 'pending';
+}
+
+const GITLAB_STATES: Record<string, PrState> = {
+  opened: 'OPEN',
+  merged: 'MERGED',
+  closed: 'CLOSED',
+  locked: 'CLOSED',
+};
+
+const gitlabProvider: RemoteProvider = {
+  host: 'gitlab',
+
+  async fetchIssue(cwd, ref) {
+    const parsed = parseGitLabIssueRef(ref);
+    if (!parsed) throw new Error(`Not a GitLab issue reference: ${ref}`);
+    const args = ['issue', 'view', String(parsed.number), '--output', 'json'];
+    if (parsed.repo) args.push('--repo', parsed.repo);
+    const raw = JSON.parse(await glab(args, cwd)) as {
+      iid: number;
+      title: string;
+      description?: string | null;
+      web_url: string;
+      state: string;
+      labels?: string[];
+    };
+    return {
+      number: raw.iid,
+      title: raw.title,
+      body: raw.description ?? '',
+      url: raw.web_url,
+      state: raw.state,
+      labels: raw.labels ?? [],
+    };
+  },
+
+  async fetchPrStatus(cwd, branch) {
+    let stdout: string;
+    try {
+      stdout = await glab(['mr', 'view', branch, '--output', 'json'], cwd);
+    } catch (err) {
+      if (err instanceof Error && /no (open )?merge requests?\b/i.test(err.message)) return null;
+      throw err;
+    }
+    const raw = JSON.parse(stdout) as {
+      iid: number;
+      web_url: string;
+      state: string;
+      head_pipeline?: { status?: string } | null;
+    };
+    return {
+      number: raw.iid,
+      url: raw.web_url,
+      state: GITLAB_STATES[raw.state] ?? 'OPEN',
+      ci: gitlabCiStatus(raw.head_pipeline?.status),
+    };
+  },
+
+  async createPullRequest(cwd, opts) {
+    const stdout = await glab(
+      [
+        'mr',
+        'create',
+        '--source-branch',
+        opts.branch,
+        '--target-branch',
+        opts.base,
+        '--title',
+        opts.title,
+        '--description',
+        opts.body,
+        '--yes',
+      ],
+      cwd,
+    );
+    const url = stdout.match(/https?:\/\/\S+\/-\/merge_requests\/(\d+)/);
+    if (!url) throw new Error(`Unexpected glab mr create output: ${stdout.trim()}`);
+    return { number: parseInt(url[1], 10), url: url[0] };
+  },
+};
+
+// --- Bitbucket ---
+
+const BITBUCKET_API = 'https://api.bitbucket.org/2.0/repositories';
+
+/**
+ * Bitbucket has no standard CLI, so this talks to its REST API. Credentials
+ * come from `BITBUCKET_TOKEN` (an access token) or `BITBUCKET_USERNAME` plus
+ * `BITBUCKET_APP_PASSWORD`; without them only public repos can be read.
+ */
+function bitbucketAuth(): string | null {
+  const token = process.env.BITBUCKET_TOKEN;
+  if (token) return `Bearer ${token}`;
+  const user = process.env.BITBUCKET_USERNAME;
+  const password = process.env.BITBUCKET_APP_PASSWORD;
+  if (user && password) return `Basic ${Buffer.from(`${user}:${password}`).toString('base64')}`;
+  return null;
+}
+
+async function bitbucket<T>(repo: string, endpoint: string, body?: unknown): Promise<T> {
+  const headers: Record<string, string> = { Accept: 'application/json' };
+  const auth = bitbucketAuth();
+  if (auth) headers.Authorization = auth;
+  if (body !== undefined) headers['Content-Type'] = 'application/json';
+  const res = await fetch(`${BITBUCKET_API}/${repo}/${endpoint}`, {
+    method: body === undefined ? 'GET' : 'POST',
+    headers,
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  if (!res.ok) {
+    const detail = await res.text().catch(() => '');
+    const hint = res.status === 401 && !auth ? ' (set BITBUCKET_TOKEN)' : '';
+    throw new Error(`Bitbucket ${endpoint.split('?')[0]} failed: ${res.status}${hint} ${detail}`);
+  }
+  return (await res.json()) as T;
+}
+
+async function bitbucketRepo(cwd: string): Promise<string> {
+  const remote = parseRemoteUrl(await getRemoteUrl(cwd));
+  if (!remote) throw new Error('Cannot parse the origin remote URL');
+  return remote.path;
+}
+
+/** Accepts `123`, `#123` or an issue URL. Returns null otherwise. */
+export function parseBitbucketIssueRef(ref: string): { repo?: string; number: number } | null {
+  const text = ref.trim();
+  const bare = text.match(/^#?(\d+)$/);
+  if (bare) return { number: parseInt(bare[1], 10) };
+  const url = text.match(/^https?:\/\/bitbucket\.org\/([\w.-]+\/[\w.-]+)\/issues\/(\d+)/);
+  if (url) return { repo: url[1], number: parseInt(url[2], 10) };
+  return null;
+}
+
+/** Bitbucket commit statuses as a CI summary. */
+export function bitbucketCiStatus(states: string[]): CiStatus {
+  if (states.length === 0) return 'none';
+  if (states.some((s) => s === 'FAILED' || s === 'STOPPED')) return 'failing';
+  if (states.some((s) => s === 'INPROGRESS')) return 'pending';
+  return 'passing';
+}
+
+interface BitbucketPullRequest {
+  id: number;
+  state: string;
+  links: { html: { href: string } };
+  source: { commit?: { hash: string } | null };
+}
+
+const bitbucketProvider: RemoteProvider = {
+  host: 'bitbucket',
+
+  async fetchIssue(cwd, ref) {
+    const parsed = parseBitbucketIssueRef(ref);
+    if (!parsed) throw new Error(`Not a Bitbucket issue reference: ${ref}`);
+    const repo = parsed.repo ?? (await bitbucketRepo(cwd));
+    const raw = await bitbucket<{
+      id: number;
+      title: string;
+      content?: { raw?: string | null };
+      links: { html: { href: string } };
+      state: string;
+      kind?: string;
+    }>(repo, `issues/${parsed.number}`);
+    return {
+      number: raw.id,
+      title: raw.title,
+      body: raw.content?.raw ?? '',
+      url: raw.links.html.href,
+      state: raw.state,
+      labels: raw.kind ? [raw.kind] : [],
+    };
+  },
+
+  async fetchPrStatus(cwd, branch) {
+    const repo = await bitbucketRepo(cwd);
+    const q = encodeURIComponent(`source.branch.name="${branch}"`);
+    const states = ['OPEN', 'MERGED', 'DECLINED', 'SUPERSEDED'].map((s) => `state=${s}`).join('&');
+    const list = await bitbucket<{ values: BitbucketPullRequest[] }>(
+      repo,
+      `pullrequests?q=${q}&${states}&sort=-updated_on`,
+    );
+    const pr = list.values[0];
+    if (!pr) return null;
+    let ci: CiStatus = 'none';
+    if (pr.source.commit) {
+      const statuses = await bitbucket<{ values: Array<{ state: string }> }>(
+        repo,
+        `commit/${pr.source.commit.hash}/statuses`,
+      );
+      ci = bitbucketCiStatus(statuses.values.map((s) => s.state));
+    }
+    return {
+      number: pr.id,
+      url: pr.links.html.href,
+      state: pr.state === 'OPEN' ? 'OPEN' : pr.state === 'MERGED' ? 'MERGED' : 'CLOSED',
+      ci,
+    };
+  },
+
+  async createPullRequest(cwd, opts) {
+    const repo = await bitbucketRepo(cwd);
+    const pr = await bitbucket<BitbucketPullRequest>(repo, 'pullrequests', {
+      title: opts.title,
+      description: opts.body,
+      source: { branch: { name: opts.branch } },
+      destination: { branch: { name: opts.base } },
+    });
+    return { number: pr.id, url: pr.links.html.href };
+  },
+};
+
+const PROVIDERS: Record<RemoteHost, RemoteProvider> = {
+  github: githubProvider,
+  gitlab: gitlabProvider,
+  bitbucket: bitbucketProvider,
+};
+
+/** The provider for the repo at `cwd`, detected from its `origin` URL. */
+export async function getRemoteProvider(cwd: string): Promise<RemoteProvider> {
+  let url: string;
+  try {
+    url = await getRemoteUrl(cwd);
+  } catch {
+    throw new Error('This repository has no origin remote');
+  }
+  const remote = parseRemoteUrl(url);
+  const host = remote && detectHost(remote.hostname);
+  if (!host) throw new Error(`Unsupported remote host: ${url}`);
+  return PROVIDERS[host];
+}
@@ -0,0 +1,43 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./secrets.js', () => ({ secretEnv: () => ({}) }));
+
+vi.mock('./notifications.js', () => ({
+  notify: vi.fn(),
+}));
+
+import { parsePsOutput, treeUsage } from './resource-usage.js';
+
+const PS = `
+    1     0  1200   0.0
+  100     1 20480  12.5
+  101   100 10240  50.0
+  102   101  1024   0.5
+  200     1  4096   1.0
+garbage line
+`;
+
+describe('parsePsOutput', () => {
+  it('reads pid, parent, resident KB and CPU, skipping other lines', () => {
+    const samples = parsePsOutput(PS);
+    expect(samples).toHaveLength(5);
+    expect(samples[1]).toEqual({ pid: 100, ppid: 1, rss_kb: 20480, cpu_percent: 12.5 });
+  });
+});
+
+describe('treeUsage', () => {
+  it('sums a process and all of its descendants', () => {
+    expect(treeUsage(parsePsOutput(PS), 100)).toEqual({
+      cpu_percent: 63,
+      rss_bytes: (20480 + 10240 + 1024) * 1024,
+    });
+  });
+
+  it('is null for a process that is gone', () => {
+    expect(treeUsage(parsePsOutput(PS), 999)).toBeNull();
+  });
+});
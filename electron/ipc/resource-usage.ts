@@ -0,0 +1,113 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { getSettings } from './settings.js';
+import { listSessionProcesses, type ScrollbackStats } from './pty.js';
+import { createLogger } from './log.js';
+
+const exec = promisify(execFile);
+const log = createLogger('resource-usage');
+
+export interface ProcessSample {
+  pid: number;
+  ppid: number;
+  rss_kb: number;
+  cpu_percent: number;
+}
+
+export interface SessionResourceUsage {
+  agent_id: string;
+  task_id: string;
+  is_shell: boolean;
+  pid: number;
+  /**
+   * Summed over the session's process tree, as `ps` reports it (one core is
+   * 100). Null when `ps` fails or the process is gone.
+   */
+  cpu_percent: number | null;
+  rss_bytes: number | null;
+  scrollback: ScrollbackStats;
+}
+
+export interface ResourceUsage {
+  /** Largest resident memory first. */
+  sessions: SessionResourceUsage[];
+  /** Running agent sessions; terminals don't count toward the limits. */
+  agent_sessions: number;
+  max_agent_sessions: number;
+  max_agents_per_task: number;
+  /** Memory all scrollback buffers take, against `scrollback_budget_bytes`. */
+  scrollback_bytes: number;
+  scrollback_budget_bytes: number;
+}
+
+/** Parse `ps -A -o pid=,ppid=,rss=,%cpu=` output. */
+export function parsePsOutput(output: string): ProcessSample[] {
+  const samples: ProcessSample[] = [];
+  for (const line of output.split('\n')) {
+    const [pid, ppid, rss, cpu] = line.trim().split(/\s+/).map(Number);
+    if (!Number.isInteger(pid) || !Number.isInteger(ppid) || Number.isNaN(rss)) continue;
+    samples.push({ pid, ppid, rss_kb: rss, cpu_percent: Number.isNaN(cpu) ? 0 : cpu });
+  }
+  return samples;
+}
+
+/** CPU and memory of `pid` and everything it started; null when it isn't running. */
+export function treeUsage(
+  samples: ProcessSample[],
+  pid: number,
+): { cpu_percent: number; rss_bytes: number } | null {
+  const children = new Map<number, ProcessSample[]>();
+  let root: ProcessSample | undefined;
+  for (const s of samples) {
+    if (s.pid === pid) root = s;
+    const list = children.get(s.ppid) ?? [];
+    list.push(s);
+    children.set(s.ppid, list);
+  }
+  if (!root) return null;
+  let cpu = 0;
+  let rssKb = 0;
+  const seen = new Set<number>();
+  const stack = [root];
+  for (let s = stack.pop(); s; s = stack.pop()) {
+    if (seen.has(s.pid)) continue;
+    seen.add(s.pid);
+    cpu += s.cpu_percent;
+    rssKb += s.rss_kb;
+    stack.push(...(children.get(s.pid) ?? []));
+  }
+  return { cpu_percent: Math.round(cpu * 10) / 10, rss_bytes: rssKb * 1024 };
+}
+
+async function sampleProcesses(): Promise<ProcessSample[] | null> {
+  try {
+    const { stdout } = await exec('ps', ['-A', '-o', 'pid=,ppid=,rss=,%cpu='], {
+      maxBuffer: 8 * 1024 * 1024,
+    });
+    return parsePsOutput(stdout);
+  } catch (err) {
+    log.warn('ps failed', err);
+    return null;
+  }
+}
+
+/** What each running session uses, with the session limits it counts against. */
+export async function getResourceUsage(): Promise<ResourceUsage> {
+  const processes = listSessionProcesses();
+  const samples = await sampleProcesses();
+  const sessions = processes
+    .map((p) => {
+      const usage = samples ? treeUsage(samples, p.pid) : null;
+      return { ...p, cpu_percent: usage?.cpu_percent ?? null, rss_bytes: usage?.rss_bytes ?? null };
+    })
+    .sort((a, b) => (b.rss_bytes ?? 0) - (a.rss_bytes ?? 0));
+  const settings = getSettings();
+  return {
+    sessions,
+    agent_sessions: processes.filter((p) => !p.is_shell).length,
+    max_agent_sessions: settings.max_agent_sessions,
+    max_agents_per_task: settings.max_agents_per_task,
+    scrollback_bytes: processes.reduce((sum, p) => sum + p.scrollback.capacity_bytes, 0),
+    scrollback_budget_bytes: settings.scrollback_budget_mb * 1024 * 1024,
+  };
+}
@@ -0,0 +1,68 @@
+import { describe, it, expect } from 'vitest';
+import { bwrapArgs, sandboxPaths, seatbeltProfile } from './sandbox.js';
+
+describe('sandboxPaths', () => {
+  it('includes the worktree, temp dirs, agent state and extras once each', () => {
+    const paths = sandboxPaths('/nonexistent/wt', '/nonexistent/home', ['/tmp', '/data']);
+    expect(paths.dirs).toContain('/nonexistent/wt');
+    expect(paths.dirs).toContain('/nonexistent/home/.claude');
+    expect(paths.dirs).toContain('/data');
+    expect(new Set(paths.dirs).size).toBe(paths.dirs.length);
+    expect(paths.filePrefixes).toEqual(['/nonexistent/home/.claude.json']);
+    expect(paths.dirs).not.toContain('/nonexistent/home/.config');
+    expect(paths.dirs).not.toContain('/nonexistent/home/.local');
+  });
+
+  it("opens only what commits write in the repo's git dir", () => {
+    const paths = sandboxPaths('/nonexistent/wt', '/nonexistent/home', [], {
+      topLevel: '/nonexistent/wt',
+      common: '/nonexistent/repo/.git',
+      own: '/nonexistent/repo/.git/worktrees/wt',
+    });
+    expect(paths.dirs).toEqual(
+      expect.arrayContaining([
+        '/nonexistent/repo/.git/objects',
+        '/nonexistent/repo/.git/refs',
+        '/nonexistent/repo/.git/logs',
+        '/nonexistent/repo/.git/worktrees/wt',
+      ]),
+    );
+    expect(paths.dirs).not.toContain('/nonexistent/repo/.git');
+    expect(paths.readOnly).toEqual([
+      '/nonexistent/repo/.git/config',
+      '/nonexistent/repo/.git/hooks',
+      '/nonexistent/repo/.git/worktrees/wt/config.worktree',
+      '/nonexistent/wt/.git',
+    ]);
+  });
+});
+
+describe('seatbeltProfile', () => {
+  it('denies writes except to the allowed paths', () => {
+    const profile = seatbeltProfile({
+      dirs: ['/wt/a "b"'],
+      filePrefixes: ['/h/.claude.json'],
+      readOnly: ['/repo/.git/hooks'],
+    });
+    expect(profile).toContain('(deny file-write*)');
+    // Last, so it wins over the allow
+    expect(profile.split('\n').slice(-2)).toEqual([
+      '(deny file-write*',
+      '  (subpath "/repo/.git/hooks"))',
+    ]);
+    expect(profile).toContain('(subpath "/wt/a \\"b\\"")');
+    expect(profile).toContain('(regex "^/h/\\\\.claude\\\\.json")');
+  });
+});
+
+describe('bwrapArgs', () => {
+  it('mounts root read-only, binds existing paths and runs the command', () => {
+    const paths = { dirs: ['/tmp', '/nonexistent'], filePrefixes: [], readOnly: ['/tmp'] };
+    const args = bwrapArgs(paths, 'claude', ['-c']);
+    expect(args.slice(0, 3)).toEqual(['--ro-bind', '/', '/']);
+    // The read-only bind comes after the writable one, so it wins
+    expect(args.join(' ')).toContain('--bind /tmp /tmp --ro-bind /tmp /tmp');
+    expect(args).not.toContain('/nonexistent');
+    expect(args.slice(-3)).toEqual(['--', 'claude', '-c']);
+  });
+});
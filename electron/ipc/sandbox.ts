@@ -0,0 +1,208 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { execFileSync } from 'child_process';
+
+/**
+ * Where agents keep credentials, sessions and caches; they fail without write
+ * access. Not `.config` or `.local`: other programs' settings and
+ * `~/.local/bin` would let an agent change what runs outside the sandbox.
+ */
+const AGENT_HOME_DIRS = ['.claude', '.codex', '.gemini', '.cache', '.npm'];
+/** What committing from a worktree writes in the repo's shared git dir. */
+const GIT_WRITABLE_DIRS = ['objects', 'refs', 'logs'];
+/** Home-dir files written by replacing them (`.claude.json.tmp` then rename). */
+const AGENT_HOME_FILE_PREFIXES = ['.claude.json'];
+
+export interface SandboxPaths {
+  /** Directories the sandboxed process may write anywhere inside. */
+  dirs: string[];
+  /** Files, and siblings starting with the same name, it may write. */
+  filePrefixes: string[];
+  /** Paths inside `dirs` that stay read-only. */
+  readOnly: string[];
+}
+
+function realpath(p: string): string {
+  try {
+    return fs.realpathSync(p);
+  } catch {
+    return p;
+  }
+}
+
+/** The repo's shared `.git` dir, which commits from a worktree write objects and refs into. */
+export function gitCommonDir(cwd: string): string | null {
+  try {
+    const out = execFileSync('git', ['rev-parse', '--git-common-dir'], {
+      cwd,
+      encoding: 'utf8',
+      timeout: 5000,
+    });
+    return path.resolve(cwd, out.trim());
+  } catch {
+    return null;
+  }
+}
+
+interface GitDirs {
+  /** The checkout's top level, where its `.git` is. */
+  topLevel: string;
+  common: string;
+  /** The worktree's own git dir (`worktrees/<name>`), or `common` for the main checkout. */
+  own: string;
+}
+
+function gitDirs(cwd: string): GitDirs | null {
+  try {
+    const out = execFileSync(
+      'git',
+      ['rev-parse', '--show-toplevel', '--git-common-dir', '--git-dir'],
+      { cwd, encoding: 'utf8', timeout: 5000 },
+    );
+    const [topLevel, common, own] = out.trim().split('\n');
+    if (!topLevel || !common || !own) return null;
+    return { topLevel, common: path.resolve(cwd, common), own: path.resolve(cwd, own) };
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * What an agent in `cwd` may write: its worktree, the parts of the repo's
+ * git dir that commits write, temp and its own state. The repo's config and
+ * hooks, the worktree's `config.worktree` and its `.git` file stay
+ * read-only: the app runs git in the worktree unsandboxed, so an agent that
+ * set `core.fsmonitor`, added a hook or pointed `.git` elsewhere would get
+ * code run outside the sandbox.
+ */
+export function sandboxPaths(
+  cwd: string,
+  home: string,
+  extra: string[] = [],
+  git: GitDirs | null = gitDirs(cwd),
+): SandboxPaths {
+  const gitWritable = git
+    ? [...GIT_WRITABLE_DIRS.map((d) => path.join(git.common, d)), git.own]
+    : [];
+  const dirs = [cwd, ...gitWritable, os.tmpdir(), '/tmp', ...extra]
+    .concat(AGENT_HOME_DIRS.map((d) => path.join(home, d)))
+    .map(realpath);
+  const readOnly = git
+    ? [
+        path.join(git.common, 'config'),
+        path.join(git.common, 'hooks'),
+        path.join(git.own, 'config.worktree'),
+        path.join(git.topLevel, '.git'),
+      ].map(realpath)
+    : [];
+  return {
+    dirs: [...new Set(dirs)],
+    filePrefixes: AGENT_HOME_FILE_PREFIXES.map((f) => path.join(realpath(home), f)),
+    readOnly: [...new Set(readOnly)],
+  };
+}
+
+function sbString(s: string): string {
+  return `"${s.replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"`;
+}
+
+function escapeRegex(s: string): string {
+  return s.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+}
+
+/** A macOS Seatbelt profile that allows everything except writes outside `paths`. */
+export function seatbeltProfile(paths: SandboxPaths): string {
+  const rules = [
+    ...paths.dirs.map((d) => `(subpath ${sbString(d)})`),
+    ...paths.filePrefixes.map((f) => `(regex ${sbString(`^${escapeRegex(f)}`)})`),
+    '(subpath "/dev")',
+  ];
+  // Later rules win, so these carve the read-only paths back out
+  const readOnly = paths.readOnly.map((p) => `(subpath ${sbString(p)})`);
+  return [
+    '(version 1)',
+    '(allow default)',
+    '(deny file-write*)',
+    `(allow file-write*\n  ${rules.join('\n  ')})`,
+    ...(readOnly.length > 0 ? [`(deny file-write*\n  ${readOnly.join('\n  ')})`] : []),
+  ].join('\n');
+}
+
+/**
+ * bubblewrap arguments: the whole filesystem read-only, with writable binds
+ * for `paths` that exist and read-only ones over them again. /dev stays the
+ * host's so the PTY keeps working.
+ */
+export function bwrapArgs(paths: SandboxPaths, command: string, args: string[]): string[] {
+  const binds: string[] = [];
+  for (const d of paths.dirs) {
+    if (fs.existsSync(d)) binds.push('--bind', d, d);
+  }
+  for (const prefix of paths.filePrefixes) {
+    // bubblewrap binds exact paths, so only the file itself is writable
+    if (fs.existsSync(prefix)) binds.push('--bind', prefix, prefix);
+  }
+  for (const p of paths.readOnly) {
+    if (fs.existsSync(p)) binds.push('--ro-bind', p, p);
+  }
+  return [
+    '--ro-bind',
+    '/',
+    '/',
+    '--dev-bind',
+    '/dev',
+    '/dev',
+    '--proc',
+    '/proc',
+    ...binds,
+    '--die-with-parent',
+    '--',
+    command,
+    ...args,
+  ];
+}
+
+function hasCommand(command: string): boolean {
+  try {
+    execFileSync('which', [command], { encoding: 'utf8', timeout: 3000 });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Wrap an agent command so it can only write inside its task (macOS
+ * `sandbox-exec`, Linux `bwrap`). Throws when the platform has no supported
+ * sandbox; running unsandboxed when one was asked for would be worse.
+ */
+export function wrapInSandbox(
+  command: string,
+  args: string[],
+  cwd: string,
+  extraWritable: string[] = [],
+): { command: string; args: string[] } {
+  const paths = sandboxPaths(cwd, os.homedir(), extraWritable);
+  if (process.platform === 'darwin') {
+    return {
+      command: '/usr/bin/sandbox-exec',
+      args: ['-p', seatbeltProfile(paths), command, ...args],
+    };
+  }
+  if (process.platform === 'linux') {
+    if (!hasCommand('bwrap')) {
+      throw new Error(
+        'Agent sandboxing needs bubblewrap (bwrap). Install it or turn off sandbox_agents.',
+      );
+    }
+    // bubblewrap can only bind what exists, so one the agent could create is made empty first
+    for (const p of paths.readOnly) {
+      if (fs.existsSync(p)) continue;
+      if (path.basename(p) === 'hooks') fs.mkdirSync(p, { recursive: true });
+      else fs.writeFileSync(p, '', { flag: 'wx' });
+    }
+    return { command: 'bwrap', args: bwrapArgs(paths, command, args) };
+  }
+  throw new Error(`Agent sandboxing is not supported on ${process.platform}`);
+}
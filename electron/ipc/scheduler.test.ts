@@ -0,0 +1,114 @@
+import { afterEach, describe, expect, it, vi } from 'vitest';
+
+const spawnMock = vi.hoisted(() =>
+  vi.fn(() => ({ stdout: null, stderr: null, on: vi.fn(), kill: vi.fn() })),
+);
+vi.mock('child_process', () => ({ spawn: spawnMock, default: { spawn: spawnMock } }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+vi.mock('./settings.js', () => ({
+  getSettings: () => ({ sandbox_agents: false, container_sessions: true, max_concurrent_runs: 4 }),
+}));
+vi.mock('./notifications.js', () => ({ notify: vi.fn() }));
+vi.mock('./secrets.js', () => ({
+  secretEnv: () => ({ INJECTED_KEY: 'injected' }),
+  namedSecretEnv: (names: string[]) => Object.fromEntries(names.map((n) => [n, `stored ${n}`])),
+}));
+vi.mock('./ports.js', () => ({
+  ensureTaskPorts: () => ({ base: 20000, count: 10 }),
+  portEnv: () => ({}),
+}));
+vi.mock('./ssh.js', () => ({
+  remoteTargetFor: (p: string) => (p.startsWith('/ssh/') ? { destination: 'box' } : undefined),
+}));
+vi.mock('./containers.js', () => ({
+  findWorktreeRoot: (p: string) => p,
+  detectContainer: (root: string) => (root.startsWith('/devcontainer/') ? { image: 'node' } : null),
+}));
+vi.mock('./task-events.js', () => ({ recordTaskEvent: vi.fn() }));
+vi.mock('./checkpoints.js', () => ({ checkpointAfterTurn: vi.fn() }));
+
+import { cancelRun, enqueueRun, listRuns, runsToStart, type ScheduledRun } from './scheduler.js';
+
+function run(id: string, taskId: string, overrides: Partial<ScheduledRun> = {}): ScheduledRun {
+  return {
+    id,
+    task_id: taskId,
+    worktree_path: `/wt/${taskId}`,
+    agent_id: 'claude-code',
+    prompt: 'fix it',
+    env: {},
+    start_at: null,
+    status: 'queued',
+    created_at: 0,
+    started_at: null,
+    finished_at: null,
+    exit_code: null,
+    error: null,
+    output_tail: '',
+    ...overrides,
+  };
+}
+
+describe('runsToStart', () => {
+  it('fills the free slots in queue order', () => {
+    const runs = [run('a', 't1'), run('b', 't2'), run('c', 't3')];
+    expect(runsToStart(runs, 1000, 2).map((r) => r.id)).toEqual(['a', 'b']);
+  });
+
+  it('counts running runs against the limit and keeps one per task', () => {
+    const runs = [run('a', 't1', { status: 'running' }), run('b', 't1'), run('c', 't2')];
+    expect(runsToStart(runs, 1000, 2).map((r) => r.id)).toEqual(['c']);
+    expect(runsToStart(runs, 1000, 1)).toEqual([]);
+  });
+
+  it('waits for the start time', () => {
+    const runs = [run('a', 't1', { start_at: 5000 }), run('b', 't2', { status: 'done' })];
+    expect(runsToStart(runs, 1000, 2)).toEqual([]);
+    expect(runsToStart(runs, 5000, 2).map((r) => r.id)).toEqual(['a']);
+  });
+});
+
+describe('enqueueRun', () => {
+  afterEach(() => {
+    for (const r of listRuns()) cancelRun(r.id);
+    spawnMock.mockClear();
+  });
+
+  const queue = (worktreePath: string, secrets?: string[]) =>
+    enqueueRun({
+      taskId: worktreePath,
+      worktreePath,
+      agentId: 'claude-code',
+      prompt: 'fix it',
+      env: { TASK_VAR: 'task', API_KEY: 'from env' },
+      secrets,
+    });
+
+  it("gives the agent the task's env and the secrets it lists, like an interactive one", () => {
+    const queued = queue('/wt/secrets', ['API_KEY', 'DB_PASSWORD']);
+    expect(queued.status).toBe('running');
+    const [, , opts] = spawnMock.mock.calls[0] as unknown as [
+      string,
+      string[],
+      { cwd: string; env: Record<string, string> },
+    ];
+    expect(opts.cwd).toBe('/wt/secrets');
+    expect(opts.env).toMatchObject({
+      TASK_VAR: 'task',
+      INJECTED_KEY: 'injected',
+      DB_PASSWORD: 'stored DB_PASSWORD',
+      // The task's own value wins over a secret of the same name
+      API_KEY: 'from env',
+    });
+  });
+
+  it('refuses ssh projects and container sessions instead of running them locally', () => {
+    expect(() => queue('/ssh/box/repo')).toThrow('not supported in ssh projects');
+    expect(() => queue('/devcontainer/repo')).toThrow('not supported in projects with container');
+    expect(spawnMock).not.toHaveBeenCalled();
+    expect(listRuns().filter((r) => r.worktree_path !== '/wt/secrets')).toEqual([]);
+  });
+});
@@ -0,0 +1,312 @@
+import fs from 'fs';
+import path from 'path';
+import { randomUUID } from 'crypto';
+import { spawn, type ChildProcess } from 'child_process';
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { getStateDir } from './persistence.js';
+import { getSettings } from './settings.js';
+import { getAgentAdapter, headlessInvocation } from './adapters/index.js';
+import { buildSpawnEnv, getShellEnv, safeEnvOverridesFrom } from './shell-env.js';
+import { ensureTaskPorts, portEnv } from './ports.js';
+import { namedSecretEnv, secretEnv } from './secrets.js';
+import { wrapInSandbox } from './sandbox.js';
+import { remoteTargetFor } from './ssh.js';
+import { detectContainer, findWorktreeRoot } from './containers.js';
+import { recordTaskEvent } from './task-events.js';
+import { checkpointAfterTurn } from './checkpoints.js';
+import { notify } from './notifications.js';
+import { stripAnsi } from './ansi.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('scheduler');
+
+const TICK_MS = 30_000;
+const MAX_OUTPUT_TAIL_CHARS = 4_000;
+/** Finished runs kept in the list; older ones are dropped. */
+const MAX_FINISHED_RUNS = 200;
+
+export type ScheduledRunStatus = 'queued' | 'running' | 'done' | 'failed' | 'cancelled';
+
+/** A headless agent run in a task's worktree, queued to start when a slot is free. */
+export interface ScheduledRun {
+  id: string;
+  task_id: string;
+  worktree_path: string;
+  agent_id: string;
+  prompt: string;
+  env: Record<string, string>;
+  /** Stored secrets the task lists; absent on runs queued before they were passed. */
+  secrets?: string[];
+  /** Don't start before this time (ms since epoch); null starts as soon as possible. */
+  start_at: number | null;
+  status: ScheduledRunStatus;
+  created_at: number;
+  started_at: number | null;
+  finished_at: number | null;
+  exit_code: number | null;
+  error: string | null;
+  /** The last of the agent's output, without escape sequences. */
+  output_tail: string;
+}
+
+export interface EnqueueRunArgs {
+  taskId: string;
+  worktreePath: string;
+  agentId: string;
+  prompt: string;
+  env?: Record<string, string>;
+  secrets?: string[];
+  startAt?: number | null;
+}
+
+let win: BrowserWindow | null = null;
+let runs: ScheduledRun[] | null = null;
+const processes = new Map<string, ChildProcess>();
+let timer: ReturnType<typeof setInterval> | null = null;
+
+function storePath(): string {
+  return path.join(getStateDir(), 'scheduled-runs.json');
+}
+
+function load(): ScheduledRun[] {
+  if (runs) return runs;
+  try {
+    runs = JSON.parse(fs.readFileSync(storePath(), 'utf8')) as ScheduledRun[];
+  } catch {
+    runs = [];
+  }
+  // Their processes died with the previous app instance
+  for (const run of runs) {
+    if (run.status !== 'running') continue;
+    run.status = 'failed';
+    run.error = 'Interrupted when the app quit';
+    run.finished_at = Date.now();
+  }
+  return runs;
+}
+
+function save(): void {
+  try {
+    const file = storePath();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    const tmp = file + '.tmp';
+    fs.writeFileSync(tmp, JSON.stringify(runs ?? []), 'utf8');
+    fs.renameSync(tmp, file);
+  } catch (err) {
+    log.warn('Failed to save scheduled runs', err);
+  }
+}
+
+function changed(run: ScheduledRun): void {
+  save();
+  if (win && !win.isDestroyed()) win.webContents.send(IPC.ScheduledRunChanged, run);
+}
+
+/**
+ * Queued runs to start now, oldest first: due, within the free slots, and
+ * at most one per task, since two agents in one worktree would trip over
+ * each other.
+ */
+export function runsToStart(
+  all: ScheduledRun[],
+  now: number,
+  maxConcurrent: number,
+): ScheduledRun[] {
+  const busyTasks = new Set(all.filter((r) => r.status === 'running').map((r) => r.task_id));
+  let free = maxConcurrent - all.filter((r) => r.status === 'running').length;
+  const picked: ScheduledRun[] = [];
+  for (const run of all) {
+    if (free <= 0) break;
+    if (run.status !== 'queued' || (run.start_at !== null && run.start_at > now)) continue;
+    if (busyTasks.has(run.task_id)) continue;
+    busyTasks.add(run.task_id);
+    picked.push(run);
+    free--;
+  }
+  return picked;
+}
+
+function finish(run: ScheduledRun, status: ScheduledRunStatus, exitCode: number | null): void {
+  processes.delete(run.id);
+  if (run.status !== 'running') return;
+  run.status = status;
+  run.exit_code = exitCode;
+  run.finished_at = Date.now();
+  changed(run);
+  recordTaskEvent(run.task_id, 'run_finished', {
+    run_id: run.id,
+    agent_id: run.agent_id,
+    status,
+    exit_code: exitCode,
+  });
+  if (status !== 'cancelled') {
+    void checkpointAfterTurn(run.task_id, run.worktree_path, run.agent_id, 'run_finished');
+  }
+  if (status === 'done') {
+    notify('agent_finished', 'Scheduled run finished', run.prompt.slice(0, 120), run.task_id);
+  } else if (status === 'failed') {
+    const reason = run.error ?? `Exit code ${exitCode}`;
+    notify('agent_error', 'Scheduled run failed', reason, run.task_id);
+  }
+  // Not inline: start() can fail synchronously while pump() is looping
+  queueMicrotask(pump);
+}
+
+/**
+ * Why a headless run can't go in `worktreePath`, or null. Agents of ssh
+ * projects run on their host and container sessions in their image; runs
+ * are only spawned locally, so they'd run in the wrong place.
+ */
+function unsupportedWorktreeReason(worktreePath: string): string | null {
+  if (remoteTargetFor(worktreePath)) return 'Scheduled runs are not supported in ssh projects';
+  const root = getSettings().container_sessions ? findWorktreeRoot(worktreePath) : null;
+  if (root && detectContainer(root)) {
+    return 'Scheduled runs are not supported in projects with container sessions';
+  }
+  return null;
+}
+
+/** The environment a run's agent gets, as spawnAgent builds it for the task's agent. */
+function runEnv(run: ScheduledRun): Record<string, string> {
+  const env = buildSpawnEnv(process.env, getShellEnv(), {
+    ...portEnv(ensureTaskPorts(run.task_id)),
+    ...safeEnvOverridesFrom({
+      ...secretEnv(),
+      ...(run.secrets?.length ? namedSecretEnv(run.secrets) : {}),
+      ...run.env,
+    }),
+  });
+  delete env.CLAUDECODE;
+  delete env.CLAUDE_CODE_SESSION;
+  delete env.CLAUDE_CODE_ENTRYPOINT;
+  return env;
+}
+
+function start(run: ScheduledRun): void {
+  if (run.status !== 'queued') return;
+  run.status = 'running';
+  run.started_at = Date.now();
+  run.error = null;
+  try {
+    const adapter = getAgentAdapter(run.agent_id);
+    const invocation = adapter ? headlessInvocation(adapter, run.prompt) : null;
+    if (!invocation) throw new Error(`${run.agent_id} has no headless mode`);
+    // Queued before the project moved to ssh or containers
+    const unsupported = unsupportedWorktreeReason(run.worktree_path);
+    if (unsupported) throw new Error(unsupported);
+    const settings = getSettings();
+    const { command, args } = settings.sandbox_agents
+      ? wrapInSandbox(
+          invocation.command,
+          invocation.args,
+          run.worktree_path,
+          settings.sandbox_writable_paths,
+        )
+      : invocation;
+    const proc = spawn(command, args, {
+      cwd: run.worktree_path,
+      env: runEnv(run),
+      stdio: ['ignore', 'pipe', 'pipe'],
+    });
+    processes.set(run.id, proc);
+    const onOutput = (chunk: Buffer) => {
+      run.output_tail = (run.output_tail + stripAnsi(chunk.toString('utf8'))).slice(
+        -MAX_OUTPUT_TAIL_CHARS,
+      );
+    };
+    proc.stdout?.on('data', onOutput);
+    proc.stderr?.on('data', onOutput);
+    proc.on('error', (err) => {
+      run.error = err.message;
+      finish(run, 'failed', null);
+    });
+    proc.on('close', (code) => finish(run, code === 0 ? 'done' : 'failed', code));
+  } catch (err) {
+    run.error = err instanceof Error ? err.message : String(err);
+    finish(run, 'failed', null);
+    return;
+  }
+  changed(run);
+}
+
+/** Start whatever is due and fits under `max_concurrent_runs`. */
+function pump(): void {
+  const all = load();
+  for (const run of runsToStart(all, Date.now(), getSettings().max_concurrent_runs)) start(run);
+  const finished = all.filter((r) => r.status !== 'queued' && r.status !== 'running');
+  if (finished.length > MAX_FINISHED_RUNS) {
+    const drop = new Set(finished.slice(0, finished.length - MAX_FINISHED_RUNS));
+    runs = all.filter((r) => !drop.has(r));
+    save();
+  }
+}
+
+export function enqueueRun(args: EnqueueRunArgs): ScheduledRun {
+  const adapter = getAgentAdapter(args.agentId);
+  if (!adapter) throw new Error(`Unknown agent: ${args.agentId}`);
+  if (!adapter.headless_args) throw new Error(`${adapter.name} has no headless mode`);
+  const unsupported = unsupportedWorktreeReason(args.worktreePath);
+  if (unsupported) throw new Error(unsupported);
+  const run: ScheduledRun = {
+    id: randomUUID(),
+    task_id: args.taskId,
+    worktree_path: args.worktreePath,
+    agent_id: adapter.id,
+    prompt: args.prompt,
+    env: args.env ?? {},
+    ...(args.secrets?.length ? { secrets: args.secrets } : {}),
+    start_at: args.startAt ?? null,
+    status: 'queued',
+    created_at: Date.now(),
+    started_at: null,
+    finished_at: null,
+    exit_code: null,
+    error: null,
+    output_tail: '',
+  };
+  load().push(run);
+  changed(run);
+  pump();
+  return run;
+}
+
+export function listRuns(): ScheduledRun[] {
+  return load();
+}
+
+/** Drop a queued run, or stop a running one. */
+export function cancelRun(runId: string): void {
+  const run = load().find((r) => r.id === runId);
+  if (!run) throw new Error('Run not found');
+  if (run.status === 'queued') {
+    run.status = 'cancelled';
+    run.finished_at = Date.now();
+    changed(run);
+  } else if (run.status === 'running') {
+    processes.get(run.id)?.kill();
+    finish(run, 'cancelled', null);
+  }
+}
+
+/** Cancel a task's runs, e.g. when the task is deleted. */
+export function cancelTaskRuns(taskId: string): void {
+  for (const run of load()) {
+    if (run.task_id === taskId && (run.status === 'queued' || run.status === 'running')) {
+      cancelRun(run.id);
+    }
+  }
+}
+
+export function startScheduler(window: BrowserWindow): void {
+  win = window;
+  if (timer) return;
+  timer = setInterval(pump, TICK_MS);
+  pump();
+}
+
+export function stopScheduler(): void {
+  if (timer) clearInterval(timer);
+  timer = null;
+  for (const proc of processes.values()) proc.kill();
+}
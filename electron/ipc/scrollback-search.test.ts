@@ -0,0 +1,50 @@
+import { describe, it, expect } from 'vitest';
+import { scrollbackLines, searchScrollback, transcriptTail } from './scrollback-search.js';
+
+describe('scrollbackLines', () => {
+  it('strips escape sequences and keeps the last redraw of a line', () => {
+    const output = '\x1b[32mok\x1b[0m\r\n\x1b]0;title\x07progress 10%\rprogress 100%\r\ndone';
+    expect(scrollbackLines(output)).toEqual(['ok', 'progress 100%', 'done']);
+  });
+});
+
+describe('searchScrollback', () => {
+  const output = 'Error: first\r\nall good\r\nerror: second error\r\n';
+
+  it('finds plain text case-insensitively with offsets from the end', () => {
+    const result = searchScrollback(output, 'error');
+    expect(result.total_lines).toBe(4);
+    expect(result.matches.map((m) => [m.line, m.lines_from_end, m.column])).toEqual([
+      [0, 3, 0],
+      [2, 1, 0],
+      [2, 1, 14],
+    ]);
+  });
+
+  it('treats the query literally unless regex is set', () => {
+    expect(searchScrollback(output, 'e.ror').matches).toHaveLength(0);
+    expect(searchScrollback(output, 'e.ror', { regex: true }).matches).toHaveLength(3);
+    expect(searchScrollback(output, 'Error', { caseSensitive: true }).matches).toHaveLength(1);
+  });
+
+  it('keeps the newest matches when truncated', () => {
+    const result = searchScrollback(output, 'error', { limit: 2 });
+    expect(result.truncated).toBe(true);
+    expect(result.matches.map((m) => m.column)).toEqual([0, 14]);
+    expect(result.matches.every((m) => m.line === 2)).toBe(true);
+  });
+
+  it('skips empty matches and rejects bad patterns', () => {
+    expect(searchScrollback('abc', 'x*', { regex: true }).matches).toEqual([]);
+    expect(() => searchScrollback('abc', '(', { regex: true })).toThrow('Invalid regex');
+    expect(() => searchScrollback('abc', '')).toThrow('query must not be empty');
+  });
+});
+
+describe('transcriptTail', () => {
+  it('keeps the last lines as plain text with blank runs collapsed', () => {
+    const output = '\x1b[32mone\x1b[0m\r\ntwo\r\n\r\n\r\n\r\nthinking…\rdone\r\n';
+    expect(transcriptTail(output, 60)).toBe('one\ntwo\n\ndone');
+    expect(transcriptTail(output, 2)).toBe('done');
+  });
+});
@@ -0,0 +1,97 @@
+import { stripAnsi } from './ansi.js';
+
+const MAX_MATCHES = 500;
+const MAX_QUERY_CHARS = 1000;
+const MAX_LINE_CHARS = 500;
+const MAX_TRANSCRIPT_CHARS = 6_000;
+
+export interface ScrollbackMatch {
+  /** Line index in the searched scrollback, oldest first. */
+  line: number;
+  /** Lines between this one and the newest line; 0 is the last line. */
+  lines_from_end: number;
+  column: number;
+  length: number;
+  /** The matching line, without escape sequences. */
+  text: string;
+}
+
+export interface ScrollbackSearchResult {
+  matches: ScrollbackMatch[];
+  total_lines: number;
+  /** True when more matches exist than were returned. */
+  truncated: boolean;
+}
+
+/**
+ * Terminal output as plain lines: escape sequences removed and, where a
+ * line was redrawn with `\r`, only what was drawn last.
+ */
+export function scrollbackLines(output: string): string[] {
+  return stripAnsi(output)
+    .split('\n')
+    .map((line) => {
+      const parts = line.replace(/\r+$/, '').split('\r');
+      return parts[parts.length - 1];
+    });
+}
+
+/** The last `maxLines` lines of terminal output as plain text, blank runs collapsed. */
+export function transcriptTail(output: string, maxLines: number): string {
+  const lines = scrollbackLines(output).map((l) => l.trimEnd());
+  const kept = lines.filter((line, i) => line || (i > 0 && lines[i - 1]));
+  return kept.slice(-maxLines).join('\n').trim().slice(-MAX_TRANSCRIPT_CHARS);
+}
+
+function compileQuery(query: string, regex: boolean, caseSensitive: boolean): RegExp {
+  if (!query) throw new Error('query must not be empty');
+  if (query.length > MAX_QUERY_CHARS) throw new Error('query is too long');
+  const source = regex ? query : query.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+  try {
+    return new RegExp(source, caseSensitive ? 'g' : 'gi');
+  } catch (err) {
+    throw new Error(`Invalid regex: ${err instanceof Error ? err.message : String(err)}`);
+  }
+}
+
+/** Find `query` in terminal output, newest matches last. */
+export function searchScrollback(
+  output: string,
+  query: string,
+  opts: { regex?: boolean; caseSensitive?: boolean; limit?: number } = {},
+): ScrollbackSearchResult {
+  const pattern = compileQuery(query, opts.regex ?? false, opts.caseSensitive ?? false);
+  const limit = Math.min(opts.limit ?? MAX_MATCHES, MAX_MATCHES);
+  const lines = scrollbackLines(output);
+  // Keep the newest matches when there are too many; those are what users look for
+  const matches: ScrollbackMatch[] = [];
+  let truncated = false;
+
+  for (let i = lines.length - 1; i >= 0; i--) {
+    const text = lines[i];
+    const found: ScrollbackMatch[] = [];
+    pattern.lastIndex = 0;
+    for (let m = pattern.exec(text); m; m = pattern.exec(text)) {
+      if (m[0].length === 0) {
+        pattern.lastIndex++;
+        continue;
+      }
+      found.push({
+        line: i,
+        lines_from_end: lines.length - 1 - i,
+        column: m.index,
+        length: m[0].length,
+        text: text.slice(0, MAX_LINE_CHARS),
+      });
+    }
+    for (let j = found.length - 1; j >= 0; j--) {
+      if (matches.length === limit) {
+        truncated = true;
+        break;
+      }
+      matches.push(found[j]);
+    }
+    if (truncated) break;
+  }
+  return { matches: matches.reverse(), total_lines: lines.length, truncated };
+}
@@ -0,0 +1,41 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+const stateDir = vi.hoisted(() => ({ path: '' }));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => stateDir.path,
+}));
+
+import { createScrollbackSpill } from './scrollback-spill.js';
+
+stateDir.path = fs.mkdtempSync(path.join(os.tmpdir(), 'scrollback-spill-'));
+
+afterAll(() => fs.rmSync(stateDir.path, { recursive: true, force: true }));
+
+describe('createScrollbackSpill', () => {
+  it('returns everything written, oldest first, across flushes', () => {
+    const spill = createScrollbackSpill('agent-1');
+    const chunk = Buffer.alloc(40 * 1024, 'a');
+    spill.write(chunk);
+    spill.write(chunk);
+    spill.write(Buffer.from('tail'));
+    expect(spill.bytes).toBe(80 * 1024 + 4);
+    const data = spill.read();
+    expect(data.length).toBe(80 * 1024 + 4);
+    expect(data.subarray(-4).toString()).toBe('tail');
+    expect(fs.readdirSync(path.join(stateDir.path, 'scrollback'))).toHaveLength(1);
+  });
+
+  it('deletes its files on close and ignores later writes', () => {
+    const spill = createScrollbackSpill('agent-2');
+    spill.write(Buffer.alloc(64 * 1024, 'b'));
+    spill.close();
+    spill.write(Buffer.alloc(64 * 1024, 'c'));
+    expect(spill.read().length).toBe(0);
+    const files = fs.readdirSync(path.join(stateDir.path, 'scrollback'));
+    expect(files.some((f) => f.startsWith('agent-2'))).toBe(false);
+  });
+});
@@ -0,0 +1,105 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('scrollback-spill');
+
+/** Evicted output is buffered up to this before it is appended to the file. */
+const FLUSH_BYTES = 64 * 1024;
+/** A spill file past this starts over, keeping the previous one as `.old`. */
+const MAX_FILE_BYTES = 4 * 1024 * 1024;
+
+/**
+ * Output pushed out of a session's in-memory scrollback, kept on disk until
+ * the session ends so its transcript still covers the whole session.
+ */
+export interface ScrollbackSpill {
+  write(data: Buffer): void;
+  /** Bytes written to the spill, however many of them are still kept. */
+  readonly bytes: number;
+  /** Everything still kept, oldest first. */
+  read(): Buffer;
+  /** Delete the files; later writes are dropped. */
+  close(): void;
+}
+
+let leftoversCleared = false;
+
+function spillDir(): string {
+  return path.join(getStateDir(), 'scrollback');
+}
+
+/** Spills of a previous run belong to sessions that no longer exist. */
+function clearLeftovers(dir: string): void {
+  if (leftoversCleared) return;
+  leftoversCleared = true;
+  fs.rmSync(dir, { recursive: true, force: true });
+}
+
+export function createScrollbackSpill(agentId: string): ScrollbackSpill {
+  const dir = spillDir();
+  const file = path.join(dir, `${agentId.replace(/[^\w.-]+/g, '-')}-${Date.now()}.log`);
+  const oldFile = `${file}.old`;
+  let pending: Buffer[] = [];
+  let pendingBytes = 0;
+  let fileBytes = 0;
+  let total = 0;
+  let failed = false;
+  let closed = false;
+
+  const flush = () => {
+    if (pendingBytes === 0 || failed) return;
+    const data = Buffer.concat(pending);
+    pending = [];
+    pendingBytes = 0;
+    try {
+      if (fileBytes === 0) {
+        clearLeftovers(dir);
+        fs.mkdirSync(dir, { recursive: true });
+      }
+      if (fileBytes + data.length > MAX_FILE_BYTES && fileBytes > 0) {
+        fs.renameSync(file, oldFile);
+        fileBytes = 0;
+      }
+      fs.appendFileSync(file, data, { mode: 0o600 });
+      fileBytes += data.length;
+    } catch (err) {
+      // The transcript loses its oldest part; the session itself is unaffected
+      failed = true;
+      log.warn(`Could not spill scrollback to ${file}`, err);
+    }
+  };
+
+  const readFile = (p: string) => {
+    try {
+      return fs.readFileSync(p);
+    } catch {
+      return Buffer.alloc(0);
+    }
+  };
+
+  return {
+    write(data) {
+      if (closed) return;
+      total += data.length;
+      pending.push(Buffer.from(data));
+      pendingBytes += data.length;
+      if (pendingBytes >= FLUSH_BYTES) flush();
+    },
+    get bytes() {
+      return total;
+    },
+    read() {
+      flush();
+      return Buffer.concat([readFile(oldFile), readFile(file), ...pending]);
+    },
+    close() {
+      closed = true;
+      pending = [];
+      pendingBytes = 0;
+      fs.rmSync(file, { force: true });
+      fs.rmSync(oldFile, { force: true });
+    },
+  };
+}
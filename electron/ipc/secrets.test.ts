@@ -0,0 +1,68 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { beforeAll, describe, expect, it, vi } from 'vitest';
+
+const stateDir = vi.hoisted(() => ({ path: '' }));
+const keychain = vi.hoisted(() => ({ available: true }));
+
+vi.mock('electron', () => ({
+  safeStorage: {
+    isEncryptionAvailable: () => keychain.available,
+    getSelectedStorageBackend: () => 'gnome_libsecret',
+    encryptString: (s: string) => Buffer.from([...s].reverse().join(''), 'utf8'),
+    decryptString: (b: Buffer) => [...b.toString('utf8')].reverse().join(''),
+  },
+}));
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => stateDir.path,
+}));
+
+import {
+  deleteSecret,
+  getSecret,
+  hasSecret,
+  listSecrets,
+  secretEnv,
+  setSecret,
+  setSecretInjected,
+} from './secrets.js';
+
+describe('secrets', () => {
+  beforeAll(() => {
+    stateDir.path = fs.mkdtempSync(path.join(os.tmpdir(), 'secrets-test-'));
+  });
+
+  it('stores values encrypted and injects only the selected ones', () => {
+    setSecret('OPENAI_API_KEY', 'sk-123', true);
+    setSecret('GITHUB_TOKEN', 'ghp-456');
+
+    const onDisk = fs.readFileSync(path.join(stateDir.path, 'secrets.json'), 'utf8');
+    expect(onDisk).not.toContain('sk-123');
+    expect(getSecret('OPENAI_API_KEY')).toBe('sk-123');
+    expect(listSecrets().map((s) => [s.name, s.inject])).toEqual([
+      ['GITHUB_TOKEN', false],
+      ['OPENAI_API_KEY', true],
+    ]);
+    expect(secretEnv()).toEqual({ OPENAI_API_KEY: 'sk-123' });
+
+    setSecretInjected('GITHUB_TOKEN', true);
+    setSecret('OPENAI_API_KEY', 'sk-789');
+    expect(secretEnv()).toEqual({ OPENAI_API_KEY: 'sk-789', GITHUB_TOKEN: 'ghp-456' });
+
+    expect(hasSecret('GITHUB_TOKEN')).toBe(true);
+    deleteSecret('GITHUB_TOKEN');
+    expect(getSecret('GITHUB_TOKEN')).toBeNull();
+    expect(hasSecret('GITHUB_TOKEN')).toBe(false);
+  });
+
+  it('rejects names that are not env var names', () => {
+    expect(() => setSecret('MY-KEY', 'x')).toThrow('environment variable names');
+  });
+
+  it('refuses to store secrets without a keychain', () => {
+    keychain.available = false;
+    expect(() => setSecret('TOKEN', 'x')).toThrow('No OS keychain');
+    keychain.available = true;
+  });
+});
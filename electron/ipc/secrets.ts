@@ -0,0 +1,156 @@
+import { safeStorage } from 'electron';
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('secrets');
+
+/**
+ * API keys and tokens, encrypted with the OS keychain (Keychain on macOS,
+ * the Secret Service or KWallet on Linux) before they are written to disk.
+ * Values never leave the main process except in the environment of local
+ * agent sessions; the renderer only learns names.
+ */
+export interface SecretInfo {
+  /** Also the environment variable the secret is injected as. */
+  name: string;
+  /** Whether agent sessions get the secret in their environment. */
+  inject: boolean;
+  updated_at: number;
+}
+
+interface StoredSecret extends SecretInfo {
+  /** Base64 of the keychain-encrypted value. */
+  value: string;
+}
+
+let secrets: Map<string, StoredSecret> | null = null;
+
+function getSecretsPath(): string {
+  return path.join(getStateDir(), 'secrets.json');
+}
+
+function loadSecrets(): Map<string, StoredSecret> {
+  if (secrets) return secrets;
+  secrets = new Map();
+  try {
+    const raw = JSON.parse(fs.readFileSync(getSecretsPath(), 'utf8')) as StoredSecret[];
+    for (const s of raw) {
+      if (typeof s.name === 'string' && typeof s.value === 'string') secrets.set(s.name, s);
+    }
+  } catch {
+    // No secrets stored yet
+  }
+  return secrets;
+}
+
+function saveSecrets(all: Map<string, StoredSecret>): void {
+  const secretsPath = getSecretsPath();
+  fs.mkdirSync(path.dirname(secretsPath), { recursive: true });
+  const tmpPath = secretsPath + '.tmp';
+  fs.writeFileSync(tmpPath, JSON.stringify([...all.values()]), { encoding: 'utf8', mode: 0o600 });
+  fs.renameSync(tmpPath, secretsPath);
+}
+
+/**
+ * Refuse to store anything when there's no keychain to encrypt with. On
+ * Linux without a Secret Service Electron falls back to a hard-coded key,
+ * which is no better than plaintext.
+ */
+function assertKeychainAvailable(): void {
+  const available =
+    safeStorage.isEncryptionAvailable() &&
+    (process.platform !== 'linux' || safeStorage.getSelectedStorageBackend() !== 'basic_text');
+  if (!available) throw new Error('No OS keychain is available to encrypt secrets with');
+}
+
+function assertSecretName(name: string): void {
+  if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(name)) {
+    throw new Error(`Secret names must be environment variable names: ${name}`);
+  }
+}
+
+export function listSecrets(): SecretInfo[] {
+  return [...loadSecrets().values()]
+    .map(({ name, inject, updated_at }) => ({ name, inject, updated_at }))
+    .sort((a, b) => a.name.localeCompare(b.name));
+}
+
+/** Store or replace a secret. `inject` keeps its previous value when omitted. */
+export function setSecret(name: string, value: string, inject?: boolean): SecretInfo {
+  assertSecretName(name);
+  if (!value) throw new Error('Secret value is empty');
+  assertKeychainAvailable();
+  const all = loadSecrets();
+  const stored: StoredSecret = {
+    name,
+    inject: inject ?? all.get(name)?.inject ?? false,
+    updated_at: Date.now(),
+    value: safeStorage.encryptString(value).toString('base64'),
+  };
+  all.set(name, stored);
+  saveSecrets(all);
+  return { name, inject: stored.inject, updated_at: stored.updated_at };
+}
+
+/** Choose whether agent sessions get the secret, without touching its value. */
+export function setSecretInjected(name: string, inject: boolean): SecretInfo {
+  const all = loadSecrets();
+  const stored = all.get(name);
+  if (!stored) throw new Error(`Secret not found: ${name}`);
+  all.set(name, { ...stored, inject });
+  saveSecrets(all);
+  return { name, inject, updated_at: stored.updated_at };
+}
+
+export function getSecret(name: string): string | null {
+  const stored = loadSecrets().get(name);
+  if (!stored) return null;
+  return safeStorage.decryptString(Buffer.from(stored.value, 'base64'));
+}
+
+export function hasSecret(name: string): boolean {
+  return loadSecrets().has(name);
+}
+
+export function deleteSecret(name: string): void {
+  const all = loadSecrets();
+  if (all.delete(name)) saveSecrets(all);
+}
+
+/**
+ * The secrets marked for injection, decrypted, as env vars for an agent
+ * session. A secret that can't be decrypted, e.g. after the keychain was
+ * reset, is left out rather than failing the spawn.
+ */
+export function secretEnv(): Record<string, string> {
+  return decryptInto([...loadSecrets().values()].filter((s) => s.inject));
+}
+
+/**
+ * The secrets called `names`, injected or not, for a session of a task that
+ * lists them. Unknown names are left out like undecryptable ones.
+ */
+export function namedSecretEnv(names: string[]): Record<string, string> {
+  const all = loadSecrets();
+  const listed: StoredSecret[] = [];
+  for (const name of names) {
+    const stored = all.get(name);
+    if (stored) listed.push(stored);
+    else log.warn(`Secret not found: ${name}`);
+  }
+  return decryptInto(listed);
+}
+
+function decryptInto(list: StoredSecret[]): Record<string, string> {
+  const env: Record<string, string> = {};
+  for (const s of list) {
+    try {
+      env[s.name] = safeStorage.decryptString(Buffer.from(s.value, 'base64'));
+    } catch (err) {
+      log.warn(`Failed to decrypt secret ${s.name}:`, err);
+    }
+  }
+  return env;
+}
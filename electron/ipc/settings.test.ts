@@ -0,0 +1,82 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import {
+  DEFAULT_SETTINGS,
+  getWorktreePath,
+  getWorktreeRoot,
+  normalizeSettings,
+  validateSettingsPatch,
+} from './settings.js';
+
+describe('validateSettingsPatch', () => {
+  it('accepts known keys with valid values', () => {
+    expect(validateSettingsPatch({ worktree_dir: '.trees', base_branch: null })).toEqual({
+      worktree_dir: '.trees',
+      base_branch: null,
+    });
+  });
+
+  it('rejects unknown keys', () => {
+    expect(() => validateSettingsPatch({ theme: 'dark' })).toThrow('Unknown setting: theme');
+  });
+
+  it('rejects invalid values', () => {
+    expect(() => validateSettingsPatch({ worktree_dir: '' })).toThrow();
+    expect(() => validateSettingsPatch({ base_branch: '--force' })).toThrow();
+    expect(() => validateSettingsPatch({ shell: 'bash; rm -rf /' })).toThrow();
+    expect(() => validateSettingsPatch(null)).toThrow('settings must be an object');
+    expect(() => validateSettingsPatch({ editor_templates: { helix: { folder: 'hx' } } })).toThrow(
+      'editor_templates.helix',
+    );
+    expect(() =>
+      validateSettingsPatch({ webhooks: [{ url: 'ftp://x', secret_name: '', events: [] }] }),
+    ).toThrow('webhooks[0].url');
+    expect(() =>
+      validateSettingsPatch({
+        webhooks: [{ url: 'https://x', secret_name: '', events: ['nope'] }],
+      }),
+    ).toThrow('webhooks[0].events');
+    expect(() =>
+      validateSettingsPatch({ webhooks: [{ url: 'https://x', secret: 'k', events: [] }] }),
+    ).toThrow('webhooks[0].secret would be stored in plain text');
+    expect(() =>
+      validateSettingsPatch({ webhooks: [{ url: 'https://x', secret_name: 'a b', events: [] }] }),
+    ).toThrow('webhooks[0].secret_name');
+    expect(() => validateSettingsPatch({ record_sessions: 'yes' })).toThrow('record_sessions');
+    expect(() => validateSettingsPatch({ init_submodules: 1 })).toThrow('init_submodules');
+    expect(() => validateSettingsPatch({ lfs_fetch: 'no' })).toThrow('lfs_fetch');
+    expect(() => validateSettingsPatch({ shell_init_commands: ['nvm use', ''] })).toThrow();
+    expect(() => validateSettingsPatch({ shell_init_commands: ['a\nb'] })).toThrow('single lines');
+    expect(() =>
+      validateSettingsPatch({ mcp_servers: { fs: { command: 'npx', args: [], env: {} } } }),
+    ).toThrow('mcp_servers.fs.project_roots');
+    expect(() => validateSettingsPatch({ sandbox_writable_paths: ['relative'] })).toThrow();
+    expect(() => validateSettingsPatch({ idle_after_minutes: -1 })).toThrow('idle_after_minutes');
+  });
+});
+
+describe('normalizeSettings', () => {
+  it('fills defaults and drops invalid stored values', () => {
+    expect(normalizeSettings({ shell: '/bin/zsh', worktree_dir: 42, extra: true })).toEqual({
+      ...DEFAULT_SETTINGS,
+      shell: '/bin/zsh',
+    });
+    expect(normalizeSettings('garbage')).toEqual(DEFAULT_SETTINGS);
+  });
+});
+
+describe('getWorktreeRoot', () => {
+  it('resolves the default relative to the project root', () => {
+    expect(getWorktreeRoot('/repo')).toBe('/repo/.worktrees');
+  });
+});
+
+describe('getWorktreePath', () => {
+  it('nests a directory per branch name part', () => {
+    expect(getWorktreePath('/repo', 'task/fix-login')).toBe('/repo/.worktrees/task/fix-login');
+  });
+});
@@ -0,0 +1,437 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+import { remoteTargetFor } from './ssh.js';
+
+export const NOTIFICATION_KINDS = [
+  'agent_finished',
+  'agent_waiting',
+  'agent_error',
+  'merge_conflict',
+  'git_operation_done',
+] as const;
+
+export type NotificationKind = (typeof NOTIFICATION_KINDS)[number];
+
+export const WEBHOOK_EVENTS = [
+  'task_created',
+  'agent_exited',
+  'task_merged',
+  'task_pushed',
+  'task_deleted',
+  'checks_failed',
+  'run_finished',
+] as const;
+
+export type WebhookEvent = (typeof WEBHOOK_EVENTS)[number];
+
+export interface WebhookConfig {
+  url: string;
+  /**
+   * Name of the stored secret (see secrets.ts) that signs each body, so the
+   * key stays out of this file; receivers check `X-Parallel-Code-Signature`.
+   * Empty sends unsigned.
+   */
+  secret_name: string;
+  /** Events to send. Empty sends all of them. */
+  events: WebhookEvent[];
+}
+
+export interface McpServerConfig {
+  command: string;
+  args: string[];
+  env: Record<string, string>;
+  /** Project roots the server is added to. Empty adds it to every project. */
+  project_roots: string[];
+}
+
+/** Backend settings, persisted to `settings.json` in the app state dir. */
+export interface Settings {
+  /** Where task worktrees live: relative to the project root, or absolute. */
+  worktree_dir: string;
+  /** Branch tasks fork from and merge into. `null` auto-detects it. */
+  base_branch: string | null;
+  /** Agent preselected for new tasks. `null` uses the last one picked. */
+  default_agent_id: string | null;
+  /** Shell for plain terminals. `null` uses `$SHELL`. */
+  shell: string | null;
+  /** Start plain terminals as login shells, so they load the profile (PATH, nvm, pyenv). */
+  shell_login: boolean;
+  /** Commands run in each new plain terminal once the shell has started. */
+  shell_init_commands: string[];
+  /** Events that raise a desktop notification. All of them go to the inbox. */
+  desktop_notifications: NotificationKind[];
+  /**
+   * Editor command templates by name, overriding or adding to the presets
+   * (`vscode`, `cursor`, `zed`, `jetbrains`).
+   */
+  editor_templates: Record<string, { folder: string; file: string }>;
+  /** Port of the localhost automation API. `null` keeps it off. */
+  automation_api_port: number | null;
+  /** Endpoints that get a JSON POST on task lifecycle events. */
+  webhooks: WebhookConfig[];
+  /** Record terminal sessions as asciicast files in the state dir. */
+  record_sessions: boolean;
+  /** Minutes without output or input before an agent counts as idle. 0 turns it off. */
+  idle_after_minutes: number;
+  /** Minutes without output or input before an agent counts as hung. 0 turns it off. */
+  hung_after_minutes: number;
+  /** Run agents so they can only write inside their task worktree (and their own state). */
+  sandbox_agents: boolean;
+  /** Extra paths sandboxed agents may write to. */
+  sandbox_writable_paths: string[];
+  /**
+   * Run agents and terminals in the container of the worktree's
+   * devcontainer config or Dockerfile, when it has one.
+   */
+  container_sessions: boolean;
+  /** Container CLI, `docker` or a compatible one such as `podman`. */
+  container_runtime: string;
+  /** MCP servers by name, written into each new worktree's agent config files. */
+  mcp_servers: Record<string, McpServerConfig>;
+  /** Scheduled headless agent runs allowed at once. */
+  max_concurrent_runs: number;
+  /** Agent sessions allowed at once across all tasks; terminals don't count. 0 means no limit. */
+  max_agent_sessions: number;
+  /** Agent sessions allowed at once in one task. 0 means no limit. */
+  max_agents_per_task: number;
+  /** Most scrollback one session keeps in memory, in KB. */
+  scrollback_session_kb: number;
+  /** Scrollback memory shared by all sessions, in MB; sessions get less as more run. */
+  scrollback_budget_mb: number;
+  /** Commit each task's worktree after every agent turn, so each one can be diffed and restored. */
+  auto_checkpoint: boolean;
+  /** Seconds an agent has to be quiet before its turn counts as finished. */
+  checkpoint_quiet_seconds: number;
+  /** Days deleted tasks stay restorable; 0 keeps no trash. */
+  trash_retention_days: number;
+  /** Check out submodules in new worktrees of repos that have a `.gitmodules`. */
+  init_submodules: boolean;
+  /**
+   * Download Git LFS files into new worktrees. Off leaves pointer files,
+   * which is faster when agents don't need the binaries.
+   */
+  lfs_fetch: boolean;
+  /**
+   * Make a repo's hooks run in new worktrees when they wouldn't, e.g. a
+   * relative `core.hooksPath` such as Husky's `.husky/_`, which `npm install`
+   * creates in the main checkout only. `copy` copies the hooks directory into
+   * the worktree; `hooks_path` points the worktree's `core.hooksPath` at the
+   * main checkout's. `off` leaves git's default.
+   */
+  worktree_hooks: WorktreeHooksMode;
+  /** Git config keys whose values in the main checkout are set on each new worktree. */
+  worktree_git_config: string[];
+  /**
+   * New task branches track `origin/<base>`, so `git status` and `@{u}`
+   * compare them with the remote base, while the worktree's
+   * `push.default=current` keeps `git push` going to a branch of their own name.
+   */
+  track_base_upstream: boolean;
+}
+
+export const WORKTREE_HOOKS_MODES = ['off', 'copy', 'hooks_path'] as const;
+export type WorktreeHooksMode = (typeof WORKTREE_HOOKS_MODES)[number];
+
+export const DEFAULT_SETTINGS: Settings = {
+  worktree_dir: '.worktrees',
+  base_branch: null,
+  default_agent_id: null,
+  shell: null,
+  shell_login: true,
+  shell_init_commands: [],
+  desktop_notifications: [...NOTIFICATION_KINDS],
+  editor_templates: {},
+  automation_api_port: null,
+  webhooks: [],
+  record_sessions: false,
+  idle_after_minutes: 10,
+  hung_after_minutes: 0,
+  sandbox_agents: false,
+  sandbox_writable_paths: [],
+  container_sessions: false,
+  container_runtime: 'docker',
+  mcp_servers: {},
+  max_concurrent_runs: 2,
+  max_agent_sessions: 0,
+  max_agents_per_task: 0,
+  scrollback_session_kb: 1024,
+  scrollback_budget_mb: 64,
+  auto_checkpoint: false,
+  checkpoint_quiet_seconds: 30,
+  trash_retention_days: 7,
+  init_submodules: true,
+  lfs_fetch: true,
+  worktree_hooks: 'off',
+  worktree_git_config: [],
+  track_base_upstream: false,
+};
+
+/** `section.name` or `section.subsection.name`. */
+const GIT_CONFIG_KEY = /^[A-Za-z][\w-]*(\.[^\s=]+)?\.[A-Za-z][\w-]*$/;
+
+type Validators = { [K in keyof Settings]: (v: unknown) => string | null };
+
+function nullableString(label: string) {
+  return (v: unknown) =>
+    v === null || typeof v === 'string' ? null : `${label} must be a string or null`;
+}
+
+/** Each validator returns an error message, or null when the value is acceptable. */
+const VALIDATORS: Validators = {
+  worktree_dir: (v) => {
+    if (typeof v !== 'string' || !v.trim()) return 'worktree_dir must be a non-empty string';
+    if (v.split(/[\\/]/).includes('..')) return 'worktree_dir must not contain ".."';
+    return null;
+  },
+  base_branch: (v) => {
+    if (v === null) return null;
+    if (typeof v !== 'string' || !v.trim()) {
+      return 'base_branch must be a non-empty string or null';
+    }
+    if (v.startsWith('-')) return 'base_branch must not start with "-"';
+    return null;
+  },
+  default_agent_id: nullableString('default_agent_id'),
+  shell: (v) => {
+    if (v === null) return null;
+    if (typeof v !== 'string' || !v.trim()) return 'shell must be a non-empty string or null';
+    if (/[;&|`$(){}\n]/.test(v)) return 'shell must not contain shell metacharacters';
+    return null;
+  },
+  shell_login: (v) => (typeof v === 'boolean' ? null : 'shell_login must be a boolean'),
+  shell_init_commands: (v) => {
+    if (!Array.isArray(v) || !v.every((c) => typeof c === 'string' && c.trim())) {
+      return 'shell_init_commands must be a list of non-empty strings';
+    }
+    if (v.some((c) => /[\r\n]/.test(c))) {
+      return 'shell_init_commands entries must be single lines';
+    }
+    return null;
+  },
+  desktop_notifications: (v) => {
+    const kinds: readonly unknown[] = NOTIFICATION_KINDS;
+    if (!Array.isArray(v) || !v.every((k) => kinds.includes(k))) {
+      return `desktop_notifications must be a list of: ${NOTIFICATION_KINDS.join(', ')}`;
+    }
+    return null;
+  },
+  editor_templates: (v) => {
+    if (!v || typeof v !== 'object' || Array.isArray(v)) {
+      return 'editor_templates must be an object';
+    }
+    for (const [name, t] of Object.entries(v as Record<string, unknown>)) {
+      const template = t as { folder?: unknown; file?: unknown } | null;
+      const valid =
+        template &&
+        typeof template.folder === 'string' &&
+        template.folder.trim() &&
+        typeof template.file === 'string' &&
+        template.file.trim();
+      if (!valid) return `editor_templates.${name} must have non-empty folder and file templates`;
+    }
+    return null;
+  },
+  automation_api_port: (v) => {
+    if (v === null) return null;
+    if (typeof v !== 'number' || !Number.isInteger(v) || v < 1024 || v > 65535) {
+      return 'automation_api_port must be a port between 1024 and 65535, or null';
+    }
+    return null;
+  },
+  webhooks: (v) => {
+    if (!Array.isArray(v)) return 'webhooks must be a list';
+    const events: readonly unknown[] = WEBHOOK_EVENTS;
+    for (const [i, hook] of (v as Array<Partial<Record<keyof WebhookConfig, unknown>>>).entries()) {
+      if (!hook || typeof hook !== 'object') return `webhooks[${i}] must be an object`;
+      if (typeof hook.url !== 'string' || !/^https?:\/\//.test(hook.url)) {
+        return `webhooks[${i}].url must be an http(s) URL`;
+      }
+      if ('secret' in hook) {
+        return `webhooks[${i}].secret would be stored in plain text; use secret_name`;
+      }
+      const name = hook.secret_name;
+      if (typeof name !== 'string' || (name !== '' && !/^[A-Za-z_][A-Za-z0-9_]*$/.test(name))) {
+        return `webhooks[${i}].secret_name must name a stored secret, or be empty`;
+      }
+      if (!Array.isArray(hook.events) || !hook.events.every((e) => events.includes(e))) {
+        return `webhooks[${i}].events must be a list of: ${WEBHOOK_EVENTS.join(', ')}`;
+      }
+    }
+    return null;
+  },
+  record_sessions: (v) => (typeof v === 'boolean' ? null : 'record_sessions must be a boolean'),
+  idle_after_minutes: (v) =>
+    typeof v === 'number' && Number.isFinite(v) && v >= 0
+      ? null
+      : 'idle_after_minutes must be a non-negative number',
+  hung_after_minutes: (v) =>
+    typeof v === 'number' && Number.isFinite(v) && v >= 0
+      ? null
+      : 'hung_after_minutes must be a non-negative number',
+  sandbox_agents: (v) => (typeof v === 'boolean' ? null : 'sandbox_agents must be a boolean'),
+  max_concurrent_runs: (v) =>
+    typeof v === 'number' && Number.isInteger(v) && v >= 1
+      ? null
+      : 'max_concurrent_runs must be a positive integer',
+  max_agent_sessions: (v) =>
+    typeof v === 'number' && Number.isInteger(v) && v >= 0
+      ? null
+      : 'max_agent_sessions must be a non-negative integer',
+  max_agents_per_task: (v) =>
+    typeof v === 'number' && Number.isInteger(v) && v >= 0
+      ? null
+      : 'max_agents_per_task must be a non-negative integer',
+  scrollback_session_kb: (v) =>
+    typeof v === 'number' && Number.isInteger(v) && v >= 64
+      ? null
+      : 'scrollback_session_kb must be an integer of at least 64',
+  scrollback_budget_mb: (v) =>
+    typeof v === 'number' && Number.isInteger(v) && v >= 1
+      ? null
+      : 'scrollback_budget_mb must be a positive integer',
+  auto_checkpoint: (v) => (typeof v === 'boolean' ? null : 'auto_checkpoint must be a boolean'),
+  checkpoint_quiet_seconds: (v) =>
+    typeof v === 'number' && Number.isFinite(v) && v >= 5
+      ? null
+      : 'checkpoint_quiet_seconds must be a number of at least 5',
+  trash_retention_days: (v) =>
+    typeof v === 'number' && Number.isFinite(v) && v >= 0
+      ? null
+      : 'trash_retention_days must be a non-negative number',
+  init_submodules: (v) => (typeof v === 'boolean' ? null : 'init_submodules must be a boolean'),
+  lfs_fetch: (v) => (typeof v === 'boolean' ? null : 'lfs_fetch must be a boolean'),
+  worktree_hooks: (v) =>
+    (WORKTREE_HOOKS_MODES as readonly unknown[]).includes(v)
+      ? null
+      : `worktree_hooks must be one of ${WORKTREE_HOOKS_MODES.join(', ')}`,
+  worktree_git_config: (v) =>
+    Array.isArray(v) && v.every((k) => typeof k === 'string' && GIT_CONFIG_KEY.test(k))
+      ? null
+      : 'worktree_git_config must be a list of git config keys such as user.email',
+  track_base_upstream: (v) =>
+    typeof v === 'boolean' ? null : 'track_base_upstream must be a boolean',
+  sandbox_writable_paths: (v) => {
+    if (!Array.isArray(v) || !v.every((p) => typeof p === 'string' && path.isAbsolute(p))) {
+      return 'sandbox_writable_paths must be a list of absolute paths';
+    }
+    return null;
+  },
+  container_sessions: (v) =>
+    typeof v === 'boolean' ? null : 'container_sessions must be a boolean',
+  container_runtime: (v) =>
+    typeof v === 'string' && /^[\w./-]+$/.test(v)
+      ? null
+      : 'container_runtime must be a command name or path',
+  mcp_servers: (v) => {
+    if (!v || typeof v !== 'object' || Array.isArray(v)) return 'mcp_servers must be an object';
+    const isStrings = (a: unknown) => Array.isArray(a) && a.every((x) => typeof x === 'string');
+    for (const [name, raw] of Object.entries(v as Record<string, unknown>)) {
+      const s = raw as Partial<Record<keyof McpServerConfig, unknown>> | null;
+      if (!s || typeof s !== 'object') return `mcp_servers.${name} must be an object`;
+      if (typeof s.command !== 'string' || !s.command.trim()) {
+        return `mcp_servers.${name}.command must be a non-empty string`;
+      }
+      if (!isStrings(s.args)) return `mcp_servers.${name}.args must be a list of strings`;
+      const env = s.env as Record<string, unknown> | null;
+      const envValid =
+        !!env && typeof env === 'object' && Object.values(env).every((x) => typeof x === 'string');
+      if (!envValid) {
+        return `mcp_servers.${name}.env must map names to strings`;
+      }
+      if (!isStrings(s.project_roots)) {
+        return `mcp_servers.${name}.project_roots must be a list of paths`;
+      }
+    }
+    return null;
+  },
+};
+
+function isSettingsKey(key: string): key is keyof Settings {
+  return Object.prototype.hasOwnProperty.call(VALIDATORS, key);
+}
+
+/** Validate a partial settings object. Throws on unknown keys or bad values. */
+export function validateSettingsPatch(patch: unknown): Partial<Settings> {
+  if (!patch || typeof patch !== 'object' || Array.isArray(patch)) {
+    throw new Error('settings must be an object');
+  }
+  const result: Record<string, unknown> = {};
+  for (const [key, value] of Object.entries(patch)) {
+    if (!isSettingsKey(key)) throw new Error(`Unknown setting: ${key}`);
+    const err = VALIDATORS[key](value);
+    if (err) throw new Error(err);
+    result[key] = value;
+  }
+  return result as Partial<Settings>;
+}
+
+/** Merge stored values over defaults, dropping anything invalid. */
+export function normalizeSettings(raw: unknown): Settings {
+  const settings: Settings = { ...DEFAULT_SETTINGS };
+  if (!raw || typeof raw !== 'object' || Array.isArray(raw)) return settings;
+  const target = settings as unknown as Record<string, unknown>;
+  for (const [key, value] of Object.entries(raw)) {
+    if (isSettingsKey(key) && VALIDATORS[key](value) === null) target[key] = value;
+  }
+  return settings;
+}
+
+// --- Storage ---
+
+let cached: Settings | null = null;
+type SettingsListener = (settings: Settings) => void;
+const listeners = new Set<SettingsListener>();
+
+function getSettingsPath(): string {
+  return path.join(getStateDir(), 'settings.json');
+}
+
+export function getSettings(): Settings {
+  if (cached) return cached;
+  try {
+    cached = normalizeSettings(JSON.parse(fs.readFileSync(getSettingsPath(), 'utf8')));
+  } catch {
+    // Missing or unreadable file (or no app paths yet, e.g. in tests) — use defaults
+    cached = { ...DEFAULT_SETTINGS };
+  }
+  return cached;
+}
+
+export function updateSettings(patch: unknown): Settings {
+  const next: Settings = { ...getSettings(), ...validateSettingsPatch(patch) };
+  const settingsPath = getSettingsPath();
+  fs.mkdirSync(path.dirname(settingsPath), { recursive: true });
+  const tmpPath = settingsPath + '.tmp';
+  fs.writeFileSync(tmpPath, JSON.stringify(next, null, 2) + '\n', 'utf8');
+  fs.renameSync(tmpPath, settingsPath);
+  cached = next;
+  for (const fn of listeners) fn(next);
+  return next;
+}
+
+/** Register a listener for settings changes. Returns an unsubscribe function. */
+export function onSettingsChanged(listener: SettingsListener): () => void {
+  listeners.add(listener);
+  return () => {
+    listeners.delete(listener);
+  };
+}
+
+/** Absolute directory holding the worktrees of a project. */
+export function getWorktreeRoot(repoRoot: string): string {
+  const dir = getSettings().worktree_dir;
+  // A remote project's worktrees stay on its machine, next to the repo
+  if (path.isAbsolute(dir) && remoteTargetFor(repoRoot)) {
+    return path.join(repoRoot, DEFAULT_SETTINGS.worktree_dir);
+  }
+  // A shared absolute location is namespaced per repo to avoid collisions
+  return path.isAbsolute(dir)
+    ? path.join(dir, path.basename(repoRoot))
+    : path.join(repoRoot, dir);
+}
+
+/** Absolute path of a branch's worktree in a project. */
+export function getWorktreePath(repoRoot: string, branchName: string): string {
+  return path.join(getWorktreeRoot(repoRoot), branchName);
+}
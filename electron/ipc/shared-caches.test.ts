@@ -0,0 +1,77 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { execFileSync } from 'child_process';
+import { afterAll, describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { pnpmStoreDir, shareNodeModules } from './shared-caches.js';
+
+const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'shared-caches-'));
+const repo = path.join(dir, 'repo');
+const worktree = path.join(dir, 'worktree');
+
+function write(rel: string, content: string): void {
+  const file = path.join(repo, rel);
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, content);
+}
+
+afterAll(() => {
+  fs.rmSync(dir, { recursive: true, force: true });
+});
+
+describe('shareNodeModules', () => {
+  it('gives the worktree the packages of the main checkout', async () => {
+    write('node_modules/pkg/index.js', 'module.exports = 1;');
+    write('node_modules/pkg/package.json', '{}');
+    fs.mkdirSync(path.join(repo, 'node_modules/.bin'));
+    fs.symlinkSync('../pkg/index.js', path.join(repo, 'node_modules/.bin/pkg'));
+    fs.mkdirSync(worktree);
+
+    const shared = await shareNodeModules(repo, worktree);
+    expect(shared?.files).toBe(2);
+    const target = path.join(worktree, 'node_modules');
+    expect(fs.readFileSync(path.join(target, 'pkg/index.js'), 'utf8')).toBe('module.exports = 1;');
+    expect(fs.readlinkSync(path.join(target, '.bin/pkg'))).toBe('../pkg/index.js');
+  });
+
+  it('leaves a worktree that has its own alone', async () => {
+    write('node_modules/other/index.js', '');
+    expect(await shareNodeModules(repo, worktree)).toBeNull();
+    expect(fs.existsSync(path.join(worktree, 'node_modules/other'))).toBe(false);
+  });
+
+  it('does nothing when the main checkout has none', async () => {
+    const bare = path.join(dir, 'bare');
+    fs.mkdirSync(bare);
+    expect(await shareNodeModules(bare, worktree)).toBeNull();
+  });
+});
+
+describe('pnpmStoreDir', () => {
+  const git = (cwd: string, ...args: string[]) =>
+    execFileSync('git', ['-c', 'user.name=t', '-c', 'user.email=t@t', ...args], { cwd });
+
+  it("finds the repo's git dir from a checkout whose .git is a file", () => {
+    const main = path.join(dir, 'main');
+    const linked = path.join(dir, 'linked');
+    fs.mkdirSync(main);
+    git(main, 'init', '-q');
+    git(main, 'commit', '-q', '--allow-empty', '-m', 'init');
+    git(main, 'worktree', 'add', '-q', linked);
+    expect(fs.statSync(path.join(linked, '.git')).isFile()).toBe(true);
+
+    const expected = path.join(fs.realpathSync(main), '.git', 'pnpm-store');
+    const store = pnpmStoreDir(linked) ?? '';
+    expect(path.join(fs.realpathSync(path.dirname(store)), 'pnpm-store')).toBe(expected);
+    expect(pnpmStoreDir(main)).toBe(path.join(main, '.git', 'pnpm-store'));
+  });
+
+  it('is null outside a git repository', () => {
+    expect(pnpmStoreDir(os.tmpdir())).toBeNull();
+  });
+});
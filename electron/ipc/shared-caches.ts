@@ -0,0 +1,107 @@
+import fs from 'fs';
+import path from 'path';
+import { createLogger } from './log.js';
+import { gitCommonDir } from './sandbox.js';
+
+const log = createLogger('shared-caches');
+
+/** How a tree was shared: copy-on-write clones where the filesystem can, else hard links. */
+export type ShareMode = 'cloned' | 'linked' | 'copied';
+
+export interface SharedTree {
+  mode: ShareMode;
+  files: number;
+}
+
+/** Errors that mean the filesystem can't clone or link, rather than that the file is bad. */
+const UNSUPPORTED = new Set(['ENOTSUP', 'EOPNOTSUPP', 'EXDEV', 'ENOSYS', 'EINVAL', 'EPERM']);
+
+function isUnsupported(err: unknown): boolean {
+  return UNSUPPORTED.has((err as NodeJS.ErrnoException).code ?? '');
+}
+
+/**
+ * Fill `target` with `source`'s files without copying their content where
+ * possible. Cloned files are independent copies; hard-linked ones are the
+ * same file, so a package install rewriting one in place changes both.
+ * Symlinks are recreated as they are.
+ */
+export async function shareTree(source: string, target: string): Promise<SharedTree> {
+  let mode: ShareMode = 'cloned';
+  let files = 0;
+
+  async function shareFile(src: string, dst: string): Promise<void> {
+    if (mode === 'cloned') {
+      try {
+        await fs.promises.copyFile(src, dst, fs.constants.COPYFILE_FICLONE_FORCE);
+        return;
+      } catch (err) {
+        if (!isUnsupported(err)) throw err;
+        mode = 'linked';
+      }
+    }
+    if (mode === 'linked') {
+      try {
+        await fs.promises.link(src, dst);
+        return;
+      } catch (err) {
+        if (!isUnsupported(err)) throw err;
+        mode = 'copied';
+      }
+    }
+    await fs.promises.copyFile(src, dst);
+  }
+
+  async function walk(src: string, dst: string): Promise<void> {
+    await fs.promises.mkdir(dst, { recursive: true });
+    for (const entry of await fs.promises.readdir(src, { withFileTypes: true })) {
+      const from = path.join(src, entry.name);
+      const to = path.join(dst, entry.name);
+      if (entry.isDirectory()) {
+        await walk(from, to);
+      } else if (entry.isSymbolicLink()) {
+        await fs.promises.symlink(await fs.promises.readlink(from), to);
+      } else if (entry.isFile()) {
+        await shareFile(from, to);
+        files++;
+      }
+    }
+  }
+
+  await walk(source, target);
+  return { mode, files };
+}
+
+/**
+ * Give a new worktree the main checkout's `node_modules`, so its agents can
+ * run tests without installing first. Leaves a worktree that already has
+ * one alone; null when there was nothing to share.
+ */
+export async function shareNodeModules(
+  repoRoot: string,
+  worktreePath: string,
+): Promise<SharedTree | null> {
+  const source = path.join(repoRoot, 'node_modules');
+  const target = path.join(worktreePath, 'node_modules');
+  if (!fs.existsSync(source) || fs.existsSync(target)) return null;
+  try {
+    const shared = await shareTree(source, target);
+    log.info(`Shared node_modules into ${worktreePath}: ${shared.files} files ${shared.mode}`);
+    return shared;
+  } catch (err) {
+    // Half a node_modules is worse than none: the agent would trust it
+    await fs.promises.rm(target, { recursive: true, force: true });
+    throw err;
+  }
+}
+
+/**
+ * The absolute directory a project's worktrees share as their pnpm store:
+ * `pnpm-store` in the repository's common git dir, which is on the same disk
+ * as every worktree even when the project's `.git` is a file. Null outside a
+ * git repository.
+ */
+export function pnpmStoreDir(projectRoot: string): string | null {
+  const commonDir = gitCommonDir(projectRoot);
+  return commonDir && path.join(commonDir, 'pnpm-store');
+}
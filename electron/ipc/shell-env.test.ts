@@ -0,0 +1,43 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { buildSpawnEnv, parseEnvOutput } from './shell-env.js';
+
+describe('parseEnvOutput', () => {
+  it('reads NUL-separated variables between sentinels', () => {
+    const output = 'Welcome!\nS|PATH=/opt/bin:/usr/bin\0MULTI=a\nb\0S|trailing noise';
+    expect(parseEnvOutput(output, 'S|')).toEqual({ PATH: '/opt/bin:/usr/bin', MULTI: 'a\nb' });
+  });
+
+  it('falls back to lines without NUL separators', () => {
+    expect(parseEnvOutput('S|\nA=1\nB=x=y\nS|', 'S|')).toEqual({ A: '1', B: 'x=y' });
+  });
+
+  it('drops per-shell variables and exported functions', () => {
+    const output = 'S|PWD=/tmp\0SHLVL=2\0BASH_FUNC_nvm%%=() { :; }\0NVM_DIR=/n\0S|';
+    expect(parseEnvOutput(output, 'S|')).toEqual({ NVM_DIR: '/n' });
+  });
+
+  it('returns nothing when the sentinels are missing', () => {
+    expect(parseEnvOutput('PATH=/usr/bin', 'S|')).toEqual({});
+  });
+});
+
+describe('buildSpawnEnv', () => {
+  it('layers the login shell and overrides over the app environment', () => {
+    const env = buildSpawnEnv(
+      { PATH: '/usr/bin', HOME: '/home/me', UNSET: undefined },
+      { PATH: '/home/me/.nvm/bin:/usr/bin', NVM_DIR: '/home/me/.nvm' },
+      { NVM_DIR: '/custom', TERM: 'xterm-256color' },
+    );
+    expect(env).toEqual({
+      PATH: '/home/me/.nvm/bin:/usr/bin',
+      HOME: '/home/me',
+      NVM_DIR: '/custom',
+      TERM: 'xterm-256color',
+    });
+  });
+});
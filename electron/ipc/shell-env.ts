@@ -0,0 +1,193 @@
+import fs from 'fs';
+import path from 'path';
+import { execFile, execFileSync } from 'child_process';
+import { getStateDir } from './persistence.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('shell-env');
+
+const SENTINEL = '__PCODE_ENV__';
+const RESOLVE_TIMEOUT_MS = 5000;
+const SNAPSHOT_FILE = 'shell-env.json';
+const MAX_BUFFER = 4 * 1024 * 1024;
+
+/** Per-shell or per-terminal variables that must not leak from the probe shell. */
+const VOLATILE_VARS = new Set([
+  '_',
+  'PWD',
+  'OLDPWD',
+  'SHLVL',
+  'TERM',
+  'TERM_PROGRAM',
+  'TERM_PROGRAM_VERSION',
+  'COLUMNS',
+  'LINES',
+  'PS1',
+  'PS2',
+  'TMUX',
+  'TMUX_PANE',
+]);
+
+let shellEnv: Record<string, string> = {};
+
+/**
+ * Parse `env -0` output framed by sentinels, so whatever the shell's init
+ * files print around it is ignored.
+ */
+export function parseEnvOutput(output: string, sentinel = SENTINEL): Record<string, string> {
+  const start = output.indexOf(sentinel);
+  const end = output.lastIndexOf(sentinel);
+  if (start === -1 || end <= start) return {};
+  const body = output.slice(start + sentinel.length, end);
+  // Without `env -0` support, fall back to lines (multi-line values get cut short)
+  const entries = body.includes('\0') ? body.split('\0') : body.split('\n');
+  const env: Record<string, string> = {};
+  for (const entry of entries) {
+    const eq = entry.indexOf('=');
+    if (eq <= 0) continue;
+    const key = entry.slice(0, eq);
+    // Exported shell functions (bash `BASH_FUNC_x%%`) aren't plain variables
+    if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(key) || VOLATILE_VARS.has(key)) continue;
+    env[key] = entry.slice(eq + 1);
+  }
+  return env;
+}
+
+function probeArgs(): string[] {
+  // -i -l so both the profile and rc files run; version managers hook into either
+  return ['-ilc', `printf '${SENTINEL}'; env -0 2>/dev/null || env; printf '${SENTINEL}'`];
+}
+
+function loginShell(): string {
+  return process.env.SHELL || '/bin/sh';
+}
+
+function snapshotPath(): string {
+  return path.join(getStateDir(), SNAPSHOT_FILE);
+}
+
+function readSnapshot(): Record<string, string> | null {
+  try {
+    const raw = JSON.parse(fs.readFileSync(snapshotPath(), 'utf8')) as {
+      shell?: unknown;
+      env?: unknown;
+    };
+    if (raw.shell !== loginShell() || !raw.env || typeof raw.env !== 'object') return null;
+    return Object.fromEntries(
+      Object.entries(raw.env).filter((e): e is [string, string] => typeof e[1] === 'string'),
+    );
+  } catch {
+    return null;
+  }
+}
+
+function writeSnapshot(env: Record<string, string>): void {
+  try {
+    const file = snapshotPath();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    const tmp = `${file}.tmp`;
+    fs.writeFileSync(tmp, JSON.stringify({ shell: loginShell(), env }), { mode: 0o600 });
+    fs.renameSync(tmp, file);
+  } catch (err) {
+    log.warn('Failed to save shell environment snapshot', err);
+  }
+}
+
+function apply(env: Record<string, string>): void {
+  if (Object.keys(env).length === 0) return;
+  shellEnv = env;
+  // git, gh and friends run via execFile need the same PATH as the PTYs
+  if (env.PATH) process.env.PATH = env.PATH;
+}
+
+/**
+ * Load the user's login shell environment. GUI launches (Finder, Dock,
+ * .desktop files) start with a minimal PATH, so `claude`, `codex` and
+ * version-managed tools would not be found. A saved snapshot is used
+ * straight away when there is one and refreshed in the background;
+ * otherwise the shell is probed synchronously once.
+ */
+export function initShellEnv(): void {
+  const snapshot = readSnapshot();
+  if (snapshot) {
+    apply(snapshot);
+    void refreshShellEnv();
+    return;
+  }
+  try {
+    const output = execFileSync(loginShell(), probeArgs(), {
+      encoding: 'utf8',
+      timeout: RESOLVE_TIMEOUT_MS,
+      maxBuffer: MAX_BUFFER,
+    });
+    const env = parseEnvOutput(output);
+    apply(env);
+    writeSnapshot(env);
+  } catch (err) {
+    log.warn('Failed to resolve login shell environment', err);
+  }
+}
+
+/** Re-probe the login shell and update the snapshot. Never rejects. */
+export function refreshShellEnv(): Promise<void> {
+  return new Promise((resolve) => {
+    execFile(
+      loginShell(),
+      probeArgs(),
+      { encoding: 'utf8', timeout: RESOLVE_TIMEOUT_MS, maxBuffer: MAX_BUFFER },
+      (err, stdout) => {
+        const env = err ? {} : parseEnvOutput(stdout);
+        if (Object.keys(env).length > 0) {
+          apply(env);
+          writeSnapshot(env);
+        } else {
+          log.warn('Failed to refresh login shell environment', err ?? 'no output');
+        }
+        resolve();
+      },
+    );
+  });
+}
+
+/** The resolved login shell environment; empty until resolved. */
+export function getShellEnv(): Record<string, string> {
+  return shellEnv;
+}
+
+// Vars that could alter process loading or execution behavior
+const ENV_BLOCK_LIST = new Set([
+  'PATH',
+  'HOME',
+  'USER',
+  'SHELL',
+  'LD_PRELOAD',
+  'LD_LIBRARY_PATH',
+  'DYLD_INSERT_LIBRARIES',
+  'NODE_OPTIONS',
+  'ELECTRON_RUN_AS_NODE',
+]);
+
+/** Env overrides from the renderer, minus the ones it must not set. */
+export function safeEnvOverridesFrom(env: Record<string, string>): Record<string, string> {
+  const safe: Record<string, string> = {};
+  for (const [k, v] of Object.entries(env)) {
+    if (!ENV_BLOCK_LIST.has(k)) safe[k] = v;
+  }
+  return safe;
+}
+
+/**
+ * Environment for a spawned PTY, lowest precedence first: the app's own
+ * environment, the login shell's, then per-project and per-task overrides.
+ */
+export function buildSpawnEnv(
+  processEnv: NodeJS.ProcessEnv,
+  loginEnv: Record<string, string>,
+  overrides: Record<string, string>,
+): Record<string, string> {
+  const env: Record<string, string> = {};
+  for (const [k, v] of Object.entries(processEnv)) {
+    if (v !== undefined) env[k] = v;
+  }
+  return { ...env, ...loginEnv, ...overrides };
+}
@@ -0,0 +1,99 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/state',
+}));
+
+import {
+  localPathFor,
+  parseSshUri,
+  remoteCommand,
+  remoteTargetFor,
+  shellQuote,
+  sshArgs,
+} from './ssh.js';
+
+describe('parseSshUri', () => {
+  it('reads user, host, port and path', () => {
+    expect(parseSshUri('ssh://dev@box:2222/home/dev/repo/')).toEqual({
+      destination: 'dev@box',
+      port: 2222,
+      path: '/home/dev/repo',
+    });
+    expect(parseSshUri('ssh://box/srv/repo')).toEqual({
+      destination: 'box',
+      port: undefined,
+      path: '/srv/repo',
+    });
+  });
+
+  it('rejects addresses without a repository path', () => {
+    expect(() => parseSshUri('ssh://box')).toThrow('absolute path');
+    expect(() => parseSshUri('ssh://box/')).toThrow('absolute path');
+    expect(() => parseSshUri('https://box/repo')).toThrow('Invalid SSH address');
+  });
+});
+
+describe('stand-in paths', () => {
+  it('round-trips a remote target through its local path', () => {
+    const target = { destination: 'dev@box', port: 2222, path: '/home/dev/repo' };
+    const local = localPathFor(target);
+    expect(local).toBe('/state/ssh/dev@box:2222/home/dev/repo');
+    expect(remoteTargetFor(local)).toEqual(target);
+    expect(remoteTargetFor(`${local}/.worktrees/task/x`)).toEqual({
+      ...target,
+      path: '/home/dev/repo/.worktrees/task/x',
+    });
+  });
+
+  it('leaves local paths alone', () => {
+    expect(remoteTargetFor('/repo')).toBeUndefined();
+    expect(remoteTargetFor('/home/me/ssh/repo')).toBeUndefined();
+  });
+});
+
+describe('shellQuote', () => {
+  it('quotes only what the shell would split or expand', () => {
+    expect(shellQuote('--porcelain')).toBe('--porcelain');
+    expect(shellQuote('a b')).toBe("'a b'");
+    expect(shellQuote("it's")).toBe("'it'\\''s'");
+  });
+});
+
+describe('sshArgs', () => {
+  const target = { destination: 'box', port: 2222, path: '/srv/repo' };
+
+  it('runs batch commands in the remote path without a terminal', () => {
+    expect(sshArgs(target, 'git', ['status', '--porcelain'])).toEqual([
+      '-T',
+      '-o',
+      'BatchMode=yes',
+      '-p',
+      '2222',
+      '--',
+      'box',
+      'cd /srv/repo && exec git status --porcelain',
+    ]);
+  });
+
+  it('starts the remote login shell in a terminal, with the given environment', () => {
+    const args = sshArgs(target, null, [], { tty: true, env: { PORT: '3001' } });
+    expect(args.slice(0, 1)).toEqual(['-tt']);
+    expect(args.at(-1)).toBe('cd /srv/repo && exec env PORT=3001 "$SHELL" -l');
+  });
+});
+
+describe('remoteCommand', () => {
+  it('runs local commands as they are', () => {
+    expect(remoteCommand('git', ['status'], '/repo')).toBeNull();
+  });
+
+  it('turns stand-in path arguments back into remote ones', () => {
+    const cwd = '/state/ssh/box/srv/repo';
+    const remote = remoteCommand('git', ['worktree', 'add', `${cwd}/.worktrees/x`], cwd, {});
+    expect(remote?.file).toBe('ssh');
+    expect(remote?.args.at(-1)).toBe(
+      'cd /srv/repo && exec git worktree add /srv/repo/.worktrees/x',
+    );
+  });
+});
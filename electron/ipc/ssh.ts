@@ -0,0 +1,133 @@
+import path from 'path';
+import { getStateDir } from './persistence.js';
+
+/**
+ * Remote projects live on another machine and are reached over `ssh`. In the
+ * app they get a stand-in path under `<state dir>/ssh/<user@host:port>/`
+ * followed by the remote path, so projects, tasks and worktrees keep using
+ * plain paths; git and agents started with such a path as their cwd run on
+ * the remote machine instead.
+ */
+const SSH_DIR = 'ssh';
+
+export interface SshTarget {
+  /** `user@host`, or a bare host (or ssh config alias) for ssh's default user. */
+  destination: string;
+  port?: number;
+  /** Absolute path on the remote machine. */
+  path: string;
+}
+
+export function isSshUri(value: string): boolean {
+  return value.startsWith('ssh://');
+}
+
+/** Parse `ssh://[user@]host[:port]/absolute/path`. */
+export function parseSshUri(uri: string): SshTarget {
+  let url: URL;
+  try {
+    url = new URL(uri);
+  } catch {
+    throw new Error(`Invalid SSH address: ${uri}`);
+  }
+  if (url.protocol !== 'ssh:' || !url.hostname) throw new Error(`Invalid SSH address: ${uri}`);
+  const remotePath = decodeURIComponent(url.pathname);
+  if (!remotePath.startsWith('/') || remotePath === '/' || remotePath.split('/').includes('..')) {
+    throw new Error(`SSH address needs an absolute path to the repository: ${uri}`);
+  }
+  const user = decodeURIComponent(url.username);
+  const destination = user ? `${user}@${url.hostname}` : url.hostname;
+  // ssh would read a leading dash as an option
+  if (destination.startsWith('-')) throw new Error(`Invalid SSH address: ${uri}`);
+  return {
+    destination,
+    port: url.port ? Number(url.port) : undefined,
+    path: remotePath.replace(/\/+$/, ''),
+  };
+}
+
+export function formatSshUri(target: SshTarget): string {
+  return `ssh://${target.destination}${target.port ? `:${target.port}` : ''}${target.path}`;
+}
+
+function sshRoot(): string {
+  return path.join(getStateDir(), SSH_DIR);
+}
+
+/** The app-side path standing for a remote path. */
+export function localPathFor(target: SshTarget): string {
+  const host = target.port ? `${target.destination}:${target.port}` : target.destination;
+  return path.join(sshRoot(), host, ...target.path.split('/'));
+}
+
+/** The remote machine and path an app-side path stands for; undefined for local paths. */
+export function remoteTargetFor(localPath: string): SshTarget | undefined {
+  if (!localPath.includes(`${path.sep}${SSH_DIR}${path.sep}`)) return undefined;
+  const rel = path.relative(sshRoot(), localPath);
+  if (!rel || rel.startsWith('..') || path.isAbsolute(rel)) return undefined;
+  const [host, ...rest] = rel.split(path.sep);
+  const m = /^(.+?)(?::(\d+))?$/.exec(host);
+  if (!m || rest.length === 0) return undefined;
+  return { destination: m[1], port: m[2] ? Number(m[2]) : undefined, path: `/${rest.join('/')}` };
+}
+
+/** Quote `s` as one word for the remote POSIX shell. */
+export function shellQuote(s: string): string {
+  return /^[A-Za-z0-9_/.,:=@%+-]+$/.test(s) ? s : `'${s.replace(/'/g, `'\\''`)}'`;
+}
+
+/**
+ * `ssh` arguments that run `command` in `target.path` on the remote machine,
+ * or the user's login shell when `command` is null. `tty` allocates a
+ * terminal, for agents and shells; without one ssh never prompts, so a
+ * missing key fails instead of hanging a git command.
+ */
+export function sshArgs(
+  target: SshTarget,
+  command: string | null,
+  args: string[],
+  opts: { tty?: boolean; env?: Record<string, string> } = {},
+): string[] {
+  const env = Object.entries(opts.env ?? {}).map(([k, v]) => `${k}=${shellQuote(v)}`);
+  // The remote user's own shell: the app's configured one may not exist there
+  const run = command === null ? ['"$SHELL"', '-l'] : [command, ...args].map(shellQuote);
+  const words = [...(env.length > 0 ? ['env', ...env] : []), ...run];
+  const remote = `cd ${shellQuote(target.path)} && exec ${words.join(' ')}`;
+  return [
+    opts.tty ? '-tt' : '-T',
+    ...(opts.tty ? [] : ['-o', 'BatchMode=yes']),
+    ...(target.port ? ['-p', String(target.port)] : []),
+    '--',
+    target.destination,
+    remote,
+  ];
+}
+
+/**
+ * `file args` rewritten to run over ssh when `cwd` is a remote project's
+ * path, with stand-in paths among the arguments (a new worktree's, say)
+ * turned back into remote ones; null for local commands, which run as they are.
+ */
+export function remoteCommand(
+  file: string,
+  args: string[],
+  cwd: string | URL | undefined,
+  env?: NodeJS.ProcessEnv,
+): { file: string; args: string[] } | null {
+  const target = typeof cwd === 'string' ? remoteTargetFor(cwd) : undefined;
+  if (!target) return null;
+  const remoteArgs = args.map((arg) => {
+    const t = remoteTargetFor(arg);
+    return t && t.destination === target.destination && t.port === target.port ? t.path : arg;
+  });
+  return { file: 'ssh', args: sshArgs(target, file, remoteArgs, { env: envOverrides(env) }) };
+}
+
+/** Variables `env` sets or changes on top of this process's, the ones worth sending along. */
+export function envOverrides(env: NodeJS.ProcessEnv | undefined): Record<string, string> {
+  const overrides: Record<string, string> = {};
+  for (const [k, v] of Object.entries(env ?? {})) {
+    if (v !== undefined && process.env[k] !== v) overrides[k] = v;
+  }
+  return overrides;
+}
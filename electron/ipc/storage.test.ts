@@ -0,0 +1,150 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import type { DatabaseSync } from 'node:sqlite';
+import { afterAll, beforeAll, describe, expect, it, vi } from 'vitest';
+
+const state = vi.hoisted(() => ({ dir: '/nonexistent' }));
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => state.dir,
+}));
+
+import { appendTaskEvents, recordTaskEvent } from './task-events.js';
+import {
+  closeHistoryDatabase,
+  ftsQuery,
+  getTaskTranscripts,
+  indexTasks,
+  queryHistory,
+  searchTranscripts,
+  startHistoryIndex,
+  storeTranscript,
+} from './storage.js';
+
+// Electron's Node has it; older standalone Nodes don't
+const sqlite = await import('node:sqlite').catch(() => null);
+
+describe('ftsQuery', () => {
+  it('quotes each word so FTS5 syntax is taken literally', () => {
+    expect(ftsQuery('  fix "login" OR-bug ')).toBe('"fix" """login""" "OR-bug"');
+    expect(ftsQuery('   ')).toBe('');
+  });
+});
+
+describe('without a database', () => {
+  it('answers queries with nothing', () => {
+    expect(queryHistory({ taskId: 't1' })).toEqual([]);
+    expect(searchTranscripts('error')).toEqual([]);
+    expect(getTaskTranscripts('t1')).toEqual([]);
+  });
+});
+
+describe.skipIf(!sqlite)('history database', () => {
+  const dbFile = () => path.join(state.dir, 'history.db');
+
+  /** Run `fn` on a second connection, to see what the module wrote. */
+  function inspect<T>(fn: (d: DatabaseSync) => T): T {
+    if (!sqlite) throw new Error('node:sqlite is unavailable');
+    const d = new sqlite.DatabaseSync(dbFile());
+    try {
+      return fn(d);
+    } finally {
+      d.close();
+    }
+  }
+
+  const scalar = (sql: string) =>
+    inspect((d) => Number(Object.values(d.prepare(sql).get() ?? {})[0]));
+  const userVersion = () => scalar('PRAGMA user_version');
+  const eventCount = () => scalar('SELECT COUNT(*) FROM events');
+
+  beforeAll(async () => {
+    state.dir = fs.mkdtempSync(path.join(os.tmpdir(), 'storage-'));
+    // Logged before the database existed, so opening it imports them
+    appendTaskEvents('t1', [
+      { ts: 1000, type: 'created' },
+      { ts: 2000, type: 'agent_spawned', data: { agent_id: 'a1', agent_def_id: 'claude' } },
+      { ts: 3000, type: 'merged' },
+    ]);
+    appendTaskEvents('t2', [{ ts: 1500, type: 'created' }]);
+    await startHistoryIndex();
+    indexTasks([
+      { id: 't1', name: 'Fix login', projectId: 'p1' },
+      { id: 't2', name: 'Add docs', projectId: 'p2' },
+    ]);
+  });
+
+  afterAll(() => {
+    closeHistoryDatabase();
+    fs.rmSync(state.dir, { recursive: true, force: true });
+  });
+
+  it('creates the schema and imports the event logs of a new database', () => {
+    expect(userVersion()).toBeGreaterThan(0);
+    expect(queryHistory().map((e) => [e.task_id, e.type])).toEqual([
+      ['t1', 'merged'],
+      ['t1', 'agent_spawned'],
+      ['t2', 'created'],
+      ['t1', 'created'],
+    ]);
+  });
+
+  it('filters history by task, project, type and time', () => {
+    expect(queryHistory({ taskId: 't2' }).map((e) => e.type)).toEqual(['created']);
+    expect(queryHistory({ projectId: 'p1' }).every((e) => e.task_name === 'Fix login')).toBe(
+      true,
+    );
+    expect(queryHistory({ types: ['created'] }).map((e) => e.task_id)).toEqual(['t2', 't1']);
+    expect(queryHistory({ since: 1500, until: 3000 }).map((e) => e.ts)).toEqual([2000, 1500]);
+    expect(queryHistory({ limit: 1 })).toHaveLength(1);
+    expect(queryHistory({ types: ['agent_spawned'] })[0]?.data).toEqual({
+      agent_id: 'a1',
+      agent_def_id: 'claude',
+    });
+  });
+
+  it('indexes events as they are recorded', () => {
+    recordTaskEvent('t2', 'pushed', { remote: 'origin' });
+    const [latest] = queryHistory({ taskId: 't2' });
+    expect(latest).toMatchObject({ task_name: 'Add docs', type: 'pushed' });
+  });
+
+  it('finds transcript lines containing every word, literally', () => {
+    storeTranscript('t1', 'a1', ['running tests', 'Error: login failed', '', 'OR NOT "done"']);
+    expect(searchTranscripts('login error')).toEqual([
+      {
+        task_id: 't1',
+        task_name: 'Fix login',
+        agent_id: 'a1',
+        line_no: 1,
+        text: 'Error: login failed',
+      },
+    ]);
+    expect(searchTranscripts('OR NOT').map((m) => m.line_no)).toEqual([3]);
+    expect(searchTranscripts('login', { taskId: 't2' })).toEqual([]);
+    // Blank lines aren't stored
+    expect(getTaskTranscripts('t1')[0]?.lines).toHaveLength(3);
+  });
+
+  it('keeps only the last lines of a long transcript', () => {
+    const lines = Array.from({ length: 6000 }, (_, i) => `line ${i}`);
+    storeTranscript('t2', 'a2', lines);
+    const [stored] = getTaskTranscripts('t2');
+    expect(stored?.agent_id).toBe('a2');
+    expect(stored?.lines).toHaveLength(5000);
+    expect(stored?.lines[0]).toBe('line 1000');
+    expect(stored?.lines.slice(-1)).toEqual(['line 5999']);
+  });
+
+  it('reopens an up-to-date database without migrating or importing again', async () => {
+    const version = userVersion();
+    const events = eventCount();
+    closeHistoryDatabase();
+    expect(queryHistory()).toEqual([]);
+
+    await startHistoryIndex();
+    expect(userVersion()).toBe(version);
+    expect(eventCount()).toBe(events);
+    expect(searchTranscripts('login error')).toHaveLength(1);
+  });
+});
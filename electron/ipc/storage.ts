@@ -0,0 +1,471 @@
+import fs from 'fs';
+import path from 'path';
+import type { DatabaseSync } from 'node:sqlite';
+import { getStateDir } from './persistence.js';
+import {
+  listTaskEventLogs,
+  getTaskEvents,
+  onTaskEvent,
+  type TaskEvent,
+  type TaskEventType,
+} from './task-events.js';
+import { createLogger } from './log.js';
+import type { UsageMetrics } from './usage.js';
+import {
+  aggregateMetrics,
+  emptyMetricsRows,
+  type ActivityMetrics,
+  type MetricsRange,
+  type MetricsRows,
+} from './metrics.js';
+
+const log = createLogger('storage');
+
+const MAX_QUERY_LIMIT = 1000;
+const DEFAULT_QUERY_LIMIT = 200;
+/** Lines of an agent's output kept per session. */
+const MAX_TRANSCRIPT_LINES = 5000;
+
+/**
+ * `SCHEMA[n]` upgrades a version-`n` database to `n + 1`, tracked in
+ * `PRAGMA user_version`. Only ever append.
+ */
+const SCHEMA: string[] = [
+  `CREATE TABLE tasks (
+     id TEXT PRIMARY KEY,
+     name TEXT NOT NULL,
+     project_id TEXT,
+     branch_name TEXT,
+     created_at INTEGER,
+     deleted_at INTEGER
+   );
+   CREATE TABLE sessions (
+     id INTEGER PRIMARY KEY,
+     task_id TEXT NOT NULL,
+     agent_id TEXT NOT NULL,
+     agent_def_id TEXT,
+     started_at INTEGER NOT NULL,
+     ended_at INTEGER,
+     exit_code INTEGER
+   );
+   CREATE INDEX sessions_by_task ON sessions (task_id, started_at);
+   CREATE INDEX sessions_by_agent ON sessions (agent_id, started_at);
+   CREATE TABLE events (
+     id INTEGER PRIMARY KEY,
+     task_id TEXT NOT NULL,
+     ts INTEGER NOT NULL,
+     type TEXT NOT NULL,
+     data TEXT
+   );
+   CREATE INDEX events_by_task ON events (task_id, ts);
+   CREATE INDEX events_by_type ON events (type, ts);
+   CREATE TABLE usage (
+     task_id TEXT NOT NULL,
+     agent_id TEXT NOT NULL,
+     agent_def_id TEXT,
+     input_tokens INTEGER NOT NULL,
+     output_tokens INTEGER NOT NULL,
+     total_tokens INTEGER NOT NULL,
+     cost_usd REAL,
+     updated_at INTEGER NOT NULL,
+     PRIMARY KEY (task_id, agent_id)
+   );
+   CREATE TABLE transcripts (
+     id INTEGER PRIMARY KEY,
+     session_id INTEGER,
+     task_id TEXT NOT NULL,
+     agent_id TEXT NOT NULL,
+     line_no INTEGER NOT NULL,
+     text TEXT NOT NULL
+   );
+   CREATE INDEX transcripts_by_task ON transcripts (task_id, agent_id, line_no);`,
+];
+
+/** Full-text index over transcripts; optional, since not every SQLite build has FTS5. */
+const FTS_SCHEMA = `
+  CREATE VIRTUAL TABLE IF NOT EXISTS transcripts_fts
+    USING fts5(text, content='transcripts', content_rowid='id');
+  CREATE TRIGGER IF NOT EXISTS transcripts_ai AFTER INSERT ON transcripts BEGIN
+    INSERT INTO transcripts_fts (rowid, text) VALUES (new.id, new.text);
+  END;
+  CREATE TRIGGER IF NOT EXISTS transcripts_ad AFTER DELETE ON transcripts BEGIN
+    INSERT INTO transcripts_fts (transcripts_fts, rowid, text) VALUES ('delete', old.id, old.text);
+  END;`;
+
+export interface HistoryEvent extends TaskEvent {
+  task_id: string;
+  task_name: string | null;
+}
+
+export interface HistoryQuery {
+  taskId?: string;
+  projectId?: string;
+  types?: TaskEventType[];
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+export interface TranscriptMatch {
+  task_id: string;
+  task_name: string | null;
+  agent_id: string;
+  line_no: number;
+  text: string;
+}
+
+/** One agent's stored transcript lines, oldest first. */
+export interface AgentTranscript {
+  agent_id: string;
+  lines: string[];
+}
+
+let db: DatabaseSync | null = null;
+let hasFts = false;
+
+async function openDatabase(): Promise<DatabaseSync> {
+  // Imported here so the app still runs where node:sqlite isn't available
+  const sqlite = await import('node:sqlite');
+  fs.mkdirSync(getStateDir(), { recursive: true });
+  const opened = new sqlite.DatabaseSync(path.join(getStateDir(), 'history.db'));
+  opened.exec('PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;');
+  const version = Number(
+    (opened.prepare('PRAGMA user_version').get() as { user_version: number }).user_version,
+  );
+  for (let v = version; v < SCHEMA.length; v++) {
+    transaction(opened, () => {
+      opened.exec(SCHEMA[v]);
+      opened.exec(`PRAGMA user_version = ${v + 1}`);
+    });
+  }
+  try {
+    opened.exec(FTS_SCHEMA);
+    hasFts = true;
+  } catch (err) {
+    log.info('No full-text search in this SQLite build; searching with LIKE', err);
+  }
+  if (version === 0) backfillEvents(opened);
+  return opened;
+}
+
+/** Run `fn` against the database; failures are logged, never thrown. */
+function withDb<T>(fallback: T, fn: (d: DatabaseSync) => T): T {
+  const d = db;
+  if (!d) return fallback;
+  try {
+    return fn(d);
+  } catch (err) {
+    log.warn('History database query failed', err);
+    return fallback;
+  }
+}
+
+function transaction(d: DatabaseSync, fn: () => void): void {
+  d.exec('BEGIN');
+  try {
+    fn();
+    d.exec('COMMIT');
+  } catch (err) {
+    d.exec('ROLLBACK');
+    throw err;
+  }
+}
+
+/** Events recorded before the database existed, from the per-task JSONL logs. */
+function backfillEvents(d: DatabaseSync): void {
+  let count = 0;
+  transaction(d, () => {
+    for (const taskId of listTaskEventLogs()) {
+      for (const event of getTaskEvents(taskId)) {
+        insertEvent(d, taskId, event);
+        count++;
+      }
+    }
+  });
+  if (count > 0) log.info(`Imported ${count} task events into the history database`);
+}
+
+/** Add an event, and the session it starts or ends. */
+function insertEvent(d: DatabaseSync, taskId: string, event: TaskEvent): void {
+  const data = event.data ?? {};
+  d.prepare('INSERT INTO events (task_id, ts, type, data) VALUES (?, ?, ?, ?)').run(
+    taskId,
+    event.ts,
+    event.type,
+    event.data ? JSON.stringify(event.data) : null,
+  );
+  const agentId = typeof data.agent_id === 'string' ? data.agent_id : null;
+  if (event.type === 'agent_spawned' && agentId) {
+    d.prepare(
+      'INSERT INTO sessions (task_id, agent_id, agent_def_id, started_at) VALUES (?, ?, ?, ?)',
+    ).run(
+      taskId,
+      agentId,
+      typeof data.agent_def_id === 'string' ? data.agent_def_id : null,
+      event.ts,
+    );
+  } else if (event.type === 'agent_exited' && agentId) {
+    d.prepare(
+      `UPDATE sessions SET ended_at = ?, exit_code = ?
+       WHERE id = (SELECT MAX(id) FROM sessions WHERE agent_id = ? AND ended_at IS NULL)`,
+    ).run(event.ts, typeof data.exit_code === 'number' ? data.exit_code : null, agentId);
+  } else if (event.type === 'deleted') {
+    d.prepare('UPDATE tasks SET deleted_at = ? WHERE id = ?').run(event.ts, taskId);
+  }
+}
+
+/** Keep names and projects of tasks current, for showing and filtering history. */
+export function indexTasks(
+  tasks: Array<{
+    id: string;
+    name: string;
+    projectId?: string;
+    branchName?: string;
+    createdAt?: number;
+  }>,
+): void {
+  withDb(undefined, (d) => {
+    const upsert = d.prepare(
+      `INSERT INTO tasks (id, name, project_id, branch_name, created_at) VALUES (?, ?, ?, ?, ?)
+       ON CONFLICT (id) DO UPDATE SET
+         name = excluded.name,
+         project_id = excluded.project_id,
+         branch_name = excluded.branch_name,
+         created_at = COALESCE(tasks.created_at, excluded.created_at),
+         deleted_at = NULL`,
+    );
+    transaction(d, () => {
+      for (const t of tasks) {
+        upsert.run(t.id, t.name, t.projectId ?? null, t.branchName ?? null, t.createdAt ?? null);
+      }
+    });
+  });
+}
+
+export function indexUsage(
+  taskId: string,
+  agentId: string,
+  agentDefId: string | null,
+  metrics: UsageMetrics,
+): void {
+  withDb(undefined, (d) => {
+    d.prepare(
+      `INSERT OR REPLACE INTO usage
+         (task_id, agent_id, agent_def_id, input_tokens, output_tokens, total_tokens, cost_usd,
+          updated_at)
+       VALUES (?, ?, ?, ?, ?, ?, ?, ?)`,
+    ).run(
+      taskId,
+      agentId,
+      agentDefId,
+      metrics.input_tokens,
+      metrics.output_tokens,
+      metrics.total_tokens,
+      metrics.cost_usd,
+      Date.now(),
+    );
+  });
+}
+
+/** Store the plain-text output of an agent session that just ended. */
+export function storeTranscript(taskId: string, agentId: string, lines: string[]): void {
+  const kept = lines.slice(-MAX_TRANSCRIPT_LINES);
+  if (kept.length === 0) return;
+  withDb(undefined, (d) => {
+    const session = d
+      .prepare('SELECT MAX(id) AS id FROM sessions WHERE agent_id = ?')
+      .get(agentId) as { id: number | null } | undefined;
+    const insert = d.prepare(
+      `INSERT INTO transcripts (session_id, task_id, agent_id, line_no, text)
+       VALUES (?, ?, ?, ?, ?)`,
+    );
+    transaction(d, () => {
+      kept.forEach((text, i) => {
+        if (text.trim()) insert.run(session?.id ?? null, taskId, agentId, i, text);
+      });
+    });
+  });
+}
+
+/** The stored transcripts of a task's agents, each in the order it was written. */
+export function getTaskTranscripts(taskId: string): AgentTranscript[] {
+  return withDb([], (d) => {
+    const rows = d
+      .prepare('SELECT agent_id, text FROM transcripts WHERE task_id = ? ORDER BY id')
+      .all(taskId) as unknown as Array<{ agent_id: string; text: string }>;
+    const byAgent = new Map<string, string[]>();
+    for (const row of rows) {
+      const lines = byAgent.get(row.agent_id) ?? [];
+      lines.push(row.text);
+      byAgent.set(row.agent_id, lines);
+    }
+    return [...byAgent].map(([agent_id, lines]) => ({ agent_id, lines }));
+  });
+}
+
+function clampLimit(limit: number | undefined): number {
+  return Math.max(1, Math.min(limit ?? DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT));
+}
+
+/** Recorded task events matching `query`, newest first. */
+export function queryHistory(query: HistoryQuery = {}): HistoryEvent[] {
+  return withDb([], (d) => {
+    const where: string[] = [];
+    const params: Array<string | number> = [];
+    if (query.taskId) {
+      where.push('e.task_id = ?');
+      params.push(query.taskId);
+    }
+    if (query.projectId) {
+      where.push('t.project_id = ?');
+      params.push(query.projectId);
+    }
+    if (query.types && query.types.length > 0) {
+      where.push(`e.type IN (${query.types.map(() => '?').join(', ')})`);
+      params.push(...query.types);
+    }
+    if (query.since !== undefined) {
+      where.push('e.ts >= ?');
+      params.push(query.since);
+    }
+    if (query.until !== undefined) {
+      where.push('e.ts < ?');
+      params.push(query.until);
+    }
+    const rows = d
+      .prepare(
+        `SELECT e.task_id, t.name AS task_name, e.ts, e.type, e.data
+         FROM events e LEFT JOIN tasks t ON t.id = e.task_id
+         ${where.length > 0 ? `WHERE ${where.join(' AND ')}` : ''}
+         ORDER BY e.ts DESC, e.id DESC LIMIT ?`,
+      )
+      .all(...params, clampLimit(query.limit)) as Array<{
+      task_id: string;
+      task_name: string | null;
+      ts: number;
+      type: TaskEventType;
+      data: string | null;
+    }>;
+    return rows.map((r) => ({
+      task_id: r.task_id,
+      task_name: r.task_name,
+      ts: r.ts,
+      type: r.type,
+      ...(r.data ? { data: JSON.parse(r.data) as Record<string, unknown> } : {}),
+    }));
+  });
+}
+
+/** Activity over `range`; sessions of `runningAgentIds` that are still open count up to now. */
+export function getMetrics(range: MetricsRange, runningAgentIds: string[]): ActivityMetrics {
+  const rows = withDb(emptyMetricsRows(), (d): MetricsRows => {
+    const project = range.projectId ? 'AND t.project_id = ?' : '';
+    const p = range.projectId ? [range.projectId] : [];
+    const events = d
+      .prepare(
+        `SELECT e.task_id, e.ts, e.type FROM events e LEFT JOIN tasks t ON t.id = e.task_id
+         WHERE e.type IN ('created', 'merged') AND e.ts >= ? AND e.ts < ? ${project}`,
+      )
+      .all(range.since, range.until, ...p) as MetricsRows['events'];
+    const ended = d
+      .prepare(
+        `SELECT MIN(e.ts) AS ended_at, COALESCE(t.created_at,
+           (SELECT MIN(c.ts) FROM events c WHERE c.task_id = e.task_id AND c.type = 'created')
+         ) AS created_at
+         FROM events e LEFT JOIN tasks t ON t.id = e.task_id
+         WHERE e.type IN ('merged', 'archived', 'deleted') ${project}
+         GROUP BY e.task_id HAVING MIN(e.ts) >= ? AND MIN(e.ts) < ?`,
+      )
+      .all(...p, range.since, range.until) as MetricsRows['ended'];
+    const sessions = d
+      .prepare(
+        `SELECT s.agent_id, s.agent_def_id, s.started_at, s.ended_at
+         FROM sessions s LEFT JOIN tasks t ON t.id = s.task_id
+         WHERE s.started_at < ? AND (s.ended_at IS NULL OR s.ended_at >= ?) ${project}`,
+      )
+      .all(range.until, range.since, ...p) as MetricsRows['sessions'];
+    const usage = d
+      .prepare(
+        `SELECT u.agent_def_id, u.input_tokens, u.output_tokens, u.total_tokens, u.cost_usd
+         FROM usage u LEFT JOIN tasks t ON t.id = u.task_id
+         WHERE u.updated_at >= ? AND u.updated_at < ? ${project}`,
+      )
+      .all(range.since, range.until, ...p) as unknown as MetricsRows['usage'];
+    return { events, ended, sessions, usage };
+  });
+  return aggregateMetrics(rows, range, new Set(runningAgentIds));
+}
+
+/** `text` as an FTS5 query matching all of its words, each taken literally. */
+export function ftsQuery(text: string): string {
+  return text
+    .split(/\s+/)
+    .filter(Boolean)
+    .map((word) => `"${word.replace(/"/g, '""')}"`)
+    .join(' ');
+}
+
+/** Transcript lines containing all words of `text`, newest sessions first. */
+export function searchTranscripts(
+  text: string,
+  opts: { taskId?: string; limit?: number } = {},
+): TranscriptMatch[] {
+  const match = ftsQuery(text);
+  if (!match) return [];
+  return withDb([], (d) => {
+    const params: Array<string | number> = [];
+    let where: string;
+    if (hasFts) {
+      where = 'x.id IN (SELECT rowid FROM transcripts_fts WHERE transcripts_fts MATCH ?)';
+      params.push(match);
+    } else {
+      const words = text.split(/\s+/).filter(Boolean);
+      where = words.map(() => "x.text LIKE ? ESCAPE '\\'").join(' AND ');
+      params.push(...words.map((w) => `%${w.replace(/[\\%_]/g, '\\$&')}%`));
+    }
+    if (opts.taskId) {
+      where += ' AND x.task_id = ?';
+      params.push(opts.taskId);
+    }
+    return d
+      .prepare(
+        `SELECT x.task_id, t.name AS task_name, x.agent_id, x.line_no, x.text
+         FROM transcripts x LEFT JOIN tasks t ON t.id = x.task_id
+         WHERE ${where}
+         ORDER BY x.id DESC LIMIT ?`,
+      )
+      .all(...params, clampLimit(opts.limit)) as unknown as TranscriptMatch[];
+  });
+}
+
+/**
+ * Open the history database and index task events into it as they're
+ * recorded. Without node:sqlite, history queries come back empty. It's
+ * never closed explicitly: agents killed on quit still write transcripts,
+ * and WAL mode makes exiting with it open safe.
+ */
+export async function startHistoryIndex(): Promise<void> {
+  if (db) return;
+  try {
+    db = await openDatabase();
+  } catch (err) {
+    log.warn('History database unavailable', err);
+    return;
+  }
+  onTaskEvent((taskId, event) => withDb(undefined, (d) => insertEvent(d, taskId, event)));
+}
+
+/** Fold the write-ahead log into `history.db`, so copying the file copies everything. */
+export function checkpointHistoryDatabase(): void {
+  withDb(undefined, (d) => d.exec('PRAGMA wal_checkpoint(TRUNCATE)'));
+}
+
+/** Close the database, e.g. before its file is replaced. Queries then come back empty. */
+export function closeHistoryDatabase(): void {
+  try {
+    db?.close();
+  } catch (err) {
+    log.debug('Failed to close history database', err);
+  }
+  db = null;
+}
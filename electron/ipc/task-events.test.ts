@@ -0,0 +1,38 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { getTaskEvents, isTaskEventType, parseTaskEvents } from './task-events.js';
+
+describe('parseTaskEvents', () => {
+  it('parses one event per line and skips malformed or unknown entries', () => {
+    const text = [
+      JSON.stringify({ ts: 1, type: 'created', data: { name: 'a' } }),
+      '{"ts": 2, "type": "merg',
+      JSON.stringify({ ts: 3, type: 'exploded' }),
+      '',
+      JSON.stringify({ ts: 4, type: 'merged' }),
+    ].join('\n');
+
+    expect(parseTaskEvents(text)).toEqual([
+      { ts: 1, type: 'created', data: { name: 'a' } },
+      { ts: 4, type: 'merged' },
+    ]);
+  });
+});
+
+describe('getTaskEvents', () => {
+  it('returns no events for unknown tasks or unsafe ids', () => {
+    expect(getTaskEvents('missing-task')).toEqual([]);
+    expect(getTaskEvents('../../etc/passwd')).toEqual([]);
+  });
+});
+
+describe('isTaskEventType', () => {
+  it('accepts only known event types', () => {
+    expect(isTaskEventType('agent_exited')).toBe(true);
+    expect(isTaskEventType('rm -rf')).toBe(false);
+  });
+});
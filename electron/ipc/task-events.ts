@@ -0,0 +1,120 @@
+import fs from 'fs';
+import path from 'path';
+import { getStateDir } from './persistence.js';
+
+export const TASK_EVENT_TYPES = [
+  'created',
+  'agent_spawned',
+  'agent_exited',
+  'merged',
+  'pushed',
+  'renamed',
+  'updated',
+  'archived',
+  'restored',
+  'deleted',
+  'exported',
+  'imported',
+  'handed_off',
+  'run_finished',
+  'checkpoint',
+  'timer_started',
+  'timer_paused',
+  'error',
+] as const;
+
+export type TaskEventType = (typeof TASK_EVENT_TYPES)[number];
+
+export interface TaskEvent {
+  ts: number;
+  type: TaskEventType;
+  data?: Record<string, unknown>;
+}
+
+export function isTaskEventType(v: unknown): v is TaskEventType {
+  return typeof v === 'string' && (TASK_EVENT_TYPES as readonly string[]).includes(v);
+}
+
+type TaskEventListener = (taskId: string, event: TaskEvent) => void;
+const listeners = new Set<TaskEventListener>();
+
+/** Register a listener for newly recorded events. Returns an unsubscribe function. */
+export function onTaskEvent(listener: TaskEventListener): () => void {
+  listeners.add(listener);
+  return () => {
+    listeners.delete(listener);
+  };
+}
+
+function getEventsPath(taskId: string): string {
+  // Task ids are UUIDs; anything else could escape the events directory
+  if (!/^[\w-]+$/.test(taskId)) throw new Error(`Invalid task id: ${taskId}`);
+  return path.join(getStateDir(), 'task-events', `${taskId}.jsonl`);
+}
+
+/** Append an event to the task's log. Failures are logged, never thrown. */
+export function recordTaskEvent(
+  taskId: string,
+  type: TaskEventType,
+  data?: Record<string, unknown>,
+): void {
+  const event: TaskEvent = data ? { ts: Date.now(), type, data } : { ts: Date.now(), type };
+  try {
+    const eventsPath = getEventsPath(taskId);
+    fs.mkdirSync(path.dirname(eventsPath), { recursive: true });
+    fs.appendFileSync(eventsPath, JSON.stringify(event) + '\n', 'utf8');
+  } catch (err) {
+    console.warn(`Failed to record ${type} event for task ${taskId}:`, err);
+  }
+  for (const fn of listeners) fn(taskId, event);
+}
+
+/** Append already-recorded events, e.g. history carried over from an imported bundle. */
+export function appendTaskEvents(taskId: string, events: TaskEvent[]): void {
+  if (events.length === 0) return;
+  try {
+    const eventsPath = getEventsPath(taskId);
+    fs.mkdirSync(path.dirname(eventsPath), { recursive: true });
+    fs.appendFileSync(eventsPath, events.map((e) => JSON.stringify(e) + '\n').join(''), 'utf8');
+  } catch (err) {
+    console.warn(`Failed to append events for task ${taskId}:`, err);
+  }
+}
+
+/** Parse a JSONL event log, skipping lines that are truncated or malformed. */
+export function parseTaskEvents(text: string): TaskEvent[] {
+  const events: TaskEvent[] = [];
+  for (const line of text.split('\n')) {
+    if (!line.trim()) continue;
+    try {
+      const raw = JSON.parse(line) as Partial<TaskEvent>;
+      if (typeof raw.ts === 'number' && isTaskEventType(raw.type)) {
+        events.push(raw as TaskEvent);
+      }
+    } catch {
+      // Partial write from a crash — skip
+    }
+  }
+  return events;
+}
+
+/** Ids of the tasks that have an event log. */
+export function listTaskEventLogs(): string[] {
+  try {
+    return fs
+      .readdirSync(path.join(getStateDir(), 'task-events'))
+      .filter((name) => name.endsWith('.jsonl'))
+      .map((name) => name.slice(0, -'.jsonl'.length));
+  } catch {
+    return [];
+  }
+}
+
+/** All recorded events for a task, oldest first. */
+export function getTaskEvents(taskId: string): TaskEvent[] {
+  try {
+    return parseTaskEvents(fs.readFileSync(getEventsPath(taskId), 'utf8'));
+  } catch {
+    return [];
+  }
+}
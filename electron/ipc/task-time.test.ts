@@ -0,0 +1,70 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { coveredMs, trackTaskTime } from './task-time.js';
+import type { TaskEvent } from './task-events.js';
+
+const MIN = 60_000;
+const spawned = (ts: number, agent_id: string): TaskEvent => ({
+  ts: ts * MIN,
+  type: 'agent_spawned',
+  data: { agent_id },
+});
+const exited = (ts: number, agent_id: string): TaskEvent => ({
+  ts: ts * MIN,
+  type: 'agent_exited',
+  data: { agent_id },
+});
+const timer = (ts: number, type: 'timer_started' | 'timer_paused'): TaskEvent => ({
+  ts: ts * MIN,
+  type,
+});
+
+describe('coveredMs', () => {
+  it('counts overlapping intervals once', () => {
+    expect(
+      coveredMs([
+        [0, 10],
+        [5, 15],
+        [20, 30],
+        [22, 25],
+      ]),
+    ).toBe(25);
+    expect(coveredMs([])).toBe(0);
+  });
+});
+
+describe('trackTaskTime', () => {
+  it('counts agents running side by side once', () => {
+    const events = [spawned(0, 'a1'), spawned(10, 'a2'), exited(20, 'a1'), exited(30, 'a2')];
+    const time = trackTaskTime('t1', events, new Set());
+    expect(time.active_ms).toBe(30 * MIN);
+    expect(time.agent_ms).toBe(40 * MIN);
+    expect(time.timer).toBe('auto');
+    expect([time.first_active_at, time.last_active_at]).toEqual([0, 30 * MIN]);
+  });
+
+  it('runs open sessions until now only while their agent runs', () => {
+    const events = [spawned(0, 'a1'), spawned(0, 'crashed')];
+    expect(trackTaskTime('t1', events, new Set(['a1']), 15 * MIN).active_ms).toBe(15 * MIN);
+    expect(trackTaskTime('t1', events, new Set(), 15 * MIN).active_ms).toBe(0);
+  });
+
+  it('counts a started timer and leaves out paused time', () => {
+    const events = [
+      timer(0, 'timer_started'),
+      spawned(5, 'a1'),
+      timer(10, 'timer_paused'),
+      // Paused: the agent's time doesn't count either
+      exited(30, 'a1'),
+      timer(40, 'timer_started'),
+    ];
+    const time = trackTaskTime('t1', events, new Set(), 50 * MIN);
+    expect(time.active_ms).toBe(20 * MIN);
+    expect(time.agent_ms).toBe(25 * MIN);
+    expect(time.timer).toBe('running');
+  });
+});
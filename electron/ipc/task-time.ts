@@ -0,0 +1,126 @@
+import { getTaskEvents, recordTaskEvent, type TaskEvent } from './task-events.js';
+import { getTaskUsage } from './usage.js';
+
+/**
+ * Wall-clock time spent on a task, from its event log. By default it's the
+ * time any of its agents ran. Starting the timer counts all time until it's
+ * paused, e.g. for reviewing by hand; pausing it stops counting, agents or
+ * not, until it's started again.
+ */
+
+export type TaskTimerState = 'auto' | 'running' | 'paused';
+
+export interface TaskTime {
+  task_id: string;
+  /** Overlapping agents and the timer count once. */
+  active_ms: number;
+  /** Each agent session's running time, added up. */
+  agent_ms: number;
+  timer: TaskTimerState;
+  first_active_at: number | null;
+  last_active_at: number | null;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+type Interval = [number, number];
+
+/** Total length of the union of `intervals`. */
+export function coveredMs(intervals: Interval[]): number {
+  const sorted = intervals.filter(([a, b]) => b > a).sort((x, y) => x[0] - y[0]);
+  let total = 0;
+  let end = -Infinity;
+  for (const [a, b] of sorted) {
+    if (b <= end) continue;
+    total += b - Math.max(a, end);
+    end = b;
+  }
+  return total;
+}
+
+/** `intervals` with the parts inside any of `holes` cut out. */
+function subtract(intervals: Interval[], holes: Interval[]): Interval[] {
+  let out = intervals;
+  for (const [h0, h1] of holes) {
+    out = out.flatMap(([a, b]): Interval[] => {
+      if (h1 <= a || h0 >= b) return [[a, b]];
+      const parts: Interval[] = [
+        [a, Math.min(b, h0)],
+        [Math.max(a, h1), b],
+      ];
+      return parts.filter(([x, y]) => y > x);
+    });
+  }
+  return out;
+}
+
+/**
+ * Time tracked in `events`, oldest first. A session that was never seen to
+ * exit runs until now if its agent is still running; otherwise a crash cut
+ * it off at an unknown point and it isn't counted.
+ */
+export function trackTaskTime(
+  taskId: string,
+  events: TaskEvent[],
+  runningAgentIds: ReadonlySet<string>,
+  now = Date.now(),
+): Omit<TaskTime, 'total_tokens' | 'cost_usd'> {
+  const sessions: Interval[] = [];
+  const running: Interval[] = [];
+  const paused: Interval[] = [];
+  const open = new Map<string, number>();
+  let timer: TaskTimerState = 'auto';
+  let timerSince = 0;
+
+  for (const e of events) {
+    const agentId = typeof e.data?.agent_id === 'string' ? e.data.agent_id : null;
+    if (e.type === 'agent_spawned' && agentId) {
+      open.set(agentId, e.ts);
+    } else if (e.type === 'agent_exited' && agentId) {
+      const start = open.get(agentId);
+      if (start !== undefined) sessions.push([start, e.ts]);
+      open.delete(agentId);
+    } else if (e.type === 'timer_started' || e.type === 'timer_paused') {
+      if (timer === 'running') running.push([timerSince, e.ts]);
+      if (timer === 'paused') paused.push([timerSince, e.ts]);
+      timer = e.type === 'timer_started' ? 'running' : 'paused';
+      timerSince = e.ts;
+    }
+  }
+  for (const [agentId, start] of open) {
+    if (runningAgentIds.has(agentId)) sessions.push([start, now]);
+  }
+  if (timer === 'running') running.push([timerSince, now]);
+  if (timer === 'paused') paused.push([timerSince, now]);
+
+  const counted = [...subtract(sessions, paused), ...running].filter(([a, b]) => b > a);
+  return {
+    task_id: taskId,
+    active_ms: coveredMs(counted),
+    agent_ms: sessions.reduce((sum, [a, b]) => sum + (b - a), 0),
+    timer,
+    first_active_at: counted.length > 0 ? Math.min(...counted.map(([a]) => a)) : null,
+    last_active_at: counted.length > 0 ? Math.max(...counted.map(([, b]) => b)) : null,
+  };
+}
+
+/** The task's tracked time, with what its agents cost to compare against. */
+export function getTaskTime(taskId: string, runningAgentIds: string[]): TaskTime {
+  const time = trackTaskTime(taskId, getTaskEvents(taskId), new Set(runningAgentIds));
+  const { totals } = getTaskUsage(taskId);
+  return { ...time, total_tokens: totals.total_tokens, cost_usd: totals.cost_usd };
+}
+
+/** Start or pause the task's timer. Does nothing if it's already in that state. */
+export function setTaskTimer(
+  taskId: string,
+  running: boolean,
+  runningAgentIds: string[],
+): TaskTime {
+  const current = getTaskTime(taskId, runningAgentIds);
+  if (current.timer !== (running ? 'running' : 'paused')) {
+    recordTaskEvent(taskId, running ? 'timer_started' : 'timer_paused');
+    return getTaskTime(taskId, runningAgentIds);
+  }
+  return current;
+}
@@ -1,10 +1,25 @@
 import { beforeEach, describe, expect, it, vi } from 'vitest';
 
 const createWorktreeMock = vi.hoisted(() => vi.fn());
+const removeWorktreeMock = vi.hoisted(() => vi.fn());
+const branchExistsMock = vi.hoisted(() => vi.fn());
+const remoteBranchExistsMock = vi.hoisted(() => vi.fn());
 
 vi.mock('./git.js', () => ({
   createWorktree: createWorktreeMock,
-  removeWorktree: vi.fn(),
+  removeWorktree: removeWorktreeMock,
+  branchExists: branchExistsMock,
+  remoteBranchExists: remoteBranchExistsMock,
+  getWorktreeStatus: vi.fn(),
+  renameWorktreeBranch: vi.fn(),
+}));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./task-events.js', () => ({
+  recordTaskEvent: vi.fn(),
 }));
 
 vi.mock('./pty.js', () => ({
@@ -12,11 +27,21 @@ vi.mock('./pty.js', () => ({
   notifyAgentListChanged: vi.fn(),
 }));
 
-import { createTask } from './tasks.js';
+import {
+  createTask,
+  createTaskFromBranch,
+  createRace,
+  restoreTask,
+  renamedBranchName,
+} from './tasks.js';
 
 describe('createTask', () => {
   beforeEach(() => {
     createWorktreeMock.mockReset();
+    branchExistsMock.mockReset();
+    remoteBranchExistsMock.mockReset();
+    branchExistsMock.mockResolvedValue(false);
+    remoteBranchExistsMock.mockResolvedValue(false);
     createWorktreeMock.mockResolvedValue({
       path: '/repo/.worktrees/task/untitled',
       branch: 'task/untitled',
@@ -35,9 +60,169 @@ describe('createTask', () => {
     expect(createWorktreeMock).toHaveBeenCalledWith('/repo', 'feature/team-name/fix-login-bug', []);
   });
 
+  it('gives tasks created at the same time different branches', async () => {
+    await Promise.all([
+      createTask('fix', '/repo', [], 'task'),
+      createTask('fix', '/repo', [], 'task'),
+      createTask('fix', '/repo', [], 'task'),
+    ]);
+
+    const branches = createWorktreeMock.mock.calls.map((c) => c[1]);
+    expect(branches.sort()).toEqual(['task/fix', 'task/fix-2', 'task/fix-3']);
+  });
+
   it('never returns prefix-only branch names', async () => {
     await createTask('   ', '/repo', [], 'task/');
 
     expect(createWorktreeMock).toHaveBeenCalledWith('/repo', 'task/untitled', []);
   });
+
+  it('reports submodules that failed without failing the task', async () => {
+    createWorktreeMock.mockResolvedValue({
+      path: '/repo/.worktrees/task/fix',
+      branch: 'task/fix',
+      submodule_error: 'git submodule failed: fatal: repository not found',
+    });
+
+    await expect(createTask('fix', '/repo', [], 'task')).resolves.toMatchObject({
+      branch_name: 'task/fix',
+      submodule_error: 'git submodule failed: fatal: repository not found',
+    });
+  });
+
+  it('suffixes the branch when another task already has the name', async () => {
+    branchExistsMock.mockImplementation((_root: string, branch: string) =>
+      Promise.resolve(branch === 'task/fix-login'),
+    );
+    remoteBranchExistsMock.mockImplementation((_root: string, _remote: string, branch: string) =>
+      Promise.resolve(branch === 'task/fix-login-2'),
+    );
+
+    await createTask('Fix login!', '/repo', [], 'task');
+
+    expect(createWorktreeMock).toHaveBeenCalledWith('/repo', 'task/fix-login-3', []);
+  });
+
+  it('checks out only the sparse paths it was given', async () => {
+    await createTask('fix', '/repo', [], 'task', undefined, ['packages/web']);
+
+    expect(createWorktreeMock).toHaveBeenCalledWith('/repo', 'task/fix', [], false, undefined, [
+      'packages/web',
+    ]);
+  });
+});
+
+describe('createRace', () => {
+  beforeEach(() => {
+    createWorktreeMock.mockReset();
+    removeWorktreeMock.mockReset();
+    removeWorktreeMock.mockResolvedValue(undefined);
+    createWorktreeMock.mockImplementation(async (_root: string, branch: string) => ({
+      path: `/repo/.worktrees/${branch}`,
+      branch,
+    }));
+  });
+
+  it('creates one sibling branch per agent', async () => {
+    const race = await createRace('Fix Login', '/repo', ['claude-code', 'codex'], [], 'task');
+
+    expect(race.entries.map((e) => e.branch_name)).toEqual([
+      'task/fix-login-claude-code',
+      'task/fix-login-codex',
+    ]);
+    expect(new Set(race.entries.map((e) => e.id)).size).toBe(2);
+  });
+
+  it('rejects races with fewer than two distinct agents', async () => {
+    await expect(createRace('x', '/repo', ['codex'], [], 'task')).rejects.toThrow(/two agents/);
+    await expect(createRace('x', '/repo', ['codex', 'codex'], [], 'task')).rejects.toThrow(
+      /only race once/,
+    );
+  });
+
+  it('rolls back created worktrees when one fails', async () => {
+    createWorktreeMock
+      .mockResolvedValueOnce({ path: '/repo/.worktrees/task/x-a', branch: 'task/x-a' })
+      .mockRejectedValueOnce(new Error('boom'));
+
+    await expect(createRace('x', '/repo', ['a', 'b'], [], 'task')).rejects.toThrow('boom');
+    expect(removeWorktreeMock).toHaveBeenCalledWith('/repo', 'task/x-a', true);
+  });
+});
+
+describe('restoreTask', () => {
+  beforeEach(() => {
+    createWorktreeMock.mockReset();
+    branchExistsMock.mockReset();
+  });
+
+  it('recreates the worktree from the existing branch', async () => {
+    branchExistsMock.mockResolvedValue(true);
+    createWorktreeMock.mockResolvedValue({ path: '/repo/.worktrees/task/a', branch: 'task/a' });
+
+    await expect(restoreTask('/repo', 'task/a', [])).resolves.toEqual({
+      worktree_path: '/repo/.worktrees/task/a',
+    });
+  });
+
+  it('refuses to recreate a branch that was deleted', async () => {
+    branchExistsMock.mockResolvedValue(false);
+
+    await expect(restoreTask('/repo', 'task/a', [])).rejects.toThrow('no longer exists');
+    expect(createWorktreeMock).not.toHaveBeenCalled();
+  });
+});
+
+describe('renamedBranchName', () => {
+  it('keeps the existing prefix and slugs the new name', () => {
+    expect(renamedBranchName('feature/team/old-name', 'Fix Login Bug')).toBe(
+      'feature/team/fix-login-bug',
+    );
+    expect(renamedBranchName('old', 'New')).toBe('task/new');
+    expect(renamedBranchName('task/old', '!!!')).toBe('task/untitled');
+  });
+});
+
+describe('createTaskFromBranch', () => {
+  beforeEach(() => {
+    createWorktreeMock.mockReset();
+    branchExistsMock.mockReset();
+    remoteBranchExistsMock.mockReset();
+    createWorktreeMock.mockImplementation((_root: string, branch: string) =>
+      Promise.resolve({ path: `/repo/.worktrees/${branch}`, branch }),
+    );
+  });
+
+  it('adds a worktree for an existing local branch', async () => {
+    branchExistsMock.mockResolvedValue(true);
+
+    const result = await createTaskFromBranch('PR work', '/repo', 'feature/pr-1', []);
+
+    expect(createWorktreeMock).toHaveBeenCalledWith('/repo', 'feature/pr-1', []);
+    expect(result.branch_name).toBe('feature/pr-1');
+  });
+
+  it('creates the local branch from origin when only the remote has it', async () => {
+    branchExistsMock.mockResolvedValue(false);
+    remoteBranchExistsMock.mockResolvedValue(true);
+
+    await createTaskFromBranch('PR work', '/repo', 'feature/pr-1', []);
+
+    expect(createWorktreeMock).toHaveBeenCalledWith(
+      '/repo',
+      'feature/pr-1',
+      [],
+      false,
+      'origin/feature/pr-1',
+    );
+  });
+
+  it('fails when the branch exists nowhere', async () => {
+    branchExistsMock.mockResolvedValue(false);
+    remoteBranchExistsMock.mockResolvedValue(false);
+
+    await expect(createTaskFromBranch('x', '/repo', 'nope', [])).rejects.toThrow(
+      'does not exist',
+    );
+  });
 });
@@ -1,27 +1,27 @@
 import { randomUUID } from 'crypto';
-import { createWorktree, removeWorktree } from './git.js';
+import fs from 'fs';
+import path from 'path';
+import {
+  branchExists,
+  createWorktree,
+  type CreatedWorktree,
+  getWorktreeStatus,
+  remoteBranchExists,
+  removeWorktree,
+  renameWorktreeBranch,
+} from './git.js';
+import { getWorktreePath } from './settings.js';
+import { recordTaskEvent } from './task-events.js';
 import { killAgent, notifyAgentListChanged } from './pty.js';
+import { createLogger } from './log.js';
+import { AppError } from './errors.js';
+import { slug, uniqueBranchName } from './branch-names.js';
+
+const log = createLogger('tasks');
 
-const MAX_SLUG_LEN = 72;
 const DEFAULT_TASK_SLUG = 'untitled';
 const DEFAULT_BRANCH_PREFIX = 'task';
 
-function slug(name: string): string {
-  let result = '';
-  let prevWasHyphen = false;
-  for (const c of name.toLowerCase()) {
-    if (result.length >= MAX_SLUG_LEN) break;
-    if (/[a-z0-9]/.test(c)) {
-      result += c;
-      prevWasHyphen = false;
-    } else if (!prevWasHyphen) {
-      result += '-';
-      prevWasHyphen = true;
-    }
-  }
-  return result.replace(/^-+|-+$/g, '');
-}
-
 function sanitizeBranchPrefix(prefix: string): string {
   const normalized = prefix.trim().replace(/\\/g, '/').replace(/\/+/g, '/');
   const parts = normalized
@@ -39,23 +39,264 @@ function ensureBranchNameHasLeaf(rawBranchName: string): string {
   return parts.join('/');
 }
 
+/** Whether a new task branch named `branchName` would land on someone else's work. */
+async function branchTaken(projectRoot: string, branchName: string): Promise<boolean> {
+  if (fs.existsSync(getWorktreePath(projectRoot, branchName))) return true;
+  return (
+    (await branchExists(projectRoot, branchName)) ||
+    (await remoteBranchExists(projectRoot, 'origin', branchName))
+  );
+}
+
+/** Branches picked by task creations still in flight, which git doesn't know about yet. */
+const claimedBranches = new Set<string>();
+
+/**
+ * A free branch name based on `branchName`, held until `release` is called so
+ * tasks created at the same time can't pick it too.
+ */
+async function claimBranchName(
+  projectRoot: string,
+  branchName: string,
+): Promise<{ branchName: string; release: () => void }> {
+  const key = (b: string) => `${projectRoot}\0${b}`;
+  // Claimed right after git says it's free, before any other creation can ask
+  const claimed = await uniqueBranchName(branchName, async (b) => {
+    if ((await branchTaken(projectRoot, b)) || claimedBranches.has(key(b))) return true;
+    claimedBranches.add(key(b));
+    return false;
+  });
+  return { branchName: claimed, release: () => claimedBranches.delete(key(claimed)) };
+}
+
+/** `createWorktree` with only the arguments a task needs, sparse paths passed when it has some. */
+function addTaskWorktree(
+  projectRoot: string,
+  branchName: string,
+  symlinkDirs: string[],
+  baseRef: string | undefined,
+  sparsePaths: string[],
+): Promise<CreatedWorktree> {
+  if (sparsePaths.length > 0) {
+    return createWorktree(projectRoot, branchName, symlinkDirs, false, baseRef, sparsePaths);
+  }
+  return baseRef
+    ? createWorktree(projectRoot, branchName, symlinkDirs, false, baseRef)
+    : createWorktree(projectRoot, branchName, symlinkDirs);
+}
+
+/** A task's scope may be a folder the task is about to create; its agents start in it. */
+function ensureScopeDir(worktreePath: string, scope: string | undefined): void {
+  if (scope) fs.mkdirSync(path.join(worktreePath, scope), { recursive: true });
+}
+
+export interface CreatedTask {
+  id: string;
+  branch_name: string;
+  worktree_path: string;
+  /** Set when the worktree was created but its submodules failed to check out. */
+  submodule_error?: string;
+  /** Set when the worktree was created but its Git LFS files couldn't be downloaded. */
+  lfs_error?: string;
+  /** The branch's upstream, when `track_base_upstream` set one or it came from origin. */
+  upstream?: string;
+}
+
 export async function createTask(
   name: string,
   projectRoot: string,
   symlinkDirs: string[],
   branchPrefix: string,
-): Promise<{ id: string; branch_name: string; worktree_path: string }> {
+  baseBranch?: string,
+  sparsePaths: string[] = [],
+  scope?: string,
+): Promise<CreatedTask> {
   const prefix = sanitizeBranchPrefix(branchPrefix);
   const branchLeaf = slug(name) || DEFAULT_TASK_SLUG;
-  const branchName = ensureBranchNameHasLeaf(`${prefix}/${branchLeaf}`);
-  const worktree = await createWorktree(projectRoot, branchName, symlinkDirs);
+  const claim = await claimBranchName(
+    projectRoot,
+    ensureBranchNameHasLeaf(`${prefix}/${branchLeaf}`),
+  );
+  let worktree: CreatedWorktree;
+  try {
+    worktree = await addTaskWorktree(
+      projectRoot,
+      claim.branchName,
+      symlinkDirs,
+      baseBranch,
+      sparsePaths,
+    );
+  } finally {
+    claim.release();
+  }
+  ensureScopeDir(worktree.path, scope);
+  const id = randomUUID();
+  recordTaskEvent(id, 'created', { name, branch_name: worktree.branch, base_branch: baseBranch });
+  return {
+    id,
+    branch_name: worktree.branch,
+    worktree_path: worktree.path,
+    submodule_error: worktree.submodule_error,
+    lfs_error: worktree.lfs_error,
+    upstream: worktree.upstream,
+  };
+}
+
+/**
+ * Register an existing branch as a task: adds a worktree for it instead of
+ * creating a new branch. A branch that only exists on `origin` gets a local
+ * branch created from the remote one.
+ */
+export async function createTaskFromBranch(
+  name: string,
+  projectRoot: string,
+  branchName: string,
+  symlinkDirs: string[],
+  sparsePaths: string[] = [],
+  scope?: string,
+): Promise<CreatedTask> {
+  let worktree: CreatedWorktree;
+  if (await branchExists(projectRoot, branchName)) {
+    worktree = await addTaskWorktree(projectRoot, branchName, symlinkDirs, undefined, sparsePaths);
+  } else if (await remoteBranchExists(projectRoot, 'origin', branchName)) {
+    worktree = await addTaskWorktree(
+      projectRoot,
+      branchName,
+      symlinkDirs,
+      `origin/${branchName}`,
+      sparsePaths,
+    );
+  } else {
+    throw new Error(`Branch ${branchName} does not exist`);
+  }
+  ensureScopeDir(worktree.path, scope);
+  const id = randomUUID();
+  recordTaskEvent(id, 'created', { name, branch_name: worktree.branch, adopted: true });
   return {
-    id: randomUUID(),
+    id,
     branch_name: worktree.branch,
     worktree_path: worktree.path,
+    submodule_error: worktree.submodule_error,
+    lfs_error: worktree.lfs_error,
+    upstream: worktree.upstream,
   };
 }
 
+export interface RaceEntry {
+  agent_def_id: string;
+  id: string;
+  branch_name: string;
+  worktree_path: string;
+  submodule_error?: string;
+  lfs_error?: string;
+}
+
+/**
+ * Create one sibling worktree/branch per agent for a race. Branches share
+ * the race name and are suffixed with the agent id. If any worktree fails,
+ * the ones already created are removed so no half-race is left behind.
+ */
+export async function createRace(
+  name: string,
+  projectRoot: string,
+  agentDefIds: string[],
+  symlinkDirs: string[],
+  branchPrefix: string,
+  sparsePaths: string[] = [],
+): Promise<{ race_id: string; entries: RaceEntry[] }> {
+  if (agentDefIds.length < 2) throw new Error('A race needs at least two agents');
+  if (new Set(agentDefIds).size !== agentDefIds.length) {
+    throw new Error('Each agent can only race once');
+  }
+
+  const prefix = sanitizeBranchPrefix(branchPrefix);
+  const raceLeaf = slug(name) || DEFAULT_TASK_SLUG;
+  const entries: RaceEntry[] = [];
+  try {
+    // Sequential: concurrent `git worktree add` on one repo races on .git/worktrees
+    for (const agentDefId of agentDefIds) {
+      const agentLeaf = slug(agentDefId) || 'agent';
+      const claim = await claimBranchName(
+        projectRoot,
+        ensureBranchNameHasLeaf(`${prefix}/${raceLeaf}-${agentLeaf}`),
+      );
+      const worktree = await addTaskWorktree(
+        projectRoot,
+        claim.branchName,
+        symlinkDirs,
+        undefined,
+        sparsePaths,
+      ).finally(claim.release);
+      entries.push({
+        agent_def_id: agentDefId,
+        id: randomUUID(),
+        branch_name: worktree.branch,
+        worktree_path: worktree.path,
+        submodule_error: worktree.submodule_error,
+        lfs_error: worktree.lfs_error,
+      });
+    }
+  } catch (err) {
+    for (const entry of entries) {
+      await removeWorktree(projectRoot, entry.branch_name, true).catch((cleanupErr) =>
+        log.warn(`Failed to roll back race worktree ${entry.branch_name}:`, cleanupErr),
+      );
+    }
+    throw err;
+  }
+
+  const raceId = randomUUID();
+  for (const entry of entries) {
+    recordTaskEvent(entry.id, 'created', {
+      name,
+      branch_name: entry.branch_name,
+      race_id: raceId,
+      agent_def_id: entry.agent_def_id,
+    });
+  }
+  return { race_id: raceId, entries };
+}
+
+/** Branch a task named `newName` gets, keeping the prefix of its current branch. */
+export function renamedBranchName(branchName: string, newName: string): string {
+  const parts = branchName.split('/');
+  const prefix = parts.length > 1 ? parts.slice(0, -1).join('/') : DEFAULT_BRANCH_PREFIX;
+  return ensureBranchNameHasLeaf(`${prefix}/${slug(newName) || DEFAULT_TASK_SLUG}`);
+}
+
+/**
+ * Rename a task's branch to match its new name and move the worktree along
+ * with it. Agents are killed first since their working directory moves;
+ * the caller respawns them in the new worktree.
+ */
+export async function renameTask(
+  agentIds: string[],
+  projectRoot: string,
+  branchName: string,
+  newName: string,
+): Promise<{ branch_name: string; worktree_path: string }> {
+  const newBranch = await uniqueBranchName(
+    renamedBranchName(branchName, newName),
+    async (b) => b !== branchName && (await branchTaken(projectRoot, b)),
+  );
+  if (newBranch === branchName) {
+    return {
+      branch_name: branchName,
+      worktree_path: getWorktreePath(projectRoot, branchName),
+    };
+  }
+  for (const agentId of agentIds) {
+    try {
+      killAgent(agentId);
+    } catch (err) {
+      log.debug(`Agent ${agentId} already exited`, err);
+    }
+  }
+  const worktree = await renameWorktreeBranch(projectRoot, branchName, newBranch);
+  notifyAgentListChanged();
+  return { branch_name: worktree.branch, worktree_path: worktree.path };
+}
+
 export async function deleteTask(
   agentIds: string[],
   branchName: string,
@@ -65,10 +306,58 @@ export async function deleteTask(
   for (const agentId of agentIds) {
     try {
       killAgent(agentId);
-    } catch {
-      /* already dead */
+    } catch (err) {
+      log.debug(`Agent ${agentId} already exited`, err);
     }
   }
   await removeWorktree(projectRoot, branchName, deleteBranch);
   notifyAgentListChanged();
 }
+
+/**
+ * Remove a task's worktree to reclaim disk space while keeping its branch,
+ * so it can be restored later. Refuses when the worktree has uncommitted
+ * changes, since those live only in the worktree.
+ */
+export async function archiveTask(
+  agentIds: string[],
+  branchName: string,
+  projectRoot: string,
+): Promise<void> {
+  const worktreePath = getWorktreePath(projectRoot, branchName);
+  if (fs.existsSync(worktreePath)) {
+    const status = await getWorktreeStatus(worktreePath, undefined, true);
+    if (status.has_uncommitted_changes) {
+      throw new AppError(
+        'uncommitted_changes',
+        'Task has uncommitted changes — commit or discard them before archiving',
+        { path: worktreePath, branch: branchName },
+      );
+    }
+  }
+  await deleteTask(agentIds, branchName, false, projectRoot);
+}
+
+/** Recreate the worktree of an archived task from its branch. */
+export async function restoreTask(
+  projectRoot: string,
+  branchName: string,
+  symlinkDirs: string[],
+  sparsePaths: string[] = [],
+): Promise<{ worktree_path: string; submodule_error?: string; lfs_error?: string }> {
+  if (!(await branchExists(projectRoot, branchName))) {
+    throw new Error(`Branch ${branchName} no longer exists`);
+  }
+  const worktree = await addTaskWorktree(
+    projectRoot,
+    branchName,
+    symlinkDirs,
+    undefined,
+    sparsePaths,
+  );
+  return {
+    worktree_path: worktree.path,
+    submodule_error: worktree.submodule_error,
+    lfs_error: worktree.lfs_error,
+  };
+}
@@ -0,0 +1,47 @@
+import { describe, expect, it } from 'vitest';
+import { parseToml } from './toml.js';
+
+describe('parseToml', () => {
+  it('reads tables, dotted keys, strings, numbers, arrays and inline tables', () => {
+    const doc = [
+      '# Agent overrides',
+      'version = 1',
+      '[agents."claude-code"]',
+      'args = [',
+      '  "--model", \'opus\', # trailing comma and comment',
+      ']',
+      'env = { A = "1", "B" = \'two\' }',
+      'limits.max = 1_000',
+      'ratio = -1.5',
+      'on = true',
+      '[agents.codex.env]',
+      'X = "a\\tb\\u0041"',
+    ].join('\n');
+    expect(parseToml(doc)).toEqual({
+      version: 1,
+      agents: {
+        'claude-code': {
+          args: ['--model', 'opus'],
+          env: { A: '1', B: 'two' },
+          limits: { max: 1000 },
+          ratio: -1.5,
+          on: true,
+        },
+        codex: { env: { X: 'a\tbA' } },
+      },
+    });
+  });
+
+  it('names the line of an error', () => {
+    expect(() => parseToml('a = 1\na = 2')).toThrow('line 2: duplicate key a');
+    expect(() => parseToml('a = [1 2]')).toThrow('line 1: expected , or ]');
+    expect(() => parseToml('a = 1 b')).toThrow('expected the end of the line');
+    expect(() => parseToml('a = "open')).toThrow('unterminated string');
+  });
+
+  it('rejects what it does not support', () => {
+    expect(() => parseToml('a = """x"""')).toThrow('multi-line strings');
+    expect(() => parseToml('[[servers]]')).toThrow('arrays of tables');
+    expect(() => parseToml('when = 2026-10-14')).toThrow('expected a value');
+  });
+});
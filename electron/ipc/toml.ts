@@ -0,0 +1,172 @@
+/**
+ * The part of TOML that config files checked into a repo need: tables,
+ * dotted keys, basic and literal strings, integers, floats, booleans,
+ * arrays and inline tables. Multi-line strings, dates and arrays of tables
+ * are rejected rather than misread.
+ */
+
+export type TomlValue = string | number | boolean | TomlValue[] | TomlTable;
+
+export interface TomlTable {
+  [key: string]: TomlValue;
+}
+
+const BARE_KEY = /[A-Za-z0-9_-]+/y;
+const SCALAR = /(true|false|[+-]?\d[\d_]*(\.\d+)?)(?=[\s,\]}#]|$)/y;
+const ESCAPES: Record<string, string> = { n: '\n', t: '\t', r: '\r', '"': '"', '\\': '\\' };
+
+export function isTomlTable(v: TomlValue | undefined): v is TomlTable {
+  return typeof v === 'object' && !Array.isArray(v);
+}
+
+/** Parse a TOML document. Errors name the line they were found on. */
+export function parseToml(src: string): TomlTable {
+  const root: TomlTable = {};
+  let pos = 0;
+
+  function fail(message: string): never {
+    throw new Error(`line ${src.slice(0, pos).split('\n').length}: ${message}`);
+  }
+
+  function skipSpace(): void {
+    while (src[pos] === ' ' || src[pos] === '\t') pos++;
+    if (src[pos] === '#') while (pos < src.length && src[pos] !== '\n') pos++;
+  }
+
+  /** Spaces, comments and line breaks, as allowed between array items. */
+  function skipBlank(): void {
+    for (skipSpace(); src[pos] === '\n' || src[pos] === '\r'; skipSpace()) pos++;
+  }
+
+  function endOfLine(): void {
+    skipSpace();
+    if (pos < src.length && src[pos] !== '\n' && src[pos] !== '\r') {
+      fail('expected the end of the line');
+    }
+  }
+
+  function string(): string {
+    const quote = src[pos];
+    if (src.startsWith(quote.repeat(3), pos)) fail('multi-line strings are not supported');
+    pos++;
+    let out = '';
+    while (src[pos] !== quote) {
+      const c = src[pos];
+      if (c === undefined || c === '\n') fail('unterminated string');
+      if (quote === '"' && c === '\\') {
+        const e = src[pos + 1] ?? '';
+        if (e in ESCAPES) {
+          out += ESCAPES[e];
+          pos += 2;
+          continue;
+        }
+        const hex = src.slice(pos + 2, pos + 6);
+        if (e !== 'u' || !/^[0-9a-fA-F]{4}$/.test(hex)) fail(`unknown escape \\${e}`);
+        out += String.fromCharCode(parseInt(hex, 16));
+        pos += 6;
+        continue;
+      }
+      out += c;
+      pos++;
+    }
+    pos++;
+    return out;
+  }
+
+  function key(): string {
+    skipSpace();
+    if (src[pos] === '"' || src[pos] === "'") return string();
+    BARE_KEY.lastIndex = pos;
+    const m = BARE_KEY.exec(src);
+    if (!m) fail('expected a key');
+    pos += m[0].length;
+    return m[0];
+  }
+
+  function keyPath(): string[] {
+    const parts = [key()];
+    for (skipSpace(); src[pos] === '.'; skipSpace()) {
+      pos++;
+      parts.push(key());
+    }
+    return parts;
+  }
+
+  function value(): TomlValue {
+    skipSpace();
+    const c = src[pos];
+    if (c === '"' || c === "'") return string();
+    if (c === '[') {
+      pos++;
+      const items: TomlValue[] = [];
+      for (skipBlank(); src[pos] !== ']'; skipBlank()) {
+        items.push(value());
+        skipBlank();
+        if (src[pos] === ',') pos++;
+        else if (src[pos] !== ']') fail('expected , or ]');
+      }
+      pos++;
+      return items;
+    }
+    if (c === '{') {
+      pos++;
+      const table: TomlTable = {};
+      skipSpace();
+      if (src[pos] === '}') {
+        pos++;
+        return table;
+      }
+      for (;;) {
+        assign(table);
+        skipSpace();
+        const next = src[pos++];
+        if (next === '}') return table;
+        if (next !== ',') fail('expected , or }');
+      }
+    }
+    SCALAR.lastIndex = pos;
+    const m = SCALAR.exec(src);
+    if (!m) fail('expected a value');
+    pos += m[0].length;
+    if (m[0] === 'true' || m[0] === 'false') return m[0] === 'true';
+    return Number(m[0].replace(/_/g, ''));
+  }
+
+  function tableAt(path: string[], from: TomlTable): TomlTable {
+    let table = from;
+    for (const k of path) {
+      const next = table[k] ?? (table[k] = {});
+      if (!isTomlTable(next)) fail(`${k} is not a table`);
+      table = next;
+    }
+    return table;
+  }
+
+  function assign(table: TomlTable): void {
+    const path = keyPath();
+    skipSpace();
+    if (src[pos] !== '=') fail('expected =');
+    pos++;
+    const target = tableAt(path.slice(0, -1), table);
+    const last = path[path.length - 1];
+    if (last in target) fail(`duplicate key ${last}`);
+    target[last] = value();
+  }
+
+  let current = root;
+  for (skipBlank(); pos < src.length; skipBlank()) {
+    if (src[pos] === '[') {
+      if (src[pos + 1] === '[') fail('arrays of tables are not supported');
+      pos++;
+      const path = keyPath();
+      skipSpace();
+      if (src[pos] !== ']') fail('expected ]');
+      pos++;
+      current = tableAt(path, root);
+    } else {
+      assign(current);
+    }
+    endOfLine();
+  }
+  return root;
+}
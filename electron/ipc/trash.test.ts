@@ -0,0 +1,34 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { unexpiredEntries, type TrashEntry } from './trash.js';
+
+const DAY = 24 * 60 * 60 * 1000;
+
+function entry(id: string, deletedAt: number): TrashEntry {
+  return {
+    id,
+    task_id: `task-${id}`,
+    project_root: '/repo',
+    branch_name: `task/${id}`,
+    tip_sha: 'abc123',
+    branch_deleted: true,
+    metadata: {},
+    deleted_at: deletedAt,
+  };
+}
+
+describe('unexpiredEntries', () => {
+  it('keeps entries deleted within the retention window', () => {
+    const now = 10 * DAY;
+    const all = [entry('old', now - 8 * DAY), entry('recent', now - 2 * DAY)];
+    expect(unexpiredEntries(all, now, 7).map((e) => e.id)).toEqual(['recent']);
+  });
+
+  it('keeps nothing when retention is 0', () => {
+    expect(unexpiredEntries([entry('a', 1000)], 1000, 0)).toEqual([]);
+  });
+});
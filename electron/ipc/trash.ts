@@ -0,0 +1,149 @@
+import fs from 'fs';
+import path from 'path';
+import { randomUUID } from 'crypto';
+import { getStateDir } from './persistence.js';
+import { getSettings } from './settings.js';
+import {
+  branchExists,
+  createBranchAt,
+  createWorktree,
+  deleteSnapshots,
+  getBranchTip,
+} from './git.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('trash');
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+/** A deleted task, kept so it can be restored within `trash_retention_days`. */
+export interface TrashEntry {
+  id: string;
+  task_id: string;
+  project_root: string;
+  branch_name: string;
+  /** Commit the branch pointed at when the task was deleted; null if it was already gone. */
+  tip_sha: string | null;
+  branch_deleted: boolean;
+  /** Task fields from the frontend (name, notes, prompts, agent), returned on restore. */
+  metadata: Record<string, unknown>;
+  deleted_at: number;
+}
+
+export interface RestoredTask {
+  entry: TrashEntry;
+  worktree_path: string;
+}
+
+let entries: TrashEntry[] | null = null;
+
+function storePath(): string {
+  return path.join(getStateDir(), 'trash.json');
+}
+
+function load(): TrashEntry[] {
+  if (entries) return entries;
+  try {
+    entries = JSON.parse(fs.readFileSync(storePath(), 'utf8')) as TrashEntry[];
+  } catch {
+    entries = [];
+  }
+  return entries;
+}
+
+function save(): void {
+  try {
+    const file = storePath();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    const tmp = file + '.tmp';
+    fs.writeFileSync(tmp, JSON.stringify(entries ?? []), 'utf8');
+    fs.renameSync(tmp, file);
+  } catch (err) {
+    log.warn('Failed to save trash', err);
+  }
+}
+
+/** Entries deleted within the last `retentionDays`. */
+export function unexpiredEntries(
+  all: TrashEntry[],
+  now: number,
+  retentionDays: number,
+): TrashEntry[] {
+  return all.filter((e) => now - e.deleted_at < retentionDays * DAY_MS);
+}
+
+/** A task that can't come back has no use for its snapshots. */
+function forgetSnapshots(projectRoot: string, taskId: string): void {
+  deleteSnapshots(projectRoot, taskId).catch((err) =>
+    log.warn(`Failed to delete snapshots of task ${taskId}`, err),
+  );
+}
+
+function prune(): TrashEntry[] {
+  const all = load();
+  const kept = unexpiredEntries(all, Date.now(), getSettings().trash_retention_days);
+  if (kept.length !== all.length) {
+    for (const e of all) {
+      if (!kept.includes(e)) forgetSnapshots(e.project_root, e.task_id);
+    }
+    entries = kept;
+    save();
+  }
+  return kept;
+}
+
+/**
+ * Note a task about to be deleted, with the commit its branch points at.
+ * Call before the branch is removed. Skipped when retention is 0.
+ */
+export async function recordDeletedTask(args: {
+  taskId: string;
+  projectRoot: string;
+  branchName: string;
+  branchDeleted: boolean;
+  metadata: Record<string, unknown>;
+}): Promise<void> {
+  if (getSettings().trash_retention_days <= 0) {
+    forgetSnapshots(args.projectRoot, args.taskId);
+    return;
+  }
+  const tipSha = await getBranchTip(args.projectRoot, args.branchName).catch(() => null);
+  const all = prune().filter((e) => e.task_id !== args.taskId);
+  all.push({
+    id: randomUUID(),
+    task_id: args.taskId,
+    project_root: args.projectRoot,
+    branch_name: args.branchName,
+    tip_sha: tipSha,
+    branch_deleted: args.branchDeleted,
+    metadata: args.metadata,
+    deleted_at: Date.now(),
+  });
+  entries = all;
+  save();
+}
+
+/** Deleted tasks that can still be restored, newest first. */
+export function listTrash(): TrashEntry[] {
+  return [...prune()].sort((a, b) => b.deleted_at - a.deleted_at);
+}
+
+/**
+ * Bring a deleted task's branch and worktree back. A deleted branch is
+ * recreated at its recorded tip; uncommitted changes aren't recoverable.
+ */
+export async function restoreDeletedTask(
+  entryId: string,
+  symlinkDirs: string[],
+): Promise<RestoredTask> {
+  const entry = prune().find((e) => e.id === entryId);
+  if (!entry) throw new Error('Trash entry not found or expired');
+  if (!(await branchExists(entry.project_root, entry.branch_name))) {
+    if (!entry.tip_sha) throw new Error(`Branch ${entry.branch_name} can't be recovered`);
+    await createBranchAt(entry.project_root, entry.branch_name, entry.tip_sha);
+  }
+  const worktree = await createWorktree(entry.project_root, entry.branch_name, symlinkDirs);
+  entries = load().filter((e) => e.id !== entryId);
+  save();
+  return { entry, worktree_path: worktree.path };
+}
@@ -0,0 +1,98 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+import { createUsageParser, parseTokenCount, sumUsage } from './usage.js';
+import { getAgentAdapter } from './adapters/index.js';
+
+function parserFor(adapterId: string) {
+  const parseLine = getAgentAdapter(adapterId)?.parseUsageLine;
+  if (!parseLine) throw new Error(`${adapterId} has no usage parser`);
+  return createUsageParser(parseLine);
+}
+
+describe('parseTokenCount', () => {
+  it('parses plain, comma-separated and suffixed counts', () => {
+    expect(parseTokenCount('42')).toBe(42);
+    expect(parseTokenCount('12,345')).toBe(12345);
+    expect(parseTokenCount('1.2k')).toBe(1200);
+    expect(parseTokenCount('3.4m')).toBe(3_400_000);
+    expect(parseTokenCount('n/a')).toBe(0);
+  });
+});
+
+describe('createUsageParser', () => {
+  it('parses a Claude Code /cost summary across chunks', () => {
+    const parser = parserFor('claude-code');
+    expect(parser.feed('  Total cost:            $0.05')).toBeNull();
+    expect(parser.feed('20\n  Usage by model:\n')).toMatchObject({ cost_usd: 0.052 });
+    const usage = parser.feed(
+      '    claude-haiku:  1.2k input, 45 output, 0 cache read, 0 cache write\n' +
+        '   claude-sonnet:  22 input, 1.1k output, 34.5k cache read, 8.9k cache write\n',
+    );
+    expect(usage).toEqual({
+      input_tokens: 1222,
+      output_tokens: 1145,
+      cache_read_tokens: 34500,
+      cache_write_tokens: 8900,
+      total_tokens: 45767,
+      cost_usd: 0.052,
+    });
+  });
+
+  it('replaces the previous snapshot when a new summary starts', () => {
+    const parser = parserFor('claude-code');
+    parser.feed('Total cost: $1.00\n  model: 10 input, 10 output\n');
+    const usage = parser.feed('Total cost: $2.00\n  model: 5 input, 5 output\n');
+    expect(usage).toMatchObject({ cost_usd: 2, input_tokens: 5, output_tokens: 5 });
+  });
+
+  it('ignores usage lines without a preceding cost header', () => {
+    const parser = parserFor('claude-code');
+    expect(parser.feed('10 input, 10 output\n')).toBeNull();
+  });
+
+  it('parses the Codex exit summary through ANSI styling', () => {
+    const parser = parserFor('codex');
+    const usage = parser.feed(
+      '\x1b[1mToken usage:\x1b[0m total=12,345 input=10,000 (+ 2,000 cached) output=2,345\r\n',
+    );
+    expect(usage).toEqual({
+      input_tokens: 10000,
+      output_tokens: 2345,
+      cache_read_tokens: 2000,
+      cache_write_tokens: 0,
+      total_tokens: 12345,
+      cost_usd: null,
+    });
+  });
+});
+
+describe('aider usage', () => {
+  it('adds up per-reply tokens and keeps the session cost', () => {
+    const parser = parserFor('aider');
+    parser.feed('Tokens: 1.2k sent, 300 received. Cost: $0.01 message, $0.01 session.\n');
+    const usage = parser.feed(
+      'Tokens: 2k sent, 500 cache hit, 100 received. Cost: $0.02 message, $0.03 session.\n',
+    );
+    expect(usage).toEqual({
+      input_tokens: 3200,
+      output_tokens: 400,
+      cache_read_tokens: 500,
+      cache_write_tokens: 0,
+      total_tokens: 4100,
+      cost_usd: 0.03,
+    });
+  });
+});
+
+describe('sumUsage', () => {
+  it('sums tokens and keeps cost null when no session reported one', () => {
+    const a = parserFor('codex').feed('Token usage: total=3 input=2 output=1\n');
+    const b = parserFor('codex').feed('Token usage: total=7 input=4 output=3\n');
+    if (!a || !b) throw new Error('expected usage');
+    expect(sumUsage([a, b])).toMatchObject({ total_tokens: 10, cost_usd: null });
+  });
+});
@@ -0,0 +1,176 @@
+import fs from 'fs';
+import path from 'path';
+import { stripAnsiKeepRows } from './ansi.js';
+import { getStateDir } from './persistence.js';
+import { indexUsage } from './storage.js';
+import type { UsageLineParser } from './adapters/types.js';
+
+export interface UsageMetrics {
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+export interface SessionUsage extends UsageMetrics {
+  agent_id: string;
+  agent_def_id: string | null;
+  updated_at: number;
+}
+
+export interface TaskUsage {
+  task_id: string;
+  sessions: SessionUsage[];
+  totals: UsageMetrics;
+}
+
+const LINE_BUF_CAP = 4 * 1024;
+const SAVE_DEBOUNCE_MS = 2_000;
+
+export function emptyMetrics(): UsageMetrics {
+  return {
+    input_tokens: 0,
+    output_tokens: 0,
+    cache_read_tokens: 0,
+    cache_write_tokens: 0,
+    total_tokens: 0,
+    cost_usd: null,
+  };
+}
+
+/** Parse "1,234", "1.2k" or "3.4m" into a number. */
+export function parseTokenCount(raw: string): number {
+  const m = raw
+    .trim()
+    .replace(/,/g, '')
+    .match(/^([\d.]+)\s*([kKmM]?)$/);
+  if (!m) return 0;
+  const n = parseFloat(m[1]);
+  if (isNaN(n)) return 0;
+  const mult = m[2].toLowerCase() === 'k' ? 1_000 : m[2].toLowerCase() === 'm' ? 1_000_000 : 1;
+  return Math.round(n * mult);
+}
+
+/**
+ * Incremental parser for one session's output. Feed raw PTY text; returns
+ * the latest cumulative metrics whenever `parseLine` recognized a summary
+ * line. Whether a summary replaces the previous totals or adds to them is up
+ * to the agent's adapter, since some print session totals and some per reply.
+ */
+export function createUsageParser(parseLine: UsageLineParser) {
+  let pending = '';
+  let current: UsageMetrics | null = null;
+
+  return {
+    feed(text: string): UsageMetrics | null {
+      pending += stripAnsiKeepRows(text);
+      const lines = pending.split(/[\r\n]/);
+      // Keep the trailing partial line for the next chunk
+      pending = (lines.pop() ?? '').slice(-LINE_BUF_CAP);
+      let changed = false;
+      for (const line of lines) {
+        const next = parseLine(line, current);
+        if (next) {
+          current = next;
+          changed = true;
+        }
+      }
+      return changed && current ? { ...current } : null;
+    },
+  };
+}
+
+// --- Aggregation and persistence ---
+
+/** taskId -> agentId -> latest usage */
+let usageByTask: Map<string, Map<string, SessionUsage>> | null = null;
+let saveTimer: ReturnType<typeof setTimeout> | null = null;
+
+function getUsagePath(): string {
+  return path.join(getStateDir(), 'usage.json');
+}
+
+function loadUsage(): Map<string, Map<string, SessionUsage>> {
+  if (usageByTask) return usageByTask;
+  usageByTask = new Map();
+  try {
+    const raw = JSON.parse(fs.readFileSync(getUsagePath(), 'utf8')) as Record<
+      string,
+      Record<string, SessionUsage>
+    >;
+    for (const [taskId, sessions] of Object.entries(raw)) {
+      usageByTask.set(taskId, new Map(Object.entries(sessions)));
+    }
+  } catch {
+    // No usage recorded yet
+  }
+  return usageByTask;
+}
+
+function scheduleSave(): void {
+  if (saveTimer) return;
+  saveTimer = setTimeout(() => {
+    saveTimer = null;
+    flushUsage();
+  }, SAVE_DEBOUNCE_MS);
+}
+
+/** Write pending usage to disk immediately. */
+export function flushUsage(): void {
+  if (!usageByTask) return;
+  const out: Record<string, Record<string, SessionUsage>> = {};
+  for (const [taskId, sessions] of usageByTask) {
+    out[taskId] = Object.fromEntries(sessions);
+  }
+  try {
+    const usagePath = getUsagePath();
+    fs.mkdirSync(path.dirname(usagePath), { recursive: true });
+    const tmpPath = usagePath + '.tmp';
+    fs.writeFileSync(tmpPath, JSON.stringify(out), 'utf8');
+    fs.renameSync(tmpPath, usagePath);
+  } catch (err) {
+    console.warn('Failed to save usage data:', err);
+  }
+}
+
+export function recordSessionUsage(
+  taskId: string,
+  agentId: string,
+  agentDefId: string | null,
+  metrics: UsageMetrics,
+): void {
+  const all = loadUsage();
+  let sessions = all.get(taskId);
+  if (!sessions) {
+    sessions = new Map();
+    all.set(taskId, sessions);
+  }
+  sessions.set(agentId, {
+    ...metrics,
+    agent_id: agentId,
+    agent_def_id: agentDefId,
+    updated_at: Date.now(),
+  });
+  indexUsage(taskId, agentId, agentDefId, metrics);
+  scheduleSave();
+}
+
+export function sumUsage(items: UsageMetrics[]): UsageMetrics {
+  const totals = emptyMetrics();
+  for (const u of items) {
+    totals.input_tokens += u.input_tokens;
+    totals.output_tokens += u.output_tokens;
+    totals.cache_read_tokens += u.cache_read_tokens;
+    totals.cache_write_tokens += u.cache_write_tokens;
+    totals.total_tokens += u.total_tokens;
+    if (u.cost_usd !== null) totals.cost_usd = (totals.cost_usd ?? 0) + u.cost_usd;
+  }
+  return totals;
+}
+
+export function getTaskUsage(taskId: string): TaskUsage {
+  const sessions = [...(loadUsage().get(taskId)?.values() ?? [])];
+  return { task_id: taskId, sessions, totals: sumUsage(sessions) };
+}
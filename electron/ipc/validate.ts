@@ -1,3 +1,5 @@
+import { isValidBranchName } from './branch-names.js';
+
 /** Runtime type assertion helpers for IPC handler args. */
 
 export function assertString(val: unknown, label: string): asserts val is string {
@@ -33,3 +35,10 @@ export function assertOptionalBoolean(
   if (val !== undefined && typeof val !== 'boolean')
     throw new Error(`${label} must be a boolean or undefined`);
 }
+
+/** Reject branch names that could be misinterpreted as git flags, or that git wouldn't take. */
+export function validateBranchName(name: unknown, label: string): asserts name is string {
+  if (typeof name !== 'string' || !name) throw new Error(`${label} must be a non-empty string`);
+  if (name.startsWith('-')) throw new Error(`${label} must not start with "-"`);
+  if (!isValidBranchName(name)) throw new Error(`${label} is not a valid branch name`);
+}
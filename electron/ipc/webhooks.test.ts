@@ -0,0 +1,89 @@
+import { afterEach, describe, expect, it, vi } from 'vitest';
+
+const settings = vi.hoisted(() => ({
+  webhooks: [] as Array<{ url: string; secret_name: string; events: string[] }>,
+}));
+const storedSecrets = vi.hoisted(() => new Map<string, string>([['HOOK_KEY', 's3cret']]));
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('./settings.js', () => ({
+  getSettings: () => settings,
+}));
+
+vi.mock('./secrets.js', () => ({
+  getSecret: (name: string) => storedSecrets.get(name) ?? null,
+}));
+
+import { emitWebhookEvent, signWebhookBody, wantsEvent } from './webhooks.js';
+
+afterEach(() => {
+  vi.unstubAllGlobals();
+  settings.webhooks = [];
+});
+
+describe('signWebhookBody', () => {
+  it('produces a sha256 HMAC of the timestamp and body', () => {
+    expect(signWebhookBody('secret', 1700000000000, '{}')).toBe(
+      'sha256=8399216d111287e3bb28e25c0f4f31dffdf831c68c9ee2b96c2f67c9b81d341b',
+    );
+  });
+});
+
+describe('wantsEvent', () => {
+  it('treats an empty filter as all events', () => {
+    const hook = { url: 'https://x', secret_name: '' };
+    expect(wantsEvent({ ...hook, events: [] }, 'task_merged')).toBe(true);
+    expect(wantsEvent({ ...hook, events: ['checks_failed'] }, 'task_merged')).toBe(false);
+  });
+});
+
+describe('emitWebhookEvent', () => {
+  it('posts a signed payload to matching hooks only', async () => {
+    const fetchMock = vi.fn(async () => new Response(null, { status: 204 }));
+    vi.stubGlobal('fetch', fetchMock);
+    settings.webhooks = [
+      { url: 'https://hooks.example/a', secret_name: 'HOOK_KEY', events: ['task_merged'] },
+      { url: 'https://hooks.example/b', secret_name: '', events: ['checks_failed'] },
+    ];
+
+    emitWebhookEvent('task_merged', 'task-1', { name: 'Fix login' });
+    await vi.waitFor(() => expect(fetchMock).toHaveBeenCalledTimes(1));
+
+    const [url, init] = fetchMock.mock.calls[0] as unknown as [string, RequestInit];
+    expect(url).toBe('https://hooks.example/a');
+    const headers = init.headers as Record<string, string>;
+    expect(headers['X-Parallel-Code-Event']).toBe('task_merged');
+    const timestamp = Number(headers['X-Parallel-Code-Timestamp']);
+    expect(timestamp).toBeGreaterThan(0);
+    expect(headers['X-Parallel-Code-Signature']).toBe(
+      signWebhookBody('s3cret', timestamp, init.body as string),
+    );
+    expect(JSON.parse(init.body as string)).toMatchObject({
+      event: 'task_merged',
+      task_id: 'task-1',
+      task_name: 'Fix login',
+      text: 'Task merged: Fix login',
+    });
+  });
+
+  it('sends unsigned without a secret, and not at all when its secret is gone', async () => {
+    const fetchMock = vi.fn(async () => new Response(null, { status: 204 }));
+    vi.stubGlobal('fetch', fetchMock);
+    settings.webhooks = [
+      { url: 'https://hooks.example/lost', secret_name: 'DELETED_KEY', events: [] },
+      { url: 'https://hooks.example/plain', secret_name: '', events: [] },
+    ];
+
+    emitWebhookEvent('task_created', 'task-1');
+    await vi.waitFor(() => expect(fetchMock).toHaveBeenCalledTimes(1));
+
+    const [url, init] = fetchMock.mock.calls[0] as unknown as [string, RequestInit];
+    expect(url).toBe('https://hooks.example/plain');
+    const headers = init.headers as Record<string, string>;
+    expect(headers['X-Parallel-Code-Signature']).toBeUndefined();
+    expect(headers['X-Parallel-Code-Timestamp']).toBeUndefined();
+  });
+});
@@ -0,0 +1,144 @@
+import { createHmac } from 'crypto';
+import { getSettings, type WebhookConfig, type WebhookEvent } from './settings.js';
+import { getSecret } from './secrets.js';
+import { onTaskEvent, type TaskEventType } from './task-events.js';
+import { createLogger } from './log.js';
+
+const log = createLogger('webhooks');
+
+const DELIVERY_TIMEOUT_MS = 10_000;
+const DELIVERY_ATTEMPTS = 3;
+
+const EVENT_FOR_TASK_EVENT: Partial<Record<TaskEventType, WebhookEvent>> = {
+  created: 'task_created',
+  agent_exited: 'agent_exited',
+  merged: 'task_merged',
+  pushed: 'task_pushed',
+  deleted: 'task_deleted',
+  run_finished: 'run_finished',
+};
+
+export interface WebhookPayload {
+  event: WebhookEvent;
+  task_id: string;
+  /** Task name when known. */
+  task_name: string | null;
+  timestamp: number;
+  /** One-line summary; also lets Slack incoming webhooks display the event as-is. */
+  text: string;
+  data: Record<string, unknown>;
+}
+
+/**
+ * `sha256=<hex>` HMAC of `<timestamp>.<body>`, the timestamp being the one
+ * sent in `X-Parallel-Code-Timestamp` (epoch ms). Signing it too lets
+ * receivers reject old deliveries that were recorded and replayed.
+ */
+export function signWebhookBody(secret: string, timestamp: number, body: string): string {
+  return `sha256=${createHmac('sha256', secret).update(`${timestamp}.${body}`).digest('hex')}`;
+}
+
+/** The hook's signing key from the keychain; undefined when it has none, null when it's lost. */
+function signingSecret(hook: WebhookConfig): string | null | undefined {
+  if (!hook.secret_name) return undefined;
+  try {
+    return getSecret(hook.secret_name);
+  } catch (err) {
+    log.warn(`Failed to decrypt webhook secret ${hook.secret_name}:`, err);
+    return null;
+  }
+}
+
+export function wantsEvent(hook: WebhookConfig, event: WebhookEvent): boolean {
+  return hook.events.length === 0 || hook.events.includes(event);
+}
+
+const SUMMARIES: Record<WebhookEvent, string> = {
+  task_created: 'Task created',
+  agent_exited: 'Agent exited',
+  task_merged: 'Task merged',
+  task_pushed: 'Task branch pushed',
+  task_deleted: 'Task closed',
+  checks_failed: 'Pre-merge checks failed',
+  run_finished: 'Scheduled run finished',
+};
+
+function summarize(event: WebhookEvent, taskName: string | null, taskId: string): string {
+  return `${SUMMARIES[event]}: ${taskName ?? taskId}`;
+}
+
+async function deliver(hook: WebhookConfig, event: WebhookEvent, body: string): Promise<void> {
+  const secret = signingSecret(hook);
+  if (secret === null) {
+    // Unsigned, the receiver would have to reject it anyway
+    log.warn(`Not sending ${event} to ${hook.url}: secret ${hook.secret_name} is not stored`);
+    return;
+  }
+
+  for (let attempt = 1; attempt <= DELIVERY_ATTEMPTS; attempt++) {
+    const headers: Record<string, string> = {
+      'Content-Type': 'application/json',
+      'User-Agent': 'parallel-code',
+      'X-Parallel-Code-Event': event,
+    };
+    if (secret !== undefined) {
+      // Signed again on each attempt, so a retry isn't mistaken for a replay
+      const timestamp = Date.now();
+      headers['X-Parallel-Code-Timestamp'] = String(timestamp);
+      headers['X-Parallel-Code-Signature'] = signWebhookBody(secret, timestamp, body);
+    }
+    try {
+      const res = await fetch(hook.url, {
+        method: 'POST',
+        headers,
+        body,
+        signal: AbortSignal.timeout(DELIVERY_TIMEOUT_MS),
+      });
+      if (res.ok) return;
+      // Client errors won't succeed on retry, except rate limiting
+      if (res.status < 500 && res.status !== 429) {
+        log.warn(`Webhook ${hook.url} rejected ${event}: HTTP ${res.status}`);
+        return;
+      }
+      log.debug(`Webhook ${hook.url} returned HTTP ${res.status} (attempt ${attempt})`);
+    } catch (err) {
+      log.debug(`Webhook ${hook.url} failed (attempt ${attempt})`, err);
+    }
+    if (attempt < DELIVERY_ATTEMPTS) await new Promise((r) => setTimeout(r, 1000 * 2 ** attempt));
+  }
+  log.warn(`Giving up delivering ${event} to ${hook.url}`);
+}
+
+let getTaskName: (taskId: string) => string | null = () => null;
+let stopForwarding: (() => void) | null = null;
+
+/** Send `event` to every configured webhook that wants it. Never throws. */
+export function emitWebhookEvent(
+  event: WebhookEvent,
+  taskId: string,
+  data: Record<string, unknown> = {},
+): void {
+  const hooks = getSettings().webhooks.filter((hook) => wantsEvent(hook, event));
+  if (hooks.length === 0) return;
+  const taskName = typeof data.name === 'string' ? data.name : getTaskName(taskId);
+  const payload: WebhookPayload = {
+    event,
+    task_id: taskId,
+    task_name: taskName,
+    timestamp: Date.now(),
+    text: summarize(event, taskName, taskId),
+    data,
+  };
+  const body = JSON.stringify(payload);
+  for (const hook of hooks) void deliver(hook, event, body);
+}
+
+/** Forward recorded task events to webhooks. `taskName` resolves names for payloads. */
+export function startWebhooks(taskName: (taskId: string) => string | null): void {
+  getTaskName = taskName;
+  stopForwarding?.();
+  stopForwarding = onTaskEvent((taskId, taskEvent) => {
+    const event = EVENT_FOR_TASK_EVENT[taskEvent.type];
+    if (event) emitWebhookEvent(event, taskId, taskEvent.data);
+  });
+}
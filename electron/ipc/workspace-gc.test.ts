@@ -0,0 +1,218 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterEach, describe, expect, it, vi } from 'vitest';
+
+const execFileMock = vi.hoisted(() => vi.fn());
+
+vi.mock('./persistence.js', () => ({
+  getStateDir: () => '/nonexistent',
+}));
+
+vi.mock('child_process', () => ({
+  execFile: execFileMock,
+  default: {
+    execFile: execFileMock,
+  },
+}));
+
+import {
+  classifyWorkspace,
+  gcWorkspace,
+  keepReason,
+  keepWorktreeReason,
+  parseWorktreeList,
+  type WorktreeEntry,
+} from './workspace-gc.js';
+import { getWorktreeRoot } from './settings.js';
+
+const LIST = [
+  'worktree /repo',
+  'HEAD 1111111111111111111111111111111111111111',
+  'branch refs/heads/main',
+  '',
+  'worktree /repo/.worktrees/task/live',
+  'HEAD 2222222222222222222222222222222222222222',
+  'branch refs/heads/task/live',
+  '',
+  'worktree /repo/.worktrees/task/gone',
+  'HEAD 3333333333333333333333333333333333333333',
+  'branch refs/heads/task/gone',
+  'prunable gitdir file points to non-existent location',
+  '',
+  'worktree /repo/.worktrees/task/rebasing',
+  'HEAD 4444444444444444444444444444444444444444',
+  'detached',
+  'locked',
+  '',
+].join('\n');
+
+const worktree = (path: string, branch: string | null): WorktreeEntry => ({
+  path,
+  branch,
+  prunable: null,
+  locked: false,
+});
+
+describe('parseWorktreeList', () => {
+  it('reads branches, prunable and locked worktrees', () => {
+    expect(parseWorktreeList(LIST)).toEqual([
+      { path: '/repo', branch: 'main', prunable: null, locked: false },
+      { path: '/repo/.worktrees/task/live', branch: 'task/live', prunable: null, locked: false },
+      {
+        path: '/repo/.worktrees/task/gone',
+        branch: 'task/gone',
+        prunable: 'gitdir file points to non-existent location',
+        locked: false,
+      },
+      { path: '/repo/.worktrees/task/rebasing', branch: null, prunable: null, locked: true },
+    ]);
+  });
+});
+
+describe('classifyWorkspace', () => {
+  const managed = {
+    branches: new Set(['task/live']),
+    paths: new Set(['/repo/.worktrees/task/live', '/repo/.worktrees/task/rebase']),
+    branchPrefix: 'task',
+  };
+
+  it('finds worktrees and task branches no task owns', () => {
+    const found = classifyWorkspace(
+      {
+        worktrees: [
+          worktree('/repo', 'main'),
+          worktree('/repo/.worktrees/task/live', 'task/live'),
+          worktree('/repo/.worktrees/task/old', 'task/old'),
+          // A task mid-rebase is detached, but its path is known
+          worktree('/repo/.worktrees/task/rebase', null),
+          worktree('/repo/.worktrees/arena/run-1', 'arena/run-1'),
+          worktree('/elsewhere/mine', 'task/mine'),
+        ],
+        branches: ['task/live', 'task/old', 'task/closed', 'task/mine'],
+        worktreeRoot: '/repo/.worktrees',
+      },
+      managed,
+    );
+    expect(found.worktrees.map((w) => w.path)).toEqual(['/repo/.worktrees/task/old']);
+    // task/mine is checked out in a worktree of the user's own
+    expect(found.branches).toEqual(['task/old', 'task/closed']);
+  });
+
+  it('leaves locked worktrees and lists prunable ones apart', () => {
+    const found = classifyWorkspace(
+      { worktrees: parseWorktreeList(LIST), branches: [], worktreeRoot: '/repo/.worktrees' },
+      managed,
+    );
+    expect(found.worktrees).toEqual([]);
+    expect(found.prunable.map((w) => w.branch)).toEqual(['task/gone']);
+  });
+});
+
+describe('keepReason', () => {
+  const merged = { branch: 'task/old', tip: 'abc', unmerged: 0 };
+  const unmerged = { branch: 'task/wip', tip: 'def', unmerged: 2 };
+
+  it('deletes only confirmed branches still at the confirmed tip', () => {
+    const confirmation = {
+      worktrees: [],
+      branches: { 'task/old': 'abc', 'task/wip': 'def' },
+      force: false,
+    };
+    expect(keepReason(merged, confirmation)).toBeNull();
+    expect(keepReason({ ...merged, branch: 'task/other' }, confirmation)).toBe('not confirmed');
+    expect(keepReason({ ...merged, tip: 'abd' }, confirmation)).toContain('new commits');
+    expect(keepReason({ ...merged, tip: '' }, confirmation)).toContain('new commits');
+  });
+
+  it('keeps branches with unmerged commits unless forced', () => {
+    const confirmation = { worktrees: [], branches: { 'task/wip': 'def' }, force: false };
+    expect(keepReason(unmerged, confirmation)).toContain('2 unmerged commits');
+    expect(keepReason(unmerged, { ...confirmation, force: true })).toBeNull();
+  });
+});
+
+describe('keepWorktreeReason', () => {
+  const clean = { path: '/wt/a', branch: 'task/a', registered: true, dirty: false, bytes: 1 };
+  const dirty = { ...clean, dirty: true };
+
+  it('removes only confirmed worktrees, dirty ones only when forced', () => {
+    const confirmation = { worktrees: ['/wt/a'], branches: {}, force: false };
+    expect(keepWorktreeReason(clean, confirmation)).toBeNull();
+    expect(keepWorktreeReason({ ...clean, path: '/wt/b' }, confirmation)).toBe('not confirmed');
+    expect(keepWorktreeReason(dirty, confirmation)).toContain('uncommitted or untracked');
+    expect(keepWorktreeReason(dirty, { ...confirmation, force: true })).toBeNull();
+  });
+});
+
+describe('gcWorkspace', () => {
+  let tmp: string | null = null;
+
+  afterEach(() => {
+    vi.useRealTimers();
+    if (tmp) fs.rmSync(tmp, { recursive: true, force: true });
+    tmp = null;
+  });
+
+  /** A worktree folder whose `.git` file points at `metadata` in the repo. */
+  function worktreeDir(dir: string, metadata: string): void {
+    fs.mkdirSync(dir, { recursive: true });
+    fs.writeFileSync(path.join(dir, '.git'), `gitdir: ${metadata}\n`);
+    fs.writeFileSync(path.join(dir, 'file.txt'), 'work');
+  }
+
+  it('keeps a dirty worktree and a folder the repo did not create', async () => {
+    tmp = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), 'gc-')));
+    const repo = path.join(tmp, 'repo');
+    const commonDir = path.join(repo, '.git');
+    const root = getWorktreeRoot(repo);
+    const dirty = path.join(root, 'task', 'dirty');
+    const clean = path.join(root, 'task', 'clean');
+    const unrelated = path.join(root, 'notes');
+    for (const name of ['dirty', 'clean']) {
+      fs.mkdirSync(path.join(commonDir, 'worktrees', name), { recursive: true });
+    }
+    worktreeDir(dirty, path.join(commonDir, 'worktrees', 'dirty'));
+    worktreeDir(clean, path.join(commonDir, 'worktrees', 'clean'));
+    fs.mkdirSync(unrelated, { recursive: true });
+    fs.writeFileSync(path.join(unrelated, 'todo.md'), 'mine');
+
+    const list = [
+      `worktree ${repo}\nbranch refs/heads/main`,
+      `worktree ${dirty}\nbranch refs/heads/task/dirty`,
+      `worktree ${clean}\nbranch refs/heads/task/clean`,
+    ].join('\n\n');
+    execFileMock.mockReset();
+    execFileMock.mockImplementation(
+      (_cmd: string, args: string[], opts: { cwd: string }, cb: (...a: unknown[]) => void) => {
+        let stdout = '';
+        if (args[0] === 'rev-parse') stdout = `${commonDir}\n`;
+        else if (args[0] === 'worktree' && args[1] === 'list') stdout = `${list}\n`;
+        else if (args[0] === 'status' && opts.cwd === dirty) stdout = '?? notes.txt\n';
+        cb(null, { stdout, stderr: '' });
+      },
+    );
+    // Past the grace period for worktrees of tasks still being created
+    vi.useFakeTimers({ toFake: ['Date'] });
+    vi.setSystemTime(Date.now() + 60 * 60_000);
+
+    const managed = { branches: [], worktreePaths: [], branchPrefix: 'task' };
+    const listed = await gcWorkspace(repo, managed, true);
+    expect(listed.worktrees.map((w) => [w.path, w.dirty])).toEqual([
+      [dirty, true],
+      [clean, false],
+    ]);
+
+    const report = await gcWorkspace(repo, managed, false, {
+      worktrees: [dirty, clean, unrelated],
+      branches: {},
+      force: false,
+    });
+    expect(report.kept_worktrees).toEqual([
+      { path: dirty, reason: 'has uncommitted or untracked changes; force removes it' },
+    ]);
+    expect(fs.existsSync(path.join(dirty, 'file.txt'))).toBe(true);
+    expect(fs.existsSync(path.join(unrelated, 'todo.md'))).toBe(true);
+    expect(fs.existsSync(clean)).toBe(false);
+  });
+});
@@ -0,0 +1,379 @@
+import fs from 'fs';
+import path from 'path';
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { getBranchDeletionRisk, getBranchTip, withRepoLock } from './git.js';
+import { forgetDiskUsage, measureDir } from './disk-usage.js';
+import { getWorktreePath, getWorktreeRoot } from './settings.js';
+import { remoteTargetFor } from './ssh.js';
+import { createLogger } from './log.js';
+
+/**
+ * What piles up in a project's worktree root over time: worktrees no task
+ * owns, directories git no longer knows about, worktree metadata whose
+ * directory is gone, and task branches left behind by closed tasks. Arena
+ * worktrees and branches outside the task branch prefix are left alone.
+ */
+
+const exec = promisify(execFile);
+const log = createLogger('workspace-gc');
+const MAX_BUFFER = 10 * 1024 * 1024;
+/** A worktree this new may belong to a task that's still being created. */
+const NEW_WORKTREE_MS = 10 * 60_000;
+
+export interface WorktreeEntry {
+  path: string;
+  /** Without `refs/heads/`; null when detached. */
+  branch: string | null;
+  /** Why git considers it prunable, when it does. */
+  prunable: string | null;
+  locked: boolean;
+}
+
+export interface UnmanagedWorktree {
+  path: string;
+  branch: string | null;
+  /** False for directories git has no worktree registered at. */
+  registered: boolean;
+  /** Uncommitted or untracked changes, which removal would lose. */
+  dirty: boolean;
+  bytes: number;
+}
+
+export interface OrphanedBranch {
+  branch: string;
+  tip: string;
+  /** Commits on it the main branch doesn't have. */
+  unmerged: number;
+}
+
+/** The worktrees and branches a person agreed to delete after a dry run, and how far. */
+export interface WorkspaceGcConfirmation {
+  /** Paths of worktrees and leftover folders the dry run listed. */
+  worktrees: string[];
+  /** Branch name to the tip the dry run showed; one that moved since is kept. */
+  branches: Record<string, string>;
+  /**
+   * Remove confirmed worktrees with uncommitted or untracked changes, and
+   * delete confirmed branches with commits main doesn't have, too.
+   */
+  force: boolean;
+}
+
+export interface WorkspaceGcReport {
+  project_root: string;
+  dry_run: boolean;
+  worktrees: UnmanagedWorktree[];
+  prunable: Array<{ path: string; reason: string }>;
+  branches: OrphanedBranch[];
+  /** Worktrees a removal left alone, and why. */
+  kept_worktrees: Array<{ path: string; reason: string }>;
+  /** Orphaned branches a removal left alone, and why. */
+  kept_branches: Array<{ branch: string; reason: string }>;
+  /** Space freed, or with `dry_run` what removing everything would free. */
+  reclaimed_bytes: number;
+  /** Items that couldn't be removed; the rest of the run carries on. */
+  errors: string[];
+}
+
+/** Parse `git worktree list --porcelain`; the main checkout comes first. */
+export function parseWorktreeList(stdout: string): WorktreeEntry[] {
+  const entries: WorktreeEntry[] = [];
+  for (const block of stdout.split(/\n\n+/)) {
+    const lines = block.split('\n').filter(Boolean);
+    const head = lines[0];
+    if (!head?.startsWith('worktree ')) continue;
+    const entry: WorktreeEntry = {
+      path: head.slice('worktree '.length),
+      branch: null,
+      prunable: null,
+      locked: false,
+    };
+    for (const line of lines.slice(1)) {
+      if (line.startsWith('branch ')) entry.branch = line.slice(7).replace(/^refs\/heads\//, '');
+      else if (line === 'prunable' || line.startsWith('prunable ')) {
+        entry.prunable = line.slice(9) || 'gitdir points to a missing location';
+      } else if (line === 'locked' || line.startsWith('locked ')) entry.locked = true;
+    }
+    entries.push(entry);
+  }
+  return entries;
+}
+
+function isInside(dir: string, p: string): boolean {
+  const rel = path.relative(dir, p);
+  return rel !== '' && !rel.startsWith('..') && !path.isAbsolute(rel);
+}
+
+/** `p` with symlinks resolved, as git reports worktree paths. */
+function real(p: string): string {
+  try {
+    return fs.realpathSync(p);
+  } catch {
+    return path.resolve(p);
+  }
+}
+
+function isNew(dir: string): boolean {
+  try {
+    const st = fs.statSync(dir);
+    // Some file systems don't record when a file was created
+    return Date.now() - (st.birthtimeMs || st.ctimeMs) < NEW_WORKTREE_MS;
+  } catch {
+    return false;
+  }
+}
+
+function reason(err: unknown): string {
+  return err instanceof Error ? err.message : String(err);
+}
+
+/**
+ * Which worktrees and branches nothing owns, from what git holds. Paths are
+ * compared as given, so resolve them the same way first.
+ */
+export function classifyWorkspace(
+  state: { worktrees: WorktreeEntry[]; branches: string[]; worktreeRoot: string },
+  managed: { branches: ReadonlySet<string>; paths: ReadonlySet<string>; branchPrefix: string },
+): { worktrees: WorktreeEntry[]; prunable: WorktreeEntry[]; branches: string[] } {
+  const isTaskBranch = (b: string) => b.startsWith(`${managed.branchPrefix}/`);
+  const isManaged = (w: WorktreeEntry) =>
+    managed.paths.has(w.path) || (w.branch !== null && managed.branches.has(w.branch));
+  const [, ...linked] = state.worktrees;
+  const prunable = linked.filter((w) => w.prunable !== null && !w.locked);
+  const worktrees = linked.filter(
+    (w) =>
+      w.prunable === null &&
+      !w.locked &&
+      isInside(state.worktreeRoot, w.path) &&
+      !isManaged(w) &&
+      // A rebase in progress detaches HEAD, so a task's own worktree can show up without a branch
+      (w.branch === null ? true : isTaskBranch(w.branch)),
+  );
+  // Checked out somewhere that stays, e.g. the main checkout or a worktree of the user's own
+  const kept = state.worktrees.filter((w) => !worktrees.includes(w) && !prunable.includes(w));
+  const inUse = new Set(kept.map((w) => w.branch));
+  const branches = state.branches.filter(
+    (b) => isTaskBranch(b) && !managed.branches.has(b) && !inUse.has(b),
+  );
+  return { worktrees, prunable, branches };
+}
+
+/**
+ * Directories under `worktreeRoot` that hold no registered worktree: the
+ * remains of a removal that was cut short, or worktrees whose metadata was
+ * pruned. Only those whose `.git` file points into this repository's
+ * metadata, at an entry that's gone, count; folders without one, or pointing
+ * into another repository (two repos sharing a worktree root), are left be.
+ */
+async function findUnregisteredDirs(
+  worktreeRoot: string,
+  registered: string[],
+  commonDir: string,
+): Promise<string[]> {
+  const found: string[] = [];
+  async function walk(dir: string): Promise<void> {
+    const entries = await fs.promises.readdir(dir, { withFileTypes: true }).catch(() => []);
+    for (const e of entries) {
+      if (!e.isDirectory()) continue;
+      const p = path.join(dir, e.name);
+      if (registered.includes(p) || isNew(p)) continue;
+      if (registered.some((r) => isInside(p, r))) {
+        await walk(p);
+      } else if (await belongsToRepo(p, commonDir)) {
+        found.push(p);
+      }
+    }
+  }
+  await walk(worktreeRoot);
+  return found;
+}
+
+async function belongsToRepo(dir: string, commonDir: string): Promise<boolean> {
+  let dotGit: string;
+  try {
+    dotGit = await fs.promises.readFile(path.join(dir, '.git'), 'utf8');
+  } catch {
+    // No .git file: nothing says this repo made it, so it may be anyone's
+    return false;
+  }
+  const gitdir = /^gitdir: (.+)$/m.exec(dotGit)?.[1]?.trim();
+  if (gitdir === undefined) return false;
+  const metadata = path.resolve(dir, gitdir);
+  return isInside(commonDir, metadata) && !fs.existsSync(metadata);
+}
+
+async function git(projectRoot: string, args: string[]): Promise<string> {
+  const { stdout } = await exec('git', args, { cwd: projectRoot, maxBuffer: MAX_BUFFER });
+  return stdout;
+}
+
+/**
+ * Whether a worktree has changes removing it would lose. One git can't
+ * read counts as dirty; a leftover folder has no metadata left to ask.
+ */
+async function isDirty(w: UnmanagedWorktree): Promise<boolean> {
+  if (!w.registered) return false;
+  try {
+    return (await git(w.path, ['status', '--porcelain', '--untracked-files=all'])).trim() !== '';
+  } catch {
+    return true;
+  }
+}
+
+/** Why a worktree from a removal mustn't go, or null when it may. */
+export function keepWorktreeReason(
+  worktree: UnmanagedWorktree,
+  confirmation: WorkspaceGcConfirmation,
+): string | null {
+  if (!confirmation.worktrees.includes(worktree.path)) return 'not confirmed';
+  if (worktree.dirty && !confirmation.force) {
+    return 'has uncommitted or untracked changes; force removes it';
+  }
+  return null;
+}
+
+/** Why a confirmed orphaned branch mustn't go, or null when it may. */
+export function keepReason(
+  branch: OrphanedBranch,
+  confirmation: WorkspaceGcConfirmation,
+): string | null {
+  const confirmed = confirmation.branches[branch.branch];
+  if (confirmed === undefined) return 'not confirmed';
+  if (!branch.tip || confirmed !== branch.tip) return 'has new commits since it was confirmed';
+  if (branch.unmerged > 0 && !confirmation.force) {
+    return `has ${branch.unmerged} unmerged commits; force deletes it`;
+  }
+  return null;
+}
+
+/**
+ * Find, and unless `dryRun` remove, what no task in `managed` owns. Removal
+ * runs under the repo lock; an item that fails is reported in `errors` and
+ * the rest still go. Only worktrees and branches in `confirmation` are
+ * deleted, branches at the tip it names, and dirty worktrees and branches
+ * with unmerged commits (see `unmerged`) only with `force`, so a dry run has
+ * to come first.
+ */
+export async function gcWorkspace(
+  projectRoot: string,
+  managed: { branches: string[]; worktreePaths: string[]; branchPrefix: string },
+  dryRun: boolean,
+  confirmation: WorkspaceGcConfirmation = { worktrees: [], branches: {}, force: false },
+): Promise<WorkspaceGcReport> {
+  if (remoteTargetFor(projectRoot)) {
+    throw new Error('Cleaning up the workspace of a remote project is not supported');
+  }
+  const repoRoot = real(projectRoot);
+  const worktreeRoot = real(getWorktreeRoot(projectRoot));
+  const commonDir = real(
+    path.resolve(projectRoot, (await git(projectRoot, ['rev-parse', '--git-common-dir'])).trim()),
+  );
+  const entries = parseWorktreeList(
+    await git(projectRoot, ['worktree', 'list', '--porcelain']),
+  ).map((w) => ({ ...w, path: real(w.path) }));
+  const branchRefs = await git(projectRoot, [
+    'for-each-ref',
+    '--format=%(refname)',
+    `refs/heads/${managed.branchPrefix}/`,
+  ]);
+  const found = classifyWorkspace(
+    {
+      worktrees: entries,
+      branches: branchRefs
+        .split('\n')
+        .filter(Boolean)
+        .map((r) => r.replace(/^refs\/heads\//, '')),
+      worktreeRoot,
+    },
+    {
+      branches: new Set(managed.branches),
+      paths: new Set([
+        ...managed.worktreePaths.map(real),
+        ...managed.branches.map((b) => real(getWorktreePath(projectRoot, b))),
+        ...entries.filter((w) => isNew(w.path)).map((w) => w.path),
+      ]),
+      branchPrefix: managed.branchPrefix,
+    },
+  );
+  // A worktree root that holds the repo itself would make every folder in it look left over
+  const leftovers =
+    fs.existsSync(worktreeRoot) && repoRoot !== worktreeRoot && !isInside(worktreeRoot, repoRoot)
+      ? await findUnregisteredDirs(worktreeRoot, entries.map((w) => w.path), commonDir)
+      : [];
+
+  const worktrees: UnmanagedWorktree[] = [
+    ...found.worktrees.map((w) => ({
+      path: w.path,
+      branch: w.branch,
+      registered: true,
+      dirty: false,
+      bytes: 0,
+    })),
+    ...leftovers.map((p) => ({ path: p, branch: null, registered: false, dirty: false, bytes: 0 })),
+  ];
+  for (const w of worktrees) {
+    w.dirty = await isDirty(w);
+    w.bytes = await measureDir(w.path);
+  }
+  const branches: OrphanedBranch[] = [];
+  for (const branch of found.branches) {
+    const risk = await getBranchDeletionRisk(projectRoot, branch).catch(() => null);
+    const tip = risk?.tip ?? (await getBranchTip(projectRoot, branch)) ?? '';
+    branches.push({ branch, tip, unmerged: risk?.unmerged ?? 0 });
+  }
+  const report: WorkspaceGcReport = {
+    project_root: projectRoot,
+    dry_run: dryRun,
+    worktrees,
+    prunable: found.prunable.map((w) => ({ path: w.path, reason: w.prunable ?? '' })),
+    branches,
+    kept_worktrees: [],
+    kept_branches: [],
+    reclaimed_bytes: worktrees.reduce((sum, w) => sum + w.bytes, 0),
+    errors: [],
+  };
+  if (dryRun) return report;
+
+  report.reclaimed_bytes = 0;
+  await withRepoLock(projectRoot, async () => {
+    for (const w of worktrees) {
+      // Checked again under the lock: changes made since the listing count too
+      const keep = keepWorktreeReason({ ...w, dirty: await isDirty(w) }, confirmation);
+      if (keep !== null) {
+        report.kept_worktrees.push({ path: w.path, reason: keep });
+        continue;
+      }
+      try {
+        if (w.registered) {
+          await git(projectRoot, ['worktree', 'remove', '--force', w.path]).catch(
+            (err: unknown) => log.info(`git worktree remove failed for ${w.path}, deleting`, err),
+          );
+        }
+        await fs.promises.rm(w.path, { recursive: true, force: true });
+        report.reclaimed_bytes += w.bytes;
+      } catch (err) {
+        report.errors.push(`${w.path}: ${reason(err)}`);
+      }
+    }
+    await git(projectRoot, ['worktree', 'prune']).catch((err: unknown) => {
+      report.errors.push(`git worktree prune: ${reason(err)}`);
+    });
+    for (const b of branches) {
+      const keep = keepReason(b, confirmation);
+      if (keep !== null) {
+        report.kept_branches.push({ branch: b.branch, reason: keep });
+        continue;
+      }
+      try {
+        // Only at the confirmed tip, should a commit land between the check and here
+        await git(projectRoot, ['update-ref', '-d', `refs/heads/${b.branch}`, b.tip]);
+        log.info(`Deleted orphaned branch ${b.branch} at ${b.tip}`);
+      } catch (err) {
+        report.errors.push(`${b.branch}: ${reason(err)}`);
+      }
+    }
+  });
+  forgetDiskUsage(projectRoot);
+  return report;
+}
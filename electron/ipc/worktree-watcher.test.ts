@@ -0,0 +1,41 @@
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { classifyChanges, isIgnoredWorktreePath } from './worktree-watcher.js';
+
+describe('isIgnoredWorktreePath', () => {
+  it('ignores git internals and dependency trees', () => {
+    expect(isIgnoredWorktreePath('.git/index')).toBe(true);
+    expect(isIgnoredWorktreePath('packages/app/node_modules/x/index.js')).toBe(true);
+    expect(isIgnoredWorktreePath('src/.gitignore')).toBe(false);
+    expect(isIgnoredWorktreePath('src/main.ts')).toBe(false);
+  });
+});
+
+describe('classifyChanges', () => {
+  let dir: string;
+
+  beforeEach(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'worktree-watcher-'));
+    fs.writeFileSync(path.join(dir, 'new.ts'), '');
+    fs.writeFileSync(path.join(dir, 'edited.ts'), '');
+  });
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('derives created/modified/deleted from event type and existence', () => {
+    const pending = new Map<string, 'rename' | 'change'>([
+      ['new.ts', 'rename'],
+      ['gone.ts', 'rename'],
+      ['edited.ts', 'change'],
+    ]);
+    expect(classifyChanges(dir, pending)).toEqual([
+      { path: 'edited.ts', kind: 'modified' },
+      { path: 'gone.ts', kind: 'deleted' },
+      { path: 'new.ts', kind: 'created' },
+    ]);
+  });
+});
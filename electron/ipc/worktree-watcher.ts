@@ -0,0 +1,126 @@
+import fs from 'fs';
+import path from 'path';
+import type { BrowserWindow } from 'electron';
+import { IPC } from './channels.js';
+import { invalidateWorktreeStatus, setWorktreeStatusWatched } from './git.js';
+
+export type FileChangeKind = 'created' | 'modified' | 'deleted';
+
+export interface WorktreeFileChange {
+  path: string;
+  kind: FileChangeKind;
+}
+
+interface WorktreeWatcher {
+  watcher: fs.FSWatcher;
+  /** Number of open views interested in this worktree. */
+  refs: number;
+  pending: Map<string, 'rename' | 'change'>;
+  timeout: ReturnType<typeof setTimeout> | null;
+}
+
+const DEBOUNCE_MS = 300;
+const MAX_PENDING = 1000;
+const IGNORED_SEGMENTS = new Set(['.git', 'node_modules']);
+
+const watchers = new Map<string, WorktreeWatcher>();
+
+/** Git internals and dependency trees churn constantly and never show in a diff. */
+export function isIgnoredWorktreePath(relPath: string): boolean {
+  return relPath.split(/[\\/]/).some((seg) => IGNORED_SEGMENTS.has(seg));
+}
+
+/**
+ * Turn raw fs.watch events into change kinds. A 'rename' event is either a
+ * create or a delete, so existence is checked when the batch is flushed.
+ */
+export function classifyChanges(
+  worktreePath: string,
+  pending: Map<string, 'rename' | 'change'>,
+): WorktreeFileChange[] {
+  const changes: WorktreeFileChange[] = [];
+  for (const [relPath, eventType] of pending) {
+    const exists = fs.existsSync(path.join(worktreePath, relPath));
+    const kind: FileChangeKind = !exists
+      ? 'deleted'
+      : eventType === 'rename'
+        ? 'created'
+        : 'modified';
+    changes.push({ path: relPath.split(path.sep).join('/'), kind });
+  }
+  return changes.sort((a, b) => a.path.localeCompare(b.path));
+}
+
+function flush(win: BrowserWindow, worktreePath: string): void {
+  const entry = watchers.get(worktreePath);
+  if (!entry) return;
+  entry.timeout = null;
+  const changes = classifyChanges(worktreePath, entry.pending);
+  entry.pending = new Map();
+  if (changes.length === 0) return;
+  invalidateWorktreeStatus(worktreePath);
+  if (win.isDestroyed()) return;
+  win.webContents.send(IPC.WorktreeFilesChanged, { worktreePath, changes });
+}
+
+/**
+ * Watches a worktree recursively and sends debounced
+ * IPC.WorktreeFilesChanged events. Watchers are shared and ref-counted per
+ * worktree path.
+ */
+export function watchWorktree(win: BrowserWindow, worktreePath: string): void {
+  const existing = watchers.get(worktreePath);
+  if (existing) {
+    existing.refs++;
+    return;
+  }
+
+  const watcher = fs.watch(worktreePath, { recursive: true }, (eventType, filename) => {
+    const entry = watchers.get(worktreePath);
+    if (!entry || !filename) return;
+    const relPath = filename.toString();
+    if (isIgnoredWorktreePath(relPath)) return;
+    // Forget the cached status right away rather than after the debounce
+    if (entry.pending.size === 0) invalidateWorktreeStatus(worktreePath);
+    // A create followed by edits is still a create
+    if (entry.pending.get(relPath) !== 'rename') entry.pending.set(relPath, eventType);
+    if (entry.pending.size >= MAX_PENDING) {
+      if (entry.timeout) clearTimeout(entry.timeout);
+      flush(win, worktreePath);
+      return;
+    }
+    if (entry.timeout) clearTimeout(entry.timeout);
+    entry.timeout = setTimeout(() => flush(win, worktreePath), DEBOUNCE_MS);
+  });
+
+  watcher.on('error', () => {
+    stopWatcher(worktreePath);
+  });
+
+  watchers.set(worktreePath, { watcher, refs: 1, pending: new Map(), timeout: null });
+  setWorktreeStatusWatched(worktreePath, true);
+}
+
+function stopWatcher(worktreePath: string): void {
+  const entry = watchers.get(worktreePath);
+  if (!entry) return;
+  if (entry.timeout) clearTimeout(entry.timeout);
+  entry.watcher.close();
+  watchers.delete(worktreePath);
+  setWorktreeStatusWatched(worktreePath, false);
+}
+
+/** Drops one reference; the watcher closes when nobody is left watching. */
+export function unwatchWorktree(worktreePath: string): void {
+  const entry = watchers.get(worktreePath);
+  if (!entry) return;
+  entry.refs--;
+  if (entry.refs <= 0) stopWatcher(worktreePath);
+}
+
+/** Stops all worktree watchers. */
+export function stopAllWorktreeWatchers(): void {
+  for (const worktreePath of [...watchers.keys()]) {
+    stopWatcher(worktreePath);
+  }
+}
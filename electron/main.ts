@@ -2,46 +2,29 @@ import { app, BrowserWindow, shell } from 'electron';
 import path from 'path';
 import fs from 'fs';
 import { fileURLToPath } from 'url';
-import { execFileSync } from 'child_process';
 import { registerAllHandlers } from './ipc/register.js';
 import { killAllAgents } from './ipc/pty.js';
+import { clearTrackedSessions, loadPreviousSessions } from './ipc/orphans.js';
 import { stopAllPlanWatchers } from './ipc/plans.js';
+import { stopGitStatusPoller } from './ipc/git-status-poller.js';
+import { stopAllWorktreeWatchers } from './ipc/worktree-watcher.js';
+import { stopAllTaskTests } from './ipc/checks.js';
+import { stopPrPoller } from './ipc/pr-poller.js';
+import { stopScheduler } from './ipc/scheduler.js';
+import { stopAutomationServer } from './remote/automation-server.js';
+import { DEEP_LINK_SCHEME, findDeepLinkArg, handleDeepLink } from './ipc/deep-link.js';
+import { initFileLogging } from './ipc/log.js';
+import { flushUsage } from './ipc/usage.js';
 import { IPC } from './ipc/channels.js';
+import { initShellEnv } from './ipc/shell-env.js';
 
 const __filename = fileURLToPath(import.meta.url);
 const __dirname = path.dirname(__filename);
 
-// When launched from a .desktop file, PATH is minimal (/usr/bin:/bin).
-// Resolve the user's full login-interactive shell PATH so spawned PTYs
-// can find CLI tools like claude, codex, gemini, etc.
-//
-// Uses -ilc (interactive + login) to source both .zprofile/.profile AND
-// .zshrc/.bashrc, where version managers (nvm, volta, fnm) add to PATH.
-// Sentinel markers isolate PATH from noisy shell init output.
-//
-// Trade-off: -i (interactive) triggers .zshrc side effects (compinit, conda,
-// welcome messages). Login-only (-lc) would be quieter but would miss tools
-// that are only added to PATH in .bashrc/.zshrc (e.g. nvm). We accept the
-// side effects since the sentinel-based parsing discards all other output.
-function fixPath(): void {
-  if (process.platform === 'win32') return;
-  try {
-    const loginShell = process.env.SHELL || '/bin/sh';
-    const sentinel = '__PCODE_PATH__';
-    const result = execFileSync(loginShell, ['-ilc', `printf "${sentinel}%s${sentinel}" "$PATH"`], {
-      encoding: 'utf8',
-      timeout: 5000,
-    });
-    const match = result.match(new RegExp(`${sentinel}(.+?)${sentinel}`));
-    if (match?.[1]) {
-      process.env.PATH = match[1];
-    }
-  } catch (err) {
-    console.warn('[fixPath] Failed to resolve login shell PATH:', err);
-  }
-}
-
-fixPath();
+// Launched from Finder, the Dock or a .desktop file, PATH is minimal
+// (/usr/bin:/bin). Load the login shell's environment so spawned PTYs can
+// find CLI tools like claude, codex, gemini, etc.
+initShellEnv();
 
 // Verify that preload.cjs ALLOWED_CHANNELS stays in sync with the IPC enum.
 // Logs a warning in dev if they drift — catches mismatches before they hit users.
@@ -74,6 +57,7 @@ function getIconPath(): string | undefined {
 }
 
 function createWindow() {
+  initFileLogging();
   mainWindow = new BrowserWindow({
     width: 1400,
     height: 900,
@@ -138,11 +122,52 @@ function createWindow() {
   });
 }
 
-app.whenReady().then(createWindow);
+// parallel-code:// links. macOS delivers them via open-url; Linux launches the
+// app with the link as an argument, or passes it to the running instance.
+if (process.defaultApp) {
+  // Dev: register the electron binary plus our entry script
+  app.setAsDefaultProtocolClient(DEEP_LINK_SCHEME, process.execPath, [
+    path.resolve(process.argv[1] ?? '.'),
+  ]);
+} else {
+  app.setAsDefaultProtocolClient(DEEP_LINK_SCHEME);
+}
+
+if (!app.requestSingleInstanceLock()) {
+  app.quit();
+} else {
+  app.on('second-instance', (_event, argv) => {
+    if (mainWindow) {
+      if (mainWindow.isMinimized()) mainWindow.restore();
+      mainWindow.focus();
+    }
+    const link = findDeepLinkArg(argv);
+    if (link) handleDeepLink(link);
+  });
+  app.on('open-url', (event, url) => {
+    event.preventDefault();
+    handleDeepLink(url);
+  });
+
+  app.whenReady().then(() => {
+    loadPreviousSessions();
+    createWindow();
+    const link = findDeepLinkArg(process.argv);
+    if (link) handleDeepLink(link);
+  });
+}
 
 app.on('before-quit', () => {
   killAllAgents();
+  clearTrackedSessions();
   stopAllPlanWatchers();
+  stopGitStatusPoller();
+  stopAllWorktreeWatchers();
+  stopAllTaskTests();
+  stopPrPoller();
+  stopScheduler();
+  stopAutomationServer();
+  flushUsage();
 });
 
 app.on('window-all-closed', () => {
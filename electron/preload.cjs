@@ -1,4 +1,4 @@
-const { contextBridge, ipcRenderer } = require('electron');
+const { contextBridge, ipcRenderer, webUtils } = require('electron');
 
 // Allowlist of valid IPC channels.
 // IMPORTANT: This list MUST stay in sync with the IPC enum in electron/ipc/channels.ts.
@@ -7,22 +7,122 @@ const ALLOWED_CHANNELS = new Set([
   // Agent/PTY
   'spawn_agent',
   'write_to_agent',
+  'send_to_agent',
+  'send_to_task_agents',
+  'send_to_project_agents',
   'resize_agent',
   'pause_agent',
   'resume_agent',
   'kill_agent',
   'count_running_agents',
   'kill_all_agents',
+  'kill_project_agents',
+  'list_orphaned_sessions',
+  'resolve_orphaned_sessions',
+  'agent_restarted',
   'list_agents',
   'list_claude_commands',
+  'get_task_usage',
+  'get_task_times',
+  'set_task_timer',
+  'search_session_buffer',
+  'get_session_info',
+  'list_sessions',
+  'session_info_changed',
+  'agent_awaiting_approval',
+  'respond_to_agent',
+  'agent_idle',
+  'agent_hung',
+  'agent_event',
+  'get_agent_events',
+  'get_agent_resume_args',
+  'get_handoff_context',
+  // Scheduled runs
+  'enqueue_agent_run',
+  'list_scheduled_runs',
+  'cancel_scheduled_run',
+  'scheduled_run_changed',
+  // Merge queue
+  'enqueue_merge',
+  'list_merge_queue',
+  'remove_from_merge_queue',
+  'resume_merge_queue',
+  'merge_queue_changed',
+  // Secrets
+  'list_secrets',
+  'set_secret',
+  'has_secret',
+  'set_secret_injected',
+  'delete_secret',
+  // App data
+  'export_app_data',
+  'import_app_data',
   // Task
   'create_task',
+  'create_task_from_branch',
   'delete_task',
+  'check_branch_deletion',
+  'create_race',
+  'archive_task',
+  'restore_task',
+  'rename_task',
+  'record_task_event',
+  'get_task_events',
+  'query_history',
+  'search_transcripts',
+  'get_metrics',
+  'export_task',
+  'import_task',
+  'list_recordings',
+  'export_recording',
+  'list_trash',
+  'restore_deleted_task',
+  'get_disk_usage',
+  'gc_workspace',
+  'get_resource_usage',
+  'share_node_modules',
+  'get_pnpm_store_dir',
   // Git
   'get_changed_files',
   'get_changed_files_from_branch',
   'get_file_diff',
   'get_file_diff_from_branch',
+  'get_structured_file_diff',
+  'start_diff',
+  'read_diff_chunks',
+  'load_more_diff',
+  'cancel_diff',
+  'diff_chunk',
+  'revert_files',
+  'revert_hunks',
+  'diff_task_branches',
+  'get_file_diff_between_branches',
+  'cherry_pick',
+  'stash_task',
+  'stash_pop_task',
+  'list_stashes',
+  'create_snapshot',
+  'list_snapshots',
+  'restore_snapshot',
+  'fetch_project',
+  'update_base_branch',
+  'widen_sparse_checkout',
+  'git_progress',
+  'cancel_operation',
+  'set_watched_tasks',
+  'refresh_task_git_status',
+  'task_git_status_changed',
+  'watch_worktree',
+  'unwatch_worktree',
+  'worktree_files_changed',
+  // Logs
+  'get_recent_logs',
+  // Notifications
+  'list_notifications',
+  'mark_notifications_read',
+  'clear_notifications',
+  'notify',
+  'notification_added',
   'get_gitignored_dirs',
   'get_worktree_status',
   'commit_all',
@@ -30,10 +130,28 @@ const ALLOWED_CHANNELS = new Set([
   'check_merge_status',
   'merge_task',
   'get_branch_log',
+  'get_branch_tracking',
+  'get_task_log',
+  'get_blame',
   'push_task',
   'rebase_task',
+  'get_conflicts',
+  'resolve_conflict',
+  'continue_merge',
+  'abort_merge',
+  'find_merged_branches',
+  'get_branch_tip',
   'get_main_branch',
   'get_current_branch',
+  // Projects
+  'list_recent_projects',
+  'open_project',
+  'get_project_info',
+  'read_project_config',
+  // Settings
+  'get_settings',
+  'update_settings',
+  'settings_changed',
   // Persistence
   'save_app_state',
   'load_app_state',
@@ -62,8 +180,25 @@ const ALLOWED_CHANNELS = new Set([
   // Shell
   '__shell_reveal',
   '__shell_open_file',
-  'save_clipboard_image',
-  '__shell_open_in_editor',
+  'paste_image_to_session',
+  'read_clipboard_image',
+  'attach_files_to_session',
+  'open_in_editor',
+  // Checks
+  'run_task_tests',
+  'cancel_task_tests',
+  'task_test_output',
+  'run_task_checks',
+  'get_check_results',
+  'check_status_changed',
+  // Ports
+  'get_task_ports',
+  // Remotes
+  'fetch_issue',
+  'create_pull_request',
+  'set_watched_pr_tasks',
+  'refresh_task_pr_status',
+  'task_pr_status_changed',
   // Arena
   'save_arena_data',
   'load_arena_data',
@@ -74,6 +209,14 @@ const ALLOWED_CHANNELS = new Set([
   'start_remote_server',
   'stop_remote_server',
   'get_remote_status',
+  // Automation API
+  'automation_request',
+  'automation_response',
+  'get_automation_api_info',
+  'regenerate_automation_token',
+  // Deep links
+  'deep_link_received',
+  'take_pending_deep_links',
   // Plan
   'plan_content',
 ]);
@@ -99,4 +242,6 @@ contextBridge.exposeInMainWorld('electron', {
       ipcRenderer.removeAllListeners(channel);
     },
   },
+  // Dropped files carry no path in the renderer; only the preload can ask for it
+  getPathForFile: (file) => webUtils.getPathForFile(file),
 });
@@ -0,0 +1,46 @@
+import { describe, expect, it } from 'vitest';
+import { commandRequest, listAutomationCommands } from './automation-commands.js';
+
+describe('listAutomationCommands', () => {
+  it('gives every command its route and whether it needs a task', () => {
+    const commands = listAutomationCommands();
+    const notes = commands.find((c) => c.id === 'set_task_notes');
+    expect(notes?.http).toEqual({ method: 'POST', path: '/v1/tasks/:id/notes' });
+    expect(notes?.requires_task).toBe(true);
+    expect(commands.find((c) => c.id === 'list_projects')?.requires_task).toBe(false);
+  });
+});
+
+describe('commandRequest', () => {
+  it('turns an invocation into the request for its method', () => {
+    expect(
+      commandRequest({ command: 'send_prompt', taskId: 't1', args: { text: 'go on' } }),
+    ).toEqual({ method: 'send_prompt', taskId: 't1', body: { text: 'go on' } });
+    expect(commandRequest({ command: 'list_tasks' })).toEqual({
+      method: 'list_tasks',
+      taskId: undefined,
+      body: {},
+    });
+  });
+
+  it('rejects unknown commands with 404', () => {
+    expect(commandRequest({ command: 'drop_tables' })).toEqual({
+      error: { status: 404, message: 'unknown command: drop_tables' },
+    });
+  });
+
+  it('checks the task and arguments against the schema', () => {
+    const message = (body: Record<string, unknown>) => {
+      const result = commandRequest(body);
+      return 'error' in result ? result.error.message : null;
+    };
+    expect(message({ command: 'send_prompt', args: { text: 'x' } })).toBe('taskId is required');
+    expect(message({ command: 'send_prompt', taskId: 't1' })).toBe('text is required');
+    expect(message({ command: 'delete_tasks', args: { taskIds: 't1' } })).toBe(
+      'taskIds must be of type string[]',
+    );
+    expect(message({ command: 'list_tasks', args: { verbose: true } })).toBe(
+      'unknown parameter: verbose',
+    );
+  });
+});
@@ -0,0 +1,346 @@
+import {
+  automationRoute,
+  type AutomationMethod,
+  type AutomationRequest,
+} from './automation-protocol.js';
+
+/**
+ * What each automation method does and takes, so a command palette or a
+ * script can discover them. The handlers in the renderer still do the full
+ * validation; the schema here is what `invoke_command` checks up front.
+ */
+
+export type CommandParamType =
+  | 'string'
+  | 'string[]'
+  | 'integer'
+  | 'boolean'
+  | 'object'
+  | 'object[]'
+  /** Epoch milliseconds or an ISO date string. */
+  | 'time';
+
+export interface CommandParam {
+  type: CommandParamType;
+  required?: boolean;
+  description?: string;
+}
+
+interface CommandSpec {
+  title: string;
+  params?: Record<string, CommandParam>;
+}
+
+export interface AutomationCommand {
+  id: AutomationMethod;
+  title: string;
+  /** The command acts on one task, given as `taskId`. */
+  requires_task: boolean;
+  params: Record<string, CommandParam>;
+  http: { method: string; path: string };
+}
+
+const str = (description?: string): CommandParam => ({ type: 'string', description });
+const req = (type: CommandParamType, description?: string): CommandParam => ({
+  type,
+  required: true,
+  description,
+});
+const limit: CommandParam = { type: 'integer', description: 'At most this many results' };
+
+const COMMANDS: Record<AutomationMethod, CommandSpec> = {
+  list_projects: { title: 'List projects' },
+  get_project_info: {
+    title: "Show a project's repository and default branch",
+    params: { projectId: req('string') },
+  },
+  list_project_tasks: { title: "List a project's tasks", params: { projectId: req('string') } },
+  send_project_prompt: {
+    title: "Send a prompt to every agent of a project's tasks",
+    params: { projectId: req('string'), text: req('string') },
+  },
+  stop_project_agents: {
+    title: "Stop every agent and terminal of a project's tasks",
+    params: { projectId: req('string') },
+  },
+  list_tasks: { title: 'List tasks' },
+  query_tasks: {
+    title: 'Find tasks',
+    params: {
+      status: str('Activity (busy, waiting, ready) or lifecycle stage'),
+      label: str(),
+      agent: str('Agent id'),
+      text: str('Text in the name or notes'),
+      sort: str('updated_at, created_at or name'),
+      order: str('asc or desc'),
+      offset: { type: 'integer' },
+      limit,
+    },
+  },
+  get_task_status: { title: 'Show a task and its agents' },
+  update_task: {
+    title: 'Update a task',
+    params: {
+      name: str(),
+      lifecycle: str(),
+      description: str(),
+      labels: { type: 'string[]' },
+      env: { type: 'object', description: 'Environment variables for new sessions' },
+      secrets: { type: 'string[]', description: 'Stored secrets for new sessions, by name' },
+      notes: str('Markdown'),
+    },
+  },
+  create_task: {
+    title: 'Create a task',
+    params: {
+      projectId: req('string'),
+      name: req('string'),
+      agentId: str(),
+      prompt: str(),
+      profileId: str('Launch profile'),
+      dependsOn: { type: 'string[]', description: 'Tasks that must merge first' },
+      scope: str('Sub-directory the task works in'),
+    },
+  },
+  create_tasks: {
+    title: 'Create several tasks',
+    params: { tasks: req('object[]', 'Each as for create_task') },
+  },
+  spawn_agent: {
+    title: "Start the task's agent",
+    params: { profileId: str(), resume: { type: 'boolean' } },
+  },
+  send_prompt: { title: "Send a prompt to the task's agent", params: { text: req('string') } },
+  delete_tasks: {
+    title: 'Delete tasks',
+    params: {
+      taskIds: req('string[]'),
+      deleteBranches: { type: 'boolean' },
+      force: { type: 'boolean', description: 'Delete branches with unmerged commits too' },
+    },
+  },
+  archive_tasks: { title: 'Archive tasks', params: { taskIds: req('string[]') } },
+  rebase_tasks: {
+    title: 'Rebase tasks onto their main branch',
+    params: { taskIds: req('string[]'), keepConflicts: { type: 'boolean' } },
+  },
+  get_task_graph: { title: 'Show task dependencies' },
+  set_task_dependencies: {
+    title: 'Set the tasks that must merge first',
+    params: { dependsOn: { type: 'string[]' } },
+  },
+  widen_sparse_checkout: {
+    title: 'Check out more directories',
+    params: { paths: req('string[]') },
+  },
+  snapshot_task: { title: "Snapshot the task's worktree", params: { label: str() } },
+  list_snapshots: { title: "List the task's snapshots" },
+  restore_snapshot: { title: 'Restore a snapshot', params: { snapshotId: req('string') } },
+  get_conflicts: { title: 'List merge conflicts' },
+  resolve_conflict: {
+    title: 'Resolve a conflicted file',
+    params: { path: req('string'), content: req('string') },
+  },
+  continue_merge: { title: 'Continue the merge or rebase' },
+  abort_merge: { title: 'Abort the merge or rebase' },
+  get_task_summary: { title: 'Summarize task progress' },
+  get_task_notes: { title: "Show the task's notes" },
+  set_task_notes: {
+    title: "Set the task's notes",
+    params: { notes: req('string', 'Markdown'), append: { type: 'boolean' } },
+  },
+  get_task_time: { title: 'Show time spent on the task and its cost' },
+  start_task_timer: { title: "Start the task's timer" },
+  pause_task_timer: { title: "Pause the task's timer" },
+  get_project_time: {
+    title: "Show time spent on a project's tasks",
+    params: { projectId: req('string') },
+  },
+  gc_workspace: {
+    title: 'Clean up worktrees and branches no task owns',
+    params: {
+      projectId: req('string'),
+      dryRun: { type: 'boolean', description: 'Only list them; true unless set to false' },
+      worktrees: { type: 'string[]', description: 'Worktree paths the dry run listed to remove' },
+      branches: {
+        type: 'object',
+        description: 'Branches to delete, each to the tip the dry run listed it at',
+      },
+      force: {
+        type: 'boolean',
+        description: 'Remove confirmed dirty worktrees and branches with unmerged commits',
+      },
+    },
+  },
+  get_task_log: {
+    title: 'List commits on the task branch',
+    params: { limit, offset: { type: 'integer' } },
+  },
+  get_blame: { title: 'Blame a file', params: { path: req('string') } },
+  start_diff: { title: "Stream the task's diff in chunks" },
+  read_diff_chunks: {
+    title: 'Read chunks of a streamed diff',
+    params: {
+      handle: req('string'),
+      after: { type: 'integer', description: 'Only chunks after this seq' },
+    },
+  },
+  load_more_diff: {
+    title: 'Load more of a file a streamed diff cut off',
+    params: {
+      handle: req('string'),
+      path: req('string'),
+      from: req('integer', "The line the file's last chunk gave as more"),
+    },
+  },
+  cancel_diff: { title: 'Cancel a streamed diff', params: { handle: req('string') } },
+  list_review_comments: { title: 'List review comments' },
+  add_review_comment: {
+    title: 'Comment on lines of the diff',
+    params: {
+      path: req('string'),
+      startLine: req('integer'),
+      endLine: { type: 'integer' },
+      body: req('string'),
+    },
+  },
+  resolve_review_comment: {
+    title: 'Resolve a review comment',
+    params: { commentId: req('string'), resolved: { type: 'boolean' } },
+  },
+  send_review_comments: { title: 'Send unresolved comments to the agent' },
+  queue_run: {
+    title: 'Queue a headless agent run',
+    params: { prompt: req('string'), agentId: str(), startAt: { type: 'time' } },
+  },
+  list_runs: { title: 'List queued and finished runs' },
+  cancel_run: { title: 'Cancel a run', params: { runId: req('string') } },
+  enqueue_merges: {
+    title: 'Add tasks to the merge queue',
+    params: { taskIds: req('string[]'), squash: { type: 'boolean' } },
+  },
+  get_merge_queue: { title: 'Show the merge queue' },
+  remove_from_merge_queue: {
+    title: 'Remove a task from the merge queue',
+    params: { entryId: req('string') },
+  },
+  resume_merge_queue: {
+    title: 'Resume a paused merge queue',
+    params: { projectId: req('string') },
+  },
+  list_secrets: { title: 'List secrets' },
+  set_secret: {
+    title: 'Store a secret',
+    params: { name: req('string'), value: req('string'), inject: { type: 'boolean' } },
+  },
+  delete_secret: { title: 'Delete a secret', params: { name: req('string') } },
+  list_trash: { title: 'List deleted tasks' },
+  restore_deleted_task: { title: 'Restore a deleted task', params: { entryId: req('string') } },
+  undo_last_operation: { title: 'Restore the last deleted task' },
+  list_sessions: {
+    title: 'List agent and terminal sessions',
+    params: { taskId: str(), projectId: str() },
+  },
+  query_history: {
+    title: 'Query task history',
+    params: {
+      taskId: str(),
+      projectId: str(),
+      types: { type: 'string[]' },
+      since: { type: 'time' },
+      until: { type: 'time' },
+      limit,
+    },
+  },
+  search_transcripts: {
+    title: 'Search agent transcripts',
+    params: { query: req('string'), taskId: str(), limit },
+  },
+  get_metrics: {
+    title: 'Show task, agent time and token totals',
+    params: { since: { type: 'time' }, until: { type: 'time' }, projectId: str() },
+  },
+  get_disk_usage: { title: 'Show disk usage', params: { refresh: { type: 'boolean' } } },
+  get_resource_usage: { title: 'Show CPU and memory use' },
+};
+
+/** Every command with its schema and HTTP route. */
+export function listAutomationCommands(): AutomationCommand[] {
+  return (Object.keys(COMMANDS) as AutomationMethod[]).map((id) => {
+    const route = automationRoute(id);
+    return {
+      id,
+      title: COMMANDS[id].title,
+      requires_task: route.path.includes(':id'),
+      params: COMMANDS[id].params ?? {},
+      http: route,
+    };
+  });
+}
+
+export function findAutomationCommand(id: string): AutomationCommand | null {
+  return listAutomationCommands().find((c) => c.id === id) ?? null;
+}
+
+function hasType(value: unknown, type: CommandParamType): boolean {
+  switch (type) {
+    case 'string':
+      return typeof value === 'string';
+    case 'string[]':
+      return Array.isArray(value) && value.every((v) => typeof v === 'string');
+    case 'integer':
+      return Number.isInteger(value);
+    case 'boolean':
+      return typeof value === 'boolean';
+    case 'object':
+      return typeof value === 'object' && value !== null && !Array.isArray(value);
+    case 'object[]':
+      return Array.isArray(value) && value.every((v) => hasType(v, 'object'));
+    case 'time':
+      return typeof value === 'string' || typeof value === 'number';
+  }
+}
+
+/** Why `args` don't fit the command's parameters, or null when they do. */
+export function checkCommandArgs(
+  command: AutomationCommand,
+  args: Record<string, unknown>,
+): string | null {
+  for (const [name, param] of Object.entries(command.params)) {
+    const value = args[name];
+    if (value === undefined || value === null) {
+      if (param.required) return `${name} is required`;
+    } else if (!hasType(value, param.type)) {
+      return `${name} must be of type ${param.type}`;
+    }
+  }
+  const unknown = Object.keys(args).find((k) => !(k in command.params));
+  return unknown === undefined ? null : `unknown parameter: ${unknown}`;
+}
+
+/**
+ * The request an `invoke_command` body `{ command, taskId?, args? }` stands
+ * for, or the error to answer with when it names no command or doesn't fit
+ * the command's parameters.
+ */
+export function commandRequest(
+  body: Record<string, unknown>,
+): Omit<AutomationRequest, 'id'> | { error: { status: number; message: string } } {
+  const fail = (status: number, message: string) => ({ error: { status, message } });
+  if (typeof body.command !== 'string') return fail(400, 'command must be a string');
+  const command = findAutomationCommand(body.command);
+  if (!command) return fail(404, `unknown command: ${body.command}`);
+  const taskId = body.taskId;
+  if (command.requires_task && (typeof taskId !== 'string' || !taskId)) {
+    return fail(400, 'taskId is required');
+  }
+  const args = body.args ?? {};
+  if (!hasType(args, 'object')) return fail(400, 'args must be an object');
+  const problem = checkCommandArgs(command, args as Record<string, unknown>);
+  if (problem) return fail(400, problem);
+  return {
+    method: command.id,
+    taskId: command.requires_task ? (taskId as string) : undefined,
+    body: args as Record<string, unknown>,
+  };
+}
@@ -0,0 +1,122 @@
+import { describe, expect, it } from 'vitest';
+import { matchAutomationRoute } from './automation-protocol.js';
+
+describe('matchAutomationRoute', () => {
+  it('maps requests to methods', () => {
+    expect(matchAutomationRoute('GET', '/v1/tasks')).toEqual({
+      method: 'list_tasks',
+      taskId: undefined,
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks')?.method).toBe('create_task');
+    expect(matchAutomationRoute('POST', '/v1/tasks/batch')?.method).toBe('create_tasks');
+    expect(matchAutomationRoute('POST', '/v1/tasks/query')).toEqual({
+      method: 'query_tasks',
+      taskId: undefined,
+    });
+    expect(matchAutomationRoute('GET', '/v1/tasks/abc%20def')).toEqual({
+      method: 'get_task_status',
+      taskId: 'abc def',
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1')).toEqual({
+      method: 'update_task',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/prompt')).toEqual({
+      method: 'send_prompt',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/agent')?.method).toBe('spawn_agent');
+    expect(matchAutomationRoute('POST', '/v1/tasks/delete')).toEqual({
+      method: 'delete_tasks',
+      taskId: undefined,
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/rebase')?.method).toBe('rebase_tasks');
+    expect(matchAutomationRoute('GET', '/v1/tasks/graph')?.method).toBe('get_task_graph');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/runs')?.method).toBe('queue_run');
+    expect(matchAutomationRoute('GET', '/v1/runs')?.method).toBe('list_runs');
+    expect(matchAutomationRoute('POST', '/v1/trash/restore')?.method).toBe('restore_deleted_task');
+    expect(matchAutomationRoute('POST', '/v1/undo')?.method).toBe('undo_last_operation');
+    expect(matchAutomationRoute('POST', '/v1/search')?.method).toBe('search_transcripts');
+    expect(matchAutomationRoute('POST', '/v1/metrics')?.method).toBe('get_metrics');
+    expect(matchAutomationRoute('POST', '/v1/sessions')?.method).toBe('list_sessions');
+    expect(matchAutomationRoute('POST', '/v1/disk-usage')?.method).toBe('get_disk_usage');
+    expect(matchAutomationRoute('GET', '/v1/resources')?.method).toBe('get_resource_usage');
+    expect(matchAutomationRoute('GET', '/v1/merge-queue')?.method).toBe('get_merge_queue');
+    expect(matchAutomationRoute('POST', '/v1/merge-queue')?.method).toBe('enqueue_merges');
+    expect(matchAutomationRoute('POST', '/v1/projects/info')?.method).toBe('get_project_info');
+    expect(matchAutomationRoute('POST', '/v1/projects/tasks')?.method).toBe('list_project_tasks');
+    expect(matchAutomationRoute('POST', '/v1/projects/prompt')?.method).toBe(
+      'send_project_prompt',
+    );
+    expect(matchAutomationRoute('POST', '/v1/projects/stop')?.method).toBe('stop_project_agents');
+    expect(matchAutomationRoute('POST', '/v1/secrets')?.method).toBe('set_secret');
+    expect(matchAutomationRoute('POST', '/v1/secrets/delete')?.method).toBe('delete_secret');
+    expect(matchAutomationRoute('POST', '/v1/merge-queue/resume')?.method).toBe(
+      'resume_merge_queue',
+    );
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/dependencies')).toEqual({
+      method: 'set_task_dependencies',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/sparse-checkout')).toEqual({
+      method: 'widen_sparse_checkout',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/snapshots')?.method).toBe('snapshot_task');
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/snapshots')?.method).toBe('list_snapshots');
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/conflicts')?.method).toBe('get_conflicts');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/conflicts/continue')?.method).toBe(
+      'continue_merge',
+    );
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/snapshots/restore')).toEqual({
+      method: 'restore_snapshot',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/summary')).toEqual({
+      method: 'get_task_summary',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/notes')?.method).toBe('get_task_notes');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/notes')).toEqual({
+      method: 'set_task_notes',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/time')?.method).toBe('get_task_time');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/time/pause')).toEqual({
+      method: 'pause_task_timer',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/projects/time')?.method).toBe('get_project_time');
+    expect(matchAutomationRoute('POST', '/v1/projects/gc')?.method).toBe('gc_workspace');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/log')?.method).toBe('get_task_log');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/blame')?.method).toBe('get_blame');
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/diff')).toEqual({
+      method: 'start_diff',
+      taskId: 't1',
+    });
+    expect(matchAutomationRoute('POST', '/v1/diffs/read')?.method).toBe('read_diff_chunks');
+    expect(matchAutomationRoute('POST', '/v1/diffs/more')?.method).toBe('load_more_diff');
+    expect(matchAutomationRoute('POST', '/v1/diffs/cancel')?.method).toBe('cancel_diff');
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/comments')?.method).toBe(
+      'list_review_comments',
+    );
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/comments')?.method).toBe(
+      'add_review_comment',
+    );
+    expect(matchAutomationRoute('POST', '/v1/tasks/t1/comments/send')).toEqual({
+      method: 'send_review_comments',
+      taskId: 't1',
+    });
+  });
+
+  it('routes the command registry', () => {
+    expect(matchAutomationRoute('GET', '/v1/commands')?.method).toBe('list_commands');
+    expect(matchAutomationRoute('POST', '/v1/commands/invoke')?.method).toBe('invoke_command');
+  });
+
+  it('rejects unknown paths and methods', () => {
+    expect(matchAutomationRoute('DELETE', '/v1/tasks/t1')).toBeNull();
+    expect(matchAutomationRoute('GET', '/v1/tasks/t1/prompt')).toBeNull();
+    expect(matchAutomationRoute('GET', '/api/agents')).toBeNull();
+  });
+});
@@ -0,0 +1,245 @@
+/**
+ * Local automation API. Requests are routed here, then handed to the renderer,
+ * which owns task state, as an `AutomationRequest`.
+ */
+
+export type AutomationMethod =
+  | 'list_projects'
+  | 'get_project_info'
+  | 'list_project_tasks'
+  | 'send_project_prompt'
+  | 'stop_project_agents'
+  | 'list_tasks'
+  | 'query_tasks'
+  | 'get_task_status'
+  | 'update_task'
+  | 'create_task'
+  | 'create_tasks'
+  | 'spawn_agent'
+  | 'send_prompt'
+  | 'delete_tasks'
+  | 'archive_tasks'
+  | 'rebase_tasks'
+  | 'get_task_graph'
+  | 'set_task_dependencies'
+  | 'widen_sparse_checkout'
+  | 'snapshot_task'
+  | 'list_snapshots'
+  | 'restore_snapshot'
+  | 'get_conflicts'
+  | 'resolve_conflict'
+  | 'continue_merge'
+  | 'abort_merge'
+  | 'get_task_summary'
+  | 'get_task_notes'
+  | 'set_task_notes'
+  | 'get_task_time'
+  | 'start_task_timer'
+  | 'pause_task_timer'
+  | 'get_project_time'
+  | 'gc_workspace'
+  | 'get_task_log'
+  | 'get_blame'
+  | 'start_diff'
+  | 'read_diff_chunks'
+  | 'load_more_diff'
+  | 'cancel_diff'
+  | 'list_review_comments'
+  | 'add_review_comment'
+  | 'resolve_review_comment'
+  | 'send_review_comments'
+  | 'queue_run'
+  | 'list_runs'
+  | 'cancel_run'
+  | 'enqueue_merges'
+  | 'get_merge_queue'
+  | 'remove_from_merge_queue'
+  | 'resume_merge_queue'
+  | 'list_secrets'
+  | 'set_secret'
+  | 'delete_secret'
+  | 'list_trash'
+  | 'restore_deleted_task'
+  | 'undo_last_operation'
+  | 'list_sessions'
+  | 'query_history'
+  | 'search_transcripts'
+  | 'get_metrics'
+  | 'get_disk_usage'
+  | 'get_resource_usage';
+
+/** Methods the main process answers itself, without asking the renderer. */
+export type AutomationMetaMethod = 'list_commands' | 'invoke_command';
+
+export interface AutomationRequest {
+  id: string;
+  method: AutomationMethod;
+  taskId?: string;
+  body: Record<string, unknown>;
+}
+
+export interface AutomationResponse {
+  id: string;
+  result?: unknown;
+  /** Set when the request failed; `status` is the HTTP status to answer with. */
+  error?: { status: number; message: string };
+}
+
+export interface AutomationTaskStatus {
+  id: string;
+  name: string;
+  project_id: string;
+  branch_name: string;
+  worktree_path: string;
+  /** Monorepo sub-directory the task works in, relative to the worktree. */
+  scope: string | null;
+  lifecycle: string | null;
+  /** The sidebar dot: `busy` while an agent works, `ready` once it left committed changes. */
+  activity: 'busy' | 'waiting' | 'ready';
+  /** Tasks that must merge before this one. */
+  depends_on: string[];
+  /** Some of `depends_on` hasn't merged yet. */
+  blocked: boolean;
+  agents: Array<{
+    id: string;
+    status: 'running' | 'exited';
+    exit_code: number | null;
+    /** The agent's last output looks like a question to the user. */
+    asking: boolean;
+    /** The latest file edit, command, finished turn or error read from its output. */
+    last_event: { kind: string; detail: string | null; at: number } | null;
+  }>;
+}
+
+const ROUTES: Array<{
+  method: string;
+  pattern: RegExp;
+  name: AutomationMethod | AutomationMetaMethod;
+}> = [
+  { method: 'GET', pattern: /^\/v1\/projects$/, name: 'list_projects' },
+  { method: 'POST', pattern: /^\/v1\/projects\/info$/, name: 'get_project_info' },
+  { method: 'POST', pattern: /^\/v1\/projects\/tasks$/, name: 'list_project_tasks' },
+  { method: 'POST', pattern: /^\/v1\/projects\/prompt$/, name: 'send_project_prompt' },
+  { method: 'POST', pattern: /^\/v1\/projects\/stop$/, name: 'stop_project_agents' },
+  { method: 'POST', pattern: /^\/v1\/projects\/time$/, name: 'get_project_time' },
+  { method: 'POST', pattern: /^\/v1\/projects\/gc$/, name: 'gc_workspace' },
+  { method: 'GET', pattern: /^\/v1\/tasks$/, name: 'list_tasks' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/query$/, name: 'query_tasks' },
+  { method: 'POST', pattern: /^\/v1\/tasks$/, name: 'create_task' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/batch$/, name: 'create_tasks' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/delete$/, name: 'delete_tasks' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/archive$/, name: 'archive_tasks' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/rebase$/, name: 'rebase_tasks' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/graph$/, name: 'get_task_graph' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)$/, name: 'get_task_status' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)$/, name: 'update_task' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/agent$/, name: 'spawn_agent' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/prompt$/, name: 'send_prompt' },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/dependencies$/,
+    name: 'set_task_dependencies',
+  },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/sparse-checkout$/,
+    name: 'widen_sparse_checkout',
+  },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/snapshots$/, name: 'snapshot_task' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/snapshots$/, name: 'list_snapshots' },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/snapshots\/restore$/,
+    name: 'restore_snapshot',
+  },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/conflicts$/, name: 'get_conflicts' },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/conflicts\/resolve$/,
+    name: 'resolve_conflict',
+  },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/conflicts\/continue$/,
+    name: 'continue_merge',
+  },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/conflicts\/abort$/, name: 'abort_merge' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/summary$/, name: 'get_task_summary' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/notes$/, name: 'get_task_notes' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/notes$/, name: 'set_task_notes' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/time$/, name: 'get_task_time' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/time\/start$/, name: 'start_task_timer' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/time\/pause$/, name: 'pause_task_timer' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/log$/, name: 'get_task_log' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/blame$/, name: 'get_blame' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/diff$/, name: 'start_diff' },
+  { method: 'POST', pattern: /^\/v1\/diffs\/read$/, name: 'read_diff_chunks' },
+  { method: 'POST', pattern: /^\/v1\/diffs\/more$/, name: 'load_more_diff' },
+  { method: 'POST', pattern: /^\/v1\/diffs\/cancel$/, name: 'cancel_diff' },
+  { method: 'GET', pattern: /^\/v1\/tasks\/([^/]+)\/comments$/, name: 'list_review_comments' },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/comments$/, name: 'add_review_comment' },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/comments\/resolve$/,
+    name: 'resolve_review_comment',
+  },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/tasks\/([^/]+)\/comments\/send$/,
+    name: 'send_review_comments',
+  },
+  { method: 'POST', pattern: /^\/v1\/tasks\/([^/]+)\/runs$/, name: 'queue_run' },
+  { method: 'GET', pattern: /^\/v1\/runs$/, name: 'list_runs' },
+  { method: 'POST', pattern: /^\/v1\/runs\/cancel$/, name: 'cancel_run' },
+  { method: 'GET', pattern: /^\/v1\/merge-queue$/, name: 'get_merge_queue' },
+  { method: 'POST', pattern: /^\/v1\/merge-queue$/, name: 'enqueue_merges' },
+  {
+    method: 'POST',
+    pattern: /^\/v1\/merge-queue\/remove$/,
+    name: 'remove_from_merge_queue',
+  },
+  { method: 'POST', pattern: /^\/v1\/merge-queue\/resume$/, name: 'resume_merge_queue' },
+  { method: 'GET', pattern: /^\/v1\/secrets$/, name: 'list_secrets' },
+  { method: 'POST', pattern: /^\/v1\/secrets$/, name: 'set_secret' },
+  { method: 'POST', pattern: /^\/v1\/secrets\/delete$/, name: 'delete_secret' },
+  { method: 'GET', pattern: /^\/v1\/trash$/, name: 'list_trash' },
+  { method: 'POST', pattern: /^\/v1\/trash\/restore$/, name: 'restore_deleted_task' },
+  { method: 'POST', pattern: /^\/v1\/undo$/, name: 'undo_last_operation' },
+  { method: 'POST', pattern: /^\/v1\/sessions$/, name: 'list_sessions' },
+  { method: 'POST', pattern: /^\/v1\/history$/, name: 'query_history' },
+  { method: 'POST', pattern: /^\/v1\/search$/, name: 'search_transcripts' },
+  { method: 'POST', pattern: /^\/v1\/metrics$/, name: 'get_metrics' },
+  { method: 'POST', pattern: /^\/v1\/disk-usage$/, name: 'get_disk_usage' },
+  { method: 'GET', pattern: /^\/v1\/resources$/, name: 'get_resource_usage' },
+  { method: 'GET', pattern: /^\/v1\/commands$/, name: 'list_commands' },
+  { method: 'POST', pattern: /^\/v1\/commands\/invoke$/, name: 'invoke_command' },
+];
+
+/** The method for an HTTP request, or null when no route matches. */
+export function matchAutomationRoute(
+  httpMethod: string,
+  pathname: string,
+): { method: AutomationMethod | AutomationMetaMethod; taskId?: string } | null {
+  for (const route of ROUTES) {
+    if (route.method !== httpMethod) continue;
+    const m = pathname.match(route.pattern);
+    if (!m) continue;
+    try {
+      return { method: route.name, taskId: m[1] ? decodeURIComponent(m[1]) : undefined };
+    } catch {
+      return null; // malformed escape in the task id
+    }
+  }
+  return null;
+}
+
+/** The HTTP route of a method, with `:id` standing for the task id. */
+export function automationRoute(name: AutomationMethod): { method: string; path: string } {
+  const route = ROUTES.find((r) => r.name === name);
+  if (!route) throw new Error(`No route for ${name}`);
+  const path = route.pattern.source
+    .replace(/^\^|\$$/g, '')
+    .replace(/\\\//g, '/')
+    .replace('([^/]+)', ':id');
+  return { method: route.method, path };
+}
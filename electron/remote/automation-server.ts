@@ -0,0 +1,191 @@
+// electron/remote/automation-server.ts
+
+import { createServer, type IncomingMessage, type Server, type ServerResponse } from 'http';
+import { randomBytes, randomUUID, timingSafeEqual } from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import type { BrowserWindow } from 'electron';
+import { IPC } from '../ipc/channels.js';
+import { getStateDir } from '../ipc/persistence.js';
+import { createLogger } from '../ipc/log.js';
+import {
+  matchAutomationRoute,
+  type AutomationRequest,
+  type AutomationResponse,
+} from './automation-protocol.js';
+import { commandRequest, listAutomationCommands } from './automation-commands.js';
+
+const log = createLogger('automation');
+
+/** Creating a task waits for git to add a worktree, which can be slow on big repos. */
+const REQUEST_TIMEOUT_MS = 60_000;
+const MAX_BODY_BYTES = 256 * 1024;
+
+const SECURITY_HEADERS: Record<string, string> = {
+  'X-Content-Type-Options': 'nosniff',
+  'Referrer-Policy': 'no-referrer',
+};
+
+let server: Server | null = null;
+let serverPort: number | null = null;
+let cachedToken: string | null = null;
+const pending = new Map<
+  string,
+  { resolve: (r: AutomationResponse) => void; timer: ReturnType<typeof setTimeout> }
+>();
+
+function getTokenPath(): string {
+  return path.join(getStateDir(), 'automation-token');
+}
+
+/**
+ * Bearer token for the API, generated on first use. It lives in a file only
+ * the user can read, so scripts on this machine can pick it up.
+ */
+export function getAutomationToken(): string {
+  if (cachedToken) return cachedToken;
+  try {
+    cachedToken = fs.readFileSync(getTokenPath(), 'utf8').trim() || null;
+  } catch {
+    // Not generated yet
+  }
+  return cachedToken ?? regenerateAutomationToken();
+}
+
+/** Replace the token; clients holding the old one are rejected from then on. */
+export function regenerateAutomationToken(): string {
+  const tokenPath = getTokenPath();
+  const token = randomBytes(32).toString('hex');
+  fs.mkdirSync(path.dirname(tokenPath), { recursive: true });
+  const tmpPath = tokenPath + '.tmp';
+  fs.writeFileSync(tmpPath, token + '\n', { encoding: 'utf8', mode: 0o600 });
+  fs.renameSync(tmpPath, tokenPath);
+  cachedToken = token;
+  return token;
+}
+
+function tokenMatches(req: IncomingMessage): boolean {
+  const auth = req.headers.authorization;
+  if (!auth?.startsWith('Bearer ')) return false;
+  const given = Buffer.from(auth.slice(7));
+  const expected = Buffer.from(getAutomationToken());
+  return given.length === expected.length && timingSafeEqual(given, expected);
+}
+
+function writeJson(res: ServerResponse, status: number, payload: unknown): void {
+  res.writeHead(status, { ...SECURITY_HEADERS, 'Content-Type': 'application/json' });
+  res.end(JSON.stringify(payload));
+}
+
+function readJsonBody(req: IncomingMessage): Promise<Record<string, unknown>> {
+  return new Promise((resolve, reject) => {
+    let raw = '';
+    req.on('data', (chunk: Buffer) => {
+      raw += chunk.toString('utf8');
+      if (raw.length > MAX_BODY_BYTES) {
+        reject(new Error('request body too large'));
+        req.destroy();
+      }
+    });
+    req.on('error', reject);
+    req.on('end', () => {
+      if (!raw.trim()) return resolve({});
+      try {
+        const parsed: unknown = JSON.parse(raw);
+        if (!parsed || typeof parsed !== 'object' || Array.isArray(parsed)) {
+          reject(new Error('request body must be a JSON object'));
+        } else {
+          resolve(parsed as Record<string, unknown>);
+        }
+      } catch {
+        reject(new Error('invalid json'));
+      }
+    });
+  });
+}
+
+/** Hand a request to the renderer and wait for its `AutomationResponse`. */
+function dispatch(
+  win: BrowserWindow,
+  request: Omit<AutomationRequest, 'id'>,
+): Promise<AutomationResponse> {
+  const id = randomUUID();
+  return new Promise((resolve) => {
+    if (win.isDestroyed()) {
+      resolve({ id, error: { status: 503, message: 'app window is not available' } });
+      return;
+    }
+    const timer = setTimeout(() => {
+      pending.delete(id);
+      resolve({ id, error: { status: 504, message: 'timed out waiting for the app' } });
+    }, REQUEST_TIMEOUT_MS);
+    pending.set(id, { resolve, timer });
+    win.webContents.send(IPC.AutomationRequest, { id, ...request } satisfies AutomationRequest);
+  });
+}
+
+/** Called with the renderer's answer to a dispatched request. */
+export function resolveAutomationRequest(response: AutomationResponse): void {
+  const entry = pending.get(response.id);
+  if (!entry) return;
+  pending.delete(response.id);
+  clearTimeout(entry.timer);
+  entry.resolve(response);
+}
+
+/** Start the API on 127.0.0.1:`port`. It is never reachable from other machines. */
+export function startAutomationServer(win: BrowserWindow, port: number): void {
+  if (server && serverPort === port) return;
+  stopAutomationServer();
+
+  server = createServer((req, res) => {
+    const url = new URL(req.url ?? '/', 'http://127.0.0.1');
+    if (!tokenMatches(req)) {
+      writeJson(res, 401, { error: 'unauthorized' });
+      return;
+    }
+    const route = matchAutomationRoute(req.method ?? 'GET', url.pathname);
+    if (!route) {
+      writeJson(res, 404, { error: 'not found' });
+      return;
+    }
+    readJsonBody(req)
+      .then((body): AutomationResponse | Promise<AutomationResponse> => {
+        const { method, taskId } = route;
+        if (method === 'list_commands') return { id: '', result: listAutomationCommands() };
+        if (method === 'invoke_command') {
+          const forwarded = commandRequest(body);
+          return 'error' in forwarded ? { id: '', ...forwarded } : dispatch(win, forwarded);
+        }
+        return dispatch(win, { method, taskId, body });
+      })
+      .then((response) => {
+        if (response.error) {
+          writeJson(res, response.error.status, { error: response.error.message });
+        } else {
+          writeJson(res, 200, response.result ?? null);
+        }
+      })
+      .catch((err: unknown) => {
+        writeJson(res, 400, { error: err instanceof Error ? err.message : String(err) });
+      });
+  });
+  server.on('error', (err) => {
+    log.error(`Automation API failed on port ${port}:`, err);
+  });
+  server.listen(port, '127.0.0.1', () => {
+    log.info(`Automation API listening on http://127.0.0.1:${port}`);
+  });
+  serverPort = port;
+}
+
+export function stopAutomationServer(): void {
+  for (const [id, entry] of pending) {
+    clearTimeout(entry.timer);
+    entry.resolve({ id, error: { status: 503, message: 'automation API stopped' } });
+  }
+  pending.clear();
+  server?.close();
+  server = null;
+  serverPort = null;
+}
@@ -0,0 +1,28 @@
+import { describe, it, expect } from 'vitest';
+import { RingBuffer } from './ring-buffer.js';
+
+describe('RingBuffer', () => {
+  it('keeps the newest bytes and hands the evicted ones over in order', () => {
+    const evicted: string[] = [];
+    const buf = new RingBuffer(4, (data) => evicted.push(data.toString()));
+    buf.write(Buffer.from('abc'));
+    buf.write(Buffer.from('de'));
+    buf.write(Buffer.from('fghij'));
+    expect(buf.read().toString()).toBe('ghij');
+    expect(evicted.join('')).toBe('abcdef');
+    expect(buf.totalWritten).toBe(10);
+  });
+
+  it('evicts the oldest bytes when shrunk and keeps everything when grown', () => {
+    const evicted: string[] = [];
+    const buf = new RingBuffer(6, (data) => evicted.push(data.toString()));
+    buf.write(Buffer.from('abcdefgh'));
+    buf.resize(3);
+    expect(buf.read().toString()).toBe('fgh');
+    expect(evicted.join('')).toBe('abcde');
+    buf.resize(8);
+    buf.write(Buffer.from('ij'));
+    expect(buf.read().toString()).toBe('fghij');
+    expect(buf.capacity).toBe(8);
+  });
+});
@@ -3,22 +3,35 @@ export class RingBuffer {
   private buf: Buffer;
   private pos = 0;
   private full = false;
+  private written = 0;
 
-  constructor(private readonly capacity: number = 64 * 1024) {
-    this.buf = Buffer.alloc(capacity);
+  constructor(
+    private cap: number = 64 * 1024,
+    /** Receives the oldest bytes, in order, as newer ones push them out. */
+    private readonly onEvict?: (data: Buffer) => void,
+  ) {
+    this.buf = Buffer.alloc(cap);
   }
 
   /** Append data to the ring buffer. */
   write(data: Buffer): void {
-    if (data.length >= this.capacity) {
+    this.written += data.length;
+    const overflow = this.length + data.length - this.cap;
+    if (this.onEvict && overflow > 0) {
+      const fromBuffer = Math.min(overflow, this.length);
+      if (fromBuffer > 0) this.onEvict(this.oldest(fromBuffer));
+      if (overflow > fromBuffer) this.onEvict(data.subarray(0, overflow - fromBuffer));
+    }
+
+    if (data.length >= this.cap) {
       // Data larger than buffer — keep only the tail
-      data.copy(this.buf, 0, data.length - this.capacity);
+      data.copy(this.buf, 0, data.length - this.cap);
       this.pos = 0;
       this.full = true;
       return;
     }
 
-    const spaceAtEnd = this.capacity - this.pos;
+    const spaceAtEnd = this.cap - this.pos;
     if (data.length <= spaceAtEnd) {
       data.copy(this.buf, this.pos);
     } else {
@@ -26,10 +39,31 @@ export class RingBuffer {
       data.copy(this.buf, 0, spaceAtEnd);
     }
 
-    this.pos = (this.pos + data.length) % this.capacity;
+    this.pos = (this.pos + data.length) % this.cap;
     if (!this.full && this.pos < data.length) this.full = true;
   }
 
+  /** A copy of the `n` oldest buffered bytes. */
+  private oldest(n: number): Buffer {
+    const start = this.full ? this.pos : 0;
+    const end = start + n;
+    if (end <= this.cap) return Buffer.from(this.buf.subarray(start, end));
+    return Buffer.concat([this.buf.subarray(start), this.buf.subarray(0, end - this.cap)]);
+  }
+
+  /** Change the capacity, evicting the oldest bytes when it shrinks below what's stored. */
+  resize(capacity: number): void {
+    if (capacity === this.cap) return;
+    const data = this.read();
+    const drop = Math.max(0, data.length - capacity);
+    if (this.onEvict && drop > 0) this.onEvict(data.subarray(0, drop));
+    this.buf = Buffer.alloc(capacity);
+    this.cap = capacity;
+    data.copy(this.buf, 0, drop);
+    this.pos = (data.length - drop) % capacity;
+    this.full = data.length - drop === capacity;
+  }
+
   /** Read all buffered data in chronological order (returns a copy). */
   read(): Buffer {
     if (!this.full) return Buffer.from(this.buf.subarray(0, this.pos));
@@ -43,7 +77,17 @@ export class RingBuffer {
 
   /** Number of bytes currently stored. */
   get length(): number {
-    return this.full ? this.capacity : this.pos;
+    return this.full ? this.cap : this.pos;
+  }
+
+  /** Bytes the buffer can hold, and so the memory it takes. */
+  get capacity(): number {
+    return this.cap;
+  }
+
+  /** Bytes ever written, including those since pushed out. */
+  get totalWritten(): number {
+    return this.written;
   }
 
   /** Reset the buffer. */
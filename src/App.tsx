@@ -44,11 +44,29 @@ import {
   setNewTaskDropUrl,
   validateProjectPaths,
   setPlanContent,
+  markAgentRestarted,
+  handleSessionInfoChanged,
+  handleAgentHung,
+  handleAgentEvent,
+  handleAgentApprovalEvent,
 } from './store/store';
 import { isGitHubUrl } from './lib/github-url';
 import type { PersistedWindowState } from './store/types';
+import type {
+  AgentApprovalEvent,
+  AgentOutputEventMessage,
+  AgentRestartEvent,
+  AgentStallEvent,
+  OrphanedSession,
+  SessionInfo,
+} from './ipc/types';
 import { registerShortcut, initShortcuts } from './lib/shortcuts';
 import { setupAutosave } from './store/autosave';
+import { startAutomationBridge } from './store/automation';
+import { startMergedTaskJanitor } from './store/janitor';
+import { startDependencyWatcher } from './store/dependencies';
+import { startDeepLinkListener } from './store/deepLinks';
+import { startMergeQueueListener } from './store/merge-queue';
 import { isMac, mod } from './lib/platform';
 import { createCtrlWheelZoomHandler } from './lib/wheelZoom';
 import { ArenaOverlay } from './arena/ArenaOverlay';
@@ -290,6 +308,25 @@ function App() {
       }
     })();
 
+    // Agents that outlived a force-quit would collide with the restored tasks' new sessions
+    const orphans = await invoke<OrphanedSession[]>(IPC.ListOrphanedSessions).catch(() => []);
+    if (orphans.length > 0) {
+      const commands = [...new Set(orphans.map((o) => o.command))].join(', ');
+      const kill = await confirm(
+        t(
+          `${orphans.length} sessions from before Parallel Code last quit are still running (${commands}). They can't be reattached and may conflict with restored tasks. End them?`,
+          `上次退出前的 ${orphans.length} 个会话仍在运行（${commands}）。它们无法重新连接，且可能与恢复的任务冲突。要结束它们吗？`,
+        ),
+        {
+          title: t('Leftover Sessions', '遗留会话'),
+          kind: 'warning',
+          okLabel: t('End Them', '结束'),
+          cancelLabel: t('Leave Running', '保持运行'),
+        },
+      ).catch(() => false);
+      await invoke(IPC.ResolveOrphanedSessions, { kill }).catch(console.error);
+    }
+
     await loadAgents();
     await loadState();
     void loadCliSlashCommands();
@@ -298,6 +335,11 @@ function App() {
     await captureWindowState();
     setupAutosave();
     startTaskStatusPolling();
+    const stopAutomationBridge = startAutomationBridge();
+    const stopJanitor = startMergedTaskJanitor();
+    const stopDependencyWatcher = startDependencyWatcher();
+    const stopDeepLinkListener = startDeepLinkListener();
+    const stopMergeQueueListener = startMergeQueueListener();
 
     // Listen for plan content pushed from backend plan watcher
     const offPlanContent = window.electron.ipcRenderer.on(IPC.PlanContent, (data: unknown) => {
@@ -307,6 +349,29 @@ function App() {
       }
     });
 
+    // Crash restarts performed by the backend restart policy
+    const offAgentRestarted = window.electron.ipcRenderer.on(
+      IPC.AgentRestarted,
+      (data: unknown) => {
+        const msg = data as AgentRestartEvent;
+        markAgentRestarted(msg.agentId, msg.attempt);
+      },
+    );
+    const offSessionInfo = window.electron.ipcRenderer.on(
+      IPC.SessionInfoChanged,
+      (data: unknown) => handleSessionInfoChanged(data as SessionInfo),
+    );
+    const offAgentApproval = window.electron.ipcRenderer.on(
+      IPC.AgentAwaitingApproval,
+      (data: unknown) => handleAgentApprovalEvent(data as AgentApprovalEvent),
+    );
+    const offAgentHung = window.electron.ipcRenderer.on(IPC.AgentHung, (data: unknown) =>
+      handleAgentHung(data as AgentStallEvent),
+    );
+    const offAgentEvent = window.electron.ipcRenderer.on(IPC.AgentEvent, (data: unknown) =>
+      handleAgentEvent(data as AgentOutputEventMessage),
+    );
+
     const handlePaste = (e: ClipboardEvent) => {
       if (store.showNewTaskDialog || store.showHelpDialog || store.showSettingsDialog) return;
       const el = document.activeElement;
@@ -569,6 +634,16 @@ function App() {
       cleanupShortcuts();
       stopTaskStatusPolling();
       offPlanContent();
+      offAgentRestarted();
+      offSessionInfo();
+      offAgentApproval();
+      offAgentHung();
+      offAgentEvent();
+      stopAutomationBridge();
+      stopJanitor();
+      stopDependencyWatcher();
+      stopDeepLinkListener();
+      stopMergeQueueListener();
       unlistenFocusChanged?.();
       unlistenResized?.();
       unlistenMoved?.();
@@ -6,10 +6,12 @@ import { sf } from '../lib/fontScale';
 import { getStatusColor } from '../lib/status-colors';
 import { store } from '../store/store';
 import { localize } from '../lib/i18n';
-import type { ChangedFile } from '../ipc/types';
+import type { ChangedFile, WorktreeFilesChangedEvent } from '../ipc/types';
 
 interface ChangedFilesListProps {
   worktreePath: string;
+  /** Only list changes under this sub-directory of the worktree */
+  scope?: string;
   isActive?: boolean;
   onFileClick?: (file: ChangedFile) => void;
   ref?: (el: HTMLDivElement) => void;
@@ -43,10 +45,12 @@ export function ChangedFilesList(props: ChangedFilesListProps) {
     }
   }
 
-  // Poll every 5s, matching the git status polling interval.
+  // Refresh on worktree file-change events, and poll every 5s to pick up
+  // commits (which don't touch the worktree's files).
   // Falls back to branch-based diff when worktree path doesn't exist.
   createEffect(() => {
     const path = props.worktreePath;
+    const scope = props.scope;
     const projectRoot = props.projectRoot;
     const branchName = props.branchName;
     if (!props.isActive) return;
@@ -63,6 +67,7 @@ export function ChangedFilesList(props: ChangedFilesListProps) {
           try {
             const result = await invoke<ChangedFile[]>(IPC.GetChangedFiles, {
               worktreePath: path,
+              scope,
             });
             if (!cancelled) setFiles(result);
             return;
@@ -93,9 +98,30 @@ export function ChangedFilesList(props: ChangedFilesListProps) {
     const timer = setInterval(() => {
       if (!usingBranchFallback) void refresh();
     }, 5000);
+    let watching = false;
+    let offFilesChanged: (() => void) | undefined;
+    if (path) {
+      offFilesChanged = window.electron.ipcRenderer.on(
+        IPC.WorktreeFilesChanged,
+        (data: unknown) => {
+          const msg = data as WorktreeFilesChangedEvent;
+          if (msg.worktreePath === path && !usingBranchFallback) void refresh();
+        },
+      );
+      invoke(IPC.WatchWorktree, { worktreePath: path })
+        .then(() => {
+          watching = true;
+          if (cancelled) invoke(IPC.UnwatchWorktree, { worktreePath: path }).catch(() => {});
+        })
+        .catch(() => {
+          // Worktree may not exist — polling and branch fallback still apply
+        });
+    }
     onCleanup(() => {
       cancelled = true;
       clearInterval(timer);
+      offFilesChanged?.();
+      if (watching) invoke(IPC.UnwatchWorktree, { worktreePath: path }).catch(() => {});
     });
   });
 
@@ -1,7 +1,7 @@
 import { Show, createResource } from 'solid-js';
 import { invoke } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
-import { closeTask, getProject } from '../store/store';
+import { branchDeletionRisk, closeTask, getProject, getProjectPath } from '../store/store';
 import { ConfirmDialog } from './ConfirmDialog';
 import { theme } from '../lib/theme';
 import type { Task } from '../store/types';
@@ -16,8 +16,27 @@ interface CloseTaskDialogProps {
 export function CloseTaskDialog(props: CloseTaskDialogProps) {
   const [worktreeStatus] = createResource(
     () => (props.open && !props.task.directMode ? props.task.worktreePath : null),
-    (path) => invoke<WorktreeStatus>(IPC.GetWorktreeStatus, { worktreePath: path }),
+    // Closing deletes the worktree, so a cached "no changes" isn't good enough
+    (path) => invoke<WorktreeStatus>(IPC.GetWorktreeStatus, { worktreePath: path, fresh: true }),
   );
+  const [deletionRisk] = createResource(
+    () =>
+      props.open &&
+      !props.task.directMode &&
+      !props.task.adoptedBranch &&
+      (getProject(props.task.projectId)?.deleteBranchOnClose ?? true)
+        ? props.task.branchName
+        : null,
+    (branchName) =>
+      invoke(IPC.CheckBranchDeletion, {
+        projectRoot: getProjectPath(props.task.projectId) ?? '',
+        branchName,
+      }).then(
+        () => null,
+        (err: unknown) => branchDeletionRisk(err),
+      ),
+  );
+  const commits = (n: number) => `${n} commit${n === 1 ? '' : 's'}`;
 
   return (
     <ConfirmDialog
@@ -34,7 +53,9 @@ export function CloseTaskDialog(props: CloseTaskDialogProps) {
           <Show when={!props.task.directMode}>
             <Show
               when={
-                worktreeStatus()?.has_uncommitted_changes || worktreeStatus()?.has_committed_changes
+                worktreeStatus()?.has_uncommitted_changes ||
+                worktreeStatus()?.has_committed_changes ||
+                deletionRisk()
               }
             >
               <div
@@ -60,7 +81,31 @@ export function CloseTaskDialog(props: CloseTaskDialogProps) {
                     Warning: There are uncommitted changes that will be permanently lost.
                   </div>
                 </Show>
-                <Show when={worktreeStatus()?.has_committed_changes}>
+                <Show when={deletionRisk()}>
+                  {(risk) => (
+                    <div
+                      style={{
+                        'font-size': '12px',
+                        color: theme.warning,
+                        background: `color-mix(in srgb, ${theme.warning} 8%, transparent)`,
+                        padding: '8px 12px',
+                        'border-radius': '8px',
+                        border: `1px solid color-mix(in srgb, ${theme.warning} 20%, transparent)`,
+                        'font-weight': '600',
+                      }}
+                    >
+                      Warning: {commits(risk().unmerged)} on this branch{' '}
+                      {risk().unmerged === 1 ? 'is' : 'are'} not in main
+                      {risk().unpushed === null
+                        ? ''
+                        : risk().unpushed === 0
+                          ? ', though all are pushed'
+                          : `, ${risk().unpushed} of them not pushed anywhere`}
+                      . Its tip is <code>{risk().tip.slice(0, 12)}</code> if you need it back.
+                    </div>
+                  )}
+                </Show>
+                <Show when={worktreeStatus()?.has_committed_changes && !deletionRisk()}>
                   <div
                     style={{
                       'font-size': '12px',
@@ -120,7 +165,8 @@ export function CloseTaskDialog(props: CloseTaskDialogProps) {
       danger={!props.task.directMode}
       onConfirm={() => {
         props.onDone();
-        closeTask(props.task.id);
+        // The warning above was the confirmation
+        closeTask(props.task.id, { force: deletionRisk() != null });
       }}
       onCancel={() => props.onDone()}
     />
@@ -5,7 +5,8 @@ import { IPC } from '../../electron/ipc/channels';
 import { theme } from '../lib/theme';
 import { isBinaryDiff } from '../lib/diff-parser';
 import { getStatusColor } from '../lib/status-colors';
-import { openFileInEditor } from '../lib/shell';
+import { openFileInEditor, openInEditor } from '../lib/shell';
+import { store } from '../store/store';
 import { MonacoDiffEditor } from './MonacoDiffEditor';
 import type { ChangedFile, FileDiffResult } from '../ipc/types';
 
@@ -232,7 +233,11 @@ export function DiffViewerDialog(props: DiffViewerDialogProps) {
               </div>
 
               <button
-                onClick={() => openFileInEditor(props.worktreePath, file().path)}
+                onClick={() =>
+                  store.editorCommand
+                    ? openInEditor(store.editorCommand, props.worktreePath, file().path)
+                    : openFileInEditor(props.worktreePath, file().path)
+                }
                 disabled={!props.worktreePath}
                 style={{
                   background: 'transparent',
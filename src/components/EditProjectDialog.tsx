@@ -9,6 +9,7 @@ import {
 } from '../store/store';
 import { sanitizeBranchPrefix, toBranchName } from '../lib/branch-name';
 import { theme } from '../lib/theme';
+import { SHARED_CACHES, SHARED_CACHE_LABELS, type SharedCache } from '../lib/shared-caches';
 import type { Project, TerminalBookmark } from '../store/types';
 
 interface EditProjectDialogProps {
@@ -27,6 +28,13 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
   const [branchPrefix, setBranchPrefix] = createSignal('task');
   const [deleteBranchOnClose, setDeleteBranchOnClose] = createSignal(true);
   const [defaultDirectMode, setDefaultDirectMode] = createSignal(false);
+  const [testCommand, setTestCommand] = createSignal('');
+  const [checksText, setChecksText] = createSignal('');
+  const [requireChecks, setRequireChecks] = createSignal(false);
+  const [cleanupMerged, setCleanupMerged] = createSignal(false);
+  const [cleanupGraceHours, setCleanupGraceHours] = createSignal('24');
+  const [sparseText, setSparseText] = createSignal('');
+  const [sharedCaches, setSharedCaches] = createSignal<SharedCache[]>([]);
   const [bookmarks, setBookmarks] = createSignal<TerminalBookmark[]>([]);
   const [newCommand, setNewCommand] = createSignal('');
   let nameRef!: HTMLInputElement;
@@ -40,6 +48,13 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
     setBranchPrefix(sanitizeBranchPrefix(p.branchPrefix ?? 'task'));
     setDeleteBranchOnClose(p.deleteBranchOnClose ?? true);
     setDefaultDirectMode(p.defaultDirectMode ?? false);
+    setTestCommand(p.testCommand ?? '');
+    setChecksText((p.checks ?? []).join('\n'));
+    setRequireChecks(p.requireChecksBeforeMerge ?? false);
+    setCleanupMerged(p.cleanupMergedTasks ?? false);
+    setCleanupGraceHours(String(p.cleanupGraceHours ?? 24));
+    setSparseText((p.sparseCheckout ?? []).join('\n'));
+    setSharedCaches(p.sharedCaches ?? []);
     setBookmarks(p.terminalBookmarks ? [...p.terminalBookmarks] : []);
     setNewCommand('');
     requestAnimationFrame(() => nameRef?.focus());
@@ -61,8 +76,18 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
     setBookmarks(bookmarks().filter((b) => b.id !== id));
   }
 
+  function toggleSharedCache(cache: SharedCache, on: boolean) {
+    setSharedCaches(SHARED_CACHES.filter((c) => (c === cache ? on : sharedCaches().includes(c))));
+  }
+
   const canSave = () => name().trim().length > 0;
 
+  /** The grace period as entered, or undefined for the default when it isn't a number. */
+  function graceHoursValue(): number | undefined {
+    const hours = Number(cleanupGraceHours().trim());
+    return cleanupGraceHours().trim() && Number.isFinite(hours) && hours >= 0 ? hours : undefined;
+  }
+
   function handleSave() {
     if (!canSave() || !props.project) return;
     const sanitizedPrefix = sanitizeBranchPrefix(branchPrefix());
@@ -73,6 +98,16 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
       deleteBranchOnClose: deleteBranchOnClose(),
       defaultDirectMode: defaultDirectMode(),
       terminalBookmarks: bookmarks(),
+      testCommand: testCommand().trim() || undefined,
+      checks: checksText().split('\n').map((c) => c.trim()).filter(Boolean),
+      requireChecksBeforeMerge: requireChecks(),
+      cleanupMergedTasks: cleanupMerged(),
+      cleanupGraceHours: graceHoursValue(),
+      sparseCheckout: sparseText()
+        .split('\n')
+        .map((d) => d.trim().replace(/^\/+|\/+$/g, ''))
+        .filter(Boolean),
+      sharedCaches: sharedCaches(),
     });
     props.onClose();
   }
@@ -285,6 +320,158 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
               </Show>
             </div>
 
+            {/* Test command */}
+            <div style={{ display: 'flex', 'flex-direction': 'column', gap: '8px' }}>
+              <label
+                style={{
+                  'font-size': '11px',
+                  color: theme.fgMuted,
+                  'text-transform': 'uppercase',
+                  'letter-spacing': '0.05em',
+                }}
+              >
+                Test command
+              </label>
+              <input
+                class="input-field"
+                type="text"
+                value={testCommand()}
+                onInput={(e) => setTestCommand(e.currentTarget.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter' && canSave()) handleSave();
+                }}
+                placeholder="e.g. npm test, cargo test"
+                style={{
+                  background: theme.bgInput,
+                  border: `1px solid ${theme.border}`,
+                  'border-radius': '8px',
+                  padding: '10px 14px',
+                  color: theme.fg,
+                  'font-size': '13px',
+                  'font-family': "'JetBrains Mono', monospace",
+                  outline: 'none',
+                }}
+              />
+            </div>
+
+            {/* Pre-merge checks */}
+            <div style={{ display: 'flex', 'flex-direction': 'column', gap: '8px' }}>
+              <label
+                style={{
+                  'font-size': '11px',
+                  color: theme.fgMuted,
+                  'text-transform': 'uppercase',
+                  'letter-spacing': '0.05em',
+                }}
+              >
+                Pre-merge checks
+              </label>
+              <textarea
+                class="input-field"
+                value={checksText()}
+                onInput={(e) => setChecksText(e.currentTarget.value)}
+                placeholder={'One command per line, run in order\ne.g. npm run build'}
+                rows={3}
+                style={{
+                  background: theme.bgInput,
+                  border: `1px solid ${theme.border}`,
+                  'border-radius': '8px',
+                  padding: '10px 14px',
+                  color: theme.fg,
+                  'font-size': '13px',
+                  'font-family': "'JetBrains Mono', monospace",
+                  outline: 'none',
+                  resize: 'vertical',
+                }}
+              />
+              <label
+                style={{
+                  display: 'flex',
+                  'align-items': 'center',
+                  gap: '8px',
+                  cursor: 'pointer',
+                  'font-size': '13px',
+                  color: theme.fg,
+                }}
+              >
+                <input
+                  type="checkbox"
+                  checked={requireChecks()}
+                  onChange={(e) => setRequireChecks(e.currentTarget.checked)}
+                  style={{ cursor: 'pointer' }}
+                />
+                Refuse to merge until all checks pass
+              </label>
+            </div>
+
+            {/* Sparse checkout */}
+            <div style={{ display: 'flex', 'flex-direction': 'column', gap: '8px' }}>
+              <label
+                style={{
+                  'font-size': '11px',
+                  color: theme.fgMuted,
+                  'text-transform': 'uppercase',
+                  'letter-spacing': '0.05em',
+                }}
+              >
+                Sparse checkout
+              </label>
+              <textarea
+                class="input-field"
+                value={sparseText()}
+                onInput={(e) => setSparseText(e.currentTarget.value)}
+                placeholder={'Directories new tasks check out, one per line\ne.g. packages/web'}
+                rows={2}
+                style={{
+                  background: theme.bgInput,
+                  border: `1px solid ${theme.border}`,
+                  'border-radius': '8px',
+                  padding: '10px 14px',
+                  color: theme.fg,
+                  'font-size': '13px',
+                  'font-family': "'JetBrains Mono', monospace",
+                  outline: 'none',
+                  resize: 'vertical',
+                }}
+              />
+            </div>
+
+            {/* Shared caches */}
+            <div style={{ display: 'flex', 'flex-direction': 'column', gap: '8px' }}>
+              <label
+                style={{
+                  'font-size': '11px',
+                  color: theme.fgMuted,
+                  'text-transform': 'uppercase',
+                  'letter-spacing': '0.05em',
+                }}
+              >
+                Shared caches
+              </label>
+              <For each={SHARED_CACHES}>
+                {(cache) => (
+                  <label
+                    style={{
+                      display: 'flex',
+                      'align-items': 'center',
+                      gap: '8px',
+                      cursor: 'pointer',
+                      'font-size': '13px',
+                      color: theme.fg,
+                    }}
+                  >
+                    <input
+                      type="checkbox"
+                      checked={sharedCaches().includes(cache)}
+                      onChange={(e) => toggleSharedCache(cache, e.currentTarget.checked)}
+                      style={{ cursor: 'pointer' }}
+                    />
+                    {SHARED_CACHE_LABELS[cache]}
+                  </label>
+                )}
+              </For>
+            </div>
+
             {/* Color palette */}
             <div style={{ display: 'flex', 'flex-direction': 'column', gap: '8px' }}>
               <label
@@ -346,6 +533,45 @@ export function EditProjectDialog(props: EditProjectDialogProps) {
               Always delete branch and worklog on merge
             </label>
 
+            {/* Automatic cleanup of merged tasks */}
+            <label
+              style={{
+                display: 'flex',
+                'align-items': 'center',
+                gap: '8px',
+                cursor: 'pointer',
+                'font-size': '13px',
+                color: theme.fg,
+              }}
+            >
+              <input
+                type="checkbox"
+                checked={cleanupMerged()}
+                onChange={(e) => setCleanupMerged(e.currentTarget.checked)}
+                style={{ cursor: 'pointer' }}
+              />
+              Clean up tasks whose branch was merged, after
+              <input
+                class="input-field"
+                type="number"
+                min="0"
+                value={cleanupGraceHours()}
+                disabled={!cleanupMerged()}
+                onInput={(e) => setCleanupGraceHours(e.currentTarget.value)}
+                style={{
+                  width: '56px',
+                  background: theme.bgInput,
+                  border: `1px solid ${theme.border}`,
+                  'border-radius': '6px',
+                  padding: '2px 6px',
+                  color: theme.fg,
+                  'font-size': '13px',
+                  outline: 'none',
+                }}
+              />
+              hours
+            </label>
+
             {/* Default direct mode preference */}
             <label
               style={{
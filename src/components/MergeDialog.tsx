@@ -1,13 +1,36 @@
-import { Show, For, createSignal, createResource, createEffect } from 'solid-js';
+import { Show, For, createSignal, createResource, createEffect, onCleanup } from 'solid-js';
 import { invoke } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
-import { store, mergeTask } from '../store/store';
+import {
+  store,
+  mergeTask,
+  getProject,
+  runTaskChecks,
+  getCheckResults,
+  getBlockingTasks,
+} from '../store/store';
 import { sendPrompt } from '../store/tasks';
 import { ConfirmDialog } from './ConfirmDialog';
 import { ChangedFilesList } from './ChangedFilesList';
 import { theme } from '../lib/theme';
+import { buildSquashMessage } from '../lib/commit-message';
 import type { Task } from '../store/types';
-import type { ChangedFile, MergeStatus, WorktreeStatus } from '../ipc/types';
+import type {
+  ChangedFile,
+  CheckStatus,
+  CheckStatusEvent,
+  MergeStatus,
+  TaskCheckResults,
+  WorktreeStatus,
+} from '../ipc/types';
+
+const CHECK_STATUS_COLORS: Record<CheckStatus, string> = {
+  pending: theme.fgMuted,
+  running: theme.warning,
+  passed: theme.success,
+  failed: theme.error,
+  skipped: theme.fgSubtle,
+};
 
 interface MergeDialogProps {
   open: boolean;
@@ -37,9 +60,39 @@ export function MergeDialog(props: MergeDialogProps) {
   );
   const [mergeStatus, { refetch: refetchMergeStatus }] = createResource(
     () => (props.open ? props.task.worktreePath : null),
-    (path) => invoke<MergeStatus>(IPC.CheckMergeStatus, { worktreePath: path }),
+    (path) =>
+      invoke<MergeStatus>(IPC.CheckMergeStatus, { worktreePath: path, scope: props.task.scope }),
   );
 
+  const checks = () => getProject(props.task.projectId)?.checks ?? [];
+  const blockers = () => getBlockingTasks(props.task.id);
+  const [checkResults, setCheckResults] = createSignal<TaskCheckResults | null>(null);
+  const checkStatus = (index: number, command: string): CheckStatus => {
+    const result = checkResults()?.results[index];
+    return result?.command === command ? result.status : 'pending';
+  };
+
+  createEffect(() => {
+    if (!props.open || checks().length === 0) return;
+    const taskId = props.task.id;
+    const refresh = () =>
+      getCheckResults(taskId)
+        .then(setCheckResults)
+        .catch(() => setCheckResults(null));
+    void refresh();
+    const off = window.electron.ipcRenderer.on(IPC.CheckStatusChanged, (data: unknown) => {
+      if ((data as CheckStatusEvent).taskId === taskId) void refresh();
+    });
+    onCleanup(off);
+  });
+
+  function runChecks() {
+    setMergeError('');
+    runTaskChecks(props.task.id)
+      .then((result) => result && setCheckResults(result))
+      .catch((err) => setMergeError(String(err)));
+  }
+
   const hasConflicts = () => (mergeStatus()?.conflicting_files.length ?? 0) > 0;
   const hasCommittedChangesToMerge = () => worktreeStatus()?.has_committed_changes ?? false;
 
@@ -70,6 +123,23 @@ export function MergeDialog(props: MergeDialogProps) {
       autoFocusCancel
       message={
         <div>
+          <Show when={blockers().length > 0}>
+            <div
+              style={{
+                'margin-bottom': '12px',
+                'font-size': '12px',
+                color: theme.error,
+                background: `color-mix(in srgb, ${theme.error} 8%, transparent)`,
+                padding: '8px 12px',
+                'border-radius': '8px',
+                border: `1px solid color-mix(in srgb, ${theme.error} 20%, transparent)`,
+                'font-weight': '600',
+              }}
+            >
+              Blocked: this task depends on {blockers().map((t) => t.name).join(', ')}, which must
+              merge first.
+            </div>
+          </Show>
           <Show when={worktreeStatus()?.has_uncommitted_changes}>
             <div
               style={{
@@ -244,6 +314,58 @@ export function MergeDialog(props: MergeDialogProps) {
               </Show>
             )}
           </Show>
+          <Show when={checks().length > 0}>
+            <div
+              style={{
+                'margin-bottom': '12px',
+                'font-size': '12px',
+                padding: '8px 12px',
+                'border-radius': '8px',
+                background: theme.bgInput,
+                border: `1px solid ${theme.border}`,
+              }}
+            >
+              <div
+                style={{
+                  display: 'flex',
+                  'align-items': 'center',
+                  'justify-content': 'space-between',
+                  'margin-bottom': '6px',
+                }}
+              >
+                <span style={{ 'font-weight': '600', color: theme.fg }}>
+                  Pre-merge checks
+                  {getProject(props.task.projectId)?.requireChecksBeforeMerge ? ' (required)' : ''}
+                </span>
+                <button
+                  type="button"
+                  disabled={props.task.testsRunning}
+                  onClick={runChecks}
+                  style={{
+                    padding: '4px 10px',
+                    background: theme.bgElevated,
+                    border: `1px solid ${theme.border}`,
+                    'border-radius': '6px',
+                    color: theme.fg,
+                    cursor: props.task.testsRunning ? 'not-allowed' : 'pointer',
+                    'font-size': '12px',
+                  }}
+                >
+                  {props.task.testsRunning ? 'Running...' : 'Run checks'}
+                </button>
+              </div>
+              <For each={checks()}>
+                {(command, i) => (
+                  <div style={{ display: 'flex', gap: '8px', 'font-family': 'monospace' }}>
+                    <span style={{ color: CHECK_STATUS_COLORS[checkStatus(i(), command)] }}>
+                      {checkStatus(i(), command)}
+                    </span>
+                    <span style={{ color: theme.fgMuted }}>{command}</span>
+                  </div>
+                )}
+              </For>
+            </div>
+          </Show>
           <p style={{ margin: '0 0 12px' }}>
             Merge <strong>{props.task.branchName}</strong> into main:
           </p>
@@ -381,12 +503,17 @@ export function MergeDialog(props: MergeDialogProps) {
                 const checked = e.currentTarget.checked;
                 setSquash(checked);
                 if (checked && !squashMessage()) {
-                  const log = branchLog() ?? '';
-                  const msgOnly = log
-                    .split('\n')
-                    .map((l) => l.replace(/^- [a-f0-9]+ /, '- '))
-                    .join('\n');
-                  setSquashMessage(msgOnly);
+                  const agentId = props.task.agentIds[0];
+                  const agentDef = agentId ? store.agents[agentId]?.def : props.task.savedAgentDef;
+                  setSquashMessage(
+                    buildSquashMessage({
+                      taskName: props.task.name,
+                      branchName: props.task.branchName,
+                      agentId: agentDef?.id,
+                      agentName: agentDef?.name,
+                      branchLog: branchLog(),
+                    }),
+                  );
                 }
               }}
               style={{ cursor: 'pointer' }}
@@ -432,7 +559,9 @@ export function MergeDialog(props: MergeDialogProps) {
           </Show>
         </div>
       }
-      confirmDisabled={merging() || hasConflicts() || !hasCommittedChangesToMerge()}
+      confirmDisabled={
+        merging() || hasConflicts() || !hasCommittedChangesToMerge() || blockers().length > 0
+      }
       confirmLoading={merging()}
       confirmLabel={merging() ? 'Merging...' : squash() ? 'Squash Merge' : 'Merge'}
       onConfirm={() => {
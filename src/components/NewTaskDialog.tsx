@@ -45,6 +45,7 @@ export function NewTaskDialog(props: NewTaskDialogProps) {
   const [directMode, setDirectMode] = createSignal(false);
   const [skipPermissions, setSkipPermissions] = createSignal(false);
   const [branchPrefix, setBranchPrefix] = createSignal('');
+  const [scope, setScope] = createSignal('');
   let promptRef!: HTMLTextAreaElement;
   let formRef!: HTMLFormElement;
 
@@ -107,6 +108,7 @@ export function NewTaskDialog(props: NewTaskDialogProps) {
     setLoading(false);
     setDirectMode(false);
     setSkipPermissions(false);
+    setScope('');
 
     void (async () => {
       if (store.availableAgents.length === 0) {
@@ -134,6 +136,16 @@ export function NewTaskDialog(props: NewTaskDialogProps) {
         if (prefill.projectId) setSelectedProjectId(prefill.projectId);
       }
 
+      // Pre-fill from a parallel-code:// link
+      const link = store.newTaskDeepLink;
+      if (link) {
+        if (link.name) setName(link.name);
+        if (link.prompt) setPrompt(link.prompt);
+        if (link.projectId) setSelectedProjectId(link.projectId);
+        const agent = store.availableAgents.find((a) => a.id === link.agentId);
+        if (agent) setSelectedAgent(agent);
+      }
+
       promptRef?.focus();
     })();
 
@@ -314,6 +326,7 @@ export function NewTaskDialog(props: NewTaskDialogProps) {
           branchPrefixOverride: prefix,
           githubUrl: ghUrl,
           skipPermissions: agentSupportsSkipPermissions() && skipPermissions(),
+          scope: scope().trim() || undefined,
         });
       }
       // Drop flow: prefill prompt without auto-sending
@@ -509,6 +522,32 @@ export function NewTaskDialog(props: NewTaskDialogProps) {
             projectPath={selectedProjectPath()}
             onPrefixChange={setBranchPrefix}
           />
+          <div
+            data-nav-field="scope"
+            style={{ display: 'flex', 'align-items': 'center', gap: '6px' }}
+          >
+            <label style={{ 'font-size': '11px', color: theme.fgSubtle, 'white-space': 'nowrap' }}>
+              {t('Scope', '范围')}
+            </label>
+            <input
+              class="input-field"
+              type="text"
+              value={scope()}
+              onInput={(e) => setScope(e.currentTarget.value)}
+              placeholder={t('whole repo, or e.g. packages/api', '整个仓库，或如 packages/api')}
+              style={{
+                background: theme.bgInput,
+                border: `1px solid ${theme.border}`,
+                'border-radius': '6px',
+                padding: '4px 8px',
+                color: theme.fg,
+                'font-size': '12px',
+                'font-family': "'JetBrains Mono', monospace",
+                outline: 'none',
+                flex: '1',
+              }}
+            />
+          </div>
         </Show>
 
         <AgentSelector
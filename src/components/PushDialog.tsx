@@ -1,5 +1,5 @@
 import { Show, createSignal } from 'solid-js';
-import { pushTask } from '../store/store';
+import { pushTask, createPullRequest } from '../store/store';
 import { ConfirmDialog } from './ConfirmDialog';
 import { theme } from '../lib/theme';
 import type { Task } from '../store/types';
@@ -14,6 +14,7 @@ interface PushDialogProps {
 export function PushDialog(props: PushDialogProps) {
   const [pushError, setPushError] = createSignal('');
   const [pushing, setPushing] = createSignal(false);
+  const [openPr, setOpenPr] = createSignal(false);
 
   return (
     <ConfirmDialog
@@ -24,6 +25,24 @@ export function PushDialog(props: PushDialogProps) {
           <p style={{ margin: '0 0 8px' }}>
             Push branch <strong>{props.task.branchName}</strong> to remote?
           </p>
+          <Show when={!props.task.pr}>
+            <label
+              style={{
+                display: 'flex',
+                'align-items': 'center',
+                gap: '6px',
+                'font-size': '12px',
+                color: theme.fgMuted,
+              }}
+            >
+              <input
+                type="checkbox"
+                checked={openPr()}
+                onChange={(e) => setOpenPr(e.currentTarget.checked)}
+              />
+              Open a pull request
+            </label>
+          </Show>
           <Show when={pushError()}>
             <div
               style={{
@@ -41,7 +60,7 @@ export function PushDialog(props: PushDialogProps) {
           </Show>
         </div>
       }
-      confirmLabel={pushing() ? 'Pushing...' : 'Push'}
+      confirmLabel={pushing() ? 'Pushing...' : openPr() ? 'Push & Open PR' : 'Push'}
       onConfirm={() => {
         const taskId = props.task.id;
         const onStart = props.onStart;
@@ -49,7 +68,7 @@ export function PushDialog(props: PushDialogProps) {
         setPushError('');
         setPushing(true);
         onStart();
-        void pushTask(taskId)
+        void (openPr() ? createPullRequest(taskId) : pushTask(taskId))
           .then(() => {
             onDone(true);
           })
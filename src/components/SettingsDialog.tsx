@@ -13,6 +13,9 @@ import {
   setShowPlans,
   setInactiveColumnOpacity,
   setEditorCommand,
+  exportAppData,
+  importAppData,
+  showNotification,
 } from '../store/store';
 import { CustomAgentEditor } from './CustomAgentEditor';
 import { CustomCommandEditor } from './CustomCommandEditor';
@@ -72,6 +75,18 @@ export function SettingsDialog(props: SettingsDialogProps) {
     return [store.terminalFont, ...available];
   });
 
+  const failed = (english: string, chinese: string) => (err: unknown) => {
+    const msg = err instanceof Error ? err.message : String(err);
+    showNotification(t(`${english}: ${msg}`, `${chinese}：${msg}`));
+  };
+
+  const backUp = () =>
+    exportAppData()
+      .then((file) => file && showNotification(t(`Backed up to ${file}`, `已备份到 ${file}`)))
+      .catch(failed('Backup failed', '备份失败'));
+
+  const restore = () => importAppData().catch(failed('Restore failed', '恢复失败'));
+
   return (
     <Dialog
       open={props.open}
@@ -339,7 +354,8 @@ export function SettingsDialog(props: SettingsDialogProps) {
             />
           </label>
           <span style={{ 'font-size': '11px', color: theme.fgSubtle }}>
-            CLI command to open worktree folders. Click the path bar in a task to open it.
+            Editor preset (vscode, cursor, zed, jetbrains) or CLI command for opening worktrees
+            and files. Click the path bar in a task to open it.
           </span>
         </div>
       </div>
@@ -396,7 +412,8 @@ export function SettingsDialog(props: SettingsDialogProps) {
             />
           </label>
           <span style={{ 'font-size': '11px', color: theme.fgSubtle }}>
-            CLI command to open worktree folders. Click the path bar in a task to open it.
+            Editor preset (vscode, cursor, zed, jetbrains) or CLI command for opening worktrees
+            and files. Click the path bar in a task to open it.
           </span>
         </div>
       </div>
@@ -543,6 +560,40 @@ export function SettingsDialog(props: SettingsDialogProps) {
           </span>
         </Show>
       </div>
+
+      <div style={{ display: 'flex', 'flex-direction': 'column', gap: '10px' }}>
+        <div
+          style={{
+            'font-size': '11px',
+            color: theme.fgMuted,
+            'text-transform': 'uppercase',
+            'letter-spacing': '0.05em',
+            'font-weight': '600',
+          }}
+        >
+          {t('Data', '数据')}
+        </div>
+        <div class="settings-theme-grid">
+          <button type="button" class="settings-theme-card" onClick={backUp}>
+            <span class="settings-theme-title">{t('Back up…', '备份…')}</span>
+            <span class="settings-theme-desc">
+              {t(
+                'Tasks, settings and history in one archive',
+                '将任务、设置和历史打包为一个归档',
+              )}
+            </span>
+          </button>
+          <button type="button" class="settings-theme-card" onClick={restore}>
+            <span class="settings-theme-title">{t('Restore…', '恢复…')}</span>
+            <span class="settings-theme-desc">
+              {t(
+                'Replace all data with a backup and restart',
+                '用备份替换全部数据并重启',
+              )}
+            </span>
+          </button>
+        </div>
+      </div>
     </Dialog>
   );
 }
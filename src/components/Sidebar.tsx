@@ -2,6 +2,7 @@ import { createSignal, createEffect, createMemo, onMount, onCleanup, For, Show }
 import {
   store,
   pickAndAddProject,
+  openProject,
   removeProject,
   removeProjectWithTasks,
   toggleNewTaskDialog,
@@ -9,6 +10,7 @@ import {
   toggleSidebar,
   reorderTask,
   getTaskDotStatus,
+  getTaskProgressLabel,
   registerFocusFn,
   unregisterFocusFn,
   focusSidebar,
@@ -19,9 +21,11 @@ import {
   setPanelSizes,
   toggleSettingsDialog,
   uncollapseTask,
+  restoreTask,
+  showNotification,
   isProjectMissing,
 } from '../store/store';
-import type { Project } from '../store/types';
+import type { Project, Task } from '../store/types';
 import { ConnectPhoneModal } from './ConnectPhoneModal';
 import { ConfirmDialog } from './ConfirmDialog';
 import { EditProjectDialog } from './EditProjectDialog';
@@ -42,6 +46,7 @@ export function Sidebar() {
   const [confirmRemove, setConfirmRemove] = createSignal<string | null>(null);
   const [editingProject, setEditingProject] = createSignal<Project | null>(null);
   const [showConnectPhone, setShowConnectPhone] = createSignal(false);
+  const [remoteAddress, setRemoteAddress] = createSignal<string | null>(null);
   const [dragFromIndex, setDragFromIndex] = createSignal<number | null>(null);
   const [dropTargetIndex, setDropTargetIndex] = createSignal<number | null>(null);
   const [resizing, setResizing] = createSignal(false);
@@ -162,6 +167,16 @@ export function Sidebar() {
     });
   });
 
+  function reopenCollapsedTask(taskId: string) {
+    if (!store.tasks[taskId]?.archived) {
+      uncollapseTask(taskId);
+      return;
+    }
+    restoreTask(taskId).catch((err: unknown) =>
+      showNotification(`Restore failed: ${err instanceof Error ? err.message : String(err)}`),
+    );
+  }
+
   async function handleAddProject() {
     await pickAndAddProject();
   }
@@ -351,16 +366,28 @@ export function Sidebar() {
             >
               Projects
             </label>
-            <IconButton
-              icon={
-                <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
-                  <path d="M7.75 2a.75.75 0 0 1 .75.75V7h4.25a.75.75 0 0 1 0 1.5H8.5v4.25a.75.75 0 0 1-1.5 0V8.5H2.75a.75.75 0 0 1 0-1.5H7V2.75A.75.75 0 0 1 7.75 2Z" />
-                </svg>
-              }
-              onClick={() => handleAddProject()}
-              title="Add project"
-              size="sm"
-            />
+            <div style={{ display: 'flex', gap: '2px' }}>
+              <IconButton
+                icon={
+                  <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
+                    <path d="M7.75 2a.75.75 0 0 1 .75.75V7h4.25a.75.75 0 0 1 0 1.5H8.5v4.25a.75.75 0 0 1-1.5 0V8.5H2.75a.75.75 0 0 1 0-1.5H7V2.75A.75.75 0 0 1 7.75 2Z" />
+                  </svg>
+                }
+                onClick={() => handleAddProject()}
+                title="Add project"
+                size="sm"
+              />
+              <IconButton
+                icon={
+                  <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
+                    <path d="M0 2.75C0 1.784.784 1 1.75 1h12.5c.966 0 1.75.784 1.75 1.75v10.5A1.75 1.75 0 0 1 14.25 15H1.75A1.75 1.75 0 0 1 0 13.25Zm1.75-.25a.25.25 0 0 0-.25.25v10.5c0 .138.112.25.25.25h12.5a.25.25 0 0 0 .25-.25V2.75a.25.25 0 0 0-.25-.25ZM7.25 8a.749.749 0 0 1-.22.53l-2.25 2.25a.749.749 0 0 1-1.275-.326.749.749 0 0 1 .215-.734L5.44 8 3.72 6.28a.749.749 0 0 1 .326-1.275.749.749 0 0 1 .734.215l2.25 2.25c.141.14.22.331.22.53Zm1.5 1.5h3a.75.75 0 0 1 0 1.5h-3a.75.75 0 0 1 0-1.5Z" />
+                  </svg>
+                }
+                onClick={() => setRemoteAddress('ssh://')}
+                title="Add remote project (SSH)"
+                size="sm"
+              />
+            </div>
           </div>
 
           <For each={store.projects}>
@@ -647,14 +674,18 @@ export function Sidebar() {
                         class="task-item task-item-appearing"
                         role="button"
                         tabIndex={0}
-                        onClick={() => uncollapseTask(taskId)}
+                        onClick={() => reopenCollapsedTask(taskId)}
                         onKeyDown={(e) => {
                           if (e.key === 'Enter' || e.key === ' ') {
                             e.preventDefault();
-                            uncollapseTask(taskId);
+                            reopenCollapsedTask(taskId);
                           }
                         }}
-                        title="Click to restore"
+                        title={
+                          t().archived
+                            ? 'Archived — click to recreate worktree'
+                            : 'Click to restore'
+                        }
                         style={{
                           padding: '7px 10px',
                           'border-radius': '6px',
@@ -754,6 +785,41 @@ export function Sidebar() {
         {/* Edit project dialog */}
         <EditProjectDialog project={editingProject()} onClose={() => setEditingProject(null)} />
 
+        {/* Remote project address dialog */}
+        <ConfirmDialog
+          open={remoteAddress() !== null}
+          title="Add remote project"
+          message={
+            <input
+              class="input-field"
+              type="text"
+              value={remoteAddress() ?? ''}
+              placeholder="ssh://user@host/path/to/repo"
+              onInput={(e) => setRemoteAddress(e.currentTarget.value)}
+              style={{
+                width: '100%',
+                'box-sizing': 'border-box',
+                background: theme.bgInput,
+                border: `1px solid ${theme.border}`,
+                'border-radius': '8px',
+                padding: '10px 14px',
+                color: theme.fg,
+                'font-size': '13px',
+                outline: 'none',
+              }}
+            />
+          }
+          confirmLabel="Add"
+          confirmDisabled={!remoteAddress()?.trim().startsWith('ssh://')}
+          autoFocusCancel={false}
+          onConfirm={() => {
+            const address = remoteAddress()?.trim();
+            setRemoteAddress(null);
+            if (address) void openProject(address);
+          }}
+          onCancel={() => setRemoteAddress(null)}
+        />
+
         {/* Confirm remove project dialog */}
         <ConfirmDialog
           open={confirmRemove() !== null}
@@ -802,6 +868,7 @@ function TaskRow(props: TaskRowProps) {
           <div
             class={`task-item${t().closingStatus === 'removing' ? ' task-item-removing' : ' task-item-appearing'}`}
             data-task-index={idx()}
+            title={getTaskProgressLabel(props.taskId)}
             onClick={() => {
               setActiveTask(props.taskId);
               focusSidebar();
@@ -845,9 +912,38 @@ function TaskRow(props: TaskRowProps) {
               </span>
             </Show>
             <span style={{ overflow: 'hidden', 'text-overflow': 'ellipsis' }}>{t().name}</span>
+            <TestRunBadge task={t()} />
           </div>
         </>
       )}
     </Show>
   );
 }
+
+/** Pass/fail mark for the task's latest test run. */
+function TestRunBadge(props: { task: Task }) {
+  const run = () => props.task.lastTestRun;
+  const passed = () => run()?.exit_code === 0;
+  const title = () => {
+    const r = run();
+    if (props.task.testsRunning) return 'Tests running…';
+    if (!r) return '';
+    const counts = r.passed !== null ? ` (${r.passed} passed, ${r.failed ?? 0} failed)` : '';
+    return `${r.command}: ${passed() ? 'passed' : 'failed'}${counts}`;
+  };
+  return (
+    <Show when={props.task.testsRunning || run()}>
+      <span
+        title={title()}
+        style={{
+          'margin-left': 'auto',
+          'flex-shrink': '0',
+          'font-size': sf(10),
+          color: props.task.testsRunning ? theme.fgSubtle : passed() ? theme.success : theme.error,
+        }}
+      >
+        {props.task.testsRunning ? '…' : passed() ? '✓' : '✗'}
+      </span>
+    </Show>
+  );
+}
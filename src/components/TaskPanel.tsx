@@ -4,9 +4,11 @@ import { revealItemInDir, openInEditor } from '../lib/shell';
 import {
   store,
   retryCloseTask,
+  keepTask,
   setActiveTask,
   markAgentExited,
   restartAgent,
+  resumeAgentSession,
   switchAgent,
   updateTaskName,
   updateTaskNotes,
@@ -28,6 +30,16 @@ import {
   clearPendingAction,
   showNotification,
   collapseTask,
+  archiveTask,
+  runTaskTests,
+  cancelTaskTests,
+  getTaskSpawnEnv,
+  getTaskAgentProfile,
+  getProjectAgentConfig,
+  rebaseTasks,
+  dismissDependencyRebase,
+  getAgentApproval,
+  respondToAgent,
 } from '../store/store';
 import { ResizablePanel, type PanelChild } from './ResizablePanel';
 import { EditableText, type EditableTextHandle } from './EditableText';
@@ -49,6 +61,7 @@ import { sf } from '../lib/fontScale';
 import { mod, isMac } from '../lib/platform';
 import { extractLabel, consumePendingShellCommand } from '../lib/bookmarks';
 import { handleDragReorder } from '../lib/dragReorder';
+import { taskCwd } from '../lib/task-scope';
 import { marked } from 'marked';
 import type { Task } from '../store/types';
 import type { ChangedFile } from '../ipc/types';
@@ -312,6 +325,34 @@ export function TaskPanel(props: TaskPanelProps) {
                 </Show>
               </div>
             </Show>
+            <Show when={getProject(props.task.projectId)?.testCommand}>
+              <IconButton
+                icon={
+                  <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
+                    <Show
+                      when={props.task.testsRunning}
+                      fallback={
+                        <path d="M4 2.75v10.5a.75.75 0 0 0 1.14.64l8.5-5.25a.75.75 0 0 0 0-1.28l-8.5-5.25A.75.75 0 0 0 4 2.75Z" />
+                      }
+                    >
+                      <path d="M4.5 3h7A1.5 1.5 0 0 1 13 4.5v7a1.5 1.5 0 0 1-1.5 1.5h-7A1.5 1.5 0 0 1 3 11.5v-7A1.5 1.5 0 0 1 4.5 3Z" />
+                    </Show>
+                  </svg>
+                }
+                onClick={() => {
+                  if (props.task.testsRunning) {
+                    cancelTaskTests(props.task.id).catch(() => {});
+                    return;
+                  }
+                  runTaskTests(props.task.id).catch((err: unknown) =>
+                    showNotification(
+                      `Tests failed to run: ${err instanceof Error ? err.message : String(err)}`,
+                    ),
+                  );
+                }}
+                title={props.task.testsRunning ? 'Stop tests' : 'Run tests'}
+              />
+            </Show>
             <IconButton
               icon={
                 <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
@@ -321,6 +362,23 @@ export function TaskPanel(props: TaskPanelProps) {
               onClick={() => collapseTask(props.task.id)}
               title="Collapse task"
             />
+            <Show when={!props.task.directMode}>
+              <IconButton
+                icon={
+                  <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
+                    <path d="M1.75 2h12.5c.966 0 1.75.784 1.75 1.75v1.5A1.75 1.75 0 0 1 15 6.75v6.5A1.75 1.75 0 0 1 13.25 15H2.75A1.75 1.75 0 0 1 1 13.25v-6.5A1.75 1.75 0 0 1 0 5.25v-1.5C0 2.784.784 2 1.75 2ZM2.5 7v6.25c0 .138.112.25.25.25h10.5a.25.25 0 0 0 .25-.25V7Zm-.75-3.5a.25.25 0 0 0-.25.25v1.5c0 .138.112.25.25.25h12.5a.25.25 0 0 0 .25-.25v-1.5a.25.25 0 0 0-.25-.25ZM6 9.25a.75.75 0 0 1 .75-.75h2.5a.75.75 0 0 1 0 1.5h-2.5A.75.75 0 0 1 6 9.25Z" />
+                  </svg>
+                }
+                onClick={() =>
+                  archiveTask(props.task.id).catch((err: unknown) =>
+                    showNotification(
+                      `Archive failed: ${err instanceof Error ? err.message : String(err)}`,
+                    ),
+                  )
+                }
+                title="Archive task (remove worktree, keep branch)"
+              />
+            </Show>
             <IconButton
               icon={
                 <svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor">
@@ -656,6 +714,7 @@ export function TaskPanel(props: TaskPanelProps) {
                     <div style={{ flex: '1', overflow: 'hidden' }}>
                       <ChangedFilesList
                         worktreePath={props.task.worktreePath}
+                        scope={props.task.scope}
                         isActive={props.isActive}
                         onFileClick={setDiffFile}
                         ref={(el) => (changedFilesRef = el)}
@@ -874,8 +933,10 @@ export function TaskPanel(props: TaskPanelProps) {
                             props.isActive && store.focusedPanel[props.task.id] === `shell:${i()}`
                           }
                           command={getShellCommand()}
-                          args={['-l']}
-                          cwd={props.task.worktreePath}
+                          args={[]}
+                          cwd={taskCwd(props.task)}
+                          env={getTaskSpawnEnv(props.task)}
+                          secrets={props.task.secrets}
                           initialCommand={initialCommand}
                           onData={(data) => markAgentOutput(shellId, data, props.task.id)}
                           onExit={(info) =>
@@ -1110,7 +1171,9 @@ export function TaskPanel(props: TaskPanelProps) {
                           <button
                             onClick={(e) => {
                               e.stopPropagation();
-                              restartAgent(a().id, true);
+                              resumeAgentSession(props.task.id, a().id).catch((err) =>
+                                showNotification(String(err)),
+                              );
                             }}
                             style={{
                               background: theme.bgElevated,
@@ -1127,6 +1190,148 @@ export function TaskPanel(props: TaskPanelProps) {
                         </Show>
                       </div>
                     </Show>
+                    <Show when={props.task.dependencyMerged}>
+                      {(depName) => (
+                        <div
+                          style={{
+                            position: 'absolute',
+                            top: '8px',
+                            left: '12px',
+                            'z-index': '10',
+                            display: 'flex',
+                            'align-items': 'center',
+                            gap: '8px',
+                            'font-size': sf(11),
+                            color: theme.fg,
+                            background: 'color-mix(in srgb, var(--island-bg) 80%, transparent)',
+                            padding: '4px 12px',
+                            'border-radius': '8px',
+                            border: `1px solid ${theme.accent}`,
+                          }}
+                        >
+                          {depName()} merged. Rebase onto main?
+                          <button
+                            onClick={(e) => {
+                              e.stopPropagation();
+                              const taskId = props.task.id;
+                              rebaseTasks([taskId])
+                                .then(([result]) => {
+                                  if (result.ok) dismissDependencyRebase(taskId);
+                                  else showNotification(`Rebase failed: ${result.error ?? ''}`);
+                                })
+                                .catch((err) => showNotification(String(err)));
+                            }}
+                            style={{
+                              background: theme.bgElevated,
+                              border: `1px solid ${theme.border}`,
+                              color: theme.fg,
+                              padding: '2px 8px',
+                              'border-radius': '4px',
+                              cursor: 'pointer',
+                              'font-size': sf(10),
+                            }}
+                          >
+                            Rebase
+                          </button>
+                          <button
+                            title="Dismiss"
+                            onClick={(e) => {
+                              e.stopPropagation();
+                              dismissDependencyRebase(props.task.id);
+                            }}
+                            style={{
+                              background: 'transparent',
+                              border: 'none',
+                              color: theme.fgMuted,
+                              cursor: 'pointer',
+                              'font-size': sf(10),
+                            }}
+                          >
+                            Dismiss
+                          </button>
+                        </div>
+                      )}
+                    </Show>
+                    <Show
+                      when={a().status !== 'exited' && !getAgentApproval(a().id) && a().stall}
+                    >
+                      {(stall) => (
+                        <div
+                          class="stall-badge"
+                          style={{
+                            position: 'absolute',
+                            top: '8px',
+                            right: '12px',
+                            'z-index': '10',
+                            'font-size': sf(11),
+                            color: stall() === 'hung' ? theme.error : theme.fgMuted,
+                            background: 'color-mix(in srgb, var(--island-bg) 80%, transparent)',
+                            padding: '4px 12px',
+                            'border-radius': '8px',
+                            border: `1px solid ${stall() === 'hung' ? theme.error : theme.border}`,
+                          }}
+                        >
+                          {stall() === 'hung' ? 'No output for a long time — may be hung' : 'Idle'}
+                        </div>
+                      )}
+                    </Show>
+                    <Show when={a().status !== 'exited' && getAgentApproval(a().id)}>
+                      {(prompt) => (
+                        <div
+                          class="approval-badge"
+                          title={prompt()}
+                          style={{
+                            position: 'absolute',
+                            top: '8px',
+                            right: '12px',
+                            'z-index': '10',
+                            'max-width': '60%',
+                            'font-size': sf(11),
+                            color: theme.fg,
+                            background: 'color-mix(in srgb, var(--island-bg) 80%, transparent)',
+                            padding: '4px 12px',
+                            'border-radius': '8px',
+                            border: `1px solid ${theme.warning}`,
+                            display: 'flex',
+                            'align-items': 'center',
+                            gap: '8px',
+                          }}
+                        >
+                          <span
+                            style={{
+                              overflow: 'hidden',
+                              'text-overflow': 'ellipsis',
+                              'white-space': 'nowrap',
+                            }}
+                          >
+                            {prompt().split('\n').pop()}
+                          </span>
+                          <For each={[true, false]}>
+                            {(approve) => (
+                              <button
+                                onClick={(e) => {
+                                  e.stopPropagation();
+                                  respondToAgent(a().id, approve).catch((err) =>
+                                    showNotification(String(err)),
+                                  );
+                                }}
+                                style={{
+                                  background: theme.bgElevated,
+                                  border: `1px solid ${theme.border}`,
+                                  color: approve ? theme.success : theme.error,
+                                  padding: '2px 8px',
+                                  'border-radius': '4px',
+                                  cursor: 'pointer',
+                                  'font-size': sf(10),
+                                }}
+                              >
+                                {approve ? 'Approve' : 'Deny'}
+                              </button>
+                            )}
+                          </For>
+                        </div>
+                      )}
+                    </Show>
                     <Show when={`${a().id}:${a().generation}`} keyed>
                       <TerminalView
                         taskId={props.task.id}
@@ -1136,14 +1341,21 @@ export function TaskPanel(props: TaskPanelProps) {
                         }
                         command={a().def.command}
                         args={[
-                          ...(a().resumed && a().def.resume_args?.length
-                            ? (a().def.resume_args ?? [])
+                          ...(a().resumed && (a().resumeArgs ?? a().def.resume_args)?.length
+                            ? (a().resumeArgs ?? a().def.resume_args ?? [])
                             : a().def.args),
                           ...(props.task.skipPermissions && a().def.skip_permissions_args?.length
                             ? (a().def.skip_permissions_args ?? [])
                             : []),
+                          ...(getProjectAgentConfig(props.task.projectId, a().def)?.args ?? []),
+                          ...(getTaskAgentProfile(props.task, a().def)?.args ?? []),
                         ]}
-                        cwd={props.task.worktreePath}
+                        cwd={taskCwd(props.task)}
+                        env={getTaskSpawnEnv(props.task, a().def)}
+                        secrets={props.task.secrets}
+                        restartPolicy={a().def.restart_policy}
+                        agentDefId={a().def.id}
+                        usageParser={a().def.usage_parser}
                         onExit={(code) => markAgentExited(a().id, code)}
                         onData={(data) => markAgentOutput(a().id, data, props.task.id)}
                         onPromptDetected={(text) => setLastPrompt(props.task.id, text)}
@@ -1194,6 +1406,16 @@ export function TaskPanel(props: TaskPanelProps) {
     };
   }
 
+  const closeButtonStyle = {
+    background: theme.bgElevated,
+    border: `1px solid ${theme.border}`,
+    color: theme.fg,
+    padding: '6px 16px',
+    'border-radius': '6px',
+    cursor: 'pointer',
+    'font-size': '12px',
+  };
+
   return (
     <div
       ref={panelRef}
@@ -1244,23 +1466,41 @@ export function TaskPanel(props: TaskPanelProps) {
             >
               {props.task.closingError}
             </div>
-            <button
-              onClick={(e) => {
-                e.stopPropagation();
-                retryCloseTask(props.task.id);
-              }}
-              style={{
-                background: theme.bgElevated,
-                border: `1px solid ${theme.border}`,
-                color: theme.fg,
-                padding: '6px 16px',
-                'border-radius': '6px',
-                cursor: 'pointer',
-                'font-size': '12px',
-              }}
+            <Show
+              when={props.task.closingRisk}
+              fallback={
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    retryCloseTask(props.task.id);
+                  }}
+                  style={closeButtonStyle}
+                >
+                  Retry
+                </button>
+              }
             >
-              Retry
-            </button>
+              <div style={{ display: 'flex', gap: '8px' }}>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    keepTask(props.task.id);
+                  }}
+                  style={closeButtonStyle}
+                >
+                  Keep task
+                </button>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    retryCloseTask(props.task.id, { force: true });
+                  }}
+                  style={{ ...closeButtonStyle, color: theme.error }}
+                >
+                  Delete anyway
+                </button>
+              </div>
+            </Show>
           </Show>
         </div>
       </Show>
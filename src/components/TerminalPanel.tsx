@@ -147,7 +147,7 @@ export function TerminalPanel(props: TerminalPanelProps) {
             isShell
             isFocused={props.isActive && store.focusedPanel[props.terminal.id] === 'terminal'}
             command=""
-            args={['-l']}
+            args={[]}
             cwd=""
             onReady={(focusFn) => registerFocusFn(`${props.terminal.id}:terminal`, focusFn)}
             fontSize={Math.round(13 * getFontScale(`${props.terminal.id}:terminal`))}
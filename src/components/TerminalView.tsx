@@ -10,9 +10,9 @@ import { getTerminalTheme } from '../lib/theme';
 import { matchesGlobalShortcut } from '../lib/shortcuts';
 import { isMac } from '../lib/platform';
 import { handleTerminalClipboardKeyEvent } from '../lib/terminal-clipboard';
-import { store } from '../store/store';
+import { showNotification, store } from '../store/store';
 import { registerTerminal, unregisterTerminal, markDirty } from '../lib/terminalFitManager';
-import type { PtyOutput } from '../ipc/types';
+import type { AgentDef, ClipboardImage, PtyOutput, RestartPolicy } from '../ipc/types';
 
 // Pre-computed base64 lookup table — avoids atob() intermediate string allocation.
 const B64_LOOKUP = new Uint8Array(128);
@@ -46,6 +46,10 @@ interface TerminalViewProps {
   args: string[];
   cwd: string;
   env?: Record<string, string>;
+  secrets?: string[];
+  restartPolicy?: RestartPolicy;
+  agentDefId?: string;
+  usageParser?: AgentDef['usage_parser'];
   onExit?: (exitInfo: {
     exit_code: number | null;
     signal: string | null;
@@ -123,10 +127,19 @@ export function TerminalView(props: TerminalViewProps) {
         getSelection: () => term?.getSelection() ?? '',
         readClipboardText: () => navigator.clipboard.readText(),
         readClipboardItems: () => navigator.clipboard.read(),
-        saveClipboardImage: (base64Data, mimeType) =>
-          invoke<string>(IPC.SaveClipboardImage, { base64Data, mimeType }),
+        readNativeClipboardImage: async () => {
+          const image = await invoke<ClipboardImage | null>(IPC.ReadClipboardImage);
+          return image && { base64Data: image.base64_data, mimeType: image.mime_type };
+        },
+        pasteClipboardImage: (base64Data, mimeType) =>
+          invoke<string>(IPC.PasteImageToSession, { agentId, base64Data, mimeType }),
         writeClipboardText: (text) => navigator.clipboard.writeText(text),
         enqueueInput,
+        pasteText: (data) => {
+          // Typed input queued before the paste must reach the PTY first
+          flushPendingInput();
+          fireAndForget(IPC.WriteToAgent, { agentId, data, paste: true });
+        },
       }),
     );
 
@@ -360,14 +373,19 @@ export function TerminalView(props: TerminalViewProps) {
 
     invoke(IPC.SpawnAgent, {
       taskId,
+      projectId: store.tasks[taskId]?.projectId,
       agentId,
       command: props.command,
       args: props.args,
       cwd: props.cwd,
       env: props.env ?? {},
+      secrets: props.secrets,
       cols: term.cols,
       rows: term.rows,
       isShell: props.isShell,
+      restartPolicy: props.restartPolicy,
+      agentDefId: props.agentDefId,
+      usageParser: props.usageParser,
       onOutput,
       // eslint-disable-next-line solid/reactivity -- promise catch handler reads current prop values intentionally
     }).catch((err) => {
@@ -419,9 +437,31 @@ export function TerminalView(props: TerminalViewProps) {
     markDirty(props.agentId);
   });
 
+  // Dropped files are attached to the session and their paths typed into it
+  const hasFiles = (e: DragEvent) => e.dataTransfer?.types.includes('Files') ?? false;
+  const handleDrop = (e: DragEvent) => {
+    if (!hasFiles(e)) return;
+    e.preventDefault();
+    e.stopPropagation();
+    const files = Array.from(e.dataTransfer?.files ?? []);
+    const paths = files.map((f) => window.electron.getPathForFile(f)).filter(Boolean);
+    if (paths.length === 0) return;
+    invoke(IPC.AttachFilesToSession, { agentId: props.agentId, paths })
+      .then(() => term?.focus())
+      .catch((err: unknown) =>
+        showNotification(err instanceof Error ? err.message : String(err)),
+      );
+  };
+
   return (
     <div
       ref={containerRef}
+      onDragOver={(e) => {
+        if (!hasFiles(e)) return;
+        e.preventDefault();
+        if (e.dataTransfer) e.dataTransfer.dropEffect = 'copy';
+      }}
+      onDrop={handleDrop}
       style={{
         width: '100%',
         height: '100%',
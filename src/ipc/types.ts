@@ -5,6 +5,19 @@ export type PtyOutput =
       data: { exit_code: number | null; signal: string | null; last_output: string[] };
     };
 
+export interface RestartPolicy {
+  policy: 'never' | 'on-failure' | 'always';
+  max_retries: number;
+}
+
+/** A named bundle of extra arguments and environment for launching an agent. */
+export interface AgentProfile {
+  id: string;
+  name: string;
+  args: string[];
+  env: Record<string, string>;
+}
+
 export interface AgentDef {
   id: string;
   name: string;
@@ -12,14 +25,162 @@ export interface AgentDef {
   args: string[];
   resume_args: string[];
   skip_permissions_args: string[];
+  /** Arguments for a one-shot run; the prompt follows them. Null when unsupported. */
+  headless_args?: string[] | null;
+  /** Built-in launch profiles. */
+  profiles?: AgentProfile[];
   description: string;
+  restart_policy?: RestartPolicy;
+  /** Built-in adapter id whose usage summaries the output is scanned for. */
+  usage_parser?: string;
   available?: boolean;
 }
 
+/** Title and working directory a terminal session last reported (OSC 0/2 and 7). */
+export interface SessionInfo {
+  agent_id: string;
+  task_id: string;
+  command: string;
+  is_shell: boolean;
+  title: string | null;
+  cwd: string;
+  outside_worktree: boolean;
+  last_output_at: number;
+  last_input_at: number;
+  /** Set while the agent has been quiet longer than the idle or hung threshold. */
+  stall: 'idle' | 'hung' | null;
+}
+
+/** An agent went quiet for longer than the `idle_after_minutes`/`hung_after_minutes` setting. */
+export interface AgentStallEvent {
+  agent_id: string;
+  task_id: string;
+  last_activity_at: number;
+}
+
+/** An image read from the system clipboard by the main process. */
+export interface ClipboardImage {
+  base64_data: string;
+  mime_type: string;
+  width: number;
+  height: number;
+  size: number;
+}
+
+/** A PTY session the main process runs, or recently ran. */
+export interface SessionSummary {
+  agent_id: string;
+  task_id: string;
+  project_id: string | null;
+  agent_def_id: string | null;
+  is_shell: boolean;
+  command: string;
+  status: 'running' | 'restarting' | 'exited';
+  pid: number | null;
+  started_at: number;
+  last_output_at: number;
+  last_input_at: number;
+  exited_at: number | null;
+  exit_code: number | null;
+  signal: string | null;
+  buffered_bytes: number;
+  spilled_bytes: number;
+}
+
+/** Agent overrides a project checks in as `.parallel-code.toml`. */
+export interface ProjectAgentConfig {
+  args: string[];
+  env: Record<string, string>;
+  profile: string | null;
+}
+
+export interface ProjectConfig {
+  agents: Record<string, ProjectAgentConfig>;
+}
+
+export interface ProjectConfigFile {
+  config: ProjectConfig;
+  /** sha256 of the file; trusting the config means trusting this content. */
+  hash: string;
+}
+
+/** A session from before the app was force-quit whose process is still running. */
+export interface OrphanedSession {
+  agent_id: string;
+  task_id: string;
+  pid: number;
+  command: string;
+  is_shell: boolean;
+  started_at: number;
+}
+
+/** Something an agent reported doing, read from its output. */
+export interface AgentOutputEvent {
+  kind: 'editing' | 'command' | 'turn_complete' | 'error';
+  /** The file being edited, the command being run, or the error message. */
+  detail?: string;
+  at: number;
+}
+
+/** An `AgentOutputEvent` as the backend pushes it. */
+export interface AgentOutputEventMessage extends AgentOutputEvent {
+  agent_id: string;
+  task_id: string;
+}
+
+/** A permission prompt appeared (`prompt` set) or was answered (`prompt` null). */
+export interface AgentApprovalEvent {
+  agent_id: string;
+  task_id: string;
+  prompt: string | null;
+}
+
+export interface AgentRestartEvent {
+  agentId: string;
+  taskId: string;
+  attempt: number;
+  maxRetries: number;
+  exitCode: number;
+  signal: string | null;
+  delayMs: number;
+  lastOutput: string[];
+}
+
 export interface CreateTaskResult {
   id: string;
   branch_name: string;
   worktree_path: string;
+  /** Set when the worktree was created but its submodules failed to check out. */
+  submodule_error?: string;
+  /** Set when the worktree was created but its Git LFS files couldn't be downloaded. */
+  lfs_error?: string;
+  /** The branch's upstream, e.g. `origin/main`, when it has one. */
+  upstream?: string;
+}
+
+export interface RaceEntry {
+  agent_def_id: string;
+  id: string;
+  branch_name: string;
+  worktree_path: string;
+  submodule_error?: string;
+  lfs_error?: string;
+}
+
+export interface CreateRaceResult {
+  race_id: string;
+  entries: RaceEntry[];
+}
+
+export interface RenameTaskResult {
+  branch_name: string;
+  worktree_path: string;
+}
+
+export interface RestoreTaskResult {
+  worktree_path: string;
+  submodule_error?: string;
+  lfs_error?: string;
 }
 
 export interface TaskInfo {
@@ -42,6 +203,23 @@ export interface ChangedFile {
 export interface WorktreeStatus {
   has_committed_changes: boolean;
   has_uncommitted_changes: boolean;
+  /** Commits ahead of / behind the base branch; set by the background poller. */
+  ahead?: number;
+  behind?: number;
+}
+
+/** What deleting a branch would lose, from an `unmerged_commits` error. */
+export interface BranchDeletionRisk {
+  branch: string;
+  /** The commit the branch points at, to recreate it from. */
+  tip: string;
+  unmerged: number;
+  /** Null when the repo has no remote. */
+  unpushed: number | null;
+}
+
+export interface TaskGitStatus extends Required<WorktreeStatus> {
+  task_id: string;
 }
 
 export interface MergeStatus {
@@ -49,14 +227,573 @@ export interface MergeStatus {
   conflicting_files: string[];
 }
 
+export interface CherryPickResult {
+  applied: string[];
+  conflict: { commit: string; conflicting_files: string[] } | null;
+}
+
+export interface TestRunResult {
+  task_id: string;
+  command: string;
+  exit_code: number | null;
+  signal: string | null;
+  passed: number | null;
+  failed: number | null;
+  started_at: number;
+  finished_at: number;
+  output_tail: string;
+}
+
+export interface TaskTestOutputEvent {
+  taskId: string;
+  chunk: string;
+}
+
+export type CheckStatus = 'pending' | 'running' | 'passed' | 'failed' | 'skipped';
+
+export interface CheckResult {
+  command: string;
+  status: CheckStatus;
+  exit_code: number | null;
+  started_at: number | null;
+  finished_at: number | null;
+  output_tail: string;
+}
+
+export interface TaskCheckResults {
+  task_id: string;
+  head: string;
+  finished: boolean;
+  results: CheckResult[];
+}
+
+export interface CheckStatusEvent {
+  taskId: string;
+  index: number;
+  result: CheckResult;
+}
+
+export interface TaskPorts {
+  task_id: string;
+  base: number;
+  count: number;
+}
+
+export interface RemoteIssue {
+  number: number;
+  title: string;
+  body: string;
+  url: string;
+  state: string;
+  labels: string[];
+}
+
+/** A `parallel-code://task/new` link, as parsed by the backend. */
+export interface DeepLinkAction {
+  action: 'new_task';
+  name?: string;
+  prompt?: string;
+  agent?: string;
+  project?: string;
+}
+
+export interface CreatedPullRequest {
+  number: number;
+  url: string;
+}
+
+export interface TaskPrStatus {
+  task_id: string;
+  number: number;
+  url: string;
+  state: 'OPEN' | 'MERGED' | 'CLOSED';
+  ci: 'none' | 'pending' | 'passing' | 'failing';
+}
+
+export interface ProjectInfo {
+  default_branch: string | null;
+  remote_url: string | null;
+  repo_name: string;
+}
+
+export interface UpdateBaseBranchResult {
+  main_branch: string;
+  updated: boolean;
+}
+
+export interface GitProgress {
+  phase: string;
+  percent: number | null;
+  current: number | null;
+  total: number | null;
+}
+
+export interface GitProgressEvent {
+  opId: string;
+  projectRoot: string;
+  operation: 'fetch' | 'update_base_branch' | 'create_task' | 'create_race' | 'restore_task';
+  line: string;
+  /** Null for lines that aren't progress, e.g. hints. */
+  progress: GitProgress | null;
+}
+
+export interface WorktreeFileChange {
+  path: string;
+  kind: 'created' | 'modified' | 'deleted';
+}
+
+export interface WorktreeFilesChangedEvent {
+  worktreePath: string;
+  changes: WorktreeFileChange[];
+}
+
+export interface StashEntry {
+  index: number;
+  message: string;
+  created_at: number;
+}
+
+export interface ConflictRegion {
+  /** 1-based lines of the `<<<<<<<` and `>>>>>>>` markers. */
+  start_line: number;
+  end_line: number;
+  ours: string;
+  /** Only with `merge.conflictStyle=diff3`. */
+  base: string | null;
+  theirs: string;
+}
+
+export interface ConflictFile {
+  path: string;
+  base: string | null;
+  /** Main while rebasing, the task's branch while merging. */
+  ours: string | null;
+  theirs: string | null;
+  /** The working-tree file with its conflict markers. */
+  working: string | null;
+  regions: ConflictRegion[];
+  binary: boolean;
+}
+
+/** A commit on a task branch that main doesn't have. */
+export interface TaskCommit {
+  sha: string;
+  author_name: string;
+  author_email: string;
+  /** Author date, epoch ms. */
+  date: number;
+  subject: string;
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+}
+
+/** Who last touched one line of a worktree file. */
+export interface BlameLine {
+  line: number;
+  content: string;
+  /** All zeros for uncommitted changes. */
+  sha: string;
+  author: string;
+  date: number;
+  summary: string;
+  /** Written on the task branch rather than inherited from main. */
+  on_branch: boolean;
+}
+
+export interface BranchTracking {
+  /** e.g. `origin/main` */
+  upstream: string;
+  ahead: number;
+  behind: number;
+}
+
+export interface ConflictState {
+  operation: 'merge' | 'rebase' | 'cherry-pick' | null;
+  files: ConflictFile[];
+}
+
+export interface TaskSnapshot {
+  id: string;
+  label: string;
+  /** Commit holding the files; its parent is where the branch was. */
+  commit: string;
+  created_at: number;
+}
+
 export interface MergeResult {
   main_branch: string;
   lines_added: number;
   lines_removed: number;
 }
 
+export interface DiffHunk {
+  id: number;
+  header: string;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: Array<{ type: 'context' | 'add' | 'del'; text: string }>;
+}
+
+export interface StructuredFileDiff {
+  path: string;
+  binary: boolean;
+  additions: number;
+  deletions: number;
+  hunks: DiffHunk[];
+}
+
+/** Part of a file in a streamed diff; a hunk cut off by the previous part goes on under its id. */
+export interface DiffFilePart {
+  path: string;
+  binary: boolean;
+  hunks: DiffHunk[];
+  /** The file's last part, which carries its totals. */
+  done: boolean;
+  additions: number;
+  deletions: number;
+  /** With `done`: the diff line the size cap stopped at, to load more from. */
+  more: number | null;
+}
+
+export interface DiffChunk {
+  handle: string;
+  seq: number;
+  files: DiffFilePart[];
+  /** The stream's last chunk; `error` says why when git failed. */
+  done: boolean;
+  error: string | null;
+}
+
+export interface DiffPage {
+  path: string;
+  hunks: DiffHunk[];
+  more: number | null;
+}
+
 export interface FileDiffResult {
   diff: string;
   oldContent: string;
   newContent: string;
 }
+
+export interface UsageMetrics {
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+export interface SessionUsage extends UsageMetrics {
+  agent_id: string;
+  agent_def_id: string | null;
+  updated_at: number;
+}
+
+export interface TaskUsage {
+  task_id: string;
+  sessions: SessionUsage[];
+  totals: UsageMetrics;
+}
+
+export interface ScrollbackMatch {
+  line: number;
+  lines_from_end: number;
+  column: number;
+  length: number;
+  text: string;
+}
+
+export interface ScrollbackSearchResult {
+  matches: ScrollbackMatch[];
+  total_lines: number;
+  truncated: boolean;
+}
+
+export interface BackendSettings {
+  worktree_dir: string;
+  base_branch: string | null;
+  default_agent_id: string | null;
+  shell: string | null;
+  desktop_notifications: NotificationKind[];
+  editor_templates: Record<string, { folder: string; file: string }>;
+}
+
+export type NotificationKind =
+  | 'agent_finished'
+  | 'agent_waiting'
+  | 'agent_error'
+  | 'merge_conflict'
+  | 'git_operation_done';
+
+export interface LogEntry {
+  ts: number;
+  level: 'debug' | 'info' | 'warn' | 'error';
+  scope: string;
+  message: string;
+  error?: string;
+}
+
+export interface AppNotification {
+  id: string;
+  kind: NotificationKind;
+  title: string;
+  body: string;
+  task_id: string | null;
+  created_at: number;
+  read: boolean;
+}
+
+export interface RecentProject {
+  path: string;
+  name: string;
+  last_opened: number;
+}
+
+export type TaskEventType =
+  | 'created'
+  | 'agent_spawned'
+  | 'agent_exited'
+  | 'merged'
+  | 'pushed'
+  | 'renamed'
+  | 'updated'
+  | 'archived'
+  | 'restored'
+  | 'deleted'
+  | 'exported'
+  | 'imported'
+  | 'handed_off'
+  | 'run_finished'
+  | 'checkpoint'
+  | 'timer_started'
+  | 'timer_paused'
+  | 'error';
+
+export interface TaskEvent {
+  ts: number;
+  type: TaskEventType;
+  data?: Record<string, unknown>;
+}
+
+/** A task event from the history database, with the task's name when known. */
+export interface HistoryEvent extends TaskEvent {
+  task_id: string;
+  task_name: string | null;
+}
+
+export interface TranscriptMatch {
+  task_id: string;
+  task_name: string | null;
+  agent_id: string;
+  line_no: number;
+  text: string;
+}
+
+export interface TokenTotals {
+  input_tokens: number;
+  output_tokens: number;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+export interface AgentActivity extends TokenTotals {
+  agent_def_id: string | null;
+  sessions: number;
+  active_ms: number;
+}
+
+/** How the app was used over a range, from the history database. */
+export interface ActivityMetrics extends TokenTotals {
+  since: number;
+  until: number;
+  tasks_created: number;
+  tasks_merged: number;
+  avg_task_lifetime_ms: number | null;
+  /** `day` is a local `YYYY-MM-DD`; oldest first. */
+  days: Array<{ day: string; created: number; merged: number }>;
+  agents: AgentActivity[];
+}
+
+/** Wall-clock time tracked for a task, next to what its agents cost. */
+export interface TaskTime {
+  task_id: string;
+  /** Overlapping agents and the timer count once. */
+  active_ms: number;
+  agent_ms: number;
+  timer: 'auto' | 'running' | 'paused';
+  first_active_at: number | null;
+  last_active_at: number | null;
+  total_tokens: number;
+  cost_usd: number | null;
+}
+
+export interface ImportTaskResult {
+  id: string;
+  branch_name: string;
+  worktree_path: string;
+  metadata: Record<string, unknown>;
+}
+
+/** A deleted task kept restorable for `trash_retention_days`. */
+export interface TrashEntry {
+  id: string;
+  task_id: string;
+  project_root: string;
+  branch_name: string;
+  tip_sha: string | null;
+  branch_deleted: boolean;
+  metadata: Record<string, unknown>;
+  deleted_at: number;
+}
+
+export interface RestoredTaskResult {
+  entry: TrashEntry;
+  worktree_path: string;
+}
+
+export interface WorktreeDiskUsage {
+  branch_name: string;
+  path: string;
+  bytes: number;
+}
+
+export interface ProjectDiskUsage {
+  project_root: string;
+  worktree_root: string;
+  total_bytes: number;
+  worktrees: WorktreeDiskUsage[];
+  computed_at: number;
+}
+
+export interface DiskUsage {
+  total_bytes: number;
+  projects: ProjectDiskUsage[];
+}
+
+export interface WorkspaceGcReport {
+  project_root: string;
+  dry_run: boolean;
+  /** `registered` is false for directories git has no worktree at; `dirty` ones have changes. */
+  worktrees: Array<{
+    path: string;
+    branch: string | null;
+    registered: boolean;
+    dirty: boolean;
+    bytes: number;
+  }>;
+  prunable: Array<{ path: string; reason: string }>;
+  branches: Array<{ branch: string; tip: string; unmerged: number }>;
+  /** Worktrees a removal left alone: unconfirmed, or dirty without force. */
+  kept_worktrees: Array<{ path: string; reason: string }>;
+  /** Branches a removal left alone: unconfirmed, moved since, or unmerged without force. */
+  kept_branches: Array<{ branch: string; reason: string }>;
+  reclaimed_bytes: number;
+  errors: string[];
+}
+
+export interface SessionResourceUsage {
+  agent_id: string;
+  task_id: string;
+  is_shell: boolean;
+  pid: number;
+  cpu_percent: number | null;
+  rss_bytes: number | null;
+  scrollback: ScrollbackStats;
+}
+
+export interface ScrollbackStats {
+  capacity_bytes: number;
+  buffered_bytes: number;
+  spilled_bytes: number;
+}
+
+export interface ResourceUsage {
+  sessions: SessionResourceUsage[];
+  agent_sessions: number;
+  max_agent_sessions: number;
+  max_agents_per_task: number;
+  scrollback_bytes: number;
+  scrollback_budget_bytes: number;
+}
+
+/** What one agent leaves the next when a task is handed off. */
+export interface HandoffContext {
+  diff: string;
+  diff_truncated: boolean;
+  commits: string;
+  transcript: string;
+}
+
+/** A headless agent run queued in the backend scheduler. */
+export interface ScheduledRun {
+  id: string;
+  task_id: string;
+  worktree_path: string;
+  agent_id: string;
+  prompt: string;
+  env: Record<string, string>;
+  secrets?: string[];
+  start_at: number | null;
+  status: 'queued' | 'running' | 'done' | 'failed' | 'cancelled';
+  created_at: number;
+  started_at: number | null;
+  finished_at: number | null;
+  exit_code: number | null;
+  error: string | null;
+  output_tail: string;
+}
+
+/** A task branch in its project's merge queue. */
+export interface MergeQueueEntry {
+  id: string;
+  task_id: string;
+  project_root: string;
+  branch_name: string;
+  worktree_path: string;
+  squash: boolean;
+  message: string | null;
+  checks: string[];
+  /** Tasks that must merge through the queue first. */
+  depends_on: string[];
+  status: 'queued' | 'rebasing' | 'checking' | 'merging' | 'merged' | 'failed' | 'cancelled';
+  enqueued_at: number;
+  started_at: number | null;
+  finished_at: number | null;
+  /** Why the entry failed and paused the queue. */
+  report: {
+    stage: 'dependencies' | 'rebase' | 'checks' | 'merge';
+    message: string;
+    conflicting_files: string[];
+    check: CheckResult | null;
+  } | null;
+  lines_added: number | null;
+  lines_removed: number | null;
+}
+
+export interface MergeQueueState {
+  entries: MergeQueueEntry[];
+  /** Project roots whose queue stopped at a failure. */
+  paused: string[];
+}
+
+/** A keychain-encrypted secret; its value stays in the main process. */
+export interface SecretInfo {
+  name: string;
+  /** Whether agent sessions get it as the environment variable `name`. */
+  inject: boolean;
+  updated_at: number;
+}
+
+/** An asciicast recording of one terminal session. */
+export interface RecordingInfo {
+  id: string;
+  task_id: string;
+  agent_id: string;
+  started_at: number;
+  size_bytes: number;
+}
@@ -0,0 +1,33 @@
+import { describe, it, expect } from 'vitest';
+import { buildSquashMessage, coAuthorTrailer } from './commit-message';
+
+describe('buildSquashMessage', () => {
+  it('includes task name, branch, agent, commits and trailer', () => {
+    const msg = buildSquashMessage({
+      taskName: 'Fix login redirect',
+      branchName: 'task/fix-login-redirect',
+      agentId: 'claude-code',
+      agentName: 'Claude Code',
+      branchLog: '- a1b2c3d wip\n- e4f5a6b fix tests\n',
+    });
+    expect(msg).toBe(
+      'Fix login redirect\n\n' +
+        'Branch: task/fix-login-redirect\nAgent: Claude Code\n\n' +
+        '- wip\n- fix tests\n\n' +
+        'Co-authored-by: Claude <noreply@anthropic.com>',
+    );
+  });
+
+  it('falls back to the branch name and skips empty sections', () => {
+    const msg = buildSquashMessage({ taskName: '  ', branchName: 'task/x', agentId: 'opencode' });
+    expect(msg).toBe('task/x\n\nBranch: task/x');
+  });
+});
+
+describe('coAuthorTrailer', () => {
+  it('is only produced for agents with a known author identity', () => {
+    expect(coAuthorTrailer('codex')).toBe('Co-authored-by: Codex <noreply@openai.com>');
+    expect(coAuthorTrailer('custom-agent')).toBeUndefined();
+    expect(coAuthorTrailer(undefined)).toBeUndefined();
+  });
+});
@@ -0,0 +1,38 @@
+/** Default squash-merge commit message, editable in the merge dialog. */
+
+const AGENT_CO_AUTHORS: Record<string, string> = {
+  'claude-code': 'Claude <noreply@anthropic.com>',
+  codex: 'Codex <noreply@openai.com>',
+  gemini: 'Gemini <noreply@google.com>',
+};
+
+export interface SquashMessageInput {
+  taskName: string;
+  branchName: string;
+  /** Agent definition id and display name; omit for tasks without an agent. */
+  agentId?: string;
+  agentName?: string;
+  /** Output of `git log` for the branch, one "- <sha> <subject>" line per commit. */
+  branchLog?: string;
+}
+
+export function coAuthorTrailer(agentId: string | undefined): string | undefined {
+  const author = agentId ? AGENT_CO_AUTHORS[agentId] : undefined;
+  return author ? `Co-authored-by: ${author}` : undefined;
+}
+
+export function buildSquashMessage(input: SquashMessageInput): string {
+  const commits = (input.branchLog ?? '')
+    .split('\n')
+    .map((l) => l.trim().replace(/^- [a-f0-9]+ /, '- '))
+    .filter(Boolean);
+
+  const body = [`Branch: ${input.branchName}`];
+  if (input.agentName) body.push(`Agent: ${input.agentName}`);
+
+  const sections = [input.taskName.trim() || input.branchName, body.join('\n')];
+  if (commits.length > 0) sections.push(commits.join('\n'));
+  const trailer = coAuthorTrailer(input.agentId);
+  if (trailer) sections.push(trailer);
+  return sections.join('\n\n');
+}
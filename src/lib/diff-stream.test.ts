@@ -0,0 +1,65 @@
+import { describe, it, expect } from 'vitest';
+import { applyDiffChunk, applyDiffPage } from './diff-stream';
+import type { DiffFilePart, DiffHunk } from '../ipc/types';
+
+const hunk = (id: number, ...texts: string[]): DiffHunk => ({
+  id,
+  header: `@@ -${id} +${id} @@`,
+  old_start: id,
+  old_lines: 1,
+  new_start: id,
+  new_lines: 1,
+  lines: texts.map((text) => ({ type: 'add' as const, text })),
+});
+
+const part = (path: string, hunks: DiffHunk[], over: Partial<DiffFilePart> = {}) => ({
+  path,
+  binary: false,
+  hunks,
+  done: false,
+  additions: 0,
+  deletions: 0,
+  more: null,
+  ...over,
+});
+
+const chunk = (seq: number, files: DiffFilePart[]) => ({
+  handle: 'h',
+  seq,
+  files,
+  done: false,
+  error: null,
+});
+
+describe('applyDiffChunk', () => {
+  it('joins a file and a hunk split across chunks', () => {
+    let files = applyDiffChunk([], chunk(0, [part('a.ts', [hunk(0, 'one')])]));
+    files = applyDiffChunk(
+      files,
+      chunk(1, [
+        part('a.ts', [hunk(0, 'two'), hunk(1, 'three')], { done: true, additions: 3 }),
+        part('b.ts', [hunk(0, 'four')], { done: true, additions: 1, more: 1 }),
+      ]),
+    );
+    expect(files.map((f) => [f.path, f.done, f.additions, f.more])).toEqual([
+      ['a.ts', true, 3, null],
+      ['b.ts', true, 1, 1],
+    ]);
+    expect(files[0].hunks.map((h) => [h.id, h.lines.map((l) => l.text)])).toEqual([
+      [0, ['one', 'two']],
+      [1, ['three']],
+    ]);
+  });
+});
+
+describe('applyDiffPage', () => {
+  it('continues the cut-off hunk with the loaded lines', () => {
+    const [file] = applyDiffChunk(
+      [],
+      chunk(0, [part('a.ts', [hunk(0, 'one')], { done: true, more: 1 })]),
+    );
+    const loaded = applyDiffPage(file, { path: 'a.ts', hunks: [hunk(0, 'two')], more: null });
+    expect(loaded.hunks).toEqual([hunk(0, 'one', 'two')]);
+    expect(loaded.more).toBeNull();
+  });
+});
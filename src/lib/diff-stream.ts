@@ -0,0 +1,36 @@
+import type { DiffChunk, DiffHunk, DiffPage, StructuredFileDiff } from '../ipc/types';
+
+/** A file of a streamed diff as far as it has arrived. */
+export interface StreamedFileDiff extends StructuredFileDiff {
+  /** All its parts are in; totals are the whole file's from then on. */
+  done: boolean;
+  /** Where the size cap cut it off, to load more from; null when it's all here. */
+  more: number | null;
+}
+
+/** `hunks` followed by `next`, joining a hunk that was split between them. */
+function joinHunks(hunks: DiffHunk[], next: DiffHunk[]): DiffHunk[] {
+  const last = hunks[hunks.length - 1];
+  const [first, ...rest] = next;
+  if (last === undefined || first === undefined || last.id !== first.id) return [...hunks, ...next];
+  return [...hunks.slice(0, -1), { ...last, lines: [...last.lines, ...first.lines] }, ...rest];
+}
+
+/** `files` with a chunk of the stream added; a file split across chunks stays one entry. */
+export function applyDiffChunk(files: StreamedFileDiff[], chunk: DiffChunk): StreamedFileDiff[] {
+  const out = [...files];
+  for (const part of chunk.files) {
+    const last = out[out.length - 1];
+    const merged = last !== undefined && !last.done && last.path === part.path;
+    const hunks = merged ? joinHunks(last.hunks, part.hunks) : part.hunks;
+    const file: StreamedFileDiff = { ...part, hunks };
+    if (merged) out[out.length - 1] = file;
+    else out.push(file);
+  }
+  return out;
+}
+
+/** `file` with a page from `loadMoreDiff` added. */
+export function applyDiffPage(file: StreamedFileDiff, page: DiffPage): StreamedFileDiff {
+  return { ...file, hunks: joinHunks(file.hunks, page.hunks), more: page.more };
+}
@@ -0,0 +1,4 @@
+/** POSIX-style environment variable name: letters, digits and `_`, not starting with a digit. */
+export function isEnvVarName(name: string): boolean {
+  return /^[A-Za-z_][A-Za-z0-9_]*$/.test(name);
+}
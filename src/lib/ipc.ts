@@ -1,6 +1,7 @@
 // Core IPC — wraps Electron's ipcRenderer for frontend-backend communication.
 
 import { IPC } from '../../electron/ipc/channels';
+import { AppError, decodeFromIpc } from '../../electron/ipc/errors';
 
 declare global {
   interface Window {
@@ -10,6 +11,8 @@ declare global {
         on: (channel: string, listener: (...args: unknown[]) => void) => () => void;
         removeAllListeners: (channel: string) => void;
       };
+      /** The local path of a dropped file; empty for files that aren't on disk. */
+      getPathForFile: (file: File) => string;
     };
   }
 }
@@ -39,7 +42,14 @@ export async function invoke<T>(cmd: IPC, args?: Record<string, unknown>): Promi
   // Triggers Channel.toJSON() to replace Channel instances with
   // plain { __CHANNEL_ID__: id } objects.
   const safeArgs = args ? (JSON.parse(JSON.stringify(args)) as Record<string, unknown>) : undefined;
-  return window.electron.ipcRenderer.invoke(cmd, safeArgs) as Promise<T>;
+  try {
+    return (await window.electron.ipcRenderer.invoke(cmd, safeArgs)) as T;
+  } catch (err) {
+    // Typed backend errors arrive encoded in the message; restore them
+    const decoded = err instanceof Error ? decodeFromIpc(err.message) : null;
+    if (decoded) throw new AppError(decoded.code, decoded.message, decoded.context);
+    throw err;
+  }
 }
 
 /**
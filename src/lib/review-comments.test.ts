@@ -0,0 +1,33 @@
+import { describe, it, expect } from 'vitest';
+import { parseReviewComments, reviewCommentsPrompt, type ReviewComment } from './review-comments';
+
+const comment = (over: Partial<ReviewComment>): ReviewComment => ({
+  id: 'c1',
+  path: 'src/a.ts',
+  startLine: 1,
+  endLine: 1,
+  body: 'Rename this',
+  createdAt: 0,
+  ...over,
+});
+
+describe('reviewCommentsPrompt', () => {
+  it('lists comments in file and line order with their anchors', () => {
+    const prompt = reviewCommentsPrompt([
+      comment({ id: 'c2', path: 'src/b.ts', startLine: 4, endLine: 9, body: 'Split\nthis up' }),
+      comment({ id: 'c1', startLine: 12, endLine: 12 }),
+    ]);
+    expect(prompt).toContain(
+      '1. src/a.ts:12\n   Rename this\n2. src/b.ts:4-9\n   Split\n   this up',
+    );
+  });
+});
+
+describe('parseReviewComments', () => {
+  it('drops malformed comments', () => {
+    const valid = comment({ resolvedAt: 5 });
+    expect(parseReviewComments([valid, { ...valid, startLine: '3' }, null])).toEqual([valid]);
+    expect(parseReviewComments([{ id: 'x' }])).toBeUndefined();
+    expect(parseReviewComments('nope')).toBeUndefined();
+  });
+});
@@ -0,0 +1,53 @@
+export interface ReviewComment {
+  id: string;
+  /** File path relative to the worktree. */
+  path: string;
+  /** 1-based line range in the task's version of the file. */
+  startLine: number;
+  endLine: number;
+  body: string;
+  createdAt: number;
+  resolvedAt?: number;
+}
+
+export function formatCommentAnchor(c: Pick<ReviewComment, 'path' | 'startLine' | 'endLine'>) {
+  const lines = c.startLine === c.endLine ? `${c.startLine}` : `${c.startLine}-${c.endLine}`;
+  return `${c.path}:${lines}`;
+}
+
+/** Follow-up prompt asking the agent to address `comments`, in file and line order. */
+export function reviewCommentsPrompt(comments: ReviewComment[]): string {
+  const sorted = [...comments].sort(
+    (a, b) => a.path.localeCompare(b.path) || a.startLine - b.startLine,
+  );
+  const items = sorted.map((c, i) => {
+    const body = c.body.trim().replace(/\n/g, '\n   ');
+    return `${i + 1}. ${formatCommentAnchor(c)}\n   ${body}`;
+  });
+  return [
+    'Please address these review comments on your changes:',
+    '',
+    ...items,
+    '',
+    'Line numbers refer to the files as they are now.',
+  ].join('\n');
+}
+
+/** Comments read back from saved state, dropping malformed ones. */
+export function parseReviewComments(v: unknown): ReviewComment[] | undefined {
+  if (!Array.isArray(v)) return undefined;
+  const comments = v.filter(
+    (c: unknown): c is ReviewComment =>
+      typeof c === 'object' &&
+      c !== null &&
+      typeof (c as ReviewComment).id === 'string' &&
+      typeof (c as ReviewComment).path === 'string' &&
+      Number.isInteger((c as ReviewComment).startLine) &&
+      Number.isInteger((c as ReviewComment).endLine) &&
+      typeof (c as ReviewComment).body === 'string' &&
+      typeof (c as ReviewComment).createdAt === 'number' &&
+      ((c as ReviewComment).resolvedAt === undefined ||
+        typeof (c as ReviewComment).resolvedAt === 'number'),
+  );
+  return comments.length > 0 ? comments : undefined;
+}
@@ -0,0 +1,20 @@
+import { describe, expect, it } from 'vitest';
+import { sharedCacheEnv } from './shared-caches';
+
+describe('sharedCacheEnv', () => {
+  it('points Cargo and pnpm at the main checkout', () => {
+    expect(sharedCacheEnv('/work/app/', ['cargo', 'pnpm'], '/work/repo.git/pnpm-store')).toEqual({
+      CARGO_TARGET_DIR: '/work/app/target',
+      npm_config_store_dir: '/work/repo.git/pnpm-store',
+    });
+  });
+
+  it("leaves pnpm's own store until the shared one is resolved", () => {
+    expect(sharedCacheEnv('/work/app', ['pnpm'])).toEqual({});
+  });
+
+  it('sets nothing for node_modules, which is shared at setup instead', () => {
+    expect(sharedCacheEnv('/work/app', ['node_modules'])).toEqual({});
+    expect(sharedCacheEnv('/work/app', undefined)).toEqual({});
+  });
+});
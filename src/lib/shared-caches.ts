@@ -0,0 +1,31 @@
+/**
+ * Dependency caches a project's task worktrees can share with its main
+ * checkout instead of each installing and building from scratch.
+ */
+export const SHARED_CACHES = ['node_modules', 'cargo', 'pnpm'] as const;
+
+export type SharedCache = (typeof SHARED_CACHES)[number];
+
+export const SHARED_CACHE_LABELS: Record<SharedCache, string> = {
+  node_modules: 'Clone node_modules into new worktrees',
+  cargo: "Build Rust into the main checkout's target directory",
+  pnpm: 'Use one pnpm store for all worktrees',
+};
+
+/**
+ * Environment that points a task's tools at the shared caches: Cargo builds
+ * into the main checkout's `target`, pnpm uses `pnpmStoreDir`, the store in
+ * the repo's git dir the backend resolved, so it's on the same disk as every
+ * worktree and can hard-link from it. Until that's known pnpm keeps its own.
+ */
+export function sharedCacheEnv(
+  projectRoot: string,
+  caches: readonly SharedCache[] | undefined,
+  pnpmStoreDir?: string,
+): Record<string, string> {
+  const join = (...parts: string[]) => [projectRoot.replace(/\/+$/, ''), ...parts].join('/');
+  const env: Record<string, string> = {};
+  if (caches?.includes('cargo')) env.CARGO_TARGET_DIR = join('target');
+  if (caches?.includes('pnpm') && pnpmStoreDir) env.npm_config_store_dir = pnpmStoreDir;
+  return env;
+}
@@ -14,9 +14,17 @@ export async function openFileInEditor(worktreePath: string, filePath: string):
   if (errorMessage) throw new Error(errorMessage);
 }
 
-export async function openInEditor(editorCommand: string, worktreePath: string): Promise<void> {
-  await window.electron.ipcRenderer.invoke(IPC.ShellOpenInEditor, {
-    editorCommand,
+/** Launch `editor` (preset, template name or command) on a worktree, optionally at a file. */
+export async function openInEditor(
+  editor: string,
+  worktreePath: string,
+  filePath?: string,
+  line?: number,
+): Promise<void> {
+  await window.electron.ipcRenderer.invoke(IPC.OpenInEditor, {
+    editor,
     worktreePath,
+    filePath,
+    line,
   });
 }
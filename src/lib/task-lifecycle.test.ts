@@ -0,0 +1,36 @@
+import { describe, it, expect } from 'vitest';
+import { canTransition, isTaskLifecycle } from './task-lifecycle';
+
+describe('canTransition', () => {
+  it('allows the normal run → review → merge path', () => {
+    expect(canTransition('queued', 'agent-running')).toBe(true);
+    expect(canTransition('agent-running', 'needs-review')).toBe(true);
+    expect(canTransition('needs-review', 'merging')).toBe(true);
+    expect(canTransition('merging', 'merged')).toBe(true);
+  });
+
+  it('lets a task complete when its PR is merged on GitHub', () => {
+    expect(canTransition('needs-review', 'merged')).toBe(true);
+    expect(canTransition('agent-running', 'merged')).toBe(true);
+  });
+
+  it('lets failed tasks be retried', () => {
+    expect(canTransition('failed', 'agent-running')).toBe(true);
+    expect(canTransition('failed', 'merging')).toBe(true);
+  });
+
+  it('rejects skipping steps, self-transitions and leaving merged', () => {
+    expect(canTransition('queued', 'merged')).toBe(false);
+    expect(canTransition('needs-review', 'needs-review')).toBe(false);
+    expect(canTransition('merged', 'agent-running')).toBe(false);
+  });
+});
+
+describe('isTaskLifecycle', () => {
+  it('accepts known states only', () => {
+    expect(isTaskLifecycle('needs-review')).toBe(true);
+    expect(isTaskLifecycle('done')).toBe(false);
+    expect(isTaskLifecycle('hasOwnProperty')).toBe(false);
+    expect(isTaskLifecycle(undefined)).toBe(false);
+  });
+});
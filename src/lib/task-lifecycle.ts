@@ -0,0 +1,26 @@
+/** Where a task is in its life, from creation to merge. */
+export type TaskLifecycle =
+  | 'queued'
+  | 'agent-running'
+  | 'needs-review'
+  | 'merging'
+  | 'merged'
+  | 'failed';
+
+const TRANSITIONS: Record<TaskLifecycle, readonly TaskLifecycle[]> = {
+  queued: ['agent-running', 'failed'],
+  // Running and reviewed tasks can also be merged outside the app, via their PR
+  'agent-running': ['needs-review', 'merging', 'merged', 'failed'],
+  'needs-review': ['agent-running', 'merging', 'merged', 'failed'],
+  merging: ['merged', 'needs-review', 'failed'],
+  merged: [],
+  failed: ['agent-running', 'needs-review', 'merging'],
+};
+
+export function canTransition(from: TaskLifecycle, to: TaskLifecycle): boolean {
+  return TRANSITIONS[from].includes(to);
+}
+
+export function isTaskLifecycle(v: unknown): v is TaskLifecycle {
+  return typeof v === 'string' && Object.prototype.hasOwnProperty.call(TRANSITIONS, v);
+}
@@ -0,0 +1,45 @@
+import { describe, expect, it } from 'vitest';
+import { queryTasks, type QueryableTask } from './task-query';
+
+function task(id: string, fields: Partial<QueryableTask> = {}): QueryableTask {
+  return {
+    id,
+    name: id,
+    notes: '',
+    labels: [],
+    agentDefIds: ['claude-code'],
+    activity: 'waiting',
+    lifecycle: 'agent-running',
+    ...fields,
+  };
+}
+
+const ids = (r: { tasks: QueryableTask[] }) => r.tasks.map((t) => t.id);
+
+describe('queryTasks', () => {
+  const tasks = [
+    task('a', { name: 'Fix login', labels: ['bug'], updatedAt: 30, createdAt: 1 }),
+    task('b', { notes: 'Decided to drop the LOGIN cache', activity: 'busy', updatedAt: 10 }),
+    task('c', { agentDefIds: ['codex'], lifecycle: 'merged', createdAt: 5, updatedAt: 20 }),
+  ];
+
+  it('filters by status, label, agent and text', () => {
+    expect(ids(queryTasks(tasks, { status: 'busy' }))).toEqual(['b']);
+    expect(ids(queryTasks(tasks, { status: 'merged' }))).toEqual(['c']);
+    expect(ids(queryTasks(tasks, { label: 'bug' }))).toEqual(['a']);
+    expect(ids(queryTasks(tasks, { agent: 'codex' }))).toEqual(['c']);
+    expect(ids(queryTasks(tasks, { text: 'login' }))).toEqual(['a', 'b']);
+  });
+
+  it('sorts newest first by default and puts missing times last', () => {
+    expect(ids(queryTasks(tasks, { sort: 'updated_at' }))).toEqual(['a', 'c', 'b']);
+    expect(ids(queryTasks(tasks, { sort: 'created_at' }))).toEqual(['c', 'a', 'b']);
+    expect(ids(queryTasks(tasks, { sort: 'created_at', order: 'asc' }))).toEqual(['a', 'c', 'b']);
+  });
+
+  it('pages after filtering and reports the total', () => {
+    const result = queryTasks(tasks, { sort: 'updated_at', offset: 1, limit: 1 });
+    expect(result.total).toBe(3);
+    expect(ids(result)).toEqual(['c']);
+  });
+});
@@ -0,0 +1,72 @@
+/** Filtering, sorting and paging of the task list for the automation API. */
+
+export type TaskSortKey = 'updated_at' | 'created_at' | 'name';
+
+export interface TaskQuery {
+  /** Matches the sidebar activity (`busy`, `waiting`, `ready`) or the lifecycle stage. */
+  status?: string;
+  label?: string;
+  /** Agent definition id, e.g. `claude-code`. */
+  agent?: string;
+  /** Case-insensitive substring of the task's name or notes. */
+  text?: string;
+  sort?: TaskSortKey;
+  /** Defaults to newest first for times and A–Z for names. */
+  order?: 'asc' | 'desc';
+  offset?: number;
+  limit?: number;
+}
+
+export interface QueryableTask {
+  id: string;
+  name: string;
+  notes: string;
+  labels: string[];
+  agentDefIds: string[];
+  activity: string;
+  lifecycle: string | null;
+  createdAt?: number;
+  updatedAt?: number;
+}
+
+export const TASK_SORT_KEYS: readonly TaskSortKey[] = ['updated_at', 'created_at', 'name'];
+
+/**
+ * The page of `tasks` matching `query`, and how many matched in all. Tasks
+ * without a timestamp sort after those with one, whichever the order; ties
+ * keep their sidebar order.
+ */
+export function queryTasks<T extends QueryableTask>(
+  tasks: T[],
+  query: TaskQuery,
+): { total: number; tasks: T[] } {
+  const text = query.text?.trim().toLowerCase();
+  const matches = tasks.filter(
+    (t) =>
+      (!query.status || t.activity === query.status || t.lifecycle === query.status) &&
+      (!query.label || t.labels.includes(query.label)) &&
+      (!query.agent || t.agentDefIds.includes(query.agent)) &&
+      (!text || t.name.toLowerCase().includes(text) || t.notes.toLowerCase().includes(text)),
+  );
+
+  if (query.sort) {
+    const sort = query.sort;
+    const desc = (query.order ?? (sort === 'name' ? 'asc' : 'desc')) === 'desc';
+    matches.sort((a, b) => {
+      if (sort === 'name') {
+        const cmp = a.name.localeCompare(b.name);
+        return desc ? -cmp : cmp;
+      }
+      const ta = sort === 'created_at' ? a.createdAt : a.updatedAt;
+      const tb = sort === 'created_at' ? b.createdAt : b.updatedAt;
+      if (ta === undefined || tb === undefined) {
+        return (ta === undefined ? 1 : 0) - (tb === undefined ? 1 : 0);
+      }
+      return desc ? tb - ta : ta - tb;
+    });
+  }
+
+  const offset = query.offset ?? 0;
+  const end = query.limit === undefined ? undefined : offset + query.limit;
+  return { total: matches.length, tasks: matches.slice(offset, end) };
+}
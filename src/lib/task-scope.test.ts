@@ -0,0 +1,27 @@
+import { describe, it, expect } from 'vitest';
+import { normalizeTaskScope, taskCwd } from './task-scope';
+
+describe('normalizeTaskScope', () => {
+  it('normalizes separators and strips ./ and trailing slashes', () => {
+    expect(normalizeTaskScope(' ./packages\\api/ ')).toBe('packages/api');
+    expect(normalizeTaskScope('packages//web')).toBe('packages/web');
+  });
+
+  it('treats an empty scope as the whole repo', () => {
+    expect(normalizeTaskScope('')).toBeUndefined();
+    expect(normalizeTaskScope(' ./ ')).toBeUndefined();
+  });
+
+  it('rejects scopes outside the repo', () => {
+    expect(() => normalizeTaskScope('../other')).toThrow('inside the repo');
+    expect(() => normalizeTaskScope('/etc')).toThrow('inside the repo');
+    expect(() => normalizeTaskScope('C:\\work')).toThrow('inside the repo');
+  });
+});
+
+describe('taskCwd', () => {
+  it('starts scoped tasks in their sub-directory', () => {
+    expect(taskCwd({ worktreePath: '/wt', scope: 'packages/api' })).toBe('/wt/packages/api');
+    expect(taskCwd({ worktreePath: '/wt' })).toBe('/wt');
+  });
+});
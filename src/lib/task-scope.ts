@@ -0,0 +1,20 @@
+/**
+ * A task's scope as `packages/api`: forward slashes, no leading `./` or
+ * trailing slash. Empty means the whole repo. Throws when it leaves the repo.
+ */
+export function normalizeTaskScope(raw: string): string | undefined {
+  const parts = raw
+    .trim()
+    .replace(/\\/g, '/')
+    .split('/')
+    .filter((p) => p.length > 0 && p !== '.');
+  if (raw.trim().startsWith('/') || /^[A-Za-z]:/.test(raw.trim()) || parts.includes('..')) {
+    throw new Error(`Scope must be a folder inside the repo: ${raw}`);
+  }
+  return parts.length > 0 ? parts.join('/') : undefined;
+}
+
+/** Where a task's agents and shells start: its scope inside the worktree, if it has one. */
+export function taskCwd(task: { worktreePath: string; scope?: string }): string {
+  return task.scope ? `${task.worktreePath}/${task.scope}` : task.worktreePath;
+}
@@ -0,0 +1,65 @@
+import { describe, expect, it } from 'vitest';
+import { formatTaskProgress, summarizeEvents, taskPhase, type PhaseInput } from './task-summary';
+
+const base: PhaseInput = {
+  lifecycle: 'agent-running',
+  busy: false,
+  asking: false,
+  running: true,
+  lastEvent: undefined,
+};
+
+describe('taskPhase', () => {
+  it('follows the lifecycle outside of agent work', () => {
+    expect(taskPhase({ ...base, lifecycle: 'merged', busy: true })).toBe('merged');
+    expect(taskPhase({ ...base, lifecycle: 'queued' })).toBe('queued');
+  });
+
+  it('tells what a busy agent is doing from its last event', () => {
+    const at = 1;
+    expect(taskPhase({ ...base, busy: true, lastEvent: { kind: 'editing', at } })).toBe('editing');
+    expect(taskPhase({ ...base, busy: true, lastEvent: { kind: 'command', at } })).toBe(
+      'running_command',
+    );
+    expect(taskPhase({ ...base, busy: true, lastEvent: { kind: 'turn_complete', at } })).toBe(
+      'working',
+    );
+  });
+
+  it('puts a question before everything but the lifecycle', () => {
+    expect(taskPhase({ ...base, busy: true, asking: true })).toBe('waiting_for_input');
+  });
+
+  it('is idle while an agent runs and ready for review otherwise', () => {
+    expect(taskPhase(base)).toBe('idle');
+    expect(taskPhase({ ...base, running: false })).toBe('needs_review');
+    expect(taskPhase({ ...base, lifecycle: 'needs-review' })).toBe('needs_review');
+  });
+});
+
+describe('summarizeEvents', () => {
+  it('merges events from several agents in time order', () => {
+    const summary = summarizeEvents([
+      { kind: 'editing', detail: 'b.ts', at: 3 },
+      { kind: 'command', detail: 'npm test', at: 2 },
+      { kind: 'editing', detail: 'a.ts', at: 1 },
+      { kind: 'error', detail: 'Exit code 1', at: 4 },
+      { kind: 'editing', detail: 'a.ts', at: 5 },
+      { kind: 'command', detail: 'npm test', at: 6 },
+    ]);
+    expect(summary).toEqual({
+      files_touched: ['a.ts', 'b.ts'],
+      commands_run: ['npm test', 'npm test'],
+      last_error: 'Exit code 1',
+    });
+  });
+});
+
+describe('formatTaskProgress', () => {
+  it('names the file or command and counts commits', () => {
+    const event = { kind: 'editing' as const, detail: 'src/app.ts', at: 1 };
+    expect(formatTaskProgress('editing', event, 3)).toBe('Editing src/app.ts · 3 commits');
+    expect(formatTaskProgress('idle', event, 1)).toBe('Idle · 1 commit');
+    expect(formatTaskProgress('needs_review', undefined, 0)).toBe('Ready for review');
+  });
+});
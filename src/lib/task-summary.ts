@@ -0,0 +1,100 @@
+import type { AgentOutputEvent } from '../ipc/types';
+import type { TaskLifecycle } from './task-lifecycle';
+
+/** What a task is doing right now, finer-grained than its lifecycle. */
+export type TaskPhase =
+  | 'queued'
+  | 'editing'
+  | 'running_command'
+  | 'working'
+  | 'waiting_for_input'
+  | 'idle'
+  | 'needs_review'
+  | 'merging'
+  | 'merged'
+  | 'failed';
+
+export interface TaskSummary {
+  task_id: string;
+  phase: TaskPhase;
+  /** Files the agents reported editing, in the order first touched. */
+  files_touched: string[];
+  /** Commands the agents reported running, oldest first. */
+  commands_run: string[];
+  last_error: string | null;
+  /** Epoch ms of the latest output from any of the task's agents. */
+  last_activity_at: number | null;
+  /** Commits on the task branch ahead of the base; null until git status is known. */
+  commit_count: number | null;
+}
+
+export interface PhaseInput {
+  lifecycle: TaskLifecycle | undefined;
+  /** An agent is producing output. */
+  busy: boolean;
+  /** An agent is waiting on a question or permission prompt. */
+  asking: boolean;
+  /** An agent process is running. */
+  running: boolean;
+  lastEvent: AgentOutputEvent | undefined;
+}
+
+export function taskPhase(input: PhaseInput): TaskPhase {
+  switch (input.lifecycle) {
+    case 'queued':
+    case 'merging':
+    case 'merged':
+    case 'failed':
+      return input.lifecycle;
+  }
+  if (input.asking) return 'waiting_for_input';
+  if (input.busy) {
+    if (input.lastEvent?.kind === 'editing') return 'editing';
+    if (input.lastEvent?.kind === 'command') return 'running_command';
+    return 'working';
+  }
+  if (input.running && input.lifecycle !== 'needs-review') return 'idle';
+  return 'needs_review';
+}
+
+/** Files, commands and the last error from the agents' events, merged in time order. */
+export function summarizeEvents(
+  events: AgentOutputEvent[],
+): Pick<TaskSummary, 'files_touched' | 'commands_run' | 'last_error'> {
+  const files = new Set<string>();
+  const commands: string[] = [];
+  let lastError: string | null = null;
+  for (const e of [...events].sort((a, b) => a.at - b.at)) {
+    if (e.kind === 'editing' && e.detail) files.add(e.detail);
+    else if (e.kind === 'command' && e.detail) commands.push(e.detail);
+    else if (e.kind === 'error') lastError = e.detail ?? 'Error';
+  }
+  return { files_touched: [...files], commands_run: commands, last_error: lastError };
+}
+
+const PHASE_LABELS: Record<TaskPhase, string> = {
+  queued: 'Queued',
+  editing: 'Editing',
+  running_command: 'Running',
+  working: 'Working',
+  waiting_for_input: 'Waiting for input',
+  idle: 'Idle',
+  needs_review: 'Ready for review',
+  merging: 'Merging',
+  merged: 'Merged',
+  failed: 'Failed',
+};
+
+/** One line for the task list, e.g. "Editing src/app.ts · 3 commits". */
+export function formatTaskProgress(
+  phase: TaskPhase,
+  lastEvent: AgentOutputEvent | undefined,
+  commitCount: number | null,
+): string {
+  const detail =
+    (phase === 'editing' || phase === 'running_command') && lastEvent?.detail
+      ? ` ${lastEvent.detail}`
+      : '';
+  const commits = commitCount ? ` · ${commitCount} commit${commitCount === 1 ? '' : 's'}` : '';
+  return `${PHASE_LABELS[phase]}${detail}${commits}`;
+}
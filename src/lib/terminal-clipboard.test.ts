@@ -45,6 +45,26 @@ describe('handleTerminalClipboardKeyEvent', () => {
     expect(enqueueInput).toHaveBeenCalledWith('hello');
   });
 
+  it('sends clipboard text through pasteText when provided', async () => {
+    const enqueueInput = vi.fn();
+    const pasteText = vi.fn();
+    const event = createKeyboardEvent({ key: 'v', metaKey: true });
+
+    handleTerminalClipboardKeyEvent(event, {
+      isMac: true,
+      isGlobalShortcut: () => false,
+      getSelection: () => '',
+      readClipboardText: vi.fn().mockResolvedValue('line 1\nline 2'),
+      writeClipboardText: vi.fn(),
+      enqueueInput,
+      pasteText,
+    });
+
+    await Promise.resolve();
+    expect(pasteText).toHaveBeenCalledWith('line 1\nline 2');
+    expect(enqueueInput).not.toHaveBeenCalled();
+  });
+
   it('falls back to clipboard image when text is empty', async () => {
     const enqueueInput = vi.fn();
     const readClipboardText = vi.fn().mockResolvedValue('');
@@ -54,7 +74,7 @@ describe('handleTerminalClipboardKeyEvent', () => {
         getType: vi.fn().mockResolvedValue(new Blob(['png-bytes'], { type: 'image/png' })),
       },
     ]);
-    const saveClipboardImage = vi.fn().mockResolvedValue('/tmp/clipboard-image.png');
+    const pasteClipboardImage = vi.fn().mockResolvedValue('@.parallel-code/images/a.png ');
     const event = createKeyboardEvent({ key: 'v', metaKey: true });
 
     const handled = handleTerminalClipboardKeyEvent(event, {
@@ -63,7 +83,7 @@ describe('handleTerminalClipboardKeyEvent', () => {
       getSelection: () => '',
       readClipboardText,
       readClipboardItems,
-      saveClipboardImage,
+      pasteClipboardImage,
       writeClipboardText: vi.fn(),
       enqueueInput,
     });
@@ -75,8 +95,33 @@ describe('handleTerminalClipboardKeyEvent', () => {
     await new Promise((resolve) => setTimeout(resolve, 0));
     expect(readClipboardText).toHaveBeenCalledTimes(1);
     expect(readClipboardItems).toHaveBeenCalledTimes(1);
-    expect(saveClipboardImage).toHaveBeenCalledTimes(1);
-    expect(enqueueInput).toHaveBeenCalledWith('/tmp/clipboard-image.png');
+    expect(pasteClipboardImage).toHaveBeenCalledWith(expect.any(String), 'image/png');
+    // The backend types the reference into the PTY itself
+    expect(enqueueInput).not.toHaveBeenCalled();
+  });
+
+  it('reads the image natively when the web clipboard has none', async () => {
+    const readNativeClipboardImage = vi.fn().mockResolvedValue({
+      base64Data: 'cG5n',
+      mimeType: 'image/png',
+    });
+    const pasteClipboardImage = vi.fn().mockResolvedValue('@.parallel-code/images/a.png ');
+
+    handleTerminalClipboardKeyEvent(createKeyboardEvent({ key: 'v', metaKey: true }), {
+      isMac: true,
+      isGlobalShortcut: () => false,
+      getSelection: () => '',
+      readClipboardText: vi.fn().mockResolvedValue(''),
+      readClipboardItems: vi.fn().mockResolvedValue([]),
+      readNativeClipboardImage,
+      pasteClipboardImage,
+      writeClipboardText: vi.fn(),
+      enqueueInput: vi.fn(),
+    });
+
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    expect(readNativeClipboardImage).toHaveBeenCalledTimes(1);
+    expect(pasteClipboardImage).toHaveBeenCalledWith('cG5n', 'image/png');
   });
 
   it('prevents default and writes selection on macOS copy shortcut', () => {
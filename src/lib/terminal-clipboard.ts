@@ -4,9 +4,14 @@ export interface TerminalClipboardDeps {
   getSelection: () => string;
   readClipboardText: () => Promise<string>;
   readClipboardItems?: () => Promise<ClipboardImageItem[]>;
-  saveClipboardImage?: (base64Data: string, mimeType: string) => Promise<string>;
+  /** Reads the clipboard image natively, for images `readClipboardItems` can't see. */
+  readNativeClipboardImage?: () => Promise<{ base64Data: string; mimeType: string } | null>;
+  /** Saves the image for the session and types its reference into the PTY. */
+  pasteClipboardImage?: (base64Data: string, mimeType: string) => Promise<unknown>;
   writeClipboardText: (text: string) => Promise<void> | void;
   enqueueInput: (text: string) => void;
+  /** Sends clipboard text as a paste; falls back to `enqueueInput`. */
+  pasteText?: (text: string) => void;
 }
 
 export interface ClipboardImageItem {
@@ -24,10 +29,10 @@ function bytesToBase64(bytes: Uint8Array): string {
   return btoa(binary);
 }
 
-async function extractClipboardImagePath(
+async function readWebClipboardImage(
   deps: TerminalClipboardDeps,
-): Promise<string | null> {
-  if (!deps.readClipboardItems || !deps.saveClipboardImage) return null;
+): Promise<{ base64Data: string; mimeType: string } | null> {
+  if (!deps.readClipboardItems) return null;
   const items = await deps.readClipboardItems().catch(() => []);
   for (const item of items) {
     const mimeType = item.types.find((type) => type.startsWith('image/'));
@@ -36,12 +41,25 @@ async function extractClipboardImagePath(
     if (!blob || blob.size === 0) continue;
     const bytes = new Uint8Array(await blob.arrayBuffer());
     if (bytes.length === 0) continue;
-    const base64Data = bytesToBase64(bytes);
-    return deps.saveClipboardImage(base64Data, mimeType).catch(() => null);
+    return { base64Data: bytesToBase64(bytes), mimeType };
   }
   return null;
 }
 
+/** Paste the first clipboard image, if any. Returns whether one was pasted. */
+async function pasteClipboardImage(deps: TerminalClipboardDeps): Promise<boolean> {
+  if (!deps.pasteClipboardImage) return false;
+  const image =
+    (await readWebClipboardImage(deps)) ??
+    (await deps.readNativeClipboardImage?.().catch(() => null)) ??
+    null;
+  if (!image) return false;
+  return deps
+    .pasteClipboardImage(image.base64Data, image.mimeType)
+    .then(() => true)
+    .catch(() => false);
+}
+
 /**
  * Handle terminal copy/paste shortcuts.
  * Returns true when xterm should continue processing the key event.
@@ -77,11 +95,10 @@ export function handleTerminalClipboardKeyEvent(
     e.stopPropagation();
     void deps.readClipboardText().then(async (text) => {
       if (text) {
-        deps.enqueueInput(text);
+        (deps.pasteText ?? deps.enqueueInput)(text);
         return;
       }
-      const imagePath = await extractClipboardImagePath(deps);
-      if (imagePath) deps.enqueueInput(imagePath);
+      await pasteClipboardImage(deps);
     });
     return false;
   }
@@ -99,3 +99,32 @@ export function markDirty(id: string): void {
     scheduleFlush();
   }
 }
+
+/**
+ * Scroll a terminal to the row nearest `match.lines_from_end` that shows the
+ * match and select it. Line counts differ from the backend's when lines
+ * wrap, so the text decides. Returns false when it's no longer in the buffer.
+ */
+export function revealTerminalMatch(
+  id: string,
+  match: { lines_from_end: number; column: number; length: number; text: string },
+): boolean {
+  const term = entries.get(id)?.term;
+  if (!term) return false;
+  const buf = term.buffer.active;
+  const needle = match.text.slice(match.column, match.column + match.length);
+  if (!needle) return false;
+  const start = Math.max(0, buf.length - 1 - match.lines_from_end);
+  // Nearest rows first
+  for (let offset = 0; offset < buf.length; offset++) {
+    for (const row of offset === 0 ? [start] : [start - offset, start + offset]) {
+      if (row < 0 || row >= buf.length) continue;
+      const column = buf.getLine(row)?.translateToString(true).indexOf(needle) ?? -1;
+      if (column < 0) continue;
+      term.scrollToLine(Math.max(0, row - Math.floor(term.rows / 2)));
+      term.select(column, row, needle.length);
+      return true;
+    }
+  }
+  return false;
+}
@@ -2,9 +2,21 @@ import { produce } from 'solid-js/store';
 import { invoke } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
 import { store, setStore } from './core';
-import type { AgentDef } from '../ipc/types';
+import type {
+  AgentDef,
+  AgentOutputEvent,
+  AgentOutputEventMessage,
+  AgentStallEvent,
+  SessionInfo,
+  SessionSummary,
+} from '../ipc/types';
 import type { Agent } from './types';
 import { refreshTaskStatus, clearAgentActivity, markAgentSpawned } from './taskStatus';
+import { transitionTask } from './lifecycle';
+import { showNotification } from './notification';
+
+/** Agents already warned about leaving their worktree, until they return. */
+const outsideWorktree = new Set<string>();
 
 export async function loadAgents(): Promise<void> {
   const defaults = await invoke<AgentDef[]>(IPC.ListAgents);
@@ -53,6 +65,7 @@ export function markAgentExited(
         s.agents[agentId].status = 'exited';
         s.agents[agentId].exitCode = exitInfo.exit_code;
         s.agents[agentId].signal = exitInfo.signal;
+        s.agents[agentId].stall = undefined;
         s.agents[agentId].lastOutput = exitInfo.last_output;
       }
     }),
@@ -60,39 +73,135 @@ export function markAgentExited(
   if (agent) {
     clearAgentActivity(agentId);
     refreshTaskStatus(agent.taskId);
+    const task = store.tasks[agent.taskId];
+    const allExited = task?.agentIds.every((id) => store.agents[id]?.status !== 'running');
+    if (allExited) {
+      const failed = exitInfo.exit_code !== null && exitInfo.exit_code !== 0;
+      transitionTask(agent.taskId, failed ? 'failed' : 'needs-review');
+    }
+  }
+}
+
+/** Record an automatic crash restart reported by the backend restart policy. */
+export function markAgentRestarted(agentId: string, attempt: number): void {
+  if (!store.agents[agentId]) return;
+  setStore('agents', agentId, 'restartCount', attempt);
+  markAgentSpawned(agentId);
+}
+
+/** Warn once when an agent's shell reports a directory outside its task worktree. */
+export function handleSessionInfoChanged(info: SessionInfo): void {
+  if (store.agents[info.agent_id]) {
+    setStore('agents', info.agent_id, 'stall', info.stall ?? undefined);
   }
+  if (info.is_shell || !info.outside_worktree) {
+    outsideWorktree.delete(info.agent_id);
+    return;
+  }
+  if (outsideWorktree.has(info.agent_id) || !store.agents[info.agent_id]) return;
+  outsideWorktree.add(info.agent_id);
+  const name = store.tasks[info.task_id]?.name ?? 'An agent';
+  showNotification(`${name}: agent left its worktree for ${info.cwd}`);
+}
+
+/** Keep the latest event read from an agent's output, for its status badge. */
+export function handleAgentEvent(event: AgentOutputEventMessage): void {
+  if (!store.agents[event.agent_id]) return;
+  const { kind, detail, at } = event;
+  setStore('agents', event.agent_id, 'lastEvent', { kind, detail, at });
+}
+
+/** The sessions the backend runs or recently ran, optionally only a task's or a project's. */
+export function listSessions(
+  filter: { taskId?: string; projectId?: string } = {},
+): Promise<SessionSummary[]> {
+  return invoke<SessionSummary[]>(IPC.ListSessions, filter);
+}
+
+/** Prompt every agent of a project's tasks; returns the IDs of those that got it. */
+export function sendToProjectAgents(projectId: string, text: string): Promise<string[]> {
+  return invoke<string[]>(IPC.SendToProjectAgents, { projectId, text, submit: true });
+}
+
+/** Stop every agent and terminal of a project's tasks; returns their IDs. */
+export function stopProjectAgents(projectId: string): Promise<string[]> {
+  return invoke<string[]>(IPC.KillProjectAgents, { projectId });
+}
+
+/** The agent session's timeline: the events read from its output, oldest first. */
+export function getAgentEvents(agentId: string): Promise<AgentOutputEvent[]> {
+  return invoke<AgentOutputEvent[]>(IPC.GetAgentEvents, { agentId });
+}
+
+/** Tell the user about an agent that looks stuck; idle agents are only flagged. */
+export function handleAgentHung(event: AgentStallEvent): void {
+  if (!store.agents[event.agent_id]) return;
+  setStore('agents', event.agent_id, 'stall', 'hung');
+  const name = store.tasks[event.task_id]?.name ?? 'An agent';
+  const minutes = Math.round((Date.now() - event.last_activity_at) / 60_000);
+  showNotification(`${name}: agent has been silent for ${minutes} min and may be hung`);
 }
 
-export function restartAgent(agentId: string, useResumeArgs: boolean): void {
+export function restartAgent(
+  agentId: string,
+  useResumeArgs: boolean,
+  resumeArgs?: string[],
+): void {
   setStore(
     produce((s) => {
       if (s.agents[agentId]) {
+        s.agents[agentId].restartCount = undefined;
+        s.agents[agentId].lastEvent = undefined;
         s.agents[agentId].status = 'running';
         s.agents[agentId].exitCode = null;
         s.agents[agentId].signal = null;
         s.agents[agentId].lastOutput = [];
+        s.agents[agentId].stall = undefined;
         s.agents[agentId].resumed = useResumeArgs;
+        s.agents[agentId].resumeArgs = useResumeArgs ? resumeArgs : undefined;
         s.agents[agentId].generation += 1;
       }
     }),
   );
+  const taskId = store.agents[agentId]?.taskId;
+  if (taskId) transitionTask(taskId, 'agent-running');
   markAgentSpawned(agentId);
 }
 
+/**
+ * Relaunch a task's agent on the conversation it last had. Falls back to the
+ * agent's generic resume flags when no conversation was recorded.
+ */
+export async function resumeAgentSession(taskId: string, agentId: string): Promise<void> {
+  const agent = store.agents[agentId];
+  if (!agent || agent.taskId !== taskId) return;
+  const resumeArgs = await invoke<string[] | null>(IPC.GetAgentResumeArgs, {
+    taskId,
+    agentDefId: agent.def.id,
+  }).catch(() => null);
+  restartAgent(agentId, true, resumeArgs ?? undefined);
+}
+
 export function switchAgent(agentId: string, newDef: AgentDef): void {
   setStore(
     produce((s) => {
       if (s.agents[agentId]) {
         s.agents[agentId].def = newDef;
+        s.agents[agentId].restartCount = undefined;
+        s.agents[agentId].lastEvent = undefined;
         s.agents[agentId].status = 'running';
         s.agents[agentId].exitCode = null;
         s.agents[agentId].signal = null;
         s.agents[agentId].lastOutput = [];
+        s.agents[agentId].stall = undefined;
         s.agents[agentId].resumed = false;
+        s.agents[agentId].resumeArgs = undefined;
         s.agents[agentId].generation += 1;
       }
     }),
   );
+  const taskId = store.agents[agentId]?.taskId;
+  if (taskId) transitionTask(taskId, 'agent-running');
   markAgentSpawned(agentId);
 }
 
@@ -0,0 +1,25 @@
+import { invoke } from '../lib/ipc';
+import { confirm, openDialog } from '../lib/dialog';
+import { IPC } from '../../electron/ipc/channels';
+
+/** Back up all app data into a folder the user picks. Returns the archive path. */
+export async function exportAppData(): Promise<string | null> {
+  const outDir = await openDialog({ directory: true, multiple: false });
+  if (!outDir) return null;
+  return invoke<string>(IPC.ExportAppData, { outDir: outDir as string });
+}
+
+/**
+ * Replace all app data with a backup the user picks, then restart. Running
+ * agents are stopped; the current data is backed up first.
+ */
+export async function importAppData(): Promise<void> {
+  const archivePath = await openDialog({ directory: false, multiple: false });
+  if (!archivePath) return;
+  const ok = await confirm(
+    'Restoring replaces all tasks, settings and history with the backup, stops running agents and restarts the app. Your current data is backed up first.',
+    { title: 'Restore backup', kind: 'warning', okLabel: 'Restore' },
+  );
+  if (!ok) return;
+  await invoke(IPC.ImportAppData, { archivePath: archivePath as string });
+}
@@ -0,0 +1,26 @@
+import { createSignal } from 'solid-js';
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type { AgentApprovalEvent } from '../ipc/types';
+
+// Permission prompts agents are waiting on, keyed by agent id
+const [pendingApprovals, setPendingApprovals] = createSignal<Record<string, string>>({});
+
+/** Text of the permission prompt an agent is waiting on, if any. */
+export function getAgentApproval(agentId: string): string | undefined {
+  return pendingApprovals()[agentId];
+}
+
+export function handleAgentApprovalEvent(msg: AgentApprovalEvent): void {
+  setPendingApprovals((prev) => {
+    const next = { ...prev };
+    if (msg.prompt) next[msg.agent_id] = msg.prompt;
+    else delete next[msg.agent_id];
+    return next;
+  });
+}
+
+/** Answer an agent's permission prompt without focusing its terminal. */
+export function respondToAgent(agentId: string, approve: boolean): Promise<void> {
+  return invoke(IPC.RespondToAgent, { agentId, approve });
+}
@@ -0,0 +1,754 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { isAppError } from '../../electron/ipc/errors';
+import { normalizeTaskScope } from '../lib/task-scope';
+import { queryTasks, TASK_SORT_KEYS, type TaskSortKey } from '../lib/task-query';
+import { isTaskLifecycle } from '../lib/task-lifecycle';
+import { store } from './core';
+import {
+  abortTaskMerge,
+  appendTaskNotes,
+  archiveTasks,
+  continueTaskMerge,
+  createTask,
+  createTasks,
+  deleteTasks,
+  getTaskBlame,
+  getTaskConflicts,
+  getTaskLog,
+  listTaskSnapshots,
+  rebaseTasks,
+  resolveTaskConflict,
+  restoreTaskSnapshot,
+  sendPrompt,
+  snapshotTask,
+  updateTask,
+  updateTaskNotes,
+  widenSparseCheckout,
+  type CreateTaskOptions,
+  type TaskPatch,
+} from './tasks';
+import {
+  listSessions,
+  restartAgent,
+  resumeAgentSession,
+  sendToProjectAgents,
+  stopProjectAgents,
+} from './agents';
+import { findAgentProfile, setTaskAgentProfile } from './profiles';
+import {
+  getTaskGraph,
+  isTaskBlocked,
+  setTaskDependencies,
+  validateTaskDependencies,
+} from './dependencies';
+import { cancelScheduledRun, listScheduledRuns, queueAgentRun } from './scheduler';
+import {
+  enqueueMerges,
+  listMergeQueue,
+  removeFromMergeQueue,
+  resumeMergeQueue,
+} from './merge-queue';
+import {
+  addReviewComment,
+  listReviewComments,
+  resolveReviewComment,
+  sendReviewComments,
+} from './review';
+import { deleteSecret, listSecrets, setSecret } from './secrets';
+import { getProject, getProjectInfo } from './projects';
+import { getTaskSummary } from './task-summary';
+import { listDeletedTasks, restoreDeletedTask, undoLastOperation } from './trash';
+import { getMetrics, queryHistory, searchTranscripts } from './history';
+import { getProjectTime, getTaskTime, setTaskTimer } from './task-time';
+import { gcWorkspace, getDiskUsage, taskDiskUsage } from './disk-usage';
+import { getResourceUsage } from './resource-usage';
+import { cancelTaskDiff, loadMoreTaskDiff, readTaskDiffChunks, startTaskDiff } from './diff-stream';
+import { getTaskDotStatus, isAgentAskingQuestion } from './taskStatus';
+import type { TaskEventType } from '../ipc/types';
+import type {
+  AutomationRequest,
+  AutomationResponse,
+  AutomationTaskStatus,
+} from '../../electron/remote/automation-protocol';
+
+/** A failure reported to the API caller with a specific HTTP status. */
+class RequestError extends Error {
+  constructor(
+    readonly status: number,
+    message: string,
+  ) {
+    super(message);
+  }
+}
+
+function requireString(body: Record<string, unknown>, key: string): string {
+  const value = body[key];
+  if (typeof value !== 'string' || !value.trim()) {
+    throw new RequestError(400, `${key} must be a non-empty string`);
+  }
+  return value;
+}
+
+function optionalString(body: Record<string, unknown>, key: string): string | undefined {
+  const value = body[key];
+  if (value === undefined || value === null) return undefined;
+  if (typeof value !== 'string') throw new RequestError(400, `${key} must be a string`);
+  return value;
+}
+
+function requireStringArray(body: Record<string, unknown>, key: string): string[] {
+  const value = optionalStringArray(body, key);
+  if (!value || value.length === 0) {
+    throw new RequestError(400, `${key} must be a non-empty array of strings`);
+  }
+  return value;
+}
+
+function optionalStringArray(body: Record<string, unknown>, key: string): string[] | undefined {
+  const value = body[key];
+  if (value === undefined || value === null) return undefined;
+  if (!Array.isArray(value) || !value.every((v) => typeof v === 'string')) {
+    throw new RequestError(400, `${key} must be an array of strings`);
+  }
+  return value as string[];
+}
+
+/** A time field as a timestamp: epoch milliseconds or an ISO date string. */
+function optionalTime(body: Record<string, unknown>, key: string): number | undefined {
+  const value = body[key];
+  if (value === undefined || value === null) return undefined;
+  const time = typeof value === 'string' ? Date.parse(value) : value;
+  if (typeof time !== 'number' || !Number.isFinite(time)) {
+    throw new RequestError(400, `${key} must be a timestamp or an ISO date`);
+  }
+  return time;
+}
+
+function optionalLine(body: Record<string, unknown>, key: string): number | undefined {
+  const value = body[key];
+  if (value === undefined || value === null) return undefined;
+  if (typeof value !== 'number' || !Number.isInteger(value) || value < 1) {
+    throw new RequestError(400, `${key} must be a positive integer`);
+  }
+  return value;
+}
+
+function optionalLimit(body: Record<string, unknown>): number | undefined {
+  const value = body.limit;
+  if (value === undefined || value === null) return undefined;
+  if (typeof value !== 'number' || !Number.isInteger(value) || value < 1) {
+    throw new RequestError(400, 'limit must be a positive integer');
+  }
+  return value;
+}
+
+function optionalOffset(body: Record<string, unknown>): number | undefined {
+  const value = body.offset;
+  if (value === undefined || value === null) return undefined;
+  if (typeof value !== 'number' || !Number.isInteger(value) || value < 0) {
+    throw new RequestError(400, 'offset must be a non-negative integer');
+  }
+  return value;
+}
+
+function optionalStringRecord(
+  body: Record<string, unknown>,
+  key: string,
+): Record<string, string> | undefined {
+  const value = body[key];
+  if (value === undefined || value === null) return undefined;
+  if (
+    typeof value !== 'object' ||
+    Array.isArray(value) ||
+    !Object.values(value).every((v) => typeof v === 'string')
+  ) {
+    throw new RequestError(400, `${key} must be an object of strings`);
+  }
+  return value as Record<string, string>;
+}
+
+/** Report a store action's validation error as a bad request. */
+function badRequest(err: unknown): never {
+  throw new RequestError(400, err instanceof Error ? err.message : String(err));
+}
+
+function asBadRequest<T>(fn: () => T): T {
+  try {
+    return fn();
+  } catch (err) {
+    badRequest(err);
+  }
+}
+
+function taskStatus(taskId: string): AutomationTaskStatus {
+  const task = store.tasks[taskId];
+  if (!task) throw new RequestError(404, 'task not found');
+  return {
+    id: task.id,
+    name: task.name,
+    project_id: task.projectId,
+    branch_name: task.branchName,
+    worktree_path: task.worktreePath,
+    scope: task.scope ?? null,
+    lifecycle: task.lifecycle ?? null,
+    activity: getTaskDotStatus(task.id),
+    depends_on: task.dependsOn ?? [],
+    blocked: isTaskBlocked(task.id),
+    agents: task.agentIds.flatMap((id) => {
+      const agent = store.agents[id];
+      if (!agent) return [];
+      return [
+        {
+          id,
+          status: agent.status,
+          exit_code: agent.exitCode,
+          asking: isAgentAskingQuestion(id),
+          last_event: agent.lastEvent
+            ? {
+                kind: agent.lastEvent.kind,
+                detail: agent.lastEvent.detail ?? null,
+                at: agent.lastEvent.at,
+              }
+            : null,
+        },
+      ];
+    }),
+  };
+}
+
+/** The task's main agent. */
+function mainAgentId(taskId: string): string {
+  const agentId = store.tasks[taskId]?.agentIds[0];
+  if (!agentId || !store.agents[agentId]) throw new RequestError(409, 'task has no agent');
+  return agentId;
+}
+
+/** What a `create_task` body asks for, checked against the current projects and agents. */
+function createTaskOptions(body: Record<string, unknown>): CreateTaskOptions {
+  const projectId = requireString(body, 'projectId');
+  if (!store.projects.some((p) => p.id === projectId)) {
+    throw new RequestError(404, 'project not found');
+  }
+  const agentId = optionalString(body, 'agentId');
+  const agentDef = agentId
+    ? store.availableAgents.find((a) => a.id === agentId)
+    : (store.availableAgents.find((a) => a.id === store.lastAgentId) ?? store.availableAgents[0]);
+  if (!agentDef) {
+    throw new RequestError(400, agentId ? 'unknown agentId' : 'no agents available');
+  }
+  const profileId = optionalString(body, 'profileId');
+  if (profileId && !findAgentProfile(projectId, agentDef, profileId)) {
+    throw new RequestError(400, 'unknown profileId');
+  }
+  const dependsOn = optionalStringArray(body, 'dependsOn');
+  asBadRequest(() => validateTaskDependencies(null, projectId, dependsOn ?? []));
+  const scope = optionalString(body, 'scope');
+  if (scope !== undefined) asBadRequest(() => normalizeTaskScope(scope));
+  return {
+    name: requireString(body, 'name'),
+    agentDef,
+    projectId,
+    initialPrompt: optionalString(body, 'prompt'),
+    agentProfileId: profileId,
+    dependsOn,
+    scope,
+  };
+}
+
+async function handle(req: AutomationRequest): Promise<unknown> {
+  const taskId = req.taskId ?? '';
+  switch (req.method) {
+    case 'list_projects':
+      return store.projects.map((p) => ({ id: p.id, name: p.name, path: p.path }));
+
+    case 'get_project_info': {
+      const projectId = requireString(req.body, 'projectId');
+      const project = getProject(projectId);
+      if (!project) throw new RequestError(404, 'project not found');
+      const info = await getProjectInfo(projectId).catch(badRequest);
+      return { ...info, default_base_branch: project.defaultBaseBranch ?? null };
+    }
+
+    case 'list_project_tasks':
+    case 'send_project_prompt':
+    case 'stop_project_agents': {
+      const projectId = requireString(req.body, 'projectId');
+      if (!getProject(projectId)) throw new RequestError(404, 'project not found');
+      if (req.method === 'send_project_prompt') {
+        const text = requireString(req.body, 'text');
+        return { agent_ids: await sendToProjectAgents(projectId, text) };
+      }
+      if (req.method === 'stop_project_agents') {
+        return { agent_ids: await stopProjectAgents(projectId) };
+      }
+      return store.taskOrder
+        .filter((id) => store.tasks[id]?.projectId === projectId)
+        .map(taskStatus);
+    }
+
+    case 'list_tasks':
+      return store.taskOrder.filter((id) => store.tasks[id]).map(taskStatus);
+
+    case 'query_tasks': {
+      const sort = optionalString(req.body, 'sort');
+      if (sort !== undefined && !TASK_SORT_KEYS.includes(sort as TaskSortKey)) {
+        throw new RequestError(400, `sort must be one of ${TASK_SORT_KEYS.join(', ')}`);
+      }
+      const order = optionalString(req.body, 'order');
+      if (order !== undefined && order !== 'asc' && order !== 'desc') {
+        throw new RequestError(400, 'order must be asc or desc');
+      }
+      const tasks = store.taskOrder.flatMap((id) => {
+        const task = store.tasks[id];
+        if (!task) return [];
+        const agentDefIds = task.agentIds.flatMap((a) => store.agents[a]?.def.id ?? []);
+        if (agentDefIds.length === 0 && task.savedAgentDef) agentDefIds.push(task.savedAgentDef.id);
+        return [
+          {
+            id,
+            name: task.name,
+            notes: task.notes,
+            labels: task.labels ?? [],
+            agentDefIds,
+            activity: getTaskDotStatus(id),
+            lifecycle: task.lifecycle ?? null,
+            createdAt: task.createdAt,
+            updatedAt: task.updatedAt,
+          },
+        ];
+      });
+      const result = queryTasks(tasks, {
+        status: optionalString(req.body, 'status'),
+        label: optionalString(req.body, 'label'),
+        agent: optionalString(req.body, 'agent'),
+        text: optionalString(req.body, 'text'),
+        sort: sort as TaskSortKey | undefined,
+        order,
+        offset: optionalOffset(req.body),
+        limit: optionalLimit(req.body),
+      });
+      return { total: result.total, tasks: result.tasks.map((t) => taskStatus(t.id)) };
+    }
+
+    case 'get_task_status':
+      return taskStatus(taskId);
+
+    case 'update_task': {
+      taskStatus(taskId);
+      const lifecycle = optionalString(req.body, 'lifecycle');
+      if (lifecycle !== undefined && !isTaskLifecycle(lifecycle)) {
+        throw new RequestError(400, `unknown lifecycle: ${lifecycle}`);
+      }
+      const patch: TaskPatch = {
+        name: optionalString(req.body, 'name'),
+        lifecycle,
+        description: optionalString(req.body, 'description'),
+        labels: optionalStringArray(req.body, 'labels'),
+        env: optionalStringRecord(req.body, 'env'),
+        secrets: optionalStringArray(req.body, 'secrets'),
+        notes: optionalString(req.body, 'notes'),
+      };
+      asBadRequest(() => updateTask(taskId, patch));
+      return taskStatus(taskId);
+    }
+
+    case 'create_task':
+      return taskStatus(await createTask(createTaskOptions(req.body)));
+
+    case 'create_tasks': {
+      const items = req.body.tasks;
+      if (!Array.isArray(items) || items.length === 0) {
+        throw new RequestError(400, 'tasks must be a non-empty array');
+      }
+      // Checked up front, so a bad entry creates nothing rather than half a batch
+      const batch = items.map((item: unknown, index) => {
+        try {
+          if (!item || typeof item !== 'object' || Array.isArray(item)) {
+            throw new RequestError(400, 'must be an object');
+          }
+          return createTaskOptions(item as Record<string, unknown>);
+        } catch (err) {
+          if (!(err instanceof RequestError)) throw err;
+          throw new RequestError(err.status, `tasks[${index}]: ${err.message}`);
+        }
+      });
+      return createTasks(batch);
+    }
+
+    case 'spawn_agent': {
+      taskStatus(taskId);
+      const agentId = mainAgentId(taskId);
+      if (store.agents[agentId]?.status === 'running') {
+        throw new RequestError(409, 'agent is already running');
+      }
+      const profileId = optionalString(req.body, 'profileId');
+      if (profileId !== undefined) {
+        const def = store.agents[agentId]?.def;
+        const task = store.tasks[taskId];
+        if (profileId && (!def || !task || !findAgentProfile(task.projectId, def, profileId))) {
+          throw new RequestError(400, 'unknown profileId');
+        }
+        // An empty profileId goes back to the agent's plain launch
+        setTaskAgentProfile(taskId, profileId || undefined);
+      }
+      if (req.body.resume === true) await resumeAgentSession(taskId, agentId);
+      else restartAgent(agentId, false);
+      return taskStatus(taskId);
+    }
+
+    case 'send_prompt': {
+      taskStatus(taskId);
+      const text = requireString(req.body, 'text');
+      const agentId = mainAgentId(taskId);
+      if (store.agents[agentId]?.status !== 'running') {
+        throw new RequestError(409, 'agent is not running');
+      }
+      await sendPrompt(taskId, agentId, text);
+      return taskStatus(taskId);
+    }
+
+    case 'delete_tasks':
+      return deleteTasks(
+        requireStringArray(req.body, 'taskIds'),
+        req.body.deleteBranches === true,
+        req.body.force === true,
+      );
+
+    case 'archive_tasks':
+      return archiveTasks(requireStringArray(req.body, 'taskIds'));
+
+    case 'rebase_tasks':
+      return rebaseTasks(requireStringArray(req.body, 'taskIds'), {
+        keepConflicts: req.body.keepConflicts === true,
+      });
+
+    case 'get_task_graph':
+      return getTaskGraph();
+
+    case 'set_task_dependencies': {
+      taskStatus(taskId);
+      const dependsOn = optionalStringArray(req.body, 'dependsOn') ?? [];
+      asBadRequest(() => setTaskDependencies(taskId, dependsOn));
+      return taskStatus(taskId);
+    }
+
+    case 'widen_sparse_checkout': {
+      taskStatus(taskId);
+      const paths = requireStringArray(req.body, 'paths');
+      if (!store.tasks[taskId]?.sparsePaths) {
+        throw new RequestError(409, 'task is not a sparse checkout');
+      }
+      return { paths: await widenSparseCheckout(taskId, paths).catch(badRequest) };
+    }
+
+    case 'snapshot_task': {
+      taskStatus(taskId);
+      const label = optionalString(req.body, 'label') ?? '';
+      if (store.tasks[taskId]?.directMode) {
+        throw new RequestError(409, 'direct-mode tasks have no worktree to snapshot');
+      }
+      return snapshotTask(taskId, label);
+    }
+
+    case 'list_snapshots':
+      taskStatus(taskId);
+      return listTaskSnapshots(taskId);
+
+    case 'restore_snapshot': {
+      taskStatus(taskId);
+      const snapshotId = requireString(req.body, 'snapshotId');
+      if (!(await listTaskSnapshots(taskId)).some((s) => s.id === snapshotId)) {
+        throw new RequestError(404, 'snapshot not found');
+      }
+      return restoreTaskSnapshot(taskId, snapshotId);
+    }
+
+    case 'get_conflicts':
+      taskStatus(taskId);
+      return getTaskConflicts(taskId);
+
+    case 'resolve_conflict': {
+      taskStatus(taskId);
+      const filePath = requireString(req.body, 'path');
+      const content = req.body.content;
+      if (typeof content !== 'string') throw new RequestError(400, 'content must be a string');
+      await resolveTaskConflict(taskId, filePath, content).catch(badRequest);
+      return getTaskConflicts(taskId);
+    }
+
+    case 'continue_merge':
+      taskStatus(taskId);
+      return continueTaskMerge(taskId).catch((err: unknown) => {
+        // Conflicts left to resolve aren't a malformed request
+        if (isAppError(err, 'merge_conflict')) throw new RequestError(409, err.message);
+        return badRequest(err);
+      });
+
+    case 'abort_merge':
+      taskStatus(taskId);
+      await abortTaskMerge(taskId);
+      return getTaskConflicts(taskId);
+
+    case 'get_task_summary':
+      taskStatus(taskId);
+      return getTaskSummary(taskId);
+
+    case 'get_task_notes':
+      taskStatus(taskId);
+      return { notes: store.tasks[taskId].notes };
+
+    case 'set_task_notes': {
+      taskStatus(taskId);
+      const notes = req.body.notes;
+      if (typeof notes !== 'string') throw new RequestError(400, 'notes must be a string');
+      if (req.body.append === true) {
+        if (!notes.trim()) throw new RequestError(400, 'notes must be a non-empty string');
+        appendTaskNotes(taskId, notes);
+      } else {
+        updateTaskNotes(taskId, notes);
+      }
+      return { notes: store.tasks[taskId].notes };
+    }
+
+    case 'get_task_time':
+      taskStatus(taskId);
+      return getTaskTime(taskId);
+
+    case 'start_task_timer':
+    case 'pause_task_timer':
+      taskStatus(taskId);
+      return setTaskTimer(taskId, req.method === 'start_task_timer');
+
+    case 'get_project_time': {
+      const projectId = requireString(req.body, 'projectId');
+      if (!getProject(projectId)) throw new RequestError(404, 'project not found');
+      return getProjectTime(projectId);
+    }
+
+    case 'gc_workspace': {
+      const projectId = requireString(req.body, 'projectId');
+      if (!getProject(projectId)) throw new RequestError(404, 'project not found');
+      // Removing is the confirmation, so it has to be asked for; worktrees by their dry-run
+      // paths, branches by their dry-run tips
+      return gcWorkspace(projectId, req.body.dryRun !== false, {
+        worktrees: optionalStringArray(req.body, 'worktrees'),
+        branches: optionalStringRecord(req.body, 'branches'),
+        force: req.body.force === true,
+      }).catch(badRequest);
+    }
+
+    case 'get_task_log': {
+      taskStatus(taskId);
+      const offset = optionalOffset(req.body) ?? 0;
+      return getTaskLog(taskId, { limit: optionalLimit(req.body), offset });
+    }
+
+    case 'get_blame':
+      taskStatus(taskId);
+      return getTaskBlame(taskId, requireString(req.body, 'path')).catch(badRequest);
+
+    case 'start_diff': {
+      taskStatus(taskId);
+      // The stream keeps its chunks; callers here poll them with read_diff_chunks
+      const { handle, base } = await startTaskDiff(taskId, () => {}).catch(badRequest);
+      return { handle, base };
+    }
+
+    case 'read_diff_chunks': {
+      const after = req.body.after;
+      if (after !== undefined && after !== null && !Number.isInteger(after)) {
+        throw new RequestError(400, 'after must be an integer');
+      }
+      const handle = requireString(req.body, 'handle');
+      const chunks = await readTaskDiffChunks(
+        handle,
+        typeof after === 'number' ? after : undefined,
+      ).catch(badRequest);
+      return { chunks };
+    }
+
+    case 'load_more_diff': {
+      const from = req.body.from;
+      if (typeof from !== 'number' || !Number.isInteger(from) || from < 0) {
+        throw new RequestError(400, 'from must be a non-negative integer');
+      }
+      const handle = requireString(req.body, 'handle');
+      return loadMoreTaskDiff(handle, requireString(req.body, 'path'), from).catch(badRequest);
+    }
+
+    case 'cancel_diff':
+      return { cancelled: await cancelTaskDiff(requireString(req.body, 'handle')) };
+
+    case 'list_review_comments':
+      taskStatus(taskId);
+      return listReviewComments(taskId);
+
+    case 'add_review_comment': {
+      taskStatus(taskId);
+      const startLine = optionalLine(req.body, 'startLine');
+      if (startLine === undefined) throw new RequestError(400, 'startLine is required');
+      const comment = {
+        path: requireString(req.body, 'path'),
+        startLine,
+        endLine: optionalLine(req.body, 'endLine'),
+        body: requireString(req.body, 'body'),
+      };
+      return asBadRequest(() => addReviewComment(taskId, comment));
+    }
+
+    case 'resolve_review_comment': {
+      taskStatus(taskId);
+      const commentId = requireString(req.body, 'commentId');
+      if (!listReviewComments(taskId).some((c) => c.id === commentId)) {
+        throw new RequestError(404, 'review comment not found');
+      }
+      resolveReviewComment(taskId, commentId, req.body.resolved !== false);
+      return listReviewComments(taskId);
+    }
+
+    case 'send_review_comments': {
+      taskStatus(taskId);
+      if (store.agents[mainAgentId(taskId)]?.status !== 'running') {
+        throw new RequestError(409, 'agent is not running');
+      }
+      if (listReviewComments(taskId, { unresolvedOnly: true }).length === 0) {
+        throw new RequestError(409, 'no unresolved review comments');
+      }
+      return sendReviewComments(taskId);
+    }
+
+    case 'queue_run': {
+      taskStatus(taskId);
+      const prompt = requireString(req.body, 'prompt');
+      const agentDefId = optionalString(req.body, 'agentId');
+      if (agentDefId && !store.availableAgents.some((a) => a.id === agentDefId)) {
+        throw new RequestError(400, 'unknown agentId');
+      }
+      return queueAgentRun(taskId, prompt, {
+        agentDefId,
+        startAt: optionalTime(req.body, 'startAt'),
+      }).catch(badRequest);
+    }
+
+    case 'list_runs':
+      return listScheduledRuns();
+
+    case 'cancel_run':
+      await cancelScheduledRun(requireString(req.body, 'runId')).catch(badRequest);
+      return listScheduledRuns();
+
+    case 'get_merge_queue':
+      return listMergeQueue();
+
+    case 'enqueue_merges':
+      return enqueueMerges(requireStringArray(req.body, 'taskIds'), {
+        squash: req.body.squash === true,
+      });
+
+    case 'remove_from_merge_queue':
+      await removeFromMergeQueue(requireString(req.body, 'entryId')).catch(badRequest);
+      return listMergeQueue();
+
+    case 'resume_merge_queue': {
+      const projectId = requireString(req.body, 'projectId');
+      if (!store.projects.some((p) => p.id === projectId)) {
+        throw new RequestError(404, 'project not found');
+      }
+      await resumeMergeQueue(projectId);
+      return listMergeQueue();
+    }
+
+    // Values go in but never come back out over the API
+    case 'list_secrets':
+      return listSecrets();
+
+    case 'set_secret': {
+      const name = requireString(req.body, 'name');
+      const value = requireString(req.body, 'value');
+      const inject = typeof req.body.inject === 'boolean' ? req.body.inject : undefined;
+      return setSecret(name, value, inject).catch(badRequest);
+    }
+
+    case 'delete_secret':
+      await deleteSecret(requireString(req.body, 'name'));
+      return listSecrets();
+
+    case 'list_trash':
+      return listDeletedTasks();
+
+    case 'restore_deleted_task': {
+      const entryId = requireString(req.body, 'entryId');
+      if (!(await listDeletedTasks()).some((e) => e.id === entryId)) {
+        throw new RequestError(404, 'trash entry not found');
+      }
+      return taskStatus(await restoreDeletedTask(entryId));
+    }
+
+    case 'undo_last_operation': {
+      const restoredId = await undoLastOperation();
+      if (!restoredId) throw new RequestError(404, 'nothing to undo');
+      return taskStatus(restoredId);
+    }
+
+    case 'list_sessions':
+      return listSessions({
+        taskId: optionalString(req.body, 'taskId'),
+        projectId: optionalString(req.body, 'projectId'),
+      });
+
+    case 'query_history':
+      return queryHistory({
+        taskId: optionalString(req.body, 'taskId'),
+        projectId: optionalString(req.body, 'projectId'),
+        // Unknown types are rejected by the backend
+        types: optionalStringArray(req.body, 'types') as TaskEventType[] | undefined,
+        since: optionalTime(req.body, 'since'),
+        until: optionalTime(req.body, 'until'),
+        limit: optionalLimit(req.body),
+      }).catch(badRequest);
+
+    case 'get_metrics':
+      return getMetrics({
+        since: optionalTime(req.body, 'since'),
+        until: optionalTime(req.body, 'until'),
+        projectId: optionalString(req.body, 'projectId'),
+      }).catch(badRequest);
+
+    case 'search_transcripts':
+      return searchTranscripts(requireString(req.body, 'query'), {
+        taskId: optionalString(req.body, 'taskId'),
+        limit: optionalLimit(req.body),
+      }).catch(badRequest);
+
+    case 'get_disk_usage': {
+      const usage = await getDiskUsage(req.body.refresh === true);
+      return { ...usage, tasks: taskDiskUsage(usage) };
+    }
+
+    case 'get_resource_usage':
+      return getResourceUsage();
+  }
+}
+
+async function respond(req: AutomationRequest): Promise<void> {
+  let response: AutomationResponse;
+  try {
+    response = { id: req.id, result: await handle(req) };
+  } catch (err) {
+    const status = err instanceof RequestError ? err.status : 500;
+    const message = err instanceof Error ? err.message : String(err);
+    response = { id: req.id, error: { status, message } };
+  }
+  await invoke(IPC.AutomationResponse, { ...response });
+}
+
+/**
+ * Answer requests from the local automation API with the store, so scripts
+ * see and change exactly what the UI does. Returns an unsubscribe function.
+ */
+export function startAutomationBridge(): () => void {
+  return window.electron.ipcRenderer.on(IPC.AutomationRequest, (data: unknown) => {
+    respond(data as AutomationRequest).catch(console.error);
+  });
+}
@@ -30,6 +30,8 @@ function persistedSnapshot(): string {
     editorCommand: store.editorCommand,
     customAgents: store.customAgents,
     customSlashCommands: store.customSlashCommands,
+    taskTemplates: store.taskTemplates,
+    promptTemplates: store.promptTemplates,
     tasks: Object.fromEntries(
       [...store.taskOrder, ...store.collapsedTaskOrder]
         .filter((id) => store.tasks[id])
@@ -44,6 +46,8 @@ function persistedSnapshot(): string {
               directMode: t.directMode,
               savedInitialPrompt: t.savedInitialPrompt,
               collapsed: t.collapsed,
+              description: t.description,
+              labels: t.labels?.join('\n'),
             },
           ];
         }),
@@ -0,0 +1,120 @@
+import { produce } from 'solid-js/store';
+import { invoke } from '../lib/ipc';
+import { openDialog } from '../lib/dialog';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore, updateWindowTitle } from './core';
+import { getProjectPath } from './projects';
+import { markAgentSpawned, rescheduleTaskStatusPolling } from './taskStatus';
+import type { ImportTaskResult, RecordingInfo } from '../ipc/types';
+import type { Agent, Task } from './types';
+
+function str(v: unknown): string | undefined {
+  return typeof v === 'string' && v ? v : undefined;
+}
+
+/**
+ * Export a task as a portable bundle into a folder the user picks. Env
+ * overrides are left out since they commonly hold secrets.
+ */
+export async function exportTaskBundle(taskId: string): Promise<string | null> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode || task.archived) return null;
+  const outDir = await openDialog({ directory: true, multiple: false });
+  if (!outDir) return null;
+
+  const agentDef = task.agentIds[0] ? store.agents[task.agentIds[0]]?.def : task.savedAgentDef;
+  return invoke<string>(IPC.ExportTask, {
+    taskId,
+    worktreePath: task.worktreePath,
+    branchName: task.branchName,
+    outDir: outDir as string,
+    metadata: {
+      name: task.name,
+      notes: task.notes,
+      description: task.description,
+      labels: task.labels,
+      last_prompt: task.lastPrompt,
+      initial_prompt: task.savedInitialPrompt,
+      agent_def_id: agentDef?.id,
+    },
+  });
+}
+
+/** Recreate a task from a bundle folder the user picks. Returns the new task id. */
+export async function importTaskBundle(projectId: string): Promise<string | null> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  const bundlePath = await openDialog({ directory: true, multiple: false });
+  if (!bundlePath) return null;
+
+  const result = await invoke<ImportTaskResult>(IPC.ImportTask, {
+    projectRoot,
+    bundlePath: bundlePath as string,
+    symlinkDirs: [],
+  });
+  const meta = result.metadata;
+  const agentDef =
+    store.availableAgents.find((a) => a.id === meta.agent_def_id) ?? store.availableAgents[0];
+  if (!agentDef) throw new Error('No agents available');
+  const name = str(meta.name) ?? result.branch_name;
+
+  const agentId = crypto.randomUUID();
+  const task: Task = {
+    id: result.id,
+    name,
+    projectId,
+    branchName: result.branch_name,
+    worktreePath: result.worktree_path,
+    agentIds: [agentId],
+    shellAgentIds: [],
+    notes: str(meta.notes) ?? '',
+    lastPrompt: str(meta.last_prompt) ?? '',
+    savedInitialPrompt: str(meta.initial_prompt),
+    description: str(meta.description),
+    labels: Array.isArray(meta.labels)
+      ? meta.labels.filter((l): l is string => typeof l === 'string')
+      : undefined,
+    createdAt: Date.now(),
+    lifecycle: 'agent-running',
+  };
+  const agent: Agent = {
+    id: agentId,
+    taskId: result.id,
+    def: agentDef,
+    resumed: false,
+    status: 'running',
+    exitCode: null,
+    signal: null,
+    lastOutput: [],
+    generation: 0,
+  };
+
+  setStore(
+    produce((s) => {
+      s.tasks[result.id] = task;
+      s.agents[agentId] = agent;
+      s.taskOrder.push(result.id);
+      s.activeTaskId = result.id;
+      s.activeAgentId = agentId;
+    }),
+  );
+  markAgentSpawned(agentId);
+  rescheduleTaskStatusPolling();
+  updateWindowTitle(name);
+  return result.id;
+}
+
+/** A task's session recordings, newest first. */
+export function listTaskRecordings(taskId: string): Promise<RecordingInfo[]> {
+  return invoke<RecordingInfo[]>(IPC.ListRecordings, { taskId });
+}
+
+/** Copy a recording into a folder the user picks. Returns the written path. */
+export async function exportTaskRecording(
+  taskId: string,
+  recordingId: string,
+): Promise<string | null> {
+  const outDir = await openDialog({ directory: true, multiple: false });
+  if (!outDir) return null;
+  return invoke<string>(IPC.ExportRecording, { taskId, recordingId, outDir: outDir as string });
+}
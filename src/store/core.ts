@@ -19,6 +19,8 @@ export const [store, setStore] = createStore<AppStore>({
   customAgents: [],
   customSlashCommands: [],
   cliSlashCommands: [],
+  taskTemplates: [],
+  promptTemplates: [],
   showNewTaskDialog: false,
   sidebarVisible: true,
   fontScales: {},
@@ -49,6 +51,7 @@ export const [store, setStore] = createStore<AppStore>({
   editorCommand: '',
   newTaskDropUrl: null,
   newTaskPrefillPrompt: null,
+  newTaskDeepLink: null,
   missingProjectIds: {},
   remoteAccess: {
     enabled: false,
@@ -0,0 +1,46 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore } from './core';
+import { toggleNewTaskDialog } from './navigation';
+import { showNotification } from './notification';
+import type { DeepLinkAction } from '../ipc/types';
+
+/** Project by id, then name, then path. */
+function matchProject(ref: string | undefined): string | undefined {
+  if (!ref) return undefined;
+  const lower = ref.toLowerCase();
+  const project =
+    store.projects.find((p) => p.id === ref) ??
+    store.projects.find((p) => p.name.toLowerCase() === lower) ??
+    store.projects.find((p) => p.path.replace(/\/+$/, '') === ref.replace(/\/+$/, ''));
+  return project?.id;
+}
+
+/**
+ * Open the new task dialog filled in from a link. Links can come from any web
+ * page, so the task is only created (and its agent started) once the user
+ * confirms the dialog.
+ */
+export function applyDeepLink(link: DeepLinkAction): void {
+  const projectId = matchProject(link.project);
+  if (link.project && !projectId) showNotification(`No project matches "${link.project}"`);
+  if (store.showNewTaskDialog) toggleNewTaskDialog(false);
+  setStore('newTaskDeepLink', {
+    name: link.name,
+    prompt: link.prompt,
+    agentId: link.agent,
+    projectId,
+  });
+  toggleNewTaskDialog(true);
+}
+
+/** Apply links that arrived during startup and listen for more. Returns an unsubscribe function. */
+export function startDeepLinkListener(): () => void {
+  const off = window.electron.ipcRenderer.on(IPC.DeepLinkReceived, (data: unknown) => {
+    applyDeepLink(data as DeepLinkAction);
+  });
+  invoke<DeepLinkAction[]>(IPC.TakePendingDeepLinks)
+    .then((links) => links.forEach(applyDeepLink))
+    .catch(console.error);
+  return off;
+}
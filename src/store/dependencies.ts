@@ -0,0 +1,110 @@
+import { store, setStore } from './core';
+import { onTaskLifecycleChange } from './lifecycle';
+import type { Task } from './types';
+
+export interface TaskGraphNode {
+  id: string;
+  name: string;
+  project_id: string;
+  branch_name: string;
+  lifecycle: string | null;
+  /** Some dependency hasn't merged yet. */
+  blocked: boolean;
+}
+
+export interface TaskGraph {
+  nodes: TaskGraphNode[];
+  /** `from` must merge before `to`. */
+  edges: Array<{ from: string; to: string }>;
+}
+
+/**
+ * Dependencies of a task that still have to merge first. A dependency that
+ * was closed no longer blocks: it either merged with cleanup or was dropped.
+ */
+export function getBlockingTasks(taskId: string): Task[] {
+  const deps = store.tasks[taskId]?.dependsOn ?? [];
+  return deps
+    .map((id) => store.tasks[id])
+    .filter((dep): dep is Task => !!dep && dep.lifecycle !== 'merged');
+}
+
+export function isTaskBlocked(taskId: string): boolean {
+  return getBlockingTasks(taskId).length > 0;
+}
+
+/** Tasks that declared `taskId` as a dependency. */
+export function getTaskDependents(taskId: string): Task[] {
+  return Object.values(store.tasks).filter((t) => t.dependsOn?.includes(taskId));
+}
+
+/** Whether `from` reaches `target` by following dependencies. */
+function dependsTransitively(from: string, target: string, seen = new Set<string>()): boolean {
+  if (from === target) return true;
+  if (seen.has(from)) return false;
+  seen.add(from);
+  return (store.tasks[from]?.dependsOn ?? []).some((id) => dependsTransitively(id, target, seen));
+}
+
+/** Check that `taskId` in `projectId` may depend on each of `dependsOn`. */
+export function validateTaskDependencies(
+  taskId: string | null,
+  projectId: string,
+  dependsOn: string[],
+): void {
+  for (const depId of dependsOn) {
+    const dep = store.tasks[depId];
+    if (!dep) throw new Error(`Task not found: ${depId}`);
+    if (dep.projectId !== projectId) throw new Error(`${dep.name} is in another project`);
+    if (dep.directMode) throw new Error(`${dep.name} works on the main branch directly`);
+    if (taskId && dependsTransitively(depId, taskId)) {
+      throw new Error(`${dep.name} already depends on this task`);
+    }
+  }
+}
+
+/** Replace the tasks `taskId` depends on; an empty list clears them. */
+export function setTaskDependencies(taskId: string, dependsOn: string[]): void {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const deps = [...new Set(dependsOn)];
+  validateTaskDependencies(taskId, task.projectId, deps);
+  setStore('tasks', taskId, 'dependsOn', deps.length > 0 ? deps : undefined);
+}
+
+export function getTaskGraph(): TaskGraph {
+  const tasks = Object.values(store.tasks);
+  return {
+    nodes: tasks.map((t) => ({
+      id: t.id,
+      name: t.name,
+      project_id: t.projectId,
+      branch_name: t.branchName,
+      lifecycle: t.lifecycle ?? null,
+      blocked: isTaskBlocked(t.id),
+    })),
+    edges: tasks.flatMap((t) =>
+      (t.dependsOn ?? []).filter((id) => store.tasks[id]).map((from) => ({ from, to: t.id })),
+    ),
+  };
+}
+
+/**
+ * When a task merges, offer its dependents a rebase onto main, which now
+ * holds the work they were built on. Returns an unsubscribe function.
+ */
+export function startDependencyWatcher(): () => void {
+  return onTaskLifecycleChange(({ taskId, to }) => {
+    if (to !== 'merged') return;
+    const name = store.tasks[taskId]?.name;
+    if (!name) return;
+    for (const dependent of getTaskDependents(taskId)) {
+      if (dependent.directMode || dependent.archived) continue;
+      setStore('tasks', dependent.id, 'dependencyMerged', name);
+    }
+  });
+}
+
+export function dismissDependencyRebase(taskId: string): void {
+  setStore('tasks', taskId, 'dependencyMerged', undefined);
+}
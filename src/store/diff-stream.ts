@@ -0,0 +1,65 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import type { DiffChunk, DiffPage } from '../ipc/types';
+
+export interface TaskDiffStream {
+  handle: string;
+  /** The commit the task's changes are compared against. */
+  base: string;
+  /** Stop the stream; `onChunk` isn't called again. */
+  cancel: () => Promise<boolean>;
+}
+
+/**
+ * Stream everything the task changed, for diffs too big to load at once.
+ * `onChunk` gets each chunk as git produces it, ending with one that's
+ * `done`; files past their size cap end with `more` set for `loadMoreTaskDiff`.
+ */
+export async function startTaskDiff(
+  taskId: string,
+  onChunk: (chunk: DiffChunk) => void,
+): Promise<TaskDiffStream> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const handle = crypto.randomUUID();
+  const unsubscribe = window.electron.ipcRenderer.on(IPC.DiffChunk, (data: unknown) => {
+    const chunk = data as DiffChunk;
+    if (chunk.handle !== handle) return;
+    if (chunk.done) unsubscribe();
+    onChunk(chunk);
+  });
+  const cancel = () => {
+    unsubscribe();
+    return cancelTaskDiff(handle);
+  };
+  try {
+    const { base } = await invoke<{ handle: string; base: string }>(IPC.StartDiff, {
+      worktreePath: task.worktreePath,
+      handle,
+    });
+    return { handle, base, cancel };
+  } catch (err) {
+    unsubscribe();
+    throw err;
+  }
+}
+
+/** Chunks a stream sent after `after` (a `seq`), for callers that poll instead of listening. */
+export function readTaskDiffChunks(handle: string, after?: number): Promise<DiffChunk[]> {
+  return invoke<DiffChunk[]>(IPC.ReadDiffChunks, { handle, after });
+}
+
+/** The next page of a file the stream cut off, starting at its `more` line. */
+export function loadMoreTaskDiff(
+  handle: string,
+  filePath: string,
+  from: number,
+): Promise<DiffPage> {
+  return invoke<DiffPage>(IPC.LoadMoreDiff, { handle, filePath, from });
+}
+
+/** Stop a diff stream and free what it holds. Resolves false when the handle is unknown. */
+export function cancelTaskDiff(handle: string): Promise<boolean> {
+  return invoke<boolean>(IPC.CancelDiff, { handle });
+}
@@ -0,0 +1,84 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import { getProjectBranchPrefix, getProjectPath } from './projects';
+import type { DiskUsage, WorkspaceGcReport } from '../ipc/types';
+import type { Task } from './types';
+
+/** Untouched for this long with no agent running, a task counts as stale. */
+const STALE_AFTER_MS = 7 * 24 * 60 * 60 * 1000;
+
+export interface TaskDiskUsage {
+  taskId: string;
+  name: string;
+  bytes: number;
+  stale: boolean;
+}
+
+/**
+ * How much space the worktrees of all projects take. Sizes are cached in
+ * the main process for ten minutes; pass `refresh` after cleaning up.
+ */
+export function getDiskUsage(refresh = false): Promise<DiskUsage> {
+  return invoke<DiskUsage>(IPC.GetDiskUsage, {
+    projectRoots: store.projects.map((p) => p.path),
+    refresh,
+  });
+}
+
+function isStale(task: Task, now: number): boolean {
+  if (task.mergedAt !== undefined) return true;
+  if (task.agentIds.some((id) => store.agents[id]?.status === 'running')) return false;
+  const touched = task.updatedAt ?? task.createdAt;
+  return touched !== undefined && now - touched > STALE_AFTER_MS;
+}
+
+/** Tasks with a worktree on disk, largest first. */
+export function taskDiskUsage(usage: DiskUsage): TaskDiskUsage[] {
+  const byPath = new Map<string, number>();
+  for (const project of usage.projects) {
+    for (const w of project.worktrees) byPath.set(w.path, w.bytes);
+  }
+  const now = Date.now();
+  return Object.values(store.tasks)
+    .filter((task) => !task.directMode && !task.archived && byPath.has(task.worktreePath))
+    .map((task) => ({
+      taskId: task.id,
+      name: task.name,
+      bytes: byPath.get(task.worktreePath) ?? 0,
+      stale: isStale(task, now),
+    }))
+    .sort((a, b) => b.bytes - a.bytes);
+}
+
+/** The heaviest stale tasks, worth suggesting for cleanup. */
+export function cleanupCandidates(usage: DiskUsage, limit = 5): TaskDiskUsage[] {
+  return taskDiskUsage(usage).filter((t) => t.stale).slice(0, limit);
+}
+
+/**
+ * Worktrees, worktree metadata and task branches of a project that no task
+ * owns, archived and collapsed ones included. Removes them unless `dryRun`:
+ * only the worktree paths and branches in `confirm` (name to the tip a dry
+ * run showed), and dirty worktrees and branches with unmerged commits only
+ * with `force`.
+ */
+export function gcWorkspace(
+  projectId: string,
+  dryRun: boolean,
+  confirm: { worktrees?: string[]; branches?: Record<string, string>; force?: boolean } = {},
+): Promise<WorkspaceGcReport> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) return Promise.reject(new Error('Project not found'));
+  const tasks = Object.values(store.tasks).filter((t) => t.projectId === projectId);
+  return invoke<WorkspaceGcReport>(IPC.GcWorkspace, {
+    projectRoot,
+    branchNames: tasks.map((t) => t.branchName),
+    worktreePaths: tasks.map((t) => t.worktreePath),
+    branchPrefix: getProjectBranchPrefix(projectId),
+    dryRun,
+    confirmedWorktrees: confirm.worktrees ?? [],
+    confirmedBranches: confirm.branches ?? {},
+    force: confirm.force ?? false,
+  });
+}
@@ -0,0 +1,19 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type { GitProgressEvent } from '../ipc/types';
+
+/** Follow progress of long git operations (fetches, worktree checkouts). Returns an unsubscribe. */
+export function onGitProgress(listener: (event: GitProgressEvent) => void): () => void {
+  return window.electron.ipcRenderer.on(IPC.GitProgress, (data: unknown) =>
+    listener(data as GitProgressEvent),
+  );
+}
+
+/**
+ * Stop a running git operation started with this `opId`; the call that
+ * started it fails with `operation_cancelled`. Resolves false when it had
+ * already finished.
+ */
+export function cancelGitOperation(opId: string): Promise<boolean> {
+  return invoke<boolean>(IPC.CancelOperation, { opId });
+}
@@ -0,0 +1,37 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type {
+  ActivityMetrics,
+  HistoryEvent,
+  TaskEventType,
+  TranscriptMatch,
+} from '../ipc/types';
+
+export interface HistoryQuery {
+  taskId?: string;
+  projectId?: string;
+  types?: TaskEventType[];
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+/** Recorded task events across tasks, including deleted ones, newest first. */
+export function queryHistory(query: HistoryQuery = {}): Promise<HistoryEvent[]> {
+  return invoke<HistoryEvent[]>(IPC.QueryHistory, { ...query });
+}
+
+/** Tasks, agent time and token spend over a range; the last 30 days by default. */
+export function getMetrics(
+  range: { since?: number; until?: number; projectId?: string } = {},
+): Promise<ActivityMetrics> {
+  return invoke<ActivityMetrics>(IPC.GetMetrics, { ...range });
+}
+
+/** Lines of finished agent sessions containing all words of `query`. */
+export function searchTranscripts(
+  query: string,
+  opts: { taskId?: string; limit?: number } = {},
+): Promise<TranscriptMatch[]> {
+  return invoke<TranscriptMatch[]>(IPC.SearchTranscripts, { query, ...opts });
+}
@@ -0,0 +1,24 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type { AppNotification, NotificationKind } from '../ipc/types';
+
+// The inbox lives in the backend so desktop notifications and the in-app
+// list stay in sync; new entries are pushed as IPC.NotificationAdded.
+
+export function listNotifications(): Promise<AppNotification[]> {
+  return invoke<AppNotification[]>(IPC.ListNotifications);
+}
+
+/** Mark the given notifications read, or all of them when `ids` is omitted. */
+export async function markNotificationsRead(ids?: string[]): Promise<void> {
+  await invoke(IPC.MarkNotificationsRead, { ids });
+}
+
+export async function clearNotifications(): Promise<void> {
+  await invoke(IPC.ClearNotifications);
+}
+
+/** Raise a notification for an event only the renderer can observe. */
+export function notify(kind: NotificationKind, title: string, body: string, taskId?: string) {
+  invoke(IPC.Notify, { kind, title, body, taskId }).catch(console.error);
+}
@@ -0,0 +1,70 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore } from './core';
+import { getProjectPath } from './projects';
+import { createTask } from './tasks';
+import { isGitHubUrl } from '../lib/github-url';
+import type { AgentDef, RemoteIssue } from '../ipc/types';
+import type { LinkedIssue } from './types';
+
+function fetchIssue(projectId: string, ref: string): Promise<RemoteIssue> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  return invoke<RemoteIssue>(IPC.FetchRemoteIssue, { projectRoot, ref });
+}
+
+function toLink(issue: RemoteIssue): LinkedIssue {
+  return { number: issue.number, title: issue.title, url: issue.url };
+}
+
+/** `githubUrl` only holds GitHub links; other hosts' issues are kept on `issue`. */
+function githubUrlOf(issue: RemoteIssue): string | undefined {
+  return isGitHubUrl(issue.url) ? issue.url : undefined;
+}
+
+/** First prompt for an agent working on an issue. */
+export function issuePrompt(issue: RemoteIssue): string {
+  const header = `Resolve issue #${issue.number}: ${issue.title}\n${issue.url}`;
+  const body = issue.body.trim();
+  return body ? `${header}\n\n${body}` : header;
+}
+
+/**
+ * Link a task to an issue given as a URL, `#123` or `owner/repo#123`, on
+ * whichever host the project's origin remote points at.
+ */
+export async function linkTaskIssue(taskId: string, issueRef: string): Promise<LinkedIssue> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const issue = await fetchIssue(task.projectId, issueRef);
+  const link = toLink(issue);
+  setStore('tasks', taskId, { issue: link, githubUrl: githubUrlOf(issue) ?? task.githubUrl });
+  return link;
+}
+
+/**
+ * Create a task for an issue: named (and so branched) after the issue, linked
+ * to it, and by default prompting the agent with the issue body.
+ */
+export async function createTaskFromIssue(
+  projectId: string,
+  issueRef: string,
+  opts: { agentDef?: AgentDef; seedPrompt?: boolean } = {},
+): Promise<string> {
+  const issue = await fetchIssue(projectId, issueRef);
+  const agentDef =
+    opts.agentDef ??
+    store.availableAgents.find((a) => a.id === store.lastAgentId) ??
+    store.availableAgents[0];
+  if (!agentDef) throw new Error('No agents available');
+
+  const taskId = await createTask({
+    name: `#${issue.number} ${issue.title}`,
+    agentDef,
+    projectId,
+    githubUrl: githubUrlOf(issue),
+    initialPrompt: opts.seedPrompt === false ? undefined : issuePrompt(issue),
+  });
+  setStore('tasks', taskId, 'issue', toLink(issue));
+  return taskId;
+}
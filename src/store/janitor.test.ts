@@ -0,0 +1,106 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
+import type { Task } from './types';
+
+// A plain object stands in for the SolidJS store
+const mockState = vi.hoisted(() => ({
+  tasks: {} as Record<string, Task>,
+  agents: {} as Record<string, unknown>,
+  taskGitStatus: {} as Record<string, unknown>,
+}));
+vi.mock('./core', () => ({ store: mockState, setStore: vi.fn() }));
+
+vi.mock('../lib/ipc', async () => {
+  const { IPC } = await import('../../electron/ipc/channels');
+  return {
+    invoke: vi.fn(async (channel: string) => {
+      if (channel === IPC.GetBranchTip) return 'tip-now';
+      if (channel === IPC.FindMergedBranches) return [];
+      return undefined;
+    }),
+  };
+});
+
+vi.mock('./projects', () => ({
+  getProject: () => ({ cleanupMergedTasks: true, deleteBranchOnClose: true }),
+  getProjectPath: () => '/repo',
+}));
+
+// Closing is refused with a risk, as the backend does for unmerged commits without force
+const closeTask = vi.hoisted(() =>
+  vi.fn(async (taskId: string, opts: { force?: boolean } = {}) => {
+    if (opts.force) return;
+    Object.assign(mockState.tasks[taskId] ?? {}, {
+      closingStatus: 'error',
+      closingRisk: { branch: 'task/old', tip: 'tip-now', unmerged: 2, unpushed: 0 },
+    });
+  }),
+);
+const keepTask = vi.hoisted(() =>
+  vi.fn((taskId: string) => {
+    Object.assign(mockState.tasks[taskId] ?? {}, {
+      closingStatus: undefined,
+      closingRisk: undefined,
+    });
+  }),
+);
+vi.mock('./tasks', () => ({ closeTask, keepTask, archiveTask: vi.fn() }));
+vi.mock('./lifecycle', () => ({ onTaskLifecycleChange: () => () => {}, transitionTask: vi.fn() }));
+vi.mock('./taskStatus', () => ({ isAgentIdle: () => true }));
+const showNotification = vi.hoisted(() => vi.fn());
+vi.mock('./notification', () => ({ showNotification }));
+
+import { startMergedTaskJanitor } from './janitor';
+
+const task = (over: Partial<Task>): Task => ({
+  id: 't1',
+  name: 'Old fix',
+  projectId: 'p1',
+  branchName: 'task/old',
+  worktreePath: '/nonexistent/wt',
+  agentIds: [],
+  shellAgentIds: [],
+  notes: '',
+  lastPrompt: '',
+  // Merged two days ago, past the default grace period
+  mergedAt: Date.now() - 48 * 3_600_000,
+  ...over,
+});
+
+describe('merged task janitor', () => {
+  let stop: (() => void) | null = null;
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+    closeTask.mockClear();
+    keepTask.mockClear();
+    showNotification.mockClear();
+  });
+
+  afterEach(() => {
+    stop?.();
+    stop = null;
+    vi.useRealTimers();
+  });
+
+  it('forces closing a task whose branch is still at its merged tip', async () => {
+    mockState.tasks = { t1: task({ mergedTip: 'tip-now' }) };
+    stop = startMergedTaskJanitor();
+    await vi.waitFor(() => expect(closeTask).toHaveBeenCalledWith('t1', { force: true }));
+    expect(showNotification).not.toHaveBeenCalled();
+  });
+
+  it('does not force a legacy task without a merged tip, and says it was kept', async () => {
+    mockState.tasks = { legacy: task({ id: 'legacy' }) };
+    stop = startMergedTaskJanitor();
+    await vi.waitFor(() => expect(showNotification).toHaveBeenCalledTimes(1));
+    expect(closeTask).toHaveBeenCalledWith('legacy');
+    expect(keepTask).toHaveBeenCalledWith('legacy');
+    expect(mockState.tasks.legacy?.closingStatus).toBeUndefined();
+    expect(showNotification.mock.calls[0]?.[0]).toContain('2 commits not merged');
+
+    // Left alone on later runs instead of failing every five minutes
+    await vi.advanceTimersByTimeAsync(5 * 60_000);
+    expect(closeTask).toHaveBeenCalledTimes(1);
+    expect(showNotification).toHaveBeenCalledTimes(1);
+  });
+});
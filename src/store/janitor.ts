@@ -0,0 +1,150 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore } from './core';
+import { getProject, getProjectPath } from './projects';
+import { archiveTask, closeTask, keepTask } from './tasks';
+import { onTaskLifecycleChange, transitionTask } from './lifecycle';
+import { isAgentIdle } from './taskStatus';
+import { showNotification } from './notification';
+import type { Task } from './types';
+
+/** How often branches are checked against main and due tasks cleaned up. */
+const JANITOR_INTERVAL_MS = 5 * 60_000;
+const DEFAULT_GRACE_HOURS = 24;
+
+/** Tasks left open because they merged before `mergedTip` was recorded; not retried. */
+const skippedLegacyTasks = new Set<string>();
+
+/** Branch tasks of projects that opted into cleaning up merged tasks. */
+function watchedTasks(): Task[] {
+  return Object.values(store.tasks).filter(
+    (task) =>
+      !task.archived &&
+      !task.directMode &&
+      !task.closingStatus &&
+      getProject(task.projectId)?.cleanupMergedTasks === true,
+  );
+}
+
+async function branchTip(task: Task): Promise<string | null> {
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) return null;
+  return invoke<string | null>(IPC.GetBranchTip, {
+    projectRoot,
+    branchName: task.branchName,
+  }).catch(() => null);
+}
+
+/** Note when the task merged, and where its branch was then: commits past that are new work. */
+async function recordMerged(taskId: string): Promise<void> {
+  setStore('tasks', taskId, 'mergedAt', Date.now());
+  const task = store.tasks[taskId];
+  const tip = task ? await branchTip(task) : null;
+  if (tip && store.tasks[taskId]) setStore('tasks', taskId, 'mergedTip', tip);
+}
+
+/** Note when tasks got merged: by lifecycle (merged in the app or via a PR), or by git. */
+async function detectMerged(tasks: Task[]): Promise<void> {
+  const byProject = new Map<string, Task[]>();
+  for (const task of tasks) {
+    if (task.mergedAt !== undefined) continue;
+    if (task.lifecycle === 'merged') {
+      await recordMerged(task.id);
+      continue;
+    }
+    byProject.set(task.projectId, [...(byProject.get(task.projectId) ?? []), task]);
+  }
+  for (const [projectId, projectTasks] of byProject) {
+    const projectRoot = getProjectPath(projectId);
+    if (!projectRoot) continue;
+    const merged = await invoke<string[]>(IPC.FindMergedBranches, {
+      projectRoot,
+      branchNames: projectTasks.map((t) => t.branchName),
+    }).catch((err: unknown) => {
+      console.warn('Failed to check for merged branches:', err);
+      return [];
+    });
+    for (const task of projectTasks) {
+      if (!merged.includes(task.branchName) || !store.tasks[task.id]) continue;
+      transitionTask(task.id, 'merged');
+      if (store.tasks[task.id]?.mergedAt === undefined) await recordMerged(task.id);
+    }
+  }
+}
+
+function isDue(task: Task, now: number): boolean {
+  if (task.mergedAt === undefined) return false;
+  const graceHours = getProject(task.projectId)?.cleanupGraceHours ?? DEFAULT_GRACE_HOURS;
+  if (now - task.mergedAt < graceHours * 3_600_000) return false;
+  // Leave tasks someone is still working in
+  if (store.taskGitStatus[task.id]?.has_uncommitted_changes) return false;
+  return !task.agentIds.some((id) => store.agents[id]?.status === 'running' && !isAgentIdle(id));
+}
+
+/**
+ * Close a task that merged before its branch tip was recorded. Without the
+ * tip, commits made after the merge can't be told apart, so nothing is
+ * forced: a branch with commits main doesn't have stays, and the user is
+ * told once.
+ */
+async function closeLegacyTask(task: Task): Promise<void> {
+  await closeTask(task.id);
+  const risk = store.tasks[task.id]?.closingRisk;
+  if (!risk) return;
+  keepTask(task.id);
+  skippedLegacyTasks.add(task.id);
+  const commits = `${risk.unmerged} commit${risk.unmerged === 1 ? '' : 's'}`;
+  showNotification(`Kept merged task ${task.name}: its branch has ${commits} not merged`);
+}
+
+/**
+ * Archive tasks whose branch was merged once the project's grace period is
+ * over. Projects that delete branches on merge get the task closed instead,
+ * since an archived task can't be restored without its branch.
+ */
+async function runJanitor(): Promise<void> {
+  await detectMerged(watchedTasks());
+  const now = Date.now();
+  for (const task of watchedTasks()) {
+    if (skippedLegacyTasks.has(task.id) || !isDue(task, now)) continue;
+    try {
+      if (!(getProject(task.projectId)?.deleteBranchOnClose ?? true)) {
+        await archiveTask(task.id);
+      } else if (task.mergedTip === undefined) {
+        await closeLegacyTask(task);
+      } else {
+        // Merged, maybe squashed, so its commits needn't be in main as they were. Commits made
+        // since are another matter: without `force`, closing stops and asks if any are unmerged
+        const tip = await branchTip(task);
+        await closeTask(task.id, { force: tip === task.mergedTip });
+      }
+    } catch (err) {
+      console.warn(`Failed to clean up merged task ${task.name}:`, err);
+    }
+  }
+}
+
+/** Start cleaning up merged tasks in the background. Returns a stop function. */
+export function startMergedTaskJanitor(): () => void {
+  const offLifecycle = onTaskLifecycleChange(({ taskId, to }) => {
+    if (to === 'merged' && store.tasks[taskId]?.mergedAt === undefined) {
+      void recordMerged(taskId);
+    }
+  });
+  let running = false;
+  const tick = () => {
+    if (running) return;
+    running = true;
+    runJanitor()
+      .catch(console.error)
+      .finally(() => {
+        running = false;
+      });
+  };
+  tick();
+  const timer = setInterval(tick, JANITOR_INTERVAL_MS);
+  return () => {
+    clearInterval(timer);
+    offLifecycle();
+  };
+}
@@ -0,0 +1,34 @@
+import { store, setStore } from './core';
+import { canTransition, type TaskLifecycle } from '../lib/task-lifecycle';
+
+export interface TaskLifecycleChange {
+  taskId: string;
+  from: TaskLifecycle | undefined;
+  to: TaskLifecycle;
+}
+
+type LifecycleListener = (change: TaskLifecycleChange) => void;
+const listeners = new Set<LifecycleListener>();
+
+/** Subscribe to task lifecycle changes. Returns an unsubscribe function. */
+export function onTaskLifecycleChange(listener: LifecycleListener): () => void {
+  listeners.add(listener);
+  return () => {
+    listeners.delete(listener);
+  };
+}
+
+/**
+ * Move a task to a new lifecycle state. Returns false (and changes nothing)
+ * when the transition is not allowed. Tasks without a state yet — e.g.
+ * restored from before lifecycles were tracked — accept any target.
+ */
+export function transitionTask(taskId: string, to: TaskLifecycle): boolean {
+  const task = store.tasks[taskId];
+  if (!task) return false;
+  const from = task.lifecycle;
+  if (from !== undefined && !canTransition(from, to)) return false;
+  setStore('tasks', taskId, 'lifecycle', to);
+  for (const fn of listeners) fn({ taskId, from, to });
+  return true;
+}
@@ -0,0 +1,99 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import { getProject, getProjectPath } from './projects';
+import { getBlockingTasks } from './dependencies';
+import { transitionTask } from './lifecycle';
+import { recordMergedLines } from './completion';
+import type { BulkTaskResult } from './tasks';
+import type { MergeQueueEntry, MergeQueueState } from '../ipc/types';
+
+export function listMergeQueue(): Promise<MergeQueueState> {
+  return invoke<MergeQueueState>(IPC.ListMergeQueue);
+}
+
+/**
+ * Queue a task to land on its project's main branch: the backend rebases it
+ * onto main as the tasks ahead of it left it, runs the project's checks and
+ * merges. Dependencies must be merged or queued; it waits for queued ones to
+ * merge, and fails if one of them doesn't.
+ */
+export async function enqueueMerge(
+  taskId: string,
+  options: { squash?: boolean; message?: string } = {},
+): Promise<MergeQueueEntry> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  if (task.directMode) throw new Error('Direct-mode tasks already work on the main branch');
+  if (task.archived) throw new Error('Task is archived');
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) throw new Error('Project not found');
+
+  const blockers = getBlockingTasks(taskId);
+  if (blockers.length > 0) {
+    const { entries } = await listMergeQueue();
+    const queued = entries
+      .filter((e) => e.status !== 'failed' && e.status !== 'cancelled')
+      .map((e) => e.task_id);
+    const missing = blockers.filter((t) => !queued.includes(t.id));
+    if (missing.length > 0) {
+      throw new Error(`Merge or queue ${missing.map((t) => t.name).join(', ')} first`);
+    }
+  }
+
+  return invoke<MergeQueueEntry>(IPC.EnqueueMerge, {
+    taskId,
+    projectRoot,
+    branchName: task.branchName,
+    worktreePath: task.worktreePath,
+    squash: options.squash ?? false,
+    message: options.message,
+    checks: getProject(task.projectId)?.checks ?? [],
+    dependsOn: blockers.map((t) => t.id),
+  });
+}
+
+/** Queue tasks in the given order, with a result per task instead of stopping at a failure. */
+export async function enqueueMerges(
+  taskIds: string[],
+  options: { squash?: boolean } = {},
+): Promise<BulkTaskResult[]> {
+  const results: BulkTaskResult[] = [];
+  for (const taskId of [...new Set(taskIds)]) {
+    try {
+      await enqueueMerge(taskId, options);
+      results.push({ taskId, ok: true });
+    } catch (err) {
+      results.push({ taskId, ok: false, error: err instanceof Error ? err.message : String(err) });
+    }
+  }
+  return results;
+}
+
+export function removeFromMergeQueue(entryId: string): Promise<void> {
+  return invoke(IPC.RemoveFromMergeQueue, { entryId });
+}
+
+/** Carry on with a project's merge queue after the failure that paused it was dealt with. */
+export function resumeMergeQueue(projectId: string): Promise<void> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) return Promise.reject(new Error('Project not found'));
+  return invoke(IPC.ResumeMergeQueue, { projectRoot });
+}
+
+function applyEntry(entry: MergeQueueEntry): void {
+  if (!store.tasks[entry.task_id]) return;
+  if (entry.status === 'merging' || entry.status === 'failed') {
+    transitionTask(entry.task_id, entry.status);
+  } else if (entry.status === 'merged') {
+    transitionTask(entry.task_id, 'merged');
+    recordMergedLines(entry.lines_added ?? 0, entry.lines_removed ?? 0);
+  }
+}
+
+/** Follow the backend's merge queue into task lifecycles. Returns an unsubscribe. */
+export function startMergeQueueListener(): () => void {
+  return window.electron.ipcRenderer.on(IPC.MergeQueueChanged, (data: unknown) =>
+    applyEntry(data as MergeQueueEntry),
+  );
+}
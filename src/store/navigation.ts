@@ -56,6 +56,7 @@ export function toggleNewTaskDialog(show?: boolean): void {
   if (!shouldShow) {
     setStore('newTaskDropUrl', null);
     setStore('newTaskPrefillPrompt', null);
+    setStore('newTaskDeepLink', null);
   }
   setStore('showNewTaskDialog', shouldShow);
 }
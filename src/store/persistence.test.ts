@@ -0,0 +1,100 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import type { Task } from './types';
+
+// A plain object stands in for the SolidJS store; setStore applies produce() to it.
+const mockState: Record<string, unknown> = {};
+vi.mock('./core', () => ({
+  store: new Proxy({}, { get: (_target, prop: string) => mockState[prop] }),
+  setStore: (fn: (s: Record<string, unknown>) => void) => fn(mockState),
+}));
+
+// SaveAppState keeps the JSON for the LoadAppState that follows.
+let savedJson: string | null = null;
+vi.mock('../lib/ipc', async () => {
+  const { IPC } = await import('../../electron/ipc/channels');
+  return {
+    invoke: vi.fn(async (channel: string, args?: { json: string }) => {
+      if (channel === IPC.SaveAppState && args) savedJson = args.json;
+      return channel === IPC.LoadAppState ? savedJson : undefined;
+    }),
+  };
+});
+
+vi.mock('./taskStatus', () => ({ markAgentSpawned: vi.fn() }));
+vi.mock('./terminals', () => ({ syncTerminalCounter: vi.fn() }));
+vi.mock('./projects', () => ({ randomPastelColor: () => '#abcdef' }));
+vi.mock('../lib/i18n', () => ({ isAppLocale: () => false, getPreferredLocale: () => 'en' }));
+
+import { loadState, saveState } from './persistence';
+
+function resetState(): void {
+  for (const key of Object.keys(mockState)) delete mockState[key];
+  Object.assign(mockState, {
+    projects: [],
+    lastProjectId: null,
+    lastAgentId: null,
+    taskOrder: [],
+    collapsedTaskOrder: [],
+    tasks: {},
+    agents: {},
+    terminals: {},
+    activeTaskId: null,
+    fontScales: {},
+    panelSizes: {},
+    availableAgents: [],
+    customAgents: [],
+    customSlashCommands: [],
+    taskTemplates: [],
+    promptTemplates: [],
+  });
+}
+
+const task = (over: Partial<Task> = {}): Task => ({
+  id: 't1',
+  name: 'Fix login',
+  projectId: 'p1',
+  branchName: 'task/fix-login',
+  worktreePath: '/nonexistent/wt',
+  agentIds: [],
+  shellAgentIds: [],
+  notes: '',
+  lastPrompt: '',
+  ...over,
+});
+
+async function roundTrip(t: Task): Promise<Task | undefined> {
+  resetState();
+  Object.assign(mockState, { taskOrder: [t.id], tasks: { [t.id]: t } });
+  await saveState();
+  resetState();
+  await loadState();
+  return (mockState.tasks as Record<string, Task>)[t.id];
+}
+
+describe('task environment persistence', () => {
+  beforeEach(() => {
+    savedJson = null;
+  });
+
+  it('keeps env and the names of the secrets a task lists', async () => {
+    const restored = await roundTrip(
+      task({ env: { NODE_ENV: 'test' }, secrets: ['OPENAI_API_KEY'] }),
+    );
+    expect(restored?.env).toEqual({ NODE_ENV: 'test' });
+    expect(restored?.secrets).toEqual(['OPENAI_API_KEY']);
+  });
+
+  it('drops entries that are not environment variable names', async () => {
+    const restored = await roundTrip(
+      task({ env: { 'BAD-NAME': 'x', GOOD: 'y' }, secrets: ['1BAD', 'GOOD_KEY'] }),
+    );
+    expect(restored?.env).toEqual({ GOOD: 'y' });
+    expect(restored?.secrets).toEqual(['GOOD_KEY']);
+  });
+
+  it('leaves both out of tasks without them', async () => {
+    const restored = await roundTrip(task());
+    expect(restored?.env).toBeUndefined();
+    expect(restored?.secrets).toBeUndefined();
+  });
+});
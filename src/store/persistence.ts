@@ -13,12 +13,17 @@ import type {
   PersistedWindowState,
   Project,
   SlashCommand,
+  TaskTemplate,
+  PromptTemplate,
 } from './types';
-import type { AgentDef } from '../ipc/types';
+import type { AgentDef, TestRunResult } from '../ipc/types';
 import { DEFAULT_TERMINAL_FONT, isTerminalFont } from '../lib/fonts';
 import { isLookPreset } from '../lib/look';
 import { isAppLocale, getPreferredLocale } from '../lib/i18n';
 import { syncTerminalCounter } from './terminals';
+import { isTaskLifecycle } from '../lib/task-lifecycle';
+import { isEnvVarName } from '../lib/env';
+import { parseReviewComments } from '../lib/review-comments';
 
 function stripControlChars(input: string): string {
   let sanitized = '';
@@ -59,6 +64,8 @@ export async function saveState(): Promise<void> {
     customAgents: store.customAgents.length > 0 ? [...store.customAgents] : undefined,
     customSlashCommands:
       store.customSlashCommands.length > 0 ? [...store.customSlashCommands] : undefined,
+    taskTemplates: store.taskTemplates.length > 0 ? [...store.taskTemplates] : undefined,
+    promptTemplates: store.promptTemplates.length > 0 ? [...store.promptTemplates] : undefined,
   };
 
   for (const taskId of store.taskOrder) {
@@ -81,6 +88,26 @@ export async function saveState(): Promise<void> {
       skipPermissions: task.skipPermissions,
       githubUrl: task.githubUrl,
       savedInitialPrompt: task.savedInitialPrompt,
+      adoptedBranch: task.adoptedBranch,
+      sparsePaths: task.sparsePaths,
+      scope: task.scope,
+      env: task.env,
+      secrets: task.secrets,
+      agentProfileId: task.agentProfileId,
+      mergedAt: task.mergedAt,
+      mergedTip: task.mergedTip,
+      baseBranch: task.baseBranch,
+      dependsOn: task.dependsOn,
+      lastTestRun: persistableTestRun(task.lastTestRun),
+      issue: task.issue,
+      lifecycle: task.lifecycle,
+      updatedAt: task.updatedAt,
+      createdAt: task.createdAt,
+      labels: task.labels,
+      description: task.description,
+      archived: task.archived,
+      raceId: task.raceId,
+      reviewComments: task.reviewComments,
     };
   }
 
@@ -104,6 +131,26 @@ export async function saveState(): Promise<void> {
       skipPermissions: task.skipPermissions,
       githubUrl: task.githubUrl,
       savedInitialPrompt: task.savedInitialPrompt,
+      adoptedBranch: task.adoptedBranch,
+      sparsePaths: task.sparsePaths,
+      scope: task.scope,
+      env: task.env,
+      secrets: task.secrets,
+      agentProfileId: task.agentProfileId,
+      mergedAt: task.mergedAt,
+      mergedTip: task.mergedTip,
+      baseBranch: task.baseBranch,
+      dependsOn: task.dependsOn,
+      lastTestRun: persistableTestRun(task.lastTestRun),
+      issue: task.issue,
+      lifecycle: task.lifecycle,
+      updatedAt: task.updatedAt,
+      createdAt: task.createdAt,
+      labels: task.labels,
+      description: task.description,
+      archived: task.archived,
+      raceId: task.raceId,
+      reviewComments: task.reviewComments,
       collapsed: true,
     };
   }
@@ -189,8 +236,65 @@ function parseCustomSlashCommands(v: unknown): SlashCommand[] {
     );
 }
 
+/** The run's output can be large and is only useful live, so it isn't persisted. */
+function persistableTestRun(run: TestRunResult | undefined): TestRunResult | undefined {
+  return run && { ...run, output_tail: '' };
+}
+
+function parseTaskSecrets(v: unknown): string[] | undefined {
+  if (!Array.isArray(v)) return undefined;
+  const names = v.filter((n): n is string => typeof n === 'string' && isEnvVarName(n));
+  return names.length > 0 ? names : undefined;
+}
+
+function parseTaskEnv(v: unknown): Record<string, string> | undefined {
+  if (!v || typeof v !== 'object' || Array.isArray(v)) return undefined;
+  const entries = Object.entries(v).filter(
+    (e): e is [string, string] => isEnvVarName(e[0]) && typeof e[1] === 'string',
+  );
+  return entries.length > 0 ? Object.fromEntries(entries) : undefined;
+}
+
+function parseTaskIds(v: unknown): string[] | undefined {
+  if (!Array.isArray(v)) return undefined;
+  const ids = v.filter((id): id is string => typeof id === 'string');
+  return ids.length > 0 ? ids : undefined;
+}
+
+function parseTaskTemplates(v: unknown): TaskTemplate[] {
+  if (!Array.isArray(v)) return [];
+  const isOptionalString = (x: unknown) => x === undefined || typeof x === 'string';
+  return v.filter(
+    (item: unknown): item is TaskTemplate =>
+      typeof item === 'object' &&
+      item !== null &&
+      typeof (item as TaskTemplate).id === 'string' &&
+      typeof (item as TaskTemplate).name === 'string' &&
+      typeof (item as TaskTemplate).namePattern === 'string' &&
+      typeof (item as TaskTemplate).agentId === 'string' &&
+      isOptionalString((item as TaskTemplate).baseBranch) &&
+      isOptionalString((item as TaskTemplate).initialPrompt) &&
+      Array.isArray((item as TaskTemplate).setupCommands) &&
+      (item as TaskTemplate).setupCommands.every((c) => typeof c === 'string'),
+  );
+}
+
+function parsePromptTemplates(v: unknown): PromptTemplate[] {
+  if (!Array.isArray(v)) return [];
+  return v.filter(
+    (item: unknown): item is PromptTemplate =>
+      typeof item === 'object' &&
+      item !== null &&
+      typeof (item as PromptTemplate).id === 'string' &&
+      typeof (item as PromptTemplate).name === 'string' &&
+      typeof (item as PromptTemplate).body === 'string' &&
+      ((item as PromptTemplate).sendOnSpawn === undefined ||
+        typeof (item as PromptTemplate).sendOnSpawn === 'boolean'),
+  );
+}
+
+/** Saved state as read back; the backend has already migrated it to the current layout. */
 interface LegacyPersistedState {
-  projectRoot?: string;
   projects?: Project[];
   lastProjectId?: string | null;
   lastAgentId?: string | null;
@@ -241,9 +345,8 @@ export async function loadState(): Promise<void> {
     return;
   }
 
-  // Migrate from old format if needed
-  let projects: Project[] = raw.projects ?? [];
-  let lastProjectId: string | null = raw.lastProjectId ?? null;
+  const projects: Project[] = raw.projects ?? [];
+  const lastProjectId: string | null = raw.lastProjectId ?? null;
   const lastAgentId: string | null = raw.lastAgentId ?? null;
 
   // Assign colors to projects that don't have one (backward compat)
@@ -251,22 +354,6 @@ export async function loadState(): Promise<void> {
     if (!p.color) p.color = randomPastelColor();
   }
 
-  if (projects.length === 0 && raw.projectRoot) {
-    const segments = raw.projectRoot.split('/');
-    const name = segments[segments.length - 1] || raw.projectRoot;
-    const id = crypto.randomUUID();
-    projects = [{ id, name, path: raw.projectRoot, color: randomPastelColor() }];
-    lastProjectId = id;
-
-    // Assign this project to all existing tasks
-    for (const taskId of raw.taskOrder) {
-      const pt = raw.tasks[taskId];
-      if (pt && !pt.projectId) {
-        pt.projectId = id;
-      }
-    }
-  }
-
   const restoredRunningAgentIds: string[] = [];
   const today = getLocalDateKey();
 
@@ -339,6 +426,12 @@ export async function loadState(): Promise<void> {
       s.customSlashCommands = parseCustomSlashCommands(
         (raw as unknown as Record<string, unknown>).customSlashCommands,
       );
+      s.taskTemplates = parseTaskTemplates(
+        (raw as unknown as Record<string, unknown>).taskTemplates,
+      );
+      s.promptTemplates = parsePromptTemplates(
+        (raw as unknown as Record<string, unknown>).promptTemplates,
+      );
 
       // Make custom agents findable during task restoration
       for (const ca of s.customAgents) {
@@ -383,6 +476,26 @@ export async function loadState(): Promise<void> {
           skipPermissions: pt.skipPermissions === true,
           githubUrl: pt.githubUrl,
           savedInitialPrompt: pt.savedInitialPrompt,
+          adoptedBranch: pt.adoptedBranch,
+          sparsePaths: pt.sparsePaths,
+          scope: typeof pt.scope === 'string' ? pt.scope : undefined,
+          env: parseTaskEnv(pt.env),
+          secrets: parseTaskSecrets(pt.secrets),
+          agentProfileId: typeof pt.agentProfileId === 'string' ? pt.agentProfileId : undefined,
+          mergedAt: typeof pt.mergedAt === 'number' ? pt.mergedAt : undefined,
+          mergedTip: typeof pt.mergedTip === 'string' ? pt.mergedTip : undefined,
+          baseBranch: typeof pt.baseBranch === 'string' ? pt.baseBranch : undefined,
+          dependsOn: parseTaskIds(pt.dependsOn),
+          lastTestRun: pt.lastTestRun,
+          issue: pt.issue,
+          lifecycle: isTaskLifecycle(pt.lifecycle) ? pt.lifecycle : undefined,
+          updatedAt: pt.updatedAt,
+          createdAt: pt.createdAt,
+          labels: pt.labels,
+          description: pt.description,
+          archived: pt.archived,
+          raceId: pt.raceId,
+          reviewComments: parseReviewComments(pt.reviewComments),
         };
 
         s.tasks[taskId] = task;
@@ -447,6 +560,26 @@ export async function loadState(): Promise<void> {
           skipPermissions: pt.skipPermissions === true,
           githubUrl: pt.githubUrl,
           savedInitialPrompt: pt.savedInitialPrompt,
+          adoptedBranch: pt.adoptedBranch,
+          sparsePaths: pt.sparsePaths,
+          scope: typeof pt.scope === 'string' ? pt.scope : undefined,
+          env: parseTaskEnv(pt.env),
+          secrets: parseTaskSecrets(pt.secrets),
+          agentProfileId: typeof pt.agentProfileId === 'string' ? pt.agentProfileId : undefined,
+          mergedAt: typeof pt.mergedAt === 'number' ? pt.mergedAt : undefined,
+          mergedTip: typeof pt.mergedTip === 'string' ? pt.mergedTip : undefined,
+          baseBranch: typeof pt.baseBranch === 'string' ? pt.baseBranch : undefined,
+          dependsOn: parseTaskIds(pt.dependsOn),
+          lastTestRun: pt.lastTestRun,
+          issue: pt.issue,
+          lifecycle: isTaskLifecycle(pt.lifecycle) ? pt.lifecycle : undefined,
+          updatedAt: pt.updatedAt,
+          createdAt: pt.createdAt,
+          labels: pt.labels,
+          description: pt.description,
+          archived: pt.archived,
+          raceId: pt.raceId,
+          reviewComments: parseReviewComments(pt.reviewComments),
           collapsed: true,
           savedAgentDef: agentDef ?? undefined,
         };
@@ -0,0 +1,104 @@
+import { produce } from 'solid-js/store';
+import { store, setStore } from './core';
+import { isEnvVarName } from '../lib/env';
+import type { AgentDef, AgentProfile, ProjectAgentConfig } from '../ipc/types';
+import type { ProjectAgentProfile, Task } from './types';
+
+/** Launch profiles for an agent in a project: the agent's built-in ones, then the project's. */
+export function getAgentProfiles(projectId: string, agentDef: AgentDef): AgentProfile[] {
+  const project = store.projects.find((p) => p.id === projectId);
+  const own = (project?.agentProfiles ?? []).filter((p) => p.agentId === agentDef.id);
+  return [...(agentDef.profiles ?? []), ...own];
+}
+
+export function findAgentProfile(
+  projectId: string,
+  agentDef: AgentDef,
+  profileId: string | undefined,
+): AgentProfile | undefined {
+  if (!profileId) return undefined;
+  return getAgentProfiles(projectId, agentDef).find((p) => p.id === profileId);
+}
+
+/** What the project's `.parallel-code.toml` sets for an agent. */
+export function getProjectAgentConfig(
+  projectId: string,
+  agentDef: AgentDef,
+): ProjectAgentConfig | undefined {
+  return store.projects.find((p) => p.id === projectId)?.agentConfig?.[agentDef.id];
+}
+
+/**
+ * The profile a task's agent launches with, if it has one and it still
+ * exists. Tasks that didn't pick one get the project config's.
+ */
+export function getTaskAgentProfile(task: Task, agentDef: AgentDef): AgentProfile | undefined {
+  const profileId =
+    task.agentProfileId ?? getProjectAgentConfig(task.projectId, agentDef)?.profile ?? undefined;
+  return findAgentProfile(task.projectId, agentDef, profileId);
+}
+
+function validateProfile(profile: Omit<ProjectAgentProfile, 'id'>): void {
+  if (!profile.name.trim()) throw new Error('Profile name must not be empty');
+  const invalid = Object.keys(profile.env).find((k) => !isEnvVarName(k));
+  if (invalid !== undefined) throw new Error(`Invalid environment variable name: ${invalid}`);
+}
+
+export function addProjectAgentProfile(
+  projectId: string,
+  profile: Omit<ProjectAgentProfile, 'id'>,
+): string {
+  validateProfile(profile);
+  const idx = store.projects.findIndex((p) => p.id === projectId);
+  if (idx === -1) throw new Error('Project not found');
+  const id = `profile-${crypto.randomUUID()}`;
+  setStore(
+    produce((s) => {
+      const list = s.projects[idx].agentProfiles ?? [];
+      s.projects[idx].agentProfiles = [...list, { ...profile, name: profile.name.trim(), id }];
+    }),
+  );
+  return id;
+}
+
+export function updateProjectAgentProfile(
+  projectId: string,
+  profileId: string,
+  updates: Partial<Omit<ProjectAgentProfile, 'id'>>,
+): void {
+  const idx = store.projects.findIndex((p) => p.id === projectId);
+  if (idx === -1) return;
+  const current = store.projects[idx].agentProfiles?.find((p) => p.id === profileId);
+  if (!current) return;
+  const next = { ...current, ...updates };
+  validateProfile(next);
+  setStore(
+    produce((s) => {
+      s.projects[idx].agentProfiles = s.projects[idx].agentProfiles?.map((p) =>
+        p.id === profileId ? next : p,
+      );
+    }),
+  );
+}
+
+export function removeProjectAgentProfile(projectId: string, profileId: string): void {
+  const idx = store.projects.findIndex((p) => p.id === projectId);
+  if (idx === -1) return;
+  setStore(
+    produce((s) => {
+      const left = (s.projects[idx].agentProfiles ?? []).filter((p) => p.id !== profileId);
+      s.projects[idx].agentProfiles = left.length > 0 ? left : undefined;
+    }),
+  );
+}
+
+/** Pick the profile a task's agent uses from its next launch on; undefined clears it. */
+export function setTaskAgentProfile(taskId: string, profileId: string | undefined): void {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const agent = task.agentIds[0] ? store.agents[task.agentIds[0]] : undefined;
+  if (profileId && agent && !findAgentProfile(task.projectId, agent.def, profileId)) {
+    throw new Error(`Unknown profile for ${agent.def.name}: ${profileId}`);
+  }
+  setStore('tasks', taskId, 'agentProfileId', profileId);
+}
@@ -1,11 +1,20 @@
 import { produce } from 'solid-js/store';
-import { openDialog } from '../lib/dialog';
+import { confirm, openDialog } from '../lib/dialog';
 import { invoke } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
 import { store, setStore } from './core';
 import { closeTask } from './tasks';
+import { showNotification } from './notification';
 import type { Project } from './types';
+import type {
+  ProjectConfig,
+  ProjectConfigFile,
+  ProjectInfo,
+  RecentProject,
+  UpdateBaseBranchResult,
+} from '../ipc/types';
 import { sanitizeBranchPrefix } from '../lib/branch-name';
+import { isEnvVarName } from '../lib/env';
 
 export const PASTEL_HUES = [0, 30, 60, 120, 180, 210, 260, 300, 330];
 
@@ -64,6 +73,14 @@ export function updateProject(
       | 'deleteBranchOnClose'
       | 'defaultDirectMode'
       | 'terminalBookmarks'
+      | 'testCommand'
+      | 'checks'
+      | 'requireChecksBeforeMerge'
+      | 'cleanupMergedTasks'
+      | 'cleanupGraceHours'
+      | 'sparseCheckout'
+      | 'defaultBaseBranch'
+      | 'sharedCaches'
     >
   >,
 ): void {
@@ -81,10 +98,43 @@ export function updateProject(
         s.projects[idx].defaultDirectMode = updates.defaultDirectMode;
       if (updates.terminalBookmarks !== undefined)
         s.projects[idx].terminalBookmarks = updates.terminalBookmarks;
+      if ('testCommand' in updates) s.projects[idx].testCommand = updates.testCommand || undefined;
+      if (updates.checks !== undefined) s.projects[idx].checks = updates.checks;
+      if (updates.requireChecksBeforeMerge !== undefined)
+        s.projects[idx].requireChecksBeforeMerge = updates.requireChecksBeforeMerge;
+      if (updates.cleanupMergedTasks !== undefined)
+        s.projects[idx].cleanupMergedTasks = updates.cleanupMergedTasks;
+      if ('cleanupGraceHours' in updates)
+        s.projects[idx].cleanupGraceHours = updates.cleanupGraceHours;
+      if ('sparseCheckout' in updates) {
+        s.projects[idx].sparseCheckout = updates.sparseCheckout?.length
+          ? updates.sparseCheckout
+          : undefined;
+      }
+      if ('defaultBaseBranch' in updates) {
+        s.projects[idx].defaultBaseBranch = updates.defaultBaseBranch || undefined;
+      }
+      if ('sharedCaches' in updates) {
+        s.projects[idx].sharedCaches = updates.sharedCaches?.length
+          ? updates.sharedCaches
+          : undefined;
+      }
     }),
   );
 }
 
+/**
+ * Replace a project's environment overrides, applied under each task's own.
+ * Takes effect for agents and shells spawned afterwards.
+ */
+export function setProjectEnv(projectId: string, env: Record<string, string>): void {
+  const invalid = Object.keys(env).find((k) => !isEnvVarName(k));
+  if (invalid !== undefined) throw new Error(`Invalid environment variable name: ${invalid}`);
+  const idx = store.projects.findIndex((p) => p.id === projectId);
+  if (idx === -1) return;
+  setStore('projects', idx, 'env', Object.keys(env).length > 0 ? { ...env } : undefined);
+}
+
 export function getProjectBranchPrefix(projectId: string): string {
   const raw = store.projects.find((p) => p.id === projectId)?.branchPrefix ?? 'task';
   return sanitizeBranchPrefix(raw);
@@ -123,19 +173,172 @@ export async function removeProjectWithTasks(projectId: string): Promise<void> {
 export async function pickAndAddProject(): Promise<string | null> {
   const selected = await openDialog({ directory: true, multiple: false });
   if (!selected) return null;
-  const path = selected as string;
-  const segments = path.split('/');
-  const name = segments[segments.length - 1] || path;
-  return addProject(name, path);
+  return openProject(selected as string);
+}
+
+export function listRecentProjects(): Promise<RecentProject[]> {
+  return invoke<RecentProject[]>(IPC.ListRecentProjects);
+}
+
+/**
+ * Open a git repository as a project. Switches to the existing project when
+ * the repo is already open; otherwise adds it. Returns null if the path is
+ * not a git repository.
+ */
+export async function openProject(path: string): Promise<string | null> {
+  let recent: RecentProject;
+  try {
+    recent = await invoke<RecentProject>(IPC.OpenProject, { path });
+  } catch (err) {
+    showNotification(err instanceof Error ? err.message : String(err));
+    return null;
+  }
+  const existing = store.projects.find((p) => p.path === recent.path);
+  const id = existing?.id ?? addProject(recent.name, recent.path);
+  if (existing) setStore('lastProjectId', id);
+  if (!existing?.defaultBaseBranch) void detectDefaultBaseBranch(id);
+  void loadProjectConfig(id);
+  void resolvePnpmStoreDir(id);
+  return id;
+}
+
+/** Projects whose config is being asked about, so a second load doesn't ask twice. */
+const askingTrust = new Set<string>();
+
+/** What a config changes, for the user to judge before it applies. */
+function describeConfig(config: ProjectConfig): string {
+  return Object.entries(config.agents)
+    .map(([id, a]) => {
+      const parts = [
+        a.args.length > 0 ? `arguments ${a.args.join(' ')}` : '',
+        Object.keys(a.env).length > 0 ? `environment ${Object.keys(a.env).join(', ')}` : '',
+        a.profile ? `profile ${a.profile}` : '',
+      ];
+      return `${id}: ${parts.filter(Boolean).join('; ') || 'nothing'}`;
+    })
+    .join('\n');
+}
+
+/** Whether the user lets this content of the project's config apply, asking when it's new. */
+async function trustConfig(projectId: string, file: ProjectConfigFile): Promise<boolean> {
+  const project = getProject(projectId);
+  if (!project) return false;
+  if (project.trustedConfigHash === file.hash) return true;
+  // Nothing to apply, so nothing to trust
+  if (Object.keys(file.config.agents).length === 0) return true;
+  if (askingTrust.has(projectId)) return false;
+  askingTrust.add(projectId);
+  try {
+    const message = [
+      `${project.name} has a .parallel-code.toml that changes how its agents launch:`,
+      describeConfig(file.config),
+      "Apply it only if you trust this repository. You're asked again whenever the file changes.",
+    ].join('\n\n');
+    return await confirm(message, {
+      title: 'Trust project config',
+      kind: 'warning',
+      okLabel: 'Trust',
+      cancelLabel: 'Ignore',
+    });
+  } finally {
+    askingTrust.delete(projectId);
+  }
+}
+
+/**
+ * Read the agent overrides the repository checks in as `.parallel-code.toml`.
+ * They apply once the user trusts the file's current content; until then, or
+ * when declined, agents launch without them. A file that doesn't parse keeps
+ * the previous overrides and says why.
+ */
+export async function loadProjectConfig(projectId: string): Promise<void> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) return;
+  try {
+    const file = await invoke<ProjectConfigFile | null>(IPC.ReadProjectConfig, { projectRoot });
+    const trusted = file !== null && (await trustConfig(projectId, file));
+    setStore(
+      produce((s) => {
+        const project = s.projects.find((p) => p.id === projectId);
+        if (!project) return;
+        project.agentConfig = file && trusted ? file.config.agents : undefined;
+        if (file && trusted) project.trustedConfigHash = file.hash;
+      }),
+    );
+  } catch (err) {
+    const name = getProject(projectId)?.name ?? projectId;
+    showNotification(`${name}: ${err instanceof Error ? err.message : String(err)}`);
+  }
+}
+
+/** The project's default branch, remote URL and repository name, read from git. */
+export async function getProjectInfo(projectId: string): Promise<ProjectInfo> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  return invoke<ProjectInfo>(IPC.GetProjectInfo, { projectRoot });
 }
 
-/** Check each project path and record which ones are missing. */
+/** Remember the repository's default branch as the base for the project's new tasks. */
+async function detectDefaultBaseBranch(projectId: string): Promise<void> {
+  try {
+    const info = await getProjectInfo(projectId);
+    if (info.default_branch && !getProject(projectId)?.defaultBaseBranch) {
+      updateProject(projectId, { defaultBaseBranch: info.default_branch });
+    }
+  } catch (err) {
+    console.warn('Failed to detect the default branch:', err);
+  }
+}
+
+/**
+ * Ask git where the project's shared pnpm store goes. The store lives in the
+ * common git dir, which a worktree or submodule checkout's `.git` file only
+ * points at, so it can't be derived from the project path.
+ */
+async function resolvePnpmStoreDir(projectId: string): Promise<void> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) return;
+  try {
+    const dir = await invoke<string | null>(IPC.GetPnpmStoreDir, { projectRoot });
+    setStore(
+      produce((s) => {
+        const project = s.projects.find((p) => p.id === projectId);
+        if (project) project.pnpmStoreDir = dir ?? undefined;
+      }),
+    );
+  } catch (err) {
+    console.warn('Failed to resolve the pnpm store:', err);
+  }
+}
+
+/** Fetch a remote for the project. Progress arrives as `GitProgress` events. */
+export async function fetchProject(projectId: string, remote?: string): Promise<void> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  await invoke(IPC.FetchProject, { projectRoot, remote });
+}
+
+/** Fetch and fast-forward the project's base branch to its remote counterpart. */
+export async function updateBaseBranch(
+  projectId: string,
+  remote?: string,
+): Promise<UpdateBaseBranchResult> {
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  return invoke<UpdateBaseBranchResult>(IPC.UpdateBaseBranch, { projectRoot, remote });
+}
+
+/** Check each project path, record which ones are missing and read the others' config. */
 export async function validateProjectPaths(): Promise<void> {
   const missing: Record<string, true> = {};
   for (const project of store.projects) {
     try {
       const exists = await invoke<boolean>(IPC.CheckPathExists, { path: project.path });
       if (!exists) missing[project.id] = true;
+      else {
+        void loadProjectConfig(project.id);
+        void resolvePnpmStoreDir(project.id);
+      }
     } catch {
       missing[project.id] = true;
     }
@@ -164,6 +367,7 @@ export async function relinkProject(projectId: string): Promise<boolean> {
       delete next[projectId];
       return next;
     });
+    void resolvePnpmStoreDir(projectId);
   }
   return exists;
 }
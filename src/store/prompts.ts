@@ -0,0 +1,111 @@
+import { produce } from 'solid-js/store';
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore } from './core';
+import { getProject, getProjectPath } from './projects';
+import type { ChangedFile, RemoteIssue } from '../ipc/types';
+import type { PromptTemplate } from './types';
+
+/** Replace `{var}` placeholders. Unknown vars are left as-is so mistakes stay visible. */
+export function substitutePromptVars(body: string, vars: Record<string, string>): string {
+  return body.replace(/\{([a-z][a-z0-9_]*)\}/g, (match, key: string) =>
+    Object.prototype.hasOwnProperty.call(vars, key) ? vars[key] : match,
+  );
+}
+
+export function getPromptTemplate(templateId: string): PromptTemplate | undefined {
+  return store.promptTemplates.find((t) => t.id === templateId);
+}
+
+export function addPromptTemplate(template: Omit<PromptTemplate, 'id'>): string {
+  const id = `prompt-${crypto.randomUUID()}`;
+  setStore(
+    produce((s) => {
+      s.promptTemplates.push({ ...template, id });
+    }),
+  );
+  return id;
+}
+
+export function updatePromptTemplate(
+  templateId: string,
+  updates: Partial<Omit<PromptTemplate, 'id'>>,
+): void {
+  setStore(
+    produce((s) => {
+      const idx = s.promptTemplates.findIndex((t) => t.id === templateId);
+      if (idx === -1) return;
+      s.promptTemplates[idx] = { ...s.promptTemplates[idx], ...updates };
+    }),
+  );
+}
+
+export function removePromptTemplate(templateId: string): void {
+  setStore(
+    produce((s) => {
+      s.promptTemplates = s.promptTemplates.filter((t) => t.id !== templateId);
+    }),
+  );
+}
+
+export function renderPrompt(templateId: string, vars: Record<string, string>): string {
+  const template = getPromptTemplate(templateId);
+  if (!template) throw new Error('Prompt template not found');
+  return substitutePromptVars(template.body, vars).trim();
+}
+
+/**
+ * Variables describing a task: `task_name`, `branch_name`, `worktree_path`,
+ * `project_name`, `notes`, `changed_files` (one path per line) and, for
+ * tasks linked to an issue, `issue_title`, `issue_url` and `issue_body`.
+ */
+export async function promptVarsForTask(taskId: string): Promise<Record<string, string>> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const vars: Record<string, string> = {
+    task_name: task.name,
+    branch_name: task.branchName,
+    worktree_path: task.worktreePath,
+    project_name: getProject(task.projectId)?.name ?? '',
+    notes: task.notes,
+    changed_files: '',
+  };
+
+  const files = await invoke<ChangedFile[]>(IPC.GetChangedFiles, {
+    worktreePath: task.worktreePath,
+    scope: task.scope,
+  }).catch(() => []);
+  vars.changed_files = files.map((f) => f.path).join('\n');
+
+  if (task.issue) {
+    vars.issue_title = task.issue.title;
+    vars.issue_url = task.issue.url;
+    const projectRoot = getProjectPath(task.projectId);
+    const issue = projectRoot
+      ? await invoke<RemoteIssue>(IPC.FetchRemoteIssue, {
+          projectRoot,
+          ref: task.issue.url,
+        }).catch(() => null)
+      : null;
+    vars.issue_body = issue?.body.trim() ?? '';
+  }
+  return vars;
+}
+
+/** Render a template against a task's variables, with `extra` taking precedence. */
+export async function renderPromptForTask(
+  templateId: string,
+  taskId: string,
+  extra: Record<string, string> = {},
+): Promise<string> {
+  return renderPrompt(templateId, { ...(await promptVarsForTask(taskId)), ...extra });
+}
+
+/** Templates marked to send on spawn, rendered and joined, or undefined when there are none. */
+export function renderSpawnPrompts(vars: Record<string, string>): string | undefined {
+  const parts = store.promptTemplates
+    .filter((t) => t.sendOnSpawn)
+    .map((t) => substitutePromptVars(t.body, vars).trim())
+    .filter(Boolean);
+  return parts.length > 0 ? parts.join('\n\n') : undefined;
+}
@@ -0,0 +1,8 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type { ResourceUsage } from '../ipc/types';
+
+/** CPU and memory of every running session, with the configured session limits. */
+export function getResourceUsage(): Promise<ResourceUsage> {
+  return invoke<ResourceUsage>(IPC.GetResourceUsage);
+}
@@ -0,0 +1,103 @@
+import { produce } from 'solid-js/store';
+import { store, setStore } from './core';
+import { sendPrompt } from './tasks';
+import { reviewCommentsPrompt, type ReviewComment } from '../lib/review-comments';
+
+/** A task's review comments, oldest first. */
+export function listReviewComments(
+  taskId: string,
+  options: { unresolvedOnly?: boolean } = {},
+): ReviewComment[] {
+  const comments = store.tasks[taskId]?.reviewComments ?? [];
+  return options.unresolvedOnly ? comments.filter((c) => c.resolvedAt === undefined) : comments;
+}
+
+/**
+ * Attach a comment to lines `startLine`–`endLine` (1-based, inclusive) of
+ * `path` in the task's diff. The comment is saved with the task.
+ */
+export function addReviewComment(
+  taskId: string,
+  comment: { path: string; startLine: number; endLine?: number; body: string },
+): ReviewComment {
+  if (!store.tasks[taskId]) throw new Error('Task not found');
+  const path = comment.path.trim().replace(/^\.\//, '');
+  const body = comment.body.trim();
+  const endLine = comment.endLine ?? comment.startLine;
+  if (!path || path.startsWith('/') || path.split('/').includes('..')) {
+    throw new Error('path must be relative to the worktree');
+  }
+  if (!Number.isInteger(comment.startLine) || comment.startLine < 1) {
+    throw new Error('startLine must be a positive integer');
+  }
+  if (!Number.isInteger(endLine) || endLine < comment.startLine) {
+    throw new Error('endLine must be an integer no less than startLine');
+  }
+  if (!body) throw new Error('Comment is empty');
+
+  const created: ReviewComment = {
+    id: crypto.randomUUID(),
+    path,
+    startLine: comment.startLine,
+    endLine,
+    body,
+    createdAt: Date.now(),
+  };
+  setStore(
+    'tasks',
+    taskId,
+    produce((t) => {
+      t.reviewComments = [...(t.reviewComments ?? []), created];
+      t.updatedAt = Date.now();
+    }),
+  );
+  return created;
+}
+
+function updateComment(
+  taskId: string,
+  commentId: string,
+  fn: (comments: ReviewComment[], index: number) => void,
+): void {
+  const index = store.tasks[taskId]?.reviewComments?.findIndex((c) => c.id === commentId) ?? -1;
+  if (index < 0) throw new Error('Review comment not found');
+  setStore(
+    'tasks',
+    taskId,
+    produce((t) => {
+      const comments = [...(t.reviewComments ?? [])];
+      fn(comments, index);
+      t.reviewComments = comments.length > 0 ? comments : undefined;
+      t.updatedAt = Date.now();
+    }),
+  );
+}
+
+/** Mark a comment resolved, or open it again with `resolved = false`. */
+export function resolveReviewComment(taskId: string, commentId: string, resolved = true): void {
+  updateComment(taskId, commentId, (comments, i) => {
+    const { resolvedAt: _, ...rest } = comments[i];
+    comments[i] = resolved ? { ...rest, resolvedAt: Date.now() } : rest;
+  });
+}
+
+export function deleteReviewComment(taskId: string, commentId: string): void {
+  updateComment(taskId, commentId, (comments, i) => comments.splice(i, 1));
+}
+
+/**
+ * Send the task's unresolved comments to its agent as a follow-up prompt.
+ * The comments stay open until resolved, so the next review can check them.
+ */
+export async function sendReviewComments(taskId: string): Promise<ReviewComment[]> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const agentId = task.agentIds[0];
+  if (!agentId || store.agents[agentId]?.status !== 'running') {
+    throw new Error('Agent is not running');
+  }
+  const open = listReviewComments(taskId, { unresolvedOnly: true });
+  if (open.length === 0) throw new Error('No unresolved review comments');
+  await sendPrompt(taskId, agentId, reviewCommentsPrompt(open));
+  return open;
+}
@@ -0,0 +1,45 @@
+import { invoke } from '../lib/ipc';
+import { taskCwd } from '../lib/task-scope';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import { getTaskSpawnEnv } from './tasks';
+import type { ScheduledRun } from '../ipc/types';
+
+/**
+ * Queue a headless run of `prompt` in a task's worktree. It starts at
+ * `startAt` (or as soon as possible) once the backend has a free slot.
+ * Uses the task's agent unless `agentDefId` picks another.
+ */
+export async function queueAgentRun(
+  taskId: string,
+  prompt: string,
+  opts: { agentDefId?: string; startAt?: number | null } = {},
+): Promise<ScheduledRun> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  if (task.archived) throw new Error('Task is archived');
+  const agentDef = opts.agentDefId
+    ? store.availableAgents.find((a) => a.id === opts.agentDefId)
+    : task.agentIds[0]
+      ? store.agents[task.agentIds[0]]?.def
+      : undefined;
+  if (!agentDef) throw new Error(`Unknown agent: ${opts.agentDefId ?? ''}`);
+  if (!agentDef.headless_args?.length) throw new Error(`${agentDef.name} has no headless mode`);
+  return invoke<ScheduledRun>(IPC.EnqueueAgentRun, {
+    taskId,
+    worktreePath: taskCwd(task),
+    agentId: agentDef.id,
+    prompt,
+    env: getTaskSpawnEnv(task, agentDef),
+    secrets: task.secrets,
+    startAt: opts.startAt ?? null,
+  });
+}
+
+export function listScheduledRuns(): Promise<ScheduledRun[]> {
+  return invoke<ScheduledRun[]>(IPC.ListScheduledRuns);
+}
+
+export function cancelScheduledRun(runId: string): Promise<void> {
+  return invoke(IPC.CancelScheduledRun, { runId });
+}
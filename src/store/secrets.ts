@@ -0,0 +1,28 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import type { SecretInfo } from '../ipc/types';
+
+export function listSecrets(): Promise<SecretInfo[]> {
+  return invoke<SecretInfo[]>(IPC.ListSecrets);
+}
+
+/**
+ * Store an API key or token in the OS keychain. With `inject`, agents
+ * spawned afterwards get it as the environment variable `name`.
+ */
+export function setSecret(name: string, value: string, inject?: boolean): Promise<SecretInfo> {
+  return invoke<SecretInfo>(IPC.SetSecret, { name, value, inject });
+}
+
+/** Whether a secret is stored under `name`; its value never reaches the renderer. */
+export function hasSecret(name: string): Promise<boolean> {
+  return invoke<boolean>(IPC.HasSecret, { name });
+}
+
+export function setSecretInjected(name: string, inject: boolean): Promise<SecretInfo> {
+  return invoke<SecretInfo>(IPC.SetSecretInjected, { name, inject });
+}
+
+export function deleteSecret(name: string): Promise<void> {
+  return invoke(IPC.DeleteSecret, { name });
+}
@@ -6,9 +6,16 @@ export {
   removeProject,
   removeProjectWithTasks,
   updateProject,
+  setProjectEnv,
   getProjectPath,
   getProjectBranchPrefix,
   pickAndAddProject,
+  openProject,
+  listRecentProjects,
+  fetchProject,
+  updateBaseBranch,
+  getProjectInfo,
+  loadProjectConfig,
   validateProjectPaths,
   relinkProject,
   isProjectMissing,
@@ -18,7 +25,16 @@ export {
   loadAgents,
   addAgentToTask,
   markAgentExited,
+  markAgentRestarted,
+  handleSessionInfoChanged,
+  handleAgentHung,
+  handleAgentEvent,
+  getAgentEvents,
+  listSessions,
+  sendToProjectAgents,
+  stopProjectAgents,
   restartAgent,
+  resumeAgentSession,
   switchAgent,
   addCustomAgent,
   removeCustomAgent,
@@ -34,13 +50,47 @@ export {
 export {
   createTask,
   createDirectTask,
+  createRace,
+  getRaceTaskIds,
+  pickRaceWinner,
   closeTask,
   retryCloseTask,
+  keepTask,
+  branchDeletionRisk,
   mergeTask,
   pushTask,
+  createPullRequest,
+  cherryPickBetweenTasks,
+  stashTask,
+  stashPopTask,
+  listTaskStashes,
+  getTaskConflicts,
+  getTaskTracking,
+  getTaskLog,
+  getTaskBlame,
+  resolveTaskConflict,
+  continueTaskMerge,
+  abortTaskMerge,
+  snapshotTask,
+  listTaskSnapshots,
+  restoreTaskSnapshot,
+  openTaskInEditor,
+  runTaskTests,
+  runTaskChecks,
+  getCheckResults,
+  getTaskPorts,
+  cancelTaskTests,
   updateTaskName,
+  renameTask,
+  updateTaskMetadata,
+  setTaskEnv,
+  setTaskSecrets,
+  getTaskSpawnEnv,
+  getTaskEvents,
   updateTaskNotes,
   sendPrompt,
+  handoffTask,
+  DEFAULT_HANDOFF_TEMPLATE,
   setLastPrompt,
   clearInitialPrompt,
   clearPrefillPrompt,
@@ -52,6 +102,13 @@ export {
   hasDirectModeTask,
   collapseTask,
   uncollapseTask,
+  archiveTask,
+  restoreTask,
+  widenSparseCheckout,
+  deleteTasks,
+  archiveTasks,
+  createTasks,
+  rebaseTasks,
   getGitHubDropDefaults,
   setNewTaskDropUrl,
   setNewTaskPrefillPrompt,
@@ -87,6 +144,7 @@ export {
   setSidebarFocusedProjectId,
 } from './focus';
 export type { PanelId, PendingAction } from './types';
+export type { BulkTaskResult, CreateTasksProgress, CreateTasksResult } from './tasks';
 export { saveState, loadState } from './persistence';
 export {
   getFontScale,
@@ -126,6 +184,7 @@ export {
   rescheduleTaskStatusPolling,
 } from './taskStatus';
 export type { TaskDotStatus } from './taskStatus';
+export { getTaskPhase, getTaskProgressLabel, getTaskSummary } from './task-summary';
 export { showNotification, clearNotification } from './notification';
 export { getCompletedTasksTodayCount, getMergedLineTotals } from './completion';
 export {
@@ -133,5 +192,81 @@ export {
   closeTerminal,
   updateTerminalName,
   syncTerminalCounter,
+  searchSessionBuffer,
 } from './terminals';
 export { startRemoteAccess, stopRemoteAccess, refreshRemoteStatus } from './remote';
+export { getAgentApproval, handleAgentApprovalEvent, respondToAgent } from './approvals';
+export {
+  renderTemplate,
+  getTaskTemplate,
+  addTaskTemplate,
+  updateTaskTemplate,
+  removeTaskTemplate,
+  createTaskFromTemplate,
+} from './templates';
+export {
+  substitutePromptVars,
+  getPromptTemplate,
+  addPromptTemplate,
+  updatePromptTemplate,
+  removePromptTemplate,
+  renderPrompt,
+  renderPromptForTask,
+  promptVarsForTask,
+} from './prompts';
+export {
+  getAgentProfiles,
+  findAgentProfile,
+  getTaskAgentProfile,
+  getProjectAgentConfig,
+  addProjectAgentProfile,
+  updateProjectAgentProfile,
+  removeProjectAgentProfile,
+  setTaskAgentProfile,
+} from './profiles';
+export {
+  getBlockingTasks,
+  isTaskBlocked,
+  getTaskDependents,
+  setTaskDependencies,
+  getTaskGraph,
+  dismissDependencyRebase,
+} from './dependencies';
+export type { TaskGraph, TaskGraphNode } from './dependencies';
+export { queueAgentRun, listScheduledRuns, cancelScheduledRun } from './scheduler';
+export {
+  enqueueMerge,
+  enqueueMerges,
+  listMergeQueue,
+  removeFromMergeQueue,
+  resumeMergeQueue,
+} from './merge-queue';
+export {
+  addReviewComment,
+  deleteReviewComment,
+  listReviewComments,
+  resolveReviewComment,
+  sendReviewComments,
+} from './review';
+export { listSecrets, setSecret, hasSecret, setSecretInjected, deleteSecret } from './secrets';
+export { listDeletedTasks, restoreDeletedTask, undoLastOperation } from './trash';
+export { getMetrics, queryHistory, searchTranscripts } from './history';
+export type { HistoryQuery } from './history';
+export { exportAppData, importAppData } from './app-data';
+export { getDiskUsage, taskDiskUsage, cleanupCandidates } from './disk-usage';
+export { getResourceUsage } from './resource-usage';
+export { getProjectTime, getTaskTime, setTaskTimer } from './task-time';
+export type { TaskDiskUsage } from './disk-usage';
+export { onGitProgress, cancelGitOperation } from './git-operations';
+export { startTaskDiff, readTaskDiffChunks, loadMoreTaskDiff, cancelTaskDiff } from './diff-stream';
+export type { TaskDiffStream } from './diff-stream';
+export { transitionTask, onTaskLifecycleChange } from './lifecycle';
+export type { TaskLifecycleChange } from './lifecycle';
+export {
+  exportTaskBundle,
+  importTaskBundle,
+  listTaskRecordings,
+  exportTaskRecording,
+} from './bundles';
+export { linkTaskIssue, createTaskFromIssue } from './issues';
+export { listNotifications, markNotificationsRead, clearNotifications } from './inbox';
@@ -0,0 +1,67 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import { getAgentEvents } from './agents';
+import { getTaskDotStatus, isAgentAskingQuestion } from './taskStatus';
+import {
+  formatTaskProgress,
+  summarizeEvents,
+  taskPhase,
+  type TaskPhase,
+  type TaskSummary,
+} from '../lib/task-summary';
+import type { AgentOutputEvent, SessionInfo } from '../ipc/types';
+
+/** The task's agent with the most recent event. */
+function latestEvent(taskId: string): AgentOutputEvent | undefined {
+  let latest: AgentOutputEvent | undefined;
+  for (const id of store.tasks[taskId]?.agentIds ?? []) {
+    const event = store.agents[id]?.lastEvent;
+    if (event && (!latest || event.at > latest.at)) latest = event;
+  }
+  return latest;
+}
+
+/** What the task is doing right now. Reactive. */
+export function getTaskPhase(taskId: string): TaskPhase {
+  const task = store.tasks[taskId];
+  const agentIds = task?.agentIds ?? [];
+  return taskPhase({
+    lifecycle: task?.lifecycle,
+    busy: getTaskDotStatus(taskId) === 'busy',
+    asking: agentIds.some((id) => isAgentAskingQuestion(id)),
+    running: agentIds.some((id) => store.agents[id]?.status === 'running'),
+    lastEvent: latestEvent(taskId),
+  });
+}
+
+/** One line of progress for the task list, e.g. "Editing src/app.ts · 3 commits". Reactive. */
+export function getTaskProgressLabel(taskId: string): string {
+  const ahead = store.taskGitStatus[taskId]?.ahead;
+  return formatTaskProgress(getTaskPhase(taskId), latestEvent(taskId), ahead ?? null);
+}
+
+/**
+ * Files touched, commands run, last activity, commits and phase of a task,
+ * from its agents' output events and git status.
+ */
+export async function getTaskSummary(taskId: string): Promise<TaskSummary> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const [events, sessions] = await Promise.all([
+    Promise.all(task.agentIds.map((id) => getAgentEvents(id))),
+    Promise.all(
+      task.agentIds.map((agentId) =>
+        invoke<SessionInfo | null>(IPC.GetSessionInfo, { agentId }).catch(() => null),
+      ),
+    ),
+  ]);
+  const outputTimes = sessions.flatMap((s) => (s ? [s.last_output_at] : []));
+  return {
+    task_id: taskId,
+    phase: getTaskPhase(taskId),
+    ...summarizeEvents(events.flat()),
+    last_activity_at: outputTimes.length > 0 ? Math.max(...outputTimes) : null,
+    commit_count: store.taskGitStatus[taskId]?.ahead ?? null,
+  };
+}
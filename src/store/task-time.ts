@@ -0,0 +1,41 @@
+import { invoke } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store } from './core';
+import type { TaskTime } from '../ipc/types';
+
+export interface ProjectTime {
+  project_id: string;
+  active_ms: number;
+  agent_ms: number;
+  total_tokens: number;
+  cost_usd: number | null;
+  /** Longest first. */
+  tasks: TaskTime[];
+}
+
+export async function getTaskTime(taskId: string): Promise<TaskTime> {
+  const [time] = await invoke<TaskTime[]>(IPC.GetTaskTimes, { taskIds: [taskId] });
+  return time;
+}
+
+/** Start the task's timer, counting all time until it's paused, or pause it. */
+export function setTaskTimer(taskId: string, running: boolean): Promise<TaskTime> {
+  return invoke<TaskTime>(IPC.SetTaskTimer, { taskId, running });
+}
+
+/** Time tracked across the project's tasks, archived ones included. */
+export async function getProjectTime(projectId: string): Promise<ProjectTime> {
+  const taskIds = Object.values(store.tasks)
+    .filter((t) => t.projectId === projectId)
+    .map((t) => t.id);
+  const tasks = await invoke<TaskTime[]>(IPC.GetTaskTimes, { taskIds });
+  const costs = tasks.map((t) => t.cost_usd).filter((c): c is number => c !== null);
+  return {
+    project_id: projectId,
+    active_ms: tasks.reduce((sum, t) => sum + t.active_ms, 0),
+    agent_ms: tasks.reduce((sum, t) => sum + t.agent_ms, 0),
+    total_tokens: tasks.reduce((sum, t) => sum + t.total_tokens, 0),
+    cost_usd: costs.length > 0 ? costs.reduce((a, b) => a + b, 0) : null,
+    tasks: [...tasks].sort((a, b) => b.active_ms - a.active_ms),
+  };
+}
@@ -10,6 +10,7 @@ vi.mock('./core', () => ({
       get(_target, prop) {
         if (prop === 'autoTrustFolders') return mockAutoTrustFolders;
         if (prop === 'activeTaskId') return mockActiveTaskId;
+        if (prop === 'agents' || prop === 'tasks') return {};
         return undefined;
       },
     },
@@ -1,8 +1,10 @@
-import { createSignal } from 'solid-js';
+import { createEffect, createSignal } from 'solid-js';
 import { invoke } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
 import { store, setStore } from './core';
-import type { WorktreeStatus } from '../ipc/types';
+import { notify } from './inbox';
+import { transitionTask } from './lifecycle';
+import type { TaskGitStatus, TaskPrStatus } from '../ipc/types';
 
 // --- Trust-specific patterns (subset of QUESTION_PATTERNS) ---
 // These are auto-accepted when autoTrustFolders is enabled.
@@ -267,13 +269,18 @@ export function isAgentAskingQuestion(agentId: string): boolean {
 }
 
 function updateQuestionState(agentId: string, hasQuestion: boolean): void {
+  if (hasQuestion === questionAgents().has(agentId)) return;
   setQuestionAgents((prev) => {
-    if (hasQuestion === prev.has(agentId)) return prev;
     const next = new Set(prev);
     if (hasQuestion) next.add(agentId);
     else next.delete(agentId);
     return next;
   });
+  if (hasQuestion) {
+    const taskId = store.agents[agentId]?.taskId;
+    const name = taskId ? store.tasks[taskId]?.name : undefined;
+    notify('agent_waiting', 'Agent is waiting for input', name ?? 'An agent', taskId);
+  }
 }
 
 // --- Agent activity tracking ---
@@ -596,106 +603,90 @@ export function getTaskDotStatus(taskId: string): TaskDotStatus {
   return 'waiting';
 }
 
-// --- Git status polling ---
-
-async function refreshTaskGitStatus(taskId: string): Promise<void> {
-  const task = store.tasks[taskId];
-  if (!task) return;
-
-  try {
-    const status = await invoke<WorktreeStatus>(IPC.GetWorktreeStatus, {
+// --- Git status ---
+// The backend polls watched worktrees and pushes TaskGitStatusChanged only
+// when a task's status actually changes; we just keep its watch list current.
+
+function syncWatchedTasks(reset = false): void {
+  const tasks = store.taskOrder
+    .map((taskId) => store.tasks[taskId])
+    .filter((task) => task && !task.archived)
+    .map((task) => ({ taskId: task.id, worktreePath: task.worktreePath, scope: task.scope }));
+  invoke(IPC.SetWatchedTasks, {
+    tasks,
+    activeTaskId: store.activeTaskId ?? undefined,
+    reset,
+  }).catch(console.error);
+
+  // Only branch tasks can have a PR; merged ones have nothing left to track
+  const prTasks = store.taskOrder
+    .map((taskId) => store.tasks[taskId])
+    .filter((task) => task && !task.archived && !task.directMode && task.lifecycle !== 'merged')
+    .map((task) => ({
+      taskId: task.id,
       worktreePath: task.worktreePath,
-    });
-    setStore('taskGitStatus', taskId, status);
-  } catch {
-    // Worktree may not exist yet or was removed — ignore
-  }
+      branchName: task.branchName,
+    }));
+  invoke(IPC.SetWatchedPrTasks, { tasks: prTasks, reset }).catch(console.error);
 }
 
-let isRefreshingAll = false;
-
-/** Refresh git status for inactive tasks (active task is handled by its own 5s timer).
- *  Limits concurrency to avoid spawning too many parallel git processes. */
-export async function refreshAllTaskGitStatus(): Promise<void> {
-  if (isRefreshingAll) return;
-  isRefreshingAll = true;
-  try {
-    const taskIds = store.taskOrder;
-    const active = activeAgents();
-    const currentTaskId = store.activeTaskId;
-    const toRefresh = taskIds.filter((taskId) => {
-      // Active task is covered by the faster refreshActiveTaskGitStatus timer
-      if (taskId === currentTaskId) return false;
-      const task = store.tasks[taskId];
-      if (!task) return false;
-      return !task.agentIds.some((id) => {
-        const a = store.agents[id];
-        return a?.status === 'running' && active.has(id);
-      });
-    });
-
-    // Process in batches of 4 to limit concurrent git processes
-    const BATCH_SIZE = 4;
-    for (let i = 0; i < toRefresh.length; i += BATCH_SIZE) {
-      const batch = toRefresh.slice(i, i + BATCH_SIZE);
-      await Promise.allSettled(batch.map((taskId) => refreshTaskGitStatus(taskId)));
-    }
-  } finally {
-    isRefreshingAll = false;
+function applyPrStatus({ task_id, ...pr }: TaskPrStatus): void {
+  const task = store.tasks[task_id];
+  if (!task) return;
+  setStore('tasks', task_id, 'pr', pr);
+  if (pr.state === 'MERGED' && transitionTask(task_id, 'merged')) {
+    notify('git_operation_done', 'Pull request merged', `${task.name}: #${pr.number}`, task_id);
   }
 }
 
-/** Refresh git status for the currently active task only. */
-async function refreshActiveTaskGitStatus(): Promise<void> {
-  const taskId = store.activeTaskId;
-  if (!taskId) return;
-  await refreshTaskGitStatus(taskId);
+/** Re-check a task's PR now, e.g. right after pushing its branch. */
+export function refreshTaskPrStatus(taskId: string): void {
+  invoke(IPC.RefreshTaskPrStatus, { taskId }).catch(console.error);
 }
 
 /** Refresh git status for a single task (e.g. after agent exits). */
 export function refreshTaskStatus(taskId: string): void {
-  refreshTaskGitStatus(taskId);
+  invoke(IPC.RefreshTaskGitStatus, { taskId }).catch(console.error);
 }
 
-let allTasksTimer: ReturnType<typeof setInterval> | null = null;
-let activeTaskTimer: ReturnType<typeof setInterval> | null = null;
-let lastPollingTaskCount = 0;
+let offGitStatusChanged: (() => void) | null = null;
+let offPrStatusChanged: (() => void) | null = null;
 
-function computeAllTasksInterval(): number {
-  const taskCount = store.taskOrder.length;
-  return Math.min(120_000, 30_000 + Math.max(0, taskCount - 3) * 5_000);
+export function startTaskStatusPolling(): void {
+  if (offGitStatusChanged) return;
+  offGitStatusChanged = window.electron.ipcRenderer.on(
+    IPC.TaskGitStatusChanged,
+    (data: unknown) => {
+      const { task_id, ...status } = data as TaskGitStatus;
+      if (store.tasks[task_id]) setStore('taskGitStatus', task_id, status);
+    },
+  );
+  offPrStatusChanged = window.electron.ipcRenderer.on(IPC.TaskPrStatusChanged, (data: unknown) =>
+    applyPrStatus(data as TaskPrStatus),
+  );
+  syncWatchedTasks(true);
+  // A newly active task is polled right away and at the faster rate
+  let lastActiveTaskId = store.activeTaskId;
+  createEffect(() => {
+    const activeTaskId = store.activeTaskId;
+    if (activeTaskId === lastActiveTaskId) return;
+    lastActiveTaskId = activeTaskId;
+    syncWatchedTasks();
+  });
 }
 
-export function startTaskStatusPolling(): void {
-  if (allTasksTimer || activeTaskTimer) return;
-  // Active task polls every 5s for responsive UI
-  activeTaskTimer = setInterval(refreshActiveTaskGitStatus, 5_000);
-  // Scale interval: 30s base + 5s per additional task beyond 3
-  lastPollingTaskCount = store.taskOrder.length;
-  allTasksTimer = setInterval(refreshAllTaskGitStatus, computeAllTasksInterval());
-  // Run once immediately
-  refreshActiveTaskGitStatus();
-  refreshAllTaskGitStatus();
-}
-
-/** Call when tasks are added/removed to recalculate the all-tasks polling interval. */
+/** Call when tasks are added/removed to update the backend's watch list. */
 export function rescheduleTaskStatusPolling(): void {
-  if (!allTasksTimer) return;
-  const currentCount = store.taskOrder.length;
-  if (currentCount === lastPollingTaskCount) return;
-  lastPollingTaskCount = currentCount;
-  clearInterval(allTasksTimer);
-  allTasksTimer = setInterval(refreshAllTaskGitStatus, computeAllTasksInterval());
+  if (!offGitStatusChanged) return;
+  syncWatchedTasks();
 }
 
 export function stopTaskStatusPolling(): void {
-  if (allTasksTimer) {
-    clearInterval(allTasksTimer);
-    allTasksTimer = null;
-  }
-  if (activeTaskTimer) {
-    clearInterval(activeTaskTimer);
-    activeTaskTimer = null;
-  }
-  lastPollingTaskCount = 0;
+  if (!offGitStatusChanged) return;
+  offGitStatusChanged();
+  offGitStatusChanged = null;
+  offPrStatusChanged?.();
+  offPrStatusChanged = null;
+  invoke(IPC.SetWatchedTasks, { tasks: [], reset: true }).catch(console.error);
+  invoke(IPC.SetWatchedPrTasks, { tasks: [], reset: true }).catch(console.error);
 }
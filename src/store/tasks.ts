@@ -1,19 +1,54 @@
 import { produce } from 'solid-js/store';
-import { invoke } from '../lib/ipc';
+import { invoke, fireAndForget } from '../lib/ipc';
 import { IPC } from '../../electron/ipc/channels';
+import { isAppError } from '../../electron/ipc/errors';
 import { store, setStore, updateWindowTitle, cleanupPanelEntries } from './core';
 import { setTaskFocusedPanel } from './focus';
 import { getProject, getProjectPath, getProjectBranchPrefix, isProjectMissing } from './projects';
 import { setPendingShellCommand } from '../lib/bookmarks';
+import { isEnvVarName } from '../lib/env';
+import { normalizeTaskScope } from '../lib/task-scope';
+import { sharedCacheEnv } from '../lib/shared-caches';
 import {
   markAgentSpawned,
   markAgentBusy,
   clearAgentActivity,
   isAgentIdle,
   rescheduleTaskStatusPolling,
+  refreshTaskPrStatus,
 } from './taskStatus';
 import { recordMergedLines, recordTaskCompleted } from './completion';
-import type { AgentDef, CreateTaskResult, MergeResult } from '../ipc/types';
+import { restartAgent, switchAgent } from './agents';
+import { transitionTask } from './lifecycle';
+import { canTransition, type TaskLifecycle } from '../lib/task-lifecycle';
+import { promptVarsForTask, renderSpawnPrompts, substitutePromptVars } from './prompts';
+import { findAgentProfile, getProjectAgentConfig, getTaskAgentProfile } from './profiles';
+import { getBlockingTasks, validateTaskDependencies } from './dependencies';
+import { taskTrashMetadata } from './trash';
+import { showNotification } from './notification';
+import type {
+  AgentDef,
+  BranchDeletionRisk,
+  CherryPickResult,
+  CreateRaceResult,
+  CreatedPullRequest,
+  CreateTaskResult,
+  MergeResult,
+  RenameTaskResult,
+  RestoreTaskResult,
+  StashEntry,
+  TaskSnapshot,
+  ConflictState,
+  BranchTracking,
+  TaskCommit,
+  BlameLine,
+  TaskCheckResults,
+  TaskEvent,
+  TaskPorts,
+  TestRunResult,
+  TaskEventType,
+  HandoffContext,
+} from '../ipc/types';
 import { parseGitHubUrl, taskNameFromGitHubUrl } from '../lib/github-url';
 import type { Agent, Task } from './types';
 
@@ -28,13 +63,13 @@ function isAgentNotFoundError(err: unknown): boolean {
   return String(err).toLowerCase().includes('agent not found');
 }
 
-async function writeToAgentWhenReady(agentId: string, data: string): Promise<void> {
+async function writeToAgentWhenReady(agentId: string, data: string, paste = false): Promise<void> {
   const deadline = Date.now() + AGENT_WRITE_READY_TIMEOUT_MS;
   let lastErr: unknown;
 
   while (Date.now() <= deadline) {
     try {
-      await invoke(IPC.WriteToAgent, { agentId, data });
+      await invoke(IPC.WriteToAgent, { agentId, data, paste });
       return;
     } catch (err) {
       lastErr = err;
@@ -57,6 +92,80 @@ export interface CreateTaskOptions {
   branchPrefixOverride?: string;
   githubUrl?: string;
   skipPermissions?: boolean;
+  baseBranch?: string;
+  /** Adopt this existing branch instead of creating a new one. */
+  existingBranch?: string;
+  /** Launch profile for the agent, built-in or the project's. */
+  agentProfileId?: string;
+  /** Tasks that must merge first; without a baseBranch, branches from the first one. */
+  dependsOn?: string[];
+  /** Id for the worktree checkout's progress events, and for cancelling it. */
+  opId?: string;
+  /** Directories to check out; defaults to the project's sparse checkout. */
+  sparsePaths?: string[];
+  /** Sub-directory the task works in: agents start there, and status and diffs cover only it. */
+  scope?: string;
+}
+
+/** A worktree works without its submodules or LFS files, so their failures are only reported. */
+function reportWorktreeSetup(
+  taskName: string,
+  result: { submodule_error?: string; lfs_error?: string },
+): void {
+  const problems = [
+    result.submodule_error && `submodules failed to check out (${result.submodule_error})`,
+    result.lfs_error && `LFS files weren't downloaded (${result.lfs_error})`,
+  ].filter((p): p is string => !!p);
+  if (problems.length === 0) return;
+  console.warn(`Worktree setup of ${taskName} was incomplete:`, problems);
+  showNotification(`${taskName}: ${problems.map((p) => p.split('\n')[0]).join('; ')}`);
+}
+
+/** Give a new worktree the main checkout's packages. Failing only costs the agent an install. */
+async function shareNodeModulesInto(
+  taskName: string,
+  projectRoot: string,
+  worktreePath: string,
+): Promise<void> {
+  try {
+    await invoke(IPC.ShareNodeModules, { projectRoot, worktreePath });
+  } catch (err) {
+    console.warn(`Sharing node_modules into ${taskName} failed:`, err);
+    const message = err instanceof Error ? err.message : String(err);
+    showNotification(`${taskName}: node_modules wasn't shared (${message.split('\n')[0]})`);
+  }
+}
+
+/** A sparse checkout has to include the task's scope, or it would start in an empty folder. */
+function withScope(
+  sparsePaths: string[] | undefined,
+  scope: string | undefined,
+): string[] | undefined {
+  if (!scope || !sparsePaths?.length) return sparsePaths;
+  const covered = sparsePaths.some((p) => scope === p || scope.startsWith(`${p}/`));
+  return covered ? sparsePaths : [...sparsePaths, scope];
+}
+
+function checkAgentProfile(projectId: string, agentDef: AgentDef, profileId?: string): void {
+  if (profileId && !findAgentProfile(projectId, agentDef, profileId)) {
+    throw new Error(`Unknown profile for ${agentDef.name}: ${profileId}`);
+  }
+}
+
+/** A new task's own prompt, else the templates marked to send on spawn, rendered for it. */
+function initialPromptFor(
+  initialPrompt: string | undefined,
+  task: Pick<Task, 'name' | 'projectId' | 'branchName' | 'worktreePath'>,
+): string | undefined {
+  if (initialPrompt) return initialPrompt;
+  return renderSpawnPrompts({
+    task_name: task.name,
+    branch_name: task.branchName,
+    worktree_path: task.worktreePath,
+    project_name: getProject(task.projectId)?.name ?? '',
+    notes: '',
+    changed_files: '',
+  });
 }
 
 export async function createTask(opts: CreateTaskOptions): Promise<string> {
@@ -64,7 +173,6 @@ export async function createTask(opts: CreateTaskOptions): Promise<string> {
     name,
     agentDef,
     projectId,
-    symlinkDirs = [],
     initialPrompt,
     githubUrl,
     skipPermissions,
@@ -72,13 +180,50 @@ export async function createTask(opts: CreateTaskOptions): Promise<string> {
   const projectRoot = getProjectPath(projectId);
   if (!projectRoot) throw new Error('Project not found');
   if (isProjectMissing(projectId)) throw new Error('Project folder not found');
+  checkAgentProfile(projectId, agentDef, opts.agentProfileId);
+  const dependsOn = [...new Set(opts.dependsOn ?? [])];
+  validateTaskDependencies(null, projectId, dependsOn);
+  const baseBranch =
+    opts.baseBranch ??
+    store.tasks[dependsOn[0]]?.branchName ??
+    getProject(projectId)?.defaultBaseBranch;
+  const scope = opts.scope === undefined ? undefined : normalizeTaskScope(opts.scope);
 
   const branchPrefix = opts.branchPrefixOverride ?? getProjectBranchPrefix(projectId);
-  const result = await invoke<CreateTaskResult>(IPC.CreateTask, {
+  const sparsePaths = withScope(opts.sparsePaths ?? getProject(projectId)?.sparseCheckout, scope);
+  const shareNodeModules = getProject(projectId)?.sharedCaches?.includes('node_modules');
+  // The worktree gets its own copy instead of a symlink into the main checkout
+  const symlinkDirs = (opts.symlinkDirs ?? []).filter(
+    (d) => !shareNodeModules || d !== 'node_modules',
+  );
+  const result = opts.existingBranch
+    ? await invoke<CreateTaskResult>(IPC.CreateTaskFromBranch, {
+        name,
+        projectRoot,
+        branchName: opts.existingBranch,
+        symlinkDirs,
+        sparsePaths,
+        scope,
+        opId: opts.opId,
+      })
+    : await invoke<CreateTaskResult>(IPC.CreateTask, {
+        name,
+        projectRoot,
+        symlinkDirs,
+        branchPrefix,
+        baseBranch,
+        sparsePaths,
+        scope,
+        opId: opts.opId,
+      });
+  reportWorktreeSetup(name, result);
+  if (shareNodeModules) await shareNodeModulesInto(name, projectRoot, result.worktree_path);
+
+  const prompt = initialPromptFor(initialPrompt, {
     name,
-    projectRoot,
-    symlinkDirs,
-    branchPrefix,
+    projectId,
+    branchName: result.branch_name,
+    worktreePath: result.worktree_path,
   });
 
   const agentId = crypto.randomUUID();
@@ -92,10 +237,18 @@ export async function createTask(opts: CreateTaskOptions): Promise<string> {
     shellAgentIds: [],
     notes: '',
     lastPrompt: '',
-    initialPrompt: initialPrompt || undefined,
+    initialPrompt: prompt || undefined,
     skipPermissions: skipPermissions || undefined,
     githubUrl,
-    savedInitialPrompt: initialPrompt || undefined,
+    savedInitialPrompt: prompt || undefined,
+    agentProfileId: opts.agentProfileId,
+    dependsOn: dependsOn.length > 0 ? dependsOn : undefined,
+    baseBranch: opts.existingBranch ? undefined : baseBranch,
+    createdAt: Date.now(),
+    lifecycle: 'agent-running',
+    adoptedBranch: opts.existingBranch ? true : undefined,
+    sparsePaths: sparsePaths?.length ? sparsePaths : undefined,
+    scope,
   };
 
   const agent: Agent = {
@@ -137,6 +290,7 @@ export interface CreateDirectTaskOptions {
   initialPrompt?: string;
   githubUrl?: string;
   skipPermissions?: boolean;
+  agentProfileId?: string;
 }
 
 export async function createDirectTask(opts: CreateDirectTaskOptions): Promise<string> {
@@ -147,9 +301,16 @@ export async function createDirectTask(opts: CreateDirectTaskOptions): Promise<s
   const projectRoot = getProjectPath(projectId);
   if (!projectRoot) throw new Error('Project not found');
   if (isProjectMissing(projectId)) throw new Error('Project folder not found');
+  checkAgentProfile(projectId, agentDef, opts.agentProfileId);
 
   const id = crypto.randomUUID();
   const agentId = crypto.randomUUID();
+  const prompt = initialPromptFor(initialPrompt, {
+    name,
+    projectId,
+    branchName: mainBranch,
+    worktreePath: projectRoot,
+  });
 
   const task: Task = {
     id,
@@ -161,11 +322,14 @@ export async function createDirectTask(opts: CreateDirectTaskOptions): Promise<s
     shellAgentIds: [],
     notes: '',
     lastPrompt: '',
-    initialPrompt: initialPrompt || undefined,
-    savedInitialPrompt: initialPrompt || undefined,
+    initialPrompt: prompt || undefined,
+    savedInitialPrompt: prompt || undefined,
     directMode: true,
     skipPermissions: skipPermissions || undefined,
     githubUrl,
+    agentProfileId: opts.agentProfileId,
+    createdAt: Date.now(),
+    lifecycle: 'agent-running',
   };
 
   const agent: Agent = {
@@ -192,27 +356,162 @@ export async function createDirectTask(opts: CreateDirectTaskOptions): Promise<s
     }),
   );
 
+  recordTaskEvent(id, 'created', { name, branch_name: mainBranch, direct_mode: true });
   markAgentSpawned(agentId);
   rescheduleTaskStatusPolling();
   updateWindowTitle(name);
   return id;
 }
 
-export async function closeTask(taskId: string): Promise<void> {
+export interface CreateRaceOptions {
+  name: string;
+  prompt: string;
+  agentDefs: AgentDef[];
+  projectId: string;
+  symlinkDirs?: string[];
+  skipPermissions?: boolean;
+}
+
+/**
+ * Race the same prompt across several agents: one sibling task per agent,
+ * each in its own worktree, grouped by a shared raceId for comparison.
+ * Returns the race id.
+ */
+export async function createRace(opts: CreateRaceOptions): Promise<string> {
+  const { name, prompt, agentDefs, projectId, symlinkDirs = [], skipPermissions } = opts;
+  const projectRoot = getProjectPath(projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  if (isProjectMissing(projectId)) throw new Error('Project folder not found');
+
+  const sparsePaths = getProject(projectId)?.sparseCheckout;
+  const result = await invoke<CreateRaceResult>(IPC.CreateRace, {
+    name,
+    projectRoot,
+    agentDefIds: agentDefs.map((d) => d.id),
+    symlinkDirs,
+    branchPrefix: getProjectBranchPrefix(projectId),
+    sparsePaths,
+  });
+  // The race's worktrees come from one checkout, so one report covers them
+  reportWorktreeSetup(name, result.entries.find((e) => e.submodule_error || e.lfs_error) ?? {});
+
+  const defsById = new Map(agentDefs.map((d) => [d.id, d]));
+  const agentIds: string[] = [];
+  setStore(
+    produce((s) => {
+      for (const entry of result.entries) {
+        const agentDef = defsById.get(entry.agent_def_id);
+        if (!agentDef) continue;
+        const agentId = crypto.randomUUID();
+        agentIds.push(agentId);
+        s.tasks[entry.id] = {
+          id: entry.id,
+          name: `${name} (${agentDef.name})`,
+          projectId,
+          branchName: entry.branch_name,
+          worktreePath: entry.worktree_path,
+          agentIds: [agentId],
+          shellAgentIds: [],
+          notes: '',
+          lastPrompt: '',
+          initialPrompt: prompt || undefined,
+          savedInitialPrompt: prompt || undefined,
+          createdAt: Date.now(),
+          lifecycle: 'agent-running',
+          skipPermissions: skipPermissions || undefined,
+          raceId: result.race_id,
+          sparsePaths,
+        };
+        s.agents[agentId] = {
+          id: agentId,
+          taskId: entry.id,
+          def: agentDef,
+          resumed: false,
+          status: 'running',
+          exitCode: null,
+          signal: null,
+          lastOutput: [],
+          generation: 0,
+        };
+        s.taskOrder.push(entry.id);
+      }
+      const first = result.entries[0];
+      if (first) {
+        s.activeTaskId = first.id;
+        s.activeAgentId = agentIds[0] ?? null;
+      }
+      s.lastProjectId = projectId;
+    }),
+  );
+
+  for (const agentId of agentIds) markAgentSpawned(agentId);
+  rescheduleTaskStatusPolling();
+  updateWindowTitle(name);
+  return result.race_id;
+}
+
+/** Return the ids of all tasks racing under the given race id. */
+export function getRaceTaskIds(raceId: string): string[] {
+  return [...store.taskOrder, ...store.collapsedTaskOrder].filter(
+    (id) => store.tasks[id]?.raceId === raceId,
+  );
+}
+
+/** Merge the winning race task and close its siblings. */
+export async function pickRaceWinner(
+  taskId: string,
+  options?: { squash?: boolean; message?: string },
+): Promise<void> {
+  const raceId = store.tasks[taskId]?.raceId;
+  if (!raceId) throw new Error('Task is not part of a race');
+  const losers = getRaceTaskIds(raceId).filter((id) => id !== taskId);
+  await mergeTask(taskId, { ...options, cleanup: true });
+  // Picking a winner is the confirmation that the others' commits can go
+  for (const id of losers) {
+    await closeTask(id, { force: true });
+  }
+}
+
+/** The commits at stake when `err` refused to delete a branch, else null. */
+export function branchDeletionRisk(err: unknown): BranchDeletionRisk | null {
+  if (!isAppError(err, 'unmerged_commits')) return null;
+  const { branch, commit, unmerged, unpushed } = err.context;
+  if (!branch || !commit || unmerged === undefined) return null;
+  return { branch, tip: commit, unmerged, unpushed: unpushed ?? null };
+}
+
+/**
+ * `deleteBranch` overrides the project's delete-branch-on-close setting. A
+ * branch with commits the main branch doesn't have is only deleted with
+ * `force`; otherwise closing stops with `closingRisk` set.
+ */
+export async function closeTask(
+  taskId: string,
+  opts: { deleteBranch?: boolean; force?: boolean } = {},
+): Promise<void> {
   const task = store.tasks[taskId];
   if (!task || task.closingStatus === 'closing' || task.closingStatus === 'removing') return;
 
   const agentIds = [...task.agentIds];
   const shellAgentIds = [...task.shellAgentIds];
   const branchName = task.branchName;
+  const baseBranch = task.baseBranch;
   const projectRoot = getProjectPath(task.projectId) ?? '';
-  const deleteBranch = getProject(task.projectId)?.deleteBranchOnClose ?? true;
+  const deleteBranch =
+    !task.adoptedBranch &&
+    (opts.deleteBranch ?? getProject(task.projectId)?.deleteBranchOnClose ?? true);
 
   // Mark as closing — task stays visible but UI shows closing state
   setStore('tasks', taskId, 'closingStatus', 'closing');
   setStore('tasks', taskId, 'closingError', undefined);
+  setStore('tasks', taskId, 'closingRisk', undefined);
 
   try {
+    // Before the agents are stopped, so a refusal leaves them running
+    if (deleteBranch && !opts.force && !task.directMode) {
+      await invoke(IPC.CheckBranchDeletion, { projectRoot, branchName, baseBranch });
+    }
+
     // Kill agents
     for (const agentId of agentIds) {
       await invoke(IPC.KillAgent, { agentId }).catch(console.error);
@@ -222,30 +521,52 @@ export async function closeTask(taskId: string): Promise<void> {
     }
 
     // Skip git cleanup for direct mode (no worktree/branch to remove)
+    let branchTip: string | null = null;
     if (!task.directMode) {
       // Remove worktree + branch
-      await invoke(IPC.DeleteTask, {
+      ({ branchTip } = await invoke<{ branchTip: string | null }>(IPC.DeleteTask, {
+        taskId,
         agentIds: [...agentIds, ...shellAgentIds],
         branchName,
         deleteBranch,
+        baseBranch,
+        force: opts.force ?? false,
         projectRoot,
-      });
+        metadata: taskTrashMetadata(task),
+      }));
     }
 
     // Backend cleanup succeeded — remove from UI
+    recordTaskEvent(taskId, 'deleted', {
+      branch_name: branchName,
+      branch_deleted: deleteBranch,
+      ...(branchTip ? { branch_tip: branchTip } : {}),
+    });
     removeTaskFromStore(taskId, [...agentIds, ...shellAgentIds]);
   } catch (err) {
     // Backend cleanup failed — show error, allow retry
     console.error('Failed to close task:', err);
+    recordTaskEvent(taskId, 'error', { action: 'close', message: String(err) });
     setStore('tasks', taskId, 'closingStatus', 'error');
-    setStore('tasks', taskId, 'closingError', String(err));
+    setStore('tasks', taskId, 'closingError', err instanceof Error ? err.message : String(err));
+    setStore('tasks', taskId, 'closingRisk', branchDeletionRisk(err) ?? undefined);
   }
 }
 
-export async function retryCloseTask(taskId: string): Promise<void> {
+export async function retryCloseTask(
+  taskId: string,
+  opts: { force?: boolean } = {},
+): Promise<void> {
+  setStore('tasks', taskId, 'closingStatus', undefined);
+  setStore('tasks', taskId, 'closingError', undefined);
+  await closeTask(taskId, opts);
+}
+
+/** Leave a task open after closing it was refused, e.g. to merge or push it first. */
+export function keepTask(taskId: string): void {
   setStore('tasks', taskId, 'closingStatus', undefined);
   setStore('tasks', taskId, 'closingError', undefined);
-  await closeTask(taskId);
+  setStore('tasks', taskId, 'closingRisk', undefined);
 }
 
 const REMOVE_ANIMATION_MS = 300;
@@ -280,6 +601,12 @@ function removeTaskFromStore(taskId: string, agentIds: string[]): void {
         }
 
         cleanupPanelEntries(s, taskId);
+        for (const t of Object.values(s.tasks)) {
+          if (t.dependsOn?.includes(taskId)) {
+            const left = t.dependsOn.filter((id) => id !== taskId);
+            t.dependsOn = left.length > 0 ? left : undefined;
+          }
+        }
 
         if (s.activeTaskId === taskId) {
           s.activeTaskId = neighbor;
@@ -312,6 +639,11 @@ export async function mergeTask(
   const projectRoot = getProjectPath(task.projectId);
   if (!projectRoot) return;
 
+  const blockers = getBlockingTasks(taskId);
+  if (blockers.length > 0) {
+    throw new Error(`Merge ${blockers.map((t) => t.name).join(', ')} first`);
+  }
+
   const agentIds = [...task.agentIds];
   const shellAgentIds = [...task.shellAgentIds];
   const branchName = task.branchName;
@@ -327,14 +659,32 @@ export async function mergeTask(
     }
   }
 
+  const project = getProject(task.projectId);
+  const checks = project?.checks ?? [];
+  const requireChecks =
+    project?.requireChecksBeforeMerge && checks.length > 0
+      ? { taskId, worktreePath: task.worktreePath, commands: checks }
+      : undefined;
+
   // Merge branch into main. Cleanup is optional.
-  const mergeResult = await invoke<MergeResult>(IPC.MergeTask, {
-    projectRoot,
-    branchName,
-    squash: options?.squash ?? false,
-    message: options?.message,
-    cleanup,
-  });
+  transitionTask(taskId, 'merging');
+  let mergeResult: MergeResult;
+  try {
+    mergeResult = await invoke<MergeResult>(IPC.MergeTask, {
+      projectRoot,
+      branchName,
+      squash: options?.squash ?? false,
+      message: options?.message,
+      cleanup,
+      requireChecks,
+    });
+  } catch (err) {
+    transitionTask(taskId, 'failed');
+    recordTaskEvent(taskId, 'error', { action: 'merge', message: String(err) });
+    throw err;
+  }
+  transitionTask(taskId, 'merged');
+  recordTaskEvent(taskId, 'merged', { ...mergeResult, squash: options?.squash ?? false });
   recordMergedLines(mergeResult.lines_added, mergeResult.lines_removed);
 
   if (cleanup) {
@@ -354,28 +704,499 @@ export async function pushTask(taskId: string): Promise<void> {
     projectRoot,
     branchName: task.branchName,
   });
+  recordTaskEvent(taskId, 'pushed', { branch_name: task.branchName });
+  // A push is usually followed by opening a PR, so look for one sooner
+  refreshTaskPrStatus(taskId);
+}
+
+/**
+ * Push the task's branch and open a PR (a merge request on GitLab) against
+ * `base`, defaulting to the project's main branch.
+ */
+export async function createPullRequest(
+  taskId: string,
+  opts: { title?: string; body?: string; base?: string } = {},
+): Promise<CreatedPullRequest> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode) throw new Error('Task has no branch to open a pull request for');
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) throw new Error('Project not found');
+
+  await pushTask(taskId);
+  const pr = await invoke<CreatedPullRequest>(IPC.CreatePullRequest, {
+    projectRoot,
+    branchName: task.branchName,
+    title: opts.title ?? task.name,
+    body: opts.body ?? (task.issue ? `Closes #${task.issue.number}` : ''),
+    base: opts.base,
+  });
+  refreshTaskPrStatus(taskId);
+  return pr;
+}
+
+/** Transplant commits made in one task onto another task's branch. */
+export async function cherryPickBetweenTasks(
+  fromTaskId: string,
+  toTaskId: string,
+  commits: string[],
+): Promise<CherryPickResult> {
+  const from = store.tasks[fromTaskId];
+  const to = store.tasks[toTaskId];
+  if (!from || !to) throw new Error('Task not found');
+  if (from.projectId !== to.projectId) throw new Error('Tasks belong to different projects');
+
+  return invoke<CherryPickResult>(IPC.CherryPick, {
+    worktreePath: to.worktreePath,
+    commits,
+  });
+}
+
+export async function stashTask(taskId: string, message: string): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode) return;
+  await invoke(IPC.StashTask, { worktreePath: task.worktreePath, message });
+}
+
+export async function stashPopTask(taskId: string, index?: number): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode) return;
+  await invoke(IPC.StashPopTask, {
+    worktreePath: task.worktreePath,
+    branchName: task.branchName,
+    index,
+  });
+}
+
+export function listTaskStashes(taskId: string): Promise<StashEntry[]> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode) return Promise.resolve([]);
+  return invoke<StashEntry[]>(IPC.ListStashes, {
+    worktreePath: task.worktreePath,
+    branchName: task.branchName,
+  });
+}
+
+/** What the task's agents committed, newest first; `offset` pages further back. */
+export async function getTaskLog(
+  taskId: string,
+  options: { limit?: number; offset?: number } = {},
+): Promise<TaskCommit[]> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  return invoke<TaskCommit[]>(IPC.GetTaskLog, { worktreePath: task.worktreePath, ...options });
+}
+
+/** Line-by-line blame of a file in the task's worktree, flagging what the task wrote. */
+export async function getTaskBlame(taskId: string, filePath: string): Promise<BlameLine[]> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  return invoke<BlameLine[]>(IPC.GetBlame, { worktreePath: task.worktreePath, path: filePath });
+}
+
+/** The task branch's upstream and how far it is ahead of and behind it; null without one. */
+export async function getTaskTracking(taskId: string): Promise<BranchTracking | null> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  return invoke<BranchTracking | null>(IPC.GetBranchTracking, { worktreePath: task.worktreePath });
+}
+
+function conflictWorktree(taskId: string): string {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  return task.worktreePath;
+}
+
+/** What the task's stopped merge, rebase or cherry-pick conflicts on, file by file. */
+export async function getTaskConflicts(taskId: string): Promise<ConflictState> {
+  return invoke<ConflictState>(IPC.GetConflicts, { worktreePath: conflictWorktree(taskId) });
+}
+
+export async function resolveTaskConflict(
+  taskId: string,
+  filePath: string,
+  content: string,
+): Promise<void> {
+  await invoke(IPC.ResolveConflict, {
+    worktreePath: conflictWorktree(taskId),
+    path: filePath,
+    content,
+  });
+}
+
+/** Commit the resolution and carry on; a rebase may stop at the next conflicting commit. */
+export async function continueTaskMerge(taskId: string): Promise<ConflictState> {
+  return invoke<ConflictState>(IPC.ContinueMerge, { worktreePath: conflictWorktree(taskId) });
+}
+
+export async function abortTaskMerge(taskId: string): Promise<void> {
+  await invoke(IPC.AbortMerge, { worktreePath: conflictWorktree(taskId) });
+}
+
+export async function snapshotTask(taskId: string, label = ''): Promise<TaskSnapshot> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  if (task.directMode) throw new Error('Direct-mode tasks have no worktree to snapshot');
+  return invoke<TaskSnapshot>(IPC.CreateSnapshot, {
+    worktreePath: task.worktreePath,
+    taskId,
+    label,
+  });
+}
+
+export function listTaskSnapshots(taskId: string): Promise<TaskSnapshot[]> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode) return Promise.resolve([]);
+  return invoke<TaskSnapshot[]>(IPC.ListSnapshots, { worktreePath: task.worktreePath, taskId });
+}
+
+/** Roll the task's worktree back to a snapshot; what it had before is snapshotted as `backup`. */
+export async function restoreTaskSnapshot(
+  taskId: string,
+  snapshotId: string,
+): Promise<{ restored: TaskSnapshot; backup: TaskSnapshot }> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  if (task.directMode) throw new Error('Direct-mode tasks have no snapshots');
+  return invoke<{ restored: TaskSnapshot; backup: TaskSnapshot }>(IPC.RestoreSnapshot, {
+    worktreePath: task.worktreePath,
+    taskId,
+    snapshotId,
+  });
+}
+
+/**
+ * Open the task's worktree in an editor, optionally at `filePath:line`.
+ * `editor` is a preset or template name, or a plain command; it defaults to
+ * the configured editor command.
+ */
+export async function openTaskInEditor(
+  taskId: string,
+  editor: string = store.editorCommand,
+  filePath?: string,
+  line?: number,
+): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task) return;
+  if (!editor) throw new Error('No editor configured');
+  await invoke(IPC.OpenInEditor, { worktreePath: task.worktreePath, editor, filePath, line });
+}
+
+/**
+ * Run the project's test command in the task worktree and keep the result on
+ * the task. Output streams through `IPC.TaskTestOutput` while it runs.
+ */
+export async function runTaskTests(taskId: string): Promise<TestRunResult | null> {
+  const task = store.tasks[taskId];
+  if (!task || task.testsRunning) return null;
+  const command = getProject(task.projectId)?.testCommand;
+  if (!command) throw new Error('No test command configured for this project');
+
+  setStore('tasks', taskId, 'testsRunning', true);
+  try {
+    const result = await invoke<TestRunResult>(IPC.RunTaskTests, {
+      taskId,
+      worktreePath: task.worktreePath,
+      command,
+    });
+    if (store.tasks[taskId]) setStore('tasks', taskId, 'lastTestRun', result);
+    return result;
+  } finally {
+    if (store.tasks[taskId]) setStore('tasks', taskId, 'testsRunning', false);
+  }
+}
+
+/** Run the project's pre-merge checks in order. Status streams via `IPC.CheckStatusChanged`. */
+export async function runTaskChecks(taskId: string): Promise<TaskCheckResults | null> {
+  const task = store.tasks[taskId];
+  if (!task || task.testsRunning) return null;
+  const checks = getProject(task.projectId)?.checks ?? [];
+  if (checks.length === 0) throw new Error('No pre-merge checks configured for this project');
+
+  setStore('tasks', taskId, 'testsRunning', true);
+  try {
+    return await invoke<TaskCheckResults>(IPC.RunTaskChecks, {
+      taskId,
+      worktreePath: task.worktreePath,
+      commands: checks,
+    });
+  } finally {
+    if (store.tasks[taskId]) setStore('tasks', taskId, 'testsRunning', false);
+  }
+}
+
+export function getCheckResults(taskId: string): Promise<TaskCheckResults | null> {
+  return invoke<TaskCheckResults | null>(IPC.GetCheckResults, { taskId });
+}
+
+export async function cancelTaskTests(taskId: string): Promise<void> {
+  await invoke(IPC.CancelTaskTests, { taskId });
+}
+
+/** The port block reserved for the task's sessions (`PORT`, `PARALLEL_PORT_BASE`). */
+export function getTaskPorts(taskId: string): Promise<TaskPorts> {
+  return invoke<TaskPorts>(IPC.GetTaskPorts, { taskId });
+}
+
+function recordTaskEvent(taskId: string, type: TaskEventType, data?: object): void {
+  fireAndForget(IPC.RecordTaskEvent, { taskId, type, data });
+}
+
+export function getTaskEvents(taskId: string): Promise<TaskEvent[]> {
+  return invoke<TaskEvent[]>(IPC.GetTaskEvents, { taskId });
 }
 
 export function updateTaskName(taskId: string, name: string): void {
   setStore('tasks', taskId, 'name', name);
+  setStore('tasks', taskId, 'updatedAt', Date.now());
   if (store.activeTaskId === taskId) {
     updateWindowTitle(name);
   }
 }
 
+/**
+ * Rename a task and optionally its branch. Renaming the branch moves the
+ * worktree, so shells are closed and agents are respawned in the new path.
+ */
+export async function renameTask(
+  taskId: string,
+  name: string,
+  renameBranch: boolean,
+): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task) return;
+  if (!renameBranch || task.directMode) {
+    updateTaskName(taskId, name);
+    return;
+  }
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) throw new Error('Project not found');
+
+  for (const shellId of [...task.shellAgentIds]) {
+    await closeShell(taskId, shellId);
+  }
+  const agentIds = [...task.agentIds];
+  const result = await invoke<RenameTaskResult>(IPC.RenameTask, {
+    agentIds,
+    projectRoot,
+    branchName: task.branchName,
+    newName: name,
+  });
+  setStore(
+    produce((s) => {
+      s.tasks[taskId].branchName = result.branch_name;
+      s.tasks[taskId].worktreePath = result.worktree_path;
+    }),
+  );
+  updateTaskName(taskId, name);
+  recordTaskEvent(taskId, 'renamed', { name, branch_name: result.branch_name });
+  for (const agentId of agentIds) restartAgent(agentId, true);
+}
+
 export function updateTaskNotes(taskId: string, notes: string): void {
   setStore('tasks', taskId, 'notes', notes);
 }
 
+/** Add a paragraph to the end of a task's notes, e.g. a follow-up recorded from the CLI. */
+export function appendTaskNotes(taskId: string, text: string): void {
+  const notes = store.tasks[taskId]?.notes.trimEnd() ?? '';
+  updateTaskNotes(taskId, notes ? `${notes}\n\n${text.trim()}` : text.trim());
+}
+
+/**
+ * Replace a task's environment overrides. Takes effect for agents and
+ * shells spawned afterwards; running processes keep their environment.
+ * The values are saved in plain text with the rest of the state, so API
+ * keys belong in the keychain secrets, listed with `setTaskSecrets`.
+ */
+export function setTaskEnv(taskId: string, env: Record<string, string>): void {
+  const invalid = Object.keys(env).find((k) => !isEnvVarName(k));
+  if (invalid !== undefined) throw new Error(`Invalid environment variable name: ${invalid}`);
+  setStore('tasks', taskId, 'env', Object.keys(env).length > 0 ? { ...env } : undefined);
+}
+
+/**
+ * Replace the stored secrets a task's agents and shells get, by name,
+ * whether or not they're injected into every agent. Only the names are
+ * saved; sessions spawned afterwards get the values from the keychain.
+ */
+export function setTaskSecrets(taskId: string, names: string[]): void {
+  const invalid = names.find((n) => !isEnvVarName(n));
+  if (invalid !== undefined) throw new Error(`Invalid secret name: ${invalid}`);
+  const unique = [...new Set(names)];
+  setStore('tasks', taskId, 'secrets', unique.length > 0 ? unique : undefined);
+}
+
+/**
+ * Environment overrides for a task's agents and shells: the project's shared
+ * cache locations and its own, then when spawning `agentDef` what
+ * `.parallel-code.toml` and the agent's launch profile set for it, then the
+ * task's.
+ */
+export function getTaskSpawnEnv(task: Task, agentDef?: AgentDef): Record<string, string> {
+  const project = store.projects.find((p) => p.id === task.projectId);
+  const projectEnv = project && {
+    ...sharedCacheEnv(project.path, project.sharedCaches, project.pnpmStoreDir),
+    ...project.env,
+  };
+  const configEnv = agentDef ? getProjectAgentConfig(task.projectId, agentDef)?.env : undefined;
+  const profileEnv = agentDef ? getTaskAgentProfile(task, agentDef)?.env : undefined;
+  return { ...projectEnv, ...configEnv, ...profileEnv, ...task.env };
+}
+
+/** Normalize labels: trimmed, non-empty, de-duplicated, original order kept. */
+export function normalizeLabels(labels: string[]): string[] {
+  return [...new Set(labels.map((l) => l.trim()).filter((l) => l.length > 0))];
+}
+
+export function updateTaskMetadata(
+  taskId: string,
+  updates: { description?: string; labels?: string[] },
+): void {
+  setStore(
+    produce((s) => {
+      const t = s.tasks[taskId];
+      if (!t) return;
+      if (updates.description !== undefined) t.description = updates.description || undefined;
+      if (updates.labels !== undefined) {
+        const labels = normalizeLabels(updates.labels);
+        t.labels = labels.length > 0 ? labels : undefined;
+      }
+      t.updatedAt = Date.now();
+    }),
+  );
+}
+
+/** Fields of a task that `updateTask` can change. Omitted fields are left alone. */
+export interface TaskPatch {
+  name?: string;
+  lifecycle?: TaskLifecycle;
+  description?: string;
+  labels?: string[];
+  env?: Record<string, string>;
+  secrets?: string[];
+  notes?: string;
+}
+
+/**
+ * Apply several changes to a task at once, recording an `updated` event
+ * with the fields that changed. Everything is checked before anything is
+ * applied, so an invalid field leaves the task as it was. Returns the
+ * names of the changed fields.
+ */
+export function updateTask(taskId: string, patch: TaskPatch): Array<keyof TaskPatch> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  const name = patch.name?.trim();
+  if (name === '') throw new Error('Task name is empty');
+  const from = task.lifecycle;
+  if (patch.lifecycle !== undefined && from !== undefined && from !== patch.lifecycle) {
+    if (!canTransition(from, patch.lifecycle)) {
+      throw new Error(`A task can't move from ${from} to ${patch.lifecycle}`);
+    }
+  }
+  const invalidEnv = Object.keys(patch.env ?? {}).find((k) => !isEnvVarName(k));
+  if (invalidEnv !== undefined) {
+    throw new Error(`Invalid environment variable name: ${invalidEnv}`);
+  }
+  const invalidSecret = patch.secrets?.find((n) => !isEnvVarName(n));
+  if (invalidSecret !== undefined) throw new Error(`Invalid secret name: ${invalidSecret}`);
+
+  const changed: Array<keyof TaskPatch> = [];
+  if (name !== undefined && name !== task.name) {
+    updateTaskName(taskId, name);
+    changed.push('name');
+  }
+  if (patch.lifecycle !== undefined && patch.lifecycle !== from) {
+    transitionTask(taskId, patch.lifecycle);
+    changed.push('lifecycle');
+  }
+  if (patch.description !== undefined || patch.labels !== undefined) {
+    updateTaskMetadata(taskId, { description: patch.description, labels: patch.labels });
+    if (patch.description !== undefined) changed.push('description');
+    if (patch.labels !== undefined) changed.push('labels');
+  }
+  if (patch.env !== undefined) {
+    setTaskEnv(taskId, patch.env);
+    changed.push('env');
+  }
+  if (patch.secrets !== undefined) {
+    setTaskSecrets(taskId, patch.secrets);
+    changed.push('secrets');
+  }
+  if (patch.notes !== undefined && patch.notes !== task.notes) {
+    updateTaskNotes(taskId, patch.notes);
+    changed.push('notes');
+  }
+  if (changed.length > 0) recordTaskEvent(taskId, 'updated', { fields: changed });
+  return changed;
+}
+
 export async function sendPrompt(taskId: string, agentId: string, text: string): Promise<void> {
-  // Send text and Enter separately so TUI apps (Claude Code, Codex)
-  // don't treat the \r as part of a pasted block
-  await writeToAgentWhenReady(agentId, text);
+  // Paste the text and send Enter separately so TUI apps (Claude Code, Codex)
+  // don't treat the \r as part of the pasted block
+  await writeToAgentWhenReady(agentId, text, true);
   await new Promise((r) => setTimeout(r, 50));
   await writeToAgentWhenReady(agentId, '\r');
   setStore('tasks', taskId, 'lastPrompt', text);
 }
 
+/**
+ * Opening prompt for the agent taking over a task. Besides the task's prompt
+ * variables it can use `{from_agent}`, `{to_agent}`, `{diff}`, `{commits}`
+ * and `{transcript}`.
+ */
+export const DEFAULT_HANDOFF_TEMPLATE = [
+  '{from_agent} has been working on "{task_name}". Its changes so far:',
+  '',
+  '```diff',
+  '{diff}',
+  '```',
+  '',
+  'Commits:',
+  '{commits}',
+  '',
+  'The end of its session:',
+  '{transcript}',
+  '',
+  'Review these changes and fix any problems you find.',
+].join('\n');
+
+/**
+ * Hand a task from one agent to another, e.g. Codex implements and Claude
+ * reviews: the first agent's diff and last output are captured, the second
+ * takes its place in the same worktree, and the rendered template is sent as
+ * its opening prompt.
+ */
+export async function handoffTask(
+  taskId: string,
+  fromAgentId: string,
+  toAgentDef: AgentDef,
+  instructionTemplate = DEFAULT_HANDOFF_TEMPLATE,
+): Promise<void> {
+  const task = store.tasks[taskId];
+  const from = store.agents[fromAgentId];
+  if (!task || !from || from.taskId !== taskId) throw new Error('Agent not found');
+
+  const context = await invoke<HandoffContext>(IPC.GetHandoffContext, {
+    agentId: fromAgentId,
+    worktreePath: task.worktreePath,
+  });
+  const prompt = substitutePromptVars(instructionTemplate, {
+    ...(await promptVarsForTask(taskId)),
+    from_agent: from.def.name,
+    to_agent: toAgentDef.name,
+    diff: context.diff_truncated ? `${context.diff}\n… (truncated)` : context.diff,
+    commits: context.commits || '(none)',
+    // The PTY's buffer is gone once the agent exits; its last lines were kept
+    transcript: context.transcript || from.lastOutput.join('\n'),
+  }).trim();
+
+  const fromDefId = from.def.id;
+  switchAgent(fromAgentId, toAgentDef);
+  setStore('tasks', taskId, 'initialPrompt', prompt);
+  recordTaskEvent(taskId, 'handed_off', { from_agent: fromDefId, to_agent: toAgentDef.id });
+}
+
 export function setLastPrompt(taskId: string, text: string): void {
   setStore('tasks', taskId, 'lastPrompt', text);
 }
@@ -531,7 +1352,7 @@ export async function collapseTask(taskId: string): Promise<void> {
 
 export function uncollapseTask(taskId: string): void {
   const task = store.tasks[taskId];
-  if (!task || !task.collapsed) return;
+  if (!task || !task.collapsed || task.archived) return;
 
   const savedDef = task.savedAgentDef;
   const agentId = savedDef ? crypto.randomUUID() : null;
@@ -567,12 +1388,206 @@ export function uncollapseTask(taskId: string): void {
 
   if (agentId) {
     markAgentSpawned(agentId);
+    transitionTask(taskId, 'agent-running');
     rescheduleTaskStatusPolling();
   }
 
   updateWindowTitle(task.name);
 }
 
+/**
+ * Collapse a task and remove its worktree to reclaim disk space. The branch
+ * and task metadata are kept so restoreTask can bring it back.
+ */
+export async function archiveTask(taskId: string): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task || task.directMode || task.archived || task.closingStatus) return;
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) throw new Error('Project not found');
+
+  // Checks for uncommitted work before anything is killed
+  await invoke(IPC.ArchiveTask, {
+    agentIds: [...task.agentIds, ...task.shellAgentIds],
+    branchName: task.branchName,
+    projectRoot,
+  });
+  await collapseTask(taskId);
+  setStore('tasks', taskId, 'archived', true);
+  recordTaskEvent(taskId, 'archived', { branch_name: task.branchName });
+}
+
+/** Recreate an archived task's worktree from its branch and reopen it. */
+export async function restoreTask(taskId: string, symlinkDirs: string[] = []): Promise<void> {
+  const task = store.tasks[taskId];
+  if (!task || !task.archived) return;
+  const projectRoot = getProjectPath(task.projectId);
+  if (!projectRoot) throw new Error('Project not found');
+  if (isProjectMissing(task.projectId)) throw new Error('Project folder not found');
+
+  const result = await invoke<RestoreTaskResult>(IPC.RestoreTask, {
+    projectRoot,
+    branchName: task.branchName,
+    symlinkDirs,
+    sparsePaths: task.sparsePaths,
+  });
+  reportWorktreeSetup(task.name, result);
+  setStore(
+    produce((s) => {
+      s.tasks[taskId].archived = undefined;
+      s.tasks[taskId].worktreePath = result.worktree_path;
+    }),
+  );
+  recordTaskEvent(taskId, 'restored', { worktree_path: result.worktree_path });
+  uncollapseTask(taskId);
+}
+
+/**
+ * Check out more directories in a sparse task worktree, for when the agent
+ * needs files outside the ones the task started with.
+ */
+export async function widenSparseCheckout(taskId: string, paths: string[]): Promise<string[]> {
+  const task = store.tasks[taskId];
+  if (!task) throw new Error('Task not found');
+  if (!task.sparsePaths) throw new Error('Task checks out all files already');
+  const sparsePaths = await invoke<string[]>(IPC.WidenSparseCheckout, {
+    worktreePath: task.worktreePath,
+    paths,
+  });
+  setStore('tasks', taskId, 'sparsePaths', sparsePaths);
+  return sparsePaths;
+}
+
+// --- Bulk operations ---
+
+export interface BulkTaskResult {
+  taskId: string;
+  ok: boolean;
+  error?: string;
+  /** Why a branch wasn't deleted; delete with `force` to go ahead. */
+  branchRisk?: BranchDeletionRisk;
+}
+
+/**
+ * Run `op` over each task in turn, collecting a result per task instead of
+ * stopping at the first failure. One at a time, since tasks of the same
+ * project share a repo and git serializes on it anyway.
+ */
+async function forEachTask(
+  taskIds: string[],
+  op: (task: Task) => Promise<void>,
+): Promise<BulkTaskResult[]> {
+  const results: BulkTaskResult[] = [];
+  for (const taskId of [...new Set(taskIds)]) {
+    const task = store.tasks[taskId];
+    if (!task) {
+      results.push({ taskId, ok: false, error: 'Task not found' });
+      continue;
+    }
+    try {
+      await op(task);
+      results.push({ taskId, ok: true });
+    } catch (err) {
+      results.push({ taskId, ok: false, error: err instanceof Error ? err.message : String(err) });
+    }
+  }
+  return results;
+}
+
+export async function deleteTasks(
+  taskIds: string[],
+  deleteBranches: boolean,
+  force = false,
+): Promise<BulkTaskResult[]> {
+  const results = await forEachTask(taskIds, async (task) => {
+    await closeTask(task.id, { deleteBranch: deleteBranches, force });
+    // closeTask keeps failed tasks in the sidebar with the error instead of throwing
+    const after = store.tasks[task.id];
+    if (after?.closingStatus === 'error') throw new Error(after.closingError ?? 'Close failed');
+  });
+  return results.map((r) => {
+    const risk = r.ok ? undefined : store.tasks[r.taskId]?.closingRisk;
+    return risk ? { ...r, branchRisk: risk } : r;
+  });
+}
+
+export function archiveTasks(taskIds: string[]): Promise<BulkTaskResult[]> {
+  return forEachTask(taskIds, async (task) => {
+    if (task.directMode) throw new Error('Direct mode tasks have no worktree to archive');
+    if (task.archived) throw new Error('Task is already archived');
+    if (task.closingStatus) throw new Error('Task is being closed');
+    await archiveTask(task.id);
+  });
+}
+
+/**
+ * Rebase each task's branch onto its project's main branch. Conflicts abort
+ * that rebase, unless `keepConflicts` leaves it stopped to resolve them.
+ */
+export function rebaseTasks(
+  taskIds: string[],
+  options: { keepConflicts?: boolean } = {},
+): Promise<BulkTaskResult[]> {
+  return forEachTask(taskIds, async (task) => {
+    if (task.directMode) throw new Error('Direct mode tasks have no branch to rebase');
+    if (task.archived) throw new Error('Task is archived');
+    await invoke(IPC.RebaseTask, {
+      worktreePath: task.worktreePath,
+      keepConflicts: options.keepConflicts,
+    });
+  });
+}
+
+export interface CreateTasksResult {
+  /** Position in the batch. */
+  index: number;
+  ok: boolean;
+  taskId?: string;
+  error?: string;
+}
+
+export interface CreateTasksProgress {
+  index: number;
+  state: 'creating' | 'created' | 'failed';
+  /** The task's worktree checkout reports `GitProgress` events under this id. */
+  opId: string;
+  taskId?: string;
+  error?: string;
+}
+
+/** Worktrees set up at once in a batch; each is a checkout of its own. */
+const CREATE_TASKS_CONCURRENCY = 4;
+
+/**
+ * Create several tasks at once. Unlike the other bulk operations these run
+ * concurrently: git only serializes registering each worktree, not checking
+ * out its files. One failure doesn't stop the rest.
+ */
+export async function createTasks(
+  batch: CreateTaskOptions[],
+  onProgress?: (progress: CreateTasksProgress) => void,
+): Promise<CreateTasksResult[]> {
+  const results: CreateTasksResult[] = [];
+  let next = 0;
+  const worker = async () => {
+    for (let index = next++; index < batch.length; index = next++) {
+      const opId = batch[index].opId ?? crypto.randomUUID();
+      onProgress?.({ index, state: 'creating', opId });
+      try {
+        const taskId = await createTask({ ...batch[index], opId });
+        results[index] = { index, ok: true, taskId };
+        onProgress?.({ index, state: 'created', opId, taskId });
+      } catch (err) {
+        const error = err instanceof Error ? err.message : String(err);
+        results[index] = { index, ok: false, error };
+        onProgress?.({ index, state: 'failed', opId, error });
+      }
+    }
+  };
+  const workers = Math.min(CREATE_TASKS_CONCURRENCY, batch.length);
+  await Promise.all(Array.from({ length: workers }, worker));
+  return results;
+}
+
 // --- GitHub drop-to-create helpers ---
 
 /** Find best matching project by comparing repo name to project directory basenames. */
@@ -0,0 +1,75 @@
+import { produce } from 'solid-js/store';
+import { store, setStore } from './core';
+import { createTask, spawnShellForTask } from './tasks';
+import type { TaskTemplate } from './types';
+
+/** Replace `{{param}}` placeholders. Unknown params are left as-is so mistakes stay visible. */
+export function renderTemplate(pattern: string, params: Record<string, string>): string {
+  return pattern.replace(/\{\{\s*([\w-]+)\s*\}\}/g, (match, key: string) =>
+    Object.prototype.hasOwnProperty.call(params, key) ? params[key] : match,
+  );
+}
+
+export function getTaskTemplate(templateId: string): TaskTemplate | undefined {
+  return store.taskTemplates.find((t) => t.id === templateId);
+}
+
+export function addTaskTemplate(template: Omit<TaskTemplate, 'id'>): string {
+  const id = `template-${crypto.randomUUID()}`;
+  setStore(
+    produce((s) => {
+      s.taskTemplates.push({ ...template, id });
+    }),
+  );
+  return id;
+}
+
+export function updateTaskTemplate(
+  templateId: string,
+  updates: Partial<Omit<TaskTemplate, 'id'>>,
+): void {
+  setStore(
+    produce((s) => {
+      const idx = s.taskTemplates.findIndex((t) => t.id === templateId);
+      if (idx === -1) return;
+      s.taskTemplates[idx] = { ...s.taskTemplates[idx], ...updates };
+    }),
+  );
+}
+
+export function removeTaskTemplate(templateId: string): void {
+  setStore(
+    produce((s) => {
+      s.taskTemplates = s.taskTemplates.filter((t) => t.id !== templateId);
+    }),
+  );
+}
+
+/**
+ * Create a task from a template: name and prompt are rendered with `params`,
+ * the template's agent is spawned, and setup commands run in a shell.
+ */
+export async function createTaskFromTemplate(
+  templateId: string,
+  projectId: string,
+  params: Record<string, string> = {},
+): Promise<string> {
+  const template = getTaskTemplate(templateId);
+  if (!template) throw new Error('Template not found');
+  const agentDef = store.availableAgents.find((a) => a.id === template.agentId);
+  if (!agentDef) throw new Error(`Agent "${template.agentId}" is not available`);
+
+  const taskId = await createTask({
+    name: renderTemplate(template.namePattern, params).trim() || template.name,
+    agentDef,
+    projectId,
+    initialPrompt: template.initialPrompt
+      ? renderTemplate(template.initialPrompt, params)
+      : undefined,
+    baseBranch: template.baseBranch || undefined,
+  });
+
+  const setup = template.setupCommands.map((c) => renderTemplate(c, params).trim()).filter(Boolean);
+  if (setup.length > 0) spawnShellForTask(taskId, setup.join(' && '));
+  return taskId;
+}
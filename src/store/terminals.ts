@@ -4,6 +4,7 @@ import { IPC } from '../../electron/ipc/channels';
 import { store, setStore, updateWindowTitle, cleanupPanelEntries } from './core';
 import { clearAgentActivity } from './taskStatus';
 import { triggerFocus, getTaskFocusedPanel } from './focus';
+import type { ScrollbackSearchResult } from '../ipc/types';
 import type { Terminal } from './types';
 
 let terminalCounter = 0;
@@ -112,3 +113,12 @@ export function syncTerminalCounter(): void {
   }
   terminalCounter = max;
 }
+
+/** Search everything the backend buffered for a session, not just the rendered rows. */
+export function searchSessionBuffer(
+  agentId: string,
+  query: string,
+  regex = false,
+): Promise<ScrollbackSearchResult> {
+  return invoke<ScrollbackSearchResult>(IPC.SearchSessionBuffer, { agentId, query, regex });
+}
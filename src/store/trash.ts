@@ -0,0 +1,129 @@
+import { produce } from 'solid-js/store';
+import { invoke, fireAndForget } from '../lib/ipc';
+import { IPC } from '../../electron/ipc/channels';
+import { store, setStore, updateWindowTitle } from './core';
+import { markAgentSpawned, rescheduleTaskStatusPolling } from './taskStatus';
+import type { RestoredTaskResult, TrashEntry } from '../ipc/types';
+import type { Agent, Task } from './types';
+
+function str(v: unknown): string | undefined {
+  return typeof v === 'string' && v ? v : undefined;
+}
+
+function strings(v: unknown): string[] | undefined {
+  return Array.isArray(v) ? v.filter((s): s is string => typeof s === 'string') : undefined;
+}
+
+/** What's kept of a task in the trash so it can be rebuilt on restore. */
+export function taskTrashMetadata(task: Task): Record<string, unknown> {
+  const agentDef = task.agentIds[0] ? store.agents[task.agentIds[0]]?.def : task.savedAgentDef;
+  return {
+    name: task.name,
+    project_id: task.projectId,
+    notes: task.notes,
+    description: task.description,
+    labels: task.labels,
+    last_prompt: task.lastPrompt,
+    initial_prompt: task.savedInitialPrompt,
+    agent_def_id: agentDef?.id,
+    agent_profile_id: task.agentProfileId,
+    skip_permissions: task.skipPermissions,
+    github_url: task.githubUrl,
+    env: task.env,
+    secrets: task.secrets,
+    depends_on: task.dependsOn,
+    created_at: task.createdAt,
+  };
+}
+
+/** Deleted tasks that can still be restored, newest first. */
+export function listDeletedTasks(): Promise<TrashEntry[]> {
+  return invoke<TrashEntry[]>(IPC.ListTrash);
+}
+
+/**
+ * Bring back a deleted task: its branch at the commit it had, a fresh
+ * worktree and a new agent. Returns the task id.
+ */
+export async function restoreDeletedTask(entryId: string): Promise<string> {
+  const entry = (await listDeletedTasks()).find((e) => e.id === entryId);
+  if (!entry) throw new Error('Trash entry not found or expired');
+  const project =
+    store.projects.find((p) => p.id === entry.metadata.project_id) ??
+    store.projects.find((p) => p.path === entry.project_root);
+  if (!project) throw new Error('Project not found');
+
+  const result = await invoke<RestoredTaskResult>(IPC.RestoreDeletedTask, {
+    entryId,
+    symlinkDirs: [],
+  });
+  const meta = result.entry.metadata;
+  const agentDef =
+    store.availableAgents.find((a) => a.id === meta.agent_def_id) ?? store.availableAgents[0];
+  if (!agentDef) throw new Error('No agents available');
+  const taskId = store.tasks[entry.task_id] ? crypto.randomUUID() : entry.task_id;
+  const name = str(meta.name) ?? entry.branch_name;
+  const env =
+    meta.env && typeof meta.env === 'object' ? (meta.env as Record<string, string>) : undefined;
+  const dependsOn = strings(meta.depends_on)?.filter((id) => store.tasks[id]);
+
+  const agentId = crypto.randomUUID();
+  const task: Task = {
+    id: taskId,
+    name,
+    projectId: project.id,
+    branchName: entry.branch_name,
+    worktreePath: result.worktree_path,
+    agentIds: [agentId],
+    shellAgentIds: [],
+    notes: str(meta.notes) ?? '',
+    lastPrompt: str(meta.last_prompt) ?? '',
+    savedInitialPrompt: str(meta.initial_prompt),
+    description: str(meta.description),
+    labels: strings(meta.labels),
+    skipPermissions: meta.skip_permissions === true ? true : undefined,
+    githubUrl: str(meta.github_url),
+    env,
+    secrets: strings(meta.secrets),
+    agentProfileId: str(meta.agent_profile_id),
+    dependsOn: dependsOn && dependsOn.length > 0 ? dependsOn : undefined,
+    createdAt: typeof meta.created_at === 'number' ? meta.created_at : Date.now(),
+    lifecycle: 'agent-running',
+  };
+  const agent: Agent = {
+    id: agentId,
+    taskId,
+    def: agentDef,
+    resumed: false,
+    status: 'running',
+    exitCode: null,
+    signal: null,
+    lastOutput: [],
+    generation: 0,
+  };
+
+  setStore(
+    produce((s) => {
+      s.tasks[taskId] = task;
+      s.agents[agentId] = agent;
+      s.taskOrder.push(taskId);
+      s.activeTaskId = taskId;
+      s.activeAgentId = agentId;
+    }),
+  );
+  markAgentSpawned(agentId);
+  rescheduleTaskStatusPolling();
+  updateWindowTitle(name);
+  fireAndForget(IPC.RecordTaskEvent, {
+    taskId,
+    type: 'restored',
+    data: { worktree_path: result.worktree_path, from_trash: true },
+  });
+  return taskId;
+}
+
+/** Restore the most recently deleted task. Returns its id, or null when the trash is empty. */
+export async function undoLastOperation(): Promise<string | null> {
+  const [latest] = await listDeletedTasks();
+  return latest ? restoreDeletedTask(latest.id) : null;
+}
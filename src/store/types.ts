@@ -1,7 +1,19 @@
-import type { AgentDef, WorktreeStatus } from '../ipc/types';
+import type {
+  AgentDef,
+  AgentOutputEvent,
+  AgentProfile,
+  BranchDeletionRisk,
+  ProjectAgentConfig,
+  TaskPrStatus,
+  TestRunResult,
+  WorktreeStatus,
+} from '../ipc/types';
 import type { TerminalFont } from '../lib/fonts';
 import type { LookPreset } from '../lib/look';
 import type { AppLocale } from '../lib/i18n';
+import type { TaskLifecycle } from '../lib/task-lifecycle';
+import type { ReviewComment } from '../lib/review-comments';
+import type { SharedCache } from '../lib/shared-caches';
 
 export interface TerminalBookmark {
   id: string;
@@ -17,6 +29,24 @@ export interface Project {
   deleteBranchOnClose?: boolean; // default true if unset
   defaultDirectMode?: boolean; // default false if unset
   terminalBookmarks?: TerminalBookmark[];
+  testCommand?: string; // run in a task worktree by runTaskTests
+  checks?: string[]; // pre-merge check commands, run in order
+  requireChecksBeforeMerge?: boolean;
+  env?: Record<string, string>; // injected into every agent/shell of the project's tasks
+  agentProfiles?: ProjectAgentProfile[]; // launch profiles added on top of the agents' own
+  cleanupMergedTasks?: boolean; // archive (or delete) tasks once their branch is merged
+  cleanupGraceHours?: number; // wait this long after the merge, default 24
+  sparseCheckout?: string[]; // directories new task worktrees check out; everything if unset
+  defaultBaseBranch?: string; // new tasks fork from it; detected when the project is opened
+  agentConfig?: Record<string, ProjectAgentConfig>; // by agent id, from .parallel-code.toml
+  trustedConfigHash?: string; // of the .parallel-code.toml the user let apply; asked on change
+  sharedCaches?: SharedCache[]; // dependency caches task worktrees share with the main checkout
+  pnpmStoreDir?: string; // absolute, in the repo's common git dir; resolved when opened
+}
+
+/** A launch profile a project defines for one agent. */
+export interface ProjectAgentProfile extends AgentProfile {
+  agentId: string;
 }
 
 export interface Agent {
@@ -24,11 +54,15 @@ export interface Agent {
   taskId: string;
   def: AgentDef;
   resumed: boolean;
+  resumeArgs?: string[]; // resumes a specific conversation, instead of def.resume_args
   status: 'running' | 'exited';
   exitCode: number | null;
   signal: string | null;
   lastOutput: string[];
   generation: number;
+  restartCount?: number; // automatic crash restarts of the current process
+  stall?: 'idle' | 'hung'; // quiet past the idle/hung threshold, cleared on new output
+  lastEvent?: AgentOutputEvent; // latest thing the agent's output said it was doing
 }
 
 export interface Task {
@@ -46,6 +80,8 @@ export interface Task {
   prefillPrompt?: string; // fills prompt input without sending
   closingStatus?: 'closing' | 'removing' | 'error';
   closingError?: string;
+  /** Set when closing stopped because deleting the branch would lose commits. */
+  closingRisk?: BranchDeletionRisk;
   directMode?: boolean;
   skipPermissions?: boolean;
   githubUrl?: string;
@@ -53,6 +89,35 @@ export interface Task {
   savedAgentDef?: AgentDef;
   planContent?: string;
   planFileName?: string;
+  raceId?: string; // sibling tasks racing the same prompt share this id
+  archived?: boolean; // worktree removed, branch kept; implies collapsed
+  description?: string;
+  labels?: string[];
+  createdAt?: number; // epoch ms; absent on tasks created before it was tracked
+  updatedAt?: number;
+  lifecycle?: TaskLifecycle;
+  env?: Record<string, string>; // injected into every agent/shell spawned for the task
+  secrets?: string[]; // names of keychain secrets its agents/shells get; values stay out of state
+  agentProfileId?: string; // launch profile of the task's agent, built-in or the project's
+  mergedAt?: number; // when the branch was seen merged, for cleanupMergedTasks
+  mergedTip?: string; // the branch's tip then; commits past it weren't merged
+  baseBranch?: string; // what the branch was forked from; main if unset
+  dependsOn?: string[]; // tasks that must merge before this one
+  dependencyMerged?: string; // name of a dependency that just merged; offers a rebase
+  adoptedBranch?: boolean; // branch pre-existed the task; never deleted on close
+  sparsePaths?: string[]; // sparse-checkout directories of the worktree; all files if unset
+  scope?: string; // monorepo sub-directory the task works in, relative to the worktree
+  lastTestRun?: TestRunResult;
+  testsRunning?: boolean;
+  issue?: LinkedIssue;
+  reviewComments?: ReviewComment[]; // notes on the task's diff, see store/review.ts
+  pr?: Omit<TaskPrStatus, 'task_id'>; // PR for the task branch, kept current by the PR poller
+}
+
+export interface LinkedIssue {
+  number: number;
+  title: string;
+  url: string;
 }
 
 export interface Terminal {
@@ -77,6 +142,26 @@ export interface PersistedTask {
   githubUrl?: string;
   savedInitialPrompt?: string;
   collapsed?: boolean;
+  raceId?: string;
+  archived?: boolean;
+  description?: string;
+  labels?: string[];
+  createdAt?: number;
+  updatedAt?: number;
+  lifecycle?: string;
+  env?: Record<string, string>;
+  secrets?: string[];
+  agentProfileId?: string;
+  mergedAt?: number;
+  mergedTip?: string;
+  baseBranch?: string;
+  dependsOn?: string[];
+  adoptedBranch?: boolean;
+  sparsePaths?: string[];
+  scope?: string;
+  lastTestRun?: TestRunResult;
+  issue?: LinkedIssue;
+  reviewComments?: ReviewComment[];
 }
 
 export interface PersistedTerminal {
@@ -100,6 +185,28 @@ export interface SlashCommand {
   source: 'built-in' | 'custom' | 'cli';
 }
 
+export interface TaskTemplate {
+  id: string;
+  name: string;
+  /** Task name with `{{param}}` placeholders, e.g. "fix {{issue}}". */
+  namePattern: string;
+  /** Branch or ref to fork from instead of the repo's current HEAD. */
+  baseBranch?: string;
+  agentId: string;
+  initialPrompt?: string;
+  /** Run in a shell terminal of the new task, in order. */
+  setupCommands: string[];
+}
+
+export interface PromptTemplate {
+  id: string;
+  name: string;
+  /** Prompt text with `{var}` placeholders, e.g. "Review {changed_files}". */
+  body: string;
+  /** Send to the agent of every new task that has no prompt of its own. */
+  sendOnSpawn?: boolean;
+}
+
 export interface PersistedState {
   projects: Project[];
   lastProjectId: string | null;
@@ -126,6 +233,8 @@ export interface PersistedState {
   editorCommand?: string;
   customAgents?: AgentDef[];
   customSlashCommands?: SlashCommand[];
+  taskTemplates?: TaskTemplate[];
+  promptTemplates?: PromptTemplate[];
   locale?: AppLocale;
 }
 
@@ -164,6 +273,8 @@ export interface AppStore {
   customAgents: AgentDef[];
   customSlashCommands: SlashCommand[];
   cliSlashCommands: SlashCommand[];
+  taskTemplates: TaskTemplate[];
+  promptTemplates: PromptTemplate[];
   showNewTaskDialog: boolean;
   sidebarVisible: boolean;
   fontScales: Record<string, number>;
@@ -194,6 +305,13 @@ export interface AppStore {
   editorCommand: string;
   newTaskDropUrl: string | null;
   newTaskPrefillPrompt: { prompt: string; projectId: string | null } | null;
+  /** New task fields from a `parallel-code://` link, applied when the dialog opens. */
+  newTaskDeepLink: {
+    name?: string;
+    prompt?: string;
+    agentId?: string;
+    projectId?: string;
+  } | null;
   missingProjectIds: Record<string, true>;
   remoteAccess: RemoteAccess;
   showArena: boolean;